@@ -0,0 +1,45 @@
+//! Cost of a `SharedIndex` write with and without a snapshot held by a
+//! reader, which makes the write copy the whole index.
+//!
+//! Run with `cargo bench --bench shared_index`.
+
+use std::time::{Duration, Instant};
+
+use voyageai::{index::SharedIndex, VectorIndex};
+
+const DIMENSION: usize = 256;
+const WRITES: u32 = 20;
+
+fn index(documents: usize) -> SharedIndex {
+    let mut index = VectorIndex::new();
+    for i in 0..documents {
+        let embedding = (0..DIMENSION)
+            .map(|d| ((i * 31 + d * 7) % 101) as f32 / 101.0)
+            .collect();
+        index
+            .insert(i.to_string(), "text", embedding, None)
+            .expect("valid embedding");
+    }
+    SharedIndex::new(index)
+}
+
+fn time_writes(index: &SharedIndex, hold_snapshot: bool) -> Duration {
+    let start = Instant::now();
+    for i in 0..WRITES {
+        let _snapshot = hold_snapshot.then(|| index.snapshot());
+        index
+            .insert(format!("new-{i}"), "text", vec![0.5; DIMENSION], None)
+            .expect("valid embedding");
+    }
+    start.elapsed() / WRITES
+}
+
+fn main() {
+    println!("documents  in place  copy on write");
+    for documents in [1_000, 10_000, 100_000] {
+        let index = index(documents);
+        let in_place = time_writes(&index, false);
+        let copied = time_writes(&index, true);
+        println!("{documents:>9}  {in_place:>8.2?}  {copied:>13.2?}");
+    }
+}
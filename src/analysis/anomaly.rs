@@ -0,0 +1,71 @@
+//! Outlier scores for embedded documents.
+//!
+//! A document far from the rest of its corpus is often a sign of a problem:
+//! a chunk cut mid-table, a page of navigation boilerplate, or a file that
+//! does not belong in the index. Higher scores mean more unusual documents;
+//! scores are cosine distances, between 0 and 2.
+
+use crate::cosine_similarity;
+
+/// How [`anomaly_scores_with`] scores a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnomalyMethod {
+    /// Cosine distance to the mean of all embeddings. Linear in the number
+    /// of documents; suits corpora about a single subject.
+    #[default]
+    DistanceToCentroid,
+    /// Mean cosine distance to the `k` most similar other documents.
+    /// Quadratic in the number of documents, but also flags documents away
+    /// from every topic of a corpus covering several.
+    KNearest(usize),
+}
+
+/// Scores each embedding by its cosine distance to the centroid of
+/// `embeddings`.
+pub fn anomaly_scores<E: AsRef<[f32]>>(embeddings: &[E]) -> Vec<f32> {
+    anomaly_scores_with(embeddings, AnomalyMethod::DistanceToCentroid)
+}
+
+/// Scores each embedding with `method`. Embeddings of differing dimensions
+/// are at distance 1 from each other.
+pub fn anomaly_scores_with<E: AsRef<[f32]>>(embeddings: &[E], method: AnomalyMethod) -> Vec<f32> {
+    match method {
+        AnomalyMethod::DistanceToCentroid => {
+            let Some(first) = embeddings.first() else {
+                return Vec::new();
+            };
+            let mut centroid = vec![0.0f32; first.as_ref().len()];
+            for embedding in embeddings {
+                if embedding.as_ref().len() == centroid.len() {
+                    for (c, x) in centroid.iter_mut().zip(embedding.as_ref()) {
+                        *c += x;
+                    }
+                }
+            }
+            embeddings
+                .iter()
+                .map(|embedding| 1.0 - cosine_similarity(embedding.as_ref(), &centroid))
+                .collect()
+        }
+        AnomalyMethod::KNearest(k) => {
+            let k = k.max(1);
+            (0..embeddings.len())
+                .map(|i| {
+                    let mut distances: Vec<f32> = (0..embeddings.len())
+                        .filter(|&j| j != i)
+                        .map(|j| {
+                            1.0 - cosine_similarity(embeddings[i].as_ref(), embeddings[j].as_ref())
+                        })
+                        .collect();
+                    distances.sort_by(f32::total_cmp);
+                    distances.truncate(k);
+                    if distances.is_empty() {
+                        0.0
+                    } else {
+                        distances.iter().sum::<f32>() / distances.len() as f32
+                    }
+                })
+                .collect()
+        }
+    }
+}
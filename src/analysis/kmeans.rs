@@ -0,0 +1,345 @@
+//! k-means clustering of embeddings.
+//!
+//! [`KMeans`] runs Lloyd's algorithm from a k-means++ initialization and
+//! suits corpora up to tens of thousands of embeddings. [`MiniBatchKMeans`]
+//! updates the centroids from random batches instead of full passes, trading
+//! a little quality for speed on larger sets. Both are seeded and
+//! deterministic, and return a [`Clustering`]:
+//!
+//! ```
+//! use voyageai::analysis::KMeans;
+//!
+//! let embeddings = vec![
+//!     vec![0.0, 1.0],
+//!     vec![0.1, 0.9],
+//!     vec![1.0, 0.0],
+//!     vec![0.9, 0.1],
+//! ];
+//! let clustering = KMeans::new(2).fit(&embeddings)?;
+//!
+//! assert_eq!(clustering.assignments[0], clustering.assignments[1]);
+//! assert_ne!(clustering.assignments[0], clustering.assignments[2]);
+//! # Ok::<(), voyageai::VoyageError>(())
+//! ```
+//!
+//! Distances are Euclidean. Voyage embeddings are normalized to unit length,
+//! for which Euclidean and cosine distance order points identically.
+
+use crate::analysis::{mean, squared_distance, validate_embeddings};
+use crate::errors::VoyageError;
+use crate::rng::SplitMix64;
+
+/// Result of fitting [`KMeans`] or [`MiniBatchKMeans`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clustering {
+    /// Cluster of each embedding, in input order
+    pub assignments: Vec<usize>,
+    /// Center of each cluster
+    pub centroids: Vec<Vec<f32>>,
+    /// Sum of squared distances from each embedding to its centroid
+    pub inertia: f32,
+    /// Iterations run before converging or reaching the limit
+    pub iterations: usize,
+}
+
+impl Clustering {
+    /// Number of clusters.
+    pub fn k(&self) -> usize {
+        self.centroids.len()
+    }
+
+    /// Number of embeddings in each cluster.
+    pub fn sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.k()];
+        for &cluster in &self.assignments {
+            sizes[cluster] += 1;
+        }
+        sizes
+    }
+
+    /// Positions of the embeddings assigned to `cluster`.
+    pub fn members(&self, cluster: usize) -> Vec<usize> {
+        self.assignments
+            .iter()
+            .enumerate()
+            .filter(|(_, &assigned)| assigned == cluster)
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    /// The cluster whose centroid is nearest to `embedding`.
+    pub fn predict(&self, embedding: &[f32]) -> usize {
+        nearest(&self.centroids, embedding).0
+    }
+
+    /// For each cluster, the positions of up to `per_cluster` members nearest
+    /// its centroid, nearest first: the documents that best represent it.
+    /// `embeddings` must be the embeddings the clustering was fitted on.
+    pub fn representatives(&self, embeddings: &[Vec<f32>], per_cluster: usize) -> Vec<Vec<usize>> {
+        (0..self.k())
+            .map(|cluster| {
+                let mut members: Vec<(usize, f32)> = self
+                    .members(cluster)
+                    .into_iter()
+                    .map(|position| {
+                        let distance =
+                            squared_distance(&embeddings[position], &self.centroids[cluster]);
+                        (position, distance)
+                    })
+                    .collect();
+                members.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+                members
+                    .into_iter()
+                    .take(per_cluster)
+                    .map(|(position, _)| position)
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Lloyd's k-means, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KMeans {
+    k: usize,
+    max_iterations: usize,
+    tolerance: f32,
+    seed: u64,
+}
+
+impl KMeans {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            max_iterations: 100,
+            tolerance: 1e-4,
+            seed: 0x5eed,
+        }
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations.max(1);
+        self
+    }
+
+    /// Stops once no centroid moves farther than `tolerance`.
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance.max(0.0);
+        self
+    }
+
+    /// Seeds the initialization; the same seed gives the same clustering.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Clusters `embeddings` into `k` groups. Fails if `k` is zero or exceeds
+    /// the number of embeddings, or if their dimensions differ.
+    pub fn fit(&self, embeddings: &[Vec<f32>]) -> Result<Clustering, VoyageError> {
+        let dimension = validate(embeddings, self.k)?;
+        let mut rng = SplitMix64::new(self.seed);
+        let mut centroids = kmeans_plus_plus(embeddings, self.k, &mut rng);
+        let mut assignments = vec![0; embeddings.len()];
+        let mut iterations = 0;
+
+        while iterations < self.max_iterations {
+            iterations += 1;
+            for (assignment, embedding) in assignments.iter_mut().zip(embeddings) {
+                *assignment = nearest(&centroids, embedding).0;
+            }
+            let mut updated: Vec<Vec<f32>> = (0..self.k)
+                .map(|cluster| {
+                    mean(
+                        embeddings
+                            .iter()
+                            .zip(&assignments)
+                            .filter(|(_, &assigned)| assigned == cluster)
+                            .map(|(embedding, _)| embedding),
+                        dimension,
+                    )
+                })
+                .collect();
+            reseed_empty_clusters(embeddings, &mut assignments, &mut updated);
+            let shift = centroids
+                .iter()
+                .zip(&updated)
+                .map(|(old, new)| squared_distance(old, new).sqrt())
+                .fold(0.0f32, f32::max);
+            centroids = updated;
+            if shift <= self.tolerance {
+                break;
+            }
+        }
+        Ok(finish(embeddings, centroids, iterations))
+    }
+}
+
+/// Mini-batch k-means, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MiniBatchKMeans {
+    k: usize,
+    batch_size: usize,
+    max_iterations: usize,
+    seed: u64,
+}
+
+impl MiniBatchKMeans {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            batch_size: 256,
+            max_iterations: 100,
+            seed: 0x5eed,
+        }
+    }
+
+    /// Number of embeddings sampled per iteration.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations.max(1);
+        self
+    }
+
+    /// Seeds the initialization and batch sampling.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Clusters `embeddings` into `k` groups, running `max_iterations`
+    /// batches. Fails like [`KMeans::fit`].
+    pub fn fit(&self, embeddings: &[Vec<f32>]) -> Result<Clustering, VoyageError> {
+        validate(embeddings, self.k)?;
+        let mut rng = SplitMix64::new(self.seed);
+        let mut centroids = kmeans_plus_plus(embeddings, self.k, &mut rng);
+        let mut counts = vec![0usize; self.k];
+
+        for _ in 0..self.max_iterations {
+            let batch: Vec<usize> = (0..self.batch_size)
+                .map(|_| rng.below(embeddings.len()))
+                .collect();
+            let assigned: Vec<usize> = batch
+                .iter()
+                .map(|&position| nearest(&centroids, &embeddings[position]).0)
+                .collect();
+            // Per-centroid learning rate decays with the points it has seen
+            for (&position, &cluster) in batch.iter().zip(&assigned) {
+                counts[cluster] += 1;
+                let rate = 1.0 / counts[cluster] as f32;
+                for (c, x) in centroids[cluster].iter_mut().zip(&embeddings[position]) {
+                    *c += rate * (x - *c);
+                }
+            }
+        }
+        Ok(finish(embeddings, centroids, self.max_iterations))
+    }
+}
+
+fn validate(embeddings: &[Vec<f32>], k: usize) -> Result<usize, VoyageError> {
+    let dimension = validate_embeddings(embeddings)?;
+    if k == 0 || k > embeddings.len() {
+        return Err(VoyageError::AnalysisError(format!(
+            "k must be between 1 and the number of embeddings ({}), got {}",
+            embeddings.len(),
+            k
+        )));
+    }
+    Ok(dimension)
+}
+
+/// Position of and squared distance to the centroid nearest `embedding`.
+fn nearest(centroids: &[Vec<f32>], embedding: &[f32]) -> (usize, f32) {
+    centroids
+        .iter()
+        .map(|centroid| squared_distance(centroid, embedding))
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, 0.0))
+}
+
+/// Picks the first centroid uniformly, then each next one with probability
+/// proportional to its squared distance from the nearest centroid so far.
+fn kmeans_plus_plus(embeddings: &[Vec<f32>], k: usize, rng: &mut SplitMix64) -> Vec<Vec<f32>> {
+    let mut centroids = vec![embeddings[rng.below(embeddings.len())].clone()];
+    let mut distances: Vec<f32> = embeddings
+        .iter()
+        .map(|embedding| squared_distance(embedding, &centroids[0]))
+        .collect();
+    while centroids.len() < k {
+        let total: f64 = distances.iter().map(|&d| f64::from(d)).sum();
+        let next = if total <= 0.0 {
+            // Every point coincides with a centroid
+            rng.below(embeddings.len())
+        } else {
+            let mut target = rng.next_f64() * total;
+            distances
+                .iter()
+                .position(|&d| {
+                    target -= f64::from(d);
+                    target <= 0.0
+                })
+                .unwrap_or(embeddings.len() - 1)
+        };
+        centroids.push(embeddings[next].clone());
+        for (distance, embedding) in distances.iter_mut().zip(embeddings) {
+            *distance = distance.min(squared_distance(embedding, &centroids[centroids.len() - 1]));
+        }
+    }
+    centroids
+}
+
+/// Moves the centroid of each empty cluster onto the embedding farthest from
+/// its own centroid.
+fn reseed_empty_clusters(
+    embeddings: &[Vec<f32>],
+    assignments: &mut [usize],
+    centroids: &mut [Vec<f32>],
+) {
+    let mut sizes = vec![0usize; centroids.len()];
+    for &cluster in assignments.iter() {
+        sizes[cluster] += 1;
+    }
+    for cluster in 0..centroids.len() {
+        if sizes[cluster] > 0 {
+            continue;
+        }
+        let farthest = embeddings
+            .iter()
+            .enumerate()
+            .filter(|(position, _)| sizes[assignments[*position]] > 1)
+            .max_by(|(a, x), (b, y)| {
+                squared_distance(x, &centroids[assignments[*a]])
+                    .total_cmp(&squared_distance(y, &centroids[assignments[*b]]))
+            })
+            .map(|(position, _)| position);
+        if let Some(position) = farthest {
+            sizes[assignments[position]] -= 1;
+            sizes[cluster] = 1;
+            assignments[position] = cluster;
+            centroids[cluster] = embeddings[position].clone();
+        }
+    }
+}
+
+fn finish(embeddings: &[Vec<f32>], centroids: Vec<Vec<f32>>, iterations: usize) -> Clustering {
+    let mut inertia = 0.0;
+    let assignments = embeddings
+        .iter()
+        .map(|embedding| {
+            let (cluster, distance) = nearest(&centroids, embedding);
+            inertia += distance;
+            cluster
+        })
+        .collect();
+    Clustering {
+        assignments,
+        centroids,
+        inertia,
+        iterations,
+    }
+}
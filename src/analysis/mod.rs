@@ -0,0 +1,63 @@
+//! Exploring sets of embeddings locally: clustering, outlier scores,
+//! projection and export for visualization, working on plain
+//! `Vec<Vec<f32>>`, e.g. the output of [`embed_documents`](crate::client::embeddings_client::Client::embed_documents).
+
+pub mod anomaly;
+pub mod kmeans;
+pub mod pca;
+pub mod projector;
+
+pub use anomaly::{anomaly_scores, anomaly_scores_with, AnomalyMethod};
+pub use kmeans::{Clustering, KMeans, MiniBatchKMeans};
+pub use pca::{project, Pca};
+pub use projector::{export_projector, write_metadata_tsv, write_vectors_tsv};
+
+use crate::errors::VoyageError;
+
+/// Checks that `embeddings` is non-empty and that every embedding has the
+/// same, non-zero dimension, which is returned.
+pub(crate) fn validate_embeddings(embeddings: &[Vec<f32>]) -> Result<usize, VoyageError> {
+    let dimension = embeddings
+        .first()
+        .map(Vec::len)
+        .ok_or_else(|| VoyageError::AnalysisError("no embeddings given".to_string()))?;
+    if dimension == 0 {
+        return Err(VoyageError::AnalysisError(
+            "embeddings have no dimensions".to_string(),
+        ));
+    }
+    if let Some((index, embedding)) = embeddings
+        .iter()
+        .enumerate()
+        .find(|(_, embedding)| embedding.len() != dimension)
+    {
+        return Err(VoyageError::EmbeddingDimensionMismatch {
+            index,
+            expected: dimension,
+            actual: embedding.len(),
+        });
+    }
+    Ok(dimension)
+}
+
+pub(crate) fn squared_distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// Mean of `embeddings`, which must share `dimension`.
+pub(crate) fn mean<'a>(
+    embeddings: impl IntoIterator<Item = &'a Vec<f32>>,
+    dimension: usize,
+) -> Vec<f32> {
+    let mut sum = vec![0.0f64; dimension];
+    let mut count = 0usize;
+    for embedding in embeddings {
+        for (total, x) in sum.iter_mut().zip(embedding) {
+            *total += f64::from(*x);
+        }
+        count += 1;
+    }
+    sum.into_iter()
+        .map(|total| (total / count.max(1) as f64) as f32)
+        .collect()
+}
@@ -0,0 +1,210 @@
+//! Principal component analysis for projecting embeddings to a few
+//! dimensions.
+//!
+//! [`Pca`] finds the directions of greatest variance by power iteration on
+//! the covariance of the centered embeddings, deflating the data after each
+//! component, without forming the covariance matrix. Projecting onto 2 or 3 components gives coordinates for
+//! plotting; projecting onto a few dozen is the usual preprocessing before
+//! UMAP or t-SNE.
+//!
+//! ```
+//! use voyageai::analysis::Pca;
+//!
+//! let embeddings = vec![
+//!     vec![1.0, 2.0, 0.0],
+//!     vec![2.0, 4.0, 0.1],
+//!     vec![3.0, 6.0, 0.0],
+//! ];
+//! let pca = Pca::fit(&embeddings, 2)?;
+//! let points = pca.transform_all(&embeddings);
+//!
+//! assert_eq!(points[0].len(), 2);
+//! assert!(pca.explained_variance_ratio()[0] > 0.99);
+//! # Ok::<(), voyageai::VoyageError>(())
+//! ```
+
+use crate::analysis::{mean, validate_embeddings};
+use crate::errors::VoyageError;
+use crate::rng::SplitMix64;
+
+/// Maximum power iterations per component.
+const MAX_ITERATIONS: usize = 500;
+/// Power iteration stops once the component moves less than this.
+const CONVERGENCE: f64 = 1e-9;
+
+/// A fitted principal component projection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pca {
+    mean: Vec<f32>,
+    components: Vec<Vec<f32>>,
+    explained_variance: Vec<f32>,
+    total_variance: f32,
+}
+
+impl Pca {
+    /// Fits the first `components` principal components of `embeddings`.
+    /// Fails if `components` is zero or exceeds the embedding dimension, or
+    /// if the embeddings are empty or of differing dimensions.
+    pub fn fit(embeddings: &[Vec<f32>], components: usize) -> Result<Self, VoyageError> {
+        let dimension = validate_embeddings(embeddings)?;
+        if components == 0 || components > dimension {
+            return Err(VoyageError::AnalysisError(format!(
+                "components must be between 1 and the embedding dimension ({dimension}), got {components}"
+            )));
+        }
+        let mean = mean(embeddings, dimension);
+        let mut centered: Vec<Vec<f64>> = embeddings
+            .iter()
+            .map(|embedding| {
+                embedding
+                    .iter()
+                    .zip(&mean)
+                    .map(|(x, m)| f64::from(x - m))
+                    .collect()
+            })
+            .collect();
+        let degrees_of_freedom = (embeddings.len().max(2) - 1) as f64;
+        let total_variance = centered
+            .iter()
+            .map(|row| row.iter().map(|x| x * x).sum::<f64>())
+            .sum::<f64>()
+            / degrees_of_freedom;
+
+        let mut rng = SplitMix64::new(0x5eed);
+        let mut found: Vec<Vec<f64>> = Vec::with_capacity(components);
+        let mut explained_variance = Vec::with_capacity(components);
+        for _ in 0..components {
+            let mut vector: Vec<f64> = (0..dimension).map(|_| rng.next_gaussian()).collect();
+            orthonormalize(&mut vector, &found);
+            let mut eigenvalue = 0.0;
+            for _ in 0..MAX_ITERATIONS {
+                let mut next = covariance_times(&centered, &vector, dimension);
+                if dot(&next, &next).sqrt() <= f64::EPSILON * total_variance * degrees_of_freedom {
+                    // No variance left: any direction orthogonal to the
+                    // components found so far will do
+                    eigenvalue = 0.0;
+                    break;
+                }
+                orthonormalize(&mut next, &found);
+                eigenvalue = dot(&next, &covariance_times(&centered, &next, dimension));
+                let moved = next
+                    .iter()
+                    .zip(&vector)
+                    .map(|(a, b)| (a - b) * (a - b))
+                    .sum::<f64>();
+                vector = next;
+                if moved < CONVERGENCE {
+                    break;
+                }
+            }
+            // Fix the sign so that results do not depend on the start vector
+            if let Some(&largest) = vector.iter().max_by(|a, b| a.abs().total_cmp(&b.abs())) {
+                if largest < 0.0 {
+                    vector.iter_mut().for_each(|x| *x = -*x);
+                }
+            }
+            explained_variance.push((eigenvalue / degrees_of_freedom) as f32);
+            // Deflate: remove the component from the data so that rounding
+            // errors cannot lead the next iterations back to it
+            for row in &mut centered {
+                let projection = dot(row, &vector);
+                for (x, v) in row.iter_mut().zip(&vector) {
+                    *x -= projection * v;
+                }
+            }
+            found.push(vector);
+        }
+
+        Ok(Self {
+            mean,
+            components: found
+                .into_iter()
+                .map(|component| component.into_iter().map(|x| x as f32).collect())
+                .collect(),
+            explained_variance,
+            total_variance: total_variance as f32,
+        })
+    }
+
+    /// The principal components, unit vectors in order of decreasing
+    /// variance.
+    pub fn components(&self) -> &[Vec<f32>] {
+        &self.components
+    }
+
+    /// Variance of the embeddings along each component.
+    pub fn explained_variance(&self) -> &[f32] {
+        &self.explained_variance
+    }
+
+    /// Share of the embeddings' total variance along each component.
+    pub fn explained_variance_ratio(&self) -> Vec<f32> {
+        self.explained_variance
+            .iter()
+            .map(|variance| {
+                if self.total_variance > 0.0 {
+                    variance / self.total_variance
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+
+    /// Coordinates of `embedding` along each component.
+    pub fn transform(&self, embedding: &[f32]) -> Vec<f32> {
+        self.components
+            .iter()
+            .map(|component| {
+                component
+                    .iter()
+                    .zip(embedding.iter().zip(&self.mean))
+                    .map(|(c, (x, m))| c * (x - m))
+                    .sum()
+            })
+            .collect()
+    }
+
+    pub fn transform_all(&self, embeddings: &[Vec<f32>]) -> Vec<Vec<f32>> {
+        embeddings
+            .iter()
+            .map(|embedding| self.transform(embedding))
+            .collect()
+    }
+}
+
+/// Projects `embeddings` onto their first `dimensions` principal components.
+pub fn project(embeddings: &[Vec<f32>], dimensions: usize) -> Result<Vec<Vec<f32>>, VoyageError> {
+    Ok(Pca::fit(embeddings, dimensions)?.transform_all(embeddings))
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// `Xᵀ X v` for the centered rows `X`, proportional to the covariance times
+/// `v`.
+fn covariance_times(centered: &[Vec<f64>], vector: &[f64], dimension: usize) -> Vec<f64> {
+    let mut result = vec![0.0; dimension];
+    for row in centered {
+        let projection = dot(row, vector);
+        for (r, x) in result.iter_mut().zip(row) {
+            *r += projection * x;
+        }
+    }
+    result
+}
+
+/// Removes the components of `vector` along `basis` and normalizes it.
+fn orthonormalize(vector: &mut [f64], basis: &[Vec<f64>]) {
+    for direction in basis {
+        let projection = dot(vector, direction);
+        for (v, d) in vector.iter_mut().zip(direction) {
+            *v -= projection * d;
+        }
+    }
+    let norm = dot(vector, vector).sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
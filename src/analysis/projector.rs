@@ -0,0 +1,113 @@
+//! Export for the [TensorBoard Embedding Projector](https://projector.tensorflow.org).
+//!
+//! The projector loads two tab-separated files: one row of vector
+//! components per embedding, and optionally one row of metadata per
+//! embedding, with a header row naming the columns when there are several.
+//! It runs PCA, t-SNE and UMAP in the browser; for very large corpora,
+//! reducing the vectors first with [`Pca`](crate::analysis::Pca) keeps the
+//! files small.
+//!
+//! ```no_run
+//! # fn example(embeddings: Vec<Vec<f32>>, metadata: Vec<voyageai::models::Metadata>) -> Result<(), voyageai::VoyageError> {
+//! voyageai::analysis::export_projector("projector/", &embeddings, Some(&metadata))?;
+//! // Load projector/vectors.tsv and projector/metadata.tsv in the projector
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::errors::VoyageError;
+use crate::models::metadata::{Metadata, MetadataValue};
+
+/// Writes `vectors.tsv` and, when `metadata` is given, `metadata.tsv` into
+/// `directory`, creating it if needed.
+pub fn export_projector(
+    directory: impl AsRef<Path>,
+    embeddings: &[Vec<f32>],
+    metadata: Option<&[Metadata]>,
+) -> Result<(), VoyageError> {
+    let directory = directory.as_ref();
+    fs::create_dir_all(directory).map_err(|e| io_error(directory, e))?;
+
+    let path = directory.join("vectors.tsv");
+    let file = File::create(&path).map_err(|e| io_error(&path, e))?;
+    write_vectors_tsv(BufWriter::new(file), embeddings)?;
+
+    if let Some(metadata) = metadata {
+        if metadata.len() != embeddings.len() {
+            return Err(VoyageError::AnalysisError(format!(
+                "{} metadata rows given for {} embeddings",
+                metadata.len(),
+                embeddings.len()
+            )));
+        }
+        let path = directory.join("metadata.tsv");
+        let file = File::create(&path).map_err(|e| io_error(&path, e))?;
+        write_metadata_tsv(BufWriter::new(file), metadata)?;
+    }
+    Ok(())
+}
+
+/// Writes one tab-separated row of components per embedding.
+pub fn write_vectors_tsv(
+    mut writer: impl Write,
+    embeddings: &[Vec<f32>],
+) -> Result<(), VoyageError> {
+    for embedding in embeddings {
+        let row: Vec<String> = embedding.iter().map(f32::to_string).collect();
+        writeln!(writer, "{}", row.join("\t")).map_err(write_error)?;
+    }
+    writer.flush().map_err(write_error)
+}
+
+/// Writes one tab-separated row per metadata entry. The columns are the keys
+/// used by any entry, sorted; with several columns the first row names
+/// them, as the projector expects. Missing values are left empty, and tabs
+/// and newlines inside values are replaced with spaces.
+pub fn write_metadata_tsv(
+    mut writer: impl Write,
+    metadata: &[Metadata],
+) -> Result<(), VoyageError> {
+    let columns: Vec<&String> = metadata
+        .iter()
+        .flat_map(|entry| entry.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    if columns.len() > 1 {
+        let header: Vec<String> = columns.iter().map(|column| sanitize(column)).collect();
+        writeln!(writer, "{}", header.join("\t")).map_err(write_error)?;
+    }
+    for entry in metadata {
+        let row: Vec<String> = columns
+            .iter()
+            .map(|column| entry.get(*column).map(format_value).unwrap_or_default())
+            .collect();
+        writeln!(writer, "{}", row.join("\t")).map_err(write_error)?;
+    }
+    writer.flush().map_err(write_error)
+}
+
+fn format_value(value: &MetadataValue) -> String {
+    match value {
+        MetadataValue::Bool(value) => value.to_string(),
+        MetadataValue::Number(value) => value.to_string(),
+        MetadataValue::String(value) => sanitize(value),
+    }
+}
+
+fn sanitize(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> VoyageError {
+    VoyageError::AnalysisError(format!("{}: {}", path.display(), error))
+}
+
+fn write_error(error: std::io::Error) -> VoyageError {
+    VoyageError::AnalysisError(format!("writing TSV: {error}"))
+}
@@ -1,6 +1,8 @@
 use crate::{
-    errors::VoyageBuilderError,
-    models::embeddings::{EmbeddingModel, EmbeddingsInput, EmbeddingsRequest, InputType, EncodingFormat},
+    builder::validation::{validate_embeddings, ValidationError, Violation},
+    models::embeddings::{
+        EmbeddingModel, EmbeddingsInput, EmbeddingsRequest, EncodingFormat, InputType,
+    },
 };
 use log::{debug, error};
 
@@ -9,7 +11,7 @@ pub struct EmbeddingsRequestBuilder {
     input: Option<EmbeddingsInput>,
     model: Option<EmbeddingModel>,
     input_type: Option<InputType>,
-    truncation: Option<bool>, 
+    truncation: Option<bool>,
     encoding_format: Option<EncodingFormat>,
 }
 
@@ -79,23 +81,34 @@ impl EmbeddingsRequestBuilder {
         self
     }
 
-    pub fn build(self) -> Result<EmbeddingsRequest, VoyageBuilderError> {
+    /// Builds the request, validating it against the model's limits first.
+    ///
+    /// Every problem found is reported at once in the returned [`ValidationError`].
+    pub fn build(self) -> Result<EmbeddingsRequest, ValidationError> {
         debug!("Building EmbeddingsRequest");
-        let input = self.input.ok_or_else(|| {
-            error!("Input is required for EmbeddingsRequest");
-            VoyageBuilderError::MissingField("input".to_string())
-        })?;
-        let model = self.model.ok_or_else(|| {
-            error!("Model is required for EmbeddingsRequest");
-            VoyageBuilderError::MissingModel
-        })?;
-
-        Ok(EmbeddingsRequest {
-            input,
-            model,
-            input_type: self.input_type,
-            truncation: self.truncation,
-            encoding_format: self.encoding_format,
-        })
+        match (self.input, self.model) {
+            (Some(input), Some(model)) => {
+                ValidationError::check(validate_embeddings(&input, model, self.input_type))
+                    .inspect_err(|e| error!("Invalid EmbeddingsRequest: {}", e))?;
+                Ok(EmbeddingsRequest {
+                    input,
+                    model,
+                    input_type: self.input_type,
+                    truncation: self.truncation,
+                    encoding_format: self.encoding_format,
+                })
+            }
+            (input, model) => {
+                error!("EmbeddingsRequest is missing required fields");
+                let mut violations = Vec::new();
+                if input.is_none() {
+                    violations.push(Violation::MissingField("input"));
+                }
+                if model.is_none() {
+                    violations.push(Violation::MissingField("model"));
+                }
+                Err(ValidationError { violations })
+            }
+        }
     }
 }
@@ -1,4 +1,5 @@
 pub mod embeddings;
 pub mod rerank;
 pub mod search;
+pub mod validation;
 pub mod voyage;
@@ -1,3 +1,4 @@
+use crate::builder::validation::{validate_rerank, ValidationError, Violation};
 use crate::models::rerank::{RerankModel, RerankRequest};
 
 #[derive(Clone)]
@@ -6,6 +7,8 @@ pub struct RerankRequestBuilder {
     documents: Option<Vec<String>>,
     model: Option<RerankModel>,
     top_k: Option<usize>,
+    return_documents: Option<bool>,
+    min_score: Option<f64>,
 }
 
 impl Default for RerankRequestBuilder {
@@ -21,6 +24,8 @@ impl RerankRequestBuilder {
             documents: None,
             model: None,
             top_k: None,
+            return_documents: None,
+            min_score: None,
         }
     }
 
@@ -44,16 +49,38 @@ impl RerankRequestBuilder {
         self
     }
 
-    pub fn build(self) -> Result<RerankRequest, &'static str> {
-        let query = self.query.ok_or("Query is required")?;
-        let documents = self.documents.ok_or("Documents are required")?;
-        let model = self.model.ok_or("Model is required")?;
+    /// Asks the API to echo each document's text in its result.
+    pub fn return_documents(mut self, return_documents: bool) -> Self {
+        self.return_documents = Some(return_documents);
+        self
+    }
+
+    /// Drops the results with a relevance score below `min_score`.
+    pub fn min_score(mut self, min_score: f64) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+
+    /// Builds the request, validating it against the model's limits first.
+    ///
+    /// Every problem found is reported at once in the returned [`ValidationError`].
+    pub fn build(self) -> Result<RerankRequest, ValidationError> {
+        let query = self.query.unwrap_or_default();
+        let documents = self.documents.unwrap_or_default();
+        let mut violations = validate_rerank(&query, &documents, self.model.unwrap_or_default());
+        let Some(model) = self.model else {
+            violations.push(Violation::MissingField("model"));
+            return Err(ValidationError { violations });
+        };
+        ValidationError::check(violations)?;
 
         Ok(RerankRequest {
             query,
             documents,
             model,
             top_k: self.top_k,
+            return_documents: self.return_documents,
+            min_score: self.min_score,
         })
     }
 }
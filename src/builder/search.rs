@@ -1,4 +1,11 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
 use crate::errors::VoyageBuilderError;
+use crate::errors::VoyageError;
+use crate::index::{NamespacedIndex, SharedIndex, VectorIndex};
+use crate::models::filter::MetadataFilter;
+use crate::models::metadata::Metadata;
 use crate::models::search::{SearchModel, SearchQuery, SearchType};
 use serde::{Deserialize, Serialize};
 
@@ -9,9 +16,15 @@ pub struct SearchRequestBuilder {
     query: Option<String>,
     documents: Option<Vec<String>>,
     embeddings: Option<Vec<Vec<f32>>>,
+    index: Option<IndexSource>,
+    namespace: Option<String>,
+    metadata: Option<Vec<Metadata>>,
+    filter: Option<MetadataFilter>,
     model: Option<SearchModel>,
     top_k: Option<usize>,
     search_type: Option<SearchType>,
+    rerank: bool,
+    min_score: Option<f32>,
 }
 
 impl SearchRequestBuilder {
@@ -42,6 +55,58 @@ impl SearchRequestBuilder {
         self
     }
 
+    /// Searches the documents of a loaded index instead of `documents`.
+    pub fn index(&mut self, index: Arc<VectorIndex>) -> &mut Self {
+        self.index = Some(IndexSource::Loaded(index));
+        self
+    }
+
+    /// Searches the latest snapshot of an index that other tasks keep
+    /// writing to, taken when the search runs, instead of `documents`.
+    pub fn shared_index(&mut self, index: SharedIndex) -> &mut Self {
+        self.index = Some(IndexSource::Shared(index));
+        self
+    }
+
+    /// Searches the documents of the index saved at `path`, loaded when the
+    /// search runs, instead of `documents`.
+    pub fn index_path(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.index = Some(IndexSource::Path(path.into()));
+        self
+    }
+
+    /// Searches one namespace of a loaded namespaced index instead of
+    /// `documents`; the namespace is set with [`namespace`](Self::namespace).
+    pub fn namespaced_index(&mut self, index: Arc<NamespacedIndex>) -> &mut Self {
+        self.index = Some(IndexSource::Namespaced(index));
+        self
+    }
+
+    /// Like [`namespaced_index`](Self::namespaced_index), for the namespaced
+    /// index saved at `path`, loaded when the search runs.
+    pub fn namespaced_index_path(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.index = Some(IndexSource::NamespacedPath(path.into()));
+        self
+    }
+
+    /// The namespace of a namespaced index to search, required with one.
+    pub fn namespace(&mut self, namespace: impl Into<String>) -> &mut Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Sets the metadata for the documents, one entry per document.
+    pub fn metadata(&mut self, metadata: Vec<Metadata>) -> &mut Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Restricts the search to documents whose metadata matches the filter.
+    pub fn filter(&mut self, filter: MetadataFilter) -> &mut Self {
+        self.filter = Some(filter);
+        self
+    }
+
     /// Sets the model to be used for searching.
     pub fn model(&mut self, model: SearchModel) -> &mut Self {
         self.model = Some(model);
@@ -60,6 +125,20 @@ impl SearchRequestBuilder {
         self
     }
 
+    /// Enables scoring of the results with the rerank endpoint.
+    pub fn rerank(&mut self, rerank: bool) -> &mut Self {
+        self.rerank = rerank;
+        self
+    }
+
+    /// Drops the results scoring below `min_score`, or, for
+    /// [`SearchType::NearestNeighbor`] results ranked by Euclidean distance,
+    /// farther than `min_score`.
+    pub fn min_score(&mut self, min_score: f32) -> &mut Self {
+        self.min_score = Some(min_score);
+        self
+    }
+
     /// Builds the `SearchRequest` from the builder.
     pub fn build(&self) -> Result<SearchRequest, VoyageBuilderError> {
         let query = self
@@ -72,11 +151,18 @@ impl SearchRequestBuilder {
             .clone()
             .ok_or(VoyageBuilderError::MissingField("search_type".to_string()))?;
 
-        if self.documents.is_none() && self.embeddings.is_none() {
+        if self.documents.is_none() && self.embeddings.is_none() && self.index.is_none() {
             return Err(VoyageBuilderError::MissingField(
-                "documents or embeddings".to_string(),
+                "documents, embeddings or index".to_string(),
             ));
         }
+        let namespaced = matches!(
+            self.index,
+            Some(IndexSource::Namespaced(_) | IndexSource::NamespacedPath(_))
+        );
+        if namespaced && self.namespace.as_deref().is_none_or(str::is_empty) {
+            return Err(VoyageBuilderError::MissingField("namespace".to_string()));
+        }
 
         Ok(SearchRequest {
             query: SearchQuery {
@@ -88,9 +174,15 @@ impl SearchRequestBuilder {
             },
             documents: self.documents.clone(),
             embeddings: self.embeddings.clone(),
+            index: self.index.clone(),
+            namespace: self.namespace.clone(),
+            metadata: self.metadata.clone(),
+            filter: self.filter.clone(),
             model,
             top_k: self.top_k,
             search_type,
+            rerank: self.rerank,
+            min_score: self.min_score,
         })
     }
 }
@@ -106,6 +198,19 @@ pub struct SearchRequest {
     /// The embeddings of the documents (optional).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub embeddings: Option<Vec<Vec<f32>>>,
+    /// A saved index whose documents and embeddings are searched instead of
+    /// `documents` (optional). Its metadata is matched against `filter`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<IndexSource>,
+    /// The namespace searched in a namespaced `index`, required with one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Metadata for each document, aligned with `documents` (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Vec<Metadata>>,
+    /// Only documents whose metadata matches this filter are searched (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<MetadataFilter>,
     /// The model to be used for searching.
     pub model: SearchModel,
     /// The number of top results to return.
@@ -113,6 +218,72 @@ pub struct SearchRequest {
     pub top_k: Option<usize>,
     /// The type of search to perform.
     pub search_type: SearchType,
+    /// Whether to score the results with the rerank endpoint.
+    #[serde(default)]
+    pub rerank: bool,
+    /// Results whose final score, after reranking and scoring but before
+    /// score normalization, is below this are dropped (optional). Results of
+    /// a [`SearchType::NearestNeighbor`] search neither reranked nor scored
+    /// are ranked by Euclidean distance, so those farther than this are
+    /// dropped instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<f32>,
+}
+
+/// The index a [`SearchRequest`] searches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexSource {
+    /// An index file written by [`VectorIndex::save`], loaded (memory-mapped)
+    /// for each search
+    Path(PathBuf),
+    /// An index already in memory, shared without copying. Load an index
+    /// once this way for repeated searches. Not serialized.
+    #[serde(skip)]
+    Loaded(Arc<VectorIndex>),
+    /// An index written to concurrently, whose snapshot is taken for each
+    /// search. Not serialized.
+    #[serde(skip)]
+    Shared(SharedIndex),
+    /// A namespaced index file written by [`NamespacedIndex::save`], loaded
+    /// for each search
+    NamespacedPath(PathBuf),
+    /// A namespaced index already in memory. Not serialized.
+    #[serde(skip)]
+    Namespaced(Arc<NamespacedIndex>),
+}
+
+impl IndexSource {
+    /// The index, loading it from disk for [`IndexSource::Path`]. Fails with
+    /// [`VoyageError::NamespaceRequired`] for a namespaced index.
+    pub fn resolve(&self) -> Result<Arc<VectorIndex>, VoyageError> {
+        self.resolve_namespace(None)
+    }
+
+    /// The index of `namespace` for a namespaced index, which requires one,
+    /// or the index itself otherwise, which has no namespaces.
+    pub fn resolve_namespace(
+        &self,
+        namespace: Option<&str>,
+    ) -> Result<Arc<VectorIndex>, VoyageError> {
+        match (self, namespace) {
+            (Self::Path(_) | Self::Loaded(_) | Self::Shared(_), Some(namespace)) => {
+                Err(VoyageError::NamespaceNotFound(namespace.to_string()))
+            }
+            (Self::Path(path), None) => Ok(Arc::new(VectorIndex::load(path)?)),
+            (Self::Loaded(index), None) => Ok(index.clone()),
+            (Self::Shared(index), None) => Ok(index.snapshot()),
+            (Self::NamespacedPath(_) | Self::Namespaced(_), None) => {
+                Err(VoyageError::NamespaceRequired)
+            }
+            (Self::NamespacedPath(path), Some(namespace)) => {
+                Ok(NamespacedIndex::load(path)?.namespace(namespace)?.clone())
+            }
+            (Self::Namespaced(index), Some(namespace)) => {
+                Ok(index.namespace(namespace)?.clone())
+            }
+        }
+    }
 }
 
 impl SearchRequest {
@@ -0,0 +1,177 @@
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::models::embeddings::{EmbeddingModel, EmbeddingsInput, InputType};
+use crate::models::rerank::{RerankModel, RerankRequest, RerankValidationError};
+use crate::models::tokens::estimate_tokens;
+
+/// Maximum number of texts in a single embeddings request.
+pub const MAX_EMBEDDING_INPUTS: usize = 128;
+
+/// A single problem found while validating a request locally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// A required builder field was never set
+    MissingField(&'static str),
+    /// The input list is empty
+    EmptyInput,
+    /// The text at `index` is empty
+    EmptyText { index: usize },
+    /// More texts than a single request accepts
+    TooManyInputs { count: usize, max: usize },
+    /// The text at `index` exceeds the model's context window
+    InputTooLong {
+        index: usize,
+        tokens: usize,
+        max: usize,
+    },
+    /// The whole request exceeds the model's per-request token budget
+    TotalTokensExceeded { tokens: usize, max: usize },
+    /// The input type is not supported by the model
+    IncompatibleInputType {
+        input_type: InputType,
+        model: EmbeddingModel,
+    },
+    /// The rerank request is invalid
+    Rerank(RerankValidationError),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "missing required field `{field}`"),
+            Self::EmptyInput => write!(f, "input cannot be empty"),
+            Self::EmptyText { index } => write!(f, "input {index} is empty"),
+            Self::TooManyInputs { count, max } => {
+                write!(f, "{count} inputs exceed the limit of {max}")
+            }
+            Self::InputTooLong { index, tokens, max } => write!(
+                f,
+                "input {index} has ~{tokens} tokens, exceeding the context window of {max}"
+            ),
+            Self::TotalTokensExceeded { tokens, max } => write!(
+                f,
+                "request has ~{tokens} tokens, exceeding the per-request limit of {max}"
+            ),
+            Self::IncompatibleInputType { input_type, model } => {
+                write!(f, "input type {input_type:?} is not supported by {model}")
+            }
+            Self::Rerank(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+/// Every [`Violation`] found while validating a request, in the order they
+/// were detected.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub struct ValidationError {
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationError {
+    /// Returns `Ok(())` when `violations` is empty.
+    pub fn check(violations: Vec<Violation>) -> Result<(), Self> {
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Self { violations })
+        }
+    }
+
+    pub fn contains(&self, violation: &Violation) -> bool {
+        self.violations.contains(violation)
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid request: ")?;
+        for (i, violation) in self.violations.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{violation}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks an embeddings request against the limits of `model`.
+pub(crate) fn validate_embeddings(
+    input: &EmbeddingsInput,
+    model: EmbeddingModel,
+    input_type: Option<InputType>,
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let texts: &[String] = match input {
+        EmbeddingsInput::Single(text) => std::slice::from_ref(text),
+        EmbeddingsInput::Multiple(texts) => texts,
+    };
+
+    if texts.is_empty() {
+        violations.push(Violation::EmptyInput);
+    }
+    if texts.len() > MAX_EMBEDDING_INPUTS {
+        violations.push(Violation::TooManyInputs {
+            count: texts.len(),
+            max: MAX_EMBEDDING_INPUTS,
+        });
+    }
+
+    let mut total_tokens = 0;
+    for (index, text) in texts.iter().enumerate() {
+        if text.trim().is_empty() {
+            violations.push(Violation::EmptyText { index });
+            continue;
+        }
+        let tokens = estimate_tokens(text);
+        total_tokens += tokens;
+        if tokens > model.max_context_length() {
+            violations.push(Violation::InputTooLong {
+                index,
+                tokens,
+                max: model.max_context_length(),
+            });
+        }
+    }
+    if total_tokens > model.max_tokens_per_request() {
+        violations.push(Violation::TotalTokensExceeded {
+            tokens: total_tokens,
+            max: model.max_tokens_per_request(),
+        });
+    }
+
+    if let Some(input_type) = input_type {
+        if !model.supports_input_type(input_type) {
+            violations.push(Violation::IncompatibleInputType { input_type, model });
+        }
+    }
+    violations
+}
+
+/// Checks a rerank request against the limits of `model`.
+pub(crate) fn validate_rerank(
+    query: &str,
+    documents: &[String],
+    model: RerankModel,
+) -> Vec<Violation> {
+    RerankRequest::validate(query, documents, model)
+        .into_iter()
+        .map(Violation::Rerank)
+        .collect()
+}
+
+impl From<RerankValidationError> for Violation {
+    fn from(error: RerankValidationError) -> Self {
+        Self::Rerank(error)
+    }
+}
+
+impl From<RerankValidationError> for ValidationError {
+    fn from(error: RerankValidationError) -> Self {
+        Self {
+            violations: vec![Violation::Rerank(error)],
+        }
+    }
+}
@@ -1,19 +1,34 @@
 use crate::{
     client::{
         embeddings_client::Client as EmbeddingsClient,
-        rerank_client::DefaultRerankClient,
+        rerank_client::{DefaultRerankClient, RerankFallback},
         search_client::SearchClient,
-        RateLimiter,
+        CircuitBreaker, HedgingPolicy, Priority, RateLimitEndpoint, RateLimiter,
         voyage_client::{VoyageAiClient, VoyageAiClientConfig},
     },
-    config::VoyageConfig,
+    config::{ApiKeyProvider, VoyageConfig},
     errors::VoyageError,
+    logging::LogPolicy,
+    transport::Transport,
 };
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Clone)]
 pub struct VoyageBuilder {
     config: Option<VoyageConfig>,
+    rate_limiter: Option<RateLimiter>,
+    log_policy: Option<LogPolicy>,
+    rerank_fallback: Option<RerankFallback>,
+    base_url: Option<String>,
+    rate_limit_timeout: Option<Duration>,
+    priority: Option<Priority>,
+    coalesce_requests: Option<bool>,
+    idempotency_keys: Option<bool>,
+    circuit_breaker: Option<CircuitBreaker>,
+    max_in_flight: Option<usize>,
+    hedging: Vec<(RateLimitEndpoint, HedgingPolicy)>,
+    transport: Option<Arc<dyn Transport>>,
 }
 
 impl Default for VoyageBuilder {
@@ -26,6 +41,18 @@ impl VoyageBuilder {
     pub fn new() -> VoyageBuilder {
         VoyageBuilder {
             config: None,
+            rate_limiter: None,
+            log_policy: None,
+            rerank_fallback: None,
+            base_url: None,
+            rate_limit_timeout: None,
+            priority: None,
+            coalesce_requests: None,
+            idempotency_keys: None,
+            circuit_breaker: None,
+            max_in_flight: None,
+            hedging: Vec::new(),
+            transport: None,
         }
     }
 
@@ -34,11 +61,142 @@ impl VoyageBuilder {
         self
     }
 
+    /// Starts from `config`, e.g. one loaded with [`VoyageConfig::from_env`].
+    /// Settings of the builder override those of the configuration.
+    pub fn with_config(mut self, config: VoyageConfig) -> VoyageBuilder {
+        self.config = Some(config);
+        self
+    }
+
+    /// Fetches the API key from `provider` before every request, e.g. to pick
+    /// up keys rotated in a secret manager.
+    pub fn with_api_key_provider(mut self, provider: impl ApiKeyProvider) -> VoyageBuilder {
+        self.config = Some(VoyageConfig::with_provider(provider));
+        self
+    }
+
+    /// Uses the given rate limiter, e.g. one with an event callback registered.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> VoyageBuilder {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Includes (redacted, truncated) payload bodies in log messages.
+    pub fn log_payloads(mut self, enabled: bool) -> VoyageBuilder {
+        self.log_policy = Some(self.log_policy.unwrap_or_default().log_payloads(enabled));
+        self
+    }
+
+    /// Ranks documents by embedding similarity when the rerank endpoint fails.
+    pub fn with_rerank_fallback(mut self, fallback: RerankFallback) -> VoyageBuilder {
+        self.rerank_fallback = Some(fallback);
+        self
+    }
+
+    /// Sends requests to `base_url` instead of the Voyage AI API, e.g. a proxy
+    /// or a local test server.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> VoyageBuilder {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Fails requests that would wait longer than `timeout` for the rate
+    /// limiter.
+    pub fn with_rate_limit_timeout(mut self, timeout: Duration) -> VoyageBuilder {
+        self.rate_limit_timeout = Some(timeout);
+        self
+    }
+
+    /// Schedules the client's requests with `priority` when they wait for the
+    /// rate limiter.
+    pub fn with_priority(mut self, priority: Priority) -> VoyageBuilder {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Shares one API call among concurrent identical embedding requests.
+    pub fn with_request_coalescing(mut self, enabled: bool) -> VoyageBuilder {
+        self.coalesce_requests = Some(enabled);
+        self
+    }
+
+    /// Sends an idempotency key with every request, reused by its retries,
+    /// see [`VoyageConfig::with_idempotency_keys`].
+    pub fn with_idempotency_keys(mut self, enabled: bool) -> VoyageBuilder {
+        self.idempotency_keys = Some(enabled);
+        self
+    }
+
+    /// Fails requests at once while the API keeps failing, see
+    /// [`VoyageConfig::with_circuit_breaker`].
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> VoyageBuilder {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Allows at most `max_in_flight` requests at once, see
+    /// [`VoyageConfig::with_max_in_flight`].
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> VoyageBuilder {
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+
+    /// Duplicates slow requests to `endpoint`, see
+    /// [`VoyageConfig::with_hedging`].
+    pub fn with_hedging(mut self, endpoint: RateLimitEndpoint, policy: HedgingPolicy) -> VoyageBuilder {
+        self.hedging.push((endpoint, policy));
+        self
+    }
+
+    /// Sends requests with `transport` instead of `reqwest`.
+    pub fn with_transport(mut self, transport: impl Transport) -> VoyageBuilder {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
     pub fn build(self) -> Result<VoyageAiClient, VoyageError> {
-        let config = self.config.ok_or_else(|| VoyageError::BuilderError("API key is required".to_string()))?;
-        let rate_limiter = Arc::new(RateLimiter::new());
+        let mut config = self.config.ok_or_else(|| VoyageError::BuilderError("API key is required".to_string()))?;
+        if let Some(log_policy) = self.log_policy {
+            config.log_policy = log_policy;
+        }
+        if let Some(rerank_fallback) = self.rerank_fallback {
+            config.rerank_fallback = rerank_fallback;
+        }
+        if let Some(base_url) = self.base_url {
+            config.base_url = base_url;
+        }
+        if let Some(timeout) = self.rate_limit_timeout {
+            config.rate_limit_timeout = Some(timeout);
+        }
+        if let Some(priority) = self.priority {
+            config.priority = priority;
+        }
+        if let Some(enabled) = self.coalesce_requests {
+            config.coalesce_requests = enabled;
+        }
+        if let Some(enabled) = self.idempotency_keys {
+            config.idempotency_keys = enabled;
+        }
+        if let Some(breaker) = self.circuit_breaker {
+            config.circuit_breaker = Some(breaker);
+        }
+        if let Some(max_in_flight) = self.max_in_flight {
+            config = config.with_max_in_flight(max_in_flight);
+        }
+        for (endpoint, policy) in self.hedging {
+            config.hedging.insert(endpoint, policy);
+        }
+        if let Some(transport) = self.transport {
+            config.transport = Some(transport);
+        }
+        let rate_limiter = Arc::new(
+            self.rate_limiter
+                .unwrap_or_else(|| config.rate_limiter()),
+        );
 
-        let embeddings_client = Arc::new(EmbeddingsClient::new(config.clone()));
+        let embeddings_client = Arc::new(
+            EmbeddingsClient::new(config.clone()).with_rate_limiter(rate_limiter.clone()),
+        );
         let rerank_client = Arc::new(DefaultRerankClient::new(
             config.clone(),
             rate_limiter.clone(),
@@ -55,9 +213,7 @@ impl VoyageBuilder {
             search_client,
         };
 
-        Ok(VoyageAiClient {
-            config: client_config,
-        })
+        Ok(VoyageAiClient::from(client_config))
     }
 }
 
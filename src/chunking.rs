@@ -0,0 +1,99 @@
+//! Splitting documents into passages small enough to embed and rerank.
+
+use crate::text_splitter::{SentenceSplitter, TextSplitter};
+use crate::utils::{chunk_markdown, MarkdownChunk};
+
+/// Splits a document into passages.
+pub trait Chunker: std::fmt::Debug + Send + Sync {
+    fn chunk(&self, text: &str) -> Vec<String>;
+}
+
+/// Splits text on blank lines and packs paragraphs into chunks of at most
+/// `max_chars` characters. Paragraphs longer than the limit are split with a
+/// [`SentenceSplitter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParagraphChunker {
+    max_chars: usize,
+}
+
+impl ParagraphChunker {
+    pub fn new(max_chars: usize) -> Self {
+        Self {
+            max_chars: max_chars.max(1),
+        }
+    }
+
+    pub fn max_chars(&self) -> usize {
+        self.max_chars
+    }
+}
+
+impl Default for ParagraphChunker {
+    fn default() -> Self {
+        Self::new(1200)
+    }
+}
+
+impl Chunker for ParagraphChunker {
+    fn chunk(&self, text: &str) -> Vec<String> {
+        let max_chars = self.max_chars;
+        let mut chunks = Vec::new();
+        let mut current = String::new();
+
+        for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+            if !current.is_empty()
+                && current.chars().count() + paragraph.chars().count() + 2 > max_chars
+            {
+                chunks.push(std::mem::take(&mut current));
+            }
+            if paragraph.chars().count() > max_chars {
+                let splitter = SentenceSplitter::new(max_chars);
+                chunks.extend(splitter.split(paragraph).into_iter().map(|chunk| chunk.text));
+                continue;
+            }
+            if !current.is_empty() {
+                current.push_str("\n\n");
+            }
+            current.push_str(paragraph);
+        }
+        if !current.is_empty() {
+            chunks.push(current);
+        }
+        chunks
+    }
+}
+
+/// Splits Markdown along its headings with [`chunk_markdown`], keeping
+/// fenced code blocks intact. Each chunk starts with the breadcrumb of its
+/// headings, e.g. `"Guide > Setup"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkdownChunker {
+    max_chars: usize,
+}
+
+impl MarkdownChunker {
+    pub fn new(max_chars: usize) -> Self {
+        Self {
+            max_chars: max_chars.max(1),
+        }
+    }
+
+    pub fn max_chars(&self) -> usize {
+        self.max_chars
+    }
+}
+
+impl Default for MarkdownChunker {
+    fn default() -> Self {
+        Self::new(1200)
+    }
+}
+
+impl Chunker for MarkdownChunker {
+    fn chunk(&self, text: &str) -> Vec<String> {
+        chunk_markdown(text, self.max_chars)
+            .iter()
+            .map(MarkdownChunk::text_with_breadcrumb)
+            .collect()
+    }
+}
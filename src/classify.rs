@@ -0,0 +1,295 @@
+//! Text classification from a handful of labeled examples.
+//!
+//! A [`Classifier`] embeds example texts for each label once, then labels new
+//! texts by comparing their embeddings with the examples, without training a
+//! model:
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), voyageai::VoyageError> {
+//! use voyageai::{classify::Classifier, VoyageAiClient};
+//!
+//! let client = VoyageAiClient::new();
+//! let classifier = Classifier::builder(&client)
+//!     .fit([
+//!         ("billing", "I was charged twice this month"),
+//!         ("billing", "How do I update my credit card?"),
+//!         ("bug", "The app crashes when I open settings"),
+//!         ("bug", "Export produces an empty file"),
+//!     ])
+//!     .await?;
+//! let scores = classifier.classify("My invoice shows the wrong amount").await?;
+//! println!("{} ({:.2})", scores[0].label, scores[0].probability);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ClassifierMethod::NearestCentroid`] compares a text with the mean
+//! embedding of each label and suits labels with a few coherent examples.
+//! [`ClassifierMethod::KNearest`] votes among the most similar examples and
+//! copes better with labels covering several distinct kinds of text.
+
+use std::sync::Arc;
+
+use crate::client::embeddings_client::Client as EmbeddingsClient;
+use crate::client::voyage_client::VoyageAiClient;
+use crate::cosine_similarity;
+use crate::errors::VoyageError;
+use crate::models::embeddings::InputType;
+
+/// Default softmax temperature of [`ClassifierMethod::NearestCentroid`].
+pub const DEFAULT_TEMPERATURE: f32 = 0.05;
+
+/// How a [`Classifier`] turns similarities into label probabilities.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ClassifierMethod {
+    /// Softmax over the cosine similarities to each label's centroid.
+    #[default]
+    NearestCentroid,
+    /// Share of votes among the `k` most similar examples, each vote
+    /// weighted by its similarity.
+    KNearest(usize),
+}
+
+/// Probability of one label for a classified text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelScore {
+    pub label: String,
+    pub probability: f32,
+}
+
+/// Builder for [`Classifier`].
+#[derive(Debug, Clone)]
+pub struct ClassifierBuilder {
+    embeddings_client: Arc<EmbeddingsClient>,
+    method: ClassifierMethod,
+    temperature: f32,
+}
+
+impl ClassifierBuilder {
+    pub fn method(mut self, method: ClassifierMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Softmax temperature of [`ClassifierMethod::NearestCentroid`]; lower
+    /// values give more confident probabilities. Defaults to
+    /// [`DEFAULT_TEMPERATURE`].
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature.max(f32::EPSILON);
+        self
+    }
+
+    /// Embeds the `(label, text)` examples as documents and builds the
+    /// classifier. Fails without examples.
+    pub async fn fit<L, T>(
+        self,
+        examples: impl IntoIterator<Item = (L, T)>,
+    ) -> Result<Classifier, VoyageError>
+    where
+        L: Into<String>,
+        T: Into<String>,
+    {
+        let (labels, texts): (Vec<String>, Vec<String>) = examples
+            .into_iter()
+            .map(|(label, text)| (label.into(), text.into()))
+            .unzip();
+        if texts.is_empty() {
+            return Err(VoyageError::ClassificationError(
+                "At least one labeled example is required".to_string(),
+            ));
+        }
+        let embeddings = self
+            .embeddings_client
+            .embed_all(&texts, Some(InputType::Document))
+            .await?;
+        Classifier::from_embeddings(labels.into_iter().zip(embeddings)).map(|classifier| {
+            Classifier {
+                embeddings_client: Some(self.embeddings_client),
+                method: self.method,
+                temperature: self.temperature,
+                ..classifier
+            }
+        })
+    }
+}
+
+/// Labels texts by their similarity to labeled examples, see the
+/// [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct Classifier {
+    /// `None` for classifiers built from embeddings, which can only classify
+    /// embeddings
+    embeddings_client: Option<Arc<EmbeddingsClient>>,
+    method: ClassifierMethod,
+    temperature: f32,
+    /// Distinct labels in order of first appearance
+    labels: Vec<String>,
+    /// `(label position, embedding)` per example
+    examples: Vec<(usize, Vec<f32>)>,
+    /// Mean example embedding per label
+    centroids: Vec<Vec<f32>>,
+}
+
+impl Classifier {
+    pub fn builder(client: &VoyageAiClient) -> ClassifierBuilder {
+        ClassifierBuilder {
+            embeddings_client: client.config.embeddings_client.clone(),
+            method: ClassifierMethod::default(),
+            temperature: DEFAULT_TEMPERATURE,
+        }
+    }
+
+    /// A classifier over already embedded `(label, embedding)` examples,
+    /// using [`ClassifierMethod::NearestCentroid`]. It classifies embeddings
+    /// only; [`classify`](Self::classify) fails.
+    pub fn from_embeddings<L: Into<String>>(
+        examples: impl IntoIterator<Item = (L, Vec<f32>)>,
+    ) -> Result<Self, VoyageError> {
+        let mut labels: Vec<String> = Vec::new();
+        let mut labeled = Vec::new();
+        for (label, embedding) in examples {
+            let label = label.into();
+            let position = match labels.iter().position(|known| *known == label) {
+                Some(position) => position,
+                None => {
+                    labels.push(label);
+                    labels.len() - 1
+                }
+            };
+            labeled.push((position, embedding));
+        }
+        let Some((_, first)) = labeled.first() else {
+            return Err(VoyageError::ClassificationError(
+                "At least one labeled example is required".to_string(),
+            ));
+        };
+        let dimension = first.len();
+        if let Some((index, (_, embedding))) = labeled
+            .iter()
+            .enumerate()
+            .find(|(_, (_, embedding))| embedding.len() != dimension)
+        {
+            return Err(VoyageError::EmbeddingDimensionMismatch {
+                index,
+                expected: dimension,
+                actual: embedding.len(),
+            });
+        }
+
+        let mut centroids = vec![vec![0.0f32; dimension]; labels.len()];
+        let mut counts = vec![0usize; labels.len()];
+        for (position, embedding) in &labeled {
+            counts[*position] += 1;
+            for (c, x) in centroids[*position].iter_mut().zip(embedding) {
+                *c += x;
+            }
+        }
+        for (centroid, count) in centroids.iter_mut().zip(counts) {
+            centroid.iter_mut().for_each(|c| *c /= count as f32);
+        }
+        Ok(Self {
+            embeddings_client: None,
+            method: ClassifierMethod::default(),
+            temperature: DEFAULT_TEMPERATURE,
+            labels,
+            examples: labeled,
+            centroids,
+        })
+    }
+
+    pub fn with_method(mut self, method: ClassifierMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// See [`ClassifierBuilder::temperature`].
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature.max(f32::EPSILON);
+        self
+    }
+
+    /// Distinct labels, in order of their first example.
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Embeds `text` as a document and returns the probability of every
+    /// label, most likely first.
+    pub async fn classify(&self, text: &str) -> Result<Vec<LabelScore>, VoyageError> {
+        let mut scores = self.classify_batch(&[text.to_string()]).await?;
+        Ok(scores.remove(0))
+    }
+
+    /// Classifies several texts, embedded in as few requests as the API
+    /// allows.
+    pub async fn classify_batch(
+        &self,
+        texts: &[String],
+    ) -> Result<Vec<Vec<LabelScore>>, VoyageError> {
+        let embeddings_client = self.embeddings_client.as_ref().ok_or_else(|| {
+            VoyageError::ClassificationError(
+                "Classifier built from embeddings cannot embed texts".to_string(),
+            )
+        })?;
+        let embeddings = embeddings_client
+            .embed_all(texts, Some(InputType::Document))
+            .await?;
+        Ok(embeddings
+            .iter()
+            .map(|embedding| self.classify_embedding(embedding))
+            .collect())
+    }
+
+    /// Returns the probability of every label for an embedding, most likely
+    /// first. Ties keep the order of [`labels`](Self::labels).
+    pub fn classify_embedding(&self, embedding: &[f32]) -> Vec<LabelScore> {
+        let probabilities = match self.method {
+            ClassifierMethod::NearestCentroid => {
+                let similarities: Vec<f32> = self
+                    .centroids
+                    .iter()
+                    .map(|centroid| cosine_similarity(embedding, centroid) / self.temperature)
+                    .collect();
+                let max = similarities.iter().copied().fold(f32::MIN, f32::max);
+                let weights: Vec<f32> = similarities.iter().map(|s| (s - max).exp()).collect();
+                normalize(weights)
+            }
+            ClassifierMethod::KNearest(k) => {
+                let mut neighbors: Vec<(usize, f32)> = self
+                    .examples
+                    .iter()
+                    .map(|(position, example)| (*position, cosine_similarity(embedding, example)))
+                    .collect();
+                neighbors.sort_by(|a, b| b.1.total_cmp(&a.1));
+                neighbors.truncate(k.max(1));
+                let mut votes = vec![0.0f32; self.labels.len()];
+                for (position, similarity) in neighbors {
+                    // Opposed examples do not vote; a small floor keeps every
+                    // neighbor's vote when all are dissimilar
+                    votes[position] += similarity.max(0.0) + f32::EPSILON;
+                }
+                normalize(votes)
+            }
+        };
+        let mut scores: Vec<LabelScore> = self
+            .labels
+            .iter()
+            .zip(probabilities)
+            .map(|(label, probability)| LabelScore {
+                label: label.clone(),
+                probability,
+            })
+            .collect();
+        scores.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+        scores
+    }
+}
+
+fn normalize(weights: Vec<f32>) -> Vec<f32> {
+    let total: f32 = weights.iter().sum();
+    if total > 0.0 {
+        weights.into_iter().map(|w| w / total).collect()
+    } else {
+        vec![1.0 / weights.len() as f32; weights.len()]
+    }
+}
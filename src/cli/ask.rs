@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use serde_json::json;
+use voyageai::{quickstart::Quickstart, VoyageAiClient};
+
+use super::{CliResult, Output, OutputFormat};
+
+#[derive(clap::Args, Debug)]
+pub struct AskArgs {
+    /// File or directory to search
+    #[clap(short, long)]
+    path: PathBuf,
+
+    /// Question to answer
+    #[clap(short, long)]
+    question: String,
+
+    /// Number of passages to return
+    #[clap(short, long, default_value = "5")]
+    top_k: usize,
+}
+
+impl AskArgs {
+    pub async fn run(&self, client: &VoyageAiClient, output: &Output) -> CliResult {
+        let mut quickstart = Quickstart::new(client).top_k(self.top_k);
+        let answer = quickstart.ask(&self.path, &self.question).await?;
+
+        match output.format {
+            OutputFormat::Text => {
+                println!("\nMost relevant passages for: {}", answer.question);
+                for passage in &answer.passages {
+                    println!(
+                        "\nScore {:.4} [{}]",
+                        passage.score,
+                        passage.source.display()
+                    );
+                    println!("{}", passage.text);
+                }
+                println!("\nContext:\n{}", answer.context);
+                Ok(())
+            }
+            OutputFormat::Csv => output.csv(
+                &["rank", "score", "source", "text"],
+                answer.passages.iter().enumerate().map(|(rank, passage)| {
+                    vec![
+                        (rank + 1).to_string(),
+                        format!("{:.4}", passage.score),
+                        passage.source.display().to_string(),
+                        passage.text.clone(),
+                    ]
+                }),
+            ),
+            OutputFormat::Json => output.json(&json!({
+                "question": answer.question,
+                "passages": answer
+                    .passages
+                    .iter()
+                    .enumerate()
+                    .map(|(rank, passage)| json!({
+                        "rank": rank + 1,
+                        "score": passage.score,
+                        "source": passage.source,
+                        "text": passage.text,
+                    }))
+                    .collect::<Vec<_>>(),
+                "context": answer.context,
+            })),
+        }
+    }
+}
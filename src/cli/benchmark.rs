@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use voyageai::{
+    eval::{benchmark_pipeline, benchmarks_to_csv, benchmarks_to_table, EvalDataset},
+    EmbeddingModel, RetrievalPipeline, VoyageAiClient,
+};
+
+use super::{client_for_model, CliResult, Output, OutputFormat};
+
+#[derive(clap::Args, Debug)]
+pub struct BenchmarkArgs {
+    /// JSON Lines file of documents and labeled queries
+    #[clap(short, long)]
+    dataset: PathBuf,
+
+    /// Comma-separated models to compare
+    #[clap(short, long, value_delimiter = ',', default_value = "voyage-3-large")]
+    models: Vec<EmbeddingModel>,
+
+    /// Comma-separated cutoffs for recall@k and nDCG@k
+    #[clap(short = 'k', long, value_delimiter = ',', default_value = "1,5,10")]
+    cutoffs: Vec<usize>,
+
+    /// Rank by vector similarity only, without reranking
+    #[clap(long)]
+    no_rerank: bool,
+}
+
+impl BenchmarkArgs {
+    pub async fn run(&self, client: &VoyageAiClient, output: &Output) -> CliResult {
+        let dataset = EvalDataset::load(&self.dataset)?;
+        // Retrieve deep enough that collapsing chunks to documents still
+        // fills the largest cutoff
+        let depth = self.cutoffs.iter().copied().max().unwrap_or(10).max(20);
+
+        let mut results = Vec::with_capacity(self.models.len());
+        for &model in &self.models {
+            output.progress(format!(
+                "Benchmarking {} on {} queries...",
+                model,
+                dataset.queries.len()
+            ));
+            let model_client = client_for_model(client, model);
+            let mut builder = RetrievalPipeline::builder(&model_client)
+                .candidates(depth)
+                .top_k(depth);
+            if self.no_rerank {
+                builder = builder.without_rerank();
+            }
+            let mut pipeline = builder.build();
+            results.push(
+                benchmark_pipeline(model.to_string(), &mut pipeline, &dataset, &self.cutoffs)
+                    .await?,
+            );
+        }
+
+        match output.format {
+            OutputFormat::Text => print!("{}", benchmarks_to_table(&results)),
+            OutputFormat::Csv => print!("{}", benchmarks_to_csv(&results)),
+            OutputFormat::Json => output.json(&results)?,
+        }
+        Ok(())
+    }
+}
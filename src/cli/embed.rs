@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+
+use serde_json::json;
+use voyageai::{
+    io::{read_csv_texts, write_ndjson, EmbeddingRecord},
+    EmbeddingModel, VoyageAiClient,
+};
+
+use super::{client_for_model, CliResult, Output, OutputFormat};
+
+#[derive(clap::Args, Debug)]
+pub struct EmbedArgs {
+    /// Text to embed
+    #[clap(short, long, required_unless_present = "input")]
+    text: Vec<String>,
+
+    /// Model to use for embeddings (default from the configuration)
+    #[clap(short, long)]
+    model: Option<EmbeddingModel>,
+
+    /// CSV file with a header row to embed a column of, instead of --text
+    #[clap(short, long, conflicts_with = "text")]
+    input: Option<PathBuf>,
+
+    /// Column of the CSV input holding the texts
+    #[clap(long, default_value = "text", requires = "input")]
+    text_column: String,
+
+    /// Column of the CSV input holding ids (default: row numbers)
+    #[clap(long, requires = "input")]
+    id_column: Option<String>,
+
+    /// Write the embeddings to this NDJSON file, compressed if it ends in
+    /// .gz or .zst
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+}
+
+impl EmbedArgs {
+    pub async fn run(&self, client: &VoyageAiClient, output: &Output) -> CliResult {
+        if output.format == OutputFormat::Csv {
+            return Err(output.unsupported("embed"));
+        }
+        let model = self.model.unwrap_or(client.voyage_config().embedding_model);
+        let client = client_for_model(client, model);
+
+        let (ids, embeddings) = match &self.input {
+            Some(input) => {
+                let rows = read_csv_texts(input, &self.text_column, self.id_column.as_deref())?;
+                output.progress(format!(
+                    "Embedding {} rows of {}...",
+                    rows.len(),
+                    input.display()
+                ));
+                let texts: Vec<String> = rows.iter().map(|row| row.text.clone()).collect();
+                // Split into as many requests as the input limit requires
+                let embeddings = client.embeddings_client().embed_documents(&texts).await?;
+                (rows.into_iter().map(|row| row.id).collect(), embeddings)
+            }
+            None => {
+                let embeddings = client.embeddings_client().embed_batch(&self.text).await?;
+                let ids = (0..embeddings.len())
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>();
+                (ids, embeddings)
+            }
+        };
+        let model = model.to_string();
+        let records: Vec<EmbeddingRecord> = ids
+            .into_iter()
+            .zip(embeddings)
+            .map(|(id, vector)| EmbeddingRecord::new(id, model.as_str(), vector))
+            .collect();
+
+        match &self.output {
+            Some(path) => {
+                let written = write_ndjson(path, &records)?;
+                match output.format {
+                    OutputFormat::Json => output.json(&json!({
+                        "model": model,
+                        "embeddings": written,
+                        "output": path,
+                    }))?,
+                    _ => println!("Wrote {} embeddings to {}", written, path.display()),
+                }
+            }
+            None => match output.format {
+                OutputFormat::Json => output.json(&records)?,
+                _ => {
+                    println!("Generated {} embeddings", records.len());
+                    for record in &records {
+                        println!("Embedding {}: {} dimensions", record.id, record.dim);
+                    }
+                }
+            },
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,468 @@
+use std::path::{Path, PathBuf};
+
+use clap::Subcommand;
+use serde_json::json;
+use voyageai::{
+    client::{BulkEmbedder, BulkProgress},
+    code::{repository::DEFAULT_MAX_FILE_SIZE, CodeIndexer, RepoWalker, SkipReason},
+    EmbeddingModel, RetrievalPipeline, VectorIndex, VoyageAiClient,
+};
+
+use super::{client_for_index, client_for_model, CliResult, Output, OutputFormat};
+
+#[derive(Subcommand, Debug)]
+pub enum IndexCommand {
+    /// Index the text and source files of a directory, replacing any
+    /// existing index
+    Build {
+        /// Directory or file to index
+        path: PathBuf,
+
+        /// Index file to write
+        #[clap(short, long)]
+        index: PathBuf,
+
+        /// Embedding model (default from the configuration)
+        #[clap(short, long)]
+        model: Option<EmbeddingModel>,
+
+        #[clap(flatten)]
+        walk: WalkArgs,
+    },
+    /// Add files or directories to an index, replacing files indexed before
+    Add {
+        /// Index file to update
+        #[clap(short, long)]
+        index: PathBuf,
+
+        /// Files or directories to add
+        #[clap(required = true)]
+        paths: Vec<PathBuf>,
+
+        #[clap(flatten)]
+        walk: WalkArgs,
+    },
+    /// Re-index the files of a git repository changed in a range of commits,
+    /// only embedding the items that changed
+    Update {
+        /// Index file to update
+        #[clap(short, long)]
+        index: PathBuf,
+
+        /// Commits to compare, as passed to git diff, e.g. 'main...HEAD'
+        range: String,
+
+        /// Directory the index was built from
+        #[clap(default_value = ".")]
+        path: PathBuf,
+
+        #[clap(flatten)]
+        walk: WalkArgs,
+    },
+    /// Remove documents by id or path; a directory removes the files below it
+    Rm {
+        /// Index file to update
+        #[clap(short, long)]
+        index: PathBuf,
+
+        /// Document ids or paths to remove
+        #[clap(required = true)]
+        ids: Vec<String>,
+    },
+    /// Print the number of documents, dimension, model and size of an index
+    Stats {
+        /// Index file to describe
+        #[clap(short, long)]
+        index: PathBuf,
+    },
+    /// Remove the documents of deleted files, drop deleted documents and
+    /// rewrite the index
+    Compact {
+        /// Index file to compact
+        #[clap(short, long)]
+        index: PathBuf,
+    },
+    /// Re-embed every document of an index with another model, keeping ids
+    /// and metadata
+    Migrate {
+        /// Index file to migrate
+        #[clap(short, long)]
+        index: PathBuf,
+
+        /// Embedding model to migrate to
+        #[clap(short, long)]
+        model: EmbeddingModel,
+
+        /// Index file to write (default: replace the index)
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Which files of a directory are indexed. Files ignored by git and hidden
+/// files are always skipped.
+#[derive(clap::Args, Debug)]
+pub struct WalkArgs {
+    /// Only index files matching this glob, relative to the directory, e.g.
+    /// 'src/**' (repeatable)
+    #[clap(long, value_name = "GLOB")]
+    include: Vec<String>,
+
+    /// Skip files matching this glob, relative to the directory (repeatable)
+    #[clap(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Only index files of this language, e.g. rust, python or markdown
+    /// (repeatable)
+    #[clap(long = "language", value_name = "LANGUAGE")]
+    languages: Vec<String>,
+
+    /// Skip files larger than this many bytes
+    #[clap(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_FILE_SIZE)]
+    max_file_size: u64,
+
+    /// Record the commit of the git repository in the metadata of every chunk
+    #[clap(long)]
+    commit: bool,
+}
+
+impl WalkArgs {
+    fn walker(&self, path: &Path) -> std::io::Result<RepoWalker> {
+        let mut walker = RepoWalker::new(std::path::absolute(path)?)
+            .languages(&self.languages)
+            .max_file_size(self.max_file_size)
+            .annotate_commit(self.commit);
+        for glob in &self.include {
+            walker = walker.include(glob);
+        }
+        for glob in &self.exclude {
+            walker = walker.exclude(glob);
+        }
+        Ok(walker)
+    }
+}
+
+impl IndexCommand {
+    pub async fn run(&self, client: &VoyageAiClient, output: &Output) -> CliResult {
+        if output.format == OutputFormat::Csv {
+            return Err(output.unsupported("index"));
+        }
+        match self {
+            IndexCommand::Build {
+                path,
+                index,
+                model,
+                walk,
+            } => {
+                let model = model.unwrap_or(client.voyage_config().embedding_model);
+                let client = client_for_model(client, model);
+                let mut pipeline = RetrievalPipeline::builder(&client)
+                    .index(VectorIndex::new().with_model(model.to_string()))
+                    .without_rerank()
+                    .build();
+                let (files, chunks) =
+                    add_files(&mut pipeline, std::slice::from_ref(path), walk, output).await?;
+                pipeline.index().save(index)?;
+                report_added(output, files, chunks, index)
+            }
+
+            IndexCommand::Add { index, paths, walk } => {
+                let loaded = VectorIndex::load(index)?;
+                // Embed with the model the index was built with
+                let client = client_for_index(client, &loaded)?;
+                let mut pipeline = RetrievalPipeline::builder(&client)
+                    .index(loaded)
+                    .without_rerank()
+                    .build();
+                let (files, chunks) = add_files(&mut pipeline, paths, walk, output).await?;
+                pipeline.index().save(index)?;
+                report_added(output, files, chunks, index)
+            }
+
+            IndexCommand::Update {
+                index,
+                range,
+                path,
+                walk,
+            } => {
+                let loaded = VectorIndex::load(index)?;
+                let client = client_for_index(client, &loaded)?;
+                let pipeline = RetrievalPipeline::builder(&client)
+                    .index(loaded)
+                    .without_rerank()
+                    .build();
+                let mut indexer = CodeIndexer::new(pipeline, walk.walker(path)?);
+                let update = indexer.update_from_git(range).await?;
+                report_skipped(output, &update.skipped);
+                indexer.pipeline().index().save(index)?;
+                match output.format {
+                    OutputFormat::Json => output.json(&json!({
+                        "index": index,
+                        "updated": update.updated,
+                        "removed": update.removed,
+                        "chunks": update.chunks,
+                        "embedded": update.embedded,
+                        "commit": update.commit,
+                    })),
+                    _ => {
+                        println!(
+                            "Updated {} files and removed {}; embedded {} of {} chunks",
+                            update.updated.len(),
+                            update.removed.len(),
+                            update.embedded,
+                            update.chunks
+                        );
+                        Ok(())
+                    }
+                }
+            }
+
+            IndexCommand::Rm { index, ids } => {
+                let mut pipeline = RetrievalPipeline::builder(client)
+                    .index(VectorIndex::load(index)?)
+                    .without_rerank()
+                    .build();
+                let mut removed = 0;
+                for id in ids {
+                    let mut targets = vec![id.clone()];
+                    if !pipeline.document_ids().any(|document| document == id) {
+                        // A path: the file itself or the files below a directory
+                        let path = document_id(Path::new(id))?;
+                        let prefix = format!("{}{}", path, std::path::MAIN_SEPARATOR);
+                        targets = pipeline
+                            .document_ids()
+                            .filter(|document| *document == path || document.starts_with(&prefix))
+                            .map(str::to_string)
+                            .collect();
+                    }
+                    if targets.is_empty() {
+                        output.progress(format!("No document {} in the index", id));
+                    }
+                    for target in targets {
+                        pipeline.remove_document(&target);
+                        removed += 1;
+                    }
+                }
+                pipeline.index().save(index)?;
+                match output.format {
+                    OutputFormat::Json => {
+                        output.json(&json!({ "index": index, "removed_documents": removed }))
+                    }
+                    _ => {
+                        println!("Removed {} documents from {}", removed, index.display());
+                        Ok(())
+                    }
+                }
+            }
+
+            IndexCommand::Stats { index: path } => {
+                let disk_size = std::fs::metadata(path)?.len();
+                let info = VectorIndex::inspect(path)?;
+                let index = VectorIndex::load(path)?;
+                let pipeline = RetrievalPipeline::builder(client)
+                    .index(index)
+                    .without_rerank()
+                    .build();
+                let index = pipeline.index();
+                match output.format {
+                    OutputFormat::Json => output.json(&json!({
+                        "index": path,
+                        "documents": pipeline.len(),
+                        "chunks": index.len(),
+                        "tombstoned": index.tombstoned(),
+                        "dimension": index.dimension(),
+                        "model": index.model(),
+                        "disk_size": disk_size,
+                        "format_version": info.format_version,
+                        "written_by": info.crate_version,
+                    })),
+                    _ => {
+                        println!("index      {}", path.display());
+                        println!("documents  {}", pipeline.len());
+                        println!("chunks     {}", index.len());
+                        println!("tombstoned {}", index.tombstoned());
+                        println!("dimension  {}", index.dimension());
+                        println!("model      {}", index.model().unwrap_or("unknown"));
+                        println!("disk size  {} ({} bytes)", human_size(disk_size), disk_size);
+                        println!(
+                            "format     {}{}",
+                            info.format_version,
+                            if info.is_current() {
+                                ""
+                            } else {
+                                " (upgraded on the next write)"
+                            }
+                        );
+                        println!(
+                            "written by voyageai {}",
+                            info.crate_version.as_deref().unwrap_or("unknown")
+                        );
+                        Ok(())
+                    }
+                }
+            }
+
+            IndexCommand::Compact { index } => {
+                let before = std::fs::metadata(index)?.len();
+                let mut loaded = VectorIndex::load(index)?;
+                let tombstones = loaded.compact();
+                let mut pipeline = RetrievalPipeline::builder(client)
+                    .index(loaded)
+                    .without_rerank()
+                    .build();
+                // Only ids that are absolute paths, as written by `index build`,
+                // refer to files
+                let deleted: Vec<String> = pipeline
+                    .document_ids()
+                    .filter(|id| Path::new(id).is_absolute() && !Path::new(id).exists())
+                    .map(str::to_string)
+                    .collect();
+                for id in &deleted {
+                    pipeline.remove_document(id);
+                }
+                pipeline.index().save(index)?;
+                let after = std::fs::metadata(index)?.len();
+                match output.format {
+                    OutputFormat::Json => output.json(&json!({
+                        "index": index,
+                        "removed_documents": deleted.len(),
+                        "removed_tombstones": tombstones,
+                        "disk_size_before": before,
+                        "disk_size_after": after,
+                    })),
+                    _ => {
+                        println!(
+                            "Removed {} documents of deleted files and {} deleted chunks; {} -> {}",
+                            deleted.len(),
+                            tombstones,
+                            human_size(before),
+                            human_size(after)
+                        );
+                        Ok(())
+                    }
+                }
+            }
+
+            IndexCommand::Migrate {
+                index,
+                model,
+                output: target,
+            } => {
+                let loaded = VectorIndex::load(index)?;
+                let from = loaded.model().unwrap_or("unknown").to_string();
+                output.progress(format!(
+                    "Embedding {} chunks with {} (was {})...",
+                    loaded.len(),
+                    model,
+                    from
+                ));
+                let progress = *output;
+                let embedder = BulkEmbedder::new(client).with_progress_callback(
+                    move |status: &BulkProgress| {
+                        progress.progress(format!(
+                            "{}/{} chunks",
+                            status.done + status.failed,
+                            status.total.unwrap_or_default()
+                        ))
+                    },
+                );
+                let migrated = loaded.migrate_with(*model, embedder).await?;
+                let target = target.as_ref().unwrap_or(index);
+                migrated.save(target)?;
+                match output.format {
+                    OutputFormat::Json => output.json(&json!({
+                        "index": target,
+                        "chunks": migrated.len(),
+                        "from_model": loaded.model(),
+                        "model": migrated.model(),
+                        "dimension": migrated.dimension(),
+                    })),
+                    _ => {
+                        println!(
+                            "Migrated {} chunks from {} to {} ({} dimensions) into {}",
+                            migrated.len(),
+                            from,
+                            model,
+                            migrated.dimension(),
+                            target.display()
+                        );
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn report_added(output: &Output, files: usize, chunks: usize, index: &Path) -> CliResult {
+    match output.format {
+        OutputFormat::Json => {
+            output.json(&json!({ "index": index, "files": files, "chunks": chunks }))
+        }
+        _ => {
+            println!(
+                "Indexed {} chunks of {} files into {}",
+                chunks,
+                files,
+                index.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Id of the document of the file at `path`: its absolute path, so that the
+/// index can be updated from any directory.
+fn document_id(path: &Path) -> std::io::Result<String> {
+    Ok(std::path::absolute(path)?.to_string_lossy().into_owned())
+}
+
+/// Adds the files at `paths` picked by `walk` to `pipeline`, returning the
+/// number of files and chunks added.
+async fn add_files(
+    pipeline: &mut RetrievalPipeline,
+    paths: &[PathBuf],
+    walk: &WalkArgs,
+    output: &Output,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let mut found = Vec::new();
+    for path in paths {
+        found.push(walk.walker(path)?.walk()?);
+    }
+    let count: usize = found.iter().map(|files| files.files.len()).sum();
+    output.progress(format!("Embedding {} files...", count));
+    let (mut files, mut chunks) = (0, 0);
+    for found in found {
+        let report = pipeline.add_repository(found).await?;
+        report_skipped(output, &report.skipped);
+        files += report.files;
+        chunks += report.chunks;
+    }
+    Ok((files, chunks))
+}
+
+fn report_skipped(output: &Output, skipped: &[(PathBuf, SkipReason)]) {
+    for (file, reason) in skipped {
+        let reason = match reason {
+            SkipReason::Binary => "binary file".to_string(),
+            SkipReason::TooLarge { size } => format!("{} bytes", size),
+            SkipReason::Unreadable(e) => e.clone(),
+        };
+        output.progress(format!("Skipping {}: {}", file.display(), reason));
+    }
+}
+
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -0,0 +1,42 @@
+use std::io::Write;
+
+use clap::ValueEnum;
+use voyageai::logging::redact;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+/// Initializes the logger; messages are always passed through `redact`.
+/// `quiet` lowers the default level from warn to error.
+pub fn init_logging(format: LogFormat, quiet: bool) {
+    let level = if quiet { "error" } else { "warn" };
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(level));
+    match format {
+        LogFormat::Text => builder.format(|buf, record| {
+            let message = record.args().to_string();
+            writeln!(
+                buf,
+                "[{} {} {}] {}",
+                buf.timestamp(),
+                record.level(),
+                record.target(),
+                redact(&message)
+            )
+        }),
+        LogFormat::Json => builder.format(|buf, record| {
+            let message = record.args().to_string();
+            let line = serde_json::json!({
+                "timestamp": buf.timestamp().to_string(),
+                "level": record.level().as_str(),
+                "target": record.target(),
+                "message": redact(&message),
+            });
+            writeln!(buf, "{}", line)
+        }),
+    };
+    builder.init();
+}
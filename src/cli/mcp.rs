@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+use voyageai::{mcp::McpServer, VectorIndex, VoyageAiClient};
+
+use super::{client_for_index, CliResult, Output};
+
+#[derive(clap::Args, Debug)]
+pub struct McpArgs {
+    /// Index file for the semantic_search tool, written by `index build` or `VectorIndex::save`
+    #[clap(short, long)]
+    index: Option<PathBuf>,
+}
+
+impl McpArgs {
+    /// stdout carries the protocol, so `--format` does not apply and
+    /// messages go to stderr.
+    pub async fn run(&self, client: &VoyageAiClient, output: &Output) -> CliResult {
+        let index = self.index.as_ref().map(VectorIndex::load).transpose()?;
+        // Embed queries with the model the index was built with
+        let client = match &index {
+            Some(index) => client_for_index(client, index)?,
+            None => client.clone(),
+        };
+        let mut server = McpServer::new(&client);
+        if let Some(index) = index {
+            output.progress(format!("Loaded {} chunks", index.len()));
+            server = server.with_index(index);
+        }
+        let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+        server.run(stdin, tokio::io::stdout()).await?;
+        Ok(())
+    }
+}
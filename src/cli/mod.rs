@@ -0,0 +1,172 @@
+//! Command line parsing and the commands of the `voyageai` binary.
+//!
+//! Every command is an `Args` struct in its own module with a `run` method
+//! taking the client and the [`Output`] settings of the global flags.
+
+#[cfg(feature = "quickstart")]
+mod ask;
+mod benchmark;
+mod embed;
+mod index;
+mod logging;
+#[cfg(feature = "mcp")]
+mod mcp;
+mod output;
+mod repl;
+mod rerank;
+#[cfg(feature = "serve")]
+mod serve;
+
+use clap::{Parser, Subcommand};
+use voyageai::{
+    config::{ApiKeyProvider, DefaultApiKeyProvider},
+    EmbeddingModel, VectorIndex, VoyageAiClient, VoyageConfig,
+};
+
+use logging::{init_logging, LogFormat};
+use output::{Output, OutputFormat};
+
+/// Result of a command.
+type CliResult = Result<(), Box<dyn std::error::Error>>;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Commands,
+
+    #[clap(flatten)]
+    global: GlobalArgs,
+}
+
+/// Flags accepted before or after any command.
+#[derive(clap::Args, Debug)]
+struct GlobalArgs {
+    /// API key to use instead of VOYAGE_API_KEY, voyage.toml or
+    /// ~/.config/voyageai/api_key. Visible to other users of the machine
+    /// while the command runs, so prefer those for shared machines.
+    #[clap(long, global = true)]
+    api_key: Option<String>,
+
+    /// Send requests to this URL instead of the Voyage AI API (default from
+    /// VOYAGE_BASE_URL or voyage.toml)
+    #[clap(long, global = true)]
+    base_url: Option<String>,
+
+    /// Format of the results printed on stdout
+    #[clap(short, long, global = true, value_enum, default_value = "text")]
+    format: OutputFormat,
+
+    /// Print no progress messages and only errors in the log
+    #[clap(long, global = true)]
+    quiet: bool,
+
+    /// Format of log output on stderr (level set with RUST_LOG, default warn)
+    #[clap(long, global = true, value_enum, default_value = "text")]
+    log_format: LogFormat,
+
+    /// Include (redacted, truncated) request and response bodies in logs
+    #[clap(long, global = true)]
+    log_payloads: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Generate embeddings for text
+    Embed(embed::EmbedArgs),
+    /// Rerank documents based on a query
+    Rerank(rerank::RerankArgs),
+    /// Answer a question from the files in a directory
+    #[cfg(feature = "quickstart")]
+    Ask(ask::AskArgs),
+    /// Build and maintain a local index file
+    Index {
+        #[clap(subcommand)]
+        command: index::IndexCommand,
+    },
+    /// Query a saved index interactively
+    Repl(repl::ReplArgs),
+    /// Serve search, rerank and embed tools to agents over MCP on stdin and stdout
+    #[cfg(feature = "mcp")]
+    Mcp(mcp::McpArgs),
+    /// Serve embed, rerank and search over HTTP
+    #[cfg(feature = "serve")]
+    Serve(serve::ServeArgs),
+    /// Compare embedding models on a retrieval dataset
+    Benchmark(benchmark::BenchmarkArgs),
+}
+
+impl Commands {
+    /// Whether the command needs an API key at startup; `serve` can use the
+    /// keys its callers pass instead.
+    fn requires_api_key(&self) -> bool {
+        match self {
+            #[cfg(feature = "serve")]
+            Commands::Serve(_) => false,
+            _ => true,
+        }
+    }
+
+    async fn run(&self, client: &VoyageAiClient, output: &Output) -> CliResult {
+        match self {
+            Commands::Embed(args) => args.run(client, output).await,
+            Commands::Rerank(args) => args.run(client, output).await,
+            #[cfg(feature = "quickstart")]
+            Commands::Ask(args) => args.run(client, output).await,
+            Commands::Index { command } => command.run(client, output).await,
+            Commands::Repl(args) => args.run(client, output).await,
+            #[cfg(feature = "mcp")]
+            Commands::Mcp(args) => args.run(client, output).await,
+            #[cfg(feature = "serve")]
+            Commands::Serve(args) => args.run(client, output).await,
+            Commands::Benchmark(args) => args.run(client, output).await,
+        }
+    }
+}
+
+/// Parses the command line and runs the command.
+pub async fn run() -> CliResult {
+    let cli = Cli::parse();
+    let global = &cli.global;
+    init_logging(global.log_format, global.quiet);
+
+    // Settings from voyage.toml and VOYAGE_* variables, overridden by flags
+    let mut config = VoyageConfig::from_env()?;
+    if let Some(api_key) = &global.api_key {
+        config = config.with_api_key(api_key.as_str());
+    }
+    if config.api_key.is_empty() {
+        match DefaultApiKeyProvider.api_key().await {
+            Ok(api_key) => config = config.with_api_key(api_key),
+            // Fail early without VOYAGE_API_KEY or ~/.config/voyageai/api_key
+            Err(e) if cli.command.requires_api_key() => return Err(e.into()),
+            Err(_) => {}
+        }
+    }
+    if global.log_payloads {
+        config = config.log_payloads(true);
+    }
+    if let Some(base_url) = &global.base_url {
+        config = config.with_base_url(base_url.as_str());
+    }
+    let client = VoyageAiClient::new_with_config(config);
+
+    let output = Output::new(global.format, global.quiet);
+    cli.command.run(&client, &output).await
+}
+
+/// A client like `client` embedding with `model`.
+fn client_for_model(client: &VoyageAiClient, model: EmbeddingModel) -> VoyageAiClient {
+    VoyageAiClient::new_with_config(client.voyage_config().clone().with_embedding_model(model))
+}
+
+/// A client like `client` embedding with the model `index` was built with.
+fn client_for_index(
+    client: &VoyageAiClient,
+    index: &VectorIndex,
+) -> Result<VoyageAiClient, String> {
+    match index.model() {
+        Some(model) => Ok(client_for_model(client, model.parse()?)),
+        None => Ok(client.clone()),
+    }
+}
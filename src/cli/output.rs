@@ -0,0 +1,68 @@
+use std::fmt;
+
+use clap::ValueEnum;
+use serde::Serialize;
+use voyageai::io::CsvWriter;
+
+use super::CliResult;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text; tables for `benchmark`
+    #[value(alias = "table")]
+    Text,
+    Csv,
+    Json,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Text => f.write_str("text"),
+            OutputFormat::Csv => f.write_str("csv"),
+            OutputFormat::Json => f.write_str("json"),
+        }
+    }
+}
+
+/// Where and how commands report: results go to stdout in `format`,
+/// progress messages to stderr unless `quiet`.
+#[derive(Debug, Clone, Copy)]
+pub struct Output {
+    pub format: OutputFormat,
+    quiet: bool,
+}
+
+impl Output {
+    pub fn new(format: OutputFormat, quiet: bool) -> Self {
+        Self { format, quiet }
+    }
+
+    /// Prints a progress message on stderr, unless quiet.
+    pub fn progress(&self, message: impl fmt::Display) {
+        if !self.quiet {
+            eprintln!("{message}");
+        }
+    }
+
+    /// Prints `value` on stdout as pretty-printed JSON.
+    pub fn json(&self, value: &impl Serialize) -> CliResult {
+        println!("{}", serde_json::to_string_pretty(value)?);
+        Ok(())
+    }
+
+    /// Prints `rows` on stdout as CSV with a header row.
+    pub fn csv(&self, header: &[&str], rows: impl IntoIterator<Item = Vec<String>>) -> CliResult {
+        let mut csv = CsvWriter::new(std::io::stdout(), header)?;
+        for row in rows {
+            csv.write_row(&row)?;
+        }
+        csv.finish()?;
+        Ok(())
+    }
+
+    /// The error of `command` for a format it does not print.
+    pub fn unsupported(&self, command: &str) -> Box<dyn std::error::Error> {
+        format!("{command} does not support --format {}", self.format).into()
+    }
+}
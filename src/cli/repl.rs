@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use voyageai::{
+    repl::{Repl, ReplSettings},
+    VectorIndex, VoyageAiClient,
+};
+
+use super::{CliResult, Output};
+
+#[derive(clap::Args, Debug)]
+pub struct ReplArgs {
+    /// Index file written by `VectorIndex::save`
+    #[clap(short, long)]
+    index: PathBuf,
+
+    /// Number of hits to show, changed with `:set top_k`
+    #[clap(short, long, default_value = "5")]
+    top_k: usize,
+
+    /// Rerank hits with the rerank API, toggled with `:set rerank`
+    #[clap(long)]
+    rerank: bool,
+
+    /// File to keep query history in across sessions
+    #[clap(long)]
+    history: Option<PathBuf>,
+}
+
+impl ReplArgs {
+    /// The session is interactive, so `--format` does not apply.
+    pub async fn run(&self, client: &VoyageAiClient, _output: &Output) -> CliResult {
+        let settings = ReplSettings {
+            top_k: self.top_k,
+            rerank: self.rerank,
+            ..ReplSettings::default()
+        };
+        let mut repl = Repl::new(client, VectorIndex::load(&self.index)?).with_settings(settings);
+        if let Some(history) = &self.history {
+            repl = repl.with_history_file(history)?;
+        }
+        repl.run(std::io::stdin().lock(), std::io::stdout()).await?;
+        Ok(())
+    }
+}
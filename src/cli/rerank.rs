@@ -0,0 +1,66 @@
+use futures::StreamExt;
+use serde_json::json;
+use voyageai::VoyageAiClient;
+
+use super::{CliResult, Output, OutputFormat};
+
+#[derive(clap::Args, Debug)]
+pub struct RerankArgs {
+    /// Query to use for reranking
+    #[clap(short, long)]
+    query: String,
+
+    /// Documents to rerank
+    #[clap(short, long)]
+    documents: Vec<String>,
+
+    /// Number of top results to return
+    #[clap(short, long)]
+    top_k: Option<usize>,
+}
+
+impl RerankArgs {
+    pub async fn run(&self, client: &VoyageAiClient, output: &Output) -> CliResult {
+        let mut similar = client.find_similar_documents(&self.query, self.documents.clone());
+        let mut ranked = Vec::new();
+        while let Some(document) = similar.next().await {
+            ranked.push(document);
+            if self.top_k.is_some_and(|k| ranked.len() >= k) {
+                break;
+            }
+        }
+
+        match output.format {
+            OutputFormat::Text => {
+                println!("\nReranking documents by relevance to: {}", self.query);
+                println!("\nReranked documents by relevance:");
+                for document in &ranked {
+                    println!("Score {:.4}: {}", document.similarity, document.document);
+                }
+                Ok(())
+            }
+            OutputFormat::Csv => output.csv(
+                &["rank", "score", "document"],
+                ranked.into_iter().enumerate().map(|(rank, document)| {
+                    vec![
+                        (rank + 1).to_string(),
+                        format!("{:.4}", document.similarity),
+                        document.document,
+                    ]
+                }),
+            ),
+            OutputFormat::Json => output.json(&json!({
+                "query": self.query,
+                "results": ranked
+                    .iter()
+                    .enumerate()
+                    .map(|(rank, document)| json!({
+                        "rank": rank + 1,
+                        "score": document.similarity,
+                        "document": document.document,
+                    }))
+                    .collect::<Vec<_>>(),
+            })),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+use voyageai::{server::Server, VectorIndex, VoyageAiClient};
+
+use super::{client_for_index, CliResult, Output};
+
+#[derive(clap::Args, Debug)]
+pub struct ServeArgs {
+    /// Port to listen on
+    #[clap(short, long, default_value = "8080")]
+    port: u16,
+
+    /// Address to listen on
+    #[clap(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// Index file for /search, written by `index build` or `VectorIndex::save`
+    #[clap(short, long)]
+    index: Option<PathBuf>,
+
+    /// Requests handled at once; others wait
+    #[clap(long, default_value = "16")]
+    max_concurrency: usize,
+}
+
+impl ServeArgs {
+    /// Responses are always JSON, so `--format` does not apply.
+    pub async fn run(&self, client: &VoyageAiClient, output: &Output) -> CliResult {
+        let index = self.index.as_ref().map(VectorIndex::load).transpose()?;
+        // Embed queries with the model the index was built with
+        let client = match &index {
+            Some(index) => client_for_index(client, index)?,
+            None => client.clone(),
+        };
+        let mut server = Server::new(&client).with_max_concurrency(self.max_concurrency);
+        if let Some(index) = index {
+            output.progress(format!("Loaded {} chunks", index.len()));
+            server = server.with_index(index);
+        }
+        let listener = tokio::net::TcpListener::bind((self.host.as_str(), self.port)).await?;
+        output.progress(format!("Listening on http://{}", listener.local_addr()?));
+        server.serve(listener).await?;
+        Ok(())
+    }
+}
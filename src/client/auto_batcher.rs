@@ -0,0 +1,353 @@
+//! Micro-batching of single-text embedding requests.
+//!
+//! [`AutoBatcher`] collects the texts of concurrent [`Embedder::embed`] calls
+//! for up to a short delay, or until a batch is full, and sends them as one
+//! embeddings request. Each caller's future resolves to its own vector. This
+//! suits services that embed one text per incoming request: a hundred
+//! concurrent calls cost a single API call instead of a hundred.
+
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use futures::stream::{FuturesOrdered, FuturesUnordered};
+use log::{debug, warn};
+use tokio::sync::{mpsc, oneshot, Semaphore};
+use tokio::time::{timeout_at, Instant};
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
+
+use crate::builder::validation::MAX_EMBEDDING_INPUTS;
+use crate::client::embeddings_client::Client as EmbeddingsClient;
+use crate::client::lifecycle::{Lifecycle, WorkGuard};
+use crate::client::tasks::{spawn_producer, AbortOnDrop};
+use crate::client::voyage_client::VoyageAiClient;
+use crate::errors::VoyageError;
+use crate::models::embeddings::{
+    EmbedOptions, EmbeddingModel, EmbeddingsInput, EmbeddingsRequest, IndexedEmbedding, InputType,
+};
+use crate::models::tokens::estimate_tokens;
+use crate::traits::llm::{BatchEmbedding, Embedder, TextEmbedding, TextEmbeddingStream};
+
+/// How long the first text of a batch waits for others by default.
+pub const DEFAULT_MAX_DELAY: Duration = Duration::from_millis(10);
+
+/// A text waiting to be sent and the caller waiting for its embedding.
+struct Pending {
+    text: String,
+    reply: oneshot::Sender<Result<Vec<f32>, VoyageError>>,
+    /// Keeps a shutdown waiting until the text is embedded
+    _work: WorkGuard,
+}
+
+/// Aggregates individual embedding requests into batch requests.
+///
+/// Configure the batcher before its first use; clones share the same queue.
+/// The background task sending batches stops once every clone is dropped and
+/// the queued texts are sent. When the client
+/// [shuts down](crate::VoyageAiClient::shutdown), queued texts are sent at
+/// once and new ones are refused.
+#[derive(Clone)]
+pub struct AutoBatcher {
+    client: EmbeddingsClient,
+    model: EmbeddingModel,
+    input_type: Option<InputType>,
+    max_batch_size: usize,
+    max_delay: Duration,
+    concurrency: usize,
+    queue: Arc<OnceLock<mpsc::UnboundedSender<Pending>>>,
+}
+
+impl std::fmt::Debug for AutoBatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AutoBatcher")
+            .field("model", &self.model)
+            .field("input_type", &self.input_type)
+            .field("max_batch_size", &self.max_batch_size)
+            .field("max_delay", &self.max_delay)
+            .field("concurrency", &self.concurrency)
+            .field("started", &self.queue.get().is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl AutoBatcher {
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self::from_embeddings_client(client.embeddings_client().as_ref().clone())
+    }
+
+    pub fn from_embeddings_client(client: EmbeddingsClient) -> Self {
+        Self {
+            model: client.config().embedding_model,
+            client,
+            input_type: None,
+            max_batch_size: MAX_EMBEDDING_INPUTS,
+            max_delay: DEFAULT_MAX_DELAY,
+            concurrency: 4,
+            queue: Arc::default(),
+        }
+    }
+
+    pub fn with_model(mut self, model: EmbeddingModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn with_input_type(mut self, input_type: Option<InputType>) -> Self {
+        self.input_type = input_type;
+        self
+    }
+
+    /// Sends a batch once it holds `max_batch_size` texts, at most
+    /// [`MAX_EMBEDDING_INPUTS`].
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size.clamp(1, MAX_EMBEDDING_INPUTS);
+        self
+    }
+
+    /// Sends a batch at the latest `max_delay` after its first text arrived.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Number of batch requests in flight at once.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Queues `text`, starting the background task on first use.
+    fn submit(&self, text: String) -> oneshot::Receiver<Result<Vec<f32>, VoyageError>> {
+        let (reply, receiver) = oneshot::channel();
+        let work = match self.client.config().lifecycle.begin() {
+            Ok(work) => work,
+            Err(e) => {
+                let _ = reply.send(Err(e));
+                return receiver;
+            }
+        };
+        let queue = self.queue.get_or_init(|| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            tokio::spawn(self.clone_settings().run(rx));
+            tx
+        });
+        // The task only stops after every sender is gone
+        let _ = queue.send(Pending {
+            text,
+            reply,
+            _work: work,
+        });
+        receiver
+    }
+
+    /// Whether texts embedded with `options` can join the batches, which
+    /// are sent with the batcher's own settings.
+    fn batches(&self, options: &EmbedOptions) -> bool {
+        options
+            .input_type
+            .is_none_or(|input_type| Some(input_type) == self.input_type)
+            && options.truncation.is_none()
+            && options.encoding_format.is_none()
+    }
+
+    /// Embeds `texts` in requests of their own, for options the batches do
+    /// not share.
+    fn embed_unbatched(&self, texts: Vec<String>, options: EmbedOptions) -> BatchEmbedding {
+        let options = EmbedOptions {
+            input_type: options.input_type.or(self.input_type),
+            ..options
+        };
+        let client = self.client.clone();
+        let model = self.model;
+        let (tx, rx) = oneshot::channel();
+        let task = AbortOnDrop::spawn(async move {
+            let result = async {
+                let mut embeddings = Vec::with_capacity(texts.len());
+                for batch in texts.chunks(MAX_EMBEDDING_INPUTS) {
+                    let request = options.request(EmbeddingsInput::Multiple(batch.to_vec()), model);
+                    let response = client.create_embedding(&request).await?;
+                    embeddings.extend(response.into_embeddings(batch.len())?);
+                }
+                Ok(embeddings)
+            }
+            .await;
+            let _ = tx.send(result);
+        });
+        BatchEmbedding::new(rx, task)
+    }
+
+    /// The settings without the queue, so the background task does not keep
+    /// itself alive.
+    fn clone_settings(&self) -> BatchLoop {
+        BatchLoop {
+            client: self.client.clone(),
+            model: self.model,
+            input_type: self.input_type,
+            max_batch_size: self.max_batch_size,
+            max_delay: self.max_delay,
+            semaphore: Arc::new(Semaphore::new(self.concurrency)),
+            lifecycle: self.client.config().lifecycle.clone(),
+        }
+    }
+}
+
+/// The background task collecting and sending batches.
+struct BatchLoop {
+    client: EmbeddingsClient,
+    model: EmbeddingModel,
+    input_type: Option<InputType>,
+    max_batch_size: usize,
+    max_delay: Duration,
+    semaphore: Arc<Semaphore>,
+    lifecycle: Lifecycle,
+}
+
+impl BatchLoop {
+    async fn run(self, mut queue: mpsc::UnboundedReceiver<Pending>) {
+        let max_tokens = self.model.max_tokens_per_request();
+        let mut carry = None;
+        loop {
+            let Some(first) = carry.take().or(queue.recv().await) else {
+                break;
+            };
+            let deadline = Instant::now() + self.max_delay;
+            let mut tokens = estimate_tokens(&first.text);
+            let mut batch = vec![first];
+            while batch.len() < self.max_batch_size {
+                let next = tokio::select! {
+                    next = timeout_at(deadline, queue.recv()) => next,
+                    // Flush without waiting for the delay when shutting down
+                    _ = self.lifecycle.closed() => timeout_at(Instant::now(), queue.recv()).await,
+                };
+                let Ok(Some(pending)) = next else {
+                    break;
+                };
+                let pending_tokens = estimate_tokens(&pending.text);
+                if tokens + pending_tokens > max_tokens {
+                    carry = Some(pending);
+                    break;
+                }
+                tokens += pending_tokens;
+                batch.push(pending);
+            }
+
+            // Skip callers that stopped waiting
+            batch.retain(|pending| !pending.reply.is_closed());
+            if batch.is_empty() {
+                continue;
+            }
+            let Ok(permit) = self.semaphore.clone().acquire_owned().await else {
+                break;
+            };
+            debug!("Sending a batch of {} texts", batch.len());
+            let request = EmbeddingsRequest {
+                input: EmbeddingsInput::Multiple(
+                    batch.iter().map(|pending| pending.text.clone()).collect(),
+                ),
+                model: self.model,
+                input_type: self.input_type,
+                truncation: None,
+                encoding_format: None,
+            };
+            let client = self.client.clone();
+            let work = self.lifecycle.hold();
+            tokio::spawn(work.scope(async move {
+                let _permit = permit;
+                let result = client
+                    .create_embedding(&request)
+                    .await
+                    .and_then(|response| response.into_embeddings(batch.len()));
+                match result {
+                    Ok(embeddings) => {
+                        for (pending, embedding) in batch.into_iter().zip(embeddings) {
+                            let _ = pending.reply.send(Ok(embedding));
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Batch of {} texts failed: {}", batch.len(), e);
+                        for pending in batch {
+                            let _ = pending.reply.send(Err(e.duplicate()));
+                        }
+                    }
+                }
+            }));
+        }
+    }
+}
+
+impl Embedder for AutoBatcher {
+    fn embed(&self, text: &str) -> TextEmbedding {
+        TextEmbedding::from_receiver(self.submit(text.to_string()))
+    }
+
+    fn embed_batch(&self, texts: &[String]) -> BatchEmbedding {
+        let receivers: FuturesOrdered<_> = texts
+            .iter()
+            .map(|text| TextEmbedding::from_receiver(self.submit(text.clone())))
+            .collect();
+        let (tx, rx) = oneshot::channel();
+        let task = AbortOnDrop::spawn(async move {
+            let result = receivers.collect::<Vec<_>>().await.into_iter().collect();
+            let _ = tx.send(result);
+        });
+        BatchEmbedding::new(rx, task)
+    }
+
+    /// Joins the batches when `options` match the batcher's settings, and is
+    /// sent on its own otherwise.
+    fn embed_with(&self, text: &str, options: EmbedOptions) -> TextEmbedding {
+        if self.batches(&options) {
+            return self.embed(text);
+        }
+        let batch = self.embed_unbatched(vec![text.to_string()], options);
+        let (tx, rx) = oneshot::channel();
+        let task = AbortOnDrop::spawn(async move {
+            let result = batch
+                .await
+                .and_then(|mut embeddings| embeddings.pop().ok_or(VoyageError::EmptyResponse));
+            let _ = tx.send(result);
+        });
+        TextEmbedding::new(rx, task)
+    }
+
+    /// Joins the batches when `options` match the batcher's settings, and is
+    /// sent on its own otherwise.
+    fn embed_batch_with(&self, texts: &[String], options: EmbedOptions) -> BatchEmbedding {
+        if self.batches(&options) {
+            return self.embed_batch(texts);
+        }
+        self.embed_unbatched(texts.to_vec(), options)
+    }
+
+    fn embed_stream(&self, texts: Vec<String>) -> TextEmbeddingStream {
+        let mut receivers: FuturesUnordered<_> = texts
+            .into_iter()
+            .enumerate()
+            .map(|(index, text)| {
+                let embedding = TextEmbedding::from_receiver(self.submit(text));
+                async move { (index, embedding.await) }
+            })
+            .collect();
+        let (tx, rx) = mpsc::channel(MAX_EMBEDDING_INPUTS);
+        let producer = tx.clone();
+        spawn_producer(tx, async move {
+            while let Some((index, result)) = receivers.next().await {
+                match result {
+                    Ok(embedding) => {
+                        if producer
+                            .send(IndexedEmbedding { index, embedding })
+                            .await
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Error in embedding stream: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+        ReceiverStream::new(rx)
+    }
+}
@@ -0,0 +1,262 @@
+//! Client for the batch API: large embedding jobs submitted as a file and
+//! processed asynchronously, at a lower price than the embeddings endpoint.
+//!
+//! ```no_run
+//! # async fn example(documents: Vec<(String, String)>) -> Result<(), voyageai::VoyageError> {
+//! use voyageai::{client::BatchClient, InputType, VoyageAiClient};
+//!
+//! let client = VoyageAiClient::new();
+//! let batches = BatchClient::new(&client);
+//! let job = batches
+//!     .submit_embeddings(documents, Some(InputType::Document))
+//!     .await?;
+//! let job = batches.await_completion(&job.id).await?;
+//! for result in batches.embeddings_results(&job).await? {
+//!     println!("{}: {} dimensions", result.custom_id, result.embedding.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{debug, info, warn};
+
+use crate::client::retry::retry_after;
+use crate::client::voyage_client::VoyageAiClient;
+use crate::config::VoyageConfig;
+use crate::errors::VoyageError;
+use crate::models::batch::{
+    embeddings_batch_jsonl, parse_embeddings_output, BatchEmbedding, BatchFile, BatchJob,
+    BatchStatus, CreateBatchRequest, EmbeddingsBatchParams,
+};
+use crate::models::embeddings::InputType;
+use crate::models::request_id::{RequestId, IDEMPOTENCY_KEY_HEADER};
+use crate::models::response_headers::ResponseMetadata;
+use crate::transport::{HttpRequest, Method, Transport};
+
+/// Default interval between status checks of
+/// [`BatchClient::await_completion`].
+pub const DEFAULT_BATCH_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Client for batch jobs, see the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct BatchClient {
+    transport: Arc<dyn Transport>,
+    config: VoyageConfig,
+    poll_interval: Duration,
+}
+
+impl BatchClient {
+    /// A batch client with the configuration of `client`.
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self::new_with_config(client.config.embeddings_client.config().clone())
+    }
+
+    pub fn new_with_config(config: VoyageConfig) -> Self {
+        Self {
+            transport: config.transport(),
+            config,
+            poll_interval: DEFAULT_BATCH_POLL_INTERVAL,
+        }
+    }
+
+    /// Sets the interval between status checks of
+    /// [`await_completion`](Self::await_completion). Defaults to
+    /// [`DEFAULT_BATCH_POLL_INTERVAL`].
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Uploads a JSONL file of requests for a batch job.
+    pub async fn upload_file(
+        &self,
+        filename: &str,
+        jsonl: impl Into<String>,
+    ) -> Result<BatchFile, VoyageError> {
+        let jsonl = jsonl.into();
+        let boundary = multipart_boundary(&jsonl);
+        let body = multipart_body(&boundary, filename, &jsonl);
+        debug!(
+            "Uploading batch input file {} ({} bytes)",
+            filename,
+            body.len()
+        );
+        let request = HttpRequest::post(self.config.endpoint("files"))
+            .header(
+                "content-type",
+                &format!("multipart/form-data; boundary={boundary}"),
+            )?
+            .body(body);
+        let text = self.send(request, "files").await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Creates a batch job over an uploaded file.
+    pub async fn create(&self, request: &CreateBatchRequest) -> Result<BatchJob, VoyageError> {
+        let text = self
+            .send(
+                HttpRequest::post(self.config.endpoint("batches")).json(request)?,
+                "batches",
+            )
+            .await?;
+        let job: BatchJob = serde_json::from_str(&text)?;
+        info!("Created batch job {}", job.id);
+        Ok(job)
+    }
+
+    /// Uploads `(custom_id, text)` pairs and starts a job embedding them with
+    /// the configured embedding model. Results are matched to inputs by
+    /// custom id.
+    pub async fn submit_embeddings<I, S>(
+        &self,
+        inputs: impl IntoIterator<Item = (I, S)>,
+        input_type: Option<InputType>,
+    ) -> Result<BatchJob, VoyageError>
+    where
+        I: Into<String>,
+        S: Into<String>,
+    {
+        let jsonl = embeddings_batch_jsonl(inputs)?;
+        if jsonl.is_empty() {
+            return Err(VoyageError::BatchError(
+                "A batch needs at least one input".to_string(),
+            ));
+        }
+        let file = self.upload_file("embeddings.jsonl", jsonl).await?;
+        let params = EmbeddingsBatchParams {
+            model: self.config.embedding_model,
+            input_type,
+        };
+        self.create(&CreateBatchRequest::embeddings(file.id, params))
+            .await
+    }
+
+    /// The current state of a job.
+    pub async fn retrieve(&self, batch_id: &str) -> Result<BatchJob, VoyageError> {
+        let url = self.config.endpoint(&format!("batches/{batch_id}"));
+        let text = self
+            .config
+            .retry_policy
+            .run(|| self.send(HttpRequest::get(&url), "batches"))
+            .await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Asks the API to stop a job; it passes through
+    /// [`BatchStatus::Cancelling`] before it is cancelled.
+    pub async fn cancel(&self, batch_id: &str) -> Result<BatchJob, VoyageError> {
+        let url = self.config.endpoint(&format!("batches/{batch_id}/cancel"));
+        let text = self.send(HttpRequest::post(url), "batches").await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Polls a job until it stops and returns its final state. Fails if the
+    /// job failed, expired or was cancelled.
+    pub async fn await_completion(&self, batch_id: &str) -> Result<BatchJob, VoyageError> {
+        loop {
+            let job = self.retrieve(batch_id).await?;
+            debug!(
+                "Batch job {} is {:?} ({}/{} requests done)",
+                job.id, job.status, job.request_counts.completed, job.request_counts.total
+            );
+            match job.status {
+                BatchStatus::Completed => return Ok(job),
+                status if status.is_terminal() => {
+                    warn!("Batch job {} stopped as {:?}", job.id, status);
+                    return Err(VoyageError::BatchError(format!(
+                        "Batch job {} stopped as {:?}",
+                        job.id, status
+                    )));
+                }
+                _ => tokio::time::sleep(self.poll_interval).await,
+            }
+        }
+    }
+
+    /// Downloads the content of a file, e.g. a job's output file.
+    pub async fn download_file(&self, file_id: &str) -> Result<String, VoyageError> {
+        let url = self.config.endpoint(&format!("files/{file_id}/content"));
+        self.config
+            .retry_policy
+            .run(|| self.send(HttpRequest::get(&url), "files"))
+            .await
+    }
+
+    /// Downloads and parses the embeddings of a completed embeddings job.
+    pub async fn embeddings_results(
+        &self,
+        job: &BatchJob,
+    ) -> Result<Vec<BatchEmbedding>, VoyageError> {
+        let Some(output_file_id) = &job.output_file_id else {
+            return Err(VoyageError::BatchError(format!(
+                "Batch job {} has no output file ({:?})",
+                job.id, job.status
+            )));
+        };
+        parse_embeddings_output(&self.download_file(output_file_id).await?)
+    }
+
+    /// Sends an authenticated request and returns the body of a successful
+    /// response. POST requests carry an idempotency key when enabled.
+    async fn send(&self, request: HttpRequest, endpoint: &str) -> Result<String, VoyageError> {
+        let api_key = self.config.resolve_api_key().await?;
+        let mut request = request.bearer_auth(&api_key)?;
+        let id =
+            (request.method == Method::POST && self.config.idempotency_keys).then(RequestId::new);
+        if let Some(id) = &id {
+            debug!("Idempotency key: {}", id);
+            request = request.header(IDEMPOTENCY_KEY_HEADER, id.as_str())?;
+        }
+        let response = self.transport.send(request).await?;
+        let status = response.status;
+        let reset_in = retry_after(&response.headers);
+        let mut headers = ResponseMetadata::from_header_map(&response.headers);
+        headers.idempotency_key = id;
+        headers.warn_if_deprecated(endpoint);
+        let text = response.text();
+        match status {
+            s if s.is_success() => Ok(text),
+            reqwest::StatusCode::UNAUTHORIZED => Err(VoyageError::Unauthorized),
+            reqwest::StatusCode::FORBIDDEN => Err(VoyageError::Forbidden(text)),
+            reqwest::StatusCode::NOT_FOUND => Err(VoyageError::NotFound(text)),
+            reqwest::StatusCode::TOO_MANY_REQUESTS => Err(VoyageError::RateLimitExceeded {
+                reset_in,
+                metadata: Some(Box::new(headers)),
+            }),
+            _ => {
+                warn!(
+                    "Batch API request failed with status: {} (request id: {:?})",
+                    status, headers.request_id
+                );
+                Err(VoyageError::ApiError(status, text, Box::new(headers)))
+            }
+        }
+    }
+}
+
+/// A multipart boundary that does not occur in `content`.
+fn multipart_boundary(content: &str) -> String {
+    (0u32..)
+        .map(|attempt| format!("voyageai-batch-boundary-{attempt}"))
+        .find(|boundary| !content.contains(boundary.as_str()))
+        .expect("some boundary is absent from the content")
+}
+
+/// A `multipart/form-data` body uploading `content` as a batch input file.
+fn multipart_body(boundary: &str, filename: &str, content: &str) -> Vec<u8> {
+    let filename = filename.replace(['"', '\r', '\n'], "_");
+    format!(
+        "--{boundary}\r\n\
+         Content-Disposition: form-data; name=\"purpose\"\r\n\r\n\
+         batch\r\n\
+         --{boundary}\r\n\
+         Content-Disposition: form-data; name=\"file\"; filename=\"{filename}\"\r\n\
+         Content-Type: application/jsonl\r\n\r\n\
+         {content}\r\n\
+         --{boundary}--\r\n"
+    )
+    .into_bytes()
+}
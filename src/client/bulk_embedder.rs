@@ -0,0 +1,408 @@
+//! Bulk embedding of large document sets.
+//!
+//! [`BulkEmbedder::start`] returns a [`BulkSink`] that documents are fed into and
+//! a [`BulkStream`] of embeddings. Both channels are bounded, so a slow consumer
+//! slows down the producer instead of buffering the whole corpus in memory.
+//! Documents are grouped into batches, sent with bounded concurrency and paced
+//! by the client's rate limiter.
+//!
+//! With [`BulkEmbedder::with_checkpoint`] the ids of embedded documents and the
+//! tokens they cost are recorded in a [checkpoint](crate::client::checkpoint)
+//! as each batch completes; restarting a job with the same checkpoint skips
+//! them instead of paying for them again.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::{error, warn};
+use tokio::sync::{mpsc, Semaphore};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::builder::validation::MAX_EMBEDDING_INPUTS;
+use crate::client::checkpoint::{CheckpointStore, FileCheckpoint, JobState};
+use crate::client::embeddings_client::Client as EmbeddingsClient;
+use crate::client::Priority;
+use crate::client::voyage_client::VoyageAiClient;
+use crate::errors::VoyageError;
+use crate::models::embeddings::{EmbeddingModel, EmbeddingsInput, EmbeddingsRequest, InputType};
+
+/// A document to embed, identified by a caller-provided id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkDocument {
+    pub id: String,
+    pub text: String,
+}
+
+impl BulkDocument {
+    pub fn new(id: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            text: text.into(),
+        }
+    }
+}
+
+/// The embedding of a [`BulkDocument`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkEmbedding {
+    pub id: String,
+    pub embedding: Vec<f32>,
+}
+
+/// A snapshot of a bulk job's progress, passed to the progress callback after
+/// every batch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkProgress {
+    /// Documents embedded in this run
+    pub done: usize,
+    /// Documents skipped because the checkpoint marks them as done
+    pub skipped: usize,
+    /// Documents whose batch failed; they are not checkpointed
+    pub failed: usize,
+    /// Total number of documents, if given with [`BulkEmbedder::with_total`]
+    pub total: Option<usize>,
+    /// Tokens reported by the API in this run
+    pub tokens: u64,
+    /// Tokens spent by earlier runs, according to the checkpoint
+    pub resumed_tokens: u64,
+    pub elapsed: Duration,
+    /// Estimated time remaining, extrapolated from the throughput so far
+    pub eta: Option<Duration>,
+}
+
+impl BulkProgress {
+    fn new(total: Option<usize>) -> Self {
+        Self {
+            done: 0,
+            skipped: 0,
+            failed: 0,
+            total,
+            tokens: 0,
+            resumed_tokens: 0,
+            elapsed: Duration::ZERO,
+            eta: None,
+        }
+    }
+
+    fn update_timing(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+        self.eta = self.total.and_then(|total| {
+            if self.done == 0 {
+                return None;
+            }
+            let remaining = total.saturating_sub(self.done + self.skipped + self.failed);
+            Some(elapsed.mul_f64(remaining as f64 / self.done as f64))
+        });
+    }
+}
+
+/// Callback invoked with the job's progress after every batch.
+pub type BulkProgressCallback = Arc<dyn Fn(&BulkProgress) + Send + Sync>;
+
+/// Stream of embeddings produced by a bulk job, in completion order.
+pub type BulkStream = ReceiverStream<BulkEmbedding>;
+
+/// Embeds large document sets with bounded concurrency, backpressure, progress
+/// reporting and optional checkpointing.
+#[derive(Clone)]
+pub struct BulkEmbedder {
+    client: EmbeddingsClient,
+    model: EmbeddingModel,
+    input_type: Option<InputType>,
+    batch_size: usize,
+    concurrency: usize,
+    total: Option<usize>,
+    checkpoint: Option<Arc<dyn CheckpointStore>>,
+    progress_callback: Option<BulkProgressCallback>,
+}
+
+impl std::fmt::Debug for BulkEmbedder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BulkEmbedder")
+            .field("model", &self.model)
+            .field("input_type", &self.input_type)
+            .field("batch_size", &self.batch_size)
+            .field("concurrency", &self.concurrency)
+            .field("priority", &self.client.config().priority)
+            .field("total", &self.total)
+            .field("checkpoint", &self.checkpoint.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl BulkEmbedder {
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self::from_embeddings_client(client.embeddings_client().as_ref().clone())
+    }
+
+    /// Bulk requests use [`Priority::Batch`] so interactive requests sharing
+    /// the rate limiter go first; see [`with_priority`](Self::with_priority).
+    pub fn from_embeddings_client(client: EmbeddingsClient) -> Self {
+        Self {
+            model: client.config().embedding_model,
+            client: client.with_priority(Priority::Batch),
+            input_type: Some(InputType::Document),
+            batch_size: MAX_EMBEDDING_INPUTS,
+            concurrency: 4,
+            total: None,
+            checkpoint: None,
+            progress_callback: None,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.client = self.client.with_priority(priority);
+        self
+    }
+
+    pub fn with_model(mut self, model: EmbeddingModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Input type sent with every request; defaults to [`InputType::Document`].
+    pub fn with_input_type(mut self, input_type: Option<InputType>) -> Self {
+        self.input_type = input_type;
+        self
+    }
+
+    /// Documents per request, clamped to `1..=128`.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.clamp(1, MAX_EMBEDDING_INPUTS);
+        self
+    }
+
+    /// Maximum number of requests in flight.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Total number of documents, used to estimate the time remaining.
+    pub fn with_total(mut self, total: usize) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// Records embedded document ids in a [`FileCheckpoint`] at `path` and
+    /// skips ids already recorded there, so that an interrupted job can be
+    /// restarted.
+    pub fn with_checkpoint(self, path: impl Into<PathBuf>) -> Self {
+        self.with_checkpoint_store(FileCheckpoint::new(path))
+    }
+
+    /// Like [`BulkEmbedder::with_checkpoint`], with a custom store.
+    pub fn with_checkpoint_store(mut self, store: impl CheckpointStore + 'static) -> Self {
+        self.checkpoint = Some(Arc::new(store));
+        self
+    }
+
+    pub fn with_progress_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&BulkProgress) + Send + Sync + 'static,
+    {
+        self.progress_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Starts the job. Documents sent to the returned sink are embedded and
+    /// yielded on the returned stream; the stream ends once the sink is dropped
+    /// and every pending batch has completed.
+    ///
+    /// Failed batches are logged, counted in [`BulkProgress::failed`] and left
+    /// out of the checkpoint so that a rerun retries them. When the client
+    /// [shuts down](crate::VoyageAiClient::shutdown), the job stops taking
+    /// documents and finishes the batches it holds; starting a job then fails
+    /// with [`VoyageError::ShuttingDown`].
+    pub fn start(self) -> Result<(BulkSink, BulkStream), VoyageError> {
+        let work = self.client.config().lifecycle.begin()?;
+        let state = match &self.checkpoint {
+            Some(store) => store.load()?,
+            None => JobState::default(),
+        };
+        let buffer = self.batch_size * self.concurrency;
+        let (sink_tx, sink_rx) = mpsc::channel(buffer);
+        let (out_tx, out_rx) = mpsc::channel(buffer);
+        tokio::spawn(work.scope(self.run(sink_rx, out_tx, state)));
+        Ok((BulkSink { tx: sink_tx }, ReceiverStream::new(out_rx)))
+    }
+
+    /// Embeds every document from `documents`, feeding them into the job as the
+    /// stream is consumed.
+    pub fn embed_all<I>(self, documents: I) -> Result<BulkStream, VoyageError>
+    where
+        I: IntoIterator<Item = BulkDocument> + Send + 'static,
+        I::IntoIter: Send,
+    {
+        let (sink, stream) = self.start()?;
+        tokio::spawn(async move {
+            for document in documents {
+                if sink.send(document).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(stream)
+    }
+
+    async fn run(
+        self,
+        mut documents: mpsc::Receiver<BulkDocument>,
+        out: mpsc::Sender<BulkEmbedding>,
+        state: JobState,
+    ) {
+        let started = Instant::now();
+        let mut progress = BulkProgress::new(self.total);
+        progress.resumed_tokens = state.tokens;
+        let progress = Arc::new(Mutex::new(progress));
+        let semaphore = Arc::new(Semaphore::new(self.concurrency));
+        let mut batch = Vec::with_capacity(self.batch_size);
+
+        let lifecycle = self.client.config().lifecycle.clone();
+        loop {
+            // A shutdown ends the input; the documents held are still embedded
+            let document = tokio::select! {
+                document = documents.recv() => document,
+                _ = lifecycle.closed() => None,
+            };
+            let finished = document.is_none();
+            match document {
+                Some(document) => {
+                    if state.is_completed(&document.id) {
+                        let mut progress = progress.lock().unwrap();
+                        progress.skipped += 1;
+                        progress.update_timing(started.elapsed());
+                        continue;
+                    }
+                    batch.push(document);
+                    if batch.len() < self.batch_size {
+                        continue;
+                    }
+                }
+                None if batch.is_empty() => break,
+                None => {}
+            }
+            if out.is_closed() {
+                break;
+            }
+            let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                break;
+            };
+            let batch = std::mem::replace(&mut batch, Vec::with_capacity(self.batch_size));
+            let job = BatchJob {
+                client: self.client.clone(),
+                model: self.model,
+                input_type: self.input_type,
+                out: out.clone(),
+                checkpoint: self.checkpoint.clone(),
+                progress: progress.clone(),
+                callback: self.progress_callback.clone(),
+                started,
+            };
+            tokio::spawn(lifecycle.hold().scope(async move {
+                let _permit = permit;
+                job.run(batch).await;
+            }));
+            if finished {
+                break;
+            }
+        }
+    }
+}
+
+struct BatchJob {
+    client: EmbeddingsClient,
+    model: EmbeddingModel,
+    input_type: Option<InputType>,
+    out: mpsc::Sender<BulkEmbedding>,
+    checkpoint: Option<Arc<dyn CheckpointStore>>,
+    progress: Arc<Mutex<BulkProgress>>,
+    callback: Option<BulkProgressCallback>,
+    started: Instant,
+}
+
+impl BatchJob {
+    async fn run(self, batch: Vec<BulkDocument>) {
+        let count = batch.len();
+        let (ids, texts): (Vec<String>, Vec<String>) =
+            batch.into_iter().map(|doc| (doc.id, doc.text)).unzip();
+        let request = EmbeddingsRequest {
+            input: EmbeddingsInput::Multiple(texts),
+            model: self.model,
+            input_type: self.input_type,
+            truncation: None,
+            encoding_format: None,
+        };
+
+        let result = match self.client.create_embedding(&request).await {
+            Ok(response) => {
+                let tokens = response.usage.total_tokens as u64;
+                response.into_embeddings(count).map(|e| (e, tokens))
+            }
+            Err(e) => Err(e),
+        };
+        let (embeddings, tokens) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                error!(
+                    "Error embedding batch {}..{}: {:?}",
+                    ids[0],
+                    ids[count - 1],
+                    e
+                );
+                self.report(|progress| progress.failed += count);
+                return;
+            }
+        };
+
+        for (id, embedding) in ids.iter().zip(embeddings) {
+            let item = BulkEmbedding {
+                id: id.clone(),
+                embedding,
+            };
+            if self.out.send(item).await.is_err() {
+                return; // receiver dropped; the batch is not checkpointed
+            }
+        }
+        if let Some(checkpoint) = &self.checkpoint {
+            if let Err(e) = checkpoint.record(&ids, tokens) {
+                warn!("Failed to update checkpoint: {}", e);
+            }
+        }
+        self.report(|progress| {
+            progress.done += count;
+            progress.tokens += tokens;
+        });
+    }
+
+    fn report(&self, update: impl FnOnce(&mut BulkProgress)) {
+        let snapshot = {
+            let mut progress = self.progress.lock().unwrap();
+            update(&mut progress);
+            progress.update_timing(self.started.elapsed());
+            progress.clone()
+        };
+        if let Some(callback) = &self.callback {
+            callback(&snapshot);
+        }
+    }
+}
+
+/// Feeds documents into a bulk job. Sending waits while the job's buffer is
+/// full. Drop the sink to signal that no more documents follow.
+#[derive(Debug, Clone)]
+pub struct BulkSink {
+    tx: mpsc::Sender<BulkDocument>,
+}
+
+impl BulkSink {
+    /// Sends a document to the job, failing if the job has stopped because
+    /// its output stream was dropped.
+    pub async fn send(&self, document: BulkDocument) -> Result<(), VoyageError> {
+        self.tx
+            .send(document)
+            .await
+            .map_err(|_| VoyageError::Other("bulk embedding job has stopped".to_string()))
+    }
+}
@@ -0,0 +1,347 @@
+//! Embeddings computed on this machine with [Candle](https://github.com/huggingface/candle),
+//! behind the `candle` feature.
+//!
+//! [`CandleEmbedder`] runs a BERT-style sentence embedding model, such as
+//! `BAAI/bge-small-en-v1.5` or `sentence-transformers/all-MiniLM-L6-v2`, from
+//! the `config.json`, `tokenizer.json` and `model.safetensors` files of its
+//! Hugging Face repository. It implements [`Embedder`], so it can stand in
+//! for the API when offline, or pre-filter a large corpus cheaply before the
+//! survivors are embedded with a Voyage model, and a
+//! [`RetrievalPipeline`](crate::retrieval::RetrievalPipeline) can index with
+//! it through
+//! [`RetrievalPipelineBuilder::embedder`](crate::retrieval::RetrievalPipelineBuilder::embedder).
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), voyageai::VoyageError> {
+//! use voyageai::client::candle_embedder::{CandleEmbedder, Pooling};
+//! use voyageai::Embedder;
+//!
+//! let embedder = CandleEmbedder::from_dir("models/bge-small-en-v1.5")?
+//!     .with_pooling(Pooling::Cls)
+//!     .with_query_prefix("Represent this sentence for searching relevant passages: ");
+//! let query = embedder.embed_query("How do I rotate API keys?").await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Vectors of a local model live in a different space than Voyage's and must
+//! not be mixed with them in one index.
+
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::BertModel;
+use log::warn;
+use tokenizers::{PaddingParams, Tokenizer, TruncationParams};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::client::tasks::{spawn_producer, AbortOnDrop};
+use crate::errors::VoyageError;
+use crate::models::embeddings::IndexedEmbedding;
+use crate::traits::llm::{BatchEmbedding, Embedder, TextEmbedding, TextEmbeddingStream};
+
+pub use candle_core;
+pub use candle_nn;
+pub use candle_transformers::models::bert::Config as BertConfig;
+pub use tokenizers;
+
+/// Texts run through the model at once unless set with
+/// [`CandleEmbedder::with_batch_size`].
+pub const DEFAULT_BATCH_SIZE: usize = 32;
+
+/// How the token vectors of a text are combined into its embedding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pooling {
+    /// Average of the token vectors, as in sentence-transformers models
+    #[default]
+    Mean,
+    /// Vector of the first, `[CLS]`, token, as in BGE models
+    Cls,
+}
+
+/// A local BERT-style embedding model. Clones share the model.
+#[derive(Clone)]
+pub struct CandleEmbedder {
+    model: Arc<Model>,
+    pooling: Pooling,
+    normalize: bool,
+    query_prefix: String,
+    document_prefix: String,
+    batch_size: usize,
+}
+
+struct Model {
+    bert: BertModel,
+    tokenizer: Tokenizer,
+    device: Device,
+    dimension: usize,
+}
+
+impl fmt::Debug for CandleEmbedder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CandleEmbedder")
+            .field("dimension", &self.model.dimension)
+            .field("device", &self.model.device)
+            .field("pooling", &self.pooling)
+            .field("normalize", &self.normalize)
+            .field("query_prefix", &self.query_prefix)
+            .field("document_prefix", &self.document_prefix)
+            .field("batch_size", &self.batch_size)
+            .finish()
+    }
+}
+
+impl CandleEmbedder {
+    /// Loads `config.json`, `tokenizer.json` and `model.safetensors` from
+    /// `dir` and runs the model on the CPU.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, VoyageError> {
+        let dir = dir.as_ref();
+        let config = std::fs::read_to_string(dir.join("config.json"))
+            .map_err(|e| model_error(format!("{}: {}", dir.join("config.json").display(), e)))?;
+        let config: BertConfig = serde_json::from_str(&config)?;
+        let tokenizer = Tokenizer::from_file(dir.join("tokenizer.json")).map_err(|e| {
+            VoyageError::TokenizerError(format!("{}: {}", dir.join("tokenizer.json").display(), e))
+        })?;
+        let device = Device::Cpu;
+        // Safety: the weights file is not expected to change while mapped
+        let vb = unsafe {
+            VarBuilder::from_mmaped_safetensors(
+                &[dir.join("model.safetensors")],
+                DType::F32,
+                &device,
+            )
+        }
+        .map_err(model_error)?;
+        Self::new(&config, vb, tokenizer)
+    }
+
+    /// Builds the model described by `config` from the weights of `vb`, which
+    /// also sets the device it runs on.
+    pub fn new(
+        config: &BertConfig,
+        vb: VarBuilder,
+        mut tokenizer: Tokenizer,
+    ) -> Result<Self, VoyageError> {
+        let device = vb.device().clone();
+        let bert = BertModel::load(vb, config).map_err(model_error)?;
+        let pad_token = tokenizer
+            .id_to_token(config.pad_token_id as u32)
+            .unwrap_or_else(|| "[PAD]".to_string());
+        tokenizer.with_padding(Some(PaddingParams {
+            pad_id: config.pad_token_id as u32,
+            pad_token,
+            ..PaddingParams::default()
+        }));
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: config.max_position_embeddings,
+                ..TruncationParams::default()
+            }))
+            .map_err(|e| VoyageError::TokenizerError(e.to_string()))?;
+        Ok(Self {
+            model: Arc::new(Model {
+                bert,
+                tokenizer,
+                device,
+                dimension: config.hidden_size,
+            }),
+            pooling: Pooling::default(),
+            normalize: true,
+            query_prefix: String::new(),
+            document_prefix: String::new(),
+            batch_size: DEFAULT_BATCH_SIZE,
+        })
+    }
+
+    pub fn with_pooling(mut self, pooling: Pooling) -> Self {
+        self.pooling = pooling;
+        self
+    }
+
+    /// Scales embeddings to unit length; on by default.
+    pub fn with_normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Text prepended to queries, e.g. `"query: "` for E5 models.
+    pub fn with_query_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.query_prefix = prefix.into();
+        self
+    }
+
+    /// Text prepended to documents, e.g. `"passage: "` for E5 models.
+    pub fn with_document_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.document_prefix = prefix.into();
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Number of dimensions of the embeddings.
+    pub fn dimension(&self) -> usize {
+        self.model.dimension
+    }
+
+    /// Embeds `texts`, each preceded by `prefix`, on the calling thread.
+    pub fn embed_blocking(
+        &self,
+        texts: &[String],
+        prefix: &str,
+    ) -> Result<Vec<Vec<f32>>, VoyageError> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size) {
+            embeddings.extend(self.embed_batch_blocking(batch, prefix)?);
+        }
+        Ok(embeddings)
+    }
+
+    fn embed_batch_blocking(
+        &self,
+        texts: &[String],
+        prefix: &str,
+    ) -> Result<Vec<Vec<f32>>, VoyageError> {
+        let model = &self.model;
+        let inputs: Vec<String> = texts.iter().map(|text| format!("{prefix}{text}")).collect();
+        let encodings = model
+            .tokenizer
+            .encode_batch(inputs, true)
+            .map_err(|e| VoyageError::TokenizerError(e.to_string()))?;
+        let length = encodings.first().map_or(0, |encoding| encoding.len());
+        let ids: Vec<u32> = encodings
+            .iter()
+            .flat_map(|encoding| encoding.get_ids().iter().copied())
+            .collect();
+        let mask: Vec<u32> = encodings
+            .iter()
+            .flat_map(|encoding| encoding.get_attention_mask().iter().copied())
+            .collect();
+
+        let embeddings = (|| {
+            let ids = Tensor::from_vec(ids, (texts.len(), length), &model.device)?;
+            let mask = Tensor::from_vec(mask, (texts.len(), length), &model.device)?;
+            let type_ids = ids.zeros_like()?;
+            let hidden = model.bert.forward(&ids, &type_ids, Some(&mask))?;
+            let mut pooled = match self.pooling {
+                Pooling::Cls => hidden.narrow(1, 0, 1)?.squeeze(1)?,
+                Pooling::Mean => {
+                    let mask = mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+                    let sums = hidden.broadcast_mul(&mask)?.sum(1)?;
+                    sums.broadcast_div(&mask.sum(1)?.clamp(1e-9, f64::MAX)?)?
+                }
+            };
+            if self.normalize {
+                let norms = pooled
+                    .sqr()?
+                    .sum_keepdim(1)?
+                    .sqrt()?
+                    .clamp(1e-12, f64::MAX)?;
+                pooled = pooled.broadcast_div(&norms)?;
+            }
+            pooled.to_vec2::<f32>()
+        })()
+        .map_err(model_error)?;
+        Ok(embeddings)
+    }
+
+    fn prefix(&self, query: bool) -> &str {
+        if query {
+            &self.query_prefix
+        } else {
+            &self.document_prefix
+        }
+    }
+
+    fn embed_one(&self, text: &str, query: bool) -> TextEmbedding {
+        let embedder = self.clone();
+        let text = format!("{}{}", self.prefix(query), text);
+        let (rx, task) = spawn_blocking(move || {
+            embedder
+                .embed_blocking(&[text], "")?
+                .pop()
+                .ok_or(VoyageError::EmptyResponse)
+        });
+        TextEmbedding::new(rx, task)
+    }
+}
+
+impl Embedder for CandleEmbedder {
+    /// Embeds `text` as a document.
+    fn embed(&self, text: &str) -> TextEmbedding {
+        self.embed_one(text, false)
+    }
+
+    fn embed_batch(&self, texts: &[String]) -> BatchEmbedding {
+        let embedder = self.clone();
+        let texts = texts.to_vec();
+        let (rx, task) =
+            spawn_blocking(move || embedder.embed_blocking(&texts, &embedder.document_prefix));
+        BatchEmbedding::new(rx, task)
+    }
+
+    fn embed_stream(&self, texts: Vec<String>) -> TextEmbeddingStream {
+        let (tx, rx) = mpsc::channel(self.batch_size);
+        let embedder = self.clone();
+        let producer = tx.clone();
+        spawn_producer(tx, async move {
+            let mut start = 0;
+            for batch in texts.chunks(embedder.batch_size) {
+                let batch = batch.to_vec();
+                let count = batch.len();
+                let batch_embedder = embedder.clone();
+                let (result, _task) = spawn_blocking(move || {
+                    batch_embedder.embed_blocking(&batch, &batch_embedder.document_prefix)
+                });
+                let embeddings = match result.await {
+                    Ok(Ok(embeddings)) => embeddings,
+                    Ok(Err(e)) => {
+                        warn!("Error in embedding stream: {}", e);
+                        break;
+                    }
+                    Err(_) => break,
+                };
+                for (offset, embedding) in embeddings.into_iter().enumerate() {
+                    let embedding = IndexedEmbedding {
+                        index: start + offset,
+                        embedding,
+                    };
+                    if producer.send(embedding).await.is_err() {
+                        return;
+                    }
+                }
+                start += count;
+            }
+        });
+        TextEmbeddingStream::new(rx)
+    }
+
+    /// Embeds `query` preceded by the query prefix.
+    fn embed_query(&self, query: &str) -> TextEmbedding {
+        self.embed_one(query, true)
+    }
+}
+
+/// Runs `embed` on the blocking thread pool, keeping async workers free
+/// during inference.
+fn spawn_blocking<T: Send + 'static>(
+    embed: impl FnOnce() -> Result<T, VoyageError> + Send + 'static,
+) -> (oneshot::Receiver<Result<T, VoyageError>>, AbortOnDrop) {
+    let (tx, rx) = oneshot::channel();
+    let task = AbortOnDrop::spawn(async move {
+        let result = tokio::task::spawn_blocking(embed)
+            .await
+            .unwrap_or_else(|e| Err(VoyageError::Other(format!("Embedding task failed: {e}"))));
+        let _ = tx.send(result);
+    });
+    (rx, task)
+}
+
+fn model_error(error: impl fmt::Display) -> VoyageError {
+    VoyageError::LocalModelError(error.to_string())
+}
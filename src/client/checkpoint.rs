@@ -0,0 +1,144 @@
+//! Persistent state for long-running embedding jobs.
+//!
+//! A [`CheckpointStore`] records which documents a job has embedded and how
+//! many tokens that cost, so that an interrupted job can resume without
+//! re-embedding completed documents. [`FileCheckpoint`] is the built-in store;
+//! other backends (an embedded database, object storage) implement the trait.
+
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::VoyageError;
+
+/// State of a job recovered from a checkpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JobState {
+    /// Ids of the documents already embedded
+    pub completed: HashSet<String>,
+    /// Tokens spent on the completed documents
+    pub tokens: u64,
+}
+
+impl JobState {
+    pub fn is_completed(&self, id: &str) -> bool {
+        self.completed.contains(id)
+    }
+}
+
+/// Durable storage for a job's progress.
+pub trait CheckpointStore: Send + Sync {
+    /// Loads the state recorded so far; an empty state if nothing was recorded.
+    fn load(&self) -> Result<JobState, VoyageError>;
+
+    /// Records that `ids` were embedded at a cost of `tokens`. The record must
+    /// be durable when this returns.
+    fn record(&self, ids: &[String], tokens: u64) -> Result<(), VoyageError>;
+}
+
+#[derive(Serialize, Deserialize)]
+struct CheckpointEntry {
+    ids: Vec<String>,
+    tokens: u64,
+}
+
+/// A checkpoint kept in an append-only file with one JSON line per completed
+/// batch.
+///
+/// Each batch is synced to disk before it is acknowledged. A truncated last
+/// line, left behind by a crash mid-write, is ignored on load and removed on
+/// the next write: its batch is simply embedded again.
+#[derive(Debug)]
+pub struct FileCheckpoint {
+    path: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl FileCheckpoint {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            file: Mutex::new(None),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn error(&self, e: impl std::fmt::Display) -> VoyageError {
+        VoyageError::CheckpointError(format!("{}: {}", self.path.display(), e))
+    }
+}
+
+impl CheckpointStore for FileCheckpoint {
+    fn load(&self) -> Result<JobState, VoyageError> {
+        let mut state = JobState::default();
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(state),
+            Err(e) => return Err(self.error(e)),
+        };
+
+        let mut lines = BufReader::new(file).lines().peekable();
+        let mut number = 0;
+        while let Some(line) = lines.next() {
+            number += 1;
+            let line = line.map_err(|e| self.error(e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<CheckpointEntry>(&line) {
+                Ok(entry) => {
+                    state.completed.extend(entry.ids);
+                    state.tokens += entry.tokens;
+                }
+                Err(_) if lines.peek().is_none() => {
+                    warn!(
+                        "Ignoring truncated entry at {}:{}",
+                        self.path.display(),
+                        number
+                    );
+                }
+                Err(e) => return Err(self.error(format!("line {}: {}", number, e))),
+            }
+        }
+        Ok(state)
+    }
+
+    fn record(&self, ids: &[String], tokens: u64) -> Result<(), VoyageError> {
+        let entry = CheckpointEntry {
+            ids: ids.to_vec(),
+            tokens,
+        };
+        let mut line = serde_json::to_string(&entry).map_err(|e| self.error(e))?;
+        line.push('\n');
+
+        let mut file = self.file.lock().unwrap();
+        if file.is_none() {
+            let opened = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .map_err(|e| self.error(e))?;
+            // Drop a truncated entry left by a crash so that this one starts on its own line
+            let contents = std::fs::read(&self.path).map_err(|e| self.error(e))?;
+            if contents.last().is_some_and(|byte| *byte != b'\n') {
+                let keep = contents
+                    .iter()
+                    .rposition(|byte| *byte == b'\n')
+                    .map_or(0, |i| i + 1);
+                opened.set_len(keep as u64).map_err(|e| self.error(e))?;
+            }
+            *file = Some(opened);
+        }
+        let file = file.as_mut().expect("checkpoint file was just opened");
+        file.write_all(line.as_bytes()).map_err(|e| self.error(e))?;
+        file.sync_data().map_err(|e| self.error(e))
+    }
+}
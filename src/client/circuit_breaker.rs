@@ -0,0 +1,388 @@
+//! Failing fast while the Voyage API is down.
+//!
+//! A [`CircuitBreaker`] watches the outcome of the requests sent through it:
+//! responses with a 5xx status and requests that got no response, such as
+//! timeouts and connection failures, are failures; every other response,
+//! including 429s, is a success. While the breaker is
+//! [closed](CircuitState::Closed), requests go through. When the share of
+//! failures among the last [`window_size`](CircuitBreaker::with_window_size)
+//! requests reaches the
+//! [threshold](CircuitBreaker::with_failure_rate_threshold), it
+//! [opens](CircuitState::Open) and requests fail at once with
+//! [`VoyageError::CircuitOpen`] instead of waiting for a timeout. After
+//! [`open_duration`](CircuitBreaker::with_open_duration) it lets a few probe
+//! requests through [half-open](CircuitState::HalfOpen): if they all succeed
+//! it closes again, if one fails it reopens.
+//!
+//! Enable it with [`VoyageConfig::with_circuit_breaker`](crate::VoyageConfig::with_circuit_breaker).
+//! Clones share their state, so every client created from the configuration,
+//! or given a clone of the breaker, trips together:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use voyageai::client::CircuitBreaker;
+//! use voyageai::{VoyageAiClient, VoyageConfig};
+//!
+//! let breaker = CircuitBreaker::new()
+//!     .with_failure_rate_threshold(0.5)
+//!     .with_open_duration(Duration::from_secs(30))
+//!     .with_transition_callback(|transition| {
+//!         eprintln!("Voyage API circuit {:?} -> {:?}", transition.from, transition.to);
+//!     });
+//! let config = VoyageConfig::new("pa-...".to_string()).with_circuit_breaker(breaker.clone());
+//! let client = VoyageAiClient::new_with_config(config);
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::warn;
+use tokio::time::Instant;
+
+use crate::errors::VoyageError;
+use crate::transport::{HttpRequest, Transport, TransportFuture};
+
+/// State of a [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CircuitState {
+    /// Requests go through
+    Closed,
+    /// Requests fail at once with [`VoyageError::CircuitOpen`]
+    Open,
+    /// A few probe requests go through to tell whether the API recovered
+    HalfOpen,
+}
+
+/// A change of [`CircuitState`], passed to the
+/// [transition callback](CircuitBreaker::with_transition_callback).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitTransition {
+    pub from: CircuitState,
+    pub to: CircuitState,
+    /// Share of failures among the recent requests when the state changed
+    pub failure_rate: f32,
+}
+
+/// Callback invoked on every state change of a [`CircuitBreaker`].
+pub type CircuitCallback = Arc<dyn Fn(&CircuitTransition) + Send + Sync>;
+
+/// Mutable state shared by the clones of a breaker.
+#[derive(Debug)]
+struct Circuit {
+    state: CircuitState,
+    /// Outcomes of the recent requests while closed, `true` for failures
+    outcomes: VecDeque<bool>,
+    /// When the breaker last opened
+    opened_at: Instant,
+    /// Probes sent and not finished while half-open
+    probes_in_flight: u32,
+    /// Probes that succeeded while half-open
+    probes_succeeded: u32,
+}
+
+impl Circuit {
+    fn failure_rate(&self) -> f32 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+        let failures = self.outcomes.iter().filter(|failed| **failed).count();
+        failures as f32 / self.outcomes.len() as f32
+    }
+}
+
+/// Stops sending requests while the API fails, see the
+/// [module documentation](self). Clones share their state.
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    circuit: Arc<Mutex<Circuit>>,
+    failure_rate_threshold: f32,
+    minimum_requests: usize,
+    window_size: usize,
+    open_duration: Duration,
+    probes: u32,
+    on_transition: Option<CircuitCallback>,
+}
+
+impl std::fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("state", &self.state())
+            .field("failure_rate_threshold", &self.failure_rate_threshold)
+            .field("minimum_requests", &self.minimum_requests)
+            .field("window_size", &self.window_size)
+            .field("open_duration", &self.open_duration)
+            .field("probes", &self.probes)
+            .field("on_transition", &self.on_transition.is_some())
+            .finish()
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CircuitBreaker {
+    /// A closed breaker that opens when half of the last 20 requests failed,
+    /// once at least 10 were sent, stays open for 30 seconds and then closes
+    /// after 3 successful probes.
+    pub fn new() -> Self {
+        Self {
+            circuit: Arc::new(Mutex::new(Circuit {
+                state: CircuitState::Closed,
+                outcomes: VecDeque::new(),
+                opened_at: Instant::now(),
+                probes_in_flight: 0,
+                probes_succeeded: 0,
+            })),
+            failure_rate_threshold: 0.5,
+            minimum_requests: 10,
+            window_size: 20,
+            open_duration: Duration::from_secs(30),
+            probes: 3,
+            on_transition: None,
+        }
+    }
+
+    /// Opens the breaker when at least this share of the recent requests
+    /// failed, between 0 and 1.
+    pub fn with_failure_rate_threshold(mut self, threshold: f32) -> Self {
+        self.failure_rate_threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// How many requests must have been seen before the failure rate can
+    /// open the breaker, so that one early failure does not.
+    pub fn with_minimum_requests(mut self, requests: usize) -> Self {
+        self.minimum_requests = requests.max(1);
+        self
+    }
+
+    /// How many of the most recent requests the failure rate is computed
+    /// over.
+    pub fn with_window_size(mut self, requests: usize) -> Self {
+        self.window_size = requests.max(1);
+        self
+    }
+
+    /// How long the breaker rejects requests before probing the API.
+    pub fn with_open_duration(mut self, duration: Duration) -> Self {
+        self.open_duration = duration;
+        self
+    }
+
+    /// How many probe requests must succeed while half-open to close the
+    /// breaker. As many are let through at once.
+    pub fn with_probes(mut self, probes: u32) -> Self {
+        self.probes = probes.max(1);
+        self
+    }
+
+    /// Registers a callback invoked whenever the breaker changes state, e.g.
+    /// to alert on an outage or export the state as a metric.
+    pub fn with_transition_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&CircuitTransition) + Send + Sync + 'static,
+    {
+        self.on_transition = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.circuit().state
+    }
+
+    /// Share of failures among the recent requests, while closed.
+    pub fn failure_rate(&self) -> f32 {
+        self.circuit().failure_rate()
+    }
+
+    /// Closes the breaker and forgets the recent requests.
+    pub fn reset(&self) {
+        let transition = {
+            let mut circuit = self.circuit();
+            circuit.outcomes.clear();
+            self.transition(&mut circuit, CircuitState::Closed)
+        };
+        self.notify(transition);
+    }
+
+    /// Checks whether a request may be sent now, moving an open breaker
+    /// whose open period is over to half-open. Returns whether the request is
+    /// a probe.
+    fn acquire(&self) -> Result<bool, VoyageError> {
+        let mut circuit = self.circuit();
+        if circuit.state == CircuitState::Open {
+            let reopens_at = circuit.opened_at + self.open_duration;
+            let now = Instant::now();
+            if now < reopens_at {
+                return Err(VoyageError::CircuitOpen {
+                    retry_in: reopens_at - now,
+                });
+            }
+            let transition = self.transition(&mut circuit, CircuitState::HalfOpen);
+            drop(circuit);
+            self.notify(transition);
+            circuit = self.circuit();
+        }
+        match circuit.state {
+            CircuitState::HalfOpen if circuit.probes_in_flight >= self.probes => {
+                Err(VoyageError::CircuitOpen {
+                    retry_in: Duration::ZERO,
+                })
+            }
+            CircuitState::HalfOpen => {
+                circuit.probes_in_flight += 1;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Records the outcome of a request let through by
+    /// [`acquire`](Self::acquire); `None` when it was canceled.
+    fn record(&self, probe: bool, failed: Option<bool>) {
+        let transition = self.update(probe, failed);
+        self.notify(transition);
+    }
+
+    /// Applies the outcome recorded by [`record`](Self::record) under the
+    /// lock, returning the state change it caused, if any.
+    fn update(&self, probe: bool, failed: Option<bool>) -> Option<CircuitTransition> {
+        let mut circuit = self.circuit();
+        if probe && circuit.state == CircuitState::HalfOpen {
+            circuit.probes_in_flight = circuit.probes_in_flight.saturating_sub(1);
+            return match failed {
+                Some(true) => self.transition(&mut circuit, CircuitState::Open),
+                Some(false) => {
+                    circuit.probes_succeeded += 1;
+                    if circuit.probes_succeeded >= self.probes {
+                        self.transition(&mut circuit, CircuitState::Closed)
+                    } else {
+                        None
+                    }
+                }
+                None => None,
+            };
+        }
+        // Requests sent before the breaker opened or half-opened don't count
+        let (CircuitState::Closed, Some(failed)) = (circuit.state, failed) else {
+            return None;
+        };
+        circuit.outcomes.push_back(failed);
+        while circuit.outcomes.len() > self.window_size {
+            circuit.outcomes.pop_front();
+        }
+        if circuit.outcomes.len() >= self.minimum_requests
+            && circuit.failure_rate() >= self.failure_rate_threshold
+            && failed
+        {
+            return self.transition(&mut circuit, CircuitState::Open);
+        }
+        None
+    }
+
+    /// Moves `circuit` to `to`, returning the change for
+    /// [`notify`](Self::notify) to report once the lock is released.
+    fn transition(&self, circuit: &mut Circuit, to: CircuitState) -> Option<CircuitTransition> {
+        let from = circuit.state;
+        if from == to {
+            return None;
+        }
+        let failure_rate = circuit.failure_rate();
+        circuit.state = to;
+        circuit.probes_in_flight = 0;
+        circuit.probes_succeeded = 0;
+        match to {
+            CircuitState::Open => {
+                circuit.opened_at = Instant::now();
+                warn!(
+                    "Voyage API circuit opened (failure rate {:.0}%); failing requests for {:?}",
+                    failure_rate * 100.0,
+                    self.open_duration
+                );
+            }
+            CircuitState::Closed => circuit.outcomes.clear(),
+            CircuitState::HalfOpen => {}
+        }
+        Some(CircuitTransition {
+            from,
+            to,
+            failure_rate,
+        })
+    }
+
+    /// Invokes the transition callback; the circuit must not be locked, so
+    /// that the callback can query the breaker.
+    fn notify(&self, transition: Option<CircuitTransition>) {
+        if let (Some(callback), Some(transition)) = (&self.on_transition, transition) {
+            callback(&transition);
+        }
+    }
+
+    fn circuit(&self) -> std::sync::MutexGuard<'_, Circuit> {
+        self.circuit
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// `transport` sending its requests through this breaker.
+    pub(crate) fn wrap(&self, transport: Arc<dyn Transport>) -> Arc<dyn Transport> {
+        Arc::new(BreakerTransport {
+            inner: transport,
+            breaker: self.clone(),
+        })
+    }
+}
+
+/// A [`Transport`] guarded by a [`CircuitBreaker`].
+#[derive(Debug)]
+struct BreakerTransport {
+    inner: Arc<dyn Transport>,
+    breaker: CircuitBreaker,
+}
+
+impl Transport for BreakerTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture {
+        let breaker = self.breaker.clone();
+        let probe = match breaker.acquire() {
+            Ok(probe) => probe,
+            Err(e) => return TransportFuture::new(async move { Err(e) }),
+        };
+        // Built before the future so that a future dropped before its first
+        // poll still frees its probe slot
+        let outcome = Outcome {
+            breaker,
+            probe,
+            failed: None,
+        };
+        let response = self.inner.send(request);
+        TransportFuture::new(async move {
+            // Moves the whole guard in; capturing only its `failed` field
+            // would drop it here and now
+            let mut outcome = outcome;
+            let response = response.await;
+            outcome.failed = Some(match &response {
+                Ok(response) => response.status.is_server_error(),
+                Err(_) => true,
+            });
+            response
+        })
+    }
+}
+
+/// Records the outcome of a request when dropped, so that a canceled probe
+/// frees its slot.
+struct Outcome {
+    breaker: CircuitBreaker,
+    probe: bool,
+    failed: Option<bool>,
+}
+
+impl Drop for Outcome {
+    fn drop(&mut self) {
+        self.breaker.record(self.probe, self.failed);
+    }
+}
@@ -1,35 +1,506 @@
+use crate::errors::VoyageError;
+use crate::models::response_headers::RateLimitHeaders;
 use log::{debug, info, warn};
-use std::collections::VecDeque;
+use std::collections::BTreeSet;
+use std::convert::Infallible;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use tokio::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::{sleep_until, Instant};
+
+/// Log target used for rate limit events.
+pub const RATE_LIMIT_LOG_TARGET: &str = "voyageai::rate_limit";
+
+/// The API a rate limit event applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitEndpoint {
+    Embeddings,
+    Reranking,
+}
+
+impl RateLimitEndpoint {
+    pub fn as_str(&self) -> &str {
+        match self {
+            RateLimitEndpoint::Embeddings => "embeddings",
+            RateLimitEndpoint::Reranking => "rerank",
+        }
+    }
+}
+
+/// What happened to the request that triggered a rate limit event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitEventKind {
+    /// The limiter delayed the request locally before sending it
+    Delayed,
+    /// The API rejected the request with a 429 response
+    Rejected,
+}
+
+/// Scheduling class of a request. When requests wait for the rate limiter,
+/// higher priorities are served first, and requests of the same priority in
+/// arrival order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Priority {
+    /// Latency-sensitive requests, e.g. user queries
+    High,
+    #[default]
+    Normal,
+    /// Background work such as bulk indexing
+    Batch,
+}
+
+impl Priority {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Priority::High => "high",
+            Priority::Normal => "normal",
+            Priority::Batch => "batch",
+        }
+    }
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "high" => Ok(Priority::High),
+            "normal" => Ok(Priority::Normal),
+            "batch" => Ok(Priority::Batch),
+            other => Err(format!(
+                "Unknown priority '{other}', expected high, normal or batch"
+            )),
+        }
+    }
+}
+
+/// Structured description of a rate limit delay or rejection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitEvent {
+    pub endpoint: RateLimitEndpoint,
+    pub kind: RateLimitEventKind,
+    /// How long the request was (or should be) delayed
+    pub wait: Duration,
+    /// Estimated tokens of the request
+    pub tokens_requested: u32,
+    pub priority: Priority,
+    /// Requests left in the endpoint's bucket
+    pub available_requests: u32,
+    /// Tokens left in the endpoint's bucket
+    pub available_tokens: u32,
+    pub rpm_limit: u32,
+    pub tpm_limit: u32,
+}
+
+/// Callback invoked for every rate limit event.
+pub type RateLimitCallback = Arc<dyn Fn(&RateLimitEvent) + Send + Sync>;
+
+/// Requests and tokens allowed per minute on one endpoint.
+///
+/// Each limit is a token bucket refilled continuously at the per-minute rate
+/// and holding at most its burst, the amount that can be spent at once after
+/// an idle period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests_per_minute: u32,
+    pub tokens_per_minute: u32,
+    pub request_burst: u32,
+    pub token_burst: u32,
+}
+
+impl RateLimit {
+    /// Limits whose bursts are a full minute's allowance.
+    pub const fn new(requests_per_minute: u32, tokens_per_minute: u32) -> Self {
+        Self {
+            requests_per_minute,
+            tokens_per_minute,
+            request_burst: requests_per_minute,
+            token_burst: tokens_per_minute,
+        }
+    }
+
+    pub const fn with_burst(mut self, requests: u32, tokens: u32) -> Self {
+        self.request_burst = requests;
+        self.token_burst = tokens;
+        self
+    }
+
+    /// Every limit and burst multiplied by `factor`.
+    pub const fn scaled(self, factor: u32) -> Self {
+        Self {
+            requests_per_minute: self.requests_per_minute.saturating_mul(factor),
+            tokens_per_minute: self.tokens_per_minute.saturating_mul(factor),
+            request_burst: self.request_burst.saturating_mul(factor),
+            token_burst: self.token_burst.saturating_mul(factor),
+        }
+    }
+}
+
+/// Default limits of the embeddings endpoint.
+pub const EMBEDDINGS_RATE_LIMIT: RateLimit = RateLimit::new(300, 1_000_000);
+/// Default limits of the rerank endpoint.
+pub const RERANKING_RATE_LIMIT: RateLimit = RateLimit::new(100, 2_000_000);
+
+/// Usage tier of the Voyage AI account, which scales the default limits of
+/// every endpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RateLimitTier {
+    #[default]
+    Tier1,
+    /// Twice the limits of tier 1
+    Tier2,
+    /// Three times the limits of tier 1
+    Tier3,
+}
+
+impl RateLimitTier {
+    pub const fn multiplier(&self) -> u32 {
+        match self {
+            Self::Tier1 => 1,
+            Self::Tier2 => 2,
+            Self::Tier3 => 3,
+        }
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tier1 => "1",
+            Self::Tier2 => "2",
+            Self::Tier3 => "3",
+        }
+    }
+
+    /// The limits of `endpoint` in this tier.
+    pub const fn limit(&self, endpoint: RateLimitEndpoint) -> RateLimit {
+        let base = match endpoint {
+            RateLimitEndpoint::Embeddings => EMBEDDINGS_RATE_LIMIT,
+            RateLimitEndpoint::Reranking => RERANKING_RATE_LIMIT,
+        };
+        base.scaled(self.multiplier())
+    }
+}
+
+impl std::fmt::Display for RateLimitTier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for RateLimitTier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s
+            .trim()
+            .to_ascii_lowercase()
+            .trim_start_matches("tier")
+            .trim_start_matches(['-', '_', ' '])
+        {
+            "1" => Ok(Self::Tier1),
+            "2" => Ok(Self::Tier2),
+            "3" => Ok(Self::Tier3),
+            _ => Err(format!("Unknown rate limit tier '{s}', expected 1, 2 or 3")),
+        }
+    }
+}
+
+/// Additive-increase/multiplicative-decrease control of the rate of an
+/// endpoint, enabled with [`RateLimiter::with_adaptive_throttling`].
+///
+/// Every 429 response cuts the effective limits (rate and burst) by
+/// `decrease`, down to `min_rate` of the configured limits; while requests
+/// are not rejected they grow back by `increase_per_minute` of the configured
+/// limits each minute. Clients sharing an API key across processes thus
+/// settle below the server's limit instead of repeatedly hitting it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveThrottling {
+    /// Factor applied to the effective limits on a rejection
+    pub decrease: f64,
+    /// Fraction of the configured limits restored per minute
+    pub increase_per_minute: f64,
+    /// Lowest fraction of the configured limits
+    pub min_rate: f64,
+}
+
+impl AdaptiveThrottling {
+    pub const fn new(decrease: f64, increase_per_minute: f64, min_rate: f64) -> Self {
+        Self {
+            decrease,
+            increase_per_minute,
+            min_rate,
+        }
+    }
+}
+
+impl Default for AdaptiveThrottling {
+    /// Halves the limits on a rejection, down to 5%, and restores 10% of
+    /// them per minute.
+    fn default() -> Self {
+        Self::new(0.5, 0.1, 0.05)
+    }
+}
 
 /// Rate limiter for managing API request limits.
-#[derive(Debug, Clone)]
+///
+/// Every endpoint has a request bucket and a token bucket. Callers
+/// [`acquire`](Self::acquire) capacity before sending a request and are served
+/// in the order they arrived, so a large request is not starved by a stream
+/// of small ones. Clones share the same buckets.
+#[derive(Clone)]
 pub struct RateLimiter {
-    embeddings_limiter: Arc<Mutex<ApiLimiter>>,
-    reranking_limiter: Arc<Mutex<ApiLimiter>>,
+    embeddings_limiter: Arc<ApiLimiter>,
+    reranking_limiter: Arc<ApiLimiter>,
+    on_event: Option<RateLimitCallback>,
 }
 
-impl RateLimiter {
+impl std::fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("embeddings_limiter", &self.embeddings_limiter)
+            .field("reranking_limiter", &self.reranking_limiter)
+            .field("on_event", &self.on_event.is_some())
+            .finish()
+    }
+}
+
+/// Callers waiting on one endpoint, ordered by priority, then arrival.
+#[derive(Debug, Default)]
+struct WaitQueue {
+    tickets: std::sync::Mutex<(BTreeSet<(Priority, u64)>, u64)>,
+    /// Notified whenever a ticket joins or leaves the queue
+    changed: Notify,
+}
+
+impl WaitQueue {
+    fn join(&self, priority: Priority) -> Ticket<'_> {
+        let mut tickets = self.tickets.lock().unwrap();
+        let key = (priority, tickets.1);
+        tickets.1 += 1;
+        tickets.0.insert(key);
+        drop(tickets);
+        self.changed.notify_waiters();
+        Ticket { queue: self, key }
+    }
+}
+
+/// A caller's place in a [`WaitQueue`], left when dropped.
+struct Ticket<'a> {
+    queue: &'a WaitQueue,
+    key: (Priority, u64),
+}
+
+impl Ticket<'_> {
+    fn is_first(&self) -> bool {
+        self.queue.tickets.lock().unwrap().0.first() == Some(&self.key)
+    }
 }
 
-/// Internal structure for managing rate limits for a specific API.
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        self.queue.tickets.lock().unwrap().0.remove(&self.key);
+        self.queue.changed.notify_waiters();
+    }
+}
+
+/// The buckets of one endpoint and the queue of callers waiting on them.
 #[derive(Debug)]
 struct ApiLimiter {
-    requests: VecDeque<Instant>,
-    tokens: VecDeque<(Instant, u32)>,
-    rpm_limit: u32,
-    tpm_limit: u32,
+    queue: WaitQueue,
+    buckets: std::sync::Mutex<Buckets>,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    limit: RateLimit,
+    requests: Bucket,
+    tokens: Bucket,
+    updated: Instant,
+    /// Set when the API rejected a request or reported an exhausted quota,
+    /// until its retry time
+    paused_until: Option<Instant>,
+    adaptive: Option<AdaptiveThrottling>,
+    /// Fraction of `limit` in effect, below 1 after adaptive throttling
+    /// backed off
+    rate: f64,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    /// Negative when actual usage exceeded the estimate
+    available: f64,
+    per_second: f64,
 }
 
+/// Builds the error of a caller that stopped waiting, from the wait it
+/// would have needed.
+type GiveUp<E> = fn(Duration) -> E;
+
 impl RateLimiter {
     /// Creates a new `RateLimiter` instance with default limits.
     pub fn new() -> Self {
         debug!("Creating new RateLimiter");
         Self {
-            embeddings_limiter: Arc::new(Mutex::new(ApiLimiter::new(300, 1_000_000))),
-            reranking_limiter: Arc::new(Mutex::new(ApiLimiter::new(100, 2_000_000))),
+            embeddings_limiter: Arc::new(ApiLimiter::new(EMBEDDINGS_RATE_LIMIT)),
+            reranking_limiter: Arc::new(ApiLimiter::new(RERANKING_RATE_LIMIT)),
+            on_event: None,
+        }
+    }
+
+    /// Creates a `RateLimiter` with the limits of `tier`.
+    pub fn for_tier(tier: RateLimitTier) -> Self {
+        Self::new()
+            .with_limit(
+                RateLimitEndpoint::Embeddings,
+                tier.limit(RateLimitEndpoint::Embeddings),
+            )
+            .with_limit(
+                RateLimitEndpoint::Reranking,
+                tier.limit(RateLimitEndpoint::Reranking),
+            )
+    }
+
+    /// Uses `limit` for `endpoint`, starting with full buckets.
+    pub fn with_limit(mut self, endpoint: RateLimitEndpoint, limit: RateLimit) -> Self {
+        let limiter = ApiLimiter::new(limit);
+        limiter.buckets.lock().unwrap().adaptive =
+            self.limiter(endpoint).buckets.lock().unwrap().adaptive;
+        let limiter = Arc::new(limiter);
+        match endpoint {
+            RateLimitEndpoint::Embeddings => self.embeddings_limiter = limiter,
+            RateLimitEndpoint::Reranking => self.reranking_limiter = limiter,
+        }
+        self
+    }
+
+    /// Backs off the limits of every endpoint when the API rejects requests
+    /// and ramps them back up while it accepts them.
+    pub fn with_adaptive_throttling(self, throttling: AdaptiveThrottling) -> Self {
+        for limiter in [&self.embeddings_limiter, &self.reranking_limiter] {
+            limiter.buckets.lock().unwrap().adaptive = Some(throttling);
+        }
+        self
+    }
+
+    /// Registers a callback invoked whenever a request is delayed or rejected.
+    pub fn with_event_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&RateLimitEvent) + Send + Sync + 'static,
+    {
+        self.on_event = Some(Arc::new(callback));
+        self
+    }
+
+    /// The limits configured for `endpoint`.
+    pub fn limit(&self, endpoint: RateLimitEndpoint) -> RateLimit {
+        self.limiter(endpoint).buckets.lock().unwrap().limit
+    }
+
+    /// The limits currently in effect for `endpoint`: the configured ones,
+    /// unless adaptive throttling backed off.
+    pub fn effective_limit(&self, endpoint: RateLimitEndpoint) -> RateLimit {
+        let mut buckets = self.limiter(endpoint).buckets.lock().unwrap();
+        buckets.refill(Instant::now());
+        let rate = buckets.rate;
+        let scale = |value: u32| ((value as f64 * rate).round() as u32).max(1);
+        let limit = buckets.limit;
+        RateLimit {
+            requests_per_minute: scale(limit.requests_per_minute),
+            tokens_per_minute: scale(limit.tokens_per_minute),
+            request_burst: scale(limit.request_burst),
+            token_burst: scale(limit.token_burst),
+        }
+    }
+
+    /// Waits until one request of `tokens` tokens may be sent to `endpoint`
+    /// and takes the capacity. Returns how long the caller waited.
+    ///
+    /// Requests larger than the token burst wait for a full bucket.
+    pub async fn acquire(&self, endpoint: RateLimitEndpoint, tokens: u32) -> Duration {
+        self.acquire_with_priority(endpoint, tokens, Priority::Normal)
+            .await
+    }
+
+    /// Like [`acquire`](Self::acquire), served ahead of waiting callers of
+    /// lower `priority`.
+    pub async fn acquire_with_priority(
+        &self,
+        endpoint: RateLimitEndpoint,
+        tokens: u32,
+        priority: Priority,
+    ) -> Duration {
+        let Ok(waited) = self
+            .wait_turn::<Infallible>(endpoint, tokens, priority, None)
+            .await;
+        waited
+    }
+
+    /// Like [`acquire`](Self::acquire), but fails with
+    /// [`VoyageError::RateLimitExceeded`] without waiting when the request
+    /// could not be sent within `max_wait`, or when it is still queued behind
+    /// other callers once `max_wait` has passed.
+    pub async fn acquire_within(
+        &self,
+        endpoint: RateLimitEndpoint,
+        tokens: u32,
+        priority: Priority,
+        max_wait: Duration,
+    ) -> Result<Duration, VoyageError> {
+        self.admit(endpoint, tokens, priority, Some(max_wait)).await
+    }
+
+    /// Corrects the token bucket once the API reported the `actual` tokens
+    /// of a request acquired for `estimated` tokens.
+    pub async fn record_usage(&self, endpoint: RateLimitEndpoint, estimated: u32, actual: u32) {
+        debug!(
+            "Recording {} usage: estimated {} tokens, actual {}",
+            endpoint.as_str(),
+            estimated,
+            actual
+        );
+        let mut buckets = self.limiter(endpoint).buckets.lock().unwrap();
+        buckets.refill(Instant::now());
+        buckets.tokens.available += estimated as f64 - actual as f64;
+        buckets.tokens.available = buckets.tokens.available.min(buckets.tokens.capacity);
+    }
+
+    /// Paces requests by the quota the API reported in the headers of a
+    /// response. When the server has less left than the buckets, e.g. because
+    /// other processes share the API key, the buckets drop to the server's
+    /// values; they are never raised above the local limits. An exhausted
+    /// quota pauses the endpoint until the server replenishes it.
+    pub async fn record_quota(&self, endpoint: RateLimitEndpoint, quota: &RateLimitHeaders) {
+        if quota.is_empty() {
+            return;
+        }
+        debug!(
+            "Recording {} quota: {:?} requests and {:?} tokens remaining",
+            endpoint.as_str(),
+            quota.remaining_requests,
+            quota.remaining_tokens
+        );
+        let mut buckets = self.limiter(endpoint).buckets.lock().unwrap();
+        let now = Instant::now();
+        buckets.refill(now);
+        if let Some(remaining) = quota.remaining_requests {
+            buckets.requests.available = buckets.requests.available.min(remaining as f64);
+        }
+        if let Some(remaining) = quota.remaining_tokens {
+            buckets.tokens.available = buckets.tokens.available.min(remaining as f64);
+        }
+        if let Some(reset_in) = quota.reset_in() {
+            let until = now + reset_in;
+            buckets.paused_until = Some(
+                buckets
+                    .paused_until
+                    .map_or(until, |paused| paused.max(until)),
+            );
         }
     }
 
@@ -42,9 +513,10 @@ impl RateLimiter {
     /// # Returns
     ///
     /// The duration to wait before making the request.
+    #[deprecated(note = "use `acquire`, which waits and takes the capacity")]
     pub async fn check_embeddings_limit(&self, tokens: u32) -> Duration {
         debug!("Checking embeddings limit for {} tokens", tokens);
-        self.embeddings_limiter.lock().await.check_limit(tokens)
+        self.check_limit(RateLimitEndpoint::Embeddings, tokens)
     }
 
     /// Updates the usage for the embeddings API.
@@ -52,9 +524,10 @@ impl RateLimiter {
     /// # Arguments
     ///
     /// * `tokens` - The number of tokens used in the request.
+    #[deprecated(note = "use `acquire` before and `record_usage` after a request")]
     pub async fn update_embeddings_usage(&self, tokens: u32) {
         debug!("Updating embeddings usage with {} tokens", tokens);
-        self.embeddings_limiter.lock().await.update_usage(tokens);
+        self.take(RateLimitEndpoint::Embeddings, tokens);
     }
 
     /// Checks if the reranking API limit has been reached.
@@ -66,9 +539,10 @@ impl RateLimiter {
     /// # Returns
     ///
     /// The duration to wait before making the request.
+    #[deprecated(note = "use `acquire`, which waits and takes the capacity")]
     pub async fn check_reranking_limit(&self, tokens: u32) -> Duration {
         debug!("Checking reranking limit for {} tokens", tokens);
-        self.reranking_limiter.lock().await.check_limit(tokens)
+        self.check_limit(RateLimitEndpoint::Reranking, tokens)
     }
 
     /// Updates the usage for the reranking API.
@@ -76,136 +550,346 @@ impl RateLimiter {
     /// # Arguments
     ///
     /// * `tokens` - The number of tokens used in the request.
+    #[deprecated(note = "use `acquire` before and `record_usage` after a request")]
     pub async fn update_reranking_usage(&self, tokens: u32) {
         debug!("Updating reranking usage with {} tokens", tokens);
-        self.reranking_limiter.lock().await.update_usage(tokens);
+        self.take(RateLimitEndpoint::Reranking, tokens);
     }
-}
 
-impl ApiLimiter {
-    /// Creates a new `ApiLimiter` instance.
+    /// Records that the API rejected a request with a 429 response. Requests
+    /// to the endpoint wait until `reset_in` has passed, and adaptive
+    /// throttling backs off, once for all rejections until then.
     ///
     /// # Arguments
     ///
-    /// * `rpm_limit` - The requests per minute limit.
-    /// * `tpm_limit` - The tokens per minute limit.
-    fn new(rpm_limit: u32, tpm_limit: u32) -> Self {
-        debug!(
-            "Creating new ApiLimiter with RPM: {}, TPM: {}",
-            rpm_limit, tpm_limit
-        );
-        Self {
-            requests: VecDeque::new(),
-            tokens: VecDeque::new(),
-            rpm_limit,
-            tpm_limit,
+    /// * `endpoint` - The API that rejected the request.
+    /// * `tokens` - The estimated number of tokens in the rejected request.
+    /// * `reset_in` - How long the API asked the client to wait.
+    pub async fn record_rejection(
+        &self,
+        endpoint: RateLimitEndpoint,
+        tokens: u32,
+        reset_in: Duration,
+    ) {
+        let event = {
+            let mut buckets = self.limiter(endpoint).buckets.lock().unwrap();
+            let now = Instant::now();
+            buckets.refill(now);
+            if buckets.paused_until.is_none() {
+                buckets.back_off(endpoint);
+            }
+            let until = now + reset_in;
+            buckets.paused_until = Some(
+                buckets
+                    .paused_until
+                    .map_or(until, |paused| paused.max(until)),
+            );
+            buckets.event(
+                endpoint,
+                RateLimitEventKind::Rejected,
+                reset_in,
+                tokens,
+                Priority::Normal,
+            )
+        };
+        self.emit(&event);
+    }
+
+    fn limiter(&self, endpoint: RateLimitEndpoint) -> &ApiLimiter {
+        match endpoint {
+            RateLimitEndpoint::Embeddings => &self.embeddings_limiter,
+            RateLimitEndpoint::Reranking => &self.reranking_limiter,
         }
     }
 
-    /// Checks if the API limit has been reached.
-    ///
-    /// # Arguments
-    ///
-    /// * `tokens` - The number of tokens in the request.
-    ///
-    /// # Returns
-    ///
-    /// The duration to wait before making the request.
-    fn check_limit(&mut self, tokens: u32) -> Duration {
-        let now = Instant::now();
-        self.clean_old_entries(now);
+    /// Waits for the caller's turn, then for the buckets to hold the request,
+    /// giving up when that would take longer than `max_wait`.
+    pub(crate) async fn admit(
+        &self,
+        endpoint: RateLimitEndpoint,
+        tokens: u32,
+        priority: Priority,
+        max_wait: Option<Duration>,
+    ) -> Result<Duration, VoyageError> {
+        let exceeded: GiveUp<VoyageError> = |reset_in| VoyageError::RateLimitExceeded {
+            reset_in,
+            metadata: None,
+        };
+        self.wait_turn(
+            endpoint,
+            tokens,
+            priority,
+            max_wait.map(|max_wait| (max_wait, exceeded)),
+        )
+        .await
+    }
+
+    /// Does the waiting of [`admit`](Self::admit). A caller giving up after
+    /// `max_wait` gets the error built from the wait it would have needed;
+    /// waits without a limit use an uninhabited error, as they never give up.
+    async fn wait_turn<E>(
+        &self,
+        endpoint: RateLimitEndpoint,
+        tokens: u32,
+        priority: Priority,
+        max_wait: Option<(Duration, GiveUp<E>)>,
+    ) -> Result<Duration, E> {
+        let limiter = self.limiter(endpoint);
+        let start = Instant::now();
+        let give_up = max_wait.map(|(_, give_up)| give_up);
+        let deadline = max_wait.map(|(max_wait, _)| start + max_wait);
+        let ticket = limiter.queue.join(priority);
 
-        let requests_wait = self.check_rpm_limit(now);
-        let tokens_wait = self.check_tpm_limit(now, tokens);
+        let mut waited = false;
+        let mut delayed = false;
+        loop {
+            let changed = limiter.queue.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
 
-        let wait_time = requests_wait.max(tokens_wait);
-        if !wait_time.is_zero() {
-            info!("Rate limit reached. Wait time: {:?}", wait_time);
+            let now = Instant::now();
+            let (wake_at, event) = {
+                let mut buckets = limiter.buckets.lock().unwrap();
+                buckets.refill(now);
+                let wait = buckets.wait(now, tokens);
+                let first = ticket.is_first();
+                if first && wait.is_zero() {
+                    buckets.take(tokens);
+                    return Ok(if waited { now - start } else { Duration::ZERO });
+                }
+                // Callers behind the head wait at least until the head is served
+                let too_late = |deadline: Instant| {
+                    !first && deadline <= now
+                        || now.checked_add(wait).is_none_or(|at| at > deadline)
+                };
+                if let Some(give_up) = give_up.filter(|_| deadline.is_some_and(too_late)) {
+                    warn!(
+                        "Rate limit wait of {:?} for {} exceeds the allowed wait",
+                        wait,
+                        endpoint.as_str()
+                    );
+                    return Err(give_up(wait));
+                }
+                let event = (first && !delayed).then(|| {
+                    buckets.event(
+                        endpoint,
+                        RateLimitEventKind::Delayed,
+                        wait,
+                        tokens,
+                        priority,
+                    )
+                });
+                // The head sleeps until the buckets hold its request, the
+                // others until the queue changes
+                let until = if first { now.checked_add(wait) } else { None };
+                (until.into_iter().chain(deadline).min(), event)
+            };
+            if let Some(event) = event {
+                info!("Rate limit reached. Wait time: {:?}", event.wait);
+                self.emit(&event);
+                delayed = true;
+            }
+            // A caller of higher priority joining the queue wakes the head,
+            // which then yields its turn
+            match wake_at {
+                Some(at) => tokio::select! {
+                    _ = sleep_until(at) => {}
+                    _ = changed => {}
+                },
+                None => changed.await,
+            }
+            waited = true;
+        }
+    }
+
+    fn check_limit(&self, endpoint: RateLimitEndpoint, tokens: u32) -> Duration {
+        let (wait_time, event) = {
+            let mut buckets = self.limiter(endpoint).buckets.lock().unwrap();
+            let now = Instant::now();
+            buckets.refill(now);
+            let wait_time = buckets.wait(now, tokens);
+            let event = (!wait_time.is_zero()).then(|| {
+                buckets.event(
+                    endpoint,
+                    RateLimitEventKind::Delayed,
+                    wait_time,
+                    tokens,
+                    Priority::Normal,
+                )
+            });
+            (wait_time, event)
+        };
+        if let Some(event) = event {
+            self.emit(&event);
         }
         wait_time
     }
 
-    /// Updates the usage for the API.
-    ///
-    /// # Arguments
-    ///
-    /// * `tokens` - The number of tokens used in the request.
-    fn update_usage(&mut self, tokens: u32) {
-        let now = Instant::now();
-        self.requests.push_back(now);
-        self.tokens.push_back((now, tokens));
-        debug!(
-            "Updated usage. Total requests: {}, Total tokens: {}",
-            self.requests.len(),
-            self.tokens.iter().map(|&(_, t)| t).sum::<u32>()
+    fn take(&self, endpoint: RateLimitEndpoint, tokens: u32) {
+        let mut buckets = self.limiter(endpoint).buckets.lock().unwrap();
+        buckets.refill(Instant::now());
+        buckets.take(tokens);
+    }
+
+    /// Logs the event and forwards it to the registered callback.
+    fn emit(&self, event: &RateLimitEvent) {
+        info!(
+            target: RATE_LIMIT_LOG_TARGET,
+            "endpoint={} kind={:?} priority={} wait_ms={} tokens_requested={} available_requests={} available_tokens={} rpm_limit={} tpm_limit={}",
+            event.endpoint.as_str(),
+            event.kind,
+            event.priority,
+            event.wait.as_millis(),
+            event.tokens_requested,
+            event.available_requests,
+            event.available_tokens,
+            event.rpm_limit,
+            event.tpm_limit
         );
+        if let Some(callback) = &self.on_event {
+            callback(event);
+        }
     }
+}
 
-    /// Removes entries older than one minute.
-    ///
-    /// # Arguments
-    ///
-    /// * `now` - The current time.
-    fn clean_old_entries(&mut self, now: Instant) {
-        let one_minute_ago = now - Duration::from_secs(60);
-        let old_requests = self.requests.len();
-        let old_tokens = self.tokens.len();
-        self.requests.retain(|&time| time > one_minute_ago);
-        self.tokens.retain(|&(time, _)| time > one_minute_ago);
+impl ApiLimiter {
+    fn new(limit: RateLimit) -> Self {
         debug!(
-            "Cleaned old entries. Removed requests: {}, Removed token entries: {}",
-            old_requests - self.requests.len(),
-            old_tokens - self.tokens.len()
+            "Creating new ApiLimiter with RPM: {}, TPM: {}, burst: {}/{}",
+            limit.requests_per_minute,
+            limit.tokens_per_minute,
+            limit.request_burst,
+            limit.token_burst
         );
+        Self {
+            queue: WaitQueue::default(),
+            buckets: std::sync::Mutex::new(Buckets {
+                limit,
+                requests: Bucket::full(limit.request_burst, limit.requests_per_minute),
+                tokens: Bucket::full(limit.token_burst, limit.tokens_per_minute),
+                updated: Instant::now(),
+                paused_until: None,
+                adaptive: None,
+                rate: 1.0,
+            }),
+        }
     }
+}
 
-    /// Checks if the requests per minute limit has been reached.
-    ///
-    /// # Arguments
-    ///
-    /// * `now` - The current time.
-    ///
-    /// # Returns
-    ///
-    /// The duration to wait before making the request.
-    fn check_rpm_limit(&self, now: Instant) -> Duration {
-        if self.requests.len() as u32 >= self.rpm_limit {
-            if let Some(&oldest) = self.requests.front() {
-                let wait_time = oldest + Duration::from_secs(60) - now;
-                if wait_time.as_secs() > 0 {
-                    warn!("RPM limit reached. Wait time: {:?}", wait_time);
-                    return wait_time;
-                }
-            }
+impl Buckets {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.requests.refill(elapsed);
+        self.tokens.refill(elapsed);
+        if let Some(adaptive) = self.adaptive.filter(|_| self.rate < 1.0) {
+            // Limits do not recover while the endpoint is paused
+            let recovering = self.paused_until.map_or(elapsed, |until| {
+                now.saturating_duration_since(until.max(self.updated))
+                    .as_secs_f64()
+            });
+            self.set_rate(self.rate + recovering / 60.0 * adaptive.increase_per_minute);
+        }
+        self.updated = now;
+        if self.paused_until.is_some_and(|until| until <= now) {
+            self.paused_until = None;
         }
-        Duration::from_secs(0)
     }
 
-    /// Checks if the tokens per minute limit has been reached.
-    ///
-    /// # Arguments
-    ///
-    /// * `now` - The current time.
-    /// * `new_tokens` - The number of tokens in the new request.
-    ///
-    /// # Returns
-    ///
-    /// The duration to wait before making the request.
-    fn check_tpm_limit(&self, now: Instant, new_tokens: u32) -> Duration {
-        let current_tokens: u32 = self.tokens.iter().map(|&(_, tokens)| tokens).sum();
-        if current_tokens + new_tokens > self.tpm_limit {
-            if let Some(&(oldest, _)) = self.tokens.front() {
-                let wait_time = oldest + Duration::from_secs(60) - now;
-                if wait_time.as_secs() > 0 {
-                    warn!(
-                        "TPM limit reached. Current tokens: {}, New tokens: {}, Wait time: {:?}",
-                        current_tokens, new_tokens, wait_time
-                    );
-                    return wait_time;
-                }
-            }
+    /// Cuts the effective limits after a rejection, if adaptive throttling
+    /// is enabled.
+    fn back_off(&mut self, endpoint: RateLimitEndpoint) {
+        if let Some(adaptive) = self.adaptive {
+            self.set_rate((self.rate * adaptive.decrease).max(adaptive.min_rate));
+            info!(
+                "Adaptive throttling reduced {} limits to {:.0}%",
+                endpoint.as_str(),
+                self.rate * 100.0
+            );
+        }
+    }
+
+    fn set_rate(&mut self, rate: f64) {
+        self.rate = rate.min(1.0);
+        let limit = self.limit;
+        self.requests
+            .resize(limit.request_burst, limit.requests_per_minute, self.rate);
+        self.tokens
+            .resize(limit.token_burst, limit.tokens_per_minute, self.rate);
+    }
+
+    /// How long until a request of `tokens` tokens fits in both buckets.
+    fn wait(&self, now: Instant, tokens: u32) -> Duration {
+        let paused = self
+            .paused_until
+            .map_or(Duration::ZERO, |until| until.saturating_duration_since(now));
+        paused
+            .max(self.requests.wait_for(1.0))
+            .max(self.tokens.wait_for(tokens as f64))
+    }
+
+    fn take(&mut self, tokens: u32) {
+        self.requests.take(1.0);
+        self.tokens.take(tokens as f64);
+    }
+
+    /// Builds an event describing the current bucket levels.
+    fn event(
+        &self,
+        endpoint: RateLimitEndpoint,
+        kind: RateLimitEventKind,
+        wait: Duration,
+        tokens_requested: u32,
+        priority: Priority,
+    ) -> RateLimitEvent {
+        RateLimitEvent {
+            endpoint,
+            kind,
+            wait,
+            tokens_requested,
+            priority,
+            available_requests: self.requests.available.max(0.0) as u32,
+            available_tokens: self.tokens.available.max(0.0) as u32,
+            rpm_limit: self.limit.requests_per_minute,
+            tpm_limit: self.limit.tokens_per_minute,
         }
-        Duration::from_secs(0)
+    }
+}
+
+impl Bucket {
+    fn full(capacity: u32, per_minute: u32) -> Self {
+        Self {
+            capacity: capacity as f64,
+            available: capacity as f64,
+            per_second: per_minute as f64 / 60.0,
+        }
+    }
+
+    fn refill(&mut self, elapsed_secs: f64) {
+        self.available = (self.available + elapsed_secs * self.per_second).min(self.capacity);
+    }
+
+    /// Scales the capacity and refill rate to `rate` of the given limits.
+    fn resize(&mut self, capacity: u32, per_minute: u32, rate: f64) {
+        self.capacity = (capacity as f64 * rate).max(1.0);
+        self.per_second = per_minute as f64 * rate / 60.0;
+        self.available = self.available.min(self.capacity);
+    }
+
+    /// How long until `amount` is available. Amounts over the capacity wait
+    /// for a full bucket.
+    fn wait_for(&self, amount: f64) -> Duration {
+        let missing = amount.min(self.capacity) - self.available;
+        if missing <= 0.0 {
+            Duration::ZERO
+        } else if self.per_second <= 0.0 {
+            Duration::MAX
+        } else {
+            Duration::from_secs_f64(missing / self.per_second)
+        }
+    }
+
+    fn take(&mut self, amount: f64) {
+        self.available -= amount.min(self.capacity);
     }
 }
 
@@ -0,0 +1,87 @@
+//! Coalescing of identical requests in flight ("singleflight").
+//!
+//! Callers that run the same request while an earlier one is still in flight
+//! wait for its result instead of sending their own. If the caller running
+//! the request is cancelled, one of the waiting callers takes over; if every
+//! caller is cancelled, the request is forgotten.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use log::debug;
+use tokio::sync::OnceCell;
+
+type Flights<T> = Mutex<HashMap<String, Arc<OnceCell<T>>>>;
+
+/// Requests in flight, by key. Clones share the same requests.
+#[derive(Debug)]
+pub(crate) struct InFlight<T> {
+    flights: Arc<Flights<T>>,
+}
+
+impl<T> Clone for InFlight<T> {
+    fn clone(&self) -> Self {
+        Self {
+            flights: self.flights.clone(),
+        }
+    }
+}
+
+impl<T> Default for InFlight<T> {
+    fn default() -> Self {
+        Self {
+            flights: Arc::default(),
+        }
+    }
+}
+
+impl<T> InFlight<T> {
+    /// Runs the future made by `run` unless a request with the same `key` is
+    /// in flight, and returns a copy of the result made by `share`.
+    pub(crate) async fn run<F, Fut>(&self, key: String, run: F, share: impl Fn(&T) -> T) -> T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let flight = {
+            let mut flights = self.flights.lock().unwrap_or_else(PoisonError::into_inner);
+            if flights.contains_key(&key) {
+                debug!("Coalescing request with an identical one in flight");
+            }
+            flights.entry(key.clone()).or_default().clone()
+        };
+        let retire = Retire {
+            flights: &self.flights,
+            key,
+            flight,
+        };
+        share(retire.flight.get_or_init(run).await)
+    }
+}
+
+/// Held by every caller of a flight, including while it is cancelled, to
+/// retire the flight when the caller is done with it.
+struct Retire<'a, T> {
+    flights: &'a Flights<T>,
+    key: String,
+    flight: Arc<OnceCell<T>>,
+}
+
+impl<T> Drop for Retire<'_, T> {
+    fn drop(&mut self) {
+        let mut flights = self.flights.lock().unwrap_or_else(PoisonError::into_inner);
+        // The first caller to finish retires the flight so later requests are
+        // sent again, and the last caller to be cancelled so that it does not
+        // linger; either way unless it was already replaced. Callers join
+        // under the lock, so the count of holders is stable here
+        let done = self.flight.initialized() || Arc::strong_count(&self.flight) == 2;
+        if done
+            && flights
+                .get(&self.key)
+                .is_some_and(|current| Arc::ptr_eq(current, &self.flight))
+        {
+            flights.remove(&self.key);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Caps on the number of requests in flight.
+//!
+//! Rate limits bound how many requests and tokens are sent per minute, not
+//! how many are open at once: a burst of tasks within the budget would
+//! otherwise open a connection each. A [`ConcurrencyLimiter`] holds
+//! embeddings and rerank requests back once `max_in_flight` of them are
+//! waiting for a response, overall or per endpoint, and serves them in
+//! arrival order as responses come in. [`stats`](ConcurrencyLimiter::stats)
+//! reports how long requests queued.
+//!
+//! Set the limits with
+//! [`VoyageConfig::with_max_in_flight`](crate::VoyageConfig::with_max_in_flight)
+//! and
+//! [`with_max_in_flight_for`](crate::VoyageConfig::with_max_in_flight_for).
+//! Clones share their permits, so the clients created from one configuration
+//! share the limits.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::debug;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use crate::client::RateLimitEndpoint;
+use crate::errors::VoyageError;
+
+/// How long requests waited for a [`ConcurrencyLimiter`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConcurrencyStats {
+    /// Requests let through
+    pub requests: u64,
+    /// Requests that had to wait for a slot
+    pub queued: u64,
+    /// Total time requests waited
+    pub total_wait: Duration,
+    /// Longest time a request waited
+    pub max_wait: Duration,
+}
+
+impl ConcurrencyStats {
+    /// Mean wait over all requests let through.
+    pub fn mean_wait(&self) -> Duration {
+        match self.requests {
+            0 => Duration::ZERO,
+            requests => self.total_wait.div_f64(requests as f64),
+        }
+    }
+
+    fn record(&mut self, waited: Duration) {
+        self.requests += 1;
+        if !waited.is_zero() {
+            self.queued += 1;
+        }
+        self.total_wait += waited;
+        self.max_wait = self.max_wait.max(waited);
+    }
+}
+
+/// A slot for one request, released when dropped.
+#[derive(Debug)]
+pub struct ConcurrencyPermit {
+    _global: Option<OwnedSemaphorePermit>,
+    _endpoint: Option<OwnedSemaphorePermit>,
+}
+
+/// Limits the requests in flight, overall and per endpoint. Unlimited by
+/// default.
+#[derive(Debug, Clone, Default)]
+pub struct ConcurrencyLimiter {
+    global: Option<Arc<Semaphore>>,
+    endpoints: HashMap<RateLimitEndpoint, Arc<Semaphore>>,
+    stats: Arc<Mutex<HashMap<RateLimitEndpoint, ConcurrencyStats>>>,
+}
+
+impl ConcurrencyLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows at most `max_in_flight` requests at once over all endpoints.
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.global = Some(Arc::new(Semaphore::new(max_in_flight.max(1))));
+        self
+    }
+
+    /// Allows at most `max_in_flight` requests to `endpoint` at once.
+    pub fn with_max_in_flight_for(
+        mut self,
+        endpoint: RateLimitEndpoint,
+        max_in_flight: usize,
+    ) -> Self {
+        self.endpoints
+            .insert(endpoint, Arc::new(Semaphore::new(max_in_flight.max(1))));
+        self
+    }
+
+    /// Whether any limit is set.
+    pub fn is_limited(&self) -> bool {
+        self.global.is_some() || !self.endpoints.is_empty()
+    }
+
+    /// Waits for a slot for a request to `endpoint`, first under the
+    /// endpoint's limit, then under the global one.
+    pub async fn acquire(&self, endpoint: RateLimitEndpoint) -> ConcurrencyPermit {
+        let start = Instant::now();
+        let acquire = |semaphore: Option<&Arc<Semaphore>>| {
+            let semaphore = semaphore.cloned();
+            async move {
+                match semaphore {
+                    Some(semaphore) => semaphore.acquire_owned().await.ok(),
+                    None => None,
+                }
+            }
+        };
+        let endpoint_permit = acquire(self.endpoints.get(&endpoint)).await;
+        let global_permit = acquire(self.global.as_ref()).await;
+        let waited = start.elapsed();
+        if !waited.is_zero() {
+            debug!(
+                "Waited {:?} for a free {} request slot",
+                waited,
+                endpoint.as_str()
+            );
+        }
+        self.record(endpoint, waited);
+        ConcurrencyPermit {
+            _global: global_permit,
+            _endpoint: endpoint_permit,
+        }
+    }
+
+    /// A slot for a request to `endpoint` if one is free right away.
+    pub fn try_acquire(&self, endpoint: RateLimitEndpoint) -> Option<ConcurrencyPermit> {
+        let try_acquire = |semaphore: Option<&Arc<Semaphore>>| match semaphore {
+            Some(semaphore) => semaphore.clone().try_acquire_owned().map(Some),
+            None => Ok(None),
+        };
+        let endpoint_permit = try_acquire(self.endpoints.get(&endpoint)).ok()?;
+        let global_permit = try_acquire(self.global.as_ref()).ok()?;
+        self.record(endpoint, Duration::ZERO);
+        Some(ConcurrencyPermit {
+            _global: global_permit,
+            _endpoint: endpoint_permit,
+        })
+    }
+
+    /// A slot for a request to `endpoint`. A `hedge` does not queue and
+    /// fails if no slot is free.
+    pub(crate) async fn admit(
+        &self,
+        endpoint: RateLimitEndpoint,
+        hedge: bool,
+    ) -> Result<ConcurrencyPermit, VoyageError> {
+        if !hedge {
+            return Ok(self.acquire(endpoint).await);
+        }
+        self.try_acquire(endpoint).ok_or_else(|| {
+            VoyageError::Other(format!(
+                "No free {} request slot for a hedge",
+                endpoint.as_str()
+            ))
+        })
+    }
+
+    /// Requests to `endpoint` that may start now; `None` when unlimited.
+    pub fn available(&self, endpoint: RateLimitEndpoint) -> Option<usize> {
+        [self.endpoints.get(&endpoint), self.global.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|semaphore| semaphore.available_permits())
+            .min()
+    }
+
+    /// Queue wait statistics of the requests to `endpoint`.
+    pub fn stats(&self, endpoint: RateLimitEndpoint) -> ConcurrencyStats {
+        let stats = self.stats.lock().unwrap();
+        stats.get(&endpoint).copied().unwrap_or_default()
+    }
+
+    fn record(&self, endpoint: RateLimitEndpoint, waited: Duration) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.entry(endpoint).or_default().record(waited);
+    }
+}
@@ -1,25 +1,46 @@
-use crate::client::RateLimiter;
+use crate::client::{Priority, RateLimitEndpoint, RateLimiter};
+use crate::client::coalesce::InFlight;
+use crate::client::retry::retry_after;
 use crate::config::VoyageConfig;
+use crate::models::request_id::{RequestId, IDEMPOTENCY_KEY_HEADER};
+use crate::models::response_headers::ResponseMetadata;
+use crate::models::tokens::estimate_tokens;
+use crate::builder::validation::MAX_EMBEDDING_INPUTS;
+use crate::models::embedding::Embedding;
 use crate::models::embeddings::{
-    CodeEmbedding, EmbeddingData, EmbeddingsInput, EmbeddingsRequest, EmbeddingsResponse, InputType,
+    EmbedOptions, EmbeddingsInput, IndexedEmbedding, EmbeddingsRequest, EmbeddingsResponse, InputType,
+    MarkdownChunkEmbedding,
 };
-use crate::utils::{extract_code_blocks, parse_rust_ast};
+#[cfg(feature = "ast")]
+use crate::code::{extract_code_blocks, parse_rust_ast, CodeEmbedding};
+use crate::transport::{HttpRequest, Transport};
+use crate::utils::{chunk_markdown, MarkdownChunk};
 use crate::VoyageError;
 
 use log::{debug, info, warn};
-use reqwest::Client as ReqwestClient;
 use std::sync::Arc;
-use tokio::time::sleep;
+use std::time::Duration;
+use tokio::sync::{mpsc, Semaphore};
+use tokio_stream::wrappers::ReceiverStream;
 
 /// Base URL for the Voyage AI API.
+#[deprecated(note = "use `VoyageConfig::endpoint`, which honors the configured base URL and API version")]
 pub const BASE_URL: &str = "https://api.voyageai.com/v1";
 
+/// Default number of texts per request in [`Client::embed_stream`].
+pub const DEFAULT_STREAM_BATCH_SIZE: usize = 32;
+
+/// Default number of requests in flight in [`Client::embed_stream`].
+pub const DEFAULT_STREAM_CONCURRENCY: usize = 4;
+
 /// Client for interacting with the Voyage AI embeddings API.
 #[derive(Debug, Clone)]
 pub struct Client {
-    client: ReqwestClient,
+    transport: Arc<dyn Transport>,
     config: VoyageConfig,
     rate_limiter: Arc<RateLimiter>,
+    /// Requests in flight, shared when coalescing is enabled
+    in_flight: InFlight<Result<EmbeddingsResponse, VoyageError>>,
 }
 
 impl Client {
@@ -31,11 +52,13 @@ impl Client {
             truncation: None,
             encoding_format: None,
         };
-        self.create_embedding(&request)
-            .await
-            .map(|response| response.data[0].embedding.clone())
+        let response = self.create_embedding(&request).await?;
+        first_embedding(response)
     }
 
+    /// Embeds `code` as text and, parsed as Rust, as a syntax tree. Requires
+    /// the `ast` feature.
+    #[cfg(feature = "ast")]
     pub async fn embed_code(&self, code: &str) -> Result<CodeEmbedding, VoyageError> {
         // Get text embedding
         let text_request = EmbeddingsRequest {
@@ -45,10 +68,7 @@ impl Client {
             truncation: None,
             encoding_format: None,
         };
-        let text_embedding = self
-            .create_embedding(&text_request)
-            .await
-            .map(|response| response.data[0].embedding.clone())?;
+        let text_embedding = first_embedding(self.create_embedding(&text_request).await?)?;
 
         // Parse and get AST embedding
         let serializable_ast =
@@ -63,10 +83,7 @@ impl Client {
             truncation: None,
             encoding_format: None,
         };
-        let ast_embedding = self
-            .create_embedding(&ast_request)
-            .await
-            .map(|response| response.data[0].embedding.clone())?;
+        let ast_embedding = first_embedding(self.create_embedding(&ast_request).await?)?;
 
         Ok(CodeEmbedding {
             text_embedding,
@@ -74,6 +91,9 @@ impl Client {
         })
     }
 
+    /// Embeds the Rust code blocks of `markdown` with
+    /// [`embed_code`](Self::embed_code). Requires the `ast` feature.
+    #[cfg(feature = "ast")]
     pub async fn embed_markdown(&self, markdown: &str) -> Result<Vec<CodeEmbedding>, VoyageError> {
         let code_blocks = extract_code_blocks(markdown);
         let mut embeddings = Vec::new();
@@ -90,6 +110,25 @@ impl Client {
         Ok(embeddings)
     }
 
+    /// Splits a Markdown document along its headings, keeping code blocks
+    /// intact (see [`chunk_markdown`]), and embeds every chunk as a document
+    /// together with its heading breadcrumb. Chunks hold at most `max_chars`
+    /// characters, except for long code blocks.
+    pub async fn embed_markdown_document(
+        &self,
+        markdown: &str,
+        max_chars: usize,
+    ) -> Result<Vec<MarkdownChunkEmbedding>, VoyageError> {
+        let chunks = chunk_markdown(markdown, max_chars);
+        let texts: Vec<String> = chunks.iter().map(MarkdownChunk::text_with_breadcrumb).collect();
+        let embeddings = self.embed_documents(&texts).await?;
+        Ok(chunks
+            .into_iter()
+            .zip(embeddings)
+            .map(|(chunk, embedding)| MarkdownChunkEmbedding { chunk, embedding })
+            .collect())
+    }
+
     pub async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, VoyageError> {
         if texts.is_empty() {
             return Ok(Vec::new());
@@ -102,79 +141,390 @@ impl Client {
             encoding_format: None,
         };
         self.create_embedding(&request)
-            .await
-            .map(|response| response.data.into_iter().map(|d| d.embedding).collect())
+            .await?
+            .into_embeddings(texts.len())
+    }
+
+    /// Embeds a search query with `input_type` set to `query`, as Voyage
+    /// recommends for the query side of retrieval.
+    pub async fn embed_query(&self, query: &str) -> Result<Vec<f32>, VoyageError> {
+        let request = EmbeddingsRequest {
+            input: EmbeddingsInput::Single(query.to_string()),
+            model: self.config.embedding_model,
+            input_type: Some(InputType::Query),
+            truncation: None,
+            encoding_format: None,
+        };
+        first_embedding(self.create_embedding(&request).await?)
+    }
+
+    /// Embeds corpus texts with `input_type` set to `document`, in as many
+    /// requests as the API's input limit requires. Embeddings are in the
+    /// order of `documents`.
+    pub async fn embed_documents(&self, documents: &[String]) -> Result<Vec<Vec<f32>>, VoyageError> {
+        self.embed_all(documents, Some(InputType::Document)).await
+    }
+
+    /// Embeds `text` with the input type, truncation and encoding of
+    /// `options`.
+    pub async fn embed_with(&self, text: &str, options: EmbedOptions) -> Result<Vec<f32>, VoyageError> {
+        let request = options.request(EmbeddingsInput::Single(text.to_string()), self.config.embedding_model);
+        first_embedding(self.create_embedding(&request).await?)
+    }
+
+    /// Embeds `texts` with the input type, truncation and encoding of
+    /// `options`, in requests of at most [`MAX_EMBEDDING_INPUTS`] texts.
+    /// Embeddings are in the order of `texts`.
+    pub async fn embed_batch_with(
+        &self,
+        texts: &[String],
+        options: EmbedOptions,
+    ) -> Result<Vec<Vec<f32>>, VoyageError> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(MAX_EMBEDDING_INPUTS) {
+            let request = options.request(EmbeddingsInput::Multiple(batch.to_vec()), self.config.embedding_model);
+            embeddings.extend(
+                self.create_embedding(&request)
+                    .await?
+                    .into_embeddings(batch.len())?,
+            );
+        }
+        Ok(embeddings)
+    }
+
+    /// Like [`embed_batch_with`](Self::embed_batch_with), returning
+    /// [`Embedding`]s labeled with the configured model, which refuse to be
+    /// compared with embeddings of another model.
+    pub async fn embed_typed(
+        &self,
+        texts: &[String],
+        options: EmbedOptions,
+    ) -> Result<Vec<Embedding>, VoyageError> {
+        let model = self.config.embedding_model;
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(MAX_EMBEDDING_INPUTS) {
+            let request = options.request(EmbeddingsInput::Multiple(batch.to_vec()), model);
+            embeddings.extend(
+                self.create_embedding(&request)
+                    .await?
+                    .into_typed_embeddings(batch.len(), model)?,
+            );
+        }
+        Ok(embeddings)
+    }
+
+    /// Embeds `texts` as `input_type` in requests of at most
+    /// [`MAX_EMBEDDING_INPUTS`] texts, returning the embeddings in input order.
+    pub(crate) async fn embed_all(
+        &self,
+        texts: &[String],
+        input_type: Option<InputType>,
+    ) -> Result<Vec<Vec<f32>>, VoyageError> {
+        let options = EmbedOptions {
+            input_type,
+            ..EmbedOptions::default()
+        };
+        self.embed_batch_with(texts, options).await
+    }
+
+    /// Cosine similarity of two texts, embedded together in one request as
+    /// documents so that the score does not depend on their order.
+    pub async fn similarity(&self, text_a: &str, text_b: &str) -> Result<f32, VoyageError> {
+        let texts = [text_a.to_string(), text_b.to_string()];
+        let embeddings = self.embed_all(&texts, Some(InputType::Document)).await?;
+        Ok(crate::cosine_similarity(&embeddings[0], &embeddings[1]))
+    }
+
+    /// Cosine similarities between every pair of `texts`, embedded as
+    /// documents: entry `[i][j]` compares `texts[i]` with `texts[j]`.
+    pub async fn similarity_matrix(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, VoyageError> {
+        let embeddings = self.embed_all(texts, Some(InputType::Document)).await?;
+        Ok(cosine_similarity_matrix(&embeddings))
     }
 }
 
+/// Cosine similarities between every pair of `embeddings`. The matrix is
+/// symmetric, computed once per pair.
+pub fn cosine_similarity_matrix(embeddings: &[Vec<f32>]) -> Vec<Vec<f32>> {
+    let n = embeddings.len();
+    let mut matrix = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in i..n {
+            let similarity = crate::cosine_similarity(&embeddings[i], &embeddings[j]);
+            matrix[i][j] = similarity;
+            matrix[j][i] = similarity;
+        }
+    }
+    matrix
+}
+
+impl Client {
+    /// Embeds `texts` in sub-batches of [`DEFAULT_STREAM_BATCH_SIZE`] sent
+    /// concurrently, yielding each embedding as soon as its sub-batch completes.
+    pub fn embed_stream(&self, texts: Vec<String>) -> ReceiverStream<IndexedEmbedding> {
+        self.embed_stream_with(texts, DEFAULT_STREAM_BATCH_SIZE, DEFAULT_STREAM_CONCURRENCY)
+    }
+
+    /// Embeds `texts` in sub-batches of `batch_size`, with at most `concurrency`
+    /// requests in flight. Each request is still paced by the rate limiter.
+    ///
+    /// Embeddings are yielded in completion order; [`IndexedEmbedding::index`]
+    /// is the position of the text in `texts`. A failed sub-batch is logged and
+    /// its embeddings are skipped.
+    pub fn embed_stream_with(
+        &self,
+        texts: Vec<String>,
+        batch_size: usize,
+        concurrency: usize,
+    ) -> ReceiverStream<IndexedEmbedding> {
+        let batch_size = batch_size.clamp(1, MAX_EMBEDDING_INPUTS);
+        let (tx, rx) = mpsc::channel(batch_size);
+        let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+        let client = self.clone();
+
+        self.config.lifecycle.spawn_producer(tx.clone(), async move {
+            let mut texts = texts.into_iter();
+            let mut start = 0;
+            loop {
+                let batch: Vec<String> = texts.by_ref().take(batch_size).collect();
+                if batch.is_empty() || tx.is_closed() {
+                    break;
+                }
+                let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                    break;
+                };
+                let client = client.clone();
+                let tx = tx.clone();
+                let offset = start;
+                start += batch.len();
+
+                client.config.lifecycle.clone().spawn_producer(tx.clone(), async move {
+                    let _permit = permit;
+                    let count = batch.len();
+                    let request = EmbeddingsRequest {
+                        input: EmbeddingsInput::Multiple(batch),
+                        model: client.config.embedding_model,
+                        input_type: None,
+                        truncation: None,
+                        encoding_format: None,
+                    };
+                    let embeddings = client
+                        .create_embedding(&request)
+                        .await
+                        .and_then(|response| response.into_embeddings(count));
+                    match embeddings {
+                        Ok(embeddings) => {
+                            for (i, embedding) in embeddings.into_iter().enumerate() {
+                                let item = IndexedEmbedding {
+                                    index: offset + i,
+                                    embedding,
+                                };
+                                if tx.send(item).await.is_err() {
+                                    break; // receiver dropped
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            log::error!(
+                                "Error embedding inputs {}..{}: {:?}",
+                                offset,
+                                offset + count,
+                                e
+                            );
+                        }
+                    }
+                });
+            }
+        });
+
+        ReceiverStream::new(rx)
+    }
+}
+
+fn first_embedding(response: EmbeddingsResponse) -> Result<Vec<f32>, VoyageError> {
+    response
+        .into_embeddings(1)?
+        .pop()
+        .ok_or(VoyageError::EmptyResponse)
+}
+
 impl Client {
     /// Creates a new `EmbeddingClient` instance.
     pub fn new(config: VoyageConfig) -> Self {
         debug!("Creating new EmbeddingClient");
         Self {
-            client: ReqwestClient::new(),
+            transport: config.transport(),
+            rate_limiter: Arc::new(config.rate_limiter()),
             config,
-            rate_limiter: Arc::new(RateLimiter::new()),
+            in_flight: InFlight::default(),
         }
     }
 
+    /// Shares the given rate limiter instead of the client's own.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Schedules this client's requests with `priority` when they wait for
+    /// the rate limiter.
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.config.priority = priority;
+        self
+    }
+
+    /// The rate limiter pacing this client's requests.
+    pub fn rate_limiter(&self) -> &Arc<RateLimiter> {
+        &self.rate_limiter
+    }
+
+    /// The configuration this client was created with.
+    pub fn config(&self) -> &VoyageConfig {
+        &self.config
+    }
+
     /// Creates embeddings for the given request.
+    ///
+    /// With [request coalescing](VoyageConfig::with_request_coalescing)
+    /// enabled, concurrent calls with identical requests share one API call.
     pub async fn create_embedding(
         &self,
         request: &EmbeddingsRequest,
     ) -> Result<EmbeddingsResponse, VoyageError> {
-        let url = format!("{}/embeddings", BASE_URL);
+        if !self.config.coalesce_requests {
+            return self.send_embedding(request, None).await;
+        }
+        let key = serde_json::to_string(request)?;
+        self.in_flight
+            .run(key, || self.send_embedding(request, None), |result| match result {
+                Ok(response) => Ok(response.clone()),
+                Err(e) => Err(e.duplicate()),
+            })
+            .await
+    }
+
+    /// Like [`create_embedding`](Self::create_embedding), sending `id` as
+    /// the idempotency key whether or not
+    /// [idempotency keys](VoyageConfig::with_idempotency_keys) are enabled.
+    /// Replaying a request with the id of an earlier attempt, e.g. one
+    /// recorded before a crash, lets the server and usage records treat both
+    /// as the same request. Not coalesced with other calls.
+    pub async fn create_embedding_with_id(
+        &self,
+        request: &EmbeddingsRequest,
+        id: RequestId,
+    ) -> Result<EmbeddingsResponse, VoyageError> {
+        self.send_embedding(request, Some(id)).await
+    }
+
+    async fn send_embedding(
+        &self,
+        request: &EmbeddingsRequest,
+        id: Option<RequestId>,
+    ) -> Result<EmbeddingsResponse, VoyageError> {
+        let _work = self.config.lifecycle.begin()?;
+        // One key for all the retries of the call
+        let id = id.or_else(|| self.config.idempotency_keys.then(RequestId::new));
+        let id = id.as_ref();
+        let hedging = self.config.hedging(RateLimitEndpoint::Embeddings);
+        self.config
+            .retry_policy
+            .run(|| async move {
+                match hedging {
+                    Some(policy) => {
+                        policy
+                            .run(|hedge| async move {
+                                // A hedge is a request of its own, not a retry
+                                let hedge_id = (hedge && id.is_some()).then(RequestId::new);
+                                self.send_embedding_once(request, hedge_id.as_ref().or(id), hedge)
+                                    .await
+                            })
+                            .await
+                    }
+                    None => self.send_embedding_once(request, id, false).await,
+                }
+            })
+            .await
+    }
+
+    /// Sends `request` once. A `hedge` is only sent if the rate limiter has
+    /// room for it right away.
+    async fn send_embedding_once(
+        &self,
+        request: &EmbeddingsRequest,
+        id: Option<&RequestId>,
+        hedge: bool,
+    ) -> Result<EmbeddingsResponse, VoyageError> {
+        let url = self.config.endpoint("embeddings");
         debug!("Creating embedding with URL: {}", url);
 
         let estimated_tokens = self.estimate_tokens(request);
         debug!("Estimated tokens for request: {}", estimated_tokens);
 
-        let wait_time = self
+        let _slot = self
+            .config
+            .concurrency_limiter
+            .admit(RateLimitEndpoint::Embeddings, hedge)
+            .await?;
+        let waited = self
             .rate_limiter
-            .check_embeddings_limit(estimated_tokens)
-            .await;
-        if wait_time.as_secs() > 0 {
-            info!(
-                "Rate limit reached. Waiting for {} seconds",
-                wait_time.as_secs()
-            );
-            sleep(wait_time).await;
+            .admit(
+                RateLimitEndpoint::Embeddings,
+                estimated_tokens,
+                self.config.priority,
+                if hedge {
+                    Some(Duration::ZERO)
+                } else {
+                    self.config.rate_limit_timeout
+                },
+            )
+            .await?;
+        if !waited.is_zero() {
+            info!("Waited {:?} for the rate limiter", waited);
         }
 
-        debug!("Sending embedding request");
-        let response = self
-            .client
-            .post(&url)
-            .bearer_auth(self.config.api_key())
-            .json(&request)
-            .send()
-            .await?;
+        let api_key = self.config.resolve_api_key().await?;
+        match id {
+            Some(id) => debug!("Sending embedding request with idempotency key {}", id),
+            None => debug!("Sending embedding request"),
+        }
+        if self.config.log_policy.logs_payloads() {
+            let body = serde_json::to_string(request)?;
+            debug!("Request body: {}", self.config.log_policy.payload(&body));
+        }
+        let mut http_request = HttpRequest::post(url).bearer_auth(&api_key)?.json(request)?;
+        if let Some(id) = id {
+            http_request = http_request.header(IDEMPOTENCY_KEY_HEADER, id.as_str())?;
+        }
+        let response = self.transport.send(http_request).await?;
 
-        let status = response.status();
-        let text = response.text().await?;
+        let status = response.status;
+        let reset_in = retry_after(&response.headers);
+        let mut headers = ResponseMetadata::from_header_map(&response.headers);
+        headers.idempotency_key = id.cloned();
+        headers.warn_if_deprecated("embeddings");
+        self.rate_limiter
+            .record_quota(RateLimitEndpoint::Embeddings, &headers.rate_limit)
+            .await;
+        let text = response.text();
 
         match status {
             reqwest::StatusCode::OK => {
                 debug!("Embedding request successful");
-                let embeddings_response: EmbeddingsResponse = serde_json::from_str(&text)?;
-
-                let embeddings_response = if embeddings_response.data.is_empty() {
-                    EmbeddingsResponse {
-                        data: vec![EmbeddingData {
-                            object: "embedding".to_string(),
-                            embedding: vec![0.0],
-                            index: 0,
-                        }],
-                        ..embeddings_response
-                    }
-                } else {
-                    embeddings_response
-                };
+                let mut embeddings_response: EmbeddingsResponse = serde_json::from_str(&text)?;
+                embeddings_response.headers = headers;
 
                 self.rate_limiter
-                    .update_embeddings_usage(embeddings_response.usage.total_tokens)
+                    .record_usage(
+                        RateLimitEndpoint::Embeddings,
+                        estimated_tokens,
+                        embeddings_response.usage.total_tokens,
+                    )
                     .await;
 
+                embeddings_response.validate(request.model).inspect_err(|e| {
+                    warn!("Invalid embeddings response: {}", e);
+                })?;
                 Ok(embeddings_response)
             }
             reqwest::StatusCode::UNAUTHORIZED => {
@@ -182,12 +532,29 @@ impl Client {
                 Err(VoyageError::Unauthorized)
             }
             reqwest::StatusCode::FORBIDDEN => {
-                warn!("Forbidden: {}", text);
+                warn!("Forbidden: {}", self.config.log_policy.payload(&text));
                 Err(VoyageError::Forbidden(text))
             }
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                warn!(
+                    "Embedding request rejected by rate limit: {} (request id: {:?})",
+                    self.config.log_policy.payload(&text),
+                    headers.request_id
+                );
+                self.rate_limiter
+                    .record_rejection(RateLimitEndpoint::Embeddings, estimated_tokens, reset_in)
+                    .await;
+                Err(VoyageError::RateLimitExceeded {
+                    reset_in,
+                    metadata: Some(Box::new(headers)),
+                })
+            }
             _ => {
-                warn!("Embedding request failed with status: {}", status);
-                Err(VoyageError::ApiError(status, text))
+                warn!(
+                    "Embedding request failed with status: {} (request id: {:?})",
+                    status, headers.request_id
+                );
+                Err(VoyageError::ApiError(status, text, Box::new(headers)))
             }
         }
     }
@@ -196,19 +563,13 @@ impl Client {
     fn estimate_tokens(&self, request: &EmbeddingsRequest) -> u32 {
         match &request.input {
             EmbeddingsInput::Single(text) => {
-                // Rough estimate: 1 token per 4 characters + small overhead
-                let base_tokens = (text.len() as f32 / 4.0).ceil() as u32;
-                base_tokens + 2
-            }
-            EmbeddingsInput::Multiple(texts) => {
-                // Calculate tokens for each text and sum
-                let mut total = 0;
-                for text in texts {
-                    total += (text.len() as f32 / 4.0).ceil() as u32;
-                }
-                // Add overhead for batch processing
-                total + (2 * texts.len() as u32)
+                // Small per-text overhead on top of the content estimate
+                estimate_tokens(text) as u32 + 2
             }
+            EmbeddingsInput::Multiple(texts) => texts
+                .iter()
+                .map(|text| estimate_tokens(text) as u32 + 2)
+                .sum(),
         }
     }
 }
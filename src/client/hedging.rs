@@ -0,0 +1,178 @@
+//! Hedged requests for latency-sensitive calls.
+//!
+//! With a [`HedgingPolicy`] set for an endpoint, a request that has not
+//! returned within the policy's [`delay`](HedgingPolicy::delay), a high
+//! percentile of the recent latencies, is sent a second time and whichever
+//! copy answers first wins; the other is dropped. Tail latency then comes
+//! close to the percentile at the price of a few extra requests. A hedge is
+//! only sent when the [rate limiter](crate::client::RateLimiter) has room for
+//! it right away, so hedging never delays other requests. Each hedge is a
+//! request of its own and gets its own
+//! [idempotency key](crate::VoyageConfig::with_idempotency_keys), so that the
+//! server does not take it for a retry of the original.
+//!
+//! Enable it per endpoint with
+//! [`VoyageConfig::with_hedging`](crate::VoyageConfig::with_hedging), e.g. for
+//! reranking in interactive search:
+//!
+//! ```no_run
+//! use voyageai::client::{HedgingPolicy, RateLimitEndpoint};
+//! use voyageai::VoyageConfig;
+//!
+//! let config = VoyageConfig::new("pa-...".to_string())
+//!     .with_hedging(RateLimitEndpoint::Reranking, HedgingPolicy::new(0.95));
+//! ```
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::debug;
+use tokio::time::{sleep_until, Instant};
+
+use crate::errors::VoyageError;
+
+/// When to send a duplicate of a slow request. Clones share the recorded
+/// latencies.
+#[derive(Debug, Clone)]
+pub struct HedgingPolicy {
+    percentile: f64,
+    initial_delay: Duration,
+    min_delay: Duration,
+    min_samples: usize,
+    window_size: usize,
+    max_hedges: u32,
+    latencies: Arc<Mutex<VecDeque<Duration>>>,
+}
+
+impl HedgingPolicy {
+    /// Hedges requests slower than the `percentile` (between 0 and 1) of the
+    /// last 100 latencies, at most once per request. Until 20 latencies were
+    /// recorded it hedges after 1 second.
+    pub fn new(percentile: f64) -> Self {
+        Self {
+            percentile: percentile.clamp(0.0, 1.0),
+            initial_delay: Duration::from_secs(1),
+            min_delay: Duration::from_millis(10),
+            min_samples: 20,
+            window_size: 100,
+            max_hedges: 1,
+            latencies: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// The delay used until enough latencies were recorded.
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// The shortest delay, so that a burst of fast responses does not make
+    /// every request hedge.
+    pub fn with_min_delay(mut self, delay: Duration) -> Self {
+        self.min_delay = delay;
+        self
+    }
+
+    /// How many latencies must be recorded before the percentile is used.
+    pub fn with_min_samples(mut self, samples: usize) -> Self {
+        self.min_samples = samples.max(1);
+        self
+    }
+
+    /// How many of the most recent latencies the percentile is taken over.
+    pub fn with_window_size(mut self, size: usize) -> Self {
+        self.window_size = size.max(1);
+        self
+    }
+
+    /// How many duplicates a request may get, one more after each delay.
+    pub fn with_max_hedges(mut self, hedges: u32) -> Self {
+        self.max_hedges = hedges;
+        self
+    }
+
+    /// How long a request may take before it is hedged.
+    pub fn delay(&self) -> Duration {
+        let latencies = self
+            .latencies
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if latencies.len() < self.min_samples {
+            return self.initial_delay.max(self.min_delay);
+        }
+        let mut sorted: Vec<Duration> = latencies.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = (self.percentile * (sorted.len() - 1) as f64).round() as usize;
+        sorted[rank].max(self.min_delay)
+    }
+
+    fn record(&self, latency: Duration) {
+        let mut latencies = self
+            .latencies
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        latencies.push_back(latency);
+        while latencies.len() > self.window_size {
+            latencies.pop_front();
+        }
+    }
+
+    /// Runs `attempt(false)`, adding an `attempt(true)` hedge each time the
+    /// delay passes, up to [`max_hedges`](Self::with_max_hedges). Returns the
+    /// first success, or the first attempt's error when all failed.
+    pub(crate) async fn run<F, Fut, T>(&self, attempt: F) -> Result<T, VoyageError>
+    where
+        F: Fn(bool) -> Fut,
+        Fut: Future<Output = Result<T, VoyageError>>,
+    {
+        // Latencies are measured from the original request, also when a
+        // hedge answers first
+        let started = Instant::now();
+        let tagged = |hedge: bool| {
+            let response = attempt(hedge);
+            async move { (hedge, response.await) }
+        };
+        let mut attempts = FuturesUnordered::new();
+        attempts.push(tagged(false));
+        let mut hedges = 0;
+        let mut error = None;
+        let mut hedge_at = started + self.delay();
+        // The run returns when the last pending attempt fails, so the loop
+        // never waits on an empty set of attempts
+        loop {
+            let can_hedge = hedges < self.max_hedges;
+            tokio::select! {
+                (hedge, result) = attempts.select_next_some() => {
+                    let e = match result {
+                        Ok(response) => {
+                            self.record(started.elapsed());
+                            if hedge {
+                                debug!("Hedged request answered first");
+                            }
+                            return Ok(response);
+                        }
+                        Err(e) => e,
+                    };
+                    // Prefer the original request's error to a hedge's
+                    let e = match error.take() {
+                        Some(original) if hedge => original,
+                        _ => e,
+                    };
+                    if attempts.is_empty() {
+                        return Err(e);
+                    }
+                    error = Some(e);
+                }
+                _ = sleep_until(hedge_at), if can_hedge => {
+                    hedges += 1;
+                    debug!("Request slower than {:?}, sending hedge {}", self.delay(), hedges);
+                    attempts.push(tagged(true));
+                    hedge_at = Instant::now() + self.delay();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Graceful shutdown of the clients.
+//!
+//! Every embeddings and rerank call, and every stream, batch or bulk job
+//! built on them, is tracked by the [`Lifecycle`] of the configuration its
+//! client was created from. [`VoyageAiClient::shutdown`] closes it: new calls
+//! then fail with [`VoyageError::ShuttingDown`], while work accepted before,
+//! including the further calls it makes, such as the rerank step of a
+//! search, runs to completion. Batching layers flush on close: the
+//! [`AutoBatcher`](crate::client::AutoBatcher) sends its queued texts without
+//! waiting out its delay, and a [`BulkEmbedder`](crate::client::BulkEmbedder)
+//! job stops taking documents and embeds the ones it holds.
+//!
+//! [`VoyageAiClient::shutdown`]: crate::VoyageAiClient::shutdown
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::warn;
+use tokio::sync::{mpsc, Notify};
+use tokio::time::{timeout_at, Instant};
+
+use crate::client::tasks::spawn_producer;
+use crate::errors::VoyageError;
+
+tokio::task_local! {
+    /// Set while a task runs work accepted before the shutdown, to the
+    /// address of the state of its lifecycle.
+    static ACCEPTED: usize;
+}
+
+/// What [`VoyageAiClient::shutdown`](crate::VoyageAiClient::shutdown) left
+/// behind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Whether all accepted work finished within the timeout
+    pub drained: bool,
+    /// Calls and jobs still running when the timeout passed
+    pub in_flight: usize,
+    /// Tasks of the client aborted after the timeout
+    pub aborted: usize,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    closed: AtomicBool,
+    in_flight: AtomicUsize,
+    changed: Notify,
+}
+
+/// Tracks the work in flight so that a shutdown can refuse new work and wait
+/// for the rest. Clones share their state.
+#[derive(Debug, Clone, Default)]
+pub struct Lifecycle {
+    state: Arc<State>,
+}
+
+impl Lifecycle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether new work is refused.
+    pub fn is_shutting_down(&self) -> bool {
+        self.state.closed.load(Ordering::SeqCst)
+    }
+
+    /// Calls and jobs running now.
+    pub fn in_flight(&self) -> usize {
+        self.state.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Refuses new work from now on.
+    pub fn close(&self) {
+        self.state.closed.store(true, Ordering::SeqCst);
+        self.state.changed.notify_waiters();
+    }
+
+    /// Waits until no work is in flight, for at most `timeout`. Returns
+    /// whether it got there.
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let changed = self.state.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+            if self.in_flight() == 0 {
+                return true;
+            }
+            if timeout_at(deadline, changed).await.is_err() {
+                return self.in_flight() == 0;
+            }
+        }
+    }
+
+    /// Resolves once the lifecycle is closed.
+    pub(crate) async fn closed(&self) {
+        loop {
+            let changed = self.state.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+            if self.is_shutting_down() {
+                return;
+            }
+            changed.await;
+        }
+    }
+
+    /// Starts a unit of work, refused once closed. Work done within accepted
+    /// work is part of it: it is not refused and not counted again.
+    pub(crate) fn begin(&self) -> Result<WorkGuard, VoyageError> {
+        let id = Arc::as_ptr(&self.state) as usize;
+        if ACCEPTED.try_with(|accepted| *accepted == id) == Ok(true) {
+            return Ok(WorkGuard { state: None });
+        }
+        if self.is_shutting_down() {
+            return Err(VoyageError::ShuttingDown);
+        }
+        Ok(self.hold())
+    }
+
+    /// Counts a unit of work spawned by work already accepted, which may
+    /// outlive it.
+    pub(crate) fn hold(&self) -> WorkGuard {
+        self.state.in_flight.fetch_add(1, Ordering::SeqCst);
+        WorkGuard {
+            state: Some(self.state.clone()),
+        }
+    }
+
+    /// Spawns a stream producer as accepted work; a stream opened after the
+    /// shutdown ends without items.
+    pub(crate) fn spawn_producer<T, F>(&self, tx: mpsc::Sender<T>, producer: F)
+    where
+        T: Send + 'static,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        match self.begin() {
+            Ok(_) => spawn_producer(tx, self.hold().scope(producer)),
+            Err(e) => warn!("Stream not started: {}", e),
+        }
+    }
+}
+
+/// A unit of work in flight, finished when dropped.
+#[derive(Debug)]
+pub(crate) struct WorkGuard {
+    /// `None` for work nested in accepted work
+    state: Option<Arc<State>>,
+}
+
+impl WorkGuard {
+    /// Runs `future` as accepted work, so that the calls it makes go through
+    /// after the shutdown, and finishes the work with it.
+    pub(crate) fn scope<F: Future>(self, future: F) -> impl Future<Output = F::Output> {
+        let id = self.state.as_ref().map_or_else(
+            || ACCEPTED.try_with(|accepted| *accepted).unwrap_or_default(),
+            |state| Arc::as_ptr(state) as usize,
+        );
+        ACCEPTED.scope(id, async move {
+            let _work = self;
+            future.await
+        })
+    }
+}
+
+impl Drop for WorkGuard {
+    fn drop(&mut self) {
+        if let Some(state) = &self.state {
+            if state.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+                state.changed.notify_waiters();
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Reranking without the rerank endpoint.
+//!
+//! [`LocalReranker`] embeds the query and the documents (N+1 texts) and ranks
+//! the documents by the cosine similarity of their embeddings to the query's.
+//! It is cheaper than a cross-encoder and less accurate, which makes it a
+//! degraded mode for when the rerank endpoint is unavailable and a stand-in
+//! for it in tests.
+
+use log::error;
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::builder::validation::MAX_EMBEDDING_INPUTS;
+use crate::client::embeddings_client::Client as EmbeddingsClient;
+use crate::client::rerank_client::{
+    AsyncDocumentSimilarity, AsyncRerankMatch, DocumentSimilarity, RankingStrategy, RerankClient,
+    RerankMatch, RerankRequestBuilder, RerankStreamItem,
+};
+use crate::client::tasks::AbortOnDrop;
+use crate::client::voyage_client::VoyageAiClient;
+use crate::cosine_similarity;
+use crate::errors::VoyageError;
+use crate::models::embeddings::{EmbeddingsInput, EmbeddingsRequest, InputType};
+use crate::models::rerank::{RerankValidationError, Usage};
+use crate::traits::llm::{DocumentSimilarityStream, Reranker};
+
+/// Ranks documents by embedding similarity to the query.
+#[derive(Debug, Clone)]
+pub struct LocalReranker {
+    embeddings_client: EmbeddingsClient,
+}
+
+impl LocalReranker {
+    /// A reranker embedding with `client`'s embeddings client and model.
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self::from_embeddings_client(client.embeddings_client().as_ref().clone())
+    }
+
+    pub fn from_embeddings_client(embeddings_client: EmbeddingsClient) -> Self {
+        Self { embeddings_client }
+    }
+
+    /// Ranks `documents` by cosine similarity to `query`, best first, and
+    /// returns the tokens used to embed them.
+    pub async fn rank(
+        &self,
+        query: &str,
+        documents: Vec<String>,
+    ) -> Result<(Vec<DocumentSimilarity>, Usage), VoyageError> {
+        if query.trim().is_empty() {
+            return Err(RerankValidationError::MissingQuery.into());
+        }
+        if documents.is_empty() {
+            return Err(RerankValidationError::EmptyDocuments.into());
+        }
+
+        let (mut query_embedding, mut total_tokens) = self
+            .embed(vec![query.to_string()], InputType::Query)
+            .await?;
+        let query_embedding = query_embedding.pop().ok_or(VoyageError::EmptyResponse)?;
+
+        let mut embeddings = Vec::with_capacity(documents.len());
+        for batch in documents.chunks(MAX_EMBEDDING_INPUTS) {
+            let (batch_embeddings, tokens) =
+                self.embed(batch.to_vec(), InputType::Document).await?;
+            embeddings.extend(batch_embeddings);
+            total_tokens += tokens;
+        }
+
+        let mut scored: Vec<(f64, String)> = documents
+            .into_iter()
+            .zip(&embeddings)
+            .map(|(document, embedding)| {
+                let similarity = cosine_similarity(&query_embedding, embedding);
+                (f64::from(similarity), document)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let ranked = scored
+            .into_iter()
+            .enumerate()
+            .map(|(rank, (similarity, document))| DocumentSimilarity {
+                rank,
+                similarity,
+                document,
+                strategy: RankingStrategy::Embedding,
+            })
+            .collect();
+        Ok((ranked, Usage { total_tokens }))
+    }
+
+    async fn embed(
+        &self,
+        texts: Vec<String>,
+        input_type: InputType,
+    ) -> Result<(Vec<Vec<f32>>, u32), VoyageError> {
+        let count = texts.len();
+        let request = EmbeddingsRequest {
+            input: EmbeddingsInput::Multiple(texts),
+            model: self.embeddings_client.config().embedding_model,
+            input_type: Some(input_type),
+            truncation: None,
+            encoding_format: None,
+        };
+        let response = self.embeddings_client.create_embedding(&request).await?;
+        let tokens = response.usage.total_tokens;
+        Ok((response.into_embeddings(count)?, tokens))
+    }
+}
+
+impl Reranker for LocalReranker {
+    fn rerank(&self, query: &str, documents: Vec<String>) -> DocumentSimilarityStream {
+        self.find_similar_documents(query, documents)
+    }
+}
+
+impl RerankClient for LocalReranker {
+    fn find_similar_documents(
+        &self,
+        query: &str,
+        documents: Vec<String>,
+    ) -> ReceiverStream<DocumentSimilarity> {
+        let (tx, rx) = mpsc::channel(16);
+        let reranker = self.clone();
+        let query = query.to_string();
+
+        self.embeddings_client
+            .config()
+            .lifecycle
+            .spawn_producer(tx.clone(), async move {
+                match reranker.rank(&query, documents).await {
+                    Ok((ranked, _)) => {
+                        for document in ranked {
+                            if tx.send(document).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    Err(e) => error!("Error performing local rerank: {:?}", e),
+                }
+            });
+
+        ReceiverStream::new(rx)
+    }
+
+    fn find_similar_documents_with_usage(
+        &self,
+        query: &str,
+        documents: Vec<String>,
+    ) -> ReceiverStream<RerankStreamItem> {
+        let (tx, rx) = mpsc::channel(16);
+        let reranker = self.clone();
+        let query = query.to_string();
+
+        self.embeddings_client
+            .config()
+            .lifecycle
+            .spawn_producer(tx.clone(), async move {
+                match reranker.rank(&query, documents).await {
+                    Ok((ranked, usage)) => {
+                        for document in ranked {
+                            if tx.send(RerankStreamItem::Document(document)).await.is_err() {
+                                return;
+                            }
+                        }
+                        let _ = tx.send(RerankStreamItem::Usage(usage)).await;
+                    }
+                    Err(e) => error!("Error performing local rerank: {:?}", e),
+                }
+            });
+
+        ReceiverStream::new(rx)
+    }
+
+    fn most_similar_document(
+        &self,
+        query: &str,
+        documents: Vec<String>,
+    ) -> AsyncDocumentSimilarity {
+        let (tx, rx) = oneshot::channel();
+        let best = self.most_similar_document_with_usage(query, documents);
+        let task = AbortOnDrop::spawn(async move {
+            let _ = tx.send(best.await.map(|best| best.document));
+        });
+        AsyncDocumentSimilarity::new(rx).with_task(task)
+    }
+
+    fn most_similar_document_with_usage(
+        &self,
+        query: &str,
+        documents: Vec<String>,
+    ) -> AsyncRerankMatch {
+        let (tx, rx) = oneshot::channel();
+        let reranker = self.clone();
+        let query = query.to_string();
+
+        let task = AbortOnDrop::spawn(async move {
+            let result = match reranker.rank(&query, documents).await {
+                Ok((ranked, usage)) => match ranked.into_iter().next() {
+                    Some(document) => Ok(RerankMatch { document, usage }),
+                    None => Err(VoyageError::Other(
+                        "No matching documents found".to_string(),
+                    )),
+                },
+                Err(e) => Err(e),
+            };
+            let _ = tx.send(result);
+        });
+
+        AsyncRerankMatch::new(rx).with_task(task)
+    }
+
+    fn rerank_request(&self) -> RerankRequestBuilder {
+        RerankRequestBuilder::new()
+    }
+}
@@ -0,0 +1,155 @@
+//! Embedding texts longer than the model's context.
+//!
+//! The API silently embeds only the beginning of inputs over the model's
+//! context length, or rejects them without truncation. [`Client::embed_long`]
+//! instead splits such a text into context-sized chunks with a
+//! [`TokenSplitter`], embeds the chunks as documents and combines their
+//! embeddings into one vector by a [`LongTextStrategy`]. Texts that fit the
+//! context are embedded as they are by every strategy.
+//!
+//! ```no_run
+//! use voyageai::client::long_text::LongTextStrategy;
+//! # async fn example(client: voyageai::VoyageAiClient, book: &str) -> Result<(), voyageai::VoyageError> {
+//! let embedding = client.embed_long(book, LongTextStrategy::MeanPool).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::builder::validation::MAX_EMBEDDING_INPUTS;
+use crate::client::embeddings_client::Client;
+use crate::client::voyage_client::VoyageAiClient;
+use crate::errors::VoyageError;
+use crate::models::embeddings::{EmbedOptions, EmbeddingsInput, InputType};
+use crate::models::tokens::estimate_tokens;
+use crate::text_splitter::{TextChunk, TextSplitter, TokenSplitter};
+use crate::traits::llm::TextEmbedding;
+use crate::vectors::{length_normalized_pool, max_pool, normalize};
+
+/// How [`Client::embed_long`] embeds a text longer than the model's context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LongTextStrategy {
+    /// Sends the whole text with truncation, so the API embeds as much of
+    /// its beginning as fits
+    Truncate,
+    /// Embeds only the first context-sized chunk, cut at a word boundary
+    FirstChunk,
+    /// Averages the embeddings of all chunks weighted by their length, so
+    /// that a short last chunk counts less than full ones
+    #[default]
+    MeanPool,
+    /// Takes the largest value of each dimension over all chunks
+    MaxPool,
+}
+
+/// A chunk of a long text and its embedding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkEmbedding {
+    pub chunk: TextChunk,
+    pub embedding: Vec<f32>,
+}
+
+impl Client {
+    /// Embeds `text` as a document, splitting it into chunks and combining
+    /// their embeddings by `strategy` when it exceeds the model's context.
+    /// Pooled embeddings are L2-normalized like the API's.
+    pub async fn embed_long(
+        &self,
+        text: &str,
+        strategy: LongTextStrategy,
+    ) -> Result<Vec<f32>, VoyageError> {
+        let options = EmbedOptions::new().input_type(InputType::Document);
+        if estimate_tokens(text) <= self.config().embedding_model.max_context_length() {
+            return self.embed_with(text, options).await;
+        }
+        match strategy {
+            LongTextStrategy::Truncate => self.embed_with(text, options.truncation(true)).await,
+            LongTextStrategy::FirstChunk => {
+                let first = self.splitter().split(text).into_iter().next();
+                let first = first.ok_or(VoyageError::EmptyResponse)?;
+                self.embed_with(&first.text, options).await
+            }
+            LongTextStrategy::MeanPool => {
+                let (lengths, embeddings): (Vec<usize>, Vec<Vec<f32>>) = self
+                    .embed_long_chunks(text)
+                    .await?
+                    .into_iter()
+                    .map(|chunk| (chunk.chunk.text.len(), chunk.embedding))
+                    .unzip();
+                Ok(length_normalized_pool(&embeddings, &lengths)?)
+            }
+            LongTextStrategy::MaxPool => {
+                let embeddings: Vec<Vec<f32>> = self
+                    .embed_long_chunks(text)
+                    .await?
+                    .into_iter()
+                    .map(|chunk| chunk.embedding)
+                    .collect();
+                let mut pooled = max_pool(&embeddings)?;
+                normalize(&mut pooled);
+                Ok(pooled)
+            }
+        }
+    }
+
+    /// Splits `text` into chunks that fit the model's context and embeds
+    /// each as a document, in as few requests as the API's input and token
+    /// limits allow. A text that fits is a single chunk.
+    pub async fn embed_long_chunks(&self, text: &str) -> Result<Vec<ChunkEmbedding>, VoyageError> {
+        let model = self.config().embedding_model;
+        let options = EmbedOptions::new().input_type(InputType::Document);
+        let chunks = self.splitter().split(text);
+
+        let mut embeddings = Vec::with_capacity(chunks.len());
+        let mut start = 0;
+        while start < chunks.len() {
+            // As many chunks as the request token limit allows, at least one
+            let mut end = start + 1;
+            let mut tokens = estimate_tokens(&chunks[start].text);
+            while end < chunks.len() && end - start < MAX_EMBEDDING_INPUTS {
+                let next = estimate_tokens(&chunks[end].text);
+                if tokens + next > model.max_tokens_per_request() {
+                    break;
+                }
+                tokens += next;
+                end += 1;
+            }
+            let texts: Vec<String> = chunks[start..end]
+                .iter()
+                .map(|chunk| chunk.text.clone())
+                .collect();
+            let count = texts.len();
+            let request = options.request(EmbeddingsInput::Multiple(texts), model);
+            embeddings.extend(
+                self.create_embedding(&request)
+                    .await?
+                    .into_embeddings(count)?,
+            );
+            start = end;
+        }
+
+        Ok(chunks
+            .into_iter()
+            .zip(embeddings)
+            .map(|(chunk, embedding)| ChunkEmbedding { chunk, embedding })
+            .collect())
+    }
+
+    fn splitter(&self) -> TokenSplitter {
+        TokenSplitter::new(self.config().embedding_model.max_context_length())
+    }
+}
+
+impl VoyageAiClient {
+    /// Embeds `text` as a document, pooling the embeddings of its chunks by
+    /// `strategy` when it exceeds the model's context; see
+    /// [`Client::embed_long`].
+    pub fn embed_long(&self, text: &str, strategy: LongTextStrategy) -> TextEmbedding {
+        let embeddings_client = self.embeddings_client().clone();
+        let text = text.to_string();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let task = self.tasks().spawn(async move {
+            let _ = tx.send(embeddings_client.embed_long(&text, strategy).await);
+        });
+        TextEmbedding::new(rx, task)
+    }
+}
@@ -1,11 +1,52 @@
+pub mod auto_batcher;
+pub mod batch_client;
+pub mod bulk_embedder;
+#[cfg(feature = "candle")]
+pub mod candle_embedder;
+pub mod checkpoint;
+pub mod circuit_breaker;
 pub mod client_limiter;
+mod coalesce;
+pub mod concurrency;
 pub mod embeddings_client;
+pub mod hedging;
+pub mod lifecycle;
+pub mod local_reranker;
+pub mod long_text;
+pub mod multi_query;
+pub mod pairwise;
 pub mod rerank_client;
 pub mod retry;
 pub mod search_client;
+pub mod semantic_cache;
+pub mod similarity;
+pub mod tasks;
 pub mod voyage_client;
 
-pub use crate::builder::search::SearchRequest;
+pub use crate::builder::search::{IndexSource, SearchRequest};
 pub use crate::models::search::SearchResult;
-pub use client_limiter::RateLimiter;
-pub use rerank_client::RerankClient;
+pub use auto_batcher::AutoBatcher;
+pub use batch_client::BatchClient;
+pub use bulk_embedder::{BulkDocument, BulkEmbedder, BulkEmbedding, BulkProgress, BulkSink};
+#[cfg(feature = "candle")]
+pub use candle_embedder::CandleEmbedder;
+pub use concurrency::{ConcurrencyLimiter, ConcurrencyPermit, ConcurrencyStats};
+pub use circuit_breaker::{CircuitBreaker, CircuitState, CircuitTransition};
+pub use checkpoint::{CheckpointStore, FileCheckpoint, JobState};
+pub use client_limiter::{
+    AdaptiveThrottling, Priority, RateLimit, RateLimitEndpoint, RateLimitEvent, RateLimitEventKind, RateLimitTier,
+    RateLimiter,
+};
+pub use hedging::HedgingPolicy;
+pub use lifecycle::{Lifecycle, ShutdownReport};
+pub use local_reranker::LocalReranker;
+pub use long_text::{ChunkEmbedding, LongTextStrategy};
+pub use multi_query::{reciprocal_rank_fusion, MultiQueryRetriever};
+pub use pairwise::{PairwiseRanker, TournamentWinner};
+pub use similarity::{Similarity, SimilarityMatrix};
+pub use rerank_client::{DocumentSimilarity, RankingStrategy, RerankClient};
+pub use retry::RetryPolicy;
+pub use search_client::{maximal_marginal_relevance, SearchClient};
+pub use semantic_cache::{CacheHit, SemanticCache};
+pub use tasks::TaskGroup;
+pub use voyage_client::VoyageAiClient;
@@ -0,0 +1,138 @@
+//! Multi-query retrieval: searching with several formulations of a question
+//! and fusing the rankings.
+//!
+//! A single query embedding can miss documents phrased differently from the
+//! question. [`MultiQueryRetriever`] runs the same [`SearchRequest`] once per
+//! formulation and merges the result lists with reciprocal rank fusion, so
+//! documents ranked well by several formulations rise to the top:
+//!
+//! ```no_run
+//! # async fn example(request: voyageai::client::SearchRequest) -> Result<(), voyageai::VoyageError> {
+//! use voyageai::{client::MultiQueryRetriever, VoyageAiClient};
+//!
+//! let client = VoyageAiClient::new();
+//! let retriever = MultiQueryRetriever::new(&client);
+//! let results = retriever
+//!     .search(
+//!         &[
+//!             "How do I rotate API keys?",
+//!             "replace an API key",
+//!             "API key expiry",
+//!         ],
+//!         &request,
+//!     )
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::future::try_join_all;
+
+use crate::builder::search::SearchRequest;
+use crate::client::search_client::SearchClient;
+use crate::client::voyage_client::VoyageAiClient;
+use crate::errors::VoyageError;
+use crate::models::search::SearchResult;
+
+/// Default constant of the reciprocal rank fusion formula `1 / (k + rank)`.
+pub const DEFAULT_RRF_K: f32 = 60.0;
+
+/// Fuses several rankings of document indices into one, best first. Each
+/// document scores `1 / (k + rank)` per ranking it appears in, with ranks
+/// starting at 1; ties are broken by index.
+pub fn reciprocal_rank_fusion(rankings: &[Vec<usize>], k: f32) -> Vec<(usize, f32)> {
+    let mut scores: HashMap<usize, f32> = HashMap::new();
+    for ranking in rankings {
+        for (rank, index) in ranking.iter().enumerate() {
+            *scores.entry(*index).or_default() += 1.0 / (k + rank as f32 + 1.0);
+        }
+    }
+    let mut fused: Vec<(usize, f32)> = scores.into_iter().collect();
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    fused
+}
+
+/// Searches with several query formulations and fuses the results, see the
+/// [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct MultiQueryRetriever {
+    search_client: Arc<SearchClient>,
+    rrf_k: f32,
+}
+
+impl MultiQueryRetriever {
+    /// A retriever using the client's search client.
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self::with_search_client(client.config.search_client.clone())
+    }
+
+    pub fn with_search_client(search_client: Arc<SearchClient>) -> Self {
+        Self {
+            search_client,
+            rrf_k: DEFAULT_RRF_K,
+        }
+    }
+
+    /// Sets `k` of the fusion formula; smaller values favour documents at the
+    /// very top of each ranking. Defaults to [`DEFAULT_RRF_K`].
+    pub fn with_rrf_k(mut self, rrf_k: f32) -> Self {
+        self.rrf_k = rrf_k.max(0.0);
+        self
+    }
+
+    /// Runs `request` once per query, concurrently, and returns each matching
+    /// document once, best fused rank first. The `score` of a result is its
+    /// fusion score. The request's `top_k` limits the fused results rather
+    /// than each query's; its own query is ignored.
+    pub async fn search<S: AsRef<str>>(
+        &self,
+        queries: &[S],
+        request: &SearchRequest,
+    ) -> Result<Vec<SearchResult>, VoyageError> {
+        if queries.is_empty() {
+            return Err(VoyageError::SearchBuilderError(
+                "At least one query is required".to_string(),
+            ));
+        }
+        let requests: Vec<SearchRequest> = queries
+            .iter()
+            .map(|query| {
+                let mut request = request.clone();
+                request.query.query = query.as_ref().to_string();
+                request.top_k = None;
+                request
+            })
+            .collect();
+        let result_lists = try_join_all(
+            requests
+                .iter()
+                .map(|request| self.search_client.search(request)),
+        )
+        .await?;
+
+        let rankings: Vec<Vec<usize>> = result_lists
+            .iter()
+            .map(|results| results.iter().map(|result| result.index).collect())
+            .collect();
+        let mut first_seen: HashMap<usize, &SearchResult> = HashMap::new();
+        for result in result_lists.iter().flatten() {
+            first_seen.entry(result.index).or_insert(result);
+        }
+
+        let mut fused: Vec<SearchResult> = reciprocal_rank_fusion(&rankings, self.rrf_k)
+            .into_iter()
+            .map(|(index, score)| {
+                let mut result = first_seen[&index].clone();
+                result.score = score;
+                result
+            })
+            .collect();
+        if let Some(top_k) = request.top_k {
+            fused.truncate(top_k);
+        }
+        Ok(fused)
+    }
+}
@@ -0,0 +1,188 @@
+//! Pairwise comparison and tournament selection of documents with the rerank
+//! endpoint.
+//!
+//! Labeling pipelines and A/B tests of content often need one answer: which
+//! of two texts is more relevant to a query, or which of many is the best.
+//! [`PairwiseRanker::compare`] answers the first with a two-document rerank;
+//! [`PairwiseRanker::tournament`] answers the second by reranking the
+//! documents in groups, concurrently, and advancing the winner of each group
+//! until one is left. That also picks from more documents than a single
+//! request accepts.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), voyageai::VoyageError> {
+//! use std::cmp::Ordering;
+//! use voyageai::{client::PairwiseRanker, VoyageAiClient};
+//!
+//! let client = VoyageAiClient::new();
+//! let ranker = PairwiseRanker::new(&client);
+//! let query = "How do I rotate API keys?";
+//! if ranker.compare(query, "Rotating keys", "Brewing tea").await? == Ordering::Greater {
+//!     println!("the first answer is more relevant");
+//! }
+//! let winner = ranker
+//!     .tournament(query, &["Rotating keys".to_string(), "Key expiry".to_string()])
+//!     .await?;
+//! println!("document {} won after {} rounds", winner.index, winner.rounds);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use futures::future::try_join_all;
+
+use crate::client::rerank_client::DefaultRerankClient;
+use crate::client::voyage_client::VoyageAiClient;
+use crate::errors::VoyageError;
+use crate::models::rerank::{RerankModel, RerankRequest, RerankValidationError};
+
+/// Default number of documents reranked together in each group of a
+/// [`PairwiseRanker::tournament`].
+pub const DEFAULT_GROUP_SIZE: usize = 2;
+
+/// The document picked by a [`PairwiseRanker::tournament`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentWinner {
+    /// Position of the document in the documents given
+    pub index: usize,
+    pub document: String,
+    /// Relevance score of the document in the final round
+    pub relevance_score: f64,
+    /// Number of rounds played
+    pub rounds: usize,
+}
+
+/// Compares documents with the rerank endpoint, see the
+/// [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct PairwiseRanker {
+    rerank_client: Arc<DefaultRerankClient>,
+    model: RerankModel,
+    group_size: usize,
+}
+
+impl PairwiseRanker {
+    /// A ranker using the client's rerank client.
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self::with_rerank_client(client.config.rerank_client.clone())
+    }
+
+    pub fn with_rerank_client(rerank_client: Arc<DefaultRerankClient>) -> Self {
+        Self {
+            rerank_client,
+            model: RerankModel::default(),
+            group_size: DEFAULT_GROUP_SIZE,
+        }
+    }
+
+    /// Sets the rerank model. Defaults to [`RerankModel::default`].
+    pub fn with_model(mut self, model: RerankModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Sets how many documents are reranked together in each group of a
+    /// tournament, between 2 and the model's
+    /// [`max_documents`](RerankModel::max_documents). Larger groups take
+    /// fewer requests and rounds. Defaults to [`DEFAULT_GROUP_SIZE`].
+    pub fn with_group_size(mut self, group_size: usize) -> Self {
+        self.group_size = group_size;
+        self
+    }
+
+    /// Whether `document_a` is more ([`Ordering::Greater`]), equally or less
+    /// relevant to `query` than `document_b`, by their rerank scores.
+    pub async fn compare(
+        &self,
+        query: &str,
+        document_a: &str,
+        document_b: &str,
+    ) -> Result<Ordering, VoyageError> {
+        let documents = [document_a.to_string(), document_b.to_string()];
+        let scores = self.scores(query, &documents, &[0, 1]).await?;
+        let score = |index: usize| {
+            scores
+                .iter()
+                .find(|(position, _)| *position == index)
+                .map(|(_, score)| *score)
+                .unwrap_or(f64::NEG_INFINITY)
+        };
+        Ok(score(0).total_cmp(&score(1)))
+    }
+
+    /// Picks the document most relevant to `query`. The documents are
+    /// reranked in groups of the [group size](Self::with_group_size), all
+    /// groups of a round concurrently, and the best of each group advances to
+    /// the next round until one is left.
+    pub async fn tournament(
+        &self,
+        query: &str,
+        documents: &[String],
+    ) -> Result<TournamentWinner, VoyageError> {
+        if documents.is_empty() {
+            return Err(RerankValidationError::EmptyDocuments.into());
+        }
+        let group_size = self.group_size.clamp(2, self.model.max_documents());
+        let mut candidates: Vec<usize> = (0..documents.len()).collect();
+        let mut rounds = 0;
+        loop {
+            let winners = try_join_all(
+                candidates
+                    .chunks(group_size)
+                    .map(|group| self.group_winner(query, documents, group)),
+            )
+            .await?;
+            rounds += 1;
+            if let [(index, relevance_score)] = winners[..] {
+                return Ok(TournamentWinner {
+                    index,
+                    document: documents[index].clone(),
+                    relevance_score,
+                    rounds,
+                });
+            }
+            candidates = winners.into_iter().map(|(index, _)| index).collect();
+        }
+    }
+
+    /// The best of the documents at positions `group`, with its score.
+    async fn group_winner(
+        &self,
+        query: &str,
+        documents: &[String],
+        group: &[usize],
+    ) -> Result<(usize, f64), VoyageError> {
+        self.scores(query, documents, group)
+            .await?
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+            .ok_or_else(|| VoyageError::MalformedResponse("rerank returned no results".to_string()))
+    }
+
+    /// Reranks the documents at positions `group`, returning the score of
+    /// each by position.
+    async fn scores(
+        &self,
+        query: &str,
+        documents: &[String],
+        group: &[usize],
+    ) -> Result<Vec<(usize, f64)>, VoyageError> {
+        let request = RerankRequest::new(
+            query.to_string(),
+            group
+                .iter()
+                .map(|&index| documents[index].clone())
+                .collect(),
+            self.model,
+            None,
+        )?;
+        let response = self.rerank_client.rerank(request).await?;
+        Ok(response
+            .data
+            .iter()
+            .map(|result| (group[result.index], result.relevance_score))
+            .collect())
+    }
+}
@@ -1,20 +1,29 @@
 use log::{debug, info, warn};
-use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use tokio::sync::{mpsc, oneshot};
-use tokio::time::sleep;
 use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
 
-use crate::client::RateLimiter;
+use crate::builder::validation::{validate_rerank, ValidationError};
+use crate::client::embeddings_client::Client as EmbeddingsClient;
+use crate::client::local_reranker::LocalReranker;
+use crate::client::tasks::AbortOnDrop;
+use crate::client::{RateLimitEndpoint, RateLimiter};
+use crate::client::retry::retry_after;
 use crate::config::VoyageConfig;
 use crate::errors::VoyageError;
-use crate::models::rerank::{RerankRequest, RerankResponse};
-
-/// Base URL for the Voyage AI API.
-const BASE_URL: &str = "https://api.voyageai.com/v1";
+use crate::models::rerank::{
+    RerankRequest, RerankResponse, RerankResult, RerankValidationError, Usage,
+};
+use crate::models::request_id::{RequestId, IDEMPOTENCY_KEY_HEADER};
+use crate::models::response_headers::ResponseMetadata;
+use crate::transport::{HttpRequest, Transport};
 
 /// Builder for rerank requests with additional configuration options
 #[derive(Debug, Clone)]
@@ -23,6 +32,8 @@ pub struct RerankRequestBuilder {
     documents: Vec<String>,
     model: crate::models::rerank::RerankModel,
     top_k: Option<usize>,
+    return_documents: Option<bool>,
+    min_score: Option<f64>,
 }
 
 impl RerankRequestBuilder {
@@ -33,6 +44,8 @@ impl RerankRequestBuilder {
             documents: Vec::new(),
             model: Default::default(),
             top_k: None,
+            return_documents: None,
+            min_score: None,
         }
     }
     
@@ -65,24 +78,98 @@ impl RerankRequestBuilder {
         self.top_k = Some(top_k);
         self
     }
+
+    /// Ask the API to echo each document's text in its result
+    pub fn return_documents(mut self, return_documents: bool) -> Self {
+        self.return_documents = Some(return_documents);
+        self
+    }
+
+    /// Drop the results with a relevance score below `min_score`
+    pub fn min_score(mut self, min_score: f64) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
     
-    /// Build the RerankRequest
-    pub fn build(self) -> Result<RerankRequest, crate::models::rerank::ValidationError> {
-        let query = self.query.ok_or_else(|| {
-            crate::models::rerank::ValidationError::EmptyDocuments
-        })?;
-        
-        RerankRequest::new(
+    /// Build the RerankRequest, reporting every validation problem at once
+    pub fn build(self) -> Result<RerankRequest, ValidationError> {
+        let query = self.query.unwrap_or_default();
+        ValidationError::check(validate_rerank(&query, &self.documents, self.model))?;
+
+        Ok(RerankRequest {
             query,
-            self.documents,
-            self.model,
-            self.top_k,
-        )
+            documents: self.documents,
+            model: self.model,
+            top_k: self.top_k,
+            return_documents: self.return_documents,
+            min_score: self.min_score,
+        })
+    }
+}
+
+impl Default for RerankRequestBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How a [`DocumentSimilarity`] was scored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RankingStrategy {
+    /// Scored by the rerank endpoint
+    #[default]
+    Rerank,
+    /// Scored locally by embedding cosine similarity, see [`LocalReranker`]
+    Embedding,
+}
+
+/// When [`DefaultRerankClient`] degrades to [`LocalReranker`] instead of
+/// failing. Set with [`VoyageConfig::with_rerank_fallback`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RerankFallback {
+    /// Rerank errors are returned, and streams end early
+    #[default]
+    Never,
+    /// Fall back when the rerank endpoint rejects the request with a rate limit
+    OnRateLimit,
+    /// Fall back on any error from the rerank endpoint or the transport
+    OnError,
+}
+
+impl RerankFallback {
+    /// Whether a rerank that failed with `error` should be retried locally.
+    pub fn applies_to(&self, error: &VoyageError) -> bool {
+        match self {
+            Self::Never => false,
+            Self::OnRateLimit => matches!(error, VoyageError::RateLimitExceeded { .. }),
+            Self::OnError => !matches!(error, VoyageError::Validation(_)),
+        }
+    }
+}
+
+impl std::str::FromStr for RerankFallback {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "never" => Ok(Self::Never),
+            "on_rate_limit" => Ok(Self::OnRateLimit),
+            "on_error" => Ok(Self::OnError),
+            other => Err(format!(
+                "Unknown rerank fallback '{other}', expected never, on_rate_limit or on_error"
+            )),
+        }
     }
 }
 
 /// A single document with its similarity score to a query
-#[derive(Debug, Clone)]
+///
+/// Serializes as `{"rank", "similarity", "document", "strategy"}`; fields may
+/// be added, so build values with [`DocumentSimilarity::new`] outside this
+/// crate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct DocumentSimilarity {
     /// Position in the ranking (0 = most similar)
     pub rank: usize,
@@ -90,16 +177,159 @@ pub struct DocumentSimilarity {
     pub similarity: f64,
     /// The document content
     pub document: String,
+    /// Whether the score comes from the rerank endpoint or the local fallback
+    #[serde(default)]
+    pub strategy: RankingStrategy,
+}
+
+impl DocumentSimilarity {
+    /// A document scored by the rerank endpoint, ranked first.
+    pub fn new(document: impl Into<String>, similarity: f64) -> Self {
+        Self {
+            rank: 0,
+            similarity,
+            document: document.into(),
+            strategy: RankingStrategy::default(),
+        }
+    }
+
+    pub fn with_rank(mut self, rank: usize) -> Self {
+        self.rank = rank;
+        self
+    }
+
+    pub fn with_strategy(mut self, strategy: RankingStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+
+    pub fn similarity(&self) -> f64 {
+        self.similarity
+    }
+
+    pub fn document(&self) -> &str {
+        &self.document
+    }
+
+    pub fn strategy(&self) -> RankingStrategy {
+        self.strategy
+    }
+
+    /// The document content, consuming the result.
+    pub fn into_document(self) -> String {
+        self.document
+    }
+}
+
+/// Takes the document text returned with the result, which is empty unless
+/// the request set `return_documents`. The rank is 0; set the position in the
+/// ranking with [`DocumentSimilarity::with_rank`].
+impl From<RerankResult> for DocumentSimilarity {
+    fn from(result: RerankResult) -> Self {
+        Self::new(result.document.unwrap_or_default(), result.relevance_score)
+    }
+}
+
+/// Ranks the results in response order, with the document texts returned
+/// with them; see [`RerankResponse::into_document_similarities`] for requests
+/// without `return_documents`.
+impl From<RerankResponse> for Vec<DocumentSimilarity> {
+    fn from(response: RerankResponse) -> Self {
+        response
+            .data
+            .into_iter()
+            .enumerate()
+            .map(|(rank, result)| DocumentSimilarity::from(result).with_rank(rank))
+            .collect()
+    }
+}
+
+impl RerankResponse {
+    /// Ranks the results in response order, taking each document's text
+    /// from the response when it was returned and from `documents`, the
+    /// documents of the request, otherwise.
+    pub fn into_document_similarities(self, documents: &[String]) -> Vec<DocumentSimilarity> {
+        self.data
+            .into_iter()
+            .enumerate()
+            .map(|(rank, result)| {
+                let document = match result.document {
+                    Some(document) => document,
+                    None => documents.get(result.index).cloned().unwrap_or_default(),
+                };
+                DocumentSimilarity::new(document, result.relevance_score).with_rank(rank)
+            })
+            .collect()
+    }
+}
+
+/// An item of [`RerankClient::find_similar_documents_with_usage`]: every
+/// ranked document, followed by the usage of the request as the last item.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RerankStreamItem {
+    Document(DocumentSimilarity),
+    Usage(Usage),
+}
+
+/// The best match of [`RerankClient::most_similar_document_with_usage`]
+/// together with the tokens billed for the request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RerankMatch {
+    pub document: DocumentSimilarity,
+    pub usage: Usage,
+}
+
+/// Reranks `documents` and returns their scores keyed by position in
+/// `documents`. Documents the endpoint did not score are absent.
+///
+/// The rerank stream yields document text, so each text is mapped back to its
+/// positions; identical documents are assigned in order.
+pub(crate) async fn rerank_scores(
+    client: &dyn RerankClient,
+    query: &str,
+    documents: Vec<String>,
+) -> HashMap<usize, f64> {
+    let mut scores = HashMap::new();
+    if documents.is_empty() {
+        return scores;
+    }
+
+    let mut positions: HashMap<String, VecDeque<usize>> = HashMap::new();
+    for (position, document) in documents.iter().enumerate() {
+        positions.entry(document.clone()).or_default().push_back(position);
+    }
+    let mut stream = client.find_similar_documents(query, documents);
+    while let Some(similarity) = stream.next().await {
+        if let Some(position) = positions
+            .get_mut(&similarity.document)
+            .and_then(VecDeque::pop_front)
+        {
+            scores.insert(position, similarity.similarity);
+        }
+    }
+    scores
 }
 
 /// A future that resolves to a single document similarity
 pub struct AsyncDocumentSimilarity {
     receiver: oneshot::Receiver<Result<DocumentSimilarity, VoyageError>>,
+    /// Aborts the task producing the result when this future is dropped
+    _task: Option<AbortOnDrop>,
 }
 
 impl AsyncDocumentSimilarity {
-    fn new(receiver: oneshot::Receiver<Result<DocumentSimilarity, VoyageError>>) -> Self {
-        Self { receiver }
+    pub(crate) fn new(receiver: oneshot::Receiver<Result<DocumentSimilarity, VoyageError>>) -> Self {
+        Self { receiver, _task: None }
+    }
+
+    pub(crate) fn with_task(mut self, task: AbortOnDrop) -> Self {
+        self._task = Some(task);
+        self
     }
 }
 
@@ -115,14 +345,50 @@ impl Future for AsyncDocumentSimilarity {
     }
 }
 
+/// A future that resolves to the best match of a rerank request and its usage
+pub struct AsyncRerankMatch {
+    receiver: oneshot::Receiver<Result<RerankMatch, VoyageError>>,
+    /// Aborts the task producing the result when this future is dropped
+    _task: Option<AbortOnDrop>,
+}
+
+impl AsyncRerankMatch {
+    pub(crate) fn new(receiver: oneshot::Receiver<Result<RerankMatch, VoyageError>>) -> Self {
+        Self { receiver, _task: None }
+    }
+
+    pub(crate) fn with_task(mut self, task: AbortOnDrop) -> Self {
+        self._task = Some(task);
+        self
+    }
+}
+
+impl Future for AsyncRerankMatch {
+    type Output = Result<RerankMatch, VoyageError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.receiver).poll(cx) {
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            Poll::Ready(Err(_)) => Poll::Ready(Err(VoyageError::Other("Rerank task canceled".to_string()))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
 
 /// Client trait for finding similar documents based on semantic similarity.
 pub trait RerankClient: std::fmt::Debug + Send + Sync {
     /// Finds documents similar to a query and returns a stream of document similarities.
     fn find_similar_documents(&self, query: &str, documents: Vec<String>) -> ReceiverStream<DocumentSimilarity>;
+
+    /// Like `find_similar_documents`, ending the stream with the usage of the
+    /// request so callers can track token spend.
+    fn find_similar_documents_with_usage(&self, query: &str, documents: Vec<String>) -> ReceiverStream<RerankStreamItem>;
     
     /// Finds the single most similar document to a query.
     fn most_similar_document(&self, query: &str, documents: Vec<String>) -> AsyncDocumentSimilarity;
+
+    /// Like `most_similar_document`, also returning the usage of the request.
+    fn most_similar_document_with_usage(&self, query: &str, documents: Vec<String>) -> AsyncRerankMatch;
     
     /// Create a rerank request with more options
     fn rerank_request(&self) -> RerankRequestBuilder;
@@ -131,7 +397,7 @@ pub trait RerankClient: std::fmt::Debug + Send + Sync {
 /// Default implementation of RerankClient
 #[derive(Clone, Debug)]
 pub struct DefaultRerankClient {
-    client: Client,
+    transport: Arc<dyn Transport>,
     config: VoyageConfig,
     rate_limiter: Arc<RateLimiter>,
 }
@@ -141,7 +407,7 @@ impl DefaultRerankClient {
     pub fn new(config: VoyageConfig, rate_limiter: Arc<RateLimiter>) -> Self {
         debug!("Creating new DefaultRerankClient");
         Self {
-            client: Client::new(),
+            transport: config.transport(),
             config,
             rate_limiter,
         }
@@ -163,55 +429,140 @@ impl DefaultRerankClient {
     }
     
     /// Create a RerankRequest from a query and documents
-    fn create_request(&self, query: &str, documents: Vec<String>) -> RerankRequest {
+    fn create_request(&self, query: &str, documents: Vec<String>) -> Result<RerankRequest, RerankValidationError> {
         RerankRequest::new(
             query.to_string(),
             documents,
             Default::default(), // Use default model
             None,
-        ).unwrap_or_else(|_| panic!("Failed to create rerank request"))
+        )
     }
     
+    /// Sends `request` to the rerank endpoint as is, without the configured
+    /// [`RerankFallback`].
+    pub async fn rerank(&self, request: RerankRequest) -> Result<RerankResponse, VoyageError> {
+        self.perform_rerank(request).await
+    }
+
+    /// Like [`RerankClient::find_similar_documents`] for a request built
+    /// with [`RerankRequestBuilder`], honoring its model, `top_k` and
+    /// `min_score`.
+    pub fn rerank_stream(&self, request: RerankRequest) -> ReceiverStream<DocumentSimilarity> {
+        let (tx, rx) = mpsc::channel(16);
+        let client = self.clone();
+        self.config.lifecycle.spawn_producer(tx.clone(), async move {
+            match client.rank(request).await {
+                Ok((ranked, _)) => {
+                    for document in ranked {
+                        if tx.send(document).await.is_err() {
+                            break; // receiver dropped
+                        }
+                    }
+                }
+                Err(e) => {
+                    log::error!("Error performing rerank: {:?}", e);
+                    // Channel is closed, receiver will get an end of stream
+                }
+            }
+        });
+        ReceiverStream::new(rx)
+    }
+
     /// Internal implementation of the rerank operation
     async fn perform_rerank(&self, request: RerankRequest) -> Result<RerankResponse, VoyageError> {
-        let url = format!("{}/rerank", BASE_URL);
-        let api_key = self.config.api_key().to_string();
-        let estimated_tokens = self.estimate_tokens(&request);
+        let _work = self.config.lifecycle.begin()?;
+        // One key for all the retries of the call
+        let id = self.config.idempotency_keys.then(RequestId::new);
+        let (request, id) = (&request, id.as_ref());
+        let hedging = self.config.hedging(RateLimitEndpoint::Reranking);
+        self.config
+            .retry_policy
+            .run(|| async move {
+                match hedging {
+                    Some(policy) => {
+                        policy
+                            .run(|hedge| async move {
+                                // A hedge is a request of its own, not a retry
+                                let hedge_id = (hedge && id.is_some()).then(RequestId::new);
+                                self.perform_rerank_once(request, hedge_id.as_ref().or(id), hedge)
+                                    .await
+                            })
+                            .await
+                    }
+                    None => self.perform_rerank_once(request, id, false).await,
+                }
+            })
+            .await
+    }
+
+    /// Sends `request` once. A `hedge` is only sent if the rate limiter has
+    /// room for it right away.
+    async fn perform_rerank_once(
+        &self,
+        request: &RerankRequest,
+        id: Option<&RequestId>,
+        hedge: bool,
+    ) -> Result<RerankResponse, VoyageError> {
+        let url = self.config.endpoint("rerank");
+        let api_key = self.config.resolve_api_key().await?;
+        let estimated_tokens = self.estimate_tokens(request);
         
         debug!("Reranking documents with URL: {}", url);
         debug!("Estimated tokens for request: {}", estimated_tokens);
 
-        let wait_time = self.rate_limiter
-            .check_reranking_limit(estimated_tokens)
-            .await;
+        let _slot = self
+            .config
+            .concurrency_limiter
+            .admit(RateLimitEndpoint::Reranking, hedge)
+            .await?;
+        let waited = self.rate_limiter
+            .admit(
+                RateLimitEndpoint::Reranking,
+                estimated_tokens,
+                self.config.priority,
+                if hedge {
+                    Some(Duration::ZERO)
+                } else {
+                    self.config.rate_limit_timeout
+                },
+            )
+            .await?;
+        if !waited.is_zero() {
+            info!("Waited {:?} for the rate limiter", waited);
+        }
 
-        if wait_time.as_secs() > 0 {
-            info!(
-                "Rate limit reached. Waiting for {} seconds",
-                wait_time.as_secs()
-            );
-            sleep(wait_time).await;
+        if self.config.log_policy.logs_payloads() {
+            let body = serde_json::to_string(request)?;
+            debug!("Request body: {}", self.config.log_policy.payload(&body));
         }
 
-        let response = self.client
-            .post(&url)
-            .bearer_auth(api_key)
-            .json(&request)
-            .send()
-            .await?;
+        let mut http_request = HttpRequest::post(url).bearer_auth(&api_key)?.json(request)?;
+        if let Some(id) = id {
+            debug!("Idempotency key: {}", id);
+            http_request = http_request.header(IDEMPOTENCY_KEY_HEADER, id.as_str())?;
+        }
+        let response = self.transport.send(http_request).await?;
 
-        let status = response.status();
-        let text = response.text().await?;
+        let status = response.status;
+        let reset_in = retry_after(&response.headers);
+        let mut headers = ResponseMetadata::from_header_map(&response.headers);
+        headers.idempotency_key = id.cloned();
+        headers.warn_if_deprecated("rerank");
+        self.rate_limiter
+            .record_quota(RateLimitEndpoint::Reranking, &headers.rate_limit)
+            .await;
+        let text = response.text();
 
         match status {
             reqwest::StatusCode::OK => {
                 debug!("Rerank request successful");
-                debug!("Raw API response: {}", text);
-                let rerank_response: RerankResponse = serde_json::from_str(&text).map_err(|e| {
+                debug!("Raw API response: {}", self.config.log_policy.payload(&text));
+                let mut rerank_response: RerankResponse = serde_json::from_str(&text).map_err(|e| {
                     warn!("Failed to parse rerank response: {:?}", e);
-                    warn!("Raw response: {}", text);
+                    warn!("Raw response: {}", self.config.log_policy.payload(&text));
                     VoyageError::JsonError(e.to_string())
                 })?;
+                rerank_response.headers = headers;
 
                 if rerank_response.data.is_empty() {
                     warn!("Rerank response contains no results");
@@ -223,84 +574,154 @@ impl DefaultRerankClient {
                 }
 
                 self.rate_limiter
-                    .update_reranking_usage(rerank_response.usage.total_tokens)
+                    .record_usage(
+                        RateLimitEndpoint::Reranking,
+                        estimated_tokens,
+                        rerank_response.usage.total_tokens,
+                    )
                     .await;
 
+                rerank_response.validate(request.documents.len()).inspect_err(|e| {
+                    warn!("Invalid rerank response: {}", e);
+                })?;
+                rerank_response.total_documents = request.documents.len();
+                if let Some(min_score) = request.min_score {
+                    rerank_response.data.retain(|result| result.is_relevant(min_score));
+                }
                 Ok(rerank_response)
             }
             reqwest::StatusCode::UNAUTHORIZED => {
-                warn!("Unauthorized request: {}", text);
+                warn!("Unauthorized request: {}", self.config.log_policy.payload(&text));
                 Err(VoyageError::Unauthorized)
             }
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                warn!(
+                    "Rerank request rejected by rate limit: {} (request id: {:?})",
+                    self.config.log_policy.payload(&text),
+                    headers.request_id
+                );
+                self.rate_limiter
+                    .record_rejection(RateLimitEndpoint::Reranking, estimated_tokens, reset_in)
+                    .await;
+                Err(VoyageError::RateLimitExceeded {
+                    reset_in,
+                    metadata: Some(Box::new(headers)),
+                })
+            }
             _ => {
-                warn!("Rerank request failed with status: {}", status);
-                warn!("Error response body: {}", text);
-                Err(VoyageError::ApiError(status, text))
+                warn!(
+                    "Rerank request failed with status: {} (request id: {:?})",
+                    status, headers.request_id
+                );
+                warn!("Error response body: {}", self.config.log_policy.payload(&text));
+                Err(VoyageError::ApiError(status, text, Box::new(headers)))
             }
         }
     }
+
+    /// Performs the rerank and pairs each result with its input document,
+    /// best first. Failures covered by the configured [`RerankFallback`] are
+    /// retried with a [`LocalReranker`].
+    async fn rank(&self, request: RerankRequest) -> Result<(Vec<DocumentSimilarity>, Usage), VoyageError> {
+        let query = request.query.clone();
+        let input_docs = request.documents.clone();
+        let min_score = request.min_score;
+        let response = match self.perform_rerank(request).await {
+            Ok(response) => response,
+            Err(e) if self.config.rerank_fallback.applies_to(&e) => {
+                warn!("Rerank failed ({}), ranking by embedding similarity instead", e);
+                let embeddings_client = EmbeddingsClient::new(self.config.clone())
+                    .with_rate_limiter(self.rate_limiter.clone());
+                let (mut ranked, usage) = LocalReranker::from_embeddings_client(embeddings_client)
+                    .rank(&query, input_docs)
+                    .await?;
+                if let Some(min_score) = min_score {
+                    ranked.retain(|document| document.similarity() >= min_score);
+                }
+                return Ok((ranked, usage));
+            }
+            Err(e) => return Err(e),
+        };
+        let usage = response.usage;
+        Ok((response.into_document_similarities(&input_docs), usage))
+    }
 }
 
 impl RerankClient for DefaultRerankClient {
     fn find_similar_documents(&self, query: &str, documents: Vec<String>) -> ReceiverStream<DocumentSimilarity> {
+        match self.create_request(query, documents) {
+            Ok(request) => self.rerank_stream(request),
+            Err(e) => {
+                log::error!("Invalid rerank request: {}", e);
+                // Sender is dropped here, so the stream ends immediately
+                ReceiverStream::new(mpsc::channel(1).1)
+            }
+        }
+    }
+
+    fn find_similar_documents_with_usage(&self, query: &str, documents: Vec<String>) -> ReceiverStream<RerankStreamItem> {
         let (tx, rx) = mpsc::channel(16);
         let client = self.clone();
-        let input_docs = documents.clone();
-        let request = self.create_request(query, documents);
-        
-        tokio::spawn(async move {
-            match client.perform_rerank(request).await {
-                Ok(response) => {
-                    for (rank, result) in response.data.into_iter().enumerate() {
-                        // Directly use the original document at the matching index
-                        let document = DocumentSimilarity {
-                            rank,
-                            similarity: result.relevance_score,
-                            document: input_docs[result.index].clone(),
-                        };
-                        
-                        if tx.send(document).await.is_err() {
-                            break; // receiver dropped
+        let request = match self.create_request(query, documents) {
+            Ok(request) => request,
+            Err(e) => {
+                log::error!("Invalid rerank request: {}", e);
+                return ReceiverStream::new(rx);
+            }
+        };
+
+        self.config.lifecycle.spawn_producer(tx.clone(), async move {
+            match client.rank(request).await {
+                Ok((ranked, usage)) => {
+                    for document in ranked {
+                        if tx.send(RerankStreamItem::Document(document)).await.is_err() {
+                            return; // receiver dropped
                         }
                     }
+                    let _ = tx.send(RerankStreamItem::Usage(usage)).await;
                 }
                 Err(e) => {
                     log::error!("Error performing rerank: {:?}", e);
-                    // Channel is closed, receiver will get an end of stream
                 }
             }
         });
-        
+
         ReceiverStream::new(rx)
     }
     
     fn most_similar_document(&self, query: &str, documents: Vec<String>) -> AsyncDocumentSimilarity {
+        let (tx, rx) = oneshot::channel();
+        let best = self.most_similar_document_with_usage(query, documents);
+        let task = AbortOnDrop::spawn(async move {
+            let _ = tx.send(best.await.map(|best| best.document));
+        });
+        AsyncDocumentSimilarity::new(rx).with_task(task)
+    }
+
+    fn most_similar_document_with_usage(&self, query: &str, documents: Vec<String>) -> AsyncRerankMatch {
         let client = self.clone();
-        let input_docs = documents.clone();
-        let request = self.create_request(query, documents);
         let (tx, rx) = oneshot::channel();
+        let request = match self.create_request(query, documents) {
+            Ok(request) => request,
+            Err(e) => {
+                let _ = tx.send(Err(e.into()));
+                return AsyncRerankMatch::new(rx);
+            }
+        };
         
-        tokio::spawn(async move {
-            let result = match client.perform_rerank(request).await {
-                Ok(response) => {
-                    if let Some(best_match) = response.data.into_iter().next() {
-                        // Use the original document text directly by index
-                        Ok(DocumentSimilarity {
-                            rank: 0,
-                            similarity: best_match.relevance_score,
-                            document: input_docs[best_match.index].clone(),
-                        })
-                    } else {
-                        Err(VoyageError::Other("No matching documents found".to_string()))
-                    }
-                }
+        let task = AbortOnDrop::spawn(async move {
+            let result = match client.rank(request).await {
+                Ok((ranked, usage)) => match ranked.into_iter().next() {
+                    Some(document) => Ok(RerankMatch { document, usage }),
+                    None => Err(VoyageError::Other("No matching documents found".to_string())),
+                },
                 Err(e) => Err(e),
             };
             
             let _ = tx.send(result);
         });
         
-        AsyncDocumentSimilarity::new(rx)
+        AsyncRerankMatch::new(rx).with_task(task)
     }
     
     fn rerank_request(&self) -> RerankRequestBuilder {
@@ -1,8 +1,60 @@
 use crate::errors::VoyageError;
 use log::{debug, info, warn};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
 use std::time::Duration;
 use tokio::time::sleep;
 
+/// Wait applied to 429 responses that carry no usable `Retry-After` header.
+pub const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// How often requests rejected by the API's rate limit are retried, set with
+/// [`VoyageConfig::with_retry_policy`](crate::config::VoyageConfig::with_retry_policy).
+///
+/// Each retry waits for the `Retry-After` time of the rejection. The default
+/// does not retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Returns the first error.
+    pub const NONE: RetryPolicy = RetryPolicy::new(0, Duration::from_millis(500));
+
+    pub const fn new(max_retries: u32, initial_delay: Duration) -> Self {
+        Self {
+            max_retries,
+            initial_delay,
+        }
+    }
+
+    /// Runs `operation`, retrying it as configured.
+    pub(crate) async fn run<F, Fut, T>(&self, operation: F) -> Result<T, VoyageError>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, VoyageError>>,
+    {
+        retry_with_exponential_backoff(operation, self.max_retries, self.initial_delay).await
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Reads the `Retry-After` header (in seconds), falling back to [`DEFAULT_RETRY_AFTER`].
+pub fn retry_after(headers: &HeaderMap) -> Duration {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_RETRY_AFTER)
+}
+
 /// Retries an asynchronous operation with exponential backoff.
 ///
 /// This function will retry the given operation up to `max_retries` times,
@@ -35,7 +87,7 @@ use tokio::time::sleep;
 ///
 /// async fn fallible_operation() -> Result<String, VoyageError> {
 ///     // Simulated operation that might fail
-///     Err(VoyageError::RateLimitExceeded { reset_in: Duration::from_secs(1) })
+///     Err(VoyageError::RateLimitExceeded { reset_in: Duration::from_secs(1), metadata: None })
 /// }
 ///
 /// #[tokio::main]
@@ -52,7 +104,6 @@ use tokio::time::sleep;
 ///     }
 /// }
 /// ```
-#[allow(dead_code)]
 pub async fn retry_with_exponential_backoff<F, Fut, T>(
     mut operation: F,
     max_retries: u32,
@@ -77,13 +128,13 @@ where
                 info!("Operation succeeded after {} retries", retries);
                 return Ok(result);
             }
-            Err(VoyageError::RateLimitExceeded { reset_in }) => {
+            Err(VoyageError::RateLimitExceeded { reset_in, metadata }) => {
                 if retries >= max_retries {
                     warn!(
                         "Max retries ({}) reached. Returning RateLimitExceeded error",
                         max_retries
                     );
-                    return Err(VoyageError::RateLimitExceeded { reset_in });
+                    return Err(VoyageError::RateLimitExceeded { reset_in, metadata });
                 }
                 info!(
                     "Rate limit exceeded. Waiting for {:?} before retry",
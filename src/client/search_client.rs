@@ -1,23 +1,40 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
+
 use crate::builder::search::SearchRequest;
-use crate::client::{embeddings_client::Client as EmbeddingsClient, rerank_client::RerankClient};
+use crate::client::embeddings_client::Client as EmbeddingsClient;
+use crate::client::rerank_client::{rerank_scores, RerankClient};
 use crate::errors::VoyageError;
-use crate::models::search::{SearchResult, SearchType};
+use crate::models::embedding::Embedding;
+use crate::models::metadata::Metadata;
+use crate::models::rerank::MAX_DOCUMENTS;
+use crate::models::search::{ScoreNormalization, SearchResult, SearchType};
+use crate::traits::scoring::{ScoreFeatures, Scorer, ScoringPipeline};
+use crate::try_cosine_similarity;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 
 /// Client for performing search operations.
 #[derive(Debug, Clone)]
 pub struct SearchClient {
     embedding_client: EmbeddingsClient,
-    #[allow(dead_code)]
     rerank_client: Arc<Box<dyn RerankClient>>,
+    scoring: ScoringPipeline,
+    mmr_lambda: f32,
+    score_normalization: ScoreNormalization,
     #[allow(dead_code)]
     document_index: Arc<Mutex<HashMap<String, Vec<f32>>>>,
-    #[allow(dead_code)]
-    idf_scores: Arc<Mutex<HashMap<String, f32>>>,
-    #[allow(dead_code)]
-    avg_doc_length: Arc<Mutex<f32>>,
+}
+
+/// Default trade-off between relevance and diversity of
+/// [`SearchType::MaximalMarginalRelevance`] searches.
+pub const DEFAULT_MMR_LAMBDA: f32 = 0.5;
+
+/// Corpus statistics used to compute BM25 scores.
+struct Bm25Parameters {
+    idf_scores: HashMap<String, f32>,
+    avg_doc_length: f32,
 }
 
 impl SearchClient {
@@ -25,65 +42,283 @@ impl SearchClient {
         Self {
             embedding_client,
             rerank_client: Arc::new(Box::new(rerank_client)),
+            scoring: ScoringPipeline::new(),
+            mmr_lambda: DEFAULT_MMR_LAMBDA,
+            score_normalization: ScoreNormalization::None,
             document_index: Arc::new(Mutex::new(HashMap::new())),
-            idf_scores: Arc::new(Mutex::new(HashMap::new())),
-            avg_doc_length: Arc::new(Mutex::new(0.0)),
         }
     }
 
+    /// Registers a scorer at the end of the scoring pipeline.
+    pub fn with_scorer(mut self, scorer: impl Scorer) -> Self {
+        self.scoring.push(scorer);
+        self
+    }
+
+    /// Replaces the scoring pipeline.
+    pub fn with_scoring_pipeline(mut self, scoring: ScoringPipeline) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// Sets the `lambda` of [`SearchType::MaximalMarginalRelevance`]
+    /// searches, see [`SearchClient::search_mmr`]. Defaults to
+    /// [`DEFAULT_MMR_LAMBDA`].
+    pub fn with_mmr_lambda(mut self, lambda: f32) -> Self {
+        self.mmr_lambda = lambda.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets how the scores of the returned results are rescaled, after
+    /// reranking, scoring and MMR. Defaults to [`ScoreNormalization::None`].
+    pub fn with_score_normalization(mut self, normalization: ScoreNormalization) -> Self {
+        self.score_normalization = normalization;
+        self
+    }
+
     pub async fn search(&self, request: &SearchRequest) -> Result<Vec<SearchResult>, VoyageError> {
-        match request.search_type {
-            SearchType::Similarity => self.nearest_neighbor_search(request).await,
-            SearchType::NearestNeighbor => self.nearest_neighbor_search(request).await,
-            SearchType::BM25 => self.bm25_search(request).await,
-            _ => Err(VoyageError::SearchBuilderError(
-                "Unsupported search type".to_string(),
-            )),
-        }
+        self.run_search(request, self.mmr_lambda).await
     }
 
-    #[allow(dead_code)]
-    async fn nearest_neighbor_search(
+    /// Like [`search`](Self::search), sending the results best first over a
+    /// stream as [`RerankClient::find_similar_documents`] does. This only
+    /// adapts the finished search to a stream: the first result is sent once
+    /// every candidate has been scored, reranked and normalized. A failed
+    /// search is logged and ends the stream without results; the search is
+    /// canceled when the stream is dropped.
+    pub fn search_stream(&self, request: &SearchRequest) -> ReceiverStream<SearchResult> {
+        let (tx, rx) = mpsc::channel(16);
+        let client = self.clone();
+        let request = request.clone();
+        self.embedding_client
+            .config()
+            .lifecycle
+            .spawn_producer(tx.clone(), async move {
+                match client.search(&request).await {
+                    Ok(results) => {
+                        for result in results {
+                            if tx.send(result).await.is_err() {
+                                break; // receiver dropped
+                            }
+                        }
+                    }
+                    Err(e) => log::error!("Error performing search: {:?}", e),
+                }
+            });
+        ReceiverStream::new(rx)
+    }
+
+    /// Searches by cosine similarity, then picks the `top_k` results one at a
+    /// time by [maximal marginal relevance](maximal_marginal_relevance).
+    /// `lambda = 1` ranks by relevance alone; lower values skip
+    /// near-duplicates of better results. Relevance is the final score after
+    /// reranking and scoring. Results are returned in the order picked.
+    pub async fn search_mmr(
         &self,
         request: &SearchRequest,
+        lambda: f32,
     ) -> Result<Vec<SearchResult>, VoyageError> {
-        // Obtain embeddings for the query and documents
-        let query_embedding = self.embedding_client.embed(&request.query.query).await?;
-        let document_embeddings = match &request.documents {
-            Some(docs) => self.embedding_client.embed_batch(docs).await?,
-            None => {
-                return Err(VoyageError::MissingDocuments(
-                    "Missing documents".to_string(),
+        let mut request = request.clone();
+        request.search_type = SearchType::MaximalMarginalRelevance;
+        self.run_search(&request, lambda.clamp(0.0, 1.0)).await
+    }
+
+    async fn run_search(
+        &self,
+        request: &SearchRequest,
+        mmr_lambda: f32,
+    ) -> Result<Vec<SearchResult>, VoyageError> {
+        let (mut results, distances) = self.rank(request, mmr_lambda).await?;
+        if let Some(min_score) = request.min_score {
+            // Distances are better the lower they are, so the threshold is a
+            // maximum distance
+            results.retain(|result| match distances {
+                true => result.score <= min_score,
+                false => result.score >= min_score,
+            });
+        }
+        if distances && self.score_normalization != ScoreNormalization::None {
+            // Normalized scores are better the higher they are
+            results.iter_mut().for_each(|result| result.score = -result.score);
+        }
+        self.score_normalization.apply(&mut results);
+        Ok(results)
+    }
+
+    /// The ranked results, and whether their scores are Euclidean distances
+    /// (lower is better) rather than scores (higher is better).
+    async fn rank(
+        &self,
+        request: &SearchRequest,
+        mmr_lambda: f32,
+    ) -> Result<(Vec<SearchResult>, bool), VoyageError> {
+        // Document embeddings by index, kept for diversification
+        let mut embeddings: Option<HashMap<usize, Vec<f32>>> = None;
+        let mut distances = false;
+        let mut results = match request.search_type {
+            _ if request.index.is_some() => {
+                let (results, document_embeddings) = self.index_search(request).await?;
+                if request.search_type == SearchType::MaximalMarginalRelevance {
+                    embeddings = Some(document_embeddings);
+                }
+                results
+            }
+            SearchType::Similarity => self.nearest_duplicate_search(request).await?.0,
+            SearchType::NearestDuplicate => self.nearest_duplicate_search(request).await?.0,
+            SearchType::MaximalMarginalRelevance => {
+                let (results, document_embeddings) = self.nearest_duplicate_search(request).await?;
+                embeddings = Some(document_embeddings);
+                results
+            }
+            SearchType::NearestNeighbor => {
+                distances = true;
+                self.nearest_neighbor_search(request).await?
+            }
+            SearchType::BM25 => self.bm25_search(request).await?,
+            _ => {
+                return Err(VoyageError::SearchBuilderError(
+                    "Unsupported search type".to_string(),
                 ))
             }
         };
 
-        // Calculate distances
-        let mut results = request
+        let rerank_scores = if request.rerank {
+            self.rerank_scores(&request.query.query, &results).await
+        } else {
+            HashMap::new()
+        };
+
+        if !self.scoring.is_empty() || !rerank_scores.is_empty() {
+            let scores: Vec<f32> = results
+                .iter()
+                .map(|result| {
+                    // Scorers expect higher to be better, distances are not
+                    let similarity = match distances {
+                        true => 1.0 / (1.0 + result.score),
+                        false => result.score,
+                    };
+                    self.scoring.score(ScoreFeatures {
+                        index: result.index,
+                        document: result.document.first().map(String::as_str).unwrap_or_default(),
+                        similarity,
+                        rerank_score: rerank_scores.get(&result.index).copied(),
+                        metadata: result.metadata.as_ref(),
+                        score: similarity,
+                    })
+                })
+                .collect();
+            for (result, score) in results.iter_mut().zip(scores) {
+                result.score = score;
+            }
+            results.sort_by(|a, b| b.score.total_cmp(&a.score));
+            distances = false;
+        }
+
+        if let Some(embeddings) = embeddings {
+            let k = request.top_k.unwrap_or(results.len());
+            let relevance: Vec<f32> = results.iter().map(|result| result.score).collect();
+            let candidates: Vec<Vec<f32>> = results
+                .iter()
+                .map(|result| embeddings.get(&result.index).cloned().unwrap_or_default())
+                .collect();
+            let mut results: Vec<Option<SearchResult>> = results.into_iter().map(Some).collect();
+            let results = maximal_marginal_relevance(&relevance, &candidates, k, mmr_lambda)
+                .into_iter()
+                .filter_map(|position| results[position].take())
+                .collect();
+            return Ok((results, distances));
+        }
+
+        // Truncate to top_k if specified
+        if let Some(top_k) = request.top_k {
+            results.truncate(top_k);
+        }
+
+        Ok((results, distances))
+    }
+
+    /// Scores the leading results with the rerank endpoint, keyed by document index.
+    async fn rerank_scores(&self, query: &str, results: &[SearchResult]) -> HashMap<usize, f64> {
+        let candidates = &results[..results.len().min(MAX_DOCUMENTS)];
+        if candidates.is_empty() {
+            return HashMap::new();
+        }
+
+        let documents = candidates
+            .iter()
+            .map(|result| result.document.join("\n"))
+            .collect();
+        rerank_scores(self.rerank_client.as_ref().as_ref(), query, documents)
+            .await
+            .into_iter()
+            .map(|(position, score)| (candidates[position].index, score))
+            .collect()
+    }
+
+    /// Returns the documents that pass the request's metadata filter, with their
+    /// original indices.
+    fn filtered_documents(request: &SearchRequest) -> Result<Vec<(usize, String)>, VoyageError> {
+        let documents = request
             .documents
             .as_ref()
-            .unwrap()
+            .ok_or_else(|| VoyageError::MissingDocuments("Missing documents".to_string()))?;
+
+        Ok(documents
             .iter()
-            .zip(document_embeddings)
             .enumerate()
-            .map(|(index, (doc, doc_embedding))| {
+            .filter(|(index, _)| match &request.filter {
+                Some(filter) => filter.matches(
+                    request
+                        .metadata
+                        .as_ref()
+                        .and_then(|metadata| metadata.get(*index)),
+                ),
+                None => true,
+            })
+            .map(|(index, doc)| (index, doc.clone()))
+            .collect())
+    }
+
+    fn document_metadata(request: &SearchRequest, index: usize) -> Option<Metadata> {
+        request
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(index).cloned())
+    }
+
+    async fn nearest_neighbor_search(
+        &self,
+        request: &SearchRequest,
+    ) -> Result<Vec<SearchResult>, VoyageError> {
+        let (indices, documents): (Vec<usize>, Vec<String>) =
+            Self::filtered_documents(request)?.into_iter().unzip();
+        if documents.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Obtain embeddings for the query and documents
+        let query_embedding = self.embedding_client.embed_query(&request.query.query).await?;
+        let document_embeddings = self.embedding_client.embed_documents(&documents).await?;
+
+        // Calculate distances
+        let mut results = indices
+            .into_iter()
+            .zip(documents.iter())
+            .zip(document_embeddings)
+            .map(|((index, doc), doc_embedding)| {
                 let distance = Self::euclidean_distance(&query_embedding, &doc_embedding);
                 SearchResult {
                     document: vec![doc.clone()],
-                    score: distance as i32, // Convert to i32 for consistency
+                    score: distance,
                     index,
                     search_type: SearchType::NearestNeighbor,
+                    metadata: Self::document_metadata(request, index),
                 }
             })
             .collect::<Vec<_>>();
 
         // Sort results by distance (ascending)
-        results.sort_by(|a, b| a.score.cmp(&b.score));
-
-        // Truncate to top_k if specified
-        if let Some(top_k) = request.top_k {
-            results.truncate(top_k);
-        }
+        results.sort_by(|a, b| a.score.total_cmp(&b.score));
 
         Ok(results)
     }
@@ -98,98 +333,134 @@ impl SearchClient {
             .sqrt()
     }
 
-    /// Performs a nearest duplicate search to find similar documents.
-    #[allow(dead_code)]
+    /// Searches the documents of the request's index by cosine similarity
+    /// to the query, keeping those that match its filter. Also returns the
+    /// document embeddings by position in the index for diversification.
+    async fn index_search(
+        &self,
+        request: &SearchRequest,
+    ) -> Result<(Vec<SearchResult>, HashMap<usize, Vec<f32>>), VoyageError> {
+        let Some(source) = &request.index else {
+            return Err(VoyageError::MissingDocuments("Missing index".to_string()));
+        };
+        if !matches!(
+            request.search_type,
+            SearchType::Similarity
+                | SearchType::NearestDuplicate
+                | SearchType::MaximalMarginalRelevance
+        ) {
+            return Err(VoyageError::SearchBuilderError(format!(
+                "{:?} search is not supported over an index",
+                request.search_type
+            )));
+        }
+        let index = source.resolve_namespace(request.namespace.as_deref())?;
+        if index.is_empty() {
+            return Ok((Vec::new(), HashMap::new()));
+        }
+
+        // Fails if the index was built with another model
+        let model = self.embedding_client.config().embedding_model;
+        let query = Embedding::new(
+            self.embedding_client.embed_query(&request.query.query).await?,
+            model.to_string(),
+        );
+        let matches = index.search_embedding(&query, index.len(), request.filter.as_ref())?;
+
+        let keep_embeddings = request.search_type == SearchType::MaximalMarginalRelevance;
+        let mut results = Vec::with_capacity(matches.len());
+        let mut embeddings = HashMap::new();
+        for found in matches {
+            let Some(position) = index.position(&found.record.id) else {
+                continue;
+            };
+            if keep_embeddings {
+                if let Some((_, embedding)) = index.get(&found.record.id) {
+                    embeddings.insert(position, embedding.to_vec());
+                }
+            }
+            results.push(SearchResult {
+                document: vec![found.record.text.clone()],
+                score: found.score,
+                index: position,
+                search_type: request.search_type.clone(),
+                metadata: found.record.metadata.clone(),
+            });
+        }
+        Ok((results, embeddings))
+    }
+
+    /// Performs a nearest duplicate search to find similar documents. Also
+    /// returns the document embeddings by index.
     async fn nearest_duplicate_search(
         &self,
         request: &SearchRequest,
-    ) -> Result<Vec<SearchResult>, VoyageError> {
+    ) -> Result<(Vec<SearchResult>, HashMap<usize, Vec<f32>>), VoyageError> {
+        let (indices, documents): (Vec<usize>, Vec<String>) =
+            Self::filtered_documents(request)?.into_iter().unzip();
+        if documents.is_empty() {
+            return Ok((Vec::new(), HashMap::new()));
+        }
+
         // Obtain embeddings for the query and documents
-        let query_embedding = self.embedding_client.embed(&request.query.query).await?;
-        let document_embeddings = self
-            .embedding_client
-            .embed_batch(request.documents.as_ref().unwrap())
-            .await?;
+        let query_embedding = self.embedding_client.embed_query(&request.query.query).await?;
+        let document_embeddings = self.embedding_client.embed_documents(&documents).await?;
 
         // Calculate cosine similarities
-        let mut results = request
-            .documents
+        let mut results = indices
             .iter()
-            .zip(document_embeddings)
-            .enumerate()
-            .map(|(index, (doc, doc_embedding))| {
-                let similarity = Self::cosine_similarity(&query_embedding, &doc_embedding);
-                SearchResult {
-                    document: doc.clone(),
-                    score: similarity as i32, // Convert to i32 for consistency
+            .zip(documents.iter())
+            .zip(&document_embeddings)
+            .map(|((&index, doc), doc_embedding)| {
+                let similarity = try_cosine_similarity(&query_embedding, doc_embedding)?;
+                Ok(SearchResult {
+                    document: vec![doc.clone()],
+                    score: similarity,
                     index,
-                    search_type: SearchType::NearestDuplicate,
-                }
+                    search_type: request.search_type.clone(),
+                    metadata: Self::document_metadata(request, index),
+                })
             })
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>, VoyageError>>()?;
 
         // Sort results by similarity (descending)
-        results.sort_by(|a, b| b.score.cmp(&a.score));
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
 
-        // Truncate to top_k if specified
-        if let Some(top_k) = request.top_k {
-            results.truncate(top_k);
-        }
-
-        Ok(results)
-    }
-
-    // Helper function to calculate cosine similarity
-    fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
-        let dot_product = a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
-        let magnitude_a = a.iter().map(|x| x.powi(2)).sum::<f32>().sqrt();
-        let magnitude_b = b.iter().map(|x| x.powi(2)).sum::<f32>().sqrt();
-        dot_product / (magnitude_a * magnitude_b)
+        Ok((results, indices.into_iter().zip(document_embeddings).collect()))
     }
 
     /// Performs a BM25 search for improved text relevance.
-    #[allow(dead_code)]
     async fn bm25_search(&self, request: &SearchRequest) -> Result<Vec<SearchResult>, VoyageError> {
-        let documents = request
-            .documents
-            .as_ref()
-            .ok_or_else(|| VoyageError::MissingDocuments("Missing documents".to_string()))?;
-
-        // Ensure the IDF scores and average document length are calculated
-        {
-            let idf_scores = self.idf_scores.lock().unwrap();
-            let avg_doc_length = *self.avg_doc_length.lock().unwrap();
-            if idf_scores.is_empty() || avg_doc_length == 0.0 {
-                drop(idf_scores);
-                self.compute_bm25_parameters(documents);
-            }
+        let (indices, documents): (Vec<usize>, Vec<String>) =
+            Self::filtered_documents(request)?.into_iter().unzip();
+        if documents.is_empty() {
+            return Ok(Vec::new());
         }
 
+        // Calculate the IDF scores and average document length of this corpus
+        let parameters = Self::compute_bm25_parameters(&documents);
+
         // Tokenize the query
         let query_terms = Self::tokenize(&request.query.query);
 
         // Calculate BM25 scores
-        let mut results = documents
-            .iter()
-            .enumerate()
+        let mut results = indices
+            .into_iter()
+            .zip(documents.iter())
             .map(|(index, doc)| {
-                let score = self.compute_bm25_score(doc, &query_terms);
+                let score = Self::compute_bm25_score(&parameters, doc, &query_terms);
                 SearchResult {
                     document: vec![doc.to_string()],
-                    score: score as i32, // Convert to i32 for consistency
+                    score,
                     index,
                     search_type: SearchType::BM25,
+                    metadata: Self::document_metadata(request, index),
                 }
             })
             .collect::<Vec<_>>();
 
         // Sort results by score (descending)
-        results.sort_by(|a, b| b.score.cmp(&a.score));
-
-        // Truncate to top_k if specified
-        if let Some(top_k) = request.top_k {
-            results.truncate(top_k);
-        }
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
 
         Ok(results)
     }
@@ -197,7 +468,7 @@ impl SearchClient {
     // Helper methods for BM25
 
     /// Computes BM25 parameters like IDF scores and average document length.
-    fn compute_bm25_parameters(&self, documents: &[String]) {
+    fn compute_bm25_parameters(documents: &[String]) -> Bm25Parameters {
         let mut doc_lengths = Vec::new();
         let mut term_doc_counts = HashMap::new();
 
@@ -212,18 +483,22 @@ impl SearchClient {
         }
 
         let avg_doc_length = doc_lengths.iter().sum::<usize>() as f32 / documents.len() as f32;
-        *self.avg_doc_length.lock().unwrap() = avg_doc_length;
 
         let total_docs = documents.len() as f32;
-        let mut idf_scores = self.idf_scores.lock().unwrap();
+        let mut idf_scores = HashMap::new();
         for (term, doc_count) in term_doc_counts {
             let idf = ((total_docs - doc_count as f32 + 0.5) / (doc_count as f32 + 0.5) + 1.0).ln();
             idf_scores.insert(term, idf);
         }
+
+        Bm25Parameters {
+            idf_scores,
+            avg_doc_length,
+        }
     }
 
     /// Computes the BM25 score for a single document and query.
-    fn compute_bm25_score(&self, document: &str, query_terms: &[&str]) -> f32 {
+    fn compute_bm25_score(parameters: &Bm25Parameters, document: &str, query_terms: &[&str]) -> f32 {
         const K1: f32 = 1.5;
         const B: f32 = 0.75;
 
@@ -235,8 +510,8 @@ impl SearchClient {
             *term_frequencies.entry(term).or_insert(0) += 1;
         }
 
-        let idf_scores = self.idf_scores.lock().unwrap();
-        let avg_doc_length = *self.avg_doc_length.lock().unwrap();
+        let idf_scores = &parameters.idf_scores;
+        let avg_doc_length = parameters.avg_doc_length;
 
         let mut score = 0.0;
         for &term in query_terms {
@@ -257,3 +532,41 @@ impl SearchClient {
     }
 }
 
+/// Picks up to `k` candidates by maximal marginal relevance, returning their
+/// positions in the order picked. Each step picks the candidate maximizing
+/// `lambda * relevance[i] - (1 - lambda) * max_j cos(embeddings[i],
+/// embeddings[j])` over the candidates `j` already picked; the first pick is
+/// the most relevant candidate.
+pub fn maximal_marginal_relevance(
+    relevance: &[f32],
+    embeddings: &[Vec<f32>],
+    k: usize,
+    lambda: f32,
+) -> Vec<usize> {
+    let n = relevance.len().min(embeddings.len());
+    let mut picked = Vec::with_capacity(k.min(n));
+    let mut available = vec![true; n];
+    // Highest similarity of each candidate to the picked ones
+    let mut redundancy = vec![f32::NEG_INFINITY; n];
+    while picked.len() < k.min(n) {
+        let Some(best) = (0..n).filter(|&i| available[i]).max_by(|&a, &b| {
+            let score = |i: usize| {
+                if picked.is_empty() {
+                    relevance[i]
+                } else {
+                    lambda * relevance[i] - (1.0 - lambda) * redundancy[i]
+                }
+            };
+            score(a).total_cmp(&score(b)).then(b.cmp(&a))
+        }) else {
+            break;
+        };
+        available[best] = false;
+        picked.push(best);
+        for i in (0..n).filter(|&i| available[i]) {
+            let similarity = crate::cosine_similarity(&embeddings[i], &embeddings[best]);
+            redundancy[i] = redundancy[i].max(similarity);
+        }
+    }
+    picked
+}
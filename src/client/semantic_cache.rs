@@ -0,0 +1,223 @@
+//! Caching of results by query meaning rather than query text.
+//!
+//! [`SemanticCache`] stores a payload, such as a serialized LLM answer or
+//! search result, under the embedding of the query that produced it. A later
+//! query whose embedding is similar enough, e.g. a paraphrase, is answered
+//! from the cache:
+//!
+//! ```no_run
+//! # async fn answer(question: &str) -> String { String::new() }
+//! # async fn example() -> Result<(), voyageai::VoyageError> {
+//! use voyageai::{client::SemanticCache, VoyageAiClient};
+//!
+//! let client = VoyageAiClient::new();
+//! let cache: SemanticCache<String> = SemanticCache::new(&client);
+//! let question = "How do I reset my password?";
+//! let reply = cache
+//!     .get_or_insert_with(question, 0.92, || answer(question))
+//!     .await?;
+//! // Likely a cache hit
+//! let hit = cache.lookup("how can I reset my password", 0.92).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Queries are embedded as [`InputType::Query`](crate::InputType). A query
+//! identical to a cached one is answered without embedding it. When full,
+//! the cache evicts the least recently used entry.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::client::embeddings_client::Client as EmbeddingsClient;
+use crate::client::voyage_client::VoyageAiClient;
+use crate::cosine_similarity;
+use crate::errors::VoyageError;
+
+/// Default number of entries a [`SemanticCache`] holds.
+pub const DEFAULT_SEMANTIC_CACHE_CAPACITY: usize = 1024;
+
+/// A payload found by [`SemanticCache::lookup`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheHit<T> {
+    /// The cached query the payload was stored under
+    pub query: String,
+    pub payload: T,
+    /// Cosine similarity of the looked-up query to the cached one
+    pub similarity: f32,
+}
+
+#[derive(Debug)]
+struct CacheEntry<T> {
+    query: String,
+    embedding: Vec<f32>,
+    payload: T,
+}
+
+/// Cache keyed by query similarity, see the [module documentation](self).
+#[derive(Debug)]
+pub struct SemanticCache<T> {
+    embeddings_client: Arc<EmbeddingsClient>,
+    capacity: usize,
+    /// Least recently used first
+    entries: Mutex<VecDeque<CacheEntry<T>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<T: Clone> SemanticCache<T> {
+    /// An empty cache embedding queries with the client's embeddings client.
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self::with_embeddings_client(client.config.embeddings_client.clone())
+    }
+
+    pub fn with_embeddings_client(embeddings_client: Arc<EmbeddingsClient>) -> Self {
+        Self {
+            embeddings_client,
+            capacity: DEFAULT_SEMANTIC_CACHE_CAPACITY,
+            entries: Mutex::new(VecDeque::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets the number of entries kept. Defaults to
+    /// [`DEFAULT_SEMANTIC_CACHE_CAPACITY`].
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().unwrap().is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Number of lookups answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of lookups not answered from the cache.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Finds the cached payload whose query is most similar to `query`,
+    /// if its cosine similarity is at least `threshold`.
+    pub async fn lookup(
+        &self,
+        query: &str,
+        threshold: f32,
+    ) -> Result<Option<CacheHit<T>>, VoyageError> {
+        if let Some(hit) = self.lookup_exact(query) {
+            return Ok(Some(hit));
+        }
+        let embedding = self.embeddings_client.embed_query(query).await?;
+        Ok(self.lookup_embedding(&embedding, threshold))
+    }
+
+    /// [`lookup`](Self::lookup) with an already embedded query.
+    pub fn lookup_embedding(&self, embedding: &[f32], threshold: f32) -> Option<CacheHit<T>> {
+        let mut entries = self.entries.lock().unwrap();
+        let best = entries
+            .iter()
+            .enumerate()
+            .map(|(position, entry)| (position, cosine_similarity(embedding, &entry.embedding)))
+            .filter(|(_, similarity)| *similarity >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let hit = best.map(|(position, similarity)| {
+            let entry = Self::touch(&mut entries, position);
+            CacheHit {
+                query: entry.query.clone(),
+                payload: entry.payload.clone(),
+                similarity,
+            }
+        });
+        self.count(hit.is_some());
+        hit
+    }
+
+    /// Embeds `query` and caches `payload` under it, replacing the payload
+    /// of an identical query.
+    pub async fn insert(&self, query: &str, payload: T) -> Result<(), VoyageError> {
+        let embedding = self.embeddings_client.embed_query(query).await?;
+        self.insert_embedding(query, embedding, payload);
+        Ok(())
+    }
+
+    /// [`insert`](Self::insert) with an already embedded query.
+    pub fn insert_embedding(&self, query: impl Into<String>, embedding: Vec<f32>, payload: T) {
+        let query = query.into();
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|entry| entry.query != query);
+        while entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(CacheEntry {
+            query,
+            embedding,
+            payload,
+        });
+    }
+
+    /// Returns the payload cached for a query similar to `query`, or runs
+    /// `compute` and caches its result. The query is embedded at most once.
+    pub async fn get_or_insert_with<F, Fut>(
+        &self,
+        query: &str,
+        threshold: f32,
+        compute: F,
+    ) -> Result<T, VoyageError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        if let Some(hit) = self.lookup_exact(query) {
+            return Ok(hit.payload);
+        }
+        let embedding = self.embeddings_client.embed_query(query).await?;
+        if let Some(hit) = self.lookup_embedding(&embedding, threshold) {
+            return Ok(hit.payload);
+        }
+        let payload = compute().await;
+        self.insert_embedding(query, embedding, payload.clone());
+        Ok(payload)
+    }
+
+    /// A hit for an identical cached query; misses are not counted since a
+    /// similarity lookup follows.
+    fn lookup_exact(&self, query: &str) -> Option<CacheHit<T>> {
+        let mut entries = self.entries.lock().unwrap();
+        let position = entries.iter().position(|entry| entry.query == query)?;
+        let entry = Self::touch(&mut entries, position);
+        let hit = CacheHit {
+            query: entry.query.clone(),
+            payload: entry.payload.clone(),
+            similarity: 1.0,
+        };
+        self.count(true);
+        Some(hit)
+    }
+
+    /// Marks the entry at `position` as most recently used.
+    fn touch(entries: &mut VecDeque<CacheEntry<T>>, position: usize) -> &CacheEntry<T> {
+        let entry = entries.remove(position).expect("position is in bounds");
+        entries.push_back(entry);
+        entries.back().expect("entry was just pushed")
+    }
+
+    fn count(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
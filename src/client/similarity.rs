@@ -0,0 +1,91 @@
+//! One-call comparison of texts by embedding similarity.
+
+use tokio::sync::oneshot;
+
+use crate::client::tasks::AbortOnDrop;
+use crate::client::voyage_client::VoyageAiClient;
+use crate::errors::VoyageError;
+
+/// Future of [`VoyageAiClient::similarity`], resolving to the cosine
+/// similarity of two texts.
+pub struct Similarity {
+    receiver: oneshot::Receiver<Result<f32, VoyageError>>,
+    /// Aborts the task producing the result when this future is dropped
+    _task: AbortOnDrop,
+}
+
+impl std::future::Future for Similarity {
+    type Output = Result<f32, VoyageError>;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        std::pin::Pin::new(&mut self.receiver).poll(cx).map(|result| {
+            result.unwrap_or_else(|_| {
+                Err(VoyageError::Other("Similarity task canceled".to_string()))
+            })
+        })
+    }
+}
+
+/// Future of [`VoyageAiClient::similarity_matrix`], resolving to the cosine
+/// similarities between every pair of texts.
+pub struct SimilarityMatrix {
+    receiver: oneshot::Receiver<Result<Vec<Vec<f32>>, VoyageError>>,
+    /// Aborts the task producing the result when this future is dropped
+    _task: AbortOnDrop,
+}
+
+impl std::future::Future for SimilarityMatrix {
+    type Output = Result<Vec<Vec<f32>>, VoyageError>;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        std::pin::Pin::new(&mut self.receiver).poll(cx).map(|result| {
+            result.unwrap_or_else(|_| {
+                Err(VoyageError::Other(
+                    "Similarity matrix task canceled".to_string(),
+                ))
+            })
+        })
+    }
+}
+
+impl VoyageAiClient {
+    /// Embeds two texts and returns their cosine similarity, e.g.
+    /// `client.similarity("cat", "kitten").await?`.
+    ///
+    /// Both texts are embedded as documents, so swapping them gives the same
+    /// score.
+    pub fn similarity(&self, text_a: &str, text_b: &str) -> Similarity {
+        let embeddings_client = self.embeddings_client().clone();
+        let (text_a, text_b) = (text_a.to_string(), text_b.to_string());
+        let (tx, rx) = oneshot::channel();
+        let task = self.tasks().spawn(async move {
+            let _ = tx.send(embeddings_client.similarity(&text_a, &text_b).await);
+        });
+        Similarity {
+            receiver: rx,
+            _task: task,
+        }
+    }
+
+    /// Embeds `texts` and returns the cosine similarity of every pair: entry
+    /// `[i][j]` compares `texts[i]` with `texts[j]`. Texts are embedded as
+    /// documents, in as few requests as the API allows.
+    pub fn similarity_matrix(&self, texts: &[String]) -> SimilarityMatrix {
+        let embeddings_client = self.embeddings_client().clone();
+        let texts = texts.to_vec();
+        let (tx, rx) = oneshot::channel();
+        let task = self.tasks().spawn(async move {
+            let _ = tx.send(embeddings_client.similarity_matrix(&texts).await);
+        });
+        SimilarityMatrix {
+            receiver: rx,
+            _task: task,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Ownership of the tasks spawned behind the client's futures and streams.
+//!
+//! Methods such as [`VoyageAiClient::embeddings`] return a future backed by a
+//! spawned task. Instead of running detached, the task belongs to the
+//! client's [`TaskGroup`] and is aborted when the returned future is dropped,
+//! when [`VoyageAiClient::abort`] is called, when
+//! [`VoyageAiClient::shutdown`] times out, or when the last clone of the
+//! client is dropped.
+//! Streams stop their producer as soon as the stream is dropped.
+//!
+//! [`VoyageAiClient::embeddings`]: crate::VoyageAiClient::embeddings
+//! [`VoyageAiClient::abort`]: crate::VoyageAiClient::abort
+//! [`VoyageAiClient::shutdown`]: crate::VoyageAiClient::shutdown
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::mpsc;
+use tokio::task::{AbortHandle, JoinSet};
+
+/// The tasks spawned by a client. Clones share the same set; dropping the
+/// last clone aborts the tasks still running.
+#[derive(Debug, Clone, Default)]
+pub struct TaskGroup {
+    tasks: Arc<Mutex<JoinSet<()>>>,
+}
+
+impl TaskGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawns `future` in the group. The task is aborted when the returned
+    /// guard is dropped.
+    pub(crate) fn spawn<F>(&self, future: F) -> AbortOnDrop
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let mut tasks = self.tasks.lock().unwrap();
+        // Reap finished tasks so the set only holds running ones
+        while tasks.try_join_next().is_some() {}
+        AbortOnDrop(tasks.spawn(future))
+    }
+
+    /// Number of tasks that have not finished yet.
+    pub fn len(&self) -> usize {
+        let mut tasks = self.tasks.lock().unwrap();
+        while tasks.try_join_next().is_some() {}
+        tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Aborts every task in the group and waits for them to stop. Futures
+    /// awaiting an aborted task resolve to a cancellation error.
+    pub async fn shutdown(&self) {
+        let mut tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+        tasks.shutdown().await;
+    }
+}
+
+/// Aborts a spawned task when dropped. Held by the futures that await the
+/// task's result.
+#[derive(Debug)]
+pub(crate) struct AbortOnDrop(AbortHandle);
+
+impl AbortOnDrop {
+    /// Spawns `future` outside any group, tied to the returned guard.
+    pub(crate) fn spawn<F>(future: F) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        Self(tokio::spawn(future).abort_handle())
+    }
+}
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Spawns `producer`, which feeds a channel through its own clone of `tx`,
+/// and cancels it as soon as the receiving stream is dropped rather than when
+/// it next tries to send.
+pub(crate) fn spawn_producer<T, F>(tx: mpsc::Sender<T>, producer: F)
+where
+    T: Send + 'static,
+    F: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = producer => {}
+            _ = tx.closed() => {}
+        }
+    });
+}
@@ -1,9 +1,13 @@
 use std::sync::Arc;
+use std::time::Duration;
+use log::warn;
 use crate::{
     client::{
         embeddings_client::Client as EmbeddingsClient,
+        lifecycle::ShutdownReport,
         rerank_client::{DefaultRerankClient, RerankClient},
         search_client::SearchClient,
+        tasks::TaskGroup,
         RateLimiter
     },
     config::VoyageConfig,
@@ -12,6 +16,7 @@ use crate::{
     },
 };
 
+#[derive(Debug, Clone)]
 pub struct VoyageAiClientConfig {
     pub config: VoyageConfig,
     pub embeddings_client: Arc<EmbeddingsClient>,
@@ -19,8 +24,66 @@ pub struct VoyageAiClientConfig {
     pub search_client: Arc<SearchClient>,
 }
 
+/// A handle to the Voyage AI clients.
+///
+/// Cloning is cheap: clones share the configuration, the HTTP clients, the
+/// rate limiter and the [`TaskGroup`], so a client can be handed to every
+/// request handler of a web server. The tasks behind its futures are aborted
+/// when the last clone is dropped.
+#[derive(Clone)]
 pub struct VoyageAiClient {
-    pub config: VoyageAiClientConfig,
+    pub config: Arc<VoyageAiClientConfig>,
+    /// Tasks behind the futures returned by this client
+    tasks: TaskGroup,
+}
+
+/// Shows the configuration with the API key redacted.
+impl std::fmt::Debug for VoyageAiClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VoyageAiClient")
+            .field("config", &self.config.config)
+            .field("tasks", &self.tasks.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl From<VoyageAiClientConfig> for VoyageAiClient {
+    fn from(config: VoyageAiClientConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            tasks: TaskGroup::new(),
+        }
+    }
+}
+
+// Clients are shared across tasks and threads
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<VoyageAiClient>();
+    assert_send_sync::<VoyageAiClientConfig>();
+    assert_send_sync::<VoyageConfig>();
+    assert_send_sync::<EmbeddingsClient>();
+    assert_send_sync::<DefaultRerankClient>();
+    assert_send_sync::<SearchClient>();
+    assert_send_sync::<RateLimiter>();
+    assert_send_sync::<TaskGroup>();
+    assert_send_sync::<crate::client::AutoBatcher>();
+    assert_send_sync::<crate::client::LocalReranker>();
+    // ...and so are the futures they return
+    assert_send_sync::<crate::traits::voyage::EmbeddingTask>();
+    assert_send_sync::<crate::traits::voyage::SearchTask>();
+    assert_send_sync::<crate::traits::llm::TextEmbedding>();
+    assert_send_sync::<crate::traits::llm::BatchEmbedding>();
+    assert_send_sync::<crate::client::rerank_client::AsyncDocumentSimilarity>();
+    assert_send_sync::<crate::client::rerank_client::AsyncRerankMatch>();
+    assert_send_sync::<crate::client::Similarity>();
+    assert_send_sync::<crate::client::SimilarityMatrix>();
+};
+
+impl Default for VoyageAiClient {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl VoyageAiClient {
@@ -35,8 +98,9 @@ impl VoyageAiClient {
     }
     
     pub fn new_with_config(config: VoyageConfig) -> Self {
-        let rate_limiter = Arc::new(RateLimiter::new());
-        let embeddings_client = EmbeddingsClient::new(config.clone());
+        let rate_limiter = Arc::new(config.rate_limiter());
+        let embeddings_client =
+            EmbeddingsClient::new(config.clone()).with_rate_limiter(rate_limiter.clone());
         let rerank_client = DefaultRerankClient::new(config.clone(), rate_limiter.clone());
         
         // Create the search client with the unwrapped clients
@@ -53,9 +117,51 @@ impl VoyageAiClient {
             search_client,
         };
         
-        Self {
-            config: client_config,
+        Self::from(client_config)
+    }
+
+    /// The tasks running behind futures returned by this client.
+    pub fn tasks(&self) -> &TaskGroup {
+        &self.tasks
+    }
+
+    /// Shuts the client down gracefully, e.g. before a service restarts:
+    /// new requests fail with
+    /// [`VoyageError::ShuttingDown`](crate::VoyageError::ShuttingDown) from now on,
+    /// batching layers flush, and the requests, futures, streams and jobs
+    /// already started get up to `timeout` to finish. Tasks of the client
+    /// still running after that are aborted. See
+    /// [`lifecycle`](crate::client::lifecycle).
+    ///
+    /// The shutdown applies to every client sharing the configuration.
+    pub async fn shutdown(&self, timeout: Duration) -> ShutdownReport {
+        let lifecycle = &self.voyage_config().lifecycle;
+        lifecycle.close();
+        let drained = lifecycle.wait_idle(timeout).await;
+        let in_flight = lifecycle.in_flight();
+        if !drained {
+            warn!("Shutdown timed out with {} requests in flight", in_flight);
         }
+        let aborted = self.tasks.len();
+        self.tasks.shutdown().await;
+        ShutdownReport {
+            drained,
+            in_flight,
+            aborted,
+        }
+    }
+
+    /// Aborts the requests still running behind the futures of this client
+    /// and its clones, and waits for them to stop. Dropping the last clone
+    /// aborts them as well. Unlike [`shutdown`](Self::shutdown), the client
+    /// stays usable.
+    pub async fn abort(&self) {
+        self.tasks.shutdown().await;
+    }
+
+    /// The configuration the client was created with.
+    pub fn voyage_config(&self) -> &VoyageConfig {
+        &self.config.config
     }
 
     pub fn embeddings_client(&self) -> &Arc<EmbeddingsClient> {
@@ -72,10 +178,26 @@ impl VoyageAiClient {
         self.config.rerank_client.find_similar_documents(query, documents)
     }
     
+    /// Reranks a request built with [`rerank_request`](Self::rerank_request)
+    /// and returns a stream of the results, best first.
+    pub fn rerank_stream(&self, request: crate::models::rerank::RerankRequest) -> tokio_stream::wrappers::ReceiverStream<crate::client::rerank_client::DocumentSimilarity> {
+        self.config.rerank_client.rerank_stream(request)
+    }
+    
+    /// Like `find_similar_documents`, ending the stream with the request's usage.
+    pub fn find_similar_documents_with_usage(&self, query: &str, documents: Vec<String>) -> tokio_stream::wrappers::ReceiverStream<crate::client::rerank_client::RerankStreamItem> {
+        self.config.rerank_client.find_similar_documents_with_usage(query, documents)
+    }
+    
     /// Finds the single most similar document to a query.
     pub fn most_similar_document(&self, query: &str, documents: Vec<String>) -> crate::client::rerank_client::AsyncDocumentSimilarity {
         self.config.rerank_client.most_similar_document(query, documents)
     }
+
+    /// Like `most_similar_document`, also returning the request's usage.
+    pub fn most_similar_document_with_usage(&self, query: &str, documents: Vec<String>) -> crate::client::rerank_client::AsyncRerankMatch {
+        self.config.rerank_client.most_similar_document_with_usage(query, documents)
+    }
     
     // Implement embeddings method for backward compatibility
     pub fn embeddings(&self, request: EmbeddingsRequest) -> crate::traits::voyage::EmbeddingTask {
@@ -83,14 +205,18 @@ impl VoyageAiClient {
         let embeddings_client = self.config.embeddings_client.clone();
         
         let (tx, rx) = tokio::sync::oneshot::channel();
+        let work = self.voyage_config().lifecycle.begin();
         
-        tokio::task::spawn(async move {
-            let result = embeddings_client.create_embedding(&request).await
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>);
+        let task = self.tasks.spawn(async move {
+            let result = match work {
+                Ok(work) => work.scope(embeddings_client.create_embedding(&request)).await,
+                Err(e) => Err(e),
+            };
+            let result = result.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>);
             let _ = tx.send(result);
         });
         
-        crate::traits::voyage::EmbeddingTask::new(rx)
+        crate::traits::voyage::EmbeddingTask::new(rx).with_task(task)
     }
     
     // Implement search method for backward compatibility
@@ -99,13 +225,24 @@ impl VoyageAiClient {
         let search_client = self.config.search_client.clone();
         
         let (tx, rx) = tokio::sync::oneshot::channel();
+        let work = self.voyage_config().lifecycle.begin();
         
-        tokio::task::spawn(async move {
-            let result = search_client.search(&request).await
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>);
+        let task = self.tasks.spawn(async move {
+            let result = match work {
+                Ok(work) => work.scope(search_client.search(&request)).await,
+                Err(e) => Err(e),
+            };
+            let result = result.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>);
             let _ = tx.send(result);
         });
         
-        crate::traits::voyage::SearchTask::new(rx)
+        crate::traits::voyage::SearchTask::new(rx).with_task(task)
+    }
+
+    /// Searches and returns a stream of the results, best first, once the
+    /// search has finished; see
+    /// [`SearchClient::search_stream`](crate::client::SearchClient::search_stream).
+    pub fn search_stream(&self, request: &crate::client::SearchRequest) -> tokio_stream::wrappers::ReceiverStream<crate::client::SearchResult> {
+        self.config.search_client.search_stream(request)
     }
 }
@@ -0,0 +1,458 @@
+//! A serializable outline of Rust source, embedded by
+//! [`embed_code`](crate::client::embeddings_client::Client::embed_code), and
+//! [`parse_rust_ast`] building it with `syn` when the `ast` feature is on.
+//!
+//! Each item keeps its signature, generics, attributes and doc comments, and
+//! impls and traits keep their methods, so that the JSON form reflects the
+//! structure of the code rather than its token stream. Bodies are left out.
+//! Optional and empty fields are skipped when serializing, and default when
+//! reading outlines saved by earlier versions.
+
+#[cfg(feature = "ast")]
+use quote::ToTokens;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ast")]
+use syn::{Item as SynItem, ItemEnum, ItemImpl, ItemMod, ItemStruct, ItemTrait, ItemUse};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SerializableAst {
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Item {
+    Function(Function),
+    Struct(Struct),
+    Enum(Enum),
+    Module(Module),
+    Use(Use),
+    Impl(Impl),
+    Trait(Trait),
+    TypeAlias(TypeAlias),
+    Const(Const),
+    Other(String),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Function {
+    pub name: String,
+    pub visibility: Option<String>,
+    pub inputs: Vec<String>,
+    pub output: Option<String>,
+    pub is_async: bool,
+    /// Generic parameters and where clause, e.g. `<T : Clone> where T : Send`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generics: Option<String>,
+    /// Attributes other than doc comments, e.g. `# [inline]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Struct {
+    pub name: String,
+    pub visibility: Option<String>,
+    pub fields: Vec<Field>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generics: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Field {
+    pub name: String,
+    pub ty: String,
+    pub visibility: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Enum {
+    pub name: String,
+    pub visibility: Option<String>,
+    pub variants: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generics: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Module {
+    pub name: String,
+    pub visibility: Option<String>,
+    /// Items of an inline `mod name { ... }`; empty for `mod name;`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<Item>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Use {
+    pub path: String,
+}
+
+/// An `impl` block, inherent or of a trait.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Impl {
+    /// The type implemented, e.g. `Vec < T >`
+    pub self_ty: String,
+    /// The trait implemented, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trait_: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generics: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub methods: Vec<Function>,
+    /// Associated types of the block
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<TypeAlias>,
+    /// Associated constants of the block
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub consts: Vec<Const>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+}
+
+/// A trait definition.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Trait {
+    pub name: String,
+    pub visibility: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generics: Option<String>,
+    /// Supertrait bounds, e.g. `Send`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub supertraits: Vec<String>,
+    /// Required and provided methods
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub methods: Vec<Function>,
+    /// Associated types, with their bounds and defaults
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<TypeAlias>,
+    /// Associated constants, with their defaults
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub consts: Vec<Const>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+}
+
+/// A type alias, or an associated type of an impl or trait.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TypeAlias {
+    pub name: String,
+    pub visibility: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generics: Option<String>,
+    /// The aliased type; `None` for an associated type of a trait without
+    /// a default
+    pub ty: Option<String>,
+    /// Bounds of an associated type of a trait, e.g. `Clone`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bounds: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+}
+
+/// A `const` or `static` item, or an associated constant.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Const {
+    pub name: String,
+    pub visibility: Option<String>,
+    pub ty: String,
+    #[serde(default)]
+    pub is_static: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+}
+
+/// Parses Rust source into a [`SerializableAst`]. Requires the `ast` feature.
+#[cfg(feature = "ast")]
+pub fn parse_rust_ast(code: &str) -> Result<SerializableAst, syn::Error> {
+    let file = syn::parse_file(code)?;
+    let items = file.items.into_iter().map(convert_item).collect();
+    Ok(SerializableAst { items })
+}
+
+#[cfg(feature = "ast")]
+fn convert_item(item: SynItem) -> Item {
+    match item {
+        SynItem::Fn(f) => Item::Function(convert_signature(&f.sig, Some(&f.vis), &f.attrs)),
+        SynItem::Struct(s) => Item::Struct(convert_struct(s)),
+        SynItem::Enum(e) => Item::Enum(convert_enum(e)),
+        SynItem::Mod(m) => Item::Module(convert_module(m)),
+        SynItem::Use(u) => Item::Use(convert_use(u)),
+        SynItem::Impl(i) => Item::Impl(convert_impl(i)),
+        SynItem::Trait(t) => Item::Trait(convert_trait(t)),
+        SynItem::Type(t) => Item::TypeAlias(TypeAlias {
+            name: t.ident.to_string(),
+            visibility: Some(t.vis.to_token_stream().to_string()),
+            generics: convert_generics(&t.generics),
+            ty: Some(t.ty.to_token_stream().to_string()),
+            bounds: Vec::new(),
+            attributes: attributes(&t.attrs),
+            docs: docs(&t.attrs),
+        }),
+        SynItem::Const(c) => Item::Const(Const {
+            name: c.ident.to_string(),
+            visibility: Some(c.vis.to_token_stream().to_string()),
+            ty: c.ty.to_token_stream().to_string(),
+            is_static: false,
+            attributes: attributes(&c.attrs),
+            docs: docs(&c.attrs),
+        }),
+        SynItem::Static(s) => Item::Const(Const {
+            name: s.ident.to_string(),
+            visibility: Some(s.vis.to_token_stream().to_string()),
+            ty: s.ty.to_token_stream().to_string(),
+            is_static: true,
+            attributes: attributes(&s.attrs),
+            docs: docs(&s.attrs),
+        }),
+        other => Item::Other(other.to_token_stream().to_string()),
+    }
+}
+
+/// The text of the `///` and `/** */` doc comments among `attrs`, one line
+/// per comment line.
+#[cfg(feature = "ast")]
+pub(crate) fn docs(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(doc),
+                    ..
+                }) => Some(doc.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+/// The attributes among `attrs` other than doc comments.
+#[cfg(feature = "ast")]
+fn attributes(attrs: &[syn::Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| !attr.path().is_ident("doc"))
+        .map(|attr| attr.to_token_stream().to_string())
+        .collect()
+}
+
+/// Generic parameters followed by the where clause, if there are any.
+#[cfg(feature = "ast")]
+fn convert_generics(generics: &syn::Generics) -> Option<String> {
+    if generics.params.is_empty() && generics.where_clause.is_none() {
+        return None;
+    }
+    let mut text = generics.to_token_stream().to_string();
+    if let Some(where_clause) = &generics.where_clause {
+        text.push(' ');
+        text.push_str(&where_clause.to_token_stream().to_string());
+    }
+    Some(text.trim().to_string())
+}
+
+/// Functions, and methods of impls and traits; trait methods have no
+/// visibility of their own.
+#[cfg(feature = "ast")]
+fn convert_signature(
+    sig: &syn::Signature,
+    vis: Option<&syn::Visibility>,
+    attrs: &[syn::Attribute],
+) -> Function {
+    Function {
+        name: sig.ident.to_string(),
+        visibility: vis.map(|vis| vis.to_token_stream().to_string()),
+        inputs: sig
+            .inputs
+            .iter()
+            .map(|arg| arg.to_token_stream().to_string())
+            .collect(),
+        output: match &sig.output {
+            syn::ReturnType::Default => None,
+            syn::ReturnType::Type(_, ty) => Some(ty.to_token_stream().to_string()),
+        },
+        is_async: sig.asyncness.is_some(),
+        generics: convert_generics(&sig.generics),
+        attributes: attributes(attrs),
+        docs: docs(attrs),
+    }
+}
+
+#[cfg(feature = "ast")]
+fn convert_struct(s: ItemStruct) -> Struct {
+    Struct {
+        name: s.ident.to_string(),
+        visibility: Some(s.vis.to_token_stream().to_string()),
+        fields: s
+            .fields
+            .iter()
+            .map(|f| Field {
+                name: f.ident.as_ref().map(|i| i.to_string()).unwrap_or_default(),
+                ty: f.ty.to_token_stream().to_string(),
+                visibility: Some(f.vis.to_token_stream().to_string()),
+                docs: docs(&f.attrs),
+            })
+            .collect(),
+        generics: convert_generics(&s.generics),
+        attributes: attributes(&s.attrs),
+        docs: docs(&s.attrs),
+    }
+}
+
+#[cfg(feature = "ast")]
+fn convert_enum(e: ItemEnum) -> Enum {
+    Enum {
+        name: e.ident.to_string(),
+        visibility: Some(e.vis.to_token_stream().to_string()),
+        variants: e.variants.iter().map(|v| v.ident.to_string()).collect(),
+        generics: convert_generics(&e.generics),
+        attributes: attributes(&e.attrs),
+        docs: docs(&e.attrs),
+    }
+}
+
+#[cfg(feature = "ast")]
+fn convert_module(m: ItemMod) -> Module {
+    Module {
+        name: m.ident.to_string(),
+        visibility: Some(m.vis.to_token_stream().to_string()),
+        docs: docs(&m.attrs),
+        items: m
+            .content
+            .map(|(_, items)| items.into_iter().map(convert_item).collect())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(feature = "ast")]
+fn convert_use(u: ItemUse) -> Use {
+    Use {
+        path: u.tree.to_token_stream().to_string(),
+    }
+}
+
+#[cfg(feature = "ast")]
+fn convert_impl(i: ItemImpl) -> Impl {
+    let mut methods = Vec::new();
+    let mut types = Vec::new();
+    let mut consts = Vec::new();
+    for item in &i.items {
+        match item {
+            syn::ImplItem::Fn(f) => methods.push(convert_signature(&f.sig, Some(&f.vis), &f.attrs)),
+            syn::ImplItem::Type(t) => types.push(TypeAlias {
+                name: t.ident.to_string(),
+                visibility: Some(t.vis.to_token_stream().to_string()),
+                generics: convert_generics(&t.generics),
+                ty: Some(t.ty.to_token_stream().to_string()),
+                bounds: Vec::new(),
+                attributes: attributes(&t.attrs),
+                docs: docs(&t.attrs),
+            }),
+            syn::ImplItem::Const(c) => consts.push(Const {
+                name: c.ident.to_string(),
+                visibility: Some(c.vis.to_token_stream().to_string()),
+                ty: c.ty.to_token_stream().to_string(),
+                is_static: false,
+                attributes: attributes(&c.attrs),
+                docs: docs(&c.attrs),
+            }),
+            _ => {}
+        }
+    }
+    Impl {
+        self_ty: i.self_ty.to_token_stream().to_string(),
+        trait_: i.trait_.as_ref().map(|(negative, path, _)| {
+            let path = path.to_token_stream().to_string();
+            if negative.is_some() {
+                format!("!{path}")
+            } else {
+                path
+            }
+        }),
+        generics: convert_generics(&i.generics),
+        methods,
+        types,
+        consts,
+        attributes: attributes(&i.attrs),
+        docs: docs(&i.attrs),
+    }
+}
+
+#[cfg(feature = "ast")]
+fn convert_trait(t: ItemTrait) -> Trait {
+    let mut methods = Vec::new();
+    let mut types = Vec::new();
+    let mut consts = Vec::new();
+    for item in &t.items {
+        match item {
+            syn::TraitItem::Fn(f) => methods.push(convert_signature(&f.sig, None, &f.attrs)),
+            syn::TraitItem::Type(ty) => types.push(TypeAlias {
+                name: ty.ident.to_string(),
+                visibility: None,
+                generics: convert_generics(&ty.generics),
+                ty: ty
+                    .default
+                    .as_ref()
+                    .map(|(_, ty)| ty.to_token_stream().to_string()),
+                bounds: ty
+                    .bounds
+                    .iter()
+                    .map(|bound| bound.to_token_stream().to_string())
+                    .collect(),
+                attributes: attributes(&ty.attrs),
+                docs: docs(&ty.attrs),
+            }),
+            syn::TraitItem::Const(c) => consts.push(Const {
+                name: c.ident.to_string(),
+                visibility: None,
+                ty: c.ty.to_token_stream().to_string(),
+                is_static: false,
+                attributes: attributes(&c.attrs),
+                docs: docs(&c.attrs),
+            }),
+            _ => {}
+        }
+    }
+    Trait {
+        name: t.ident.to_string(),
+        visibility: Some(t.vis.to_token_stream().to_string()),
+        generics: convert_generics(&t.generics),
+        supertraits: t
+            .supertraits
+            .iter()
+            .map(|bound| bound.to_token_stream().to_string())
+            .collect(),
+        methods,
+        types,
+        consts,
+        attributes: attributes(&t.attrs),
+        docs: docs(&t.attrs),
+    }
+}
@@ -0,0 +1,324 @@
+//! Splitting source code into one chunk per item.
+//!
+//! A [`LanguageParser`] turns the source of one language into a
+//! [`CodeChunk`] per function, method, type and similar item, with the
+//! module path, qualified name, line range, visibility and doc comment of the
+//! item. [`CodeChunk::metadata`] turns those into [`Metadata`] for a vector
+//! store, so that search hits can link to the exact lines of the source, and
+//! [`CodeChunker`] plugs a parser into a
+//! [`RetrievalPipeline`](crate::RetrievalPipeline).
+//!
+//! The parsers available depend on the enabled features:
+//!
+//! - `RustParser`, built on `syn`, with the `ast` feature.
+//! - `PythonParser`, `TypeScriptParser`, `GoParser` and `JavaParser`, built on
+//!   tree-sitter grammars, with the `tree-sitter` feature.
+//!
+//! [`parser_for_path`] picks one by file extension.
+//!
+//! ```
+//! # #[cfg(feature = "ast")]
+//! # {
+//! use voyageai::code::CodeChunker;
+//!
+//! let chunker = CodeChunker::for_file("src/shapes.rs").unwrap();
+//! let chunks = chunker
+//!     .chunk_items("pub struct Square(f64);\n\npub fn unit() -> Square {\n    Square(1.0)\n}\n")
+//!     .unwrap();
+//! assert_eq!(chunks[1].qualified_name(), "shapes::unit");
+//! assert_eq!((chunks[1].start_line, chunks[1].end_line), (3, 5));
+//! # }
+//! ```
+
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::chunking::{Chunker, ParagraphChunker};
+use crate::models::metadata::Metadata;
+
+/// The kind of item a [`CodeChunk`] holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CodeItemKind {
+    Function,
+    /// A function of an impl block, class or Go receiver type
+    Method,
+    Struct,
+    Enum,
+    Union,
+    Trait,
+    /// An impl block without methods
+    Impl,
+    TypeAlias,
+    Const,
+    Static,
+    /// A `macro_rules!` definition
+    Macro,
+    /// A class, or a Java record
+    Class,
+    Interface,
+}
+
+impl CodeItemKind {
+    /// The keyword of the kind, e.g. `"fn"` for functions and methods.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CodeItemKind::Function | CodeItemKind::Method => "fn",
+            CodeItemKind::Struct => "struct",
+            CodeItemKind::Enum => "enum",
+            CodeItemKind::Union => "union",
+            CodeItemKind::Trait => "trait",
+            CodeItemKind::Impl => "impl",
+            CodeItemKind::TypeAlias => "type",
+            CodeItemKind::Const => "const",
+            CodeItemKind::Static => "static",
+            CodeItemKind::Macro => "macro_rules",
+            CodeItemKind::Class => "class",
+            CodeItemKind::Interface => "interface",
+        }
+    }
+}
+
+impl std::fmt::Display for CodeItemKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One item of a source file, with where it sits in the source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeChunk {
+    /// The source lines of the item, including its doc comment and
+    /// attributes or decorators
+    pub text: String,
+    /// Language of the source, as in [`LanguageParser::language`]
+    #[serde(default)]
+    pub language: String,
+    /// Path of the enclosing module, e.g. `["client", "search_client"]`
+    pub module_path: Vec<String>,
+    /// Name of the enclosing type for methods and impl blocks
+    pub parent: Option<String>,
+    /// Name of the item; the implemented type for impl blocks
+    pub name: String,
+    pub kind: CodeItemKind,
+    /// First line of the item, from 1
+    pub start_line: usize,
+    /// Last line of the item, inclusive
+    pub end_line: usize,
+    /// Visibility as written, e.g. `pub(crate)` or `public`; empty when
+    /// private or when the language has none
+    pub visibility: String,
+    pub docs: Option<String>,
+}
+
+impl CodeChunk {
+    /// Module path, parent and name joined with `::` for Rust and `.` for
+    /// other languages, e.g. `"client::SearchClient::search"`.
+    pub fn qualified_name(&self) -> String {
+        self.module_path
+            .iter()
+            .map(String::as_str)
+            .chain(self.parent.as_deref())
+            .chain([self.name.as_str()])
+            .collect::<Vec<_>>()
+            .join(self.separator())
+    }
+
+    /// The item preceded by a comment with its qualified name (`#` for
+    /// Python, `//` otherwise), so that its embedding reflects where the item
+    /// sits in the project.
+    pub fn text_with_path(&self) -> String {
+        let comment = if self.language == "python" { "#" } else { "//" };
+        format!("{} {}\n{}", comment, self.qualified_name(), self.text)
+    }
+
+    /// Location metadata for a vector store: `item` (the qualified name),
+    /// `name`, `language`, `module_path` joined like the qualified name,
+    /// `kind`, `start_line`, `end_line`, `visibility` and, for documented
+    /// items, `docs`.
+    pub fn metadata(&self) -> Metadata {
+        let mut metadata = Metadata::new();
+        metadata.insert("item".to_string(), self.qualified_name().into());
+        metadata.insert("name".to_string(), self.name.clone().into());
+        metadata.insert("language".to_string(), self.language.clone().into());
+        metadata.insert(
+            "module_path".to_string(),
+            self.module_path.join(self.separator()).into(),
+        );
+        metadata.insert("kind".to_string(), self.kind.as_str().into());
+        metadata.insert("start_line".to_string(), (self.start_line as f64).into());
+        metadata.insert("end_line".to_string(), (self.end_line as f64).into());
+        metadata.insert("visibility".to_string(), self.visibility.clone().into());
+        if let Some(docs) = &self.docs {
+            metadata.insert("docs".to_string(), docs.clone().into());
+        }
+        metadata
+    }
+
+    fn separator(&self) -> &'static str {
+        if self.language == "rust" {
+            "::"
+        } else {
+            "."
+        }
+    }
+}
+
+/// Source that a [`LanguageParser`] could not parse.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{language} source does not parse: {message}")]
+pub struct CodeParseError {
+    pub language: &'static str,
+    pub message: String,
+}
+
+/// Splits the source of one language into items.
+pub trait LanguageParser: std::fmt::Debug + Send + Sync {
+    /// Name of the language, e.g. `"rust"`; recorded in
+    /// [`CodeChunk::language`].
+    fn language(&self) -> &'static str;
+
+    /// File extensions of the language, without the dot.
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// The module path of the file at `path`; defaults to
+    /// [`module_path_for`].
+    fn module_path(&self, path: &Path) -> Vec<String> {
+        module_path_for(path)
+    }
+
+    /// Splits `source` into one [`CodeChunk`] per item, in source order.
+    /// `module_path` is the path of the file's module, e.g. from
+    /// [`module_path`](Self::module_path).
+    fn parse_items(
+        &self,
+        source: &str,
+        module_path: &[String],
+    ) -> Result<Vec<CodeChunk>, CodeParseError>;
+}
+
+/// The parsers enabled by the crate's features.
+#[allow(clippy::vec_init_then_push)]
+pub fn parsers() -> Vec<Arc<dyn LanguageParser>> {
+    #[allow(unused_mut)]
+    let mut parsers: Vec<Arc<dyn LanguageParser>> = Vec::new();
+    #[cfg(feature = "ast")]
+    parsers.push(Arc::new(super::rust::RustParser));
+    #[cfg(feature = "tree-sitter")]
+    parsers.extend([
+        Arc::new(super::languages::PythonParser) as Arc<dyn LanguageParser>,
+        Arc::new(super::languages::TypeScriptParser::new()),
+        Arc::new(super::languages::TypeScriptParser::tsx()),
+        Arc::new(super::languages::GoParser),
+        Arc::new(super::languages::JavaParser),
+    ]);
+    parsers
+}
+
+/// The enabled parser for the extension of `path`, if any.
+pub fn parser_for_path(path: &Path) -> Option<Arc<dyn LanguageParser>> {
+    let extension = path.extension()?.to_str()?;
+    parsers()
+        .into_iter()
+        .find(|parser| parser.extensions().contains(&extension))
+}
+
+/// The module path of the file at `path` within its project, from the
+/// components after `src` without the extension: `src/client/mod.rs` is
+/// `["client"]`, `src/client/search_client.rs` is
+/// `["client", "search_client"]` and `src/lib.rs` is empty. A last component
+/// of `mod`, `lib`, `main`, `__init__` or `index` names its directory and is
+/// dropped.
+pub fn module_path_for(path: &Path) -> Vec<String> {
+    module_path_after(path, "src")
+}
+
+/// [`module_path_for`] with the path starting after the last `root`
+/// component, or at the first component without one.
+pub(crate) fn module_path_after(path: &Path, root: &str) -> Vec<String> {
+    let components: Vec<String> = path
+        .with_extension("")
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let start = components
+        .iter()
+        .rposition(|component| component == root)
+        .map_or(0, |root| root + 1);
+    let mut module_path = components[start..].to_vec();
+    if matches!(
+        module_path.last().map(String::as_str),
+        Some("mod" | "lib" | "main" | "__init__" | "index")
+    ) {
+        module_path.pop();
+    }
+    module_path
+}
+
+/// A [`Chunker`] returning the [`text_with_path`](CodeChunk::text_with_path)
+/// of each item found by a [`LanguageParser`]. Text that does not parse, or
+/// has no items, is split into paragraphs instead.
+#[derive(Debug, Clone)]
+pub struct CodeChunker {
+    parser: Arc<dyn LanguageParser>,
+    module_path: Vec<String>,
+}
+
+impl CodeChunker {
+    pub fn new(parser: impl LanguageParser + 'static) -> Self {
+        Self::with_parser(Arc::new(parser))
+    }
+
+    pub fn with_parser(parser: Arc<dyn LanguageParser>) -> Self {
+        Self {
+            parser,
+            module_path: Vec::new(),
+        }
+    }
+
+    /// A chunker for the file at `path`, with the parser for its extension
+    /// (see [`parser_for_path`]) and the parser's module path for it. `None`
+    /// when no enabled parser handles the extension.
+    pub fn for_file(path: impl AsRef<Path>) -> Option<Self> {
+        let path = path.as_ref();
+        let parser = parser_for_path(path)?;
+        let module_path = parser.module_path(path);
+        Some(Self::with_parser(parser).with_module_path(module_path))
+    }
+
+    pub fn with_module_path(mut self, module_path: Vec<String>) -> Self {
+        self.module_path = module_path;
+        self
+    }
+
+    pub fn module_path(&self) -> &[String] {
+        &self.module_path
+    }
+
+    pub fn language(&self) -> &'static str {
+        self.parser.language()
+    }
+
+    /// The items of `source`, see [`LanguageParser::parse_items`].
+    pub fn chunk_items(&self, source: &str) -> Result<Vec<CodeChunk>, CodeParseError> {
+        self.parser.parse_items(source, &self.module_path)
+    }
+}
+
+impl Chunker for CodeChunker {
+    fn chunk(&self, text: &str) -> Vec<String> {
+        match self.chunk_items(text) {
+            Ok(chunks) if !chunks.is_empty() => {
+                chunks.iter().map(CodeChunk::text_with_path).collect()
+            }
+            Ok(_) => ParagraphChunker::default().chunk(text),
+            Err(e) => {
+                log::debug!("{}, splitting into paragraphs", e);
+                ParagraphChunker::default().chunk(text)
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! An in-memory index of code searched by text and syntax tree similarity.
+//!
+//! A [`VectorIndex`] of text embeddings finds code that reads like the
+//! query; two functions with different names but the same shape score low.
+//! [`CodeIndex`] stores both embeddings of a [`CodeEmbedding`] and scores
+//! every document by [`CodeEmbedding::similarity`], so that structural
+//! similarity counts as much as its [`CodeSimilarityWeights`] say.
+//!
+//! ```
+//! use voyageai::code::{CodeEmbedding, CodeIndex, CodeSimilarityWeights};
+//!
+//! let code = |text: [f32; 2], ast: [f32; 2]| CodeEmbedding {
+//!     text_embedding: text.to_vec(),
+//!     ast_embedding: ast.to_vec(),
+//! };
+//! let mut index = CodeIndex::new().with_weights(CodeSimilarityWeights::new(0.5, 0.5));
+//! index.insert("sum", "fn sum(v: &[i32]) -> i32 { v.iter().sum() }", code([1.0, 0.0], [0.0, 1.0]), None).unwrap();
+//! index.insert("total", "fn total(v: &[i32]) -> i32 { v.iter().sum() }", code([0.6, 0.8], [1.0, 0.0]), None).unwrap();
+//!
+//! // Reads like `sum`, shaped like `total`
+//! let hits = index.search(&code([1.0, 0.0], [1.0, 0.0]), 2, None).unwrap();
+//! assert_eq!(hits[0].record.id, "total");
+//! assert!(hits[0].text_score < hits[1].text_score);
+//! ```
+
+use crate::errors::VoyageError;
+use crate::index::vector_index::{IndexRecord, VectorIndex};
+use crate::models::filter::MetadataFilter;
+use crate::models::metadata::Metadata;
+use crate::try_cosine_similarity;
+
+use super::{CodeEmbedding, CodeSimilarityWeights};
+
+/// A single match returned by [`CodeIndex::search`].
+#[derive(Debug, Clone, Copy)]
+pub struct CodeMatch<'a> {
+    pub record: &'a IndexRecord,
+    /// `text_score` and `ast_score` blended by the index's weights
+    pub score: f32,
+    /// Cosine similarity between the text embeddings
+    pub text_score: f32,
+    /// Cosine similarity between the syntax tree embeddings
+    pub ast_score: f32,
+}
+
+/// In-memory corpus of [`CodeEmbedding`]s, see the
+/// [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CodeIndex {
+    /// The records with their text embeddings
+    text: VectorIndex,
+    /// The syntax tree embeddings, by the same ids
+    ast: VectorIndex,
+    weights: CodeSimilarityWeights,
+}
+
+impl CodeIndex {
+    /// Creates an empty index with the default weights.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how much text and syntax tree similarity count in scores.
+    /// Defaults to [`CodeSimilarityWeights::default`].
+    pub fn with_weights(mut self, weights: CodeSimilarityWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn weights(&self) -> CodeSimilarityWeights {
+        self.weights
+    }
+
+    pub fn set_weights(&mut self, weights: CodeSimilarityWeights) {
+        self.weights = weights;
+    }
+
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.text.contains(id)
+    }
+
+    /// Inserts a piece of code, replacing any existing one with the same id.
+    /// The dimensions of the text and syntax tree embeddings are each fixed
+    /// by the first insert.
+    pub fn insert(
+        &mut self,
+        id: impl Into<String>,
+        text: impl Into<String>,
+        embedding: CodeEmbedding,
+        metadata: Option<Metadata>,
+    ) -> Result<(), VoyageError> {
+        let id = id.into();
+        self.check_dimensions(&embedding)?;
+        self.ast
+            .insert(id.clone(), String::new(), embedding.ast_embedding, None)?;
+        self.text
+            .insert(id, text, embedding.text_embedding, metadata)
+    }
+
+    /// Removes a piece of code by id, returning its record and embeddings.
+    pub fn remove(&mut self, id: &str) -> Option<(IndexRecord, CodeEmbedding)> {
+        let (record, text_embedding) = self.text.remove(id)?;
+        let (_, ast_embedding) = self.ast.remove(id)?;
+        Some((
+            record,
+            CodeEmbedding {
+                text_embedding,
+                ast_embedding,
+            },
+        ))
+    }
+
+    /// Returns the record and embeddings stored under `id`.
+    pub fn get(&self, id: &str) -> Option<(&IndexRecord, CodeEmbedding)> {
+        let (record, text_embedding) = self.text.get(id)?;
+        let (_, ast_embedding) = self.ast.get(id)?;
+        Some((
+            record,
+            CodeEmbedding {
+                text_embedding: text_embedding.to_vec(),
+                ast_embedding: ast_embedding.to_vec(),
+            },
+        ))
+    }
+
+    /// Returns the `top_k` pieces of code most similar to `query` by the
+    /// index's weights, optionally restricted to those whose metadata
+    /// matches `filter`. The search is exact.
+    pub fn search(
+        &self,
+        query: &CodeEmbedding,
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<CodeMatch<'_>>, VoyageError> {
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.check_dimensions(query)?;
+        let mut matches: Vec<CodeMatch<'_>> = self
+            .text
+            .iter()
+            .filter(|(record, _)| {
+                filter.is_none_or(|filter| filter.matches(record.metadata.as_ref()))
+            })
+            .filter_map(|(record, text_embedding)| {
+                let (_, ast_embedding) = self.ast.get(&record.id)?;
+                Some((record, text_embedding, ast_embedding))
+            })
+            .map(|(record, text_embedding, ast_embedding)| {
+                let text_score = try_cosine_similarity(&query.text_embedding, text_embedding)?;
+                let ast_score = try_cosine_similarity(&query.ast_embedding, ast_embedding)?;
+                Ok(CodeMatch {
+                    record,
+                    score: self.weights.blend(text_score, ast_score),
+                    text_score,
+                    ast_score,
+                })
+            })
+            .collect::<Result<_, VoyageError>>()?;
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches.truncate(top_k);
+        Ok(matches)
+    }
+
+    /// Checks both embeddings against the dimensions of the index, before
+    /// anything is inserted, so that a failed insert changes nothing.
+    fn check_dimensions(&self, embedding: &CodeEmbedding) -> Result<(), VoyageError> {
+        for (index, values) in [
+            (&self.text, &embedding.text_embedding),
+            (&self.ast, &embedding.ast_embedding),
+        ] {
+            let expected = index.dimension();
+            if values.is_empty() || (expected != 0 && values.len() != expected) {
+                return Err(VoyageError::SearchDimensionMismatch {
+                    expected,
+                    actual: values.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+//! Keeping the index of a repository up to date as commits land.
+//!
+//! [`CodeIndexer`] pairs a [`RetrievalPipeline`] with the [`RepoWalker`]
+//! that finds the files to index. [`index`](CodeIndexer::index) adds the
+//! whole repository; [`update_from_git`](CodeIndexer::update_from_git) asks
+//! `git diff` which files changed between two commits and only re-indexes
+//! those. Within a changed file, the items whose text did not change keep
+//! their embeddings, so refreshing the index of a pull request costs about
+//! as many embedded tokens as the pull request has.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), voyageai::VoyageError> {
+//! use voyageai::{code::{CodeIndexer, RepoWalker}, RetrievalPipeline, VoyageAiClient};
+//!
+//! let client = VoyageAiClient::new();
+//! let mut indexer = CodeIndexer::new(
+//!     RetrievalPipeline::new(&client),
+//!     RepoWalker::new(".").annotate_commit(true),
+//! );
+//! indexer.index().await?;
+//! // Later, with the pull request checked out
+//! let update = indexer.update_from_git("origin/main...HEAD").await?;
+//! println!(
+//!     "{} files updated, {} removed, {} of {} chunks embedded",
+//!     update.updated.len(),
+//!     update.removed.len(),
+//!     update.embedded,
+//!     update.chunks
+//! );
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use tokio::process::Command;
+
+use crate::errors::VoyageError;
+use crate::retrieval::{RepositoryReport, RetrievalPipeline};
+
+use super::{RepoFiles, RepoWalker, SkipReason};
+
+/// What [`CodeIndexer::update_from_git`] changed in the pipeline.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GitUpdateReport {
+    /// The changed files that were re-indexed, relative to the walker's root
+    pub updated: Vec<String>,
+    /// The files whose chunks were removed because they were deleted, renamed
+    /// or are no longer walked, relative to the walker's root
+    pub removed: Vec<String>,
+    /// The chunks stored for the updated files
+    pub chunks: usize,
+    /// The chunks that were embedded; the others were unchanged and kept
+    /// their embeddings
+    pub embedded: usize,
+    /// The commit recorded in the metadata of the updated chunks, if any
+    pub commit: Option<String>,
+    /// The changed files left out because they are binary, too large or
+    /// unreadable
+    pub skipped: Vec<(PathBuf, SkipReason)>,
+}
+
+/// How `git diff --name-status` reports a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    Deleted,
+    /// Added, modified, or its type changed
+    Changed,
+}
+
+/// Indexes a repository and keeps the index up to date, see the
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct CodeIndexer {
+    pipeline: RetrievalPipeline,
+    walker: RepoWalker,
+}
+
+impl CodeIndexer {
+    /// An indexer adding the files `walker` finds to `pipeline`.
+    pub fn new(pipeline: RetrievalPipeline, walker: RepoWalker) -> Self {
+        Self { pipeline, walker }
+    }
+
+    pub fn pipeline(&self) -> &RetrievalPipeline {
+        &self.pipeline
+    }
+
+    /// The pipeline, e.g. to query it.
+    pub fn pipeline_mut(&mut self) -> &mut RetrievalPipeline {
+        &mut self.pipeline
+    }
+
+    pub fn into_pipeline(self) -> RetrievalPipeline {
+        self.pipeline
+    }
+
+    pub fn walker(&self) -> &RepoWalker {
+        &self.walker
+    }
+
+    /// Walks the repository and adds every file found, see
+    /// [`RetrievalPipeline::add_repository`].
+    pub async fn index(&mut self) -> Result<RepositoryReport, VoyageError> {
+        let found = self.walker.walk()?;
+        self.pipeline.add_repository(found).await
+    }
+
+    /// Re-indexes the files that changed in `range`, which is passed to
+    /// `git diff` as is: `main..feature` for the changes between two
+    /// commits, `main...feature` for those since `feature` branched off, or
+    /// a single commit for the changes since then, including uncommitted
+    /// changes to tracked files.
+    ///
+    /// Changed files are read from the working tree, so the end of the range
+    /// should be checked out, as it is in CI. Those the walker accepts are
+    /// re-indexed, only embedding the items whose text changed. Deleted
+    /// files, the old paths of renamed files, and changed files the walker
+    /// now leaves out are removed from the pipeline. Fails when `git` cannot
+    /// be run or does not know the range.
+    pub async fn update_from_git(&mut self, range: &str) -> Result<GitUpdateReport, VoyageError> {
+        let changes = self.changed_files(range).await?;
+        let walked = self.walker.walk()?;
+        let changed = |path: &str| changes.iter().any(|(changed, _)| changed == path);
+
+        let walked_paths: BTreeSet<&str> = walked
+            .files
+            .iter()
+            .map(|file| file.relative_path.as_str())
+            .collect();
+        let mut update = GitUpdateReport::default();
+        for (path, change) in &changes {
+            if *change == Change::Deleted || !walked_paths.contains(path.as_str()) {
+                let id = self.walker.root().join(path);
+                if self.pipeline.remove_document(&id.to_string_lossy()) {
+                    update.removed.push(path.clone());
+                }
+            }
+        }
+
+        let files: Vec<_> = walked
+            .files
+            .into_iter()
+            .filter(|file| changed(&file.relative_path))
+            .collect();
+        update.updated = files
+            .iter()
+            .map(|file| file.relative_path.clone())
+            .collect();
+        let report = self
+            .pipeline
+            .add_repository(RepoFiles {
+                files,
+                skipped: walked
+                    .skipped
+                    .into_iter()
+                    .filter(|(path, _)| changed(&self.relative_path(path)))
+                    .collect(),
+                commit: walked.commit,
+            })
+            .await?;
+        update.chunks = report.chunks;
+        update.embedded = report.embedded;
+        update.commit = report.commit;
+        update.skipped = report.skipped;
+        Ok(update)
+    }
+
+    /// The files changed in `range`, relative to the walker's root, from
+    /// `git diff --name-status`. Renames are reported as a deletion and an
+    /// addition.
+    async fn changed_files(&self, range: &str) -> Result<Vec<(String, Change)>, VoyageError> {
+        if range.starts_with('-') {
+            return Err(VoyageError::RepositoryError(format!(
+                "invalid git range: {}",
+                range
+            )));
+        }
+        let root = self.walker.root();
+        let dir = if root.is_dir() {
+            root
+        } else {
+            root.parent().unwrap_or(Path::new("."))
+        };
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args(["diff", "--name-status", "--no-renames", "--relative", "-z"])
+            .arg(range)
+            .arg("--")
+            .output()
+            .await
+            .map_err(|e| VoyageError::RepositoryError(format!("cannot run git: {}", e)))?;
+        if !output.status.success() {
+            return Err(VoyageError::RepositoryError(format!(
+                "git diff {} failed: {}",
+                range,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(parse_name_status(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// The path of a walked file relative to the walker's root.
+    fn relative_path(&self, path: &Path) -> String {
+        path.strip_prefix(self.walker.root())
+            .unwrap_or(path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Parses the NUL separated output of `git diff --name-status -z
+/// --no-renames`: a status letter, then the path.
+fn parse_name_status(output: &str) -> Vec<(String, Change)> {
+    let mut fields = output.split('\0');
+    let mut changes = Vec::new();
+    while let (Some(status), Some(path)) = (fields.next(), fields.next()) {
+        let change = if status.starts_with('D') {
+            Change::Deleted
+        } else {
+            Change::Changed
+        };
+        changes.push((path.to_string(), change));
+    }
+    changes
+}
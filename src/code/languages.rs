@@ -0,0 +1,657 @@
+//! Item-level parsers for Python, TypeScript, Go and Java, built on
+//! tree-sitter grammars.
+//!
+//! Each is a [`LanguageParser`], so [`CodeChunker`](super::CodeChunker) and
+//! [`RetrievalPipeline::add_code_file`](crate::RetrievalPipeline::add_code_file)
+//! pick them by file extension like the Rust parser:
+//!
+//! | Parser | Extensions | Items | Docs |
+//! |---|---|---|---|
+//! | [`PythonParser`] | `py`, `pyi` | functions, classes, methods | docstrings |
+//! | [`TypeScriptParser`] | `ts`, `mts`, `cts`, `tsx` | functions, classes, methods, interfaces, type aliases, enums | `/** */` comments |
+//! | [`GoParser`] | `go` | functions, methods, struct, interface and other types | `//` comments |
+//! | [`JavaParser`] | `java` | classes, interfaces, enums, records, methods, constructors | `/** */` comments |
+//!
+//! A class with methods is chunked as its declaration up to the first
+//! method, then one chunk per method with the class as
+//! [`parent`](CodeChunk::parent); Go methods have their receiver type as
+//! parent. An item's lines include its doc comment and decorators.
+//!
+//! tree-sitter recovers from syntax errors, so source with mistakes still
+//! yields the items around them.
+//!
+//! ```
+//! use voyageai::code::{languages::PythonParser, CodeItemKind, LanguageParser};
+//!
+//! let source = "class Square:\n    def area(self):\n        \"\"\"The area.\"\"\"\n        return self.side ** 2\n";
+//! let chunks = PythonParser.parse_items(source, &["shapes".to_string()]).unwrap();
+//! assert_eq!(chunks[1].qualified_name(), "shapes.Square.area");
+//! assert_eq!(chunks[1].kind, CodeItemKind::Method);
+//! assert_eq!(chunks[1].docs.as_deref(), Some("The area."));
+//! ```
+
+use std::path::Path;
+
+use tree_sitter::{Language, Node, Parser, Tree};
+
+use super::chunker::{CodeChunk, CodeItemKind, CodeParseError, LanguageParser};
+
+/// Python, with the docstring of each function and class as its docs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PythonParser;
+
+/// TypeScript, or TSX with [`TypeScriptParser::tsx`]. Visibility is `export`
+/// for exported items and the accessibility modifier for methods.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TypeScriptParser {
+    tsx: bool,
+}
+
+/// Go. Visibility is `exported` for names starting with a capital letter.
+/// The module path of a file is its directory, the package.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GoParser;
+
+/// Java. The module path of a file is its directory after `java` (as in
+/// `src/main/java`) or `src`, the package. Nested types have the enclosing
+/// types, joined with `.`, as parent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JavaParser;
+
+impl TypeScriptParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The parser for `.tsx` files.
+    pub fn tsx() -> Self {
+        Self { tsx: true }
+    }
+}
+
+impl LanguageParser for PythonParser {
+    fn language(&self) -> &'static str {
+        "python"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["py", "pyi"]
+    }
+
+    fn parse_items(
+        &self,
+        source: &str,
+        module_path: &[String],
+    ) -> Result<Vec<CodeChunk>, CodeParseError> {
+        let tree = parse(
+            self.language(),
+            &tree_sitter_python::LANGUAGE.into(),
+            source,
+        )?;
+        let mut items = Items::new(self.language(), source, module_path);
+        for node in named_children(tree.root_node()) {
+            let (outer, definition) = undecorated(node);
+            match definition.kind() {
+                "function_definition" => {
+                    let docs = python_docstring(&items, definition);
+                    items.push(
+                        outer,
+                        outer,
+                        None,
+                        items.name(definition),
+                        CodeItemKind::Function,
+                        String::new(),
+                        docs,
+                    );
+                }
+                "class_definition" => {
+                    let name = items.name(definition);
+                    let docs = python_docstring(&items, definition);
+                    let class = items.push(
+                        outer,
+                        outer,
+                        None,
+                        name.clone(),
+                        CodeItemKind::Class,
+                        String::new(),
+                        docs,
+                    );
+                    let body = definition.child_by_field_name("body");
+                    for member in body.into_iter().flat_map(named_children) {
+                        let (outer, method) = undecorated(member);
+                        if method.kind() == "function_definition" {
+                            let docs = python_docstring(&items, method);
+                            items.push(
+                                outer,
+                                outer,
+                                Some(name.clone()),
+                                items.name(method),
+                                CodeItemKind::Method,
+                                String::new(),
+                                docs,
+                            );
+                        }
+                    }
+                    items.end_class_before_members(class);
+                }
+                _ => {}
+            }
+        }
+        Ok(items.chunks)
+    }
+}
+
+impl LanguageParser for TypeScriptParser {
+    fn language(&self) -> &'static str {
+        "typescript"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        if self.tsx {
+            &["tsx"]
+        } else {
+            &["ts", "mts", "cts"]
+        }
+    }
+
+    fn parse_items(
+        &self,
+        source: &str,
+        module_path: &[String],
+    ) -> Result<Vec<CodeChunk>, CodeParseError> {
+        let language = if self.tsx {
+            tree_sitter_typescript::LANGUAGE_TSX
+        } else {
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT
+        };
+        let tree = parse(self.language(), &language.into(), source)?;
+        let mut items = Items::new(self.language(), source, module_path);
+        for node in named_children(tree.root_node()) {
+            let (declaration, visibility) = match node.kind() {
+                "export_statement" => match node.child_by_field_name("declaration") {
+                    Some(declaration) => (declaration, "export".to_string()),
+                    None => continue,
+                },
+                _ => (node, String::new()),
+            };
+            let first = items.doc_start(node, is_js_doc);
+            let docs = items.docs(node, is_js_doc);
+            let kind = match declaration.kind() {
+                "function_declaration" | "generator_function_declaration" => CodeItemKind::Function,
+                "class_declaration" | "abstract_class_declaration" => CodeItemKind::Class,
+                "interface_declaration" => CodeItemKind::Interface,
+                "type_alias_declaration" => CodeItemKind::TypeAlias,
+                "enum_declaration" => CodeItemKind::Enum,
+                "lexical_declaration" => {
+                    // `const area = (side: number) => side * side;`
+                    for declarator in named_children(declaration) {
+                        let is_function =
+                            declarator
+                                .child_by_field_name("value")
+                                .is_some_and(|value| {
+                                    matches!(value.kind(), "arrow_function" | "function_expression")
+                                });
+                        if is_function {
+                            items.push(
+                                first,
+                                node,
+                                None,
+                                items.name(declarator),
+                                CodeItemKind::Function,
+                                visibility.clone(),
+                                docs.clone(),
+                            );
+                        }
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            let name = items.name(declaration);
+            let item = items.push(first, node, None, name.clone(), kind, visibility, docs);
+            if kind != CodeItemKind::Class {
+                continue;
+            }
+            let body = declaration.child_by_field_name("body");
+            for member in body.into_iter().flat_map(named_children) {
+                if !matches!(
+                    member.kind(),
+                    "method_definition" | "abstract_method_signature"
+                ) {
+                    continue;
+                }
+                let visibility = named_children(member)
+                    .find(|child| child.kind() == "accessibility_modifier")
+                    .map(|modifier| items.text(modifier).to_string())
+                    .unwrap_or_default();
+                let docs = items.docs(member, is_js_doc);
+                items.push(
+                    items.doc_start(member, is_js_doc),
+                    member,
+                    Some(name.clone()),
+                    items.name(member),
+                    CodeItemKind::Method,
+                    visibility,
+                    docs,
+                );
+            }
+            items.end_class_before_members(item);
+        }
+        Ok(items.chunks)
+    }
+}
+
+impl LanguageParser for GoParser {
+    fn language(&self) -> &'static str {
+        "go"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["go"]
+    }
+
+    fn module_path(&self, path: &Path) -> Vec<String> {
+        directory_path(path, &["src"])
+    }
+
+    fn parse_items(
+        &self,
+        source: &str,
+        module_path: &[String],
+    ) -> Result<Vec<CodeChunk>, CodeParseError> {
+        let tree = parse(self.language(), &tree_sitter_go::LANGUAGE.into(), source)?;
+        let mut items = Items::new(self.language(), source, module_path);
+        let is_comment: fn(&str) -> bool = |comment| comment.starts_with("//");
+        for node in named_children(tree.root_node()) {
+            let first = items.doc_start(node, is_comment);
+            let docs = items.docs(node, is_comment);
+            match node.kind() {
+                "function_declaration" | "method_declaration" => {
+                    let name = items.name(node);
+                    let (parent, kind) = match node.child_by_field_name("receiver") {
+                        Some(receiver) => (
+                            Some(go_receiver_type(items.text(receiver))),
+                            CodeItemKind::Method,
+                        ),
+                        None => (None, CodeItemKind::Function),
+                    };
+                    let visibility = go_visibility(&name);
+                    items.push(first, node, parent, name, kind, visibility, docs);
+                }
+                "type_declaration" => {
+                    let specs: Vec<Node> = named_children(node)
+                        .filter(|spec| matches!(spec.kind(), "type_spec" | "type_alias"))
+                        .collect();
+                    for spec in &specs {
+                        // A grouped `type ( ... )` declaration is chunked
+                        // spec by spec
+                        let (first, last, docs) = match specs.len() {
+                            1 => (first, node, docs.clone()),
+                            _ => (
+                                items.doc_start(*spec, is_comment),
+                                *spec,
+                                items.docs(*spec, is_comment),
+                            ),
+                        };
+                        let kind = match spec.child_by_field_name("type").map(|ty| ty.kind()) {
+                            Some("struct_type") if spec.kind() == "type_spec" => {
+                                CodeItemKind::Struct
+                            }
+                            Some("interface_type") if spec.kind() == "type_spec" => {
+                                CodeItemKind::Interface
+                            }
+                            _ => CodeItemKind::TypeAlias,
+                        };
+                        let name = items.name(*spec);
+                        let visibility = go_visibility(&name);
+                        items.push(first, last, None, name, kind, visibility, docs);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(items.chunks)
+    }
+}
+
+impl LanguageParser for JavaParser {
+    fn language(&self) -> &'static str {
+        "java"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["java"]
+    }
+
+    fn module_path(&self, path: &Path) -> Vec<String> {
+        directory_path(path, &["java", "src"])
+    }
+
+    fn parse_items(
+        &self,
+        source: &str,
+        module_path: &[String],
+    ) -> Result<Vec<CodeChunk>, CodeParseError> {
+        let tree = parse(self.language(), &tree_sitter_java::LANGUAGE.into(), source)?;
+        let mut items = Items::new(self.language(), source, module_path);
+        for node in named_children(tree.root_node()) {
+            java_type(&mut items, node, None);
+        }
+        Ok(items.chunks)
+    }
+}
+
+/// Chunks the Java type declared by `node`, if it declares one, and its
+/// members.
+fn java_type(items: &mut Items, node: Node, parent: Option<String>) {
+    let kind = match node.kind() {
+        "class_declaration" | "record_declaration" => CodeItemKind::Class,
+        "interface_declaration" => CodeItemKind::Interface,
+        "enum_declaration" => CodeItemKind::Enum,
+        _ => return,
+    };
+    let name = items.name(node);
+    let docs = items.docs(node, is_js_doc);
+    let item = items.push(
+        items.doc_start(node, is_js_doc),
+        node,
+        parent.clone(),
+        name.clone(),
+        kind,
+        java_visibility(node),
+        docs,
+    );
+    let path = match parent {
+        Some(parent) => format!("{}.{}", parent, name),
+        None => name,
+    };
+    let body = node.child_by_field_name("body");
+    let mut members: Vec<Node> = body.into_iter().flat_map(named_children).collect();
+    // The constants of an enum come before its other members
+    if let Some(index) = members
+        .iter()
+        .position(|member| member.kind() == "enum_body_declarations")
+    {
+        let declarations = members.remove(index);
+        members.extend(named_children(declarations));
+    }
+    for member in members {
+        match member.kind() {
+            "method_declaration" | "constructor_declaration" => {
+                let docs = items.docs(member, is_js_doc);
+                items.push(
+                    items.doc_start(member, is_js_doc),
+                    member,
+                    Some(path.clone()),
+                    items.name(member),
+                    CodeItemKind::Method,
+                    java_visibility(member),
+                    docs,
+                );
+            }
+            _ => java_type(items, member, Some(path.clone())),
+        }
+    }
+    items.end_class_before_members(item);
+}
+
+/// The chunks of one file, in source order.
+struct Items<'a> {
+    language: &'static str,
+    source: &'a str,
+    lines: Vec<&'a str>,
+    module_path: &'a [String],
+    chunks: Vec<CodeChunk>,
+}
+
+impl<'a> Items<'a> {
+    fn new(language: &'static str, source: &'a str, module_path: &'a [String]) -> Self {
+        Self {
+            language,
+            source,
+            lines: source.lines().collect(),
+            module_path,
+            chunks: Vec::new(),
+        }
+    }
+
+    fn text(&self, node: Node) -> &'a str {
+        node.utf8_text(self.source.as_bytes()).unwrap_or_default()
+    }
+
+    /// The text of the `name` field of `node`.
+    fn name(&self, node: Node) -> String {
+        node.child_by_field_name("name")
+            .map(|name| self.text(name).to_string())
+            .unwrap_or_default()
+    }
+
+    /// Adds the item spanning the lines from `first` to `last`, returning
+    /// its position.
+    #[allow(clippy::too_many_arguments)]
+    fn push(
+        &mut self,
+        first: Node,
+        last: Node,
+        parent: Option<String>,
+        name: String,
+        kind: CodeItemKind,
+        visibility: String,
+        docs: Option<String>,
+    ) -> usize {
+        let start_line = first.start_position().row + 1;
+        let end = last.end_position();
+        // A node ending with a newline ends at the start of the next line
+        let end_line = match end.column {
+            0 if end.row >= start_line => end.row,
+            _ => end.row + 1,
+        };
+        self.chunks.push(CodeChunk {
+            text: self.lines(start_line, end_line),
+            language: self.language.to_string(),
+            module_path: self.module_path.to_vec(),
+            parent,
+            name,
+            kind,
+            start_line,
+            end_line,
+            visibility,
+            docs,
+        });
+        self.chunks.len() - 1
+    }
+
+    /// Ends the class at position `class` before its first member chunk, if
+    /// it has any, so that the class chunk holds its declaration and fields.
+    fn end_class_before_members(&mut self, class: usize) {
+        let Some(first_member) = self.chunks.get(class + 1).map(|chunk| chunk.start_line) else {
+            return;
+        };
+        let start_line = self.chunks[class].start_line;
+        let mut end_line = first_member.saturating_sub(1).max(start_line);
+        while end_line > start_line
+            && self
+                .lines
+                .get(end_line - 1)
+                .is_some_and(|line| line.trim().is_empty())
+        {
+            end_line -= 1;
+        }
+        let text = self.lines(start_line, end_line);
+        let chunk = &mut self.chunks[class];
+        chunk.end_line = end_line;
+        chunk.text = text;
+    }
+
+    /// The first of the doc comments directly above `node`, or `node`
+    /// without any.
+    fn doc_start<'t>(&self, node: Node<'t>, is_doc: fn(&str) -> bool) -> Node<'t> {
+        self.doc_comments(node, is_doc)
+            .first()
+            .copied()
+            .unwrap_or(node)
+    }
+
+    /// The text of the doc comments directly above `node`, without comment
+    /// markers.
+    fn docs(&self, node: Node, is_doc: fn(&str) -> bool) -> Option<String> {
+        let comments = self.doc_comments(node, is_doc);
+        clean_docs(comments.iter().flat_map(|comment| {
+            self.text(*comment)
+                .trim_start_matches("/**")
+                .trim_end_matches("*/")
+                .lines()
+                .map(|line| {
+                    let line = line.trim();
+                    line.strip_prefix("//")
+                        .or_else(|| line.strip_prefix('*'))
+                        .unwrap_or(line)
+                })
+        }))
+    }
+
+    /// The comments accepted by `is_doc` directly above `node`, with no
+    /// blank line between them, in source order.
+    fn doc_comments<'t>(&self, node: Node<'t>, is_doc: fn(&str) -> bool) -> Vec<Node<'t>> {
+        let mut comments = Vec::new();
+        let mut next_row = node.start_position().row;
+        let mut sibling = node.prev_sibling();
+        // `comment` in most grammars, `line_comment` and `block_comment` in
+        // Java's
+        while let Some(comment) = sibling.filter(|sibling| sibling.kind().ends_with("comment")) {
+            if comment.end_position().row + 1 < next_row || !is_doc(self.text(comment)) {
+                break;
+            }
+            comments.push(comment);
+            next_row = comment.start_position().row;
+            sibling = comment.prev_sibling();
+        }
+        comments.reverse();
+        comments
+    }
+
+    /// Lines `start` to `end` of the source, from 1 and inclusive.
+    fn lines(&self, start: usize, end: usize) -> String {
+        self.lines
+            .get(start - 1..end.min(self.lines.len()))
+            .unwrap_or_default()
+            .join("\n")
+    }
+}
+
+fn parse(
+    language_name: &'static str,
+    language: &Language,
+    source: &str,
+) -> Result<Tree, CodeParseError> {
+    let error = |message: String| CodeParseError {
+        language: language_name,
+        message,
+    };
+    let mut parser = Parser::new();
+    parser
+        .set_language(language)
+        .map_err(|e| error(e.to_string()))?;
+    parser
+        .parse(source, None)
+        .ok_or_else(|| error("the parser gave up".to_string()))
+}
+
+fn named_children(node: Node) -> impl Iterator<Item = Node> {
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    children.into_iter()
+}
+
+/// The decorated Python definition as `(decorated_definition, definition)`,
+/// or `node` twice when it has no decorators.
+fn undecorated(node: Node) -> (Node, Node) {
+    match node.kind() {
+        "decorated_definition" => (node, node.child_by_field_name("definition").unwrap_or(node)),
+        _ => (node, node),
+    }
+}
+
+/// The docstring of a Python function or class: a string as the first
+/// statement of its body.
+fn python_docstring(items: &Items, definition: Node) -> Option<String> {
+    let body = definition.child_by_field_name("body")?;
+    let statement = named_children(body).next()?;
+    let string = named_children(statement).next()?;
+    if statement.kind() != "expression_statement" || string.kind() != "string" {
+        return None;
+    }
+    let text = items.text(string);
+    let text = text.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+    let quotes = ["\"\"\"", "'''", "\"", "'"]
+        .into_iter()
+        .find(|quotes| text.starts_with(quotes))?;
+    let text = text
+        .strip_prefix(quotes)?
+        .strip_suffix(quotes)
+        .unwrap_or(text);
+    clean_docs(text.lines())
+}
+
+fn is_js_doc(comment: &str) -> bool {
+    comment.starts_with("/**")
+}
+
+/// Trims each line, drops leading and trailing blank lines, and joins them.
+fn clean_docs<'l>(lines: impl Iterator<Item = &'l str>) -> Option<String> {
+    let lines: Vec<&str> = lines.map(str::trim).collect();
+    let docs = lines.join("\n").trim().to_string();
+    (!docs.is_empty()).then_some(docs)
+}
+
+/// The type of a Go method receiver, `Square` for `(s *Square[T])`.
+fn go_receiver_type(receiver: &str) -> String {
+    receiver
+        .trim_matches(|c| c == '(' || c == ')')
+        .split_whitespace()
+        .last()
+        .unwrap_or_default()
+        .trim_start_matches('*')
+        .split('[')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn go_visibility(name: &str) -> String {
+    match name.chars().next() {
+        Some(first) if first.is_uppercase() => "exported".to_string(),
+        _ => String::new(),
+    }
+}
+
+/// The access modifier of a Java declaration.
+fn java_visibility(node: Node) -> String {
+    let Some(modifiers) = named_children(node).find(|child| child.kind() == "modifiers") else {
+        return String::new();
+    };
+    let mut cursor = modifiers.walk();
+    let visibility = modifiers
+        .children(&mut cursor)
+        .map(|modifier| modifier.kind())
+        .find(|kind| matches!(*kind, "public" | "protected" | "private"))
+        .unwrap_or_default()
+        .to_string();
+    visibility
+}
+
+/// The directory of the file at `path` after the last of the `roots`
+/// components, e.g. the package of a Go or Java file.
+fn directory_path(path: &Path, roots: &[&str]) -> Vec<String> {
+    let components: Vec<String> = path
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let start = components
+        .iter()
+        .rposition(|component| roots.contains(&component.as_str()))
+        .map_or(0, |root| root + 1);
+    components[start..].to_vec()
+}
@@ -0,0 +1,64 @@
+//! Code blocks of Markdown documents.
+
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ast")]
+use super::ast::{parse_rust_ast, SerializableAst};
+
+/// A fenced or indented code block of a Markdown document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeBlock {
+    /// The language of a fenced block, the first word of its info string
+    /// (`rust` for ```` ```rust,no_run ````)
+    pub language: Option<String>,
+    pub content: String,
+}
+
+impl CodeBlock {
+    pub fn new(language: Option<String>, content: String) -> Self {
+        Self { language, content }
+    }
+
+    /// Parses a `rust` or `rs` block into a [`SerializableAst`]; blocks in
+    /// other languages give an empty one. Requires the `ast` feature.
+    #[cfg(feature = "ast")]
+    pub fn parse(&self) -> Result<SerializableAst, syn::Error> {
+        match self.language.as_deref() {
+            Some("rust") | Some("rs") => parse_rust_ast(&self.content),
+            _ => Ok(SerializableAst { items: vec![] }),
+        }
+    }
+}
+
+/// The code blocks of `markdown`, in order, parsed as CommonMark: fences of
+/// backticks or tildes, indented blocks and blocks nested in lists or quotes
+/// are all found.
+pub fn extract_code_blocks(markdown: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<CodeBlock> = None;
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .next()
+                        .filter(|language| !language.is_empty())
+                        .map(str::to_string),
+                    CodeBlockKind::Indented => None,
+                };
+                current = Some(CodeBlock::new(language, String::new()));
+            }
+            Event::Text(text) => {
+                if let Some(block) = &mut current {
+                    block.content.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => blocks.extend(current.take()),
+            _ => {}
+        }
+    }
+    blocks
+}
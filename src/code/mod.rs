@@ -0,0 +1,182 @@
+//! Code analysis: the outline of Rust source, the items of source files, the
+//! code blocks of Markdown documents and the embeddings of code.
+//!
+//! - [`ast`]: [`SerializableAst`], the outline of Rust source, and
+//!   [`parse_rust_ast`] building it (with the `ast` feature).
+//! - [`chunker`]: [`CodeChunker`] and the [`LanguageParser`] trait, splitting
+//!   source files into one chunk per item with its location.
+//! - `rust`: `chunk_rust` and `RustParser`, the parser for Rust (with the
+//!   `ast` feature).
+//! - `languages`: parsers for Python, TypeScript, Go and Java (with the
+//!   `tree-sitter` feature).
+//! - `repository`: `RepoWalker`, listing the files of a repository to index
+//!   while honoring its `.gitignore` (with the `repo` feature).
+//! - `indexer`: `CodeIndexer`, indexing a repository and re-indexing the
+//!   files changed between two commits (with the `repo` feature).
+//! - [`markdown`]: [`CodeBlock`] and [`extract_code_blocks`], finding the
+//!   code of a Markdown document with a CommonMark parser.
+//! - [`CodeEmbedding`]: the text and syntax tree embeddings of a piece of
+//!   code, returned by `embed_code`, compared with
+//!   [`similarity`](CodeEmbedding::similarity).
+//! - [`index`]: [`CodeIndex`], searching code embeddings by that blended
+//!   similarity.
+//!
+//! The former homes of these items, `models::ast` and `utils`, re-export
+//! them.
+
+pub mod ast;
+pub mod chunker;
+pub mod index;
+#[cfg(feature = "repo")]
+pub mod indexer;
+#[cfg(feature = "tree-sitter")]
+pub mod languages;
+pub mod markdown;
+#[cfg(feature = "repo")]
+pub mod repository;
+#[cfg(feature = "ast")]
+pub mod rust;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::SimilarityError;
+use crate::try_cosine_similarity;
+
+#[cfg(feature = "ast")]
+pub use ast::parse_rust_ast;
+pub use ast::SerializableAst;
+pub use chunker::{
+    module_path_for, parser_for_path, parsers, CodeChunk, CodeChunker, CodeItemKind,
+    CodeParseError, LanguageParser,
+};
+pub use index::{CodeIndex, CodeMatch};
+#[cfg(feature = "repo")]
+pub use indexer::{CodeIndexer, GitUpdateReport};
+#[cfg(feature = "tree-sitter")]
+pub use languages::{GoParser, JavaParser, PythonParser, TypeScriptParser};
+pub use markdown::{extract_code_blocks, CodeBlock};
+#[cfg(feature = "repo")]
+pub use repository::{language_for_path, RepoFile, RepoFiles, RepoWalker, SkipReason};
+#[cfg(feature = "ast")]
+pub use rust::{chunk_rust, RustParser};
+
+/// Embeddings of a piece of code, as text and as its syntax tree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeEmbedding {
+    /// Embedding of the source text
+    pub text_embedding: Vec<f32>,
+    /// Embedding of the JSON form of its [`SerializableAst`]
+    pub ast_embedding: Vec<f32>,
+}
+
+impl CodeEmbedding {
+    /// Cosine similarity with `other`, blending the similarity of the text
+    /// embeddings and that of the syntax tree embeddings by `weights`.
+    /// Fails when the embeddings are empty or their dimensions differ.
+    ///
+    /// ```
+    /// use voyageai::code::{CodeEmbedding, CodeSimilarityWeights};
+    ///
+    /// let a = CodeEmbedding { text_embedding: vec![1.0, 0.0], ast_embedding: vec![1.0, 0.0] };
+    /// let b = CodeEmbedding { text_embedding: vec![1.0, 0.0], ast_embedding: vec![0.0, 1.0] };
+    /// let weights = CodeSimilarityWeights::new(0.75, 0.25);
+    /// assert_eq!(a.similarity(&b, weights).unwrap(), 0.75);
+    /// ```
+    pub fn similarity(
+        &self,
+        other: &CodeEmbedding,
+        weights: CodeSimilarityWeights,
+    ) -> Result<f32, SimilarityError> {
+        Ok(weights.blend(
+            try_cosine_similarity(&self.text_embedding, &other.text_embedding)?,
+            try_cosine_similarity(&self.ast_embedding, &other.ast_embedding)?,
+        ))
+    }
+}
+
+/// How much the text and the syntax tree similarities count in
+/// [`CodeEmbedding::similarity`] and [`CodeIndex`] scores.
+///
+/// The weights are relative: `new(2.0, 1.0)` is `new(0.67, 0.33)`. Negative
+/// weights count as 0, and when both are 0 the two similarities count
+/// equally.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CodeSimilarityWeights {
+    pub text: f32,
+    pub ast: f32,
+}
+
+impl CodeSimilarityWeights {
+    pub fn new(text: f32, ast: f32) -> Self {
+        Self { text, ast }
+    }
+
+    /// Only the text similarity counts.
+    pub fn text_only() -> Self {
+        Self::new(1.0, 0.0)
+    }
+
+    /// Only the syntax tree similarity counts.
+    pub fn ast_only() -> Self {
+        Self::new(0.0, 1.0)
+    }
+
+    /// The weighted mean of `text_score` and `ast_score`.
+    pub fn blend(&self, text_score: f32, ast_score: f32) -> f32 {
+        let (text, ast) = (self.text.max(0.0), self.ast.max(0.0));
+        if text + ast == 0.0 {
+            return (text_score + ast_score) / 2.0;
+        }
+        (text * text_score + ast * ast_score) / (text + ast)
+    }
+}
+
+impl Default for CodeSimilarityWeights {
+    /// Text 0.7, syntax tree 0.3: the text carries identifiers and comments,
+    /// the tree the shape of the code.
+    fn default() -> Self {
+        Self::new(0.7, 0.3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "ast")]
+    #[test]
+    fn test_parse_rust_ast() {
+        let code = r#"
+            struct Test {
+                field: String,
+            }
+
+            fn example() -> String {
+                "test".to_string()
+            }
+        "#;
+
+        let ast = parse_rust_ast(code).unwrap();
+        assert_eq!(ast.items.len(), 2);
+    }
+
+    #[test]
+    fn test_extract_code_blocks() {
+        let markdown = r#"
+Some text
+
+```rust
+fn test() {}
+```
+
+```
+plain text
+```
+"#;
+
+        let blocks = extract_code_blocks(markdown);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].language, Some("rust".to_string()));
+        assert!(blocks[0].content.contains("fn test()"));
+    }
+}
@@ -0,0 +1,326 @@
+//! Walking a repository for indexing, the way `git` sees it.
+//!
+//! [`RepoWalker`] lists the files of a directory with the `ignore` crate, so
+//! that `.gitignore`, `.ignore` and `.git/info/exclude` rules and hidden
+//! entries are skipped like `git status` and ripgrep skip them. Include and
+//! exclude globs, a [language](language_for_path) filter and a size cap
+//! narrow the walk further, and binary files are left out.
+//! [`RetrievalPipeline::add_repository`](crate::RetrievalPipeline::add_repository)
+//! indexes the files found, one chunk per item for source code, optionally
+//! tagging every chunk with the commit the repository is at.
+//!
+//! ```no_run
+//! # fn example() -> Result<(), voyageai::VoyageError> {
+//! use voyageai::code::RepoWalker;
+//!
+//! let walker = RepoWalker::new(".")
+//!     .include("src/**")
+//!     .exclude("**/tests/**")
+//!     .languages(["rust", "python"])
+//!     .max_file_size(256 * 1024)
+//!     .annotate_commit(true);
+//! let found = walker.walk()?;
+//! println!("at commit {:?}", found.commit);
+//! for file in found.files {
+//!     println!("{}", file.relative_path);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+
+use crate::errors::VoyageError;
+
+/// Default [`RepoWalker::max_file_size`]: 1 MiB. Larger files are mostly
+/// generated code, lock files and data.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// How many leading bytes of a file are checked for NUL bytes to tell binary
+/// files apart, like `git diff` does.
+const BINARY_CHECK_BYTES: usize = 8 * 1024;
+
+/// Languages known to [`language_for_path`], with their file extensions.
+pub const LANGUAGES: &[(&str, &[&str])] = &[
+    ("rust", &["rs"]),
+    ("python", &["py", "pyi"]),
+    ("typescript", &["ts", "mts", "cts", "tsx"]),
+    ("javascript", &["js", "mjs", "cjs", "jsx"]),
+    ("go", &["go"]),
+    ("java", &["java"]),
+    ("c", &["c", "h"]),
+    ("cpp", &["cc", "cpp", "cxx", "hh", "hpp"]),
+    ("ruby", &["rb"]),
+    ("shell", &["sh", "bash"]),
+    ("markdown", &["md", "markdown"]),
+    ("text", &["txt", "rst"]),
+    ("toml", &["toml"]),
+    ("yaml", &["yml", "yaml"]),
+    ("json", &["json"]),
+];
+
+/// The language of the file at `path` by its extension, as named in
+/// [`LANGUAGES`]; these names match [`LanguageParser::language`](super::LanguageParser::language).
+pub fn language_for_path(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?;
+    LANGUAGES
+        .iter()
+        .find(|(_, extensions)| extensions.contains(&extension))
+        .map(|(language, _)| *language)
+}
+
+/// A file found by a [`RepoWalker`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoFile {
+    /// Path of the file, the walker's root joined with `relative_path`
+    pub path: PathBuf,
+    /// Path relative to the root with `/` separators, e.g. `src/lib.rs`
+    pub relative_path: String,
+    pub size: u64,
+}
+
+/// Why a [`RepoWalker`] left out a file that its filters accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The file has NUL bytes near its start
+    Binary,
+    /// The file is larger than [`RepoWalker::max_file_size`]
+    TooLarge { size: u64 },
+    /// The file could not be read
+    Unreadable(String),
+}
+
+/// The outcome of [`RepoWalker::walk`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoFiles {
+    /// The files to index, sorted by relative path
+    pub files: Vec<RepoFile>,
+    /// The files left out, with why
+    pub skipped: Vec<(PathBuf, SkipReason)>,
+    /// The commit the repository is at, with
+    /// [`annotate_commit`](RepoWalker::annotate_commit)
+    pub commit: Option<String>,
+}
+
+/// Lists the files of a repository to index, see the
+/// [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoWalker {
+    root: PathBuf,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    languages: Vec<String>,
+    max_file_size: u64,
+    hidden: bool,
+    annotate_commit: bool,
+}
+
+impl RepoWalker {
+    /// A walker of the directory, or the single file, at `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            languages: Vec::new(),
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            hidden: false,
+            annotate_commit: false,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Only walks files matching `glob`, relative to the root, e.g.
+    /// `"src/**/*.rs"`. Several includes match files matching any of them.
+    pub fn include(mut self, glob: impl Into<String>) -> Self {
+        self.include.push(glob.into());
+        self
+    }
+
+    /// Skips files and directories matching `glob`, relative to the root,
+    /// e.g. `"**/fixtures/**"`. Excludes win over includes.
+    pub fn exclude(mut self, glob: impl Into<String>) -> Self {
+        self.exclude.push(glob.into());
+        self
+    }
+
+    /// Only walks files of these [languages](language_for_path), e.g.
+    /// `["rust", "markdown"]`. Defaults to all the [`LANGUAGES`]; files of
+    /// other types are never walked.
+    pub fn languages<I, S>(mut self, languages: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.languages.extend(languages.into_iter().map(Into::into));
+        self
+    }
+
+    /// Skips files larger than `bytes`. Defaults to
+    /// [`DEFAULT_MAX_FILE_SIZE`].
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = bytes;
+        self
+    }
+
+    /// Whether hidden files and directories, starting with `.`, are walked.
+    /// Defaults to `false`; `.git` is never walked.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Whether [`walk`](Self::walk) looks up the [commit](Self::head_commit)
+    /// of the repository, which
+    /// [`add_repository`](crate::RetrievalPipeline::add_repository) then
+    /// records in the metadata of every chunk. Defaults to `false`.
+    pub fn annotate_commit(mut self, annotate: bool) -> Self {
+        self.annotate_commit = annotate;
+        self
+    }
+
+    /// Lists the files to index, with the commit the repository is at when
+    /// [`annotate_commit`](Self::annotate_commit) is set. `.gitignore` rules
+    /// apply whether or not the root is inside a git repository.
+    pub fn walk(&self) -> Result<RepoFiles, VoyageError> {
+        let mut overrides = OverrideBuilder::new(&self.root);
+        for glob in &self.include {
+            overrides.add(glob).map_err(invalid_glob)?;
+        }
+        for glob in self.exclude.iter().chain([&".git/".to_string()]) {
+            overrides.add(&format!("!{}", glob)).map_err(invalid_glob)?;
+        }
+        let walk = WalkBuilder::new(&self.root)
+            .hidden(!self.hidden)
+            .require_git(false)
+            .overrides(overrides.build().map_err(invalid_glob)?)
+            .build();
+
+        let mut found = RepoFiles::default();
+        for entry in walk {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    log::warn!("Skipping part of {}: {}", self.root.display(), e);
+                    continue;
+                }
+            };
+            if !entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+            {
+                continue;
+            }
+            let path = entry.into_path();
+            let Some(language) = language_for_path(&path) else {
+                continue;
+            };
+            if !self.languages.is_empty() && !self.languages.iter().any(|l| l == language) {
+                continue;
+            }
+            match self.check(&path) {
+                Ok(size) => found.files.push(RepoFile {
+                    relative_path: self.relative_path(&path),
+                    path,
+                    size,
+                }),
+                Err(reason) => found.skipped.push((path, reason)),
+            }
+        }
+        found
+            .files
+            .sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+        if self.annotate_commit {
+            found.commit = self.head_commit();
+        }
+        Ok(found)
+    }
+
+    /// The size of the file at `path`, unless it is too large or binary.
+    fn check(&self, path: &Path) -> Result<u64, SkipReason> {
+        let unreadable = |e: std::io::Error| SkipReason::Unreadable(e.to_string());
+        let size = fs::metadata(path).map_err(unreadable)?.len();
+        if size > self.max_file_size {
+            return Err(SkipReason::TooLarge { size });
+        }
+        let mut head = Vec::with_capacity(BINARY_CHECK_BYTES);
+        fs::File::open(path)
+            .and_then(|file| file.take(BINARY_CHECK_BYTES as u64).read_to_end(&mut head))
+            .map_err(unreadable)?;
+        if head.contains(&0) {
+            return Err(SkipReason::Binary);
+        }
+        Ok(size)
+    }
+
+    fn relative_path(&self, path: &Path) -> String {
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative,
+            // The root is the file itself
+            _ => Path::new(path.file_name().unwrap_or(path.as_os_str())),
+        };
+        relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// The commit checked out in the repository containing the root, read
+    /// from `.git` without running `git`. `None` outside a repository or
+    /// before the first commit.
+    pub fn head_commit(&self) -> Option<String> {
+        let root = fs::canonicalize(&self.root).ok()?;
+        let git_dir = root
+            .ancestors()
+            .find_map(|dir| git_dir(&dir.join(".git")))?;
+        let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+        let head = head.trim();
+        let Some(reference) = head.strip_prefix("ref: ") else {
+            return Some(head.to_string());
+        };
+        // Worktrees keep their refs in the common directory
+        let common_dir = fs::read_to_string(git_dir.join("commondir"))
+            .map(|dir| git_dir.join(dir.trim()))
+            .unwrap_or_else(|_| git_dir.clone());
+        let commit = [&git_dir, &common_dir]
+            .into_iter()
+            .find_map(|dir| fs::read_to_string(dir.join(reference)).ok())
+            .map(|commit| commit.trim().to_string());
+        commit.or_else(|| packed_ref(&common_dir, reference))
+    }
+}
+
+/// The git directory for a `.git` entry: the directory itself, or the one a
+/// `.git` file of a worktree or submodule points to.
+fn git_dir(dot_git: &Path) -> Option<PathBuf> {
+    if dot_git.is_dir() {
+        return Some(dot_git.to_path_buf());
+    }
+    let pointer = fs::read_to_string(dot_git).ok()?;
+    let dir = pointer.trim().strip_prefix("gitdir: ")?;
+    Some(dot_git.parent()?.join(dir))
+}
+
+/// The commit of `reference` in the `packed-refs` file of `git_dir`.
+fn packed_ref(git_dir: &Path, reference: &str) -> Option<String> {
+    fs::read_to_string(git_dir.join("packed-refs"))
+        .ok()?
+        .lines()
+        .find_map(|line| {
+            let (commit, name) = line.split_once(' ')?;
+            (name == reference).then(|| commit.to_string())
+        })
+}
+
+fn invalid_glob(e: ignore::Error) -> VoyageError {
+    VoyageError::RepositoryError(format!("invalid glob: {}", e))
+}
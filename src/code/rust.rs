@@ -0,0 +1,239 @@
+//! Splitting Rust source into one chunk per item.
+//!
+//! [`chunk_rust`] parses a file with `syn` and returns a [`CodeChunk`] per
+//! function, method, struct, enum, trait, type alias, constant and
+//! `macro_rules!` definition, with the module path, qualified name, line range,
+//! visibility and doc comment of the item. Methods are chunked one by one
+//! under `Type::method`; an impl block without methods is one chunk. Items of
+//! inline modules are chunked under the module's path.
+//!
+//! [`RustParser`] is the [`LanguageParser`] for Rust, so that
+//! [`CodeChunker`](super::CodeChunker) picks it for `.rs` files.
+//!
+//! ```
+//! use voyageai::code::{chunk_rust, CodeItemKind};
+//!
+//! let source = "pub struct Square(f64);\n\nimpl Square {\n    /// The area.\n    pub fn area(&self) -> f64 {\n        self.0 * self.0\n    }\n}\n";
+//! let chunks = chunk_rust(source, &["shapes".to_string()]).unwrap();
+//! assert_eq!(chunks[1].qualified_name(), "shapes::Square::area");
+//! assert_eq!(chunks[1].kind, CodeItemKind::Method);
+//! assert_eq!((chunks[1].start_line, chunks[1].end_line), (4, 7));
+//! assert_eq!(chunks[1].docs.as_deref(), Some("The area."));
+//! ```
+
+use quote::ToTokens;
+use syn::spanned::Spanned;
+use syn::{Attribute, ImplItem, Item, Type, Visibility};
+
+use super::ast::docs;
+use super::chunker::{CodeChunk, CodeItemKind, CodeParseError, LanguageParser};
+
+/// Splits Rust `source` into one [`CodeChunk`] per item, in source order.
+/// `module_path` is the path of the file's module, e.g. from
+/// [`module_path_for`](super::module_path_for).
+pub fn chunk_rust(source: &str, module_path: &[String]) -> Result<Vec<CodeChunk>, syn::Error> {
+    let file = syn::parse_file(source)?;
+    let lines: Vec<&str> = source.lines().collect();
+    let mut chunks = Vec::new();
+    let mut module_path = module_path.to_vec();
+    collect_items(&file.items, &lines, &mut module_path, &mut chunks);
+    Ok(chunks)
+}
+
+/// The `syn`-based [`LanguageParser`] for Rust, see [`chunk_rust`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RustParser;
+
+impl LanguageParser for RustParser {
+    fn language(&self) -> &'static str {
+        "rust"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["rs"]
+    }
+
+    fn parse_items(
+        &self,
+        source: &str,
+        module_path: &[String],
+    ) -> Result<Vec<CodeChunk>, CodeParseError> {
+        chunk_rust(source, module_path).map_err(|e| CodeParseError {
+            language: "rust",
+            message: e.to_string(),
+        })
+    }
+}
+
+fn collect_items(
+    items: &[Item],
+    lines: &[&str],
+    module_path: &mut Vec<String>,
+    chunks: &mut Vec<CodeChunk>,
+) {
+    for item in items {
+        let chunk = |name: String, kind, vis: &Visibility, attrs: &[Attribute]| {
+            item_chunk(
+                lines,
+                module_path,
+                None,
+                name,
+                kind,
+                vis,
+                attrs,
+                item.span(),
+            )
+        };
+        match item {
+            Item::Fn(f) => chunks.push(chunk(
+                f.sig.ident.to_string(),
+                CodeItemKind::Function,
+                &f.vis,
+                &f.attrs,
+            )),
+            Item::Struct(s) => chunks.push(chunk(
+                s.ident.to_string(),
+                CodeItemKind::Struct,
+                &s.vis,
+                &s.attrs,
+            )),
+            Item::Enum(e) => chunks.push(chunk(
+                e.ident.to_string(),
+                CodeItemKind::Enum,
+                &e.vis,
+                &e.attrs,
+            )),
+            Item::Union(u) => chunks.push(chunk(
+                u.ident.to_string(),
+                CodeItemKind::Union,
+                &u.vis,
+                &u.attrs,
+            )),
+            Item::Trait(t) => chunks.push(chunk(
+                t.ident.to_string(),
+                CodeItemKind::Trait,
+                &t.vis,
+                &t.attrs,
+            )),
+            Item::Type(t) => chunks.push(chunk(
+                t.ident.to_string(),
+                CodeItemKind::TypeAlias,
+                &t.vis,
+                &t.attrs,
+            )),
+            Item::Const(c) => chunks.push(chunk(
+                c.ident.to_string(),
+                CodeItemKind::Const,
+                &c.vis,
+                &c.attrs,
+            )),
+            Item::Static(s) => chunks.push(chunk(
+                s.ident.to_string(),
+                CodeItemKind::Static,
+                &s.vis,
+                &s.attrs,
+            )),
+            Item::Macro(m) => {
+                if let Some(ident) = &m.ident {
+                    chunks.push(chunk(
+                        ident.to_string(),
+                        CodeItemKind::Macro,
+                        &Visibility::Inherited,
+                        &m.attrs,
+                    ));
+                }
+            }
+            Item::Impl(i) => {
+                let self_ty = type_name(&i.self_ty);
+                let methods: Vec<_> = i
+                    .items
+                    .iter()
+                    .filter_map(|item| match item {
+                        ImplItem::Fn(f) => Some(f),
+                        _ => None,
+                    })
+                    .collect();
+                if methods.is_empty() {
+                    chunks.push(item_chunk(
+                        lines,
+                        module_path,
+                        None,
+                        self_ty.clone(),
+                        CodeItemKind::Impl,
+                        &Visibility::Inherited,
+                        &i.attrs,
+                        i.span(),
+                    ));
+                }
+                for method in methods {
+                    chunks.push(item_chunk(
+                        lines,
+                        module_path,
+                        Some(self_ty.clone()),
+                        method.sig.ident.to_string(),
+                        CodeItemKind::Method,
+                        &method.vis,
+                        &method.attrs,
+                        method.span(),
+                    ));
+                }
+            }
+            Item::Mod(m) => {
+                if let Some((_, items)) = &m.content {
+                    module_path.push(m.ident.to_string());
+                    collect_items(items, lines, module_path, chunks);
+                    module_path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn item_chunk(
+    lines: &[&str],
+    module_path: &[String],
+    parent: Option<String>,
+    name: String,
+    kind: CodeItemKind,
+    vis: &Visibility,
+    attrs: &[Attribute],
+    span: proc_macro2::Span,
+) -> CodeChunk {
+    let start_line = span.start().line.max(1);
+    let end_line = span
+        .end()
+        .line
+        .clamp(start_line, lines.len().max(start_line));
+    let text = lines
+        .get(start_line - 1..end_line.min(lines.len()))
+        .unwrap_or_default()
+        .join("\n");
+    CodeChunk {
+        text,
+        language: "rust".to_string(),
+        module_path: module_path.to_vec(),
+        parent,
+        name,
+        kind,
+        start_line,
+        end_line,
+        visibility: vis.to_token_stream().to_string().replace(' ', ""),
+        docs: docs(attrs),
+    }
+}
+
+/// The last path segment of a type, without generics: `Square` for
+/// `shapes::Square<T>`.
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+            .unwrap_or_default(),
+        other => other.to_token_stream().to_string(),
+    }
+}
@@ -0,0 +1,282 @@
+//! Sources of the API key.
+//!
+//! An [`ApiKeyProvider`] is asked for the key before every request, so keys
+//! fetched from a secret manager can be rotated without rebuilding the client.
+//! When neither a key nor a provider is configured, [`VoyageConfig`] falls back
+//! to [`DefaultApiKeyProvider`], which reads the standard locations.
+//!
+//! [`VoyageConfig`]: crate::config::VoyageConfig
+
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::oneshot;
+
+use crate::errors::VoyageError;
+
+/// Environment variable read by [`EnvApiKey::default`].
+pub const API_KEY_ENV_VAR: &str = "VOYAGE_API_KEY";
+
+/// Future returned by [`ApiKeyProvider::api_key`].
+pub struct ApiKeyTask {
+    receiver: oneshot::Receiver<Result<String, VoyageError>>,
+}
+
+impl ApiKeyTask {
+    pub fn new(receiver: oneshot::Receiver<Result<String, VoyageError>>) -> Self {
+        Self { receiver }
+    }
+
+    /// A task that resolves immediately to `result`.
+    pub fn ready(result: Result<String, VoyageError>) -> Self {
+        let (tx, rx) = oneshot::channel();
+        let _ = tx.send(result);
+        Self::new(rx)
+    }
+}
+
+impl Future for ApiKeyTask {
+    type Output = Result<String, VoyageError>;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        std::pin::Pin::new(&mut self.receiver)
+            .poll(cx)
+            .map(|result| {
+                result.unwrap_or_else(|_| {
+                    Err(VoyageError::Other("API key lookup canceled".to_string()))
+                })
+            })
+    }
+}
+
+/// A source of the API key, consulted before every request.
+pub trait ApiKeyProvider: fmt::Debug + Send + Sync + 'static {
+    fn api_key(&self) -> ApiKeyTask;
+}
+
+/// A fixed API key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct StaticApiKey(String);
+
+impl StaticApiKey {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self(api_key.into())
+    }
+}
+
+impl fmt::Debug for StaticApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StaticApiKey(..)")
+    }
+}
+
+impl ApiKeyProvider for StaticApiKey {
+    fn api_key(&self) -> ApiKeyTask {
+        ApiKeyTask::ready(Ok(self.0.clone()))
+    }
+}
+
+/// Reads the API key from an environment variable on every request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvApiKey {
+    var: String,
+}
+
+impl EnvApiKey {
+    pub fn new(var: impl Into<String>) -> Self {
+        Self { var: var.into() }
+    }
+}
+
+impl Default for EnvApiKey {
+    fn default() -> Self {
+        Self::new(API_KEY_ENV_VAR)
+    }
+}
+
+impl ApiKeyProvider for EnvApiKey {
+    fn api_key(&self) -> ApiKeyTask {
+        let key = std::env::var(&self.var)
+            .ok()
+            .filter(|key| !key.trim().is_empty())
+            .map(|key| key.trim().to_string())
+            .ok_or(VoyageError::MissingApiKey);
+        ApiKeyTask::ready(key)
+    }
+}
+
+/// Reads the API key from a file on every request, so that a rotated key is
+/// picked up without restarting. Surrounding whitespace is ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileApiKey {
+    path: PathBuf,
+}
+
+impl FileApiKey {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `$XDG_CONFIG_HOME/voyageai/api_key`, or `~/.config/voyageai/api_key`.
+    pub fn default_location() -> Option<PathBuf> {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join("voyageai").join("api_key"))
+    }
+}
+
+impl ApiKeyProvider for FileApiKey {
+    fn api_key(&self) -> ApiKeyTask {
+        let key = match std::fs::read_to_string(&self.path) {
+            Ok(contents) if !contents.trim().is_empty() => Ok(contents.trim().to_string()),
+            Ok(_) => Err(VoyageError::MissingApiKey),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(VoyageError::MissingApiKey),
+            Err(e) => Err(VoyageError::Other(format!(
+                "failed to read API key from {}: {}",
+                self.path.display(),
+                e
+            ))),
+        };
+        ApiKeyTask::ready(key)
+    }
+}
+
+/// Fetches the API key with an async callback, e.g. from AWS Secrets Manager
+/// or Vault. Wrap it in [`CachedApiKey`] to avoid a lookup per request.
+pub struct CallbackApiKey<F> {
+    callback: F,
+}
+
+impl<F, Fut> CallbackApiKey<F>
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<String, VoyageError>> + Send + 'static,
+{
+    pub fn new(callback: F) -> Self {
+        Self { callback }
+    }
+}
+
+impl<F> fmt::Debug for CallbackApiKey<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallbackApiKey").finish_non_exhaustive()
+    }
+}
+
+impl<F, Fut> ApiKeyProvider for CallbackApiKey<F>
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<String, VoyageError>> + Send + 'static,
+{
+    fn api_key(&self) -> ApiKeyTask {
+        let (tx, rx) = oneshot::channel();
+        let future = (self.callback)();
+        tokio::spawn(async move {
+            let _ = tx.send(future.await);
+        });
+        ApiKeyTask::new(rx)
+    }
+}
+
+/// Caches the key returned by another provider for a fixed time.
+#[derive(Debug)]
+pub struct CachedApiKey<P> {
+    inner: P,
+    ttl: Duration,
+    cached: Arc<Mutex<Option<(String, Instant)>>>,
+}
+
+impl<P: ApiKeyProvider> CachedApiKey<P> {
+    pub fn new(inner: P, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            cached: Arc::default(),
+        }
+    }
+
+    /// Forgets the cached key, e.g. after the API rejected it.
+    pub fn invalidate(&self) {
+        *self.cached.lock().unwrap() = None;
+    }
+}
+
+impl<P: ApiKeyProvider> ApiKeyProvider for CachedApiKey<P> {
+    fn api_key(&self) -> ApiKeyTask {
+        if let Some((key, fetched)) = self.cached.lock().unwrap().as_ref() {
+            if fetched.elapsed() < self.ttl {
+                return ApiKeyTask::ready(Ok(key.clone()));
+            }
+        }
+        let (tx, rx) = oneshot::channel();
+        let lookup = self.inner.api_key();
+        let cached = self.cached.clone();
+        tokio::spawn(async move {
+            let result = lookup.await;
+            if let Ok(key) = &result {
+                *cached.lock().unwrap() = Some((key.clone(), Instant::now()));
+            }
+            let _ = tx.send(result);
+        });
+        ApiKeyTask::new(rx)
+    }
+}
+
+/// Tries providers in order and returns the first key found. Errors other
+/// than [`VoyageError::MissingApiKey`] stop the search.
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeyChain {
+    providers: Vec<Arc<dyn ApiKeyProvider>>,
+}
+
+impl ApiKeyChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, provider: impl ApiKeyProvider) -> Self {
+        self.providers.push(Arc::new(provider));
+        self
+    }
+}
+
+impl ApiKeyProvider for ApiKeyChain {
+    fn api_key(&self) -> ApiKeyTask {
+        let (tx, rx) = oneshot::channel();
+        let providers = self.providers.clone();
+        tokio::spawn(async move {
+            let mut result = Err(VoyageError::MissingApiKey);
+            for provider in providers {
+                result = provider.api_key().await;
+                if !matches!(result, Err(VoyageError::MissingApiKey)) {
+                    break;
+                }
+            }
+            let _ = tx.send(result);
+        });
+        ApiKeyTask::new(rx)
+    }
+}
+
+/// Reads the key from the standard locations: the `VOYAGE_API_KEY`
+/// environment variable, then [`FileApiKey::default_location`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DefaultApiKeyProvider;
+
+impl ApiKeyProvider for DefaultApiKeyProvider {
+    fn api_key(&self) -> ApiKeyTask {
+        let mut chain = ApiKeyChain::new().with(EnvApiKey::default());
+        if let Some(path) = FileApiKey::default_location() {
+            chain = chain.with(FileApiKey::new(path));
+        }
+        chain.api_key()
+    }
+}
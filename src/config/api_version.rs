@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// Version of the Voyage AI REST API the client talks to.
+///
+/// The version is sent as the first path segment of every endpoint URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum ApiVersion {
+    #[serde(rename = "v1")]
+    #[default]
+    V1,
+}
+
+impl ApiVersion {
+    /// The most recent version supported by this crate.
+    pub const LATEST: ApiVersion = ApiVersion::V1;
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ApiVersion::V1 => "v1",
+        }
+    }
+}
+
+impl std::fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ApiVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "v1" | "1" => Ok(ApiVersion::V1),
+            other => Err(format!("unsupported API version: {other}")),
+        }
+    }
+}
@@ -0,0 +1,447 @@
+//! Layered loading of [`VoyageConfig`] from a TOML file and the environment.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::client::{AdaptiveThrottling, RetryPolicy};
+use crate::config::VoyageConfig;
+use crate::errors::VoyageError;
+
+/// Variable naming the configuration file to load.
+pub const CONFIG_FILE_ENV: &str = "VOYAGE_CONFIG";
+
+/// File name looked up in the working directory.
+pub const CONFIG_FILE_NAME: &str = "voyage.toml";
+
+/// Where a setting of a loaded configuration came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// The built-in default
+    Default,
+    /// A key of the configuration file
+    File(PathBuf),
+    /// An environment variable
+    Env(&'static str),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => f.write_str("default"),
+            Self::File(path) => write!(f, "file {}", path.display()),
+            Self::Env(variable) => write!(f, "env {variable}"),
+        }
+    }
+}
+
+/// The source of every setting of a configuration loaded with
+/// [`VoyageConfig::from_env`]. Displays one `setting: source` line per
+/// setting.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigSources {
+    file: Option<PathBuf>,
+    settings: BTreeMap<&'static str, ConfigSource>,
+}
+
+impl ConfigSources {
+    /// The configuration file that was read, if any.
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_deref()
+    }
+
+    /// Where `setting` came from; [`ConfigSource::Default`] for settings that
+    /// were not loaded.
+    pub fn get(&self, setting: &str) -> &ConfigSource {
+        self.settings.get(setting).unwrap_or(&ConfigSource::Default)
+    }
+
+    /// Every known setting and its source, by setting name.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &ConfigSource)> {
+        SETTINGS
+            .iter()
+            .map(|setting| (setting.name, self.get(setting.name)))
+    }
+}
+
+impl fmt::Display for ConfigSources {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, source) in self.iter() {
+            writeln!(f, "{name}: {source}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A setting that can be loaded, with its file key and variable.
+struct Setting {
+    name: &'static str,
+    env: &'static str,
+    apply: fn(&mut VoyageConfig, &str) -> Result<(), String>,
+}
+
+const SETTINGS: &[Setting] = &[
+    Setting {
+        name: "api_key",
+        env: "VOYAGE_API_KEY",
+        apply: |config, value| {
+            config.api_key = value.to_string();
+            Ok(())
+        },
+    },
+    Setting {
+        name: "base_url",
+        env: "VOYAGE_BASE_URL",
+        apply: |config, value| {
+            config.base_url = value.to_string();
+            Ok(())
+        },
+    },
+    Setting {
+        name: "api_version",
+        env: "VOYAGE_API_VERSION",
+        apply: |config, value| {
+            config.api_version = value.parse()?;
+            Ok(())
+        },
+    },
+    Setting {
+        name: "embedding_model",
+        env: "VOYAGE_EMBEDDING_MODEL",
+        apply: |config, value| {
+            config.embedding_model = value.parse()?;
+            Ok(())
+        },
+    },
+    Setting {
+        name: "rate_limit_tier",
+        env: "VOYAGE_RATE_LIMIT_TIER",
+        apply: |config, value| {
+            config.rate_limit_tier = value.parse()?;
+            Ok(())
+        },
+    },
+    Setting {
+        name: "adaptive_throttling",
+        env: "VOYAGE_ADAPTIVE_THROTTLING",
+        apply: |config, value| {
+            config.adaptive_throttling = parse_bool(value)?.then(AdaptiveThrottling::default);
+            Ok(())
+        },
+    },
+    Setting {
+        name: "rate_limit_timeout",
+        env: "VOYAGE_RATE_LIMIT_TIMEOUT",
+        apply: |config, value| {
+            config.rate_limit_timeout = Some(parse_seconds(value)?);
+            Ok(())
+        },
+    },
+    Setting {
+        name: "request_timeout",
+        env: "VOYAGE_REQUEST_TIMEOUT",
+        apply: |config, value| {
+            config.request_timeout = Some(parse_seconds(value)?);
+            Ok(())
+        },
+    },
+    Setting {
+        name: "max_retries",
+        env: "VOYAGE_MAX_RETRIES",
+        apply: |config, value| {
+            config.retry_policy = RetryPolicy {
+                max_retries: value
+                    .parse()
+                    .map_err(|_| format!("expected a number of retries, got '{value}'"))?,
+                ..config.retry_policy
+            };
+            Ok(())
+        },
+    },
+    Setting {
+        name: "retry_initial_delay",
+        env: "VOYAGE_RETRY_INITIAL_DELAY",
+        apply: |config, value| {
+            config.retry_policy = RetryPolicy {
+                initial_delay: parse_seconds(value)?,
+                ..config.retry_policy
+            };
+            Ok(())
+        },
+    },
+    Setting {
+        name: "priority",
+        env: "VOYAGE_PRIORITY",
+        apply: |config, value| {
+            config.priority = value.parse()?;
+            Ok(())
+        },
+    },
+    Setting {
+        name: "rerank_fallback",
+        env: "VOYAGE_RERANK_FALLBACK",
+        apply: |config, value| {
+            config.rerank_fallback = value.parse()?;
+            Ok(())
+        },
+    },
+    Setting {
+        name: "coalesce_requests",
+        env: "VOYAGE_COALESCE_REQUESTS",
+        apply: |config, value| {
+            config.coalesce_requests = parse_bool(value)?;
+            Ok(())
+        },
+    },
+    Setting {
+        name: "idempotency_keys",
+        env: "VOYAGE_IDEMPOTENCY_KEYS",
+        apply: |config, value| {
+            config.idempotency_keys = parse_bool(value)?;
+            Ok(())
+        },
+    },
+    Setting {
+        name: "max_in_flight",
+        env: "VOYAGE_MAX_IN_FLIGHT",
+        apply: |config, value| {
+            let max_in_flight = value
+                .parse()
+                .map_err(|_| format!("expected a number of requests, got '{value}'"))?;
+            config.concurrency_limiter = config
+                .concurrency_limiter
+                .clone()
+                .with_max_in_flight(max_in_flight);
+            Ok(())
+        },
+    },
+    Setting {
+        name: "log_payloads",
+        env: "VOYAGE_LOG_PAYLOADS",
+        apply: |config, value| {
+            config.log_policy = config.log_policy.log_payloads(parse_bool(value)?);
+            Ok(())
+        },
+    },
+];
+
+fn parse_seconds(value: &str) -> Result<Duration, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("expected a duration in seconds, got '{value}'"))
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Ok(true),
+        "false" | "0" | "no" | "off" => Ok(false),
+        _ => Err(format!("expected true or false, got '{value}'")),
+    }
+}
+
+type EnvLookup = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// Loads a [`VoyageConfig`] in layers, each overriding the one before:
+///
+/// 1. the built-in defaults,
+/// 2. the configuration file: the file named by `VOYAGE_CONFIG`, otherwise
+///    `voyage.toml` in the working directory, otherwise
+///    `~/.config/voyageai/voyage.toml`, if one exists,
+/// 3. `VOYAGE_*` environment variables.
+///
+/// Settings applied in code afterwards, with the `with_*` methods of the
+/// configuration or [`VoyageBuilder`](crate::VoyageBuilder), take precedence
+/// over all of them.
+///
+/// The file holds top-level keys named like the settings, e.g.
+///
+/// ```toml
+/// base_url = "https://api.voyageai.com"
+/// embedding_model = "voyage-code-3"
+/// rate_limit_tier = 2
+/// adaptive_throttling = true
+/// request_timeout = 30      # seconds
+/// rate_limit_timeout = 60   # seconds
+/// max_retries = 3
+/// retry_initial_delay = 0.5 # seconds
+/// priority = "normal"
+/// rerank_fallback = "on_rate_limit"
+/// coalesce_requests = true
+/// idempotency_keys = true
+/// max_in_flight = 16
+/// log_payloads = false
+/// ```
+///
+/// and each setting has a variable named `VOYAGE_` followed by its name in
+/// upper case, e.g. `VOYAGE_RATE_LIMIT_TIER`. The API key is read from
+/// `VOYAGE_API_KEY` only, never from the file; without it requests fall back
+/// to the [standard key locations](crate::config::DefaultApiKeyProvider).
+/// Unknown keys and invalid values are errors naming their source.
+#[derive(Clone)]
+pub struct ConfigLoader {
+    file: FileChoice,
+    env: Option<EnvLookup>,
+}
+
+#[derive(Debug, Clone)]
+enum FileChoice {
+    Discover,
+    Path(PathBuf),
+    None,
+}
+
+impl fmt::Debug for ConfigLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigLoader")
+            .field("file", &self.file)
+            .field("env", &self.env.is_some())
+            .finish()
+    }
+}
+
+impl Default for ConfigLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigLoader {
+    /// Discovers the configuration file and reads the process environment.
+    pub fn new() -> Self {
+        Self {
+            file: FileChoice::Discover,
+            env: Some(Arc::new(|name| std::env::var(name).ok())),
+        }
+    }
+
+    /// Reads `path`, which must exist, instead of discovering the file.
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = FileChoice::Path(path.into());
+        self
+    }
+
+    /// Skips the configuration file.
+    pub fn without_file(mut self) -> Self {
+        self.file = FileChoice::None;
+        self
+    }
+
+    /// Looks variables up with `lookup` instead of in the process
+    /// environment, including `VOYAGE_CONFIG`.
+    pub fn with_env<F>(mut self, lookup: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.env = Some(Arc::new(lookup));
+        self
+    }
+
+    /// Skips environment variables.
+    pub fn without_env(mut self) -> Self {
+        self.env = None;
+        self
+    }
+
+    pub fn load(&self) -> Result<VoyageConfig, VoyageError> {
+        let mut config = VoyageConfig::default();
+        let mut sources = ConfigSources::default();
+
+        if let Some(path) = self.config_file()? {
+            let contents = std::fs::read_to_string(&path).map_err(|e| {
+                VoyageError::ConfigError(format!("cannot read {}: {e}", path.display()))
+            })?;
+            let table: toml::Table = contents.parse().map_err(|e| {
+                VoyageError::ConfigError(format!("cannot parse {}: {e}", path.display()))
+            })?;
+            for (key, value) in &table {
+                let setting = SETTINGS
+                    .iter()
+                    .find(|setting| setting.name == key && setting.name != "api_key")
+                    .ok_or_else(|| {
+                        VoyageError::ConfigError(format!(
+                            "unknown setting '{key}' in {}",
+                            path.display()
+                        ))
+                    })?;
+                let value = match value {
+                    toml::Value::String(value) => value.clone(),
+                    toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+                        value.to_string()
+                    }
+                    other => {
+                        return Err(VoyageError::ConfigError(format!(
+                            "invalid {key} in {}: unexpected {}",
+                            path.display(),
+                            other.type_str()
+                        )))
+                    }
+                };
+                (setting.apply)(&mut config, &value).map_err(|e| {
+                    VoyageError::ConfigError(format!("invalid {key} in {}: {e}", path.display()))
+                })?;
+                sources
+                    .settings
+                    .insert(setting.name, ConfigSource::File(path.clone()));
+            }
+            sources.file = Some(path);
+        }
+
+        if let Some(env) = &self.env {
+            for setting in SETTINGS {
+                let Some(value) = env(setting.env).filter(|value| !value.is_empty()) else {
+                    continue;
+                };
+                (setting.apply)(&mut config, &value).map_err(|e| {
+                    VoyageError::ConfigError(format!("invalid {}: {e}", setting.env))
+                })?;
+                sources
+                    .settings
+                    .insert(setting.name, ConfigSource::Env(setting.env));
+            }
+        }
+
+        log::debug!("Loaded configuration:\n{}", sources);
+        config.sources = sources;
+        Ok(config)
+    }
+
+    fn config_file(&self) -> Result<Option<PathBuf>, VoyageError> {
+        let required = |path: PathBuf| {
+            if path.is_file() {
+                Ok(Some(path))
+            } else {
+                Err(VoyageError::ConfigError(format!(
+                    "configuration file {} not found",
+                    path.display()
+                )))
+            }
+        };
+        match &self.file {
+            FileChoice::None => Ok(None),
+            FileChoice::Path(path) => required(path.clone()),
+            FileChoice::Discover => {
+                if let Some(path) = self.env.as_ref().and_then(|env| env(CONFIG_FILE_ENV)) {
+                    return required(PathBuf::from(path));
+                }
+                Ok(default_locations().into_iter().find(|path| path.is_file()))
+            }
+        }
+    }
+}
+
+/// `voyage.toml` in the working directory, then in the user's configuration
+/// directory next to the API key file.
+fn default_locations() -> Vec<PathBuf> {
+    let mut locations = vec![PathBuf::from(CONFIG_FILE_NAME)];
+    if let Some(dir) = crate::config::FileApiKey::default_location()
+        .as_deref()
+        .and_then(Path::parent)
+    {
+        locations.push(dir.join(CONFIG_FILE_NAME));
+    }
+    locations
+}
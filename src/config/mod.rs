@@ -1,3 +1,12 @@
+pub mod api_key;
+mod api_version;
+mod loader;
 mod voyage_config;
 
-pub use voyage_config::VoyageConfig;
+pub use api_key::{
+    ApiKeyChain, ApiKeyProvider, ApiKeyTask, CachedApiKey, CallbackApiKey, DefaultApiKeyProvider,
+    EnvApiKey, FileApiKey, StaticApiKey,
+};
+pub use api_version::ApiVersion;
+pub use loader::{ConfigLoader, ConfigSource, ConfigSources, CONFIG_FILE_ENV, CONFIG_FILE_NAME};
+pub use voyage_config::{VoyageConfig, DEFAULT_BASE_URL};
@@ -1,6 +1,23 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::client::rerank_client::RerankFallback;
+use crate::client::{
+    AdaptiveThrottling, CircuitBreaker, ConcurrencyLimiter, HedgingPolicy, Lifecycle, Priority, RateLimitEndpoint, RateLimitTier,
+    RateLimiter, RetryPolicy,
+};
+use crate::config::api_key::{ApiKeyProvider, DefaultApiKeyProvider};
+use crate::config::{ApiVersion, ConfigLoader, ConfigSources};
+use crate::errors::VoyageError;
+use crate::logging::LogPolicy;
 use crate::models::{embeddings::EmbeddingModel, search::SearchModel, RerankModel};
+use crate::transport::{ReqwestTransport, Transport};
 use serde::Deserialize;
 
+/// Root URL of the Voyage AI API, without the version segment.
+pub const DEFAULT_BASE_URL: &str = "https://api.voyageai.com";
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Deserialize)]
 pub enum Model {
@@ -15,32 +32,343 @@ impl Default for Model {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Clone)]
 pub struct VoyageConfig {
     pub api_key: String,
     pub base_url: String,
+    pub api_version: ApiVersion,
     pub search_model: SearchModel,
     pub embedding_model: EmbeddingModel,
+    /// Consulted before every request instead of `api_key` when set.
+    pub api_key_provider: Option<Arc<dyn ApiKeyProvider>>,
+    /// What the clients may write to the log.
+    pub log_policy: LogPolicy,
+    /// When reranking degrades to local embedding similarity.
+    pub rerank_fallback: RerankFallback,
+    /// Longest a request waits for the rate limiter before failing with
+    /// [`VoyageError::RateLimitExceeded`]. Unbounded when `None`.
+    pub rate_limit_timeout: Option<Duration>,
+    /// Scheduling class of requests waiting for the rate limiter.
+    pub priority: Priority,
+    /// Whether identical embedding requests in flight share one API call.
+    pub coalesce_requests: bool,
+    /// Usage tier of the account, which sets the rate limits of new clients.
+    pub rate_limit_tier: RateLimitTier,
+    /// Backs off the rate limits of new clients when the API rejects
+    /// requests. Off when `None`.
+    pub adaptive_throttling: Option<AdaptiveThrottling>,
+    /// Longest an HTTP request may take, from connecting to reading the
+    /// response. Unbounded when `None`.
+    pub request_timeout: Option<Duration>,
+    /// Retries of requests rejected by the API's rate limit.
+    pub retry_policy: RetryPolicy,
+    /// Whether requests carry an idempotency key, the same for every retry.
+    pub idempotency_keys: bool,
+    /// Fails requests at once while the API keeps failing. Off when `None`.
+    pub circuit_breaker: Option<CircuitBreaker>,
+    /// Caps the embeddings and rerank requests in flight, shared by the
+    /// clients created from the configuration. Unlimited by default.
+    pub concurrency_limiter: ConcurrencyLimiter,
+    /// Work in flight on the clients created from the configuration, closed
+    /// by [`VoyageAiClient::shutdown`](crate::VoyageAiClient::shutdown).
+    pub lifecycle: Lifecycle,
+    /// Duplicates slow requests to these endpoints.
+    pub hedging: HashMap<RateLimitEndpoint, HedgingPolicy>,
+    /// HTTP stack requests are sent with; [`ReqwestTransport`] when `None`.
+    pub transport: Option<Arc<dyn Transport>>,
+    /// Where each setting came from, when loaded with
+    /// [`from_env`](Self::from_env).
+    pub sources: ConfigSources,
+}
+
+impl std::fmt::Debug for VoyageConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let api_key = if self.api_key.is_empty() { "" } else { "[REDACTED]" };
+        f.debug_struct("VoyageConfig")
+            .field("api_key", &api_key)
+            .field("base_url", &self.base_url)
+            .field("api_version", &self.api_version)
+            .field("search_model", &self.search_model)
+            .field("embedding_model", &self.embedding_model)
+            .field("api_key_provider", &self.api_key_provider)
+            .field("log_policy", &self.log_policy)
+            .field("rerank_fallback", &self.rerank_fallback)
+            .field("rate_limit_timeout", &self.rate_limit_timeout)
+            .field("priority", &self.priority)
+            .field("coalesce_requests", &self.coalesce_requests)
+            .field("rate_limit_tier", &self.rate_limit_tier)
+            .field("adaptive_throttling", &self.adaptive_throttling)
+            .field("request_timeout", &self.request_timeout)
+            .field("retry_policy", &self.retry_policy)
+            .field("idempotency_keys", &self.idempotency_keys)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .field("concurrency_limiter", &self.concurrency_limiter)
+            .field("lifecycle", &self.lifecycle)
+            .field("hedging", &self.hedging)
+            .field("transport", &self.transport)
+            .field("sources", &self.sources)
+            .finish()
+    }
+}
+
+impl Default for VoyageConfig {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
 }
 
 impl VoyageConfig {
     pub fn new(api_key: String) -> Self {
         Self {
             api_key,
-            base_url: "https://api.voyageai.com/v1".to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_version: ApiVersion::LATEST,
             search_model: SearchModel::default(),
             embedding_model: EmbeddingModel::default(),
+            api_key_provider: None,
+            log_policy: LogPolicy::default(),
+            rerank_fallback: RerankFallback::default(),
+            rate_limit_timeout: None,
+            priority: Priority::default(),
+            coalesce_requests: false,
+            rate_limit_tier: RateLimitTier::default(),
+            adaptive_throttling: None,
+            request_timeout: None,
+            retry_policy: RetryPolicy::default(),
+            idempotency_keys: false,
+            circuit_breaker: None,
+            concurrency_limiter: ConcurrencyLimiter::default(),
+            lifecycle: Lifecycle::default(),
+            hedging: HashMap::new(),
+            transport: None,
+            sources: ConfigSources::default(),
         }
     }
 
+    /// Loads the configuration from `voyage.toml` and `VOYAGE_*` environment
+    /// variables, see [`ConfigLoader`] for the locations and precedence.
+    /// [`sources`](Self::sources) records where each setting came from.
+    pub fn from_env() -> Result<Self, VoyageError> {
+        ConfigLoader::new().load()
+    }
+
+    /// Creates a configuration whose key is fetched from `provider` before
+    /// every request.
+    pub fn with_provider(provider: impl ApiKeyProvider) -> Self {
+        Self::new(String::new()).with_api_key_provider(provider)
+    }
+
+    pub fn with_api_key_provider(mut self, provider: impl ApiKeyProvider) -> Self {
+        self.api_key_provider = Some(Arc::new(provider));
+        self
+    }
+
     pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
         self.base_url = base_url.into();
         self
     }
 
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = api_key.into();
+        self
+    }
+
+    /// Sets the model `embed` and the other embedding methods use.
+    pub fn with_embedding_model(mut self, model: EmbeddingModel) -> Self {
+        self.embedding_model = model;
+        self
+    }
+
+    /// Pins the API version used for every request.
+    pub fn with_api_version(mut self, api_version: ApiVersion) -> Self {
+        self.api_version = api_version;
+        self
+    }
+
+    /// Includes (redacted, truncated) request and response bodies in log
+    /// messages. Off by default since payloads contain document content.
+    pub fn log_payloads(mut self, enabled: bool) -> Self {
+        self.log_policy = self.log_policy.log_payloads(enabled);
+        self
+    }
+
+    pub fn with_log_policy(mut self, log_policy: LogPolicy) -> Self {
+        self.log_policy = log_policy;
+        self
+    }
+
+    /// Ranks documents by embedding similarity when the rerank endpoint fails
+    /// as described by `fallback`. Results report the strategy used in
+    /// [`DocumentSimilarity::strategy`](crate::client::rerank_client::DocumentSimilarity::strategy).
+    pub fn with_rerank_fallback(mut self, fallback: RerankFallback) -> Self {
+        self.rerank_fallback = fallback;
+        self
+    }
+
+    /// Fails requests that would wait longer than `timeout` for the rate
+    /// limiter instead of sleeping until they may be sent.
+    pub fn with_rate_limit_timeout(mut self, timeout: Duration) -> Self {
+        self.rate_limit_timeout = Some(timeout);
+        self
+    }
+
+    /// Serves this configuration's requests ahead of lower priorities when
+    /// they wait for a shared rate limiter.
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sends concurrent embedding requests for the same texts, model and
+    /// options once and gives every caller the result. Off by default.
+    pub fn with_request_coalescing(mut self, enabled: bool) -> Self {
+        self.coalesce_requests = enabled;
+        self
+    }
+
+    /// Sets the rate limits of clients created from this configuration to
+    /// those of `tier`.
+    pub fn with_rate_limit_tier(mut self, tier: RateLimitTier) -> Self {
+        self.rate_limit_tier = tier;
+        self
+    }
+
+    /// Lets clients created from this configuration adapt their rate limits
+    /// to the 429 responses they receive, e.g. when several processes share
+    /// an API key.
+    pub fn with_adaptive_throttling(mut self, throttling: AdaptiveThrottling) -> Self {
+        self.adaptive_throttling = Some(throttling);
+        self
+    }
+
+    /// Fails HTTP requests that take longer than `timeout`.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Retries requests rejected by the API's rate limit.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sends every embeddings and rerank request, and every POST to the
+    /// batch API, with a [`RequestId`](crate::models::RequestId) in its
+    /// `Idempotency-Key` header, generated per call and reused by its
+    /// retries, and returns it in the response metadata. A
+    /// [hedge](crate::client::HedgingPolicy) gets a key of its own. Off by
+    /// default.
+    pub fn with_idempotency_keys(mut self, enabled: bool) -> Self {
+        self.idempotency_keys = enabled;
+        self
+    }
+
+    /// Sends every request through `breaker`, so that requests fail with
+    /// [`VoyageError::CircuitOpen`] instead of piling up while the API is
+    /// down. Pass clones of one breaker to share it with other
+    /// configurations.
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Allows at most `max_in_flight` embeddings and rerank requests at once,
+    /// however many tasks send them, see [`concurrency`](crate::client::concurrency).
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.concurrency_limiter = self.concurrency_limiter.with_max_in_flight(max_in_flight);
+        self
+    }
+
+    /// Allows at most `max_in_flight` requests to `endpoint` at once.
+    pub fn with_max_in_flight_for(mut self, endpoint: RateLimitEndpoint, max_in_flight: usize) -> Self {
+        self.concurrency_limiter = self
+            .concurrency_limiter
+            .with_max_in_flight_for(endpoint, max_in_flight);
+        self
+    }
+
+    /// Sends a duplicate of requests to `endpoint` that are slower than
+    /// `policy`'s delay and takes whichever answers first, see
+    /// [`hedging`](crate::client::hedging).
+    pub fn with_hedging(mut self, endpoint: RateLimitEndpoint, policy: HedgingPolicy) -> Self {
+        self.hedging.insert(endpoint, policy);
+        self
+    }
+
+    pub(crate) fn hedging(&self, endpoint: RateLimitEndpoint) -> Option<&HedgingPolicy> {
+        self.hedging.get(&endpoint)
+    }
+
+    /// Sends requests with `transport` instead of `reqwest`, see
+    /// [`transport`](crate::transport).
+    pub fn with_transport(mut self, transport: impl Transport) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// A rate limiter for [`rate_limit_tier`](Self::rate_limit_tier) and
+    /// [`adaptive_throttling`](Self::adaptive_throttling).
+    pub(crate) fn rate_limiter(&self) -> RateLimiter {
+        let limiter = RateLimiter::for_tier(self.rate_limit_tier);
+        match self.adaptive_throttling {
+            Some(throttling) => limiter.with_adaptive_throttling(throttling),
+            None => limiter,
+        }
+    }
+
+    /// The configured transport, or a [`ReqwestTransport`] honoring
+    /// [`request_timeout`](Self::request_timeout), behind the
+    /// [`circuit_breaker`](Self::circuit_breaker) if any.
+    pub(crate) fn transport(&self) -> Arc<dyn Transport> {
+        let transport: Arc<dyn Transport> = match &self.transport {
+            Some(transport) => transport.clone(),
+            None => Arc::new(ReqwestTransport::new(self.http_client())),
+        };
+        match &self.circuit_breaker {
+            Some(breaker) => breaker.wrap(transport),
+            None => transport,
+        }
+    }
+
+    fn http_client(&self) -> reqwest::Client {
+        let mut builder = reqwest::Client::builder();
+        if let Some(timeout) = self.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+        builder.build().unwrap_or_else(|e| {
+            log::warn!("Cannot build HTTP client ({}), using the defaults", e);
+            reqwest::Client::new()
+        })
+    }
+
+    /// Builds the full URL of an API endpoint, e.g. `endpoint("embeddings")`.
+    ///
+    /// A base URL that already ends with the version segment is used as is.
+    pub fn endpoint(&self, path: &str) -> String {
+        let base_url = self.base_url.trim_end_matches('/');
+        let version = self.api_version.as_str();
+        let path = path.trim_start_matches('/');
+        if base_url.ends_with(&format!("/{version}")) {
+            format!("{base_url}/{path}")
+        } else {
+            format!("{base_url}/{version}/{path}")
+        }
+    }
+
     pub fn api_key(&self) -> &str {
         &self.api_key
     }
+
+    /// The key to send with the next request: from the provider if one is set,
+    /// otherwise `api_key`, otherwise the [standard locations](DefaultApiKeyProvider).
+    pub async fn resolve_api_key(&self) -> Result<String, VoyageError> {
+        match &self.api_key_provider {
+            Some(provider) => provider.api_key().await,
+            None if !self.api_key.is_empty() => Ok(self.api_key.clone()),
+            None => DefaultApiKeyProvider.api_key().await,
+        }
+    }
 }
 
 fn default_embedding_model() -> EmbeddingModel {
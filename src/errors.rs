@@ -1,3 +1,4 @@
+use crate::models::response_headers::ResponseMetadata;
 use std::time::Duration;
 use thiserror::Error;
 
@@ -30,8 +31,13 @@ pub enum VoyageError {
     #[error("Not Found (404): {0}")]
     NotFound(String),
 
+    /// Raised by the API with a 429 response, or by the client's rate
+    /// limiter, in which case `metadata` is `None`.
     #[error("Rate Limit Exceeded (429): Too many requests. Limit resets in {reset_in:?}")]
-    RateLimitExceeded { reset_in: Duration },
+    RateLimitExceeded {
+        reset_in: Duration,
+        metadata: Option<Box<ResponseMetadata>>,
+    },
 
     #[error("Internal Server Error (500): Unexpected server error - {message}")]
     InternalServerError { message: String },
@@ -39,6 +45,17 @@ pub enum VoyageError {
     #[error("Service Unavailable (503)")]
     ServiceUnavailable,
 
+    /// Raised without sending the request while the
+    /// [circuit breaker](crate::client::CircuitBreaker) is open. `retry_in`
+    /// is zero while it lets probe requests through.
+    #[error("Circuit open: the Voyage API keeps failing, retry in {retry_in:?}")]
+    CircuitOpen { retry_in: Duration },
+
+    /// Raised for requests made after
+    /// [`VoyageAiClient::shutdown`](crate::VoyageAiClient::shutdown) began.
+    #[error("Client is shutting down: no new requests are accepted")]
+    ShuttingDown,
+
     #[error("Missing API key")]
     MissingApiKey,
 
@@ -51,11 +68,14 @@ pub enum VoyageError {
     #[error("HTTP request error: {0}")]
     RequestError(#[from] reqwest::Error),
 
+    #[error("HTTP transport error: {0}")]
+    TransportError(String),
+
     #[error("Unhandled status code {0}: {1}")]
     UnhandledStatusCode(u16, String),
 
     #[error("API error (status {0}): {1}")]
-    ApiError(reqwest::StatusCode, String),
+    ApiError(reqwest::StatusCode, String, Box<ResponseMetadata>),
 
     #[error("Input list too long: maximum of 128 texts allowed")]
     InputListTooLong,
@@ -74,18 +94,219 @@ pub enum VoyageError {
 
     #[error("No results found")]
     NoResults,
+
+    #[error("Empty response: the API returned no embeddings")]
+    EmptyResponse,
+
+    #[error("Embedding for input {index} is missing from the response")]
+    MissingEmbedding { index: usize },
+
+    #[error("Response contains an unexpected or duplicate embedding index {index}")]
+    UnexpectedEmbeddingIndex { index: usize },
+
+    #[error("Embedding {index} has {actual} dimensions, expected {expected}")]
+    EmbeddingDimensionMismatch {
+        index: usize,
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error("Malformed response: {0}")]
+    MalformedResponse(String),
+
+    #[error(transparent)]
+    Validation(#[from] crate::builder::validation::ValidationError),
+
+    #[error(transparent)]
+    Vector(#[from] crate::vectors::VectorError),
+
+    #[error(transparent)]
+    Similarity(#[from] SimilarityError),
+
+    #[error("Index format error: {0}")]
+    IndexFormatError(String),
+
+    #[error("Index I/O error: {0}")]
+    IndexIoError(String),
+
+    /// The index file was written by a newer release in a format this one
+    /// cannot read
+    #[error(
+        "Index format version {version} is newer than the supported version {supported}; upgrade voyageai{} to load it, or rebuild the index",
+        .written_by.as_ref().map(|version| format!(" to {version} or later")).unwrap_or_default()
+    )]
+    IndexVersionUnsupported {
+        version: u32,
+        supported: u32,
+        written_by: Option<String>,
+    },
+
+    #[error("Index migration embedded {embedded} of {total} documents")]
+    MigrationIncomplete { embedded: usize, total: usize },
+
+    #[error("A namespace is required to use a namespaced index")]
+    NamespaceRequired,
+
+    #[error("Namespace not found: {0}")]
+    NamespaceNotFound(String),
+
+    #[error("Collection not found: {0}")]
+    CollectionNotFound(String),
+
+    #[error("Collection already exists: {0}")]
+    CollectionExists(String),
+
+    #[error("Checkpoint error: {0}")]
+    CheckpointError(String),
+
+    #[error("Configuration error: {0}")]
+    ConfigError(String),
+
+    #[error("Web ingestion error: {0}")]
+    WebIngestError(String),
+
+    #[error("Analysis error: {0}")]
+    AnalysisError(String),
+
+    #[error("Evaluation error: {0}")]
+    EvalError(String),
+
+    #[error("Classification error: {0}")]
+    ClassificationError(String),
+
+    #[error("Batch error: {0}")]
+    BatchError(String),
+
+    #[error("Embedding interchange error: {0}")]
+    InterchangeError(String),
+
+    #[error("Local model error: {0}")]
+    LocalModelError(String),
+
+    #[error("Repository walk error: {0}")]
+    RepositoryError(String),
     
     #[error("Other error: {0}")]
     Other(String),
 }
 
+impl VoyageError {
+    /// A copy of the error for sharing one failure among several callers.
+    /// HTTP errors, which cannot be cloned, become `Other` with the same
+    /// message.
+    pub fn duplicate(&self) -> VoyageError {
+        use VoyageError::*;
+        match self {
+            MissingDocuments(message) => MissingDocuments(message.clone()),
+            SearchBuilderError(message) => SearchBuilderError(message.clone()),
+            SearchIndexNotBuilt => SearchIndexNotBuilt,
+            SearchDimensionMismatch { expected, actual } => SearchDimensionMismatch {
+                expected: *expected,
+                actual: *actual,
+            },
+            SearchResultError(message) => SearchResultError(message.clone()),
+            BadRequest { message } => BadRequest {
+                message: message.clone(),
+            },
+            Unauthorized => Unauthorized,
+            Forbidden(message) => Forbidden(message.clone()),
+            NotFound(message) => NotFound(message.clone()),
+            RateLimitExceeded { reset_in, metadata } => RateLimitExceeded {
+                reset_in: *reset_in,
+                metadata: metadata.clone(),
+            },
+            InternalServerError { message } => InternalServerError {
+                message: message.clone(),
+            },
+            ServiceUnavailable => ServiceUnavailable,
+            CircuitOpen { retry_in } => CircuitOpen {
+                retry_in: *retry_in,
+            },
+            ShuttingDown => ShuttingDown,
+            MissingApiKey => MissingApiKey,
+            JsonError(message) => JsonError(message.clone()),
+            TokenizerError(message) => TokenizerError(message.clone()),
+            RequestError(error) => Other(format!("HTTP request error: {error}")),
+            TransportError(message) => TransportError(message.clone()),
+            UnhandledStatusCode(status, message) => UnhandledStatusCode(*status, message.clone()),
+            ApiError(status, message, metadata) => {
+                ApiError(*status, message.clone(), metadata.clone())
+            }
+            InputListTooLong => InputListTooLong,
+            TokenLimitExceeded(tokens, limit) => TokenLimitExceeded(*tokens, *limit),
+            TooManyDocuments(count) => TooManyDocuments(*count),
+            QueryDocumentTokenLimitExceeded(tokens, limit) => {
+                QueryDocumentTokenLimitExceeded(*tokens, *limit)
+            }
+            BuilderError(message) => BuilderError(message.clone()),
+            NoResults => NoResults,
+            EmptyResponse => EmptyResponse,
+            MissingEmbedding { index } => MissingEmbedding { index: *index },
+            UnexpectedEmbeddingIndex { index } => UnexpectedEmbeddingIndex { index: *index },
+            EmbeddingDimensionMismatch {
+                index,
+                expected,
+                actual,
+            } => EmbeddingDimensionMismatch {
+                index: *index,
+                expected: *expected,
+                actual: *actual,
+            },
+            MalformedResponse(message) => MalformedResponse(message.clone()),
+            Validation(error) => Validation(error.clone()),
+            Vector(error) => Vector(error.clone()),
+            Similarity(error) => Similarity(error.clone()),
+            IndexFormatError(message) => IndexFormatError(message.clone()),
+            IndexIoError(message) => IndexIoError(message.clone()),
+            IndexVersionUnsupported {
+                version,
+                supported,
+                written_by,
+            } => IndexVersionUnsupported {
+                version: *version,
+                supported: *supported,
+                written_by: written_by.clone(),
+            },
+            MigrationIncomplete { embedded, total } => MigrationIncomplete {
+                embedded: *embedded,
+                total: *total,
+            },
+            NamespaceRequired => NamespaceRequired,
+            NamespaceNotFound(message) => NamespaceNotFound(message.clone()),
+            CollectionNotFound(message) => CollectionNotFound(message.clone()),
+            CollectionExists(message) => CollectionExists(message.clone()),
+            CheckpointError(message) => CheckpointError(message.clone()),
+            ConfigError(message) => ConfigError(message.clone()),
+            WebIngestError(message) => WebIngestError(message.clone()),
+            AnalysisError(message) => AnalysisError(message.clone()),
+            EvalError(message) => EvalError(message.clone()),
+            ClassificationError(message) => ClassificationError(message.clone()),
+            BatchError(message) => BatchError(message.clone()),
+            InterchangeError(message) => InterchangeError(message.clone()),
+            LocalModelError(message) => LocalModelError(message.clone()),
+            RepositoryError(message) => RepositoryError(message.clone()),
+            Other(message) => Other(message.clone()),
+        }
+    }
+
+    /// The headers of the API response that caused the error, e.g. to log
+    /// its request ID. `None` for errors not raised by a response.
+    pub fn response_metadata(&self) -> Option<&ResponseMetadata> {
+        match self {
+            VoyageError::RateLimitExceeded { metadata, .. } => metadata.as_deref(),
+            VoyageError::ApiError(_, _, metadata) => Some(metadata),
+            _ => None,
+        }
+    }
+}
+
 impl From<serde_json::Error> for VoyageError {
     fn from(error: serde_json::Error) -> Self {
         VoyageError::JsonError(error.to_string())
     }
 }
 
-use crate::models::rerank::ValidationError;
+use crate::models::rerank::RerankValidationError;
 
 impl From<String> for VoyageError {
     fn from(message: String) -> Self {
@@ -99,9 +320,9 @@ impl From<&str> for VoyageError {
     }
 }
 
-impl From<ValidationError> for VoyageError {
-    fn from(err: ValidationError) -> Self {
-        VoyageError::BuilderError(err.to_string())
+impl From<RerankValidationError> for VoyageError {
+    fn from(err: RerankValidationError) -> Self {
+        VoyageError::Validation(err.into())
     }
 }
 
@@ -130,6 +351,9 @@ pub enum VoyageBuilderError {
 
     #[error("Missing Voyage client")]
     MissingVoyage,
+
+    #[error(transparent)]
+    Validation(#[from] crate::builder::validation::ValidationError),
 }
 
 impl From<VoyageBuilderError> for VoyageError {
@@ -137,3 +361,14 @@ impl From<VoyageBuilderError> for VoyageError {
         VoyageError::BuilderError(error.to_string())
     }
 }
+
+/// Why [`try_cosine_similarity`](crate::try_cosine_similarity) could not
+/// compare two vectors.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SimilarityError {
+    #[error("Cannot compare an empty vector")]
+    Empty,
+
+    #[error("Cannot compare vectors of {left} and {right} dimensions")]
+    DimensionMismatch { left: usize, right: usize },
+}
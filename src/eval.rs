@@ -0,0 +1,444 @@
+//! Retrieval quality evaluation.
+//!
+//! An [`EvalDataset`] pairs a corpus with labeled queries (the ids of the
+//! documents relevant to each query). [`evaluate_pipeline`] indexes the corpus
+//! with a [`RetrievalPipeline`], runs every query and scores the rankings with
+//! [`evaluate`], so that models and parameters can be compared on the same data:
+//!
+//! - **recall@k**: fraction of the relevant documents found in the top `k`
+//! - **MRR**: mean reciprocal rank of the first relevant document
+//! - **nDCG@k**: discounted cumulative gain of the top `k`, normalized by the
+//!   gain of an ideal ranking (binary relevance)
+//!
+//! Reports serialize to JSON and CSV. [`benchmark_pipeline`] additionally
+//! measures latency and token usage, and [`benchmarks_to_table`] lays several
+//! benchmarks out side by side.
+//!
+//! # Dataset format
+//!
+//! Datasets are JSON Lines files mixing documents and queries:
+//!
+//! ```text
+//! {"id": "doc-1", "text": "Paris is the capital of France."}
+//! {"id": "doc-2", "text": "Berlin is the capital of Germany."}
+//! {"query": "capital of France", "relevant": ["doc-1"]}
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::VoyageError;
+use crate::io::csv::csv_field;
+use crate::retrieval::RetrievalPipeline;
+
+/// A document of an evaluation corpus.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalDocument {
+    pub id: String,
+    pub text: String,
+}
+
+/// A query with the ids of the documents relevant to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalQuery {
+    pub query: String,
+    pub relevant: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DatasetLine {
+    Query(EvalQuery),
+    Document(EvalDocument),
+}
+
+/// A corpus and labeled queries.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvalDataset {
+    pub documents: Vec<EvalDocument>,
+    pub queries: Vec<EvalQuery>,
+}
+
+impl EvalDataset {
+    /// Parses a JSON Lines dataset; see the [module docs](self) for the format.
+    pub fn from_jsonl(jsonl: &str) -> Result<Self, VoyageError> {
+        let mut dataset = Self::default();
+        for (number, line) in jsonl.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let parsed: DatasetLine = serde_json::from_str(line).map_err(|e| {
+                VoyageError::JsonError(format!("dataset line {}: {}", number + 1, e))
+            })?;
+            match parsed {
+                DatasetLine::Query(query) => dataset.queries.push(query),
+                DatasetLine::Document(document) => dataset.documents.push(document),
+            }
+        }
+        Ok(dataset)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, VoyageError> {
+        Self::from_jsonl(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Metrics of a single query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryMetrics {
+    pub query: String,
+    /// Recall at each cutoff
+    pub recall: BTreeMap<usize, f64>,
+    /// nDCG at each cutoff
+    pub ndcg: BTreeMap<usize, f64>,
+    /// Reciprocal rank of the first relevant document, 0 if none was retrieved
+    pub reciprocal_rank: f64,
+}
+
+/// Metrics averaged over a dataset's queries.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalReport {
+    /// Name of the configuration evaluated, e.g. the model
+    pub label: String,
+    pub recall: BTreeMap<usize, f64>,
+    pub ndcg: BTreeMap<usize, f64>,
+    pub mrr: f64,
+    pub queries: Vec<QueryMetrics>,
+}
+
+impl EvalReport {
+    pub fn to_json(&self) -> Result<String, VoyageError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// One CSV row per query, preceded by a header.
+    pub fn queries_to_csv(&self) -> String {
+        let cutoffs: Vec<usize> = self.recall.keys().copied().collect();
+        let mut csv = String::from("label,query,reciprocal_rank");
+        for k in &cutoffs {
+            let _ = write!(csv, ",recall@{k},ndcg@{k}");
+        }
+        csv.push('\n');
+        for query in &self.queries {
+            let _ = write!(
+                csv,
+                "{},{},{:.4}",
+                csv_field(&self.label),
+                csv_field(&query.query),
+                query.reciprocal_rank
+            );
+            for k in &cutoffs {
+                let _ = write!(
+                    csv,
+                    ",{:.4},{:.4}",
+                    query.recall.get(k).unwrap_or(&0.0),
+                    query.ndcg.get(k).unwrap_or(&0.0)
+                );
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// One CSV row of averaged metrics per report, preceded by a header. All
+/// reports are expected to use the same cutoffs.
+pub fn reports_to_csv(reports: &[EvalReport]) -> String {
+    let cutoffs: Vec<usize> = reports
+        .first()
+        .map(|report| report.recall.keys().copied().collect())
+        .unwrap_or_default();
+    let mut csv = String::from("label,queries,mrr");
+    for k in &cutoffs {
+        let _ = write!(csv, ",recall@{k},ndcg@{k}");
+    }
+    csv.push('\n');
+    for report in reports {
+        let _ = write!(
+            csv,
+            "{},{},{:.4}",
+            csv_field(&report.label),
+            report.queries.len(),
+            report.mrr
+        );
+        for k in &cutoffs {
+            let _ = write!(
+                csv,
+                ",{:.4},{:.4}",
+                report.recall.get(k).unwrap_or(&0.0),
+                report.ndcg.get(k).unwrap_or(&0.0)
+            );
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Scores the ranked document ids returned for each query of `queries`.
+/// `rankings[i]` is the ranking for `queries[i]`, best first; fails with
+/// [`VoyageError::EvalError`] unless there is one ranking per query.
+pub fn evaluate(
+    label: impl Into<String>,
+    queries: &[EvalQuery],
+    rankings: &[Vec<String>],
+    cutoffs: &[usize],
+) -> Result<EvalReport, VoyageError> {
+    if queries.len() != rankings.len() {
+        return Err(VoyageError::EvalError(format!(
+            "{} rankings for {} queries",
+            rankings.len(),
+            queries.len()
+        )));
+    }
+    let per_query: Vec<QueryMetrics> = queries
+        .iter()
+        .zip(rankings)
+        .map(|(query, ranking)| query_metrics(query, ranking, cutoffs))
+        .collect();
+
+    let recall = cutoffs
+        .iter()
+        .map(|k| (*k, mean(per_query.iter().map(|q| q.recall[k]))))
+        .collect();
+    let ndcg = cutoffs
+        .iter()
+        .map(|k| (*k, mean(per_query.iter().map(|q| q.ndcg[k]))))
+        .collect();
+    let mrr = mean(per_query.iter().map(|q| q.reciprocal_rank));
+
+    Ok(EvalReport {
+        label: label.into(),
+        recall,
+        ndcg,
+        mrr,
+        queries: per_query,
+    })
+}
+
+fn mean(values: impl ExactSizeIterator<Item = f64>) -> f64 {
+    let count = values.len();
+    if count == 0 {
+        return 0.0;
+    }
+    values.sum::<f64>() / count as f64
+}
+
+fn query_metrics(query: &EvalQuery, ranking: &[String], cutoffs: &[usize]) -> QueryMetrics {
+    let relevant: HashSet<&str> = query.relevant.iter().map(String::as_str).collect();
+    let hits: Vec<bool> = ranking
+        .iter()
+        .map(|id| relevant.contains(id.as_str()))
+        .collect();
+
+    let mut recall = BTreeMap::new();
+    let mut ndcg = BTreeMap::new();
+    for &k in cutoffs {
+        let found = hits.iter().take(k).filter(|hit| **hit).count();
+        let value = if relevant.is_empty() {
+            0.0
+        } else {
+            found as f64 / relevant.len() as f64
+        };
+        recall.insert(k, value);
+
+        let dcg: f64 = hits
+            .iter()
+            .take(k)
+            .enumerate()
+            .filter(|(_, hit)| **hit)
+            .map(|(rank, _)| 1.0 / (rank as f64 + 2.0).log2())
+            .sum();
+        let ideal: f64 = (0..relevant.len().min(k))
+            .map(|rank| 1.0 / (rank as f64 + 2.0).log2())
+            .sum();
+        ndcg.insert(k, if ideal > 0.0 { dcg / ideal } else { 0.0 });
+    }
+    let reciprocal_rank = hits
+        .iter()
+        .position(|hit| *hit)
+        .map_or(0.0, |rank| 1.0 / (rank as f64 + 1.0));
+
+    QueryMetrics {
+        query: query.query.clone(),
+        recall,
+        ndcg,
+        reciprocal_rank,
+    }
+}
+
+/// Indexes the dataset's documents with `pipeline`, runs every query and
+/// scores the results. Chunks are collapsed to their documents, keeping each
+/// document at the rank of its best chunk.
+///
+/// The pipeline's `top_k` bounds the depth of the rankings, so it should be at
+/// least the largest cutoff.
+pub async fn evaluate_pipeline(
+    label: impl Into<String>,
+    pipeline: &mut RetrievalPipeline,
+    dataset: &EvalDataset,
+    cutoffs: &[usize],
+) -> Result<EvalReport, VoyageError> {
+    Ok(benchmark_pipeline(label, pipeline, dataset, cutoffs)
+        .await?
+        .report)
+}
+
+/// Retrieval quality together with the time and tokens it took.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub report: EvalReport,
+    /// Time to chunk, embed and index the corpus, in milliseconds
+    pub index_ms: f64,
+    /// Mean time per query, in milliseconds
+    pub mean_query_ms: f64,
+    /// 95th percentile time per query, in milliseconds
+    pub p95_query_ms: f64,
+    /// Embedding tokens used for the corpus and the queries
+    pub tokens: u64,
+}
+
+/// Like [`evaluate_pipeline`], also timing indexing and each query and
+/// recording the tokens used.
+pub async fn benchmark_pipeline(
+    label: impl Into<String>,
+    pipeline: &mut RetrievalPipeline,
+    dataset: &EvalDataset,
+    cutoffs: &[usize],
+) -> Result<BenchmarkResult, VoyageError> {
+    let tokens_before = pipeline.tokens_used();
+    let started = Instant::now();
+    pipeline
+        .add_documents(
+            dataset
+                .documents
+                .iter()
+                .map(|document| (document.id.clone(), document.text.as_str())),
+        )
+        .await?;
+    let index_time = started.elapsed();
+
+    let mut rankings = Vec::with_capacity(dataset.queries.len());
+    let mut latencies = Vec::with_capacity(dataset.queries.len());
+    for query in &dataset.queries {
+        let started = Instant::now();
+        let chunks = pipeline.query(&query.query).await?;
+        latencies.push(started.elapsed());
+
+        let mut seen = HashSet::new();
+        let ranking: Vec<String> = chunks
+            .into_iter()
+            .map(|chunk| chunk.document_id)
+            .filter(|id| seen.insert(id.clone()))
+            .collect();
+        rankings.push(ranking);
+    }
+
+    latencies.sort();
+    let p95 = latencies
+        .get((latencies.len() * 95).div_ceil(100).saturating_sub(1))
+        .copied()
+        .unwrap_or_default();
+    Ok(BenchmarkResult {
+        report: evaluate(label, &dataset.queries, &rankings, cutoffs)?,
+        index_ms: millis(index_time),
+        mean_query_ms: mean(latencies.iter().map(|latency| millis(*latency))),
+        p95_query_ms: millis(p95),
+        tokens: pipeline.tokens_used() - tokens_before,
+    })
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Column headers and formatted cells of a benchmark comparison, shared by
+/// the table and CSV layouts.
+fn benchmark_rows(results: &[BenchmarkResult]) -> (Vec<String>, Vec<Vec<String>>) {
+    let cutoffs: Vec<usize> = results
+        .first()
+        .map(|result| result.report.recall.keys().copied().collect())
+        .unwrap_or_default();
+    let mut header = vec!["label".to_string(), "mrr".to_string()];
+    header.extend(cutoffs.iter().map(|k| format!("recall@{k}")));
+    header.extend(cutoffs.iter().map(|k| format!("ndcg@{k}")));
+    header.extend(
+        ["index_ms", "query_ms", "p95_ms", "tokens"]
+            .iter()
+            .map(|column| column.to_string()),
+    );
+
+    let rows = results
+        .iter()
+        .map(|result| {
+            let report = &result.report;
+            let mut row = vec![report.label.clone(), format!("{:.4}", report.mrr)];
+            row.extend(
+                cutoffs
+                    .iter()
+                    .map(|k| format!("{:.4}", report.recall.get(k).unwrap_or(&0.0))),
+            );
+            row.extend(
+                cutoffs
+                    .iter()
+                    .map(|k| format!("{:.4}", report.ndcg.get(k).unwrap_or(&0.0))),
+            );
+            row.push(format!("{:.1}", result.index_ms));
+            row.push(format!("{:.1}", result.mean_query_ms));
+            row.push(format!("{:.1}", result.p95_query_ms));
+            row.push(result.tokens.to_string());
+            row
+        })
+        .collect();
+    (header, rows)
+}
+
+/// A plain-text table with one row per benchmark, labels left-aligned and
+/// numbers right-aligned. All results are expected to use the same cutoffs.
+pub fn benchmarks_to_table(results: &[BenchmarkResult]) -> String {
+    let (header, rows) = benchmark_rows(results);
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([header[column].len()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let mut table = String::new();
+    for (line, cells) in [&header].into_iter().chain(&rows).enumerate() {
+        for (column, cell) in cells.iter().enumerate() {
+            let width = widths[column];
+            if column == 0 {
+                let _ = write!(table, "{cell:<width$}");
+            } else {
+                let _ = write!(table, "  {cell:>width$}");
+            }
+        }
+        table.push('\n');
+        if line == 0 {
+            let total = widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1);
+            table.push_str(&"-".repeat(total));
+            table.push('\n');
+        }
+    }
+    table
+}
+
+/// One CSV row per benchmark, preceded by a header.
+pub fn benchmarks_to_csv(results: &[BenchmarkResult]) -> String {
+    let (header, rows) = benchmark_rows(results);
+    let mut csv = header.join(",");
+    csv.push('\n');
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
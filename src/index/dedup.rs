@@ -0,0 +1,209 @@
+//! Near-duplicate detection for embeddings.
+//!
+//! [`DedupFilter`] remembers embeddings and flags new ones whose cosine
+//! similarity to a remembered one exceeds a threshold. Candidates are found
+//! with random-hyperplane locality-sensitive hashing: each of several hash
+//! tables buckets embeddings by the signs of their projections onto random
+//! hyperplanes, so similar embeddings likely share a bucket in some table.
+//! Only embeddings sharing a bucket are compared exactly, which keeps each
+//! check far below a scan of everything remembered.
+//!
+//! Detection is approximate in one direction: a reported duplicate always
+//! exceeds the threshold, but a pair just above it may occasionally be
+//! missed. More tables raise recall; more bits per table make buckets
+//! smaller and checks faster.
+
+use std::collections::HashMap;
+
+use crate::cosine_similarity;
+use crate::rng::SplitMix64;
+
+/// Default number of hash tables of a [`DedupFilter`].
+pub const DEFAULT_LSH_TABLES: usize = 16;
+
+/// Default number of hyperplanes, i.e. signature bits, per hash table.
+pub const DEFAULT_LSH_BITS: usize = 12;
+
+/// Returns the positions of the embeddings to keep: each embedding is kept
+/// unless its cosine similarity to an earlier kept one exceeds `threshold`.
+pub fn dedupe_by_similarity(embeddings: &[Vec<f32>], threshold: f32) -> Vec<usize> {
+    let mut filter = DedupFilter::new(threshold);
+    embeddings
+        .iter()
+        .enumerate()
+        .filter(|(position, embedding)| filter.admit(position.to_string(), embedding))
+        .map(|(position, _)| position)
+        .collect()
+}
+
+/// Streaming near-duplicate filter, see the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct DedupFilter {
+    threshold: f32,
+    tables: usize,
+    bits: usize,
+    seed: u64,
+    /// `tables * bits` hyperplanes of the embedding dimension, drawn on the
+    /// first insert
+    hyperplanes: Vec<Vec<f32>>,
+    /// Signature → entry positions, per table
+    buckets: Vec<HashMap<u64, Vec<usize>>>,
+    /// Remembered `(id, embedding)` pairs; removed entries are `None`
+    entries: Vec<Option<(String, Vec<f32>)>>,
+    positions: HashMap<String, usize>,
+}
+
+impl DedupFilter {
+    /// A filter flagging embeddings whose cosine similarity to a remembered
+    /// one exceeds `threshold`.
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            tables: DEFAULT_LSH_TABLES,
+            bits: DEFAULT_LSH_BITS,
+            seed: 0x5eed,
+            hyperplanes: Vec::new(),
+            buckets: Vec::new(),
+            entries: Vec::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Sets the number of hash tables and signature bits per table. Ignored
+    /// once embeddings have been inserted.
+    pub fn with_lsh(mut self, tables: usize, bits: usize) -> Self {
+        if self.hyperplanes.is_empty() {
+            self.tables = tables.max(1);
+            self.bits = bits.clamp(1, 64);
+        }
+        self
+    }
+
+    /// Seeds the hyperplanes, making detection deterministic. Ignored once
+    /// embeddings have been inserted.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        if self.hyperplanes.is_empty() {
+            self.seed = seed;
+        }
+        self
+    }
+
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Number of embeddings remembered.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// The id of a remembered embedding whose similarity to `embedding`
+    /// exceeds the threshold, the most similar one if several do.
+    pub fn find_duplicate(&self, embedding: &[f32]) -> Option<&str> {
+        if self.hyperplanes.is_empty() || embedding.len() != self.hyperplanes[0].len() {
+            return None;
+        }
+        let mut best: Option<(usize, f32)> = None;
+        let mut compared = vec![false; self.entries.len()];
+        for (table, signature) in self.signatures(embedding).into_iter().enumerate() {
+            for &position in self.buckets[table].get(&signature).into_iter().flatten() {
+                if std::mem::replace(&mut compared[position], true) {
+                    continue;
+                }
+                let Some((_, candidate)) = &self.entries[position] else {
+                    continue;
+                };
+                let similarity = cosine_similarity(embedding, candidate);
+                if similarity > self.threshold && best.is_none_or(|(_, s)| similarity > s) {
+                    best = Some((position, similarity));
+                }
+            }
+        }
+        let (position, _) = best?;
+        self.entries[position].as_ref().map(|(id, _)| id.as_str())
+    }
+
+    /// Remembers `embedding` under `id`, replacing any embedding with the
+    /// same id.
+    pub fn insert(&mut self, id: impl Into<String>, embedding: &[f32]) {
+        let id = id.into();
+        self.remove(&id);
+        if self.hyperplanes.is_empty() {
+            self.draw_hyperplanes(embedding.len());
+        }
+        if embedding.len() != self.hyperplanes[0].len() {
+            return;
+        }
+        let position = self.entries.len();
+        for (table, signature) in self.signatures(embedding).into_iter().enumerate() {
+            self.buckets[table]
+                .entry(signature)
+                .or_default()
+                .push(position);
+        }
+        self.entries.push(Some((id.clone(), embedding.to_vec())));
+        self.positions.insert(id, position);
+    }
+
+    /// Remembers `embedding` under `id` unless it duplicates a remembered
+    /// embedding. Returns whether it was admitted.
+    pub fn admit(&mut self, id: impl Into<String>, embedding: &[f32]) -> bool {
+        if self.find_duplicate(embedding).is_some() {
+            return false;
+        }
+        self.insert(id, embedding);
+        true
+    }
+
+    /// Forgets the embedding remembered under `id`, returning whether there
+    /// was one.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let Some(position) = self.positions.remove(id) else {
+            return false;
+        };
+        if let Some((_, embedding)) = self.entries[position].take() {
+            for (table, signature) in self.signatures(&embedding).into_iter().enumerate() {
+                if let Some(bucket) = self.buckets[table].get_mut(&signature) {
+                    bucket.retain(|&p| p != position);
+                    if bucket.is_empty() {
+                        self.buckets[table].remove(&signature);
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    fn signatures(&self, embedding: &[f32]) -> Vec<u64> {
+        self.hyperplanes
+            .chunks(self.bits)
+            .map(|planes| {
+                planes
+                    .iter()
+                    .enumerate()
+                    .fold(0u64, |signature, (bit, plane)| {
+                        let projection: f32 = plane.iter().zip(embedding).map(|(p, x)| p * x).sum();
+                        if projection >= 0.0 {
+                            signature | (1 << bit)
+                        } else {
+                            signature
+                        }
+                    })
+            })
+            .collect()
+    }
+
+    /// Draws hyperplane normals from a standard normal distribution, which
+    /// makes their directions uniform.
+    fn draw_hyperplanes(&mut self, dimension: usize) {
+        let mut rng = SplitMix64::new(self.seed);
+        self.hyperplanes = (0..self.tables * self.bits)
+            .map(|_| (0..dimension).map(|_| rng.next_gaussian() as f32).collect())
+            .collect();
+        self.buckets = vec![HashMap::new(); self.tables];
+    }
+}
@@ -0,0 +1,398 @@
+//! Hierarchical Navigable Small World graph for approximate nearest neighbor search.
+//!
+//! Brute-force search compares the query against every stored embedding, which is
+//! exact but linear in the size of the corpus. An HNSW graph links each embedding
+//! to its closest neighbors on several layers of decreasing density; a search
+//! descends greedily from the sparse top layer and only explores the neighborhood
+//! of the query on the bottom layer, visiting a small fraction of the corpus.
+//!
+//! The result is approximate. Recall (the fraction of the true top-k found) and
+//! latency are traded off through [`HnswConfig`]:
+//!
+//! - `m` — links per node. Higher values improve recall, especially for high
+//!   dimensional embeddings, at the cost of memory (`~2·m` ids per node on the
+//!   bottom layer) and slower inserts. 12–48 is the useful range.
+//! - `ef_construction` — candidate list size while inserting. Higher values build
+//!   a better graph (higher recall for every later search) but slow down inserts
+//!   roughly linearly. It should be at least `2·m`.
+//! - `ef_search` — candidate list size while searching, raised to `top_k` when
+//!   smaller. This is the main knob at query time: doubling it roughly doubles
+//!   search latency and closes part of the remaining recall gap.
+//!
+//! With the defaults (`m = 16`, `ef_construction = 200`, `ef_search = 64`), recall@10
+//! on typical embedding corpora is well above 0.9 while visiting only a few
+//! thousand nodes per query, independent of the corpus size.
+//!
+//! The graph is not persisted by [`VectorIndex::save`](crate::VectorIndex::save);
+//! enable it again after loading, which rebuilds it from the stored embeddings.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
+
+use crate::cosine_similarity;
+use crate::rng::SplitMix64;
+
+/// Parameters of an HNSW graph. See the [module documentation](self) for how
+/// they affect recall and latency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HnswConfig {
+    /// Maximum number of links per node on the upper layers; the bottom layer
+    /// allows twice as many
+    pub m: usize,
+    /// Size of the candidate list used while inserting
+    pub ef_construction: usize,
+    /// Size of the candidate list used while searching
+    pub ef_search: usize,
+    /// Seed for level assignment, making graph construction deterministic
+    pub seed: u64,
+}
+
+impl Default for HnswConfig {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 200,
+            ef_search: 64,
+            seed: 0x5eed,
+        }
+    }
+}
+
+impl HnswConfig {
+    pub fn with_m(mut self, m: usize) -> Self {
+        self.m = m.max(2);
+        self
+    }
+
+    pub fn with_ef_construction(mut self, ef_construction: usize) -> Self {
+        self.ef_construction = ef_construction.max(1);
+        self
+    }
+
+    pub fn with_ef_search(mut self, ef_search: usize) -> Self {
+        self.ef_search = ef_search.max(1);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.m * 2
+        } else {
+            self.m
+        }
+    }
+}
+
+/// A node id paired with its distance to the current query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    node: usize,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.node.cmp(&other.node))
+    }
+}
+
+/// Embeddings addressed by node id; node ids are positions in a [`VectorIndex`](crate::VectorIndex).
+#[derive(Clone, Copy)]
+pub(crate) struct Vectors<'a> {
+    pub(crate) values: &'a [f32],
+    pub(crate) dimension: usize,
+}
+
+impl Vectors<'_> {
+    fn get(&self, node: usize) -> &[f32] {
+        let start = node * self.dimension;
+        &self.values[start..start + self.dimension]
+    }
+
+    fn distance(&self, query: &[f32], node: usize) -> f32 {
+        1.0 - cosine_similarity(query, self.get(node))
+    }
+}
+
+/// The graph itself. Node ids always mirror positions in the owning index, so
+/// removing a node renumbers every node after it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct HnswGraph {
+    config: HnswConfig,
+    /// `links[node][layer]` holds the neighbors of `node` on `layer`
+    links: Vec<Vec<Vec<usize>>>,
+    entry_point: Option<usize>,
+    max_layer: usize,
+    rng: SplitMix64,
+}
+
+impl HnswGraph {
+    pub(crate) fn new(config: HnswConfig) -> Self {
+        Self {
+            config,
+            links: Vec::new(),
+            entry_point: None,
+            max_layer: 0,
+            rng: SplitMix64::new(config.seed),
+        }
+    }
+
+    /// Builds a graph over every embedding in `vectors`.
+    pub(crate) fn build(config: HnswConfig, vectors: Vectors<'_>) -> Self {
+        let mut graph = Self::new(config);
+        let count = vectors.values.len() / vectors.dimension.max(1);
+        for _ in 0..count {
+            graph.push(vectors);
+        }
+        graph
+    }
+
+    pub(crate) fn config(&self) -> &HnswConfig {
+        &self.config
+    }
+
+    pub(crate) fn set_ef_search(&mut self, ef_search: usize) {
+        self.config.ef_search = ef_search.max(1);
+    }
+
+    /// Links the embedding appended last to `vectors` into the graph.
+    pub(crate) fn push(&mut self, vectors: Vectors<'_>) {
+        let layer = self.random_layer();
+        self.links.push(vec![Vec::new(); layer + 1]);
+        self.attach(self.links.len() - 1, vectors);
+    }
+
+    /// Relinks a node whose embedding was replaced in place.
+    pub(crate) fn update(&mut self, node: usize, vectors: Vectors<'_>) {
+        self.detach(node, vectors);
+        self.attach(node, vectors);
+    }
+
+    /// Removes a node; every node after it moves down by one.
+    pub(crate) fn remove(&mut self, node: usize, vectors: Vectors<'_>) {
+        self.detach(node, vectors);
+        self.links.remove(node);
+        for layers in &mut self.links {
+            for neighbors in layers.iter_mut() {
+                for neighbor in neighbors.iter_mut() {
+                    if *neighbor > node {
+                        *neighbor -= 1;
+                    }
+                }
+            }
+        }
+        self.entry_point = self
+            .entry_point
+            .map(|entry| if entry > node { entry - 1 } else { entry });
+    }
+
+    /// Returns up to `top_k` `(node, distance)` pairs closest to `query`,
+    /// nearest first.
+    pub(crate) fn search(
+        &self,
+        query: &[f32],
+        top_k: usize,
+        vectors: Vectors<'_>,
+    ) -> Vec<(usize, f32)> {
+        let Some(mut entry) = self.entry_point else {
+            return Vec::new();
+        };
+        for layer in (1..=self.max_layer).rev() {
+            entry = self.greedy_closest(query, entry, layer, vectors);
+        }
+        let ef = self.config.ef_search.max(top_k);
+        self.search_layer(query, &[entry], ef, 0, vectors)
+            .into_iter()
+            .take(top_k)
+            .map(|candidate| (candidate.node, candidate.distance))
+            .collect()
+    }
+
+    fn attach(&mut self, node: usize, vectors: Vectors<'_>) {
+        let layer = self.links[node].len() - 1;
+        let Some(mut entry) = self.entry_point else {
+            self.entry_point = Some(node);
+            self.max_layer = layer;
+            return;
+        };
+
+        let query = vectors.get(node);
+        for current in ((layer + 1)..=self.max_layer).rev() {
+            entry = self.greedy_closest(query, entry, current, vectors);
+        }
+
+        let mut entries = vec![entry];
+        for current in (0..=layer.min(self.max_layer)).rev() {
+            let found = self.search_layer(
+                query,
+                &entries,
+                self.config.ef_construction,
+                current,
+                vectors,
+            );
+            let neighbors: Vec<usize> = found
+                .iter()
+                .map(|candidate| candidate.node)
+                .filter(|&candidate| candidate != node)
+                .take(self.config.max_links(current))
+                .collect();
+            for &neighbor in &neighbors {
+                self.links[neighbor][current].push(node);
+                self.shrink(neighbor, current, vectors);
+            }
+            self.links[node][current] = neighbors;
+            entries = found.into_iter().map(|candidate| candidate.node).collect();
+        }
+
+        if layer > self.max_layer {
+            self.max_layer = layer;
+            self.entry_point = Some(node);
+        }
+    }
+
+    /// Unlinks a node from the graph, leaving an empty slot. Former neighbors
+    /// are reconnected to each other so the graph stays navigable.
+    fn detach(&mut self, node: usize, vectors: Vectors<'_>) {
+        let former: Vec<Vec<usize>> = self.links[node].iter_mut().map(std::mem::take).collect();
+        for layers in &mut self.links {
+            for neighbors in layers.iter_mut() {
+                neighbors.retain(|&neighbor| neighbor != node);
+            }
+        }
+
+        for (layer, neighbors) in former.iter().enumerate() {
+            for &neighbor in neighbors {
+                for &candidate in neighbors {
+                    if candidate != neighbor && !self.links[neighbor][layer].contains(&candidate) {
+                        self.links[neighbor][layer].push(candidate);
+                    }
+                }
+                self.shrink(neighbor, layer, vectors);
+            }
+        }
+
+        if self.entry_point == Some(node) {
+            let replacement = (0..self.links.len())
+                .filter(|&candidate| candidate != node)
+                .max_by(|&a, &b| {
+                    self.links[a]
+                        .len()
+                        .cmp(&self.links[b].len())
+                        .then(b.cmp(&a))
+                });
+            self.entry_point = replacement;
+            self.max_layer = replacement.map_or(0, |entry| self.links[entry].len() - 1);
+        }
+    }
+
+    /// Keeps only the closest neighbors of `node` on `layer`.
+    fn shrink(&mut self, node: usize, layer: usize, vectors: Vectors<'_>) {
+        let max_links = self.config.max_links(layer);
+        if self.links[node][layer].len() <= max_links {
+            return;
+        }
+        let origin = vectors.get(node);
+        let mut scored: Vec<Candidate> = self.links[node][layer]
+            .iter()
+            .map(|&neighbor| Candidate {
+                distance: vectors.distance(origin, neighbor),
+                node: neighbor,
+            })
+            .collect();
+        scored.sort();
+        self.links[node][layer] = scored
+            .into_iter()
+            .take(max_links)
+            .map(|candidate| candidate.node)
+            .collect();
+    }
+
+    fn greedy_closest(
+        &self,
+        query: &[f32],
+        entry: usize,
+        layer: usize,
+        vectors: Vectors<'_>,
+    ) -> usize {
+        self.search_layer(query, &[entry], 1, layer, vectors)
+            .first()
+            .map_or(entry, |candidate| candidate.node)
+    }
+
+    /// Best-first search of one layer, returning up to `ef` candidates nearest first.
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entries: &[usize],
+        ef: usize,
+        layer: usize,
+        vectors: Vectors<'_>,
+    ) -> Vec<Candidate> {
+        let mut visited: HashSet<usize> = entries.iter().copied().collect();
+        let mut candidates: BinaryHeap<Reverse<Candidate>> = BinaryHeap::new();
+        let mut found: BinaryHeap<Candidate> = BinaryHeap::new();
+        for &node in entries {
+            let candidate = Candidate {
+                distance: vectors.distance(query, node),
+                node,
+            };
+            candidates.push(Reverse(candidate));
+            found.push(candidate);
+        }
+        while found.len() > ef {
+            found.pop();
+        }
+
+        while let Some(Reverse(current)) = candidates.pop() {
+            if found.len() >= ef
+                && found
+                    .peek()
+                    .is_some_and(|worst| current.distance > worst.distance)
+            {
+                break;
+            }
+            let Some(neighbors) = self.links[current.node].get(layer) else {
+                continue;
+            };
+            for &neighbor in neighbors {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let candidate = Candidate {
+                    distance: vectors.distance(query, neighbor),
+                    node: neighbor,
+                };
+                if found.len() < ef || found.peek().is_some_and(|worst| candidate < *worst) {
+                    candidates.push(Reverse(candidate));
+                    found.push(candidate);
+                    if found.len() > ef {
+                        found.pop();
+                    }
+                }
+            }
+        }
+        found.into_sorted_vec()
+    }
+
+    /// Draws a layer from the exponential distribution `floor(-ln(u) / ln(m))`.
+    fn random_layer(&mut self) -> usize {
+        let uniform = self.rng.next_f64();
+        let level_multiplier = 1.0 / (self.config.m.max(2) as f64).ln();
+        (-uniform.ln() * level_multiplier).floor() as usize
+    }
+}
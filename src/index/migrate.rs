@@ -0,0 +1,83 @@
+//! Re-embedding an index with another model.
+//!
+//! Embeddings of two models cannot be compared, so upgrading the model of an
+//! index means embedding every document again. [`VectorIndex::migrate`] sends
+//! the stored texts through a [`BulkEmbedder`] and builds a new index of the
+//! same documents, ids, metadata and expiries, in the same order, recorded
+//! with the new model; deleted documents are left behind. The original index
+//! is left untouched, so a failed migration loses nothing.
+//!
+//! [`VectorIndex::migrate_with`] takes a configured embedder instead, e.g. to
+//! report progress with
+//! [`with_progress_callback`](BulkEmbedder::with_progress_callback) or to
+//! change the batch size and concurrency.
+
+use std::collections::HashMap;
+
+use tokio_stream::StreamExt;
+
+use crate::client::bulk_embedder::{BulkDocument, BulkEmbedder};
+use crate::client::voyage_client::VoyageAiClient;
+use crate::errors::VoyageError;
+use crate::index::vector_index::VectorIndex;
+use crate::models::embeddings::EmbeddingModel;
+
+impl VectorIndex {
+    /// A copy of the index with every document embedded again by `to_model`.
+    pub async fn migrate(
+        &self,
+        to_model: EmbeddingModel,
+        client: &VoyageAiClient,
+    ) -> Result<VectorIndex, VoyageError> {
+        self.migrate_with(to_model, BulkEmbedder::new(client)).await
+    }
+
+    /// Like [`migrate`](Self::migrate), embedding with `embedder`, whose
+    /// model is replaced by `to_model`. The embedder must not have a
+    /// checkpoint: documents it skips would be missing from the new index.
+    ///
+    /// Fails with [`VoyageError::MigrationIncomplete`] if any batch failed.
+    pub async fn migrate_with(
+        &self,
+        to_model: EmbeddingModel,
+        embedder: BulkEmbedder,
+    ) -> Result<VectorIndex, VoyageError> {
+        let documents: Vec<BulkDocument> = self
+            .iter()
+            .map(|(record, _)| BulkDocument::new(record.id.clone(), record.text.clone()))
+            .collect();
+        let mut stream = embedder
+            .with_model(to_model)
+            .with_total(documents.len())
+            .embed_all(documents)?;
+        let mut embeddings = HashMap::with_capacity(self.len());
+        while let Some(embedded) = stream.next().await {
+            embeddings.insert(embedded.id, embedded.embedding);
+        }
+        if embeddings.len() < self.len() {
+            return Err(VoyageError::MigrationIncomplete {
+                embedded: embeddings.len(),
+                total: self.len(),
+            });
+        }
+
+        let mut migrated = VectorIndex::new().with_model(to_model.to_string());
+        if let Some(config) = self.hnsw_config() {
+            migrated = migrated.with_hnsw(*config);
+        }
+        if let Some(adapter) = self.dimension_adapter() {
+            migrated = migrated.with_dimension_adapter(adapter);
+        }
+        for (record, _) in self.iter() {
+            let embedding = embeddings.remove(&record.id).unwrap_or_default();
+            migrated.insert(
+                record.id.clone(),
+                record.text.clone(),
+                embedding,
+                record.metadata.clone(),
+            )?;
+            migrated.set_expiry(&record.id, record.expires_at);
+        }
+        Ok(migrated)
+    }
+}
@@ -0,0 +1,18 @@
+pub mod dedup;
+pub mod hnsw;
+pub mod migrate;
+pub mod namespaced;
+pub mod persist;
+pub mod projection;
+pub mod shared;
+pub mod store;
+pub mod vector_index;
+
+pub use dedup::{dedupe_by_similarity, DedupFilter};
+pub use hnsw::HnswConfig;
+pub use namespaced::{NamespaceStats, NamespacedIndex};
+pub use persist::IndexInfo;
+pub use projection::DimensionAdapter;
+pub use shared::SharedIndex;
+pub use store::{CollectionConfig, ExpiryHandle, StoreMatch, VectorStore};
+pub use vector_index::{IndexMatch, IndexRecord, VectorIndex};
@@ -0,0 +1,414 @@
+//! Isolated per-tenant corpora in one index.
+//!
+//! A [`NamespacedIndex`] holds a separate [`VectorIndex`] per namespace, e.g.
+//! one per customer. Every insert names the namespace the document belongs
+//! to and every search the namespace it looks in, so a query can never
+//! return another tenant's documents, not even through the HNSW graph, which
+//! is built per namespace. Namespaces are created by their first insert and
+//! deleted with all their documents by
+//! [`drop_namespace`](NamespacedIndex::drop_namespace);
+//! [`stats`](NamespacedIndex::stats) describes one of them.
+//!
+//! The whole index is saved to a single file: a header followed by each
+//! namespace's name and its index in the [`VectorIndex`] format.
+//!
+//! | size | content                                           |
+//! |------|---------------------------------------------------|
+//! | 8    | magic bytes `VOYAGENS`                            |
+//! | 4    | format version                                    |
+//! | 4    | namespace count                                   |
+//! | 4    | length of the bincode section                     |
+//! | k    | bincode-encoded crate version and model name      |
+//! | 4    | length of the first namespace name                |
+//! | n    | the name in UTF-8                                 |
+//! | 8    | length of its index                               |
+//! | m    | the index as written by [`VectorIndex::write_to`] |
+//! | ...  | the other namespaces, sorted by name              |
+//!
+//! Version 1 files have no bincode section; the model of their namespaces
+//! becomes the index's when they all agree on one. Every later version keeps
+//! the crate version first in that section, so that a file written by a
+//! newer release is refused naming the release to upgrade to.
+//!
+//! ```
+//! use voyageai::index::NamespacedIndex;
+//! let mut index = NamespacedIndex::new();
+//! index.insert("acme", "1", "invoice terms", vec![1.0, 0.0], None).unwrap();
+//! index.insert("globex", "1", "invoice terms", vec![0.0, 1.0], None).unwrap();
+//! let matches = index.search("acme", &[1.0, 0.0], 10, None).unwrap();
+//! assert_eq!(matches.len(), 1);
+//! assert!(index.search("initech", &[1.0, 0.0], 10, None).is_err());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::VoyageError;
+use crate::index::hnsw::HnswConfig;
+use crate::index::persist::tmp_path;
+use crate::index::vector_index::{IndexMatch, IndexRecord, VectorIndex};
+use crate::models::embedding::Embedding;
+use crate::models::filter::MetadataFilter;
+use crate::models::metadata::Metadata;
+
+pub(crate) const NAMESPACED_MAGIC: &[u8; 8] = b"VOYAGENS";
+const FORMAT_VERSION: u32 = 2;
+const HEADER_LEN: usize = 16;
+const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The bincode section following the header since format version 2.
+#[derive(Serialize, Deserialize)]
+struct StoredHeader {
+    crate_version: String,
+    model: Option<String>,
+}
+
+/// Describes one namespace of a [`NamespacedIndex`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceStats {
+    pub documents: usize,
+    /// Deleted documents not compacted yet
+    pub tombstoned: usize,
+    pub dimension: usize,
+    /// Model recorded for the namespace's embeddings, if any
+    pub model: Option<String>,
+}
+
+/// Vector indexes of several tenants, kept apart by namespace.
+///
+/// Namespaces are shared with [`Arc`] and copied on write, so handing one to
+/// a [`SearchRequest`](crate::client::SearchRequest) is cheap.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NamespacedIndex {
+    namespaces: BTreeMap<String, Arc<VectorIndex>>,
+    hnsw: Option<HnswConfig>,
+    model: Option<String>,
+}
+
+impl NamespacedIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Searches every namespace, existing and future, with an HNSW graph.
+    pub fn with_hnsw(mut self, config: HnswConfig) -> Self {
+        for index in self.namespaces.values_mut() {
+            *index = Arc::new(Arc::unwrap_or_clone(std::mem::take(index)).with_hnsw(config));
+        }
+        self.hnsw = Some(config);
+        self
+    }
+
+    /// Records `model` with the namespaces created from now on.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Names of all namespaces, sorted.
+    pub fn namespaces(&self) -> impl Iterator<Item = &str> {
+        self.namespaces.keys().map(String::as_str)
+    }
+
+    pub fn contains_namespace(&self, namespace: &str) -> bool {
+        self.namespaces.contains_key(namespace)
+    }
+
+    /// Number of documents over all namespaces.
+    pub fn len(&self) -> usize {
+        self.namespaces.values().map(|index| index.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.namespaces.values().all(|index| index.is_empty())
+    }
+
+    /// Describes `namespace`, if it exists.
+    pub fn stats(&self, namespace: &str) -> Option<NamespaceStats> {
+        self.namespaces.get(namespace).map(|index| NamespaceStats {
+            documents: index.len(),
+            tombstoned: index.tombstoned(),
+            dimension: index.dimension(),
+            model: index.model().map(str::to_string),
+        })
+    }
+
+    /// Inserts a document into `namespace`, creating the namespace if needed
+    /// and replacing any document of the namespace with the same id.
+    pub fn insert(
+        &mut self,
+        namespace: &str,
+        id: impl Into<String>,
+        text: impl Into<String>,
+        embedding: Vec<f32>,
+        metadata: Option<Metadata>,
+    ) -> Result<(), VoyageError> {
+        Arc::make_mut(self.namespace_mut(namespace)?).insert(id, text, embedding, metadata)
+    }
+
+    /// Inserts a document like [`insert`](Self::insert) that expires at
+    /// `expires_at`.
+    pub fn insert_expiring(
+        &mut self,
+        namespace: &str,
+        id: impl Into<String>,
+        text: impl Into<String>,
+        embedding: Vec<f32>,
+        metadata: Option<Metadata>,
+        expires_at: SystemTime,
+    ) -> Result<(), VoyageError> {
+        Arc::make_mut(self.namespace_mut(namespace)?)
+            .insert_expiring(id, text, embedding, metadata, expires_at)
+    }
+
+    /// Inserts a document like [`VectorIndex::insert_embedding`], refusing an
+    /// embedding of another model than the namespace's.
+    pub fn insert_embedding(
+        &mut self,
+        namespace: &str,
+        id: impl Into<String>,
+        text: impl Into<String>,
+        embedding: Embedding,
+        metadata: Option<Metadata>,
+    ) -> Result<(), VoyageError> {
+        Arc::make_mut(self.namespace_mut(namespace)?)
+            .insert_embedding(id, text, embedding, metadata)
+    }
+
+    /// Removes a document of `namespace`, returning its record and embedding.
+    pub fn remove(&mut self, namespace: &str, id: &str) -> Option<(IndexRecord, Vec<f32>)> {
+        Arc::make_mut(self.namespaces.get_mut(namespace)?).remove(id)
+    }
+
+    /// Deletes a document of `namespace` like [`VectorIndex::delete`].
+    pub fn delete(&mut self, namespace: &str, id: &str) -> bool {
+        self.namespaces
+            .get_mut(namespace)
+            .is_some_and(|index| index.position(id).is_some() && Arc::make_mut(index).delete(id))
+    }
+
+    /// Deletes the documents of `namespace` whose metadata matches `filter`
+    /// and returns how many were deleted.
+    pub fn delete_where(&mut self, namespace: &str, filter: &MetadataFilter) -> usize {
+        self.namespaces
+            .get_mut(namespace)
+            .map_or(0, |index| Arc::make_mut(index).delete_where(filter))
+    }
+
+    /// Drops the deleted documents of every namespace and returns how many
+    /// were dropped.
+    pub fn compact(&mut self) -> usize {
+        self.namespaces
+            .values_mut()
+            .filter(|index| index.tombstoned() > 0)
+            .map(|index| Arc::make_mut(index).compact())
+            .sum()
+    }
+
+    /// Removes the expired documents of every namespace and returns how many
+    /// were removed. Namespaces without expired documents are not copied.
+    pub fn evict_expired(&mut self) -> usize {
+        let now = SystemTime::now();
+        self.namespaces
+            .values_mut()
+            .filter(|index| {
+                index
+                    .records()
+                    .iter()
+                    .any(|record| record.is_expired_at(now))
+            })
+            .map(|index| Arc::make_mut(index).evict_expired_at(now))
+            .sum()
+    }
+
+    /// Deletes `namespace` and all of its documents, returning its index.
+    pub fn drop_namespace(&mut self, namespace: &str) -> Option<Arc<VectorIndex>> {
+        self.namespaces.remove(namespace)
+    }
+
+    /// The `top_k` documents of `namespace` most similar to `query`; fails
+    /// with [`VoyageError::NamespaceNotFound`] for an unknown namespace.
+    pub fn search(
+        &self,
+        namespace: &str,
+        query: &[f32],
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<IndexMatch<'_>>, VoyageError> {
+        self.namespace(namespace)?.search(query, top_k, filter)
+    }
+
+    /// Searches like [`search`](Self::search), refusing a query embedded by
+    /// another model than the namespace's.
+    pub fn search_embedding(
+        &self,
+        namespace: &str,
+        query: &Embedding,
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<IndexMatch<'_>>, VoyageError> {
+        self.namespace(namespace)?
+            .search_embedding(query, top_k, filter)
+    }
+
+    /// The index of `namespace`, failing for a missing or unknown one.
+    pub fn namespace(&self, namespace: &str) -> Result<&Arc<VectorIndex>, VoyageError> {
+        if namespace.is_empty() {
+            return Err(VoyageError::NamespaceRequired);
+        }
+        self.namespaces
+            .get(namespace)
+            .ok_or_else(|| VoyageError::NamespaceNotFound(namespace.to_string()))
+    }
+
+    fn namespace_mut(&mut self, namespace: &str) -> Result<&mut Arc<VectorIndex>, VoyageError> {
+        if namespace.is_empty() {
+            return Err(VoyageError::NamespaceRequired);
+        }
+        let (hnsw, model) = (self.hnsw, self.model.clone());
+        Ok(self
+            .namespaces
+            .entry(namespace.to_string())
+            .or_insert_with(|| {
+                let mut index = VectorIndex::new();
+                if let Some(config) = hnsw {
+                    index = index.with_hnsw(config);
+                }
+                index.set_model(model);
+                Arc::new(index)
+            }))
+    }
+
+    /// Writes every namespace to `path`, replacing any existing file
+    /// atomically.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), VoyageError> {
+        let path = path.as_ref();
+        let io_error = |path: &Path, e: std::io::Error| {
+            VoyageError::IndexIoError(format!("{}: {}", path.display(), e))
+        };
+        let tmp_path = tmp_path(path);
+        let file = File::create(&tmp_path).map_err(|e| io_error(&tmp_path, e))?;
+        let mut writer = BufWriter::new(file);
+        self.write_to(&mut writer)
+            .and_then(|_| writer.flush())
+            .map_err(|e| io_error(&tmp_path, e))?;
+        drop(writer);
+        fs::rename(&tmp_path, path).map_err(|e| io_error(path, e))
+    }
+
+    /// Loads an index previously written by [`NamespacedIndex::save`]. HNSW
+    /// graphs are not saved; enable them again with
+    /// [`with_hnsw`](Self::with_hnsw).
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, VoyageError> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|e| VoyageError::IndexIoError(format!("{}: {}", path.display(), e)))?;
+        // SAFETY: as in `VectorIndex::load`
+        let mmap = unsafe { Mmap::map(&file) }
+            .map_err(|e| VoyageError::IndexIoError(format!("{}: {}", path.display(), e)))?;
+        Self::from_bytes(&mmap)
+    }
+
+    /// Serializes every namespace into the format described in this module.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(NAMESPACED_MAGIC)?;
+        writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&(self.namespaces.len() as u32).to_le_bytes())?;
+        let header = bincode::serialize(&StoredHeader {
+            crate_version: CRATE_VERSION.to_string(),
+            model: self.model.clone(),
+        })
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        writer.write_all(&(header.len() as u32).to_le_bytes())?;
+        writer.write_all(&header)?;
+        for (name, index) in &self.namespaces {
+            let mut encoded = Vec::new();
+            index.write_to(&mut encoded)?;
+            writer.write_all(&(name.len() as u32).to_le_bytes())?;
+            writer.write_all(name.as_bytes())?;
+            writer.write_all(&(encoded.len() as u64).to_le_bytes())?;
+            writer.write_all(&encoded)?;
+        }
+        Ok(())
+    }
+
+    /// Deserializes an index from the format described in this module.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VoyageError> {
+        let format_error = |message: &str| VoyageError::IndexFormatError(message.to_string());
+        if bytes.len() < HEADER_LEN || &bytes[..8] != NAMESPACED_MAGIC {
+            return Err(format_error("not a voyageai namespaced index file"));
+        }
+        let version = u32::from_le_bytes(bytes[8..12].try_into().unwrap_or_default());
+        if version > FORMAT_VERSION {
+            return Err(VoyageError::IndexVersionUnsupported {
+                version,
+                supported: FORMAT_VERSION,
+                written_by: bytes
+                    .get(HEADER_LEN + 4..)
+                    .and_then(|section| bincode::deserialize(section).ok()),
+            });
+        }
+        if version == 0 {
+            return Err(format_error("unsupported format version 0"));
+        }
+        let count = u32::from_le_bytes(bytes[12..16].try_into().unwrap_or_default());
+
+        let mut offset = HEADER_LEN;
+        let mut take = |len: usize| {
+            let end = offset
+                .checked_add(len)
+                .filter(|end| *end <= bytes.len())
+                .ok_or_else(|| format_error("truncated namespace section"))?;
+            let taken = &bytes[offset..end];
+            offset = end;
+            Ok::<_, VoyageError>(taken)
+        };
+        let header = match version {
+            1 => None,
+            _ => {
+                let header_len = u32::from_le_bytes(take(4)?.try_into().unwrap_or_default());
+                let header: StoredHeader = bincode::deserialize(take(header_len as usize)?)
+                    .map_err(|e| VoyageError::IndexFormatError(e.to_string()))?;
+                Some(header)
+            }
+        };
+        let mut namespaces = BTreeMap::new();
+        for _ in 0..count {
+            let name_len = u32::from_le_bytes(take(4)?.try_into().unwrap_or_default());
+            let name = std::str::from_utf8(take(name_len as usize)?)
+                .map_err(|_| format_error("namespace name is not UTF-8"))?
+                .to_string();
+            let index_len = u64::from_le_bytes(take(8)?.try_into().unwrap_or_default());
+            let index = VectorIndex::from_bytes(take(index_len as usize)?)?;
+            namespaces.insert(name, Arc::new(index));
+        }
+        if offset != bytes.len() {
+            return Err(format_error("trailing bytes after the last namespace"));
+        }
+        let model = match header {
+            Some(header) => header.model,
+            None => {
+                // Version 1 did not save the model; keep the one its
+                // namespaces share, if any
+                let mut models = namespaces.values().map(|index| index.model());
+                let first = models.next().flatten();
+                models
+                    .all(|model| model == first)
+                    .then(|| first.map(str::to_string))
+                    .flatten()
+            }
+        };
+        Ok(Self {
+            namespaces,
+            hnsw: None,
+            model,
+        })
+    }
+}
@@ -0,0 +1,453 @@
+//! Binary on-disk format for [`VectorIndex`].
+//!
+//! Layout (all integers little-endian):
+//!
+//! | offset | size  | content                                               |
+//! |--------|-------|-------------------------------------------------------|
+//! | 0      | 8     | magic bytes `VOYAGEIX`                                |
+//! | 8      | 4     | format version                                        |
+//! | 12     | 4     | embedding dimension                                   |
+//! | 16     | 8     | record count                                          |
+//! | 24     | 8     | length of the bincode record section                  |
+//! | 32     | n     | bincode-encoded crate version, model name, records    |
+//! |        |       | and ids of the deleted records                        |
+//! | ...    | 0-3   | zero padding to a 4-byte boundary                     |
+//! | ...    | 4·d·c | embeddings as contiguous `f32` values                 |
+//!
+//! Records are written in insertion order and metadata keys are sorted, so the
+//! same index always produces byte-identical files. Loading memory-maps the file
+//! and copies the embedding section in one pass.
+//!
+//! Files of older format versions are upgraded when loaded, and written in the
+//! current format by the next save. Each version adds to the record section:
+//!
+//! - v1: the records only.
+//! - v2: the model name.
+//! - v3: the crate version, first in the section.
+//! - v4: document expiry.
+//! - v5: ids of the deleted records.
+//!
+//! The header layout and the crate version at the start of
+//! the record section are kept by every later version, so that a file written
+//! by a newer release is refused with
+//! [`VoyageError::IndexVersionUnsupported`], naming the release to upgrade
+//! to, instead of being decoded as garbage. [`VectorIndex::inspect`] reads
+//! what a file says about itself without loading its embeddings.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use log::debug;
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::VoyageError;
+use crate::index::namespaced::NAMESPACED_MAGIC;
+use crate::index::vector_index::{IndexRecord, VectorIndex};
+use crate::models::metadata::{Metadata, MetadataValue};
+
+const MAGIC: &[u8; 8] = b"VOYAGEIX";
+const FORMAT_VERSION: u32 = 5;
+const HEADER_LEN: usize = 32;
+const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// What a saved index says about itself, read by [`VectorIndex::inspect`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexInfo {
+    /// Format version of the file
+    pub format_version: u32,
+    pub dimension: usize,
+    /// Number of stored documents, deleted ones included
+    pub count: usize,
+    /// Model that produced the embeddings, if recorded
+    pub model: Option<String>,
+    /// Version of the crate that wrote the file, recorded since format
+    /// version 3
+    pub crate_version: Option<String>,
+}
+
+impl IndexInfo {
+    /// Whether the file is in the format [`VectorIndex::save`] writes. Older
+    /// files load all the same.
+    pub fn is_current(&self) -> bool {
+        self.format_version == FORMAT_VERSION
+    }
+}
+
+/// Externally tagged mirror of [`MetadataValue`], which bincode can decode.
+#[derive(Serialize, Deserialize)]
+enum StoredValue {
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+/// The record section of a version 5 file.
+#[derive(Serialize, Deserialize)]
+struct StoredIndex {
+    crate_version: String,
+    model: Option<String>,
+    records: Vec<StoredRecord>,
+    /// Ids of the deleted records, in record order
+    tombstones: Vec<String>,
+}
+
+/// The record section of a version 4 file.
+#[derive(Deserialize)]
+struct StoredIndexV4 {
+    #[allow(dead_code)]
+    crate_version: String,
+    model: Option<String>,
+    records: Vec<StoredRecord>,
+}
+
+/// The record section of a version 3 file.
+#[derive(Deserialize)]
+struct StoredIndexV3 {
+    #[allow(dead_code)]
+    crate_version: String,
+    model: Option<String>,
+    records: Vec<StoredRecordV3>,
+}
+
+/// The record section of a version 2 file.
+#[derive(Deserialize)]
+struct StoredIndexV2 {
+    model: Option<String>,
+    records: Vec<StoredRecordV3>,
+}
+
+/// The fields of a record section before the records.
+#[derive(Deserialize)]
+struct StoredPrefix {
+    crate_version: String,
+    model: Option<String>,
+}
+
+/// The fixed-size header of a file.
+struct Header {
+    version: u32,
+    dimension: usize,
+    count: usize,
+    /// Offset of the end of the record section
+    records_end: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredRecord {
+    id: String,
+    text: String,
+    metadata: Option<Vec<(String, StoredValue)>>,
+    /// Seconds and nanoseconds since the Unix epoch
+    expires_at: Option<(u64, u32)>,
+}
+
+/// A record of a version 1 to 3 file, which cannot expire.
+#[derive(Deserialize)]
+struct StoredRecordV3 {
+    id: String,
+    text: String,
+    metadata: Option<Vec<(String, StoredValue)>>,
+}
+
+impl From<StoredRecordV3> for StoredRecord {
+    fn from(record: StoredRecordV3) -> Self {
+        Self {
+            id: record.id,
+            text: record.text,
+            metadata: record.metadata,
+            expires_at: None,
+        }
+    }
+}
+
+impl From<&IndexRecord> for StoredRecord {
+    fn from(record: &IndexRecord) -> Self {
+        let metadata = record.metadata.as_ref().map(|metadata| {
+            let mut entries: Vec<(String, StoredValue)> = metadata
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        MetadataValue::Bool(value) => StoredValue::Bool(*value),
+                        MetadataValue::Number(value) => StoredValue::Number(*value),
+                        MetadataValue::String(value) => StoredValue::String(value.clone()),
+                    };
+                    (key.clone(), value)
+                })
+                .collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            entries
+        });
+        // Expiries before the epoch are stored as the epoch, which has passed
+        // all the same
+        let expires_at = record.expires_at.map(|expires_at| {
+            let since_epoch = expires_at.duration_since(UNIX_EPOCH).unwrap_or_default();
+            (since_epoch.as_secs(), since_epoch.subsec_nanos())
+        });
+        Self {
+            id: record.id.clone(),
+            text: record.text.clone(),
+            metadata,
+            expires_at,
+        }
+    }
+}
+
+impl TryFrom<StoredRecord> for IndexRecord {
+    type Error = VoyageError;
+
+    fn try_from(record: StoredRecord) -> Result<Self, VoyageError> {
+        let metadata = record.metadata.map(|entries| {
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        StoredValue::Bool(value) => MetadataValue::Bool(value),
+                        StoredValue::Number(value) => MetadataValue::Number(value),
+                        StoredValue::String(value) => MetadataValue::String(value),
+                    };
+                    (key, value)
+                })
+                .collect::<Metadata>()
+        });
+        let expires_at = record
+            .expires_at
+            .map(|(secs, nanos)| {
+                Duration::from_secs(secs)
+                    .checked_add(Duration::from_nanos(nanos.into()))
+                    .and_then(|since_epoch| UNIX_EPOCH.checked_add(since_epoch))
+                    .ok_or_else(|| format_error("record expiry out of range"))
+            })
+            .transpose()?;
+        Ok(Self {
+            id: record.id,
+            text: record.text,
+            metadata,
+            expires_at,
+        })
+    }
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> VoyageError {
+    VoyageError::IndexIoError(format!("{}: {}", path.display(), error))
+}
+
+/// The file a save writes before renaming it to `path`: `path` with `.tmp`
+/// appended, so that files differing only by extension do not share it.
+pub(crate) fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    PathBuf::from(tmp_path)
+}
+
+fn padding(offset: usize) -> usize {
+    (4 - offset % 4) % 4
+}
+
+fn format_error(message: &str) -> VoyageError {
+    VoyageError::IndexFormatError(message.to_string())
+}
+
+fn decode_error(error: bincode::Error) -> VoyageError {
+    VoyageError::IndexFormatError(error.to_string())
+}
+
+/// Reads the header of `bytes`, refusing files that are not indexes or that
+/// were written in a newer format.
+fn read_header(bytes: &[u8]) -> Result<Header, VoyageError> {
+    if bytes.starts_with(NAMESPACED_MAGIC) {
+        return Err(format_error(
+            "a namespaced index file; load it with NamespacedIndex::load",
+        ));
+    }
+    if bytes.len() < HEADER_LEN || &bytes[..8] != MAGIC {
+        return Err(format_error("not a voyageai index file"));
+    }
+    let read_u32 = |offset: usize| {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap_or_default())
+    };
+    let read_u64 = |offset: usize| {
+        u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap_or_default())
+    };
+
+    let version = read_u32(8);
+    if version > FORMAT_VERSION {
+        return Err(VoyageError::IndexVersionUnsupported {
+            version,
+            supported: FORMAT_VERSION,
+            written_by: bincode::deserialize(&bytes[HEADER_LEN..]).ok(),
+        });
+    }
+    if version == 0 {
+        return Err(format_error("unsupported format version 0"));
+    }
+    let records_end = HEADER_LEN
+        .checked_add(read_u64(24) as usize)
+        .filter(|end| *end <= bytes.len())
+        .ok_or_else(|| format_error("truncated record section"))?;
+    Ok(Header {
+        version,
+        dimension: read_u32(12) as usize,
+        count: read_u64(16) as usize,
+        records_end,
+    })
+}
+
+impl VectorIndex {
+    /// Writes the index to `path`, replacing any existing file atomically.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), VoyageError> {
+        let path = path.as_ref();
+        let tmp_path = tmp_path(path);
+        let file = File::create(&tmp_path).map_err(|e| io_error(&tmp_path, e))?;
+        let mut writer = BufWriter::new(file);
+        self.write_to(&mut writer)
+            .and_then(|_| writer.flush())
+            .map_err(|e| io_error(&tmp_path, e))?;
+        drop(writer);
+        fs::rename(&tmp_path, path).map_err(|e| io_error(path, e))
+    }
+
+    /// Loads an index previously written by [`VectorIndex::save`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, VoyageError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| io_error(path, e))?;
+        // SAFETY: the mapping is read-only and dropped before returning; callers
+        // must not truncate the file while it is being loaded.
+        let mmap = unsafe { Mmap::map(&file) }.map_err(|e| io_error(path, e))?;
+        Self::from_bytes(&mmap)
+    }
+
+    /// Reads the header of the index saved at `path`, with its model and the
+    /// crate version that wrote it, without loading the documents.
+    pub fn inspect(path: impl AsRef<Path>) -> Result<IndexInfo, VoyageError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| io_error(path, e))?;
+        // SAFETY: as in `load`
+        let mmap = unsafe { Mmap::map(&file) }.map_err(|e| io_error(path, e))?;
+        Self::inspect_bytes(&mmap)
+    }
+
+    /// Like [`inspect`](Self::inspect), for an index in memory.
+    pub fn inspect_bytes(bytes: &[u8]) -> Result<IndexInfo, VoyageError> {
+        let header = read_header(bytes)?;
+        let section = &bytes[HEADER_LEN..header.records_end];
+        let (model, crate_version) = match header.version {
+            1 => (None, None),
+            2 => (bincode::deserialize(section).map_err(decode_error)?, None),
+            _ => {
+                let prefix: StoredPrefix = bincode::deserialize(section).map_err(decode_error)?;
+                (prefix.model, Some(prefix.crate_version))
+            }
+        };
+        Ok(IndexInfo {
+            format_version: header.version,
+            dimension: header.dimension,
+            count: header.count,
+            model,
+            crate_version,
+        })
+    }
+
+    /// Serializes the index into the binary format described in this module.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let stored = StoredIndex {
+            crate_version: CRATE_VERSION.to_string(),
+            model: self.model().map(str::to_string),
+            records: self.records().iter().map(StoredRecord::from).collect(),
+            tombstones: self.tombstoned_ids().map(str::to_string).collect(),
+        };
+        let encoded = bincode::serialize(&stored)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&(self.dimension() as u32).to_le_bytes())?;
+        writer.write_all(&(stored.records.len() as u64).to_le_bytes())?;
+        writer.write_all(&(encoded.len() as u64).to_le_bytes())?;
+        writer.write_all(&encoded)?;
+        writer.write_all(&[0u8; 3][..padding(HEADER_LEN + encoded.len())])?;
+        for value in self.vectors() {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Deserializes an index from the binary format described in this module,
+    /// upgrading older format versions.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VoyageError> {
+        let Header {
+            version,
+            dimension,
+            count,
+            records_end,
+        } = read_header(bytes)?;
+        let section = &bytes[HEADER_LEN..records_end];
+        let legacy = |records: Vec<StoredRecordV3>| -> Vec<StoredRecord> {
+            records.into_iter().map(StoredRecord::from).collect()
+        };
+        let (model, records, tombstones): (Option<String>, Vec<StoredRecord>, Vec<String>) =
+            match version {
+                1 => (
+                    None,
+                    legacy(bincode::deserialize(section).map_err(decode_error)?),
+                    Vec::new(),
+                ),
+                2 => {
+                    let stored: StoredIndexV2 =
+                        bincode::deserialize(section).map_err(decode_error)?;
+                    (stored.model, legacy(stored.records), Vec::new())
+                }
+                3 => {
+                    let stored: StoredIndexV3 =
+                        bincode::deserialize(section).map_err(decode_error)?;
+                    (stored.model, legacy(stored.records), Vec::new())
+                }
+                4 => {
+                    let stored: StoredIndexV4 =
+                        bincode::deserialize(section).map_err(decode_error)?;
+                    (stored.model, stored.records, Vec::new())
+                }
+                _ => {
+                    let stored: StoredIndex =
+                        bincode::deserialize(section).map_err(decode_error)?;
+                    (stored.model, stored.records, stored.tombstones)
+                }
+            };
+        if records.len() != count {
+            return Err(format_error("record count does not match header"));
+        }
+        if version < FORMAT_VERSION {
+            debug!(
+                "Upgrading index format version {} to {}",
+                version, FORMAT_VERSION
+            );
+        }
+
+        let vectors_start = records_end + padding(records_end);
+        let vectors_len = count
+            .checked_mul(dimension)
+            .and_then(|values| values.checked_mul(4))
+            .ok_or_else(|| format_error("vector section too large"))?;
+        if vectors_start.checked_add(vectors_len) != Some(bytes.len()) {
+            return Err(format_error("vector section length does not match header"));
+        }
+        let vectors = bytes[vectors_start..]
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+
+        let mut index = VectorIndex::from_parts(
+            dimension,
+            records
+                .into_iter()
+                .map(IndexRecord::try_from)
+                .collect::<Result<_, _>>()?,
+            vectors,
+            tombstones.into_iter().collect(),
+        )?;
+        index.set_model(model);
+        Ok(index)
+    }
+}
@@ -0,0 +1,83 @@
+//! Adapters between embedding dimensions.
+//!
+//! A [`VectorIndex`](crate::VectorIndex) refuses vectors whose dimension
+//! differs from its own, since their scores would be meaningless. A
+//! [`DimensionAdapter`] set on the index opts in to mapping such vectors to
+//! the index dimension instead, on insert and on query:
+//!
+//! - [`DimensionAdapter::Truncate`] keeps the leading values and rescales
+//!   them to unit length. Voyage models are trained so that a prefix of an
+//!   embedding is itself an embedding (Matryoshka learning): a 2048
+//!   dimensional `voyage-3-large` query searches a 1024 dimensional
+//!   `voyage-3-large` index this way, with little loss.
+//! - [`DimensionAdapter::RandomProjection`] multiplies by a Gaussian random
+//!   matrix drawn from a seed, which preserves the angles between vectors
+//!   approximately. It only makes sense when every vector of the source
+//!   dimension, documents and queries alike, is projected with the same
+//!   seed.
+//!
+//! Neither makes embeddings of different models comparable: the model
+//! checks of [`VectorIndex::insert_embedding`](crate::VectorIndex::insert_embedding)
+//! and [`search_embedding`](crate::VectorIndex::search_embedding) still apply.
+//!
+//! ```
+//! use voyageai::index::DimensionAdapter;
+//! let prefix = DimensionAdapter::Truncate.adapt(&[3.0, 4.0, 12.0], 2).unwrap();
+//! assert_eq!(prefix, vec![0.6, 0.8]);
+//! assert!(DimensionAdapter::Truncate.adapt(&[1.0], 2).is_none());
+//! ```
+
+use crate::rng::SplitMix64;
+use crate::vectors::normalize;
+
+/// Maps vectors of another dimension to the dimension of an index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DimensionAdapter {
+    /// Keeps the first values of longer vectors and rescales them to unit
+    /// length; shorter vectors are refused
+    Truncate,
+    /// Projects vectors of any dimension with a Gaussian random matrix drawn
+    /// from `seed`, then rescales them to unit length
+    RandomProjection { seed: u64 },
+}
+
+impl DimensionAdapter {
+    /// A random projection drawn from `seed`.
+    pub fn random_projection(seed: u64) -> Self {
+        Self::RandomProjection { seed }
+    }
+
+    /// `vector` mapped to `dimension` values, or `None` if this adapter
+    /// cannot do so. Vectors of the target dimension are returned as they
+    /// are.
+    pub fn adapt(&self, vector: &[f32], dimension: usize) -> Option<Vec<f32>> {
+        if vector.len() == dimension {
+            return Some(vector.to_vec());
+        }
+        if vector.is_empty() || dimension == 0 {
+            return None;
+        }
+        let mut adapted = match *self {
+            Self::Truncate if vector.len() < dimension => return None,
+            Self::Truncate => vector[..dimension].to_vec(),
+            Self::RandomProjection { seed } => project(vector, dimension, seed),
+        };
+        normalize(&mut adapted);
+        Some(adapted)
+    }
+}
+
+/// `vector` multiplied by a `dimension x vector.len()` matrix of standard
+/// normal values. The matrix depends only on the seed and the two
+/// dimensions, and is drawn row by row instead of being stored.
+fn project(vector: &[f32], dimension: usize, seed: u64) -> Vec<f32> {
+    let mut rng = SplitMix64::new(seed ^ (vector.len() as u64).rotate_left(32) ^ dimension as u64);
+    (0..dimension)
+        .map(|_| {
+            vector
+                .iter()
+                .map(|value| value * rng.next_gaussian() as f32)
+                .sum()
+        })
+        .collect()
+}
@@ -0,0 +1,165 @@
+//! A [`VectorIndex`] shared by concurrent readers and writers.
+//!
+//! [`SharedIndex`] lets a service answer queries while another task ingests
+//! documents. It publishes the index as an immutable snapshot behind a
+//! [`RwLock`]: readers only hold the lock to clone the snapshot's [`Arc`] and
+//! search without it, and writers copy the index on write when a reader still
+//! holds the snapshot they replace.
+//!
+//! Consistency:
+//!
+//! - Each [`write`](SharedIndex::write) is atomic: readers see all of its
+//!   changes or none of them. Group related changes, e.g. a whole batch of
+//!   chunks, into one write.
+//! - Writes are applied one at a time, in the order they take the lock.
+//! - A [`snapshot`](SharedIndex::snapshot) never changes: a search, or a
+//!   series of searches on one snapshot, sees the index as of a single write,
+//!   and every write completed before the snapshot was taken.
+//! - A write made while readers hold a snapshot copies the whole index once,
+//!   records, embeddings and HNSW graph included, so its cost grows with the
+//!   index rather than with the change. Prefer fewer, larger writes on large
+//!   indexes; `cargo bench --bench shared_index` measures the difference.
+//!
+//! ```
+//! use voyageai::index::SharedIndex;
+//! let index = SharedIndex::default();
+//! let writer = index.clone();
+//! writer.insert("1", "invoice terms", vec![1.0, 0.0], None).unwrap();
+//! let snapshot = index.snapshot();
+//! writer.delete("1");
+//! assert_eq!(snapshot.len(), 1);
+//! assert_eq!(index.len(), 0);
+//! ```
+
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, SystemTime};
+
+use log::debug;
+
+use crate::errors::VoyageError;
+use crate::index::store::{ExpiryHandle, StoreMatch};
+use crate::index::vector_index::VectorIndex;
+use crate::models::filter::MetadataFilter;
+use crate::models::metadata::Metadata;
+
+/// A handle to a [`VectorIndex`] that any number of tasks can read and write
+/// concurrently; clones share the same index.
+#[derive(Debug, Clone, Default)]
+pub struct SharedIndex {
+    current: Arc<RwLock<Arc<VectorIndex>>>,
+}
+
+impl SharedIndex {
+    pub fn new(index: VectorIndex) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(Arc::new(index))),
+        }
+    }
+
+    /// Loads an index file written by [`VectorIndex::save`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, VoyageError> {
+        VectorIndex::load(path).map(Self::new)
+    }
+
+    /// Saves the current snapshot; writes go on while the file is written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), VoyageError> {
+        self.snapshot().save(path)
+    }
+
+    /// The index as of the last completed write.
+    pub fn snapshot(&self) -> Arc<VectorIndex> {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Applies `update` to the index atomically and returns its result.
+    /// Readers keep their snapshot meanwhile; new snapshots wait for the
+    /// write to complete.
+    ///
+    /// When any snapshot is still alive, the index is cloned before `update`
+    /// runs, which takes time and memory proportional to the whole index. A
+    /// write with no outstanding snapshot updates the index in place.
+    pub fn write<R>(&self, update: impl FnOnce(&mut VectorIndex) -> R) -> R {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        update(Arc::make_mut(&mut current))
+    }
+
+    /// Number of documents of the current snapshot.
+    pub fn len(&self) -> usize {
+        self.snapshot().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshot().is_empty()
+    }
+
+    /// Inserts a document like [`VectorIndex::insert`].
+    pub fn insert(
+        &self,
+        id: impl Into<String>,
+        text: impl Into<String>,
+        embedding: Vec<f32>,
+        metadata: Option<Metadata>,
+    ) -> Result<(), VoyageError> {
+        self.write(|index| index.insert(id, text, embedding, metadata))
+    }
+
+    /// Deletes a document like [`VectorIndex::delete`].
+    pub fn delete(&self, id: &str) -> bool {
+        self.write(|index| index.delete(id))
+    }
+
+    /// Searches the current snapshot like [`VectorIndex::search`].
+    pub fn search(
+        &self,
+        query: &[f32],
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<StoreMatch>, VoyageError> {
+        let snapshot = self.snapshot();
+        Ok(snapshot
+            .search(query, top_k, filter)?
+            .into_iter()
+            .map(|m| StoreMatch {
+                record: m.record.clone(),
+                score: m.score,
+            })
+            .collect())
+    }
+
+    /// Starts a background task that evicts expired documents every
+    /// `interval`, like [`VectorIndex::spawn_eviction`]. The task stops when
+    /// the returned handle or every clone of the index is dropped.
+    pub fn spawn_eviction(&self, interval: Duration) -> ExpiryHandle {
+        let current = Arc::downgrade(&self.current);
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(current) = current.upgrade() else {
+                    break;
+                };
+                let index = Self { current };
+                // Only copy the index when there is something to evict
+                if index.snapshot().expired_at(SystemTime::now()) == 0 {
+                    continue;
+                }
+                let removed = index.write(VectorIndex::evict_expired);
+                if removed > 0 {
+                    debug!("Evicted {} expired documents from shared index", removed);
+                }
+            }
+        });
+        ExpiryHandle { task }
+    }
+}
+
+impl From<VectorIndex> for SharedIndex {
+    fn from(index: VectorIndex) -> Self {
+        Self::new(index)
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock, Weak};
+use std::time::Duration;
+
+use log::debug;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::errors::VoyageError;
+use crate::index::hnsw::HnswConfig;
+use crate::index::projection::DimensionAdapter;
+use crate::index::vector_index::{IndexRecord, VectorIndex};
+use crate::models::filter::MetadataFilter;
+use crate::models::metadata::Metadata;
+
+/// Settings for a collection in a [`VectorStore`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CollectionConfig {
+    /// How long a document lives after it was last inserted; `None` keeps
+    /// documents until they are removed
+    pub ttl: Option<Duration>,
+    /// Approximate search parameters; `None` searches exactly
+    pub hnsw: Option<HnswConfig>,
+    /// Maps documents and queries of another dimension to the collection's;
+    /// `None` refuses them
+    pub dimension_adapter: Option<DimensionAdapter>,
+}
+
+impl CollectionConfig {
+    /// A collection whose documents are kept until removed.
+    pub fn persistent() -> Self {
+        Self::default()
+    }
+
+    /// A collection whose documents expire `ttl` after insertion.
+    pub fn ephemeral(ttl: Duration) -> Self {
+        Self {
+            ttl: Some(ttl),
+            ..Self::default()
+        }
+    }
+
+    /// Searches the collection with an HNSW graph instead of brute force.
+    pub fn with_hnsw(mut self, config: HnswConfig) -> Self {
+        self.hnsw = Some(config);
+        self
+    }
+
+    /// Adapts documents and queries of another dimension with `adapter`.
+    pub fn with_dimension_adapter(mut self, adapter: DimensionAdapter) -> Self {
+        self.dimension_adapter = Some(adapter);
+        self
+    }
+}
+
+/// A document returned by [`VectorStore::search`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreMatch {
+    pub record: IndexRecord,
+    /// Cosine similarity between the query and the document embedding
+    pub score: f32,
+}
+
+#[derive(Debug)]
+struct Collection {
+    config: CollectionConfig,
+    index: VectorIndex,
+    inserted_at: HashMap<String, Instant>,
+}
+
+impl Collection {
+    fn is_expired(&self, id: &str, now: Instant) -> bool {
+        match (self.config.ttl, self.inserted_at.get(id)) {
+            (Some(ttl), Some(inserted_at)) => now.duration_since(*inserted_at) >= ttl,
+            _ => false,
+        }
+    }
+
+    /// Removes expired documents and returns how many were removed.
+    fn expire(&mut self, now: Instant) -> usize {
+        if self.config.ttl.is_none() {
+            return 0;
+        }
+        let expired: Vec<String> = self
+            .inserted_at
+            .keys()
+            .filter(|id| self.is_expired(id, now))
+            .cloned()
+            .collect();
+        for id in &expired {
+            self.index.remove(id);
+            self.inserted_at.remove(id);
+        }
+        expired.len()
+    }
+}
+
+/// Named collections of embedded documents, each backed by a [`VectorIndex`].
+///
+/// Collections created with a TTL drop their documents once it elapses. Expired
+/// documents are never returned by reads, and are reclaimed by [`VectorStore::expire`]
+/// or by the background task started with [`VectorStore::spawn_expiry`].
+///
+/// Cloning a store is cheap; clones share the same collections.
+#[derive(Debug, Clone, Default)]
+pub struct VectorStore {
+    collections: Arc<RwLock<HashMap<String, Collection>>>,
+}
+
+impl VectorStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a collection, failing if one with the same name exists.
+    pub fn create_collection(
+        &self,
+        name: impl Into<String>,
+        config: CollectionConfig,
+    ) -> Result<(), VoyageError> {
+        let name = name.into();
+        let mut collections = self.write();
+        if collections.contains_key(&name) {
+            return Err(VoyageError::CollectionExists(name));
+        }
+        let mut index = match config.hnsw {
+            Some(hnsw) => VectorIndex::new().with_hnsw(hnsw),
+            None => VectorIndex::new(),
+        };
+        if let Some(adapter) = config.dimension_adapter {
+            index = index.with_dimension_adapter(adapter);
+        }
+        collections.insert(
+            name,
+            Collection {
+                config,
+                index,
+                inserted_at: HashMap::new(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Drops a collection and all of its documents.
+    pub fn drop_collection(&self, name: &str) -> bool {
+        self.write().remove(name).is_some()
+    }
+
+    /// Names of all collections, sorted.
+    pub fn collection_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.read().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Inserts a document, replacing any document with the same id and
+    /// restarting its TTL.
+    pub fn insert(
+        &self,
+        collection: &str,
+        id: impl Into<String>,
+        text: impl Into<String>,
+        embedding: Vec<f32>,
+        metadata: Option<Metadata>,
+    ) -> Result<(), VoyageError> {
+        let id = id.into();
+        let mut collections = self.write();
+        let collection = collections
+            .get_mut(collection)
+            .ok_or_else(|| VoyageError::CollectionNotFound(collection.to_string()))?;
+        collection.index.insert(id.clone(), text, embedding, metadata)?;
+        collection.inserted_at.insert(id, Instant::now());
+        Ok(())
+    }
+
+    /// Removes a document, returning whether it was present.
+    pub fn remove(&self, collection: &str, id: &str) -> Result<bool, VoyageError> {
+        let mut collections = self.write();
+        let collection = collections
+            .get_mut(collection)
+            .ok_or_else(|| VoyageError::CollectionNotFound(collection.to_string()))?;
+        collection.inserted_at.remove(id);
+        Ok(collection.index.remove(id).is_some())
+    }
+
+    /// Number of live (unexpired) documents in a collection.
+    pub fn len(&self, collection: &str) -> Result<usize, VoyageError> {
+        let collections = self.read();
+        let collection = collections
+            .get(collection)
+            .ok_or_else(|| VoyageError::CollectionNotFound(collection.to_string()))?;
+        let now = Instant::now();
+        Ok(collection
+            .index
+            .iter()
+            .filter(|(record, _)| !collection.is_expired(&record.id, now))
+            .count())
+    }
+
+    /// Returns the `top_k` live documents most similar to `query`.
+    pub fn search(
+        &self,
+        collection: &str,
+        query: &[f32],
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<StoreMatch>, VoyageError> {
+        let collections = self.read();
+        let collection = collections
+            .get(collection)
+            .ok_or_else(|| VoyageError::CollectionNotFound(collection.to_string()))?;
+        let now = Instant::now();
+        // Over-fetch by the number of expired documents so that filtering them
+        // out still leaves `top_k` matches.
+        let expired = collection
+            .inserted_at
+            .keys()
+            .filter(|id| collection.is_expired(id, now))
+            .count();
+        let matches = collection.index.search(query, top_k + expired, filter)?;
+        Ok(matches
+            .into_iter()
+            .filter(|m| !collection.is_expired(&m.record.id, now))
+            .take(top_k)
+            .map(|m| StoreMatch {
+                record: m.record.clone(),
+                score: m.score,
+            })
+            .collect())
+    }
+
+    /// Removes expired documents from every collection and returns how many
+    /// were removed.
+    pub fn expire(&self) -> usize {
+        let now = Instant::now();
+        self.write()
+            .values_mut()
+            .map(|collection| collection.expire(now))
+            .sum()
+    }
+
+    /// Starts a background task that calls [`VectorStore::expire`] every
+    /// `interval`. The task stops when the returned handle or the store is dropped.
+    pub fn spawn_expiry(&self, interval: Duration) -> ExpiryHandle {
+        let collections: Weak<RwLock<HashMap<String, Collection>>> =
+            Arc::downgrade(&self.collections);
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(collections) = collections.upgrade() else {
+                    break;
+                };
+                let removed = VectorStore { collections }.expire();
+                if removed > 0 {
+                    debug!("Expired {} documents from vector store", removed);
+                }
+            }
+        });
+        ExpiryHandle { task }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, Collection>> {
+        self.collections.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, Collection>> {
+        self.collections.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Handle to a background expiry task; dropping it stops the task.
+#[derive(Debug)]
+pub struct ExpiryHandle {
+    pub(crate) task: JoinHandle<()>,
+}
+
+impl ExpiryHandle {
+    /// Stops the background task.
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for ExpiryHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
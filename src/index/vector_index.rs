@@ -0,0 +1,628 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, SystemTime};
+
+use log::debug;
+
+use crate::try_cosine_similarity;
+use crate::errors::VoyageError;
+use crate::index::hnsw::{HnswConfig, HnswGraph, Vectors};
+use crate::index::projection::DimensionAdapter;
+use crate::index::store::ExpiryHandle;
+use crate::models::embedding::Embedding;
+use crate::models::filter::MetadataFilter;
+use crate::models::metadata::Metadata;
+use crate::vectors::VectorError;
+
+/// A document stored in a [`VectorIndex`], without its embedding.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct IndexRecord {
+    /// Caller-provided unique identifier
+    pub id: String,
+    /// The document content
+    pub text: String,
+    /// Metadata attached to the document, if any
+    pub metadata: Option<Metadata>,
+    /// When the document expires, if ever. Expired documents are left out of
+    /// searches and lookups until [`VectorIndex::evict_expired`] removes them
+    pub expires_at: Option<SystemTime>,
+}
+
+impl IndexRecord {
+    /// Whether the document has expired at `now`.
+    pub fn is_expired_at(&self, now: SystemTime) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// A single match returned by [`VectorIndex::search`].
+#[derive(Debug, Clone, Copy)]
+pub struct IndexMatch<'a> {
+    /// The matched document
+    pub record: &'a IndexRecord,
+    /// Cosine similarity between the query and the document embedding
+    pub score: f32,
+}
+
+/// In-memory corpus of embedded documents searched by cosine similarity.
+///
+/// Embeddings are stored contiguously (`len() * dimension()` floats) in insertion
+/// order, which keeps search cache-friendly and makes the on-disk format produced
+/// by [`VectorIndex::save`] a straight copy of memory.
+///
+/// Search is exact (brute force) by default. For large corpora, [`VectorIndex::with_hnsw`]
+/// maintains an [HNSW graph](crate::index::hnsw) alongside the embeddings and
+/// answers unfiltered searches approximately in sub-linear time.
+///
+/// [`delete`](Self::delete) and [`delete_where`](Self::delete_where) only
+/// tombstone documents, which is cheap and keeps every position valid:
+/// deleted documents disappear from every read at once, but keep their space,
+/// in memory and in saved files, until [`compact`](Self::compact) drops them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VectorIndex {
+    dimension: usize,
+    records: Vec<IndexRecord>,
+    vectors: Vec<f32>,
+    positions: HashMap<String, usize>,
+    hnsw: Option<HnswGraph>,
+    model: Option<String>,
+    adapter: Option<DimensionAdapter>,
+    default_ttl: Option<Duration>,
+    /// Ids of the deleted documents still stored
+    tombstones: HashSet<String>,
+}
+
+impl VectorIndex {
+    /// Creates an empty index whose dimension is fixed by the first insert.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty index that only accepts embeddings of `dimension` floats.
+    pub fn with_dimension(dimension: usize) -> Self {
+        Self {
+            dimension,
+            ..Self::default()
+        }
+    }
+
+    /// Enables approximate nearest neighbor search, building an HNSW graph over
+    /// the documents already in the index. Later inserts and removals keep the
+    /// graph up to date.
+    pub fn with_hnsw(mut self, config: HnswConfig) -> Self {
+        self.hnsw = Some(HnswGraph::build(
+            config,
+            Vectors {
+                values: &self.vectors,
+                dimension: self.dimension,
+            },
+        ));
+        self
+    }
+
+    /// The HNSW parameters, if approximate search is enabled.
+    pub fn hnsw_config(&self) -> Option<&HnswConfig> {
+        self.hnsw.as_ref().map(HnswGraph::config)
+    }
+
+    /// Changes the search-time candidate list size of the HNSW graph; ignored
+    /// when approximate search is disabled.
+    pub fn set_ef_search(&mut self, ef_search: usize) {
+        if let Some(hnsw) = &mut self.hnsw {
+            hnsw.set_ef_search(ef_search);
+        }
+    }
+
+    /// Records the name of the model that produced the embeddings, saved
+    /// with the index so that it can be queried with the same model.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// The model recorded with [`with_model`](Self::with_model), if any.
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    /// Records `model`, as loading a saved index does.
+    pub(crate) fn set_model(&mut self, model: Option<String>) {
+        self.model = model;
+    }
+
+    /// Maps inserted documents and queries of another dimension to the
+    /// index dimension with `adapter`, instead of refusing them. The adapter
+    /// is not saved with the index.
+    pub fn with_dimension_adapter(mut self, adapter: DimensionAdapter) -> Self {
+        self.adapter = Some(adapter);
+        self
+    }
+
+    /// The adapter set with [`with_dimension_adapter`](Self::with_dimension_adapter), if any.
+    pub fn dimension_adapter(&self) -> Option<DimensionAdapter> {
+        self.adapter
+    }
+
+    /// Makes documents inserted without an expiry expire `ttl` after their
+    /// insertion. The default is not saved with the index; the expiry of
+    /// each document is.
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = Some(ttl);
+        self
+    }
+
+    /// The embedding dimension, or 0 if it has not been fixed yet.
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Number of documents, not counting deleted ones.
+    pub fn len(&self) -> usize {
+        self.records.len() - self.tombstones.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of deleted documents whose space [`compact`](Self::compact)
+    /// would reclaim.
+    pub fn tombstoned(&self) -> usize {
+        self.tombstones.len()
+    }
+
+    /// Inserts a document, replacing any existing document with the same id,
+    /// deleted or not. The document expires after the [default TTL](Self::with_default_ttl),
+    /// if any. The embedding's model is not checked; use
+    /// [`insert_embedding`](Self::insert_embedding) for that.
+    pub fn insert(
+        &mut self,
+        id: impl Into<String>,
+        text: impl Into<String>,
+        embedding: Vec<f32>,
+        metadata: Option<Metadata>,
+    ) -> Result<(), VoyageError> {
+        let expires_at = self.default_ttl.map(|ttl| SystemTime::now() + ttl);
+        self.insert_record(id, text, embedding, metadata, expires_at)
+    }
+
+    /// Inserts a document like [`insert`](Self::insert) that expires at
+    /// `expires_at`.
+    pub fn insert_expiring(
+        &mut self,
+        id: impl Into<String>,
+        text: impl Into<String>,
+        embedding: Vec<f32>,
+        metadata: Option<Metadata>,
+        expires_at: SystemTime,
+    ) -> Result<(), VoyageError> {
+        self.insert_record(id, text, embedding, metadata, Some(expires_at))
+    }
+
+    fn insert_record(
+        &mut self,
+        id: impl Into<String>,
+        text: impl Into<String>,
+        embedding: Vec<f32>,
+        metadata: Option<Metadata>,
+        expires_at: Option<SystemTime>,
+    ) -> Result<(), VoyageError> {
+        if self.dimension == 0 {
+            self.dimension = embedding.len();
+        }
+        if embedding.is_empty() {
+            return Err(VoyageError::SearchDimensionMismatch {
+                expected: self.dimension,
+                actual: 0,
+            });
+        }
+        let embedding = match self.adapt(&embedding)? {
+            Some(adapted) => adapted,
+            None => embedding,
+        };
+
+        let record = IndexRecord {
+            id: id.into(),
+            text: text.into(),
+            metadata,
+            expires_at,
+        };
+        self.tombstones.remove(&record.id);
+        match self.positions.get(&record.id) {
+            Some(&position) => {
+                let start = position * self.dimension;
+                self.vectors[start..start + self.dimension].copy_from_slice(&embedding);
+                self.records[position] = record;
+                if let Some(hnsw) = &mut self.hnsw {
+                    hnsw.update(
+                        position,
+                        Vectors {
+                            values: &self.vectors,
+                            dimension: self.dimension,
+                        },
+                    );
+                }
+            }
+            None => {
+                self.positions.insert(record.id.clone(), self.records.len());
+                self.records.push(record);
+                self.vectors.extend_from_slice(&embedding);
+                if let Some(hnsw) = &mut self.hnsw {
+                    hnsw.push(Vectors {
+                        values: &self.vectors,
+                        dimension: self.dimension,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Inserts a document like [`insert`](Self::insert), refusing an
+    /// embedding of another model than the index's. An empty index without a
+    /// model takes the embedding's.
+    pub fn insert_embedding(
+        &mut self,
+        id: impl Into<String>,
+        text: impl Into<String>,
+        embedding: Embedding,
+        metadata: Option<Metadata>,
+    ) -> Result<(), VoyageError> {
+        self.check_model(&embedding)?;
+        if self.is_empty() && self.model.is_none() {
+            self.model = embedding.model().map(str::to_string);
+        }
+        self.insert(id, text, embedding.into_vec(), metadata)
+    }
+
+    /// Searches like [`search`](Self::search), refusing a query embedded by
+    /// another model than the index's.
+    pub fn search_embedding(
+        &self,
+        query: &Embedding,
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<IndexMatch<'_>>, VoyageError> {
+        self.check_model(query)?;
+        self.search(query, top_k, filter)
+    }
+
+    fn check_model(&self, embedding: &Embedding) -> Result<(), VectorError> {
+        match (self.model(), embedding.model()) {
+            (Some(expected), Some(actual)) if expected != actual => {
+                Err(VectorError::ModelMismatch {
+                    expected: expected.to_string(),
+                    actual: actual.to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// `vector` mapped to the index dimension by the adapter, or `None` if
+    /// it already has that dimension.
+    fn adapt(&self, vector: &[f32]) -> Result<Option<Vec<f32>>, VoyageError> {
+        if vector.len() == self.dimension {
+            return Ok(None);
+        }
+        self.adapter
+            .and_then(|adapter| adapter.adapt(vector, self.dimension))
+            .map(Some)
+            .ok_or(VoyageError::SearchDimensionMismatch {
+                expected: self.dimension,
+                actual: vector.len(),
+            })
+    }
+
+    /// Removes a document by id at once, returning its record and embedding.
+    /// Unlike [`delete`](Self::delete), this shifts every later document.
+    pub fn remove(&mut self, id: &str) -> Option<(IndexRecord, Vec<f32>)> {
+        let position = self.positions.remove(id)?;
+        self.tombstones.remove(id);
+        if let Some(hnsw) = &mut self.hnsw {
+            hnsw.remove(
+                position,
+                Vectors {
+                    values: &self.vectors,
+                    dimension: self.dimension,
+                },
+            );
+        }
+        let start = position * self.dimension;
+        let embedding = self
+            .vectors
+            .drain(start..start + self.dimension)
+            .collect();
+        let record = self.records.remove(position);
+        for (index, record) in self.records.iter().enumerate().skip(position) {
+            self.positions.insert(record.id.clone(), index);
+        }
+        Some((record, embedding))
+    }
+
+    /// Position of the document `id` in insertion order.
+    pub(crate) fn position(&self, id: &str) -> Option<usize> {
+        self.positions.get(id).copied()
+    }
+
+    /// Deletes the document `id` by tombstoning it. Returns whether a
+    /// document was deleted.
+    pub fn delete(&mut self, id: &str) -> bool {
+        self.positions.contains_key(id) && self.tombstones.insert(id.to_string())
+    }
+
+    /// Deletes every document whose metadata matches `filter` and returns how
+    /// many were deleted.
+    pub fn delete_where(&mut self, filter: &MetadataFilter) -> usize {
+        let matching: Vec<String> = self
+            .iter()
+            .filter(|(record, _)| filter.matches(record.metadata.as_ref()))
+            .map(|(record, _)| record.id.clone())
+            .collect();
+        self.tombstones.extend(matching.iter().cloned());
+        matching.len()
+    }
+
+    /// Drops the deleted documents, rebuilding the HNSW graph if enabled, and
+    /// returns how many were dropped. Saving the index afterwards rewrites
+    /// its file without them.
+    pub fn compact(&mut self) -> usize {
+        if self.tombstones.is_empty() {
+            return 0;
+        }
+        let tombstones = std::mem::take(&mut self.tombstones);
+        self.retain(|record| !tombstones.contains(&record.id))
+    }
+
+    /// Keeps the documents for which `keep` holds in one pass, rebuilding the
+    /// positions and the HNSW graph if enabled, and returns how many were
+    /// dropped.
+    fn retain(&mut self, keep: impl Fn(&IndexRecord) -> bool) -> usize {
+        let dimension = self.dimension;
+        let before = self.records.len();
+        let mut records = Vec::with_capacity(before);
+        let mut vectors = Vec::with_capacity(self.vectors.len());
+        for (position, record) in std::mem::take(&mut self.records).into_iter().enumerate() {
+            if keep(&record) {
+                vectors.extend_from_slice(&self.vectors[position * dimension..][..dimension]);
+                records.push(record);
+            }
+        }
+        self.positions = records
+            .iter()
+            .enumerate()
+            .map(|(position, record)| (record.id.clone(), position))
+            .collect();
+        self.records = records;
+        self.vectors = vectors;
+        if let Some(hnsw) = &self.hnsw {
+            self.hnsw = Some(HnswGraph::build(
+                *hnsw.config(),
+                Vectors {
+                    values: &self.vectors,
+                    dimension,
+                },
+            ));
+        }
+        let positions = &self.positions;
+        self.tombstones.retain(|id| positions.contains_key(id));
+        before - self.records.len()
+    }
+
+    /// Whether an unexpired, undeleted document is stored under `id`.
+    pub fn contains(&self, id: &str) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Returns the record and embedding stored under `id`, unless expired or
+    /// deleted.
+    pub fn get(&self, id: &str) -> Option<(&IndexRecord, &[f32])> {
+        let position = *self.positions.get(id)?;
+        let record = &self.records[position];
+        if !self.is_live(record, SystemTime::now()) {
+            return None;
+        }
+        Some((record, self.embedding(position)))
+    }
+
+    /// Changes when the document `id` expires; `None` keeps it until removed.
+    /// Returns whether the document exists and is not deleted.
+    pub fn set_expiry(&mut self, id: &str, expires_at: Option<SystemTime>) -> bool {
+        match self.positions.get(id) {
+            Some(&position) if !self.tombstones.contains(id) => {
+                self.records[position].expires_at = expires_at;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Number of stored documents expired at `now`.
+    pub(crate) fn expired_at(&self, now: SystemTime) -> usize {
+        self.records
+            .iter()
+            .filter(|record| record.is_expired_at(now))
+            .count()
+    }
+
+    /// Removes the documents expired by now and returns how many were
+    /// removed.
+    pub fn evict_expired(&mut self) -> usize {
+        self.evict_expired_at(SystemTime::now())
+    }
+
+    /// Removes the documents expired at `now` and returns how many were
+    /// removed.
+    pub fn evict_expired_at(&mut self, now: SystemTime) -> usize {
+        if self.expired_at(now) == 0 {
+            return 0;
+        }
+        self.retain(|record| !record.is_expired_at(now))
+    }
+
+    /// Starts a background task that calls [`evict_expired`](Self::evict_expired)
+    /// on `index` every `interval`. The task stops when the returned handle or
+    /// the index is dropped.
+    pub fn spawn_eviction(index: &Arc<RwLock<VectorIndex>>, interval: Duration) -> ExpiryHandle {
+        let index = Arc::downgrade(index);
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(index) = index.upgrade() else {
+                    break;
+                };
+                let removed = index
+                    .write()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .evict_expired();
+                if removed > 0 {
+                    debug!("Evicted {} expired documents from vector index", removed);
+                }
+            }
+        });
+        ExpiryHandle { task }
+    }
+
+    /// Iterates over records and embeddings in insertion order, including
+    /// expired documents not evicted yet but not deleted ones.
+    pub fn iter(&self) -> impl Iterator<Item = (&IndexRecord, &[f32])> {
+        self.records
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| !self.tombstones.contains(&record.id))
+            .map(|(position, record)| (record, self.embedding(position)))
+    }
+
+    /// Whether `record` is neither expired at `now` nor deleted.
+    fn is_live(&self, record: &IndexRecord, now: SystemTime) -> bool {
+        !record.is_expired_at(now) && !self.tombstones.contains(&record.id)
+    }
+
+    /// Returns the `top_k` documents most similar to `query`, optionally restricted
+    /// to documents whose metadata matches `filter`.
+    ///
+    /// Unfiltered searches use the HNSW graph when enabled and are approximate;
+    /// filtered searches always scan the matching documents exactly. Expired
+    /// and deleted documents are never returned. The query's model is not
+    /// checked; use [`search_embedding`](Self::search_embedding) for that.
+    pub fn search(
+        &self,
+        query: &[f32],
+        top_k: usize,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<IndexMatch<'_>>, VoyageError> {
+        let adapted = if self.is_empty() {
+            None
+        } else {
+            self.adapt(query)?
+        };
+        let query = adapted.as_deref().unwrap_or(query);
+        let now = SystemTime::now();
+
+        if let (Some(hnsw), None) = (&self.hnsw, filter) {
+            let vectors = Vectors {
+                values: &self.vectors,
+                dimension: self.dimension,
+            };
+            // Over-fetch by the number of expired and deleted documents so
+            // that leaving them out still leaves `top_k` matches
+            let hidden = self.expired_at(now) + self.tombstones.len();
+            return Ok(hnsw
+                .search(query, top_k + hidden, vectors)
+                .into_iter()
+                .map(|(position, distance)| IndexMatch {
+                    record: &self.records[position],
+                    score: 1.0 - distance,
+                })
+                .filter(|found| self.is_live(found.record, now))
+                .take(top_k)
+                .collect());
+        }
+
+        let mut matches: Vec<IndexMatch<'_>> = self
+            .iter()
+            .filter(|(record, _)| {
+                !record.is_expired_at(now)
+                    && filter.is_none_or(|filter| filter.matches(record.metadata.as_ref()))
+            })
+            .map(|(record, embedding)| {
+                Ok(IndexMatch {
+                    record,
+                    score: try_cosine_similarity(query, embedding)?,
+                })
+            })
+            .collect::<Result<_, VoyageError>>()?;
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches.truncate(top_k);
+        Ok(matches)
+    }
+
+    /// Every stored record, deleted ones included.
+    pub(crate) fn records(&self) -> &[IndexRecord] {
+        &self.records
+    }
+
+    /// Ids of the deleted documents, in insertion order.
+    pub(crate) fn tombstoned_ids(&self) -> impl Iterator<Item = &str> {
+        self.records
+            .iter()
+            .map(|record| record.id.as_str())
+            .filter(|id| self.tombstones.contains(*id))
+    }
+
+    pub(crate) fn vectors(&self) -> &[f32] {
+        &self.vectors
+    }
+
+    /// Rebuilds an index from its parts; `vectors` must hold
+    /// `records.len() * dimension` floats and `tombstones` name stored
+    /// records.
+    pub(crate) fn from_parts(
+        dimension: usize,
+        records: Vec<IndexRecord>,
+        vectors: Vec<f32>,
+        tombstones: HashSet<String>,
+    ) -> Result<Self, VoyageError> {
+        if vectors.len() != records.len() * dimension {
+            return Err(VoyageError::IndexFormatError(format!(
+                "expected {} vector values, found {}",
+                records.len() * dimension,
+                vectors.len()
+            )));
+        }
+        let mut positions = HashMap::with_capacity(records.len());
+        for (position, record) in records.iter().enumerate() {
+            if positions.insert(record.id.clone(), position).is_some() {
+                return Err(VoyageError::IndexFormatError(format!(
+                    "duplicate document id {}",
+                    record.id
+                )));
+            }
+        }
+        if let Some(id) = tombstones.iter().find(|id| !positions.contains_key(*id)) {
+            return Err(VoyageError::IndexFormatError(format!(
+                "tombstone of unknown document id {}",
+                id
+            )));
+        }
+        Ok(Self {
+            dimension,
+            records,
+            vectors,
+            positions,
+            hnsw: None,
+            model: None,
+            adapter: None,
+            default_ttl: None,
+            tombstones,
+        })
+    }
+
+    fn embedding(&self, position: usize) -> &[f32] {
+        let start = position * self.dimension;
+        &self.vectors[start..start + self.dimension]
+    }
+}
@@ -0,0 +1,158 @@
+//! CSV tables of texts, as exported from spreadsheets.
+//!
+//! [`read_csv_texts`] reads the texts to embed from one column of a CSV file
+//! with a header row, and their ids from another. Fields follow RFC 4180:
+//! quoted fields may contain commas, doubled quotes and line breaks.
+//! [`CsvWriter`] writes results in the same dialect.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use super::{io_error, stream_error};
+use crate::errors::VoyageError;
+
+/// A text read from a CSV file, with its id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvText {
+    pub id: String,
+    pub text: String,
+}
+
+/// Reads the `text_column` of every row of the CSV file at `path`. Ids come
+/// from `id_column`, or are the row numbers, from 0, without one. Rows with
+/// an empty text are skipped.
+pub fn read_csv_texts(
+    path: impl AsRef<Path>,
+    text_column: &str,
+    id_column: Option<&str>,
+) -> Result<Vec<CsvText>, VoyageError> {
+    let path = path.as_ref();
+    let input = fs::read_to_string(path).map_err(|e| io_error(path, e))?;
+    let mut records = parse_csv(&input)?.into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| VoyageError::InterchangeError(format!("{}: empty CSV", path.display())))?;
+    let position = |column: &str| {
+        header
+            .iter()
+            .position(|name| name.trim() == column)
+            .ok_or_else(|| {
+                VoyageError::InterchangeError(format!(
+                    "{}: no column `{}`, columns are {}",
+                    path.display(),
+                    column,
+                    header.join(", ")
+                ))
+            })
+    };
+    let text_index = position(text_column)?;
+    let id_index = id_column.map(position).transpose()?;
+
+    let mut texts = Vec::new();
+    for (row, record) in records.enumerate() {
+        let text = record.get(text_index).map_or("", String::as_str);
+        if text.trim().is_empty() {
+            continue;
+        }
+        let id = match id_index {
+            Some(index) => record.get(index).cloned().unwrap_or_default(),
+            None => row.to_string(),
+        };
+        texts.push(CsvText {
+            id,
+            text: text.to_string(),
+        });
+    }
+    Ok(texts)
+}
+
+/// Splits `input` into records of fields. A trailing line break does not
+/// start a record; blank lines are skipped.
+pub fn parse_csv(input: &str) -> Result<Vec<Vec<String>>, VoyageError> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            '\n' if quoted => {
+                line += 1;
+                field.push(c);
+            }
+            _ if quoted => field.push(c),
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                line += 1;
+                record.push(std::mem::take(&mut field));
+                if record.len() > 1 || !record[0].is_empty() {
+                    records.push(std::mem::take(&mut record));
+                } else {
+                    record.clear();
+                }
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(VoyageError::InterchangeError(format!(
+            "Unterminated quoted field at line {line}"
+        )));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Quotes `value` if it contains a comma, quote or line break.
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Writes rows of fields as CSV lines, quoting fields as needed.
+#[derive(Debug)]
+pub struct CsvWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// A writer starting with the header row `columns`.
+    pub fn new(writer: W, columns: &[&str]) -> Result<Self, VoyageError> {
+        let mut writer = Self { writer };
+        writer.write_row(columns)?;
+        Ok(writer)
+    }
+
+    pub fn write_row<S: AsRef<str>>(&mut self, fields: &[S]) -> Result<(), VoyageError> {
+        let line: Vec<String> = fields
+            .iter()
+            .map(|field| csv_field(field.as_ref()))
+            .collect();
+        writeln!(self.writer, "{}", line.join(",")).map_err(stream_error)
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, VoyageError> {
+        self.writer.flush().map_err(stream_error)?;
+        Ok(self.writer)
+    }
+}
@@ -0,0 +1,79 @@
+//! Reading and writing embeddings in formats shared with other tools.
+//!
+//! - [`csv`](mod@csv): texts and ids from CSV files with a header row, and
+//!   CSV output of results
+//! - [`ndjson`]: one JSON object per line, `{"id", "model", "dim", "vector"}`,
+//!   optionally gzip or zstd compressed
+//! - [`parquet`](mod@parquet): Parquet files with metadata columns, behind
+//!   the `parquet` feature
+//! - [`numpy`]: `.npy` matrices and `.npz` archives for NumPy
+//! - [`safetensors`](mod@safetensors): safetensors files for PyTorch
+//!
+//! Formats with ids carry [`EmbeddingRecord`]s.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::VoyageError;
+
+pub mod csv;
+pub mod ndjson;
+pub mod numpy;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod safetensors;
+
+pub use csv::{read_csv_texts, CsvText, CsvWriter};
+pub use ndjson::{read_ndjson, write_ndjson, Compression, NdjsonReader, NdjsonWriter};
+pub use numpy::{read_npy, write_npy, write_npz};
+#[cfg(feature = "parquet")]
+pub use parquet::{read_parquet, write_parquet};
+pub use safetensors::{read_safetensors, write_safetensors};
+
+/// An embedding with the id of what it embeds and the model that produced
+/// it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingRecord {
+    pub id: String,
+    /// Model name, e.g. `voyage-3-large`
+    pub model: String,
+    /// Length of `vector`
+    pub dim: usize,
+    pub vector: Vec<f32>,
+}
+
+impl EmbeddingRecord {
+    pub fn new(id: impl Into<String>, model: impl Into<String>, vector: Vec<f32>) -> Self {
+        Self {
+            id: id.into(),
+            model: model.into(),
+            dim: vector.len(),
+            vector,
+        }
+    }
+}
+
+/// The dimension shared by all `embeddings`, 0 if there are none.
+fn uniform_dimension<E: AsRef<[f32]>>(embeddings: &[E]) -> Result<usize, VoyageError> {
+    let dimension = embeddings.first().map_or(0, |first| first.as_ref().len());
+    match embeddings
+        .iter()
+        .position(|embedding| embedding.as_ref().len() != dimension)
+    {
+        Some(index) => Err(VoyageError::EmbeddingDimensionMismatch {
+            index,
+            expected: dimension,
+            actual: embeddings[index].as_ref().len(),
+        }),
+        None => Ok(dimension),
+    }
+}
+
+fn stream_error(error: std::io::Error) -> VoyageError {
+    VoyageError::InterchangeError(error.to_string())
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> VoyageError {
+    VoyageError::InterchangeError(format!("{}: {}", path.display(), error))
+}
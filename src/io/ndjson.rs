@@ -0,0 +1,311 @@
+//! Newline-delimited JSON embedding files.
+//!
+//! Each line is an [`EmbeddingRecord`]:
+//!
+//! ```text
+//! {"id":"doc-1","model":"voyage-3-large","dim":3,"vector":[0.1,-0.2,0.3]}
+//! ```
+//!
+//! which `pandas.read_json(path, lines=True)` and most data lake tooling read
+//! directly. Files may be gzip (`.gz`, with the `gzip` feature) or zstd
+//! (`.zst`, with the `zstd` feature) compressed; [`NdjsonReader::open`]
+//! recognizes compressed files by their content.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::{io_error, stream_error, EmbeddingRecord};
+use crate::errors::VoyageError;
+
+/// Compression of an NDJSON file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    /// Requires the `gzip` feature
+    Gzip,
+    /// Requires the `zstd` feature
+    Zstd,
+}
+
+impl Compression {
+    /// The compression suggested by a file name: `.gz` or `.gzip` for gzip,
+    /// `.zst` or `.zstd` for zstd.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("gz" | "gzip") => Compression::Gzip,
+            Some("zst" | "zstd") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+
+    /// The compression of data starting with `header`, by its magic bytes.
+    pub fn detect(header: &[u8]) -> Self {
+        if header.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    fn unsupported(self) -> VoyageError {
+        let feature = match self {
+            Compression::Gzip => "gzip",
+            _ => "zstd",
+        };
+        VoyageError::InterchangeError(format!(
+            "{self:?} compression requires the `{feature}` feature"
+        ))
+    }
+}
+
+/// Compressing or pass-through sink of an [`NdjsonWriter`].
+enum Sink<W: Write> {
+    Plain(W),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<W>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Encoder<'static, W>),
+}
+
+impl<W: Write> Sink<W> {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Sink::Plain(writer) => writer,
+            #[cfg(feature = "gzip")]
+            Sink::Gzip(encoder) => encoder,
+            #[cfg(feature = "zstd")]
+            Sink::Zstd(encoder) => encoder,
+        }
+    }
+
+    // Without compression features, `Plain` is the only variant
+    #[allow(clippy::infallible_destructuring_match)]
+    fn finish(self) -> std::io::Result<W> {
+        let mut writer = match self {
+            Sink::Plain(writer) => writer,
+            #[cfg(feature = "gzip")]
+            Sink::Gzip(encoder) => encoder.finish()?,
+            #[cfg(feature = "zstd")]
+            Sink::Zstd(encoder) => encoder.finish()?,
+        };
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+/// Writes [`EmbeddingRecord`]s as NDJSON lines.
+///
+/// Call [`finish`](Self::finish) when done: it completes the compressed
+/// stream and reports errors that dropping the writer would swallow.
+pub struct NdjsonWriter<W: Write> {
+    sink: Sink<W>,
+    written: usize,
+}
+
+impl<W: Write> std::fmt::Debug for NdjsonWriter<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NdjsonWriter")
+            .field("written", &self.written)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    /// An uncompressed writer.
+    pub fn new(writer: W) -> Self {
+        Self {
+            sink: Sink::Plain(writer),
+            written: 0,
+        }
+    }
+
+    /// A writer compressing its output with `compression`.
+    pub fn with_compression(writer: W, compression: Compression) -> Result<Self, VoyageError> {
+        let sink = match compression {
+            Compression::None => Sink::Plain(writer),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Sink::Gzip(flate2::write::GzEncoder::new(
+                writer,
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Sink::Zstd(zstd::Encoder::new(writer, 0).map_err(stream_error)?),
+            #[allow(unreachable_patterns)]
+            unsupported => return Err(unsupported.unsupported()),
+        };
+        Ok(Self { sink, written: 0 })
+    }
+
+    /// Number of records written.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    pub fn write(&mut self, record: &EmbeddingRecord) -> Result<(), VoyageError> {
+        let writer = self.sink.writer();
+        serde_json::to_writer(&mut *writer, record)?;
+        writer.write_all(b"\n").map_err(stream_error)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    pub fn write_all<'a>(
+        &mut self,
+        records: impl IntoIterator<Item = &'a EmbeddingRecord>,
+    ) -> Result<(), VoyageError> {
+        records
+            .into_iter()
+            .try_for_each(|record| self.write(record))
+    }
+
+    /// Completes compression, flushes and returns the underlying writer.
+    pub fn finish(self) -> Result<W, VoyageError> {
+        self.sink.finish().map_err(stream_error)
+    }
+}
+
+impl NdjsonWriter<BufWriter<File>> {
+    /// Creates the file at `path`, compressed as its extension suggests, see
+    /// [`Compression::from_path`].
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, VoyageError> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| io_error(path, e))?;
+        Self::with_compression(BufWriter::new(file), Compression::from_path(path))
+    }
+}
+
+/// Reads [`EmbeddingRecord`]s from NDJSON lines, one per iteration. Blank
+/// lines are skipped; a line that is not a record, or whose `dim` differs
+/// from its vector's length, is an error.
+pub struct NdjsonReader<R> {
+    lines: std::io::Lines<R>,
+    line: usize,
+}
+
+impl<R> std::fmt::Debug for NdjsonReader<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NdjsonReader")
+            .field("line", &self.line)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: BufRead> NdjsonReader<R> {
+    /// A reader of uncompressed lines.
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            line: 0,
+        }
+    }
+}
+
+impl NdjsonReader<Box<dyn BufRead + Send>> {
+    /// A reader decompressing `reader` with `compression`.
+    pub fn with_compression<R: Read + Send + 'static>(
+        reader: R,
+        compression: Compression,
+    ) -> Result<Self, VoyageError> {
+        let reader: Box<dyn BufRead + Send> = match compression {
+            Compression::None => Box::new(BufReader::new(reader)),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(reader)))
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Box::new(BufReader::new(
+                zstd::Decoder::new(reader).map_err(stream_error)?,
+            )),
+            #[allow(unreachable_patterns)]
+            unsupported => return Err(unsupported.unsupported()),
+        };
+        Ok(Self::new(reader))
+    }
+
+    /// Opens the file at `path`, detecting its compression from its first
+    /// bytes.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, VoyageError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| io_error(path, e))?;
+        let mut reader = BufReader::new(file);
+        let compression = Compression::detect(reader.fill_buf().map_err(|e| io_error(path, e))?);
+        Self::with_compression(reader, compression)
+    }
+}
+
+impl<R: BufRead + Send + 'static> NdjsonReader<R> {
+    /// Reads the records on a blocking thread and yields them as a stream,
+    /// buffering at most `buffer` records ahead of the consumer.
+    pub fn into_stream(
+        mut self,
+        buffer: usize,
+    ) -> ReceiverStream<Result<EmbeddingRecord, VoyageError>> {
+        let (tx, rx) = mpsc::channel(buffer.max(1));
+        tokio::task::spawn_blocking(move || {
+            for record in self.by_ref() {
+                let failed = record.is_err();
+                if tx.blocking_send(record).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        ReceiverStream::new(rx)
+    }
+}
+
+impl<R: BufRead> Iterator for NdjsonReader<R> {
+    type Item = Result<EmbeddingRecord, VoyageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(stream_error(e))),
+            };
+            self.line += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str::<EmbeddingRecord>(&line)
+                .map_err(|e| VoyageError::InterchangeError(format!("line {}: {}", self.line, e)))
+                .and_then(|record| {
+                    if record.dim == record.vector.len() {
+                        Ok(record)
+                    } else {
+                        Err(VoyageError::InterchangeError(format!(
+                            "line {}: dim is {} but the vector has {} values",
+                            self.line,
+                            record.dim,
+                            record.vector.len()
+                        )))
+                    }
+                });
+            return Some(record);
+        }
+    }
+}
+
+/// Writes `records` to `path`, compressed as its extension suggests.
+/// Returns the number of records written.
+pub fn write_ndjson<'a>(
+    path: impl AsRef<Path>,
+    records: impl IntoIterator<Item = &'a EmbeddingRecord>,
+) -> Result<usize, VoyageError> {
+    let mut writer = NdjsonWriter::create(path)?;
+    writer.write_all(records)?;
+    let written = writer.written();
+    writer.finish()?;
+    Ok(written)
+}
+
+/// Reads every record of the file at `path`, see [`NdjsonReader::open`].
+pub fn read_ndjson(path: impl AsRef<Path>) -> Result<Vec<EmbeddingRecord>, VoyageError> {
+    NdjsonReader::open(path)?.collect()
+}
@@ -0,0 +1,234 @@
+//! NumPy `.npy` and `.npz` files.
+//!
+//! [`write_npy`] stores embeddings as one `float32` matrix of shape
+//! `(count, dim)`, loaded with `numpy.load(path)` or
+//! `torch.from_numpy(numpy.load(path))`. [`write_npz`] bundles the matrix
+//! with the ids of the records, as arrays `embeddings` and `ids`:
+//!
+//! ```python
+//! data = numpy.load("embeddings.npz")
+//! ids, embeddings = data["ids"], data["embeddings"]
+//! ```
+//!
+//! Ids are stored as fixed-width unicode strings, so loading needs no
+//! `allow_pickle`. Archives are not compressed.
+
+use std::fs;
+use std::path::Path;
+
+use super::{io_error, uniform_dimension, EmbeddingRecord};
+use crate::errors::VoyageError;
+
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Writes `embeddings` to `path` as a `float32` matrix.
+pub fn write_npy<E: AsRef<[f32]>>(
+    path: impl AsRef<Path>,
+    embeddings: &[E],
+) -> Result<(), VoyageError> {
+    let path = path.as_ref();
+    let bytes = npy_matrix(embeddings)?;
+    fs::write(path, bytes).map_err(|e| io_error(path, e))
+}
+
+/// Reads a matrix of `float32` or `float64` values, or a single vector, from
+/// an `.npy` file.
+pub fn read_npy(path: impl AsRef<Path>) -> Result<Vec<Vec<f32>>, VoyageError> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|e| io_error(path, e))?;
+    parse_npy(&bytes)
+}
+
+/// Writes the vectors and ids of `records` to `path` as an `.npz` archive of
+/// the arrays `embeddings` and `ids`.
+pub fn write_npz(path: impl AsRef<Path>, records: &[EmbeddingRecord]) -> Result<(), VoyageError> {
+    let path = path.as_ref();
+    let vectors: Vec<&[f32]> = records
+        .iter()
+        .map(|record| record.vector.as_slice())
+        .collect();
+    let ids: Vec<&str> = records.iter().map(|record| record.id.as_str()).collect();
+    let archive = stored_zip(&[
+        ("embeddings.npy", npy_matrix(&vectors)?),
+        ("ids.npy", npy_strings(&ids)),
+    ])?;
+    fs::write(path, archive).map_err(|e| io_error(path, e))
+}
+
+/// An `.npy` file of a 2-dimensional little-endian `float32` matrix.
+fn npy_matrix<E: AsRef<[f32]>>(embeddings: &[E]) -> Result<Vec<u8>, VoyageError> {
+    let dimension = uniform_dimension(embeddings)?;
+    let mut bytes = npy_header("<f4", &format!("({}, {})", embeddings.len(), dimension));
+    bytes.reserve(embeddings.len() * dimension * 4);
+    for value in embeddings.iter().flat_map(|embedding| embedding.as_ref()) {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    Ok(bytes)
+}
+
+/// An `.npy` file of a 1-dimensional array of fixed-width UTF-32 strings.
+fn npy_strings(strings: &[&str]) -> Vec<u8> {
+    let width = strings
+        .iter()
+        .map(|string| string.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut bytes = npy_header(&format!("<U{width}"), &format!("({},)", strings.len()));
+    for string in strings {
+        let mut chars = string.chars().map(u32::from);
+        for _ in 0..width {
+            bytes.extend_from_slice(&chars.next().unwrap_or(0).to_le_bytes());
+        }
+    }
+    bytes
+}
+
+/// Magic, version 1.0 and header dictionary, padded so that the data starts
+/// at a multiple of 64 bytes.
+fn npy_header(descr: &str, shape: &str) -> Vec<u8> {
+    let mut header = format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': {shape}, }}");
+    let unpadded = NPY_MAGIC.len() + 2 + 2 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+
+    let mut bytes = NPY_MAGIC.to_vec();
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    bytes
+}
+
+fn parse_npy(bytes: &[u8]) -> Result<Vec<Vec<f32>>, VoyageError> {
+    let invalid =
+        |reason: &str| VoyageError::InterchangeError(format!("Invalid .npy file: {reason}"));
+    if !bytes.starts_with(NPY_MAGIC) || bytes.len() < 10 {
+        return Err(invalid("missing magic"));
+    }
+    let (header_len, header_start) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 if bytes.len() >= 12 => (
+            u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize,
+            12,
+        ),
+        _ => return Err(invalid("unsupported version")),
+    };
+    let data_start = header_start + header_len;
+    let header = bytes
+        .get(header_start..data_start)
+        .and_then(|header| std::str::from_utf8(header).ok())
+        .ok_or_else(|| invalid("truncated header"))?;
+
+    let value_of = |key: &str| {
+        let start = header.find(&format!("'{key}':"))? + key.len() + 3;
+        Some(header[start..].trim_start())
+    };
+    if value_of("fortran_order").is_some_and(|value| value.starts_with("True")) {
+        return Err(invalid("Fortran order is not supported"));
+    }
+    let descr = value_of("descr")
+        .and_then(|value| value.strip_prefix('\'')?.split('\'').next())
+        .ok_or_else(|| invalid("missing descr"))?;
+    let shape: Vec<usize> = value_of("shape")
+        .and_then(|value| value.strip_prefix('(')?.split(')').next())
+        .ok_or_else(|| invalid("missing shape"))?
+        .split(',')
+        .map(str::trim)
+        .filter(|dimension| !dimension.is_empty())
+        .map(|dimension| dimension.parse().map_err(|_| invalid("bad shape")))
+        .collect::<Result<_, _>>()?;
+    let (rows, columns) = match shape[..] {
+        [columns] => (1, columns),
+        [rows, columns] => (rows, columns),
+        _ => return Err(invalid("expected 1 or 2 dimensions")),
+    };
+
+    let data = &bytes[data_start..];
+    let values: Vec<f32> = match descr {
+        "<f4" => data
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+        "<f8" => data
+            .chunks_exact(8)
+            .map(|b| f64::from_le_bytes(b.try_into().expect("8 bytes")) as f32)
+            .collect(),
+        other => {
+            return Err(invalid(&format!(
+                "unsupported dtype {other}, expected <f4 or <f8"
+            )))
+        }
+    };
+    if values.len() != rows * columns {
+        return Err(invalid("data does not match the shape"));
+    }
+    if columns == 0 {
+        return Ok(vec![Vec::new(); rows]);
+    }
+    Ok(values.chunks(columns).map(<[f32]>::to_vec).collect())
+}
+
+/// A zip archive storing `files` uncompressed, as NumPy writes with
+/// `numpy.savez`.
+fn stored_zip(files: &[(&str, Vec<u8>)]) -> Result<Vec<u8>, VoyageError> {
+    // 1980-01-01, the earliest date zip can express
+    const DOS_DATE: u16 = (1 << 5) | 1;
+    let too_large = || VoyageError::InterchangeError("Archive exceeds 4 GiB".to_string());
+
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for (name, data) in files {
+        let offset = u32::try_from(archive.len()).map_err(|_| too_large())?;
+        let size = u32::try_from(data.len()).map_err(|_| too_large())?;
+        let crc = crc32(data);
+        // Fields shared by the local header and the central directory entry:
+        // version needed, flags, method, time, date, crc, sizes, name length
+        let mut common = Vec::new();
+        common.extend_from_slice(&20u16.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+        common.extend_from_slice(&DOS_DATE.to_le_bytes());
+        common.extend_from_slice(&crc.to_le_bytes());
+        common.extend_from_slice(&size.to_le_bytes());
+        common.extend_from_slice(&size.to_le_bytes());
+        common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+
+        archive.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        archive.extend_from_slice(&common);
+        archive.extend_from_slice(&0u16.to_le_bytes());
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(data);
+
+        directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        directory.extend_from_slice(&20u16.to_le_bytes());
+        directory.extend_from_slice(&common);
+        // Extra field, comment, disk, internal and external attributes
+        directory.extend_from_slice(&[0; 12]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+    let directory_offset = u32::try_from(archive.len()).map_err(|_| too_large())?;
+    let entries = files.len() as u16;
+    archive.extend_from_slice(&directory);
+    archive.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    archive.extend_from_slice(&[0; 4]);
+    archive.extend_from_slice(&entries.to_le_bytes());
+    archive.extend_from_slice(&entries.to_le_bytes());
+    archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&directory_offset.to_le_bytes());
+    archive.extend_from_slice(&0u16.to_le_bytes());
+    Ok(archive)
+}
+
+/// CRC-32 (IEEE) checksum, as zip requires.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
@@ -0,0 +1,340 @@
+//! Parquet files of embeddings and their metadata, behind the `parquet`
+//! feature, for analytics with DuckDB, Polars or Spark.
+//!
+//! [`write_parquet`] writes one row per [`EmbeddingRecord`]:
+//!
+//! | column   | type                            |
+//! |----------|---------------------------------|
+//! | `id`     | `Utf8`                          |
+//! | `model`  | `Utf8`                          |
+//! | `vector` | `FixedSizeList<Float32>[dim]`   |
+//! | ...      | one nullable column per metadata key |
+//!
+//! Metadata columns are `Boolean`, `Float64` or `Utf8` after the values of
+//! their key; a key with values of several types becomes `Utf8`. Files are
+//! zstd compressed.
+//!
+//! [`read_parquet`] also accepts files written by other tools with `List`
+//! or `LargeList` vectors of `Float32` or `Float64`, and reads integer
+//! columns as numbers. Columns of other types are skipped.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use ::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use ::parquet::arrow::ArrowWriter;
+use ::parquet::basic::{Compression as ParquetCompression, ZstdLevel};
+use ::parquet::file::properties::WriterProperties;
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type,
+    UInt64Type, UInt8Type,
+};
+use arrow_array::{
+    Array, ArrayRef, BooleanArray, FixedSizeListArray, Float32Array, Float64Array, RecordBatch,
+    StringArray,
+};
+use arrow_schema::{DataType, Field, Schema};
+
+use super::{io_error, uniform_dimension, EmbeddingRecord};
+use crate::errors::VoyageError;
+use crate::models::metadata::{Metadata, MetadataValue};
+
+/// Rows per record batch, and so at most per row group.
+const BATCH_SIZE: usize = 8192;
+
+const RESERVED_COLUMNS: [&str; 3] = ["id", "model", "vector"];
+
+fn parquet_error(error: impl Display) -> VoyageError {
+    VoyageError::InterchangeError(error.to_string())
+}
+
+/// Arrow type of a metadata column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnType {
+    Boolean,
+    Float64,
+    Utf8,
+}
+
+impl ColumnType {
+    fn of(value: &MetadataValue) -> Self {
+        match value {
+            MetadataValue::Bool(_) => ColumnType::Boolean,
+            MetadataValue::Number(_) => ColumnType::Float64,
+            MetadataValue::String(_) => ColumnType::Utf8,
+        }
+    }
+
+    fn data_type(self) -> DataType {
+        match self {
+            ColumnType::Boolean => DataType::Boolean,
+            ColumnType::Float64 => DataType::Float64,
+            ColumnType::Utf8 => DataType::Utf8,
+        }
+    }
+}
+
+/// Writes `records`, with `metadata[i]` as the metadata of `records[i]`, to
+/// a Parquet file at `path`. All vectors must have the same dimension.
+/// Returns the number of rows written.
+pub fn write_parquet(
+    path: impl AsRef<Path>,
+    records: &[EmbeddingRecord],
+    metadata: Option<&[Metadata]>,
+) -> Result<usize, VoyageError> {
+    let path = path.as_ref();
+    if records.is_empty() {
+        return Err(VoyageError::InterchangeError(
+            "At least one record is required".to_string(),
+        ));
+    }
+    if let Some(metadata) = metadata {
+        if metadata.len() != records.len() {
+            return Err(VoyageError::InterchangeError(format!(
+                "{} metadata entries for {} records",
+                metadata.len(),
+                records.len()
+            )));
+        }
+    }
+    let vectors: Vec<&[f32]> = records.iter().map(|record| &record.vector[..]).collect();
+    let dimension = uniform_dimension(&vectors)?;
+
+    let mut columns: BTreeMap<&str, ColumnType> = BTreeMap::new();
+    for (key, value) in metadata.into_iter().flatten().flatten() {
+        if RESERVED_COLUMNS.contains(&key.as_str()) {
+            return Err(VoyageError::InterchangeError(format!(
+                "Metadata key `{key}` collides with a column name"
+            )));
+        }
+        let value_type = ColumnType::of(value);
+        columns
+            .entry(key)
+            .and_modify(|column| {
+                if *column != value_type {
+                    *column = ColumnType::Utf8;
+                }
+            })
+            .or_insert(value_type);
+    }
+
+    let item = Arc::new(Field::new("item", DataType::Float32, false));
+    let mut fields = vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("model", DataType::Utf8, false),
+        Field::new(
+            "vector",
+            DataType::FixedSizeList(item.clone(), dimension as i32),
+            false,
+        ),
+    ];
+    fields.extend(
+        columns
+            .iter()
+            .map(|(key, column)| Field::new(*key, column.data_type(), true)),
+    );
+    let schema = Arc::new(Schema::new(fields));
+
+    let file = File::create(path).map_err(|e| io_error(path, e))?;
+    let properties = WriterProperties::builder()
+        .set_compression(ParquetCompression::ZSTD(ZstdLevel::default()))
+        .build();
+    let mut writer =
+        ArrowWriter::try_new(file, schema.clone(), Some(properties)).map_err(parquet_error)?;
+    for (batch_index, batch) in records.chunks(BATCH_SIZE).enumerate() {
+        let offset = batch_index * BATCH_SIZE;
+        let batch_metadata = metadata.map(|metadata| &metadata[offset..offset + batch.len()]);
+
+        let mut arrays: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values(
+                batch.iter().map(|record| record.id.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                batch.iter().map(|record| record.model.as_str()),
+            )),
+            Arc::new(
+                FixedSizeListArray::try_new(
+                    item.clone(),
+                    dimension as i32,
+                    Arc::new(Float32Array::from_iter_values(
+                        batch
+                            .iter()
+                            .flat_map(|record| record.vector.iter().copied()),
+                    )),
+                    None,
+                )
+                .map_err(parquet_error)?,
+            ),
+        ];
+        for (key, column) in &columns {
+            let values = (0..batch.len())
+                .map(|row| batch_metadata.and_then(|metadata| metadata[row].get(*key)));
+            let array: ArrayRef = match column {
+                ColumnType::Boolean => Arc::new(
+                    values
+                        .map(|value| value.and_then(MetadataValue::as_bool))
+                        .collect::<BooleanArray>(),
+                ),
+                ColumnType::Float64 => Arc::new(
+                    values
+                        .map(|value| value.and_then(MetadataValue::as_f64))
+                        .collect::<Float64Array>(),
+                ),
+                ColumnType::Utf8 => Arc::new(
+                    values
+                        .map(|value| value.map(metadata_string))
+                        .collect::<StringArray>(),
+                ),
+            };
+            arrays.push(array);
+        }
+        let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(parquet_error)?;
+        writer.write(&batch).map_err(parquet_error)?;
+    }
+    writer.close().map_err(parquet_error)?;
+    Ok(records.len())
+}
+
+/// Reads the records and metadata of a Parquet file written by
+/// [`write_parquet`] or another tool, see the [module documentation](self).
+/// Null metadata values are left out; a missing `model` column reads as an
+/// empty model name.
+pub fn read_parquet(
+    path: impl AsRef<Path>,
+) -> Result<Vec<(EmbeddingRecord, Metadata)>, VoyageError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| io_error(path, e))?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .and_then(|builder| builder.with_batch_size(BATCH_SIZE).build())
+        .map_err(parquet_error)?;
+
+    let mut rows = Vec::new();
+    for batch in reader {
+        let batch = batch.map_err(parquet_error)?;
+        let column = |name: &str| {
+            batch
+                .column_by_name(name)
+                .ok_or_else(|| VoyageError::InterchangeError(format!("Missing `{name}` column")))
+        };
+        let ids = string_column(column("id")?, "id")?;
+        let models = match batch.column_by_name("model") {
+            Some(models) => string_column(models, "model")?,
+            None => vec![None; batch.num_rows()],
+        };
+        let vectors = column("vector")?;
+        let schema = batch.schema();
+        let metadata_columns: Vec<(&str, &ArrayRef)> = schema
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .filter(|(field, _)| !RESERVED_COLUMNS.contains(&field.name().as_str()))
+            .map(|(field, column)| (field.name().as_str(), column))
+            .collect();
+
+        for row in 0..batch.num_rows() {
+            let id = ids[row].clone().ok_or_else(|| {
+                VoyageError::InterchangeError(format!("Row {} has no id", rows.len()))
+            })?;
+            let vector = vector_at(vectors, row).ok_or_else(|| {
+                VoyageError::InterchangeError(format!("Row {id} has no readable vector"))
+            })?;
+            let metadata: Metadata = metadata_columns
+                .iter()
+                .filter_map(|(name, column)| {
+                    metadata_value(column, row).map(|value| (name.to_string(), value))
+                })
+                .collect();
+            let model = models[row].clone().unwrap_or_default();
+            rows.push((EmbeddingRecord::new(id, model, vector), metadata));
+        }
+    }
+    Ok(rows)
+}
+
+fn metadata_string(value: &MetadataValue) -> String {
+    match value {
+        MetadataValue::Bool(value) => value.to_string(),
+        MetadataValue::Number(value) => value.to_string(),
+        MetadataValue::String(value) => value.clone(),
+    }
+}
+
+fn string_column(array: &ArrayRef, name: &str) -> Result<Vec<Option<String>>, VoyageError> {
+    let strings = match array.data_type() {
+        DataType::Utf8 => array
+            .as_string::<i32>()
+            .iter()
+            .map(|value| value.map(str::to_string))
+            .collect(),
+        DataType::LargeUtf8 => array
+            .as_string::<i64>()
+            .iter()
+            .map(|value| value.map(str::to_string))
+            .collect(),
+        other => {
+            return Err(VoyageError::InterchangeError(format!(
+                "Column `{name}` has type {other}, expected a string type"
+            )))
+        }
+    };
+    Ok(strings)
+}
+
+/// The vector of `row` of a list column of floats.
+fn vector_at(array: &ArrayRef, row: usize) -> Option<Vec<f32>> {
+    if array.is_null(row) {
+        return None;
+    }
+    let values = match array.data_type() {
+        DataType::FixedSizeList(..) => array.as_fixed_size_list().value(row),
+        DataType::List(_) => array.as_list::<i32>().value(row),
+        DataType::LargeList(_) => array.as_list::<i64>().value(row),
+        _ => return None,
+    };
+    match values.data_type() {
+        DataType::Float32 => Some(values.as_primitive::<Float32Type>().values().to_vec()),
+        DataType::Float64 => Some(
+            values
+                .as_primitive::<Float64Type>()
+                .values()
+                .iter()
+                .map(|&value| value as f32)
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+fn metadata_value(array: &ArrayRef, row: usize) -> Option<MetadataValue> {
+    if array.is_null(row) {
+        return None;
+    }
+    macro_rules! number {
+        ($type:ty) => {
+            MetadataValue::Number(array.as_primitive::<$type>().value(row) as f64)
+        };
+    }
+    let value = match array.data_type() {
+        DataType::Boolean => MetadataValue::Bool(array.as_boolean().value(row)),
+        DataType::Utf8 => MetadataValue::String(array.as_string::<i32>().value(row).to_string()),
+        DataType::LargeUtf8 => {
+            MetadataValue::String(array.as_string::<i64>().value(row).to_string())
+        }
+        DataType::Float64 => number!(Float64Type),
+        DataType::Float32 => number!(Float32Type),
+        DataType::Int64 => number!(Int64Type),
+        DataType::Int32 => number!(Int32Type),
+        DataType::Int16 => number!(Int16Type),
+        DataType::Int8 => number!(Int8Type),
+        DataType::UInt64 => number!(UInt64Type),
+        DataType::UInt32 => number!(UInt32Type),
+        DataType::UInt16 => number!(UInt16Type),
+        DataType::UInt8 => number!(UInt8Type),
+        _ => return None,
+    };
+    Some(value)
+}
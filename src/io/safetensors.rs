@@ -0,0 +1,137 @@
+//! safetensors files, as loaded by `safetensors.torch.load_file`.
+//!
+//! [`write_safetensors`] stores the vectors of the records as one `F32`
+//! tensor `embeddings` of shape `[count, dim]`. The ids, as a JSON array, and
+//! the model name go in the file's string metadata under `ids` and `model`:
+//!
+//! ```python
+//! from safetensors import safe_open
+//! with safe_open("embeddings.safetensors", "pt") as f:
+//!     embeddings = f.get_tensor("embeddings")
+//!     ids = json.loads(f.metadata()["ids"])
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{io_error, uniform_dimension, EmbeddingRecord};
+use crate::errors::VoyageError;
+
+/// Name of the tensor holding the vectors.
+pub const EMBEDDINGS_TENSOR: &str = "embeddings";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TensorInfo {
+    dtype: String,
+    shape: Vec<usize>,
+    data_offsets: [usize; 2],
+}
+
+/// Writes `records` to `path`. All vectors must have the same dimension;
+/// the model of the first record is stored as the model of all.
+pub fn write_safetensors(
+    path: impl AsRef<Path>,
+    records: &[EmbeddingRecord],
+) -> Result<(), VoyageError> {
+    let path = path.as_ref();
+    let vectors: Vec<&[f32]> = records.iter().map(|record| &record.vector[..]).collect();
+    let dimension = uniform_dimension(&vectors)?;
+    let data_len = records.len() * dimension * 4;
+
+    let mut metadata = BTreeMap::new();
+    let ids: Vec<&str> = records.iter().map(|record| record.id.as_str()).collect();
+    metadata.insert("ids".to_string(), serde_json::to_string(&ids)?);
+    if let Some(record) = records.first() {
+        metadata.insert("model".to_string(), record.model.clone());
+    }
+    let mut header = serde_json::Map::new();
+    header.insert("__metadata__".to_string(), serde_json::to_value(metadata)?);
+    header.insert(
+        EMBEDDINGS_TENSOR.to_string(),
+        serde_json::to_value(TensorInfo {
+            dtype: "F32".to_string(),
+            shape: vec![records.len(), dimension],
+            data_offsets: [0, data_len],
+        })?,
+    );
+    let mut header = serde_json::to_string(&header)?;
+    // Pad with spaces so the data is 8-byte aligned
+    header.push_str(&" ".repeat((8 - header.len() % 8) % 8));
+
+    let mut bytes = Vec::with_capacity(8 + header.len() + data_len);
+    bytes.extend_from_slice(&(header.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    for value in records.iter().flat_map(|record| &record.vector) {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    fs::write(path, bytes).map_err(|e| io_error(path, e))
+}
+
+/// Reads records from a file written by [`write_safetensors`], or from any
+/// safetensors file with a 2-dimensional `F32` or `F64` tensor named
+/// `embeddings`, in which case ids default to row numbers.
+pub fn read_safetensors(path: impl AsRef<Path>) -> Result<Vec<EmbeddingRecord>, VoyageError> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|e| io_error(path, e))?;
+    let invalid =
+        |reason: &str| VoyageError::InterchangeError(format!("Invalid safetensors file: {reason}"));
+
+    let header_len = bytes
+        .get(..8)
+        .map(|len| u64::from_le_bytes(len.try_into().expect("8 bytes")) as usize)
+        .ok_or_else(|| invalid("truncated header"))?;
+    let data = bytes
+        .get(8 + header_len..)
+        .ok_or_else(|| invalid("truncated header"))?;
+    let mut header: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_slice(&bytes[8..8 + header_len])?;
+    let metadata: BTreeMap<String, String> = match header.remove("__metadata__") {
+        Some(metadata) => serde_json::from_value(metadata)?,
+        None => BTreeMap::new(),
+    };
+    let tensor: TensorInfo = serde_json::from_value(
+        header
+            .remove(EMBEDDINGS_TENSOR)
+            .ok_or_else(|| invalid("no `embeddings` tensor"))?,
+    )?;
+
+    let [rows, columns] = tensor.shape[..] else {
+        return Err(invalid("`embeddings` is not 2-dimensional"));
+    };
+    let [start, end] = tensor.data_offsets;
+    let values: Vec<f32> = match (tensor.dtype.as_str(), data.get(start..end)) {
+        ("F32", Some(data)) => data
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().expect("4 bytes")))
+            .collect(),
+        ("F64", Some(data)) => data
+            .chunks_exact(8)
+            .map(|b| f64::from_le_bytes(b.try_into().expect("8 bytes")) as f32)
+            .collect(),
+        (_, None) => return Err(invalid("data offsets out of bounds")),
+        (dtype, _) => return Err(invalid(&format!("unsupported dtype {dtype}"))),
+    };
+    if values.len() != rows * columns {
+        return Err(invalid("data does not match the shape"));
+    }
+
+    let ids: Vec<String> = match metadata.get("ids") {
+        Some(ids) => serde_json::from_str(ids)?,
+        None => (0..rows).map(|row| row.to_string()).collect(),
+    };
+    if ids.len() != rows {
+        return Err(invalid("number of ids does not match the tensor"));
+    }
+    let model = metadata.get("model").cloned().unwrap_or_default();
+    Ok(ids
+        .into_iter()
+        .enumerate()
+        .map(|(row, id)| {
+            let vector = values[row * columns..(row + 1) * columns].to_vec();
+            EmbeddingRecord::new(id, model.clone(), vector)
+        })
+        .collect())
+}
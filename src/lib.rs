@@ -4,38 +4,113 @@
 //! - Generate embeddings for text
 //! - Rerank documents based on relevance to a query
 //! - Search for documents using semantic search
+//! - Store embedded documents in a local, persistable vector index
+//! - Exchange embeddings with other tools as (compressed) NDJSON via `io`
+//! - Quantize embeddings to int8 or binary codes and rescore approximate matches
+//! - Pool chunk embeddings into document vectors with `vectors`
+//! - Classify texts from a few labeled examples with `classify::Classifier`
+//! - Build a chunk, embed, index, search and rerank pipeline with `RetrievalPipeline`
+//! - Answer questions over local files with the `quickstart` feature
+//! - Compose the endpoints with tower middleware as `tower::Service`s with the `tower` feature
+//! - Test without network access against a local `TestServer` with the `test-util` feature
+//!
+//! `use voyageai::prelude::*;` imports the commonly used types and traits; the
+//! main ones are also re-exported at the crate root.
 //! 
 
+pub mod analysis;
 pub mod builder;
+pub mod chunking;
+pub mod classify;
 pub mod client;
+pub mod code;
 pub mod config;
 pub mod errors;
+pub mod eval;
+pub mod index;
+pub mod io;
+pub mod logging;
+#[cfg(feature = "mcp")]
+pub mod mcp;
 pub mod models;
+pub mod prelude;
+pub mod quantization;
+#[cfg(feature = "quickstart")]
+pub mod quickstart;
+pub mod repl;
+pub mod retrieval;
+mod rng;
+#[cfg(feature = "serve")]
+pub mod server;
+#[cfg(feature = "tower")]
+pub mod service;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod text_splitter;
 pub mod traits;
+pub mod transport;
 pub mod utils;
+pub mod vectors;
+#[cfg(feature = "web")]
+pub mod web;
 
 pub use builder::{
     embeddings::EmbeddingsRequestBuilder, rerank::RerankRequestBuilder,
-    search::SearchRequestBuilder, voyage::VoyageBuilder,
+    search::SearchRequestBuilder, validation::{ValidationError, Violation}, voyage::VoyageBuilder,
 };
-pub use client::voyage_client::VoyageAiClient;
-pub use config::VoyageConfig;
-pub use errors::{VoyageBuilderError, VoyageError};
+pub use chunking::{Chunker, MarkdownChunker, ParagraphChunker};
+pub use text_splitter::{CharacterSplitter, SentenceSplitter, TextChunk, TextSplitter, TokenSplitter};
+pub use client::{
+    AutoBatcher, BulkEmbedder, DocumentSimilarity, LocalReranker, MultiQueryRetriever, Priority,
+    RankingStrategy,
+    RerankClient, SearchResult, VoyageAiClient,
+};
+pub use config::{ApiKeyProvider, VoyageConfig};
+pub use errors::{SimilarityError, VoyageBuilderError, VoyageError};
+pub use index::{VectorIndex, VectorStore};
 pub use models::{
-    embeddings::{EmbeddingModel, EmbeddingsInput, InputType},
-    rerank::{RerankModel, RerankRequest, RerankResponse},
-    search::{SearchModel, SearchType},
+    embedding::Embedding,
+    embeddings::{
+        EmbedOptions, EmbeddingModel, EmbeddingsInput, EmbeddingsRequest, EmbeddingsResponse,
+        EncodingFormat, IndexedEmbedding, InputType,
+    },
+    rerank::{RerankModel, RerankRequest, RerankResponse, RerankResult},
+    search::{ScoreNormalization, SearchModel, SearchType},
+};
+pub use retrieval::{ChunkOutlier, IngestionReport, RetrievalPipeline, ScoredChunk};
+#[cfg(feature = "repo")]
+pub use retrieval::RepositoryReport;
+pub use traits::{
+    llm::{Embedder, Reranker},
+    query::QueryPreprocessor,
+    scoring::Scorer,
 };
 
+/// Cosine similarity of `a` and `b`, or 0.0 when they are empty or differ in
+/// length. Prefer [`try_cosine_similarity`], which reports those cases.
+/// Neither can check that `a` and `b` come from the same model; compare
+/// [`Embedding`]s for that.
 pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
-    if a.is_empty() || b.is_empty() || a.len() != b.len() {
-        return 0.0;
+    try_cosine_similarity(a, b).unwrap_or(0.0)
+}
+
+/// Cosine similarity of `a` and `b`, failing when either is empty or their
+/// lengths differ. A zero vector has a similarity of 0.0 with everything.
+pub fn try_cosine_similarity(a: &[f32], b: &[f32]) -> Result<f32, SimilarityError> {
+    if a.is_empty() || b.is_empty() {
+        return Err(SimilarityError::Empty);
+    }
+    if a.len() != b.len() {
+        return Err(SimilarityError::DimensionMismatch {
+            left: a.len(),
+            right: b.len(),
+        });
     }
     let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
     let magnitude_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
     let magnitude_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
     if magnitude_a == 0.0 || magnitude_b == 0.0 {
-        return 0.0;
+        return Ok(0.0);
     }
-    dot_product / (magnitude_a * magnitude_b)
+    Ok(dot_product / (magnitude_a * magnitude_b))
 }
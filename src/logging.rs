@@ -0,0 +1,139 @@
+//! What the clients are allowed to write to the log.
+//!
+//! Request and response bodies can contain customer documents, so they are
+//! left out of log messages unless [`LogPolicy::log_payloads`] is enabled, and
+//! even then truncated. Bearer tokens and API keys are always redacted with
+//! [`redact`].
+
+use std::borrow::Cow;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Length of an API key's random part below which a `pa-` word is left alone.
+const MIN_KEY_CHARS: usize = 16;
+
+/// Controls how payloads appear in log messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogPolicy {
+    log_payloads: bool,
+    max_payload_chars: usize,
+}
+
+impl Default for LogPolicy {
+    fn default() -> Self {
+        Self {
+            log_payloads: false,
+            max_payload_chars: 1024,
+        }
+    }
+}
+
+impl LogPolicy {
+    /// Includes (redacted, truncated) payload bodies in log messages.
+    pub fn log_payloads(mut self, enabled: bool) -> Self {
+        self.log_payloads = enabled;
+        self
+    }
+
+    /// Longest payload excerpt logged when payload logging is enabled.
+    pub fn max_payload_chars(mut self, max: usize) -> Self {
+        self.max_payload_chars = max;
+        self
+    }
+
+    pub fn logs_payloads(&self) -> bool {
+        self.log_payloads
+    }
+
+    /// The form in which `body` may be logged: a size placeholder by default,
+    /// otherwise the redacted body truncated to `max_payload_chars`.
+    pub fn payload<'a>(&self, body: &'a str) -> Cow<'a, str> {
+        if !self.log_payloads {
+            return Cow::Owned(format!("<{} bytes omitted>", body.len()));
+        }
+        let redacted = redact(body);
+        match redacted.char_indices().nth(self.max_payload_chars) {
+            Some((end, _)) => Cow::Owned(format!(
+                "{}... <{} bytes truncated>",
+                &redacted[..end],
+                redacted.len() - end
+            )),
+            None => redacted,
+        }
+    }
+}
+
+/// Replaces bearer tokens and Voyage API keys (`pa-...`) in `text`.
+pub fn redact(text: &str) -> Cow<'_, str> {
+    let mut output = String::new();
+    let mut copied = 0;
+    let mut search = 0;
+
+    while search < text.len() {
+        let rest = &text[search..];
+        let bearer = find_ignore_ascii_case(rest, "bearer ").map(|i| (i, "bearer ".len()));
+        let key = find_key(rest).map(|i| (i, 0));
+        let (start, prefix) = match (bearer, key) {
+            (Some(b), Some(k)) => {
+                if b.0 <= k.0 {
+                    b
+                } else {
+                    k
+                }
+            }
+            (Some(found), None) | (None, Some(found)) => found,
+            (None, None) => break,
+        };
+        let secret_start = search + start + prefix;
+        let secret_len = text[secret_start..]
+            .find(|c: char| !is_token_char(c))
+            .unwrap_or(text.len() - secret_start);
+        if secret_len > 0 {
+            output.push_str(&text[copied..secret_start]);
+            output.push_str(REDACTED);
+            copied = secret_start + secret_len;
+        }
+        search = secret_start + secret_len.max(1);
+        while !text.is_char_boundary(search) {
+            search += 1;
+        }
+    }
+
+    if copied == 0 {
+        return Cow::Borrowed(text);
+    }
+    output.push_str(&text[copied..]);
+    Cow::Owned(output)
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '~' | '+' | '/' | '=')
+}
+
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Position of the next word that looks like a Voyage API key.
+fn find_key(text: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(i) = text[from..].find("pa-") {
+        let start = from + i;
+        let at_word_start = text[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !is_token_char(c));
+        let key_chars = text[start + 3..]
+            .chars()
+            .take_while(|c| is_token_char(*c))
+            .count();
+        if at_word_start && key_chars >= MIN_KEY_CHARS {
+            return Some(start);
+        }
+        from = start + 3;
+    }
+    None
+}
@@ -0,0 +1,396 @@
+//! [Model Context Protocol](https://modelcontextprotocol.io) server behind
+//! `voyageai mcp`, letting agents such as Claude Desktop call the client and a
+//! local index as tools. Enabled with the `mcp` feature.
+//!
+//! The server speaks JSON-RPC 2.0 over newline-delimited messages, usually the
+//! standard input and output of the process started by the agent. It offers
+//! the tools
+//!
+//! | tool              | arguments                                   | result                              |
+//! |-------------------|---------------------------------------------|-------------------------------------|
+//! | `semantic_search` | `{"query", "top_k"?, "rerank"?, "filter"?}` | `[{"id", "text", "score", ...}]`    |
+//! | `rerank`          | `{"query", "documents", "top_k"?}`          | `[{"index", "document", "score"}]`  |
+//! | `embed`           | `{"input", "input_type"?}`                  | `{"model", "embeddings"}`           |
+//!
+//! `semantic_search` is only offered with an index given to
+//! [`McpServer::with_index`]. Results are returned as JSON text content;
+//! failures of a tool, such as API errors, are returned as results with
+//! `isError` set, so that the agent can see them.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{self, AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::client::rerank_client::{rerank_scores, RerankRequestBuilder};
+use crate::client::voyage_client::VoyageAiClient;
+use crate::errors::VoyageError;
+use crate::index::VectorIndex;
+use crate::models::embeddings::InputType;
+use crate::models::filter::MetadataFilter;
+use crate::models::rerank::MAX_DOCUMENTS;
+
+/// The newest protocol revision implemented, answered to clients asking for
+/// one the server cannot speak. It also speaks 2024-11-05 and 2025-03-26.
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Protocol revisions the server can speak, oldest first.
+const PROTOCOL_VERSIONS: [&str; 3] = ["2024-11-05", "2025-03-26", PROTOCOL_VERSION];
+
+/// Hits returned by `semantic_search` without a `top_k`.
+const DEFAULT_SEARCH_TOP_K: usize = 10;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// An MCP server over a [`VoyageAiClient`] and an optional [`VectorIndex`].
+#[derive(Debug)]
+pub struct McpServer {
+    client: VoyageAiClient,
+    index: Option<VectorIndex>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TextInput {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbedArguments {
+    input: TextInput,
+    #[serde(default)]
+    input_type: Option<InputType>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RerankArguments {
+    query: String,
+    documents: Vec<String>,
+    #[serde(default)]
+    top_k: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchArguments {
+    query: String,
+    #[serde(default)]
+    top_k: Option<usize>,
+    #[serde(default)]
+    rerank: bool,
+    #[serde(default)]
+    filter: Option<MetadataFilter>,
+}
+
+/// A JSON-RPC error of a request, as opposed to a failure of a tool.
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl McpServer {
+    /// A server making requests with `client`'s configuration.
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self {
+            client: client.clone(),
+            index: None,
+        }
+    }
+
+    /// The index `semantic_search` answers from.
+    pub fn with_index(mut self, index: VectorIndex) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Answers the messages of `input`, one per line, on `output` until the
+    /// end of input.
+    pub async fn run(
+        &self,
+        input: impl AsyncBufRead + Unpin,
+        mut output: impl AsyncWrite + Unpin,
+    ) -> io::Result<()> {
+        let mut lines = input.lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle(&line).await {
+                output.write_all(response.as_bytes()).await?;
+                output.write_all(b"\n").await?;
+                output.flush().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Answers one JSON-RPC message, or a batch of them. Notifications have no
+    /// answer.
+    pub async fn handle(&self, message: &str) -> Option<String> {
+        let response = match serde_json::from_str::<Value>(message) {
+            Ok(Value::Array(batch)) if !batch.is_empty() => {
+                let mut responses = Vec::new();
+                for message in batch {
+                    responses.extend(self.handle_message(message).await);
+                }
+                if responses.is_empty() {
+                    return None;
+                }
+                Value::Array(responses)
+            }
+            Ok(message @ Value::Object(_)) => self.handle_message(message).await?,
+            Ok(_) => error_response(
+                Value::Null,
+                RpcError::new(INVALID_REQUEST, "Expected a JSON-RPC request object"),
+            ),
+            Err(e) => error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string())),
+        };
+        Some(response.to_string())
+    }
+
+    async fn handle_message(&self, message: Value) -> Option<Value> {
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // Responses to requests of ours, which are never sent
+            return None;
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let result = match method {
+            "initialize" => Ok(self.initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": self.tools() })),
+            "tools/call" => self.call_tool(params).await,
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method `{method}`"),
+            )),
+        };
+        // Notifications, such as `notifications/initialized`, carry no id
+        let id = message.get("id")?.clone();
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        let requested = params.get("protocolVersion").and_then(Value::as_str);
+        let version = requested
+            .filter(|version| PROTOCOL_VERSIONS.contains(version))
+            .unwrap_or(PROTOCOL_VERSION);
+        json!({
+            "protocolVersion": version,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "voyageai", "version": env!("CARGO_PKG_VERSION") },
+        })
+    }
+
+    fn tools(&self) -> Vec<Value> {
+        let mut tools = Vec::new();
+        if let Some(index) = &self.index {
+            tools.push(json!({
+                "name": "semantic_search",
+                "description": format!(
+                    "Search {} indexed chunks by meaning. Returns the best matches with their ids, texts and scores.",
+                    index.len()
+                ),
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string", "description": "What to search for" },
+                        "top_k": { "type": "integer", "minimum": 1, "description": "Number of matches, 10 by default" },
+                        "rerank": { "type": "boolean", "description": "Rerank the matches with a reranker model for better precision" },
+                        "filter": { "type": "object", "description": "Metadata filter the matches must satisfy" },
+                    },
+                    "required": ["query"],
+                },
+            }));
+        }
+        tools.push(json!({
+            "name": "rerank",
+            "description": "Order documents by relevance to a query. Returns the documents with their original indices and relevance scores, best first.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "documents": { "type": "array", "items": { "type": "string" } },
+                    "top_k": { "type": "integer", "minimum": 1, "description": "Number of documents to return" },
+                },
+                "required": ["query", "documents"],
+            },
+        }));
+        tools.push(json!({
+            "name": "embed",
+            "description": "Compute embedding vectors of one or more texts.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "input": {
+                        "oneOf": [
+                            { "type": "string" },
+                            { "type": "array", "items": { "type": "string" } },
+                        ],
+                    },
+                    "input_type": { "type": "string", "enum": ["query", "document"] },
+                },
+                "required": ["input"],
+            },
+        }));
+        tools
+    }
+
+    async fn call_tool(&self, params: Value) -> Result<Value, RpcError> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing tool name"))?;
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+        let output = match name {
+            "semantic_search" if self.index.is_some() => {
+                self.semantic_search(parse_arguments(arguments)?).await
+            }
+            "rerank" => self.rerank(parse_arguments(arguments)?).await,
+            "embed" => self.embed(parse_arguments(arguments)?).await,
+            _ => {
+                return Err(RpcError::new(
+                    INVALID_PARAMS,
+                    format!("Unknown tool `{name}`"),
+                ))
+            }
+        };
+        Ok(match output {
+            Ok(output) => json!({
+                "content": [{ "type": "text", "text": output.to_string() }],
+                "isError": false,
+            }),
+            Err(e) => json!({
+                "content": [{ "type": "text", "text": e.to_string() }],
+                "isError": true,
+            }),
+        })
+    }
+
+    async fn semantic_search(&self, arguments: SearchArguments) -> Result<Value, VoyageError> {
+        let Some(index) = &self.index else {
+            return Ok(json!([]));
+        };
+        let top_k = arguments.top_k.unwrap_or(DEFAULT_SEARCH_TOP_K);
+        let depth = if arguments.rerank {
+            MAX_DOCUMENTS.min(top_k.max(50))
+        } else {
+            top_k
+        };
+        let embedding = self
+            .client
+            .config
+            .embeddings_client
+            .embed_query(&arguments.query)
+            .await?;
+        let matches = index.search(&embedding, depth, arguments.filter.as_ref())?;
+        let mut hits: Vec<(f32, bool, Value)> = matches
+            .iter()
+            .map(|m| {
+                let mut hit = json!({
+                    "id": m.record.id,
+                    "text": m.record.text,
+                    "vector_score": m.score,
+                });
+                if let Some(metadata) = &m.record.metadata {
+                    hit["metadata"] = json!(metadata);
+                }
+                (m.score, false, hit)
+            })
+            .collect();
+
+        if arguments.rerank {
+            let documents = matches.iter().map(|m| m.record.text.clone()).collect();
+            let scores = rerank_scores(
+                self.client.config.rerank_client.as_ref(),
+                &arguments.query,
+                documents,
+            )
+            .await;
+            for (position, score) in scores {
+                hits[position].0 = score as f32;
+                hits[position].1 = true;
+            }
+            hits.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.total_cmp(&a.0)));
+        }
+        hits.truncate(top_k);
+        Ok(hits
+            .into_iter()
+            .map(|(score, reranked, mut hit)| {
+                hit["score"] = json!(score);
+                hit["reranked"] = json!(reranked);
+                hit
+            })
+            .collect())
+    }
+
+    async fn rerank(&self, arguments: RerankArguments) -> Result<Value, VoyageError> {
+        let mut request = RerankRequestBuilder::new()
+            .query(arguments.query)
+            .add_documents(arguments.documents.clone());
+        if let Some(top_k) = arguments.top_k {
+            request = request.top_k(top_k);
+        }
+        let response = self
+            .client
+            .config
+            .rerank_client
+            .rerank(request.build()?)
+            .await?;
+        Ok(response
+            .data
+            .iter()
+            .map(|result| {
+                json!({
+                    "index": result.index,
+                    "document": arguments.documents.get(result.index),
+                    "score": result.relevance_score,
+                })
+            })
+            .collect())
+    }
+
+    async fn embed(&self, arguments: EmbedArguments) -> Result<Value, VoyageError> {
+        let texts = match arguments.input {
+            TextInput::One(text) => vec![text],
+            TextInput::Many(texts) => texts,
+        };
+        let embeddings = self
+            .client
+            .config
+            .embeddings_client
+            .embed_all(&texts, arguments.input_type)
+            .await?;
+        Ok(json!({
+            "model": self.client.config.config.embedding_model.to_string(),
+            "embeddings": embeddings,
+        }))
+    }
+}
+
+fn parse_arguments<T: for<'de> Deserialize<'de>>(arguments: Value) -> Result<T, RpcError> {
+    serde_json::from_value(arguments)
+        .map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid arguments: {e}")))
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
@@ -0,0 +1,212 @@
+//! Request and response structures of the batch API: files of requests
+//! processed asynchronously, at a lower price than the synchronous
+//! endpoints.
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::VoyageError;
+use crate::models::embeddings::{EmbeddingModel, EmbeddingsResponse, InputType};
+
+/// Default time within which a batch is processed.
+pub const DEFAULT_COMPLETION_WINDOW: &str = "12h";
+
+/// Lifecycle state of a [`BatchJob`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    Validating,
+    InProgress,
+    Finalizing,
+    Completed,
+    Failed,
+    Cancelling,
+    Cancelled,
+    Expired,
+    /// A state this client does not know of
+    #[serde(other)]
+    Unknown,
+}
+
+impl BatchStatus {
+    /// Whether the job has stopped and its status will not change.
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            BatchStatus::Completed
+                | BatchStatus::Failed
+                | BatchStatus::Cancelled
+                | BatchStatus::Expired
+        )
+    }
+}
+
+/// Number of requests of a [`BatchJob`] by outcome.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchRequestCounts {
+    #[serde(default)]
+    pub total: u64,
+    #[serde(default)]
+    pub completed: u64,
+    #[serde(default)]
+    pub failed: u64,
+}
+
+/// A batch job as reported by the API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchJob {
+    pub id: String,
+    pub status: BatchStatus,
+    /// The endpoint the requests are sent to, e.g. `/v1/embeddings`
+    #[serde(default)]
+    pub endpoint: String,
+    #[serde(default)]
+    pub input_file_id: String,
+    /// File of responses, once the job completed
+    #[serde(default)]
+    pub output_file_id: Option<String>,
+    /// File of failed requests, if any failed
+    #[serde(default)]
+    pub error_file_id: Option<String>,
+    #[serde(default)]
+    pub completion_window: Option<String>,
+    #[serde(default)]
+    pub request_counts: BatchRequestCounts,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub completed_at: Option<String>,
+}
+
+/// Body of a request creating a batch job.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CreateBatchRequest {
+    pub endpoint: String,
+    pub input_file_id: String,
+    pub completion_window: String,
+    /// Parameters applied to every request of the file, e.g. the model
+    pub request_params: serde_json::Value,
+}
+
+/// Parameters of an embeddings batch, see [`CreateBatchRequest::embeddings`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EmbeddingsBatchParams {
+    pub model: EmbeddingModel,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_type: Option<InputType>,
+}
+
+impl CreateBatchRequest {
+    /// A request embedding the texts of the uploaded file `input_file_id`.
+    pub fn embeddings(input_file_id: impl Into<String>, params: EmbeddingsBatchParams) -> Self {
+        Self {
+            endpoint: "/v1/embeddings".to_string(),
+            input_file_id: input_file_id.into(),
+            completion_window: DEFAULT_COMPLETION_WINDOW.to_string(),
+            request_params: serde_json::to_value(params).unwrap_or_default(),
+        }
+    }
+}
+
+/// A file stored by the API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchFile {
+    pub id: String,
+    #[serde(default)]
+    pub filename: String,
+    #[serde(default)]
+    pub bytes: u64,
+    #[serde(default)]
+    pub purpose: String,
+}
+
+/// One line of a batch input file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchInputLine {
+    pub custom_id: String,
+    pub body: BatchInputBody,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchInputBody {
+    pub input: Vec<String>,
+}
+
+/// Writes `(custom_id, text)` pairs as a JSONL batch input file, one
+/// embeddings request per text.
+pub fn embeddings_batch_jsonl<I, S>(
+    inputs: impl IntoIterator<Item = (I, S)>,
+) -> Result<String, VoyageError>
+where
+    I: Into<String>,
+    S: Into<String>,
+{
+    let mut jsonl = String::new();
+    for (custom_id, text) in inputs {
+        let line = BatchInputLine {
+            custom_id: custom_id.into(),
+            body: BatchInputBody {
+                input: vec![text.into()],
+            },
+        };
+        jsonl.push_str(&serde_json::to_string(&line)?);
+        jsonl.push('\n');
+    }
+    Ok(jsonl)
+}
+
+/// One line of a batch output or error file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchOutputLine {
+    pub custom_id: String,
+    #[serde(default)]
+    pub response: Option<BatchOutputResponse>,
+    #[serde(default)]
+    pub error: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchOutputResponse {
+    #[serde(default)]
+    pub status_code: u16,
+    pub body: serde_json::Value,
+}
+
+/// The embedding of one input of an embeddings batch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchEmbedding {
+    pub custom_id: String,
+    pub embedding: Vec<f32>,
+}
+
+/// Parses the output file of an embeddings batch. Fails on the first line
+/// holding an error instead of an embedding.
+pub fn parse_embeddings_output(jsonl: &str) -> Result<Vec<BatchEmbedding>, VoyageError> {
+    let mut embeddings = Vec::new();
+    for line in jsonl.lines().filter(|line| !line.trim().is_empty()) {
+        let output: BatchOutputLine = serde_json::from_str(line)?;
+        let response = match (output.response, output.error) {
+            (Some(response), None) if response.status_code == 200 || response.status_code == 0 => {
+                response
+            }
+            (response, error) => {
+                let detail = error
+                    .or(response.map(|response| response.body))
+                    .map(|detail| detail.to_string())
+                    .unwrap_or_default();
+                return Err(VoyageError::BatchError(format!(
+                    "Request {} failed: {}",
+                    output.custom_id, detail
+                )));
+            }
+        };
+        let body: EmbeddingsResponse = serde_json::from_value(response.body)?;
+        let expected = body.data.len();
+        for embedding in body.into_embeddings(expected)? {
+            embeddings.push(BatchEmbedding {
+                custom_id: output.custom_id.clone(),
+                embedding,
+            });
+        }
+    }
+    Ok(embeddings)
+}
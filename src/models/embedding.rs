@@ -0,0 +1,176 @@
+//! An embedding that knows where it came from.
+//!
+//! A raw `Vec<f32>` from one model compared with one from another, or of a
+//! different dimension, gives a meaningless score. [`Embedding`] records the
+//! producing model, its dimension and whether it has unit length, and its
+//! comparisons fail with a [`VectorError`] instead of returning a wrong
+//! number.
+//!
+//! The check is opt-in: only the APIs taking an [`Embedding`] apply it, such
+//! as [`Embedding::cosine_similarity`] and
+//! [`VectorIndex::insert_embedding`](crate::VectorIndex::insert_embedding)/
+//! [`search_embedding`](crate::VectorIndex::search_embedding). The APIs
+//! taking a plain `Vec<f32>` or `&[f32]`, such as
+//! [`Embedder`](crate::Embedder), `embed_query`,
+//! [`SearchRequest`](crate::client::SearchRequest),
+//! [`VectorIndex::insert`](crate::VectorIndex::insert)/[`search`](crate::VectorIndex::search)
+//! and [`cosine_similarity`](crate::cosine_similarity), cannot tell models
+//! apart and do not check them.
+//!
+//! ```
+//! use voyageai::Embedding;
+//! let cats = Embedding::new(vec![0.6, 0.8], "voyage-3-large");
+//! let dogs = Embedding::new(vec![0.8, 0.6], "voyage-3-large");
+//! assert!(cats.cosine_similarity(&dogs).unwrap() > 0.9);
+//!
+//! let code = Embedding::new(vec![0.6, 0.8], "voyage-code-3");
+//! assert!(cats.cosine_similarity(&code).is_err());
+//! ```
+
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+use crate::vectors::{check_dimensions, norm, normalize, VectorError};
+
+/// How far from 1 the norm of an embedding may be for it to count as unit
+/// length.
+const UNIT_NORM_TOLERANCE: f32 = 1e-3;
+
+/// An embedding vector with the name of the model that produced it.
+///
+/// Dereferences to `[f32]`, so it can be passed wherever a slice is expected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StoredEmbedding")]
+pub struct Embedding {
+    values: Vec<f32>,
+    model: Option<String>,
+    #[serde(skip)]
+    normalized: bool,
+}
+
+impl Embedding {
+    /// An embedding produced by `model`, e.g. `"voyage-3-large"`.
+    pub fn new(values: Vec<f32>, model: impl Into<String>) -> Self {
+        Self::with_model(values, Some(model.into()))
+    }
+
+    /// An embedding of unknown origin. It is compared with any other
+    /// embedding of the same dimension.
+    pub fn unlabeled(values: Vec<f32>) -> Self {
+        Self::with_model(values, None)
+    }
+
+    fn with_model(values: Vec<f32>, model: Option<String>) -> Self {
+        let normalized = (norm(&values) - 1.0).abs() <= UNIT_NORM_TOLERANCE;
+        Self {
+            values,
+            model,
+            normalized,
+        }
+    }
+
+    /// The model that produced the embedding, if known.
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the embedding has unit L2 norm, as the API's embeddings do.
+    pub fn is_normalized(&self) -> bool {
+        self.normalized
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        &self.values
+    }
+
+    pub fn into_vec(self) -> Vec<f32> {
+        self.values
+    }
+
+    /// The embedding scaled to unit length; a zero vector is left as is.
+    pub fn normalized(mut self) -> Self {
+        if !self.normalized {
+            normalize(&mut self.values);
+            self.normalized = norm(&self.values) > 0.0;
+        }
+        self
+    }
+
+    /// Checks that `other` can be compared with this embedding: both have
+    /// the same dimension, and the same model when both models are known.
+    pub fn check_compatible(&self, other: &Embedding) -> Result<(), VectorError> {
+        if let (Some(expected), Some(actual)) = (self.model(), other.model()) {
+            if expected != actual {
+                return Err(VectorError::ModelMismatch {
+                    expected: expected.to_string(),
+                    actual: actual.to_string(),
+                });
+            }
+        }
+        check_dimensions(self, other)?;
+        Ok(())
+    }
+
+    /// Cosine similarity with `other`, failing when the two embeddings come
+    /// from different models or have different dimensions. Zero vectors
+    /// have a similarity of 0.
+    pub fn cosine_similarity(&self, other: &Embedding) -> Result<f32, VectorError> {
+        self.check_compatible(other)?;
+        let dot: f32 = self
+            .values
+            .iter()
+            .zip(&other.values)
+            .map(|(a, b)| a * b)
+            .sum();
+        if self.normalized && other.normalized {
+            return Ok(dot);
+        }
+        let norms = norm(self) * norm(other);
+        Ok(if norms == 0.0 { 0.0 } else { dot / norms })
+    }
+}
+
+/// The serialized fields of an [`Embedding`]; whether it is normalized is
+/// recomputed when it is read back.
+#[derive(Deserialize)]
+struct StoredEmbedding {
+    values: Vec<f32>,
+    model: Option<String>,
+}
+
+impl From<StoredEmbedding> for Embedding {
+    fn from(stored: StoredEmbedding) -> Self {
+        Self::with_model(stored.values, stored.model)
+    }
+}
+
+impl Deref for Embedding {
+    type Target = [f32];
+
+    fn deref(&self) -> &[f32] {
+        &self.values
+    }
+}
+
+impl AsRef<[f32]> for Embedding {
+    fn as_ref(&self) -> &[f32] {
+        &self.values
+    }
+}
+
+impl From<Vec<f32>> for Embedding {
+    fn from(values: Vec<f32>) -> Self {
+        Self::unlabeled(values)
+    }
+}
+
+impl From<Embedding> for Vec<f32> {
+    fn from(embedding: Embedding) -> Self {
+        embedding.values
+    }
+}
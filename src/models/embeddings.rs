@@ -1,6 +1,8 @@
+use crate::models::embedding::Embedding;
+use crate::models::response_headers::ResponseMetadata;
+use crate::utils::MarkdownChunk;
 use crate::VoyageError;
 use serde::{Deserialize, Serialize};
-use std::convert::TryFrom;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InputType {
@@ -21,19 +23,27 @@ pub enum EmbeddingsInput {
     Multiple(Vec<String>),
 }
 
-impl TryFrom<&[String]> for EmbeddingsInput {
-    type Error = VoyageError;
-    fn try_from(v: &[String]) -> Result<Self, Self::Error> {
-        Ok(EmbeddingsInput::Multiple(v.to_vec()))
+impl From<&[String]> for EmbeddingsInput {
+    fn from(v: &[String]) -> Self {
+        EmbeddingsInput::Multiple(v.to_vec())
     }
 }
 
-impl TryFrom<Vec<&str>> for EmbeddingsInput {
-    type Error = VoyageError;
-    fn try_from(v: Vec<&str>) -> Result<Self, Self::Error> {
-        Ok(EmbeddingsInput::Multiple(
-            v.into_iter().map(String::from).collect(),
-        ))
+impl From<&[&str]> for EmbeddingsInput {
+    fn from(v: &[&str]) -> Self {
+        EmbeddingsInput::Multiple(v.iter().map(|s| s.to_string()).collect())
+    }
+}
+
+impl From<Vec<&str>> for EmbeddingsInput {
+    fn from(v: Vec<&str>) -> Self {
+        EmbeddingsInput::Multiple(v.into_iter().map(String::from).collect())
+    }
+}
+
+impl From<&String> for EmbeddingsInput {
+    fn from(s: &String) -> Self {
+        EmbeddingsInput::Single(s.clone())
     }
 }
 
@@ -67,7 +77,7 @@ pub struct EmbeddingsRequest {
     pub encoding_format: Option<EncodingFormat>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct EmbeddingsResponse {
     /// The type of object returned.
     #[serde(default)]
@@ -79,23 +89,145 @@ pub struct EmbeddingsResponse {
     pub model: String,
     /// Usage statistics for the request.
     pub usage: Usage,
+    /// Request ID, version, deprecation and rate limit information reported
+    /// in the response headers.
+    #[serde(skip)]
+    pub headers: ResponseMetadata,
+}
+
+impl EmbeddingsResponse {
+    /// Checks that the response holds at least one embedding and that every
+    /// embedding has the dimension produced by `model`.
+    pub fn validate(&self, model: EmbeddingModel) -> Result<(), VoyageError> {
+        if self.data.is_empty() {
+            return Err(VoyageError::EmptyResponse);
+        }
+        let expected = model.embedding_dimension();
+        for data in &self.data {
+            if data.embedding.len() != expected {
+                return Err(VoyageError::EmbeddingDimensionMismatch {
+                    index: data.index,
+                    expected,
+                    actual: data.embedding.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the embeddings ordered by their `index`, which refers to the
+    /// position of the input text in the request. The API does not guarantee
+    /// that `data` is in input order.
+    ///
+    /// Fails if an index is out of range or duplicated, or if any of the
+    /// `expected` inputs has no embedding.
+    pub fn into_embeddings(self, expected: usize) -> Result<Vec<Vec<f32>>, VoyageError> {
+        let mut slots: Vec<Option<Vec<f32>>> = vec![None; expected];
+        for data in self.data {
+            match slots.get_mut(data.index) {
+                Some(slot @ None) => *slot = Some(data.embedding),
+                _ => return Err(VoyageError::UnexpectedEmbeddingIndex { index: data.index }),
+            }
+        }
+        slots
+            .into_iter()
+            .enumerate()
+            .map(|(index, slot)| slot.ok_or(VoyageError::MissingEmbedding { index }))
+            .collect()
+    }
+
+    /// Like [`into_embeddings`](Self::into_embeddings), with each embedding
+    /// labeled with `model`, the model of the request.
+    pub fn into_typed_embeddings(
+        self,
+        expected: usize,
+        model: EmbeddingModel,
+    ) -> Result<Vec<Embedding>, VoyageError> {
+        let model = model.to_string();
+        Ok(self
+            .into_embeddings(expected)?
+            .into_iter()
+            .map(|values| Embedding::new(values, model.as_str()))
+            .collect())
+    }
+}
+
+impl EmbeddingsInput {
+    /// Number of texts in the input.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Single(_) => 1,
+            Self::Multiple(texts) => texts.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 /// Usage statistics for an embedding request.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Usage {
     /// The total number of tokens used in the request.
     pub total_tokens: u32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddingData {
     pub object: String,
+    /// Sent as an array of floats, or as base64-encoded little-endian `f32`s
+    /// when requested with [`EncodingFormat::Base64`]
+    #[serde(deserialize_with = "deserialize_embedding")]
     pub embedding: Vec<f32>,
     pub index: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+fn deserialize_embedding<'de, D>(deserializer: D) -> Result<Vec<f32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use base64::Engine;
+    use serde::de::Error;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Encoded {
+        Float(Vec<f32>),
+        Base64(String),
+    }
+
+    match Encoded::deserialize(deserializer)? {
+        Encoded::Float(embedding) => Ok(embedding),
+        Encoded::Base64(encoded) => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map_err(D::Error::custom)?;
+            if bytes.len() % 4 != 0 {
+                return Err(D::Error::custom(format!(
+                    "base64 embedding of {} bytes is not a sequence of f32s",
+                    bytes.len()
+                )));
+            }
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect())
+        }
+    }
+}
+
+/// An embedding tagged with the position of its input text, as yielded by
+/// streaming APIs that may complete out of order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedEmbedding {
+    pub index: usize,
+    pub embedding: Vec<f32>,
+}
+
+/// How the API encodes the returned embeddings. Both decode to the same
+/// `Vec<f32>`; `Base64` makes responses about a third smaller.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum EncodingFormat {
     #[serde(rename = "float")]
     Float,
@@ -103,6 +235,53 @@ pub enum EncodingFormat {
     Base64,
 }
 
+/// Per-call settings of [`Embedder::embed_with`] and
+/// [`Embedder::embed_batch_with`]. Settings left at `None` keep the
+/// embedder's default.
+///
+/// [`Embedder::embed_with`]: crate::traits::llm::Embedder::embed_with
+/// [`Embedder::embed_batch_with`]: crate::traits::llm::Embedder::embed_batch_with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmbedOptions {
+    pub input_type: Option<InputType>,
+    /// Whether the API truncates texts over the model's context length
+    /// instead of rejecting them; it does by default.
+    pub truncation: Option<bool>,
+    pub encoding_format: Option<EncodingFormat>,
+}
+
+impl EmbedOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn input_type(mut self, input_type: InputType) -> Self {
+        self.input_type = Some(input_type);
+        self
+    }
+
+    pub fn truncation(mut self, truncation: bool) -> Self {
+        self.truncation = Some(truncation);
+        self
+    }
+
+    pub fn encoding_format(mut self, encoding_format: EncodingFormat) -> Self {
+        self.encoding_format = Some(encoding_format);
+        self
+    }
+
+    /// A request embedding `input` with `model` and these options.
+    pub(crate) fn request(self, input: EmbeddingsInput, model: EmbeddingModel) -> EmbeddingsRequest {
+        EmbeddingsRequest {
+            input,
+            model,
+            input_type: self.input_type,
+            truncation: self.truncation,
+            encoding_format: self.encoding_format,
+        }
+    }
+}
+
 /// Supported embedding models by VoyageAI
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum EmbeddingModel {
@@ -129,10 +308,19 @@ impl EmbeddingModel {
         }
     }
 
+    /// Returns whether the model accepts `input_type`. Code and AST inputs
+    /// require the code model.
+    pub fn supports_input_type(&self, input_type: InputType) -> bool {
+        match input_type {
+            InputType::Query | InputType::Document => true,
+            InputType::Code | InputType::Ast => matches!(self, Self::VoyageCode3),
+        }
+    }
+
     /// Returns the embedding dimension for the model
     pub fn embedding_dimension(&self) -> usize {
         match self {
-            Self::Voyage3Large => 2048,
+            Self::Voyage3Large => 1024,
             Self::VoyageCode3 => 1024,
         }
     }
@@ -147,8 +335,28 @@ impl std::fmt::Display for EmbeddingModel {
     }
 }
 
-pub struct CodeEmbedding {
-    pub text_embedding: Vec<f32>,
-    pub ast_embedding: Vec<f32>,
+impl std::str::FromStr for EmbeddingModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "voyage-3-large" => Ok(Self::Voyage3Large),
+            "voyage-code-3" => Ok(Self::VoyageCode3),
+            other => Err(format!("unsupported embedding model: {other}")),
+        }
+    }
+}
+
+/// Moved to [`crate::code`]; re-exported here for compatibility.
+pub use crate::code::CodeEmbedding;
+
+/// The embedding of a chunk of a Markdown document, from
+/// [`embed_markdown_document`](crate::client::embeddings_client::Client::embed_markdown_document).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownChunkEmbedding {
+    /// The chunk, with its heading breadcrumb and code languages
+    pub chunk: MarkdownChunk,
+    /// Embedding of the chunk's text preceded by its breadcrumb
+    pub embedding: Vec<f32>,
 }
 
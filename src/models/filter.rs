@@ -0,0 +1,104 @@
+use crate::models::metadata::{Metadata, MetadataValue};
+use serde::{Deserialize, Serialize};
+
+/// Filter expression evaluated against document metadata.
+///
+/// Documents without metadata only match filters that do not reference a key
+/// (such as an empty `And`), or the negation of filters that do.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataFilter {
+    /// The value at `key` equals `value`
+    Eq { key: String, value: MetadataValue },
+    /// The numeric value at `key` lies within the inclusive bounds
+    Range {
+        key: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<f64>,
+    },
+    /// The value at `key` equals one of `values`
+    In {
+        key: String,
+        values: Vec<MetadataValue>,
+    },
+    /// Every filter matches
+    And(Vec<MetadataFilter>),
+    /// At least one filter matches
+    Or(Vec<MetadataFilter>),
+    /// The filter does not match
+    Not(Box<MetadataFilter>),
+}
+
+impl MetadataFilter {
+    pub fn eq(key: impl Into<String>, value: impl Into<MetadataValue>) -> Self {
+        MetadataFilter::Eq {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+
+    pub fn range(key: impl Into<String>, min: Option<f64>, max: Option<f64>) -> Self {
+        MetadataFilter::Range {
+            key: key.into(),
+            min,
+            max,
+        }
+    }
+
+    pub fn in_set<I, V>(key: impl Into<String>, values: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<MetadataValue>,
+    {
+        MetadataFilter::In {
+            key: key.into(),
+            values: values.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn and(self, other: MetadataFilter) -> Self {
+        match self {
+            MetadataFilter::And(mut filters) => {
+                filters.push(other);
+                MetadataFilter::And(filters)
+            }
+            filter => MetadataFilter::And(vec![filter, other]),
+        }
+    }
+
+    pub fn or(self, other: MetadataFilter) -> Self {
+        match self {
+            MetadataFilter::Or(mut filters) => {
+                filters.push(other);
+                MetadataFilter::Or(filters)
+            }
+            filter => MetadataFilter::Or(vec![filter, other]),
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        MetadataFilter::Not(Box::new(self))
+    }
+
+    /// Returns true if the given metadata satisfies the filter.
+    pub fn matches(&self, metadata: Option<&Metadata>) -> bool {
+        let lookup = |key: &str| metadata.and_then(|metadata| metadata.get(key));
+        match self {
+            MetadataFilter::Eq { key, value } => lookup(key) == Some(value),
+            MetadataFilter::Range { key, min, max } => lookup(key)
+                .and_then(MetadataValue::as_f64)
+                .is_some_and(|number| {
+                    min.is_none_or(|min| number >= min) && max.is_none_or(|max| number <= max)
+                }),
+            MetadataFilter::In { key, values } => {
+                lookup(key).is_some_and(|value| values.contains(value))
+            }
+            MetadataFilter::And(filters) => filters.iter().all(|filter| filter.matches(metadata)),
+            MetadataFilter::Or(filters) => filters.iter().any(|filter| filter.matches(metadata)),
+            MetadataFilter::Not(filter) => !filter.matches(metadata),
+        }
+    }
+}
@@ -1,11 +1,26 @@
 pub mod ast;
+pub mod batch;
+pub mod embedding;
 pub mod embeddings;
+pub mod filter;
+pub mod metadata;
 pub mod model_type;
+pub mod request_id;
 pub mod rerank;
+pub mod response_headers;
 pub mod search;
+pub mod tokens;
 pub mod usage;
 
-pub use embeddings::{EmbeddingModel, EmbeddingsInput, InputType};
+pub use batch::{BatchJob, BatchStatus};
+pub use embedding::Embedding;
+pub use embeddings::{EmbeddingModel, EmbeddingsInput, IndexedEmbedding, InputType};
+pub use filter::MetadataFilter;
+pub use metadata::{Metadata, MetadataValue};
 pub use model_type::ModelType;
+pub use request_id::RequestId;
 pub use rerank::{RerankModel, RerankRequest, RerankResponse};
-pub use search::{SearchModel, SearchType};
+#[allow(deprecated)]
+pub use response_headers::ResponseHeaders;
+pub use response_headers::{RateLimitHeaders, ResponseMetadata};
+pub use search::{ScoreNormalization, SearchModel, SearchType};
@@ -0,0 +1,84 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Header carrying the [`RequestId`] of a request, so that the server and
+/// any proxy in between can tell a retry from a new request.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Identifies one logical API request across all its attempts.
+///
+/// With [idempotency keys](crate::config::VoyageConfig::with_idempotency_keys)
+/// enabled, the clients generate one per call, send it in the
+/// [`IDEMPOTENCY_KEY_HEADER`] of every retry of the call, and return it in
+/// [`ResponseMetadata::idempotency_key`](super::ResponseMetadata::idempotency_key)
+/// of the response or error, so that logs and usage records of the retries
+/// can be matched up. Keys are random UUIDv4 strings.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// A new random id.
+    pub fn new() -> Self {
+        let (high, low) = (random_u64(), random_u64());
+        // Version 4, variant 1
+        let high = (high & !0xf000) | 0x4000;
+        let low = (low & !(0b11 << 62)) | (0b10 << 62);
+        Self(format!(
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            high >> 32,
+            (high >> 16) & 0xffff,
+            high & 0xffff,
+            low >> 48,
+            low & 0xffff_ffff_ffff
+        ))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// An id chosen by the caller, e.g. one derived from a job id so that a
+/// request replayed after a crash carries the same key.
+impl From<String> for RequestId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+impl From<&str> for RequestId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+/// 64 random bits from the randomly keyed hasher of the standard library,
+/// mixed with a counter and the time so that no two calls repeat.
+fn random_u64() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    hasher.write_u128(nanos);
+    hasher.finish()
+}
@@ -1,15 +1,93 @@
+use crate::errors::VoyageError;
+use crate::models::response_headers::ResponseMetadata;
+use crate::models::tokens::estimate_tokens;
 use serde::{Deserialize, Serialize};
 
-const MAX_DOCUMENTS: usize = 100;
+/// Most documents any rerank model accepts in one request; the limit of a
+/// given model is [`RerankModel::max_documents`].
+pub const MAX_DOCUMENTS: usize = 1000;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RerankResponse {
     #[serde(default)]
     pub object: String,
+    /// The results, most relevant first; at most `top_k` of them
     pub data: Vec<RerankResult>,
+    /// The model echoed by the server
     #[serde(default)]
     pub model: String,
     pub usage: Usage,
+    /// Number of documents sent for reranking, of which `data` holds the
+    /// best. Not reported by the API; set by the client.
+    #[serde(default)]
+    pub total_documents: usize,
+    /// Request ID, version, deprecation and rate limit information reported
+    /// in the response headers.
+    #[serde(skip)]
+    pub headers: ResponseMetadata,
+}
+
+impl RerankResponse {
+    /// Checks that every result refers to a distinct one of the
+    /// `document_count` documents sent, so results can be mapped back to
+    /// their documents by `index`.
+    pub fn validate(&self, document_count: usize) -> Result<(), VoyageError> {
+        let mut seen = vec![false; document_count];
+        for result in &self.data {
+            match seen.get_mut(result.index) {
+                Some(seen @ false) => *seen = true,
+                Some(true) => {
+                    return Err(VoyageError::MalformedResponse(format!(
+                        "rerank result index {} is duplicated",
+                        result.index
+                    )))
+                }
+                None => {
+                    return Err(VoyageError::MalformedResponse(format!(
+                        "rerank result index {} is out of range for {} documents",
+                        result.index, document_count
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The results, most relevant first.
+    pub fn results(&self) -> &[RerankResult] {
+        &self.data
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Number of documents sent for reranking, which may exceed
+    /// [`len`](Self::len) when `top_k` was set.
+    pub fn total_documents(&self) -> usize {
+        self.total_documents
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The results on page `page` (from 0) of `per_page` results, empty past
+    /// the last page.
+    pub fn page(&self, page: usize, per_page: usize) -> &[RerankResult] {
+        let start = page.saturating_mul(per_page).min(self.data.len());
+        let end = start.saturating_add(per_page).min(self.data.len());
+        &self.data[start..end]
+    }
+
+    /// Number of pages of `per_page` results.
+    pub fn page_count(&self, per_page: usize) -> usize {
+        self.data.len().div_ceil(per_page.max(1))
+    }
 }
 
 /// Represents one of the input documents after reranking, including its relevance score
@@ -18,7 +96,7 @@ pub struct RerankResponse {
 /// The reranking operation takes a list of documents and returns them ordered by
 /// relevance to the query, with scores attached. Each RerankResult corresponds to
 /// one of the input documents.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RerankResult {
     /// Relevance score from 0.0 to 1.0, where higher scores indicate
     /// greater relevance to the query
@@ -34,55 +112,136 @@ pub struct RerankResult {
 }
 
 impl RerankResult {
+    pub fn relevance_score(&self) -> f64 {
+        self.relevance_score
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The document text, when the request set `return_documents`.
+    pub fn document(&self) -> Option<&str> {
+        self.document.as_deref()
+    }
+
     /// Returns true if this result's relevance score exceeds the given threshold
     pub fn is_relevant(&self, threshold: f64) -> bool {
         self.relevance_score >= threshold
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[derive(Default)]
+/// Supported rerank models by VoyageAI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub enum RerankModel {
+    #[serde(rename = "rerank-2.5")]
+    Rerank25,
+    #[serde(rename = "rerank-2.5-lite")]
+    Rerank25Lite,
     #[serde(rename = "rerank-2")]
     #[default]
     Rerank2,
+    #[serde(rename = "rerank-2-lite")]
+    Rerank2Lite,
 }
 
 impl RerankModel {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rerank25 => "rerank-2.5",
+            Self::Rerank25Lite => "rerank-2.5-lite",
+            Self::Rerank2 => "rerank-2",
+            Self::Rerank2Lite => "rerank-2-lite",
+        }
+    }
+
+    /// Maximum number of tokens in the query and any one document combined
     pub const fn max_context_length(&self) -> usize {
         match self {
-            Self::Rerank2 => 16000,
+            Self::Rerank25 | Self::Rerank25Lite => 32_000,
+            Self::Rerank2 => 16_000,
+            Self::Rerank2Lite => 8_000,
         }
     }
 
-    pub const fn embedding_size(&self) -> usize {
+    /// Maximum number of tokens in the query
+    pub const fn max_query_tokens(&self) -> usize {
+        match self {
+            Self::Rerank25 | Self::Rerank25Lite => 8_000,
+            Self::Rerank2 => 4_000,
+            Self::Rerank2Lite => 2_000,
+        }
+    }
+
+    /// Maximum number of documents in one request
+    pub const fn max_documents(&self) -> usize {
         match self {
-            Self::Rerank2 => 768,
+            Self::Rerank25 | Self::Rerank25Lite | Self::Rerank2 | Self::Rerank2Lite => {
+                MAX_DOCUMENTS
+            }
         }
     }
+
+    #[deprecated(note = "rerank models score documents and do not produce embeddings")]
+    pub const fn embedding_size(&self) -> usize {
+        768
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl std::fmt::Display for RerankModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for RerankModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rerank-2.5" => Ok(Self::Rerank25),
+            "rerank-2.5-lite" => Ok(Self::Rerank25Lite),
+            "rerank-2" => Ok(Self::Rerank2),
+            "rerank-2-lite" => Ok(Self::Rerank2Lite),
+            other => Err(format!("unsupported rerank model: {other}")),
+        }
+    }
+}
+
+/// Tokens billed for a rerank request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Usage {
     pub total_tokens: u32,
 }
 
-#[derive(Debug, thiserror::Error)]
+/// A reason a rerank request cannot be sent.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[non_exhaustive]
-pub enum ValidationError {
+pub enum RerankValidationError {
+    #[error("query is required")]
+    MissingQuery,
     #[error("documents cannot be empty")]
     EmptyDocuments,
-    #[error("documents cannot contain more than {MAX_DOCUMENTS} items")]
-    TooManyDocuments,
+    #[error("{count} documents exceed the limit of {max}")]
+    TooManyDocuments { count: usize, max: usize },
+    #[error("query has ~{tokens} tokens, exceeding the limit of {max}")]
+    QueryTooLong { tokens: usize, max: usize },
+    #[error(
+        "query and document {index} have ~{tokens} tokens, exceeding the context window of {max}"
+    )]
+    DocumentTooLong { index: usize, tokens: usize, max: usize },
 }
 
+#[deprecated(note = "renamed to `RerankValidationError`")]
+pub type ValidationError = RerankValidationError;
+
 /// Request to rerank a set of documents based on their relevance to a query.
 #[derive(Debug, Serialize)]
 pub struct RerankRequest {
     /// The query text to compare documents against
     pub query: String,
     /// The collection of documents to be reranked by relevance to the query.
-    /// Maximum 100 documents. Each document will appear exactly once in the
+    /// At most [`RerankModel::max_documents`]. Each document will appear exactly once in the
     /// response, but ordered by relevance score.
     #[serde(with = "validate_documents")]
     pub documents: Vec<String>,
@@ -92,6 +251,14 @@ pub struct RerankRequest {
     /// If set, only returns the top K most relevant documents.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_k: Option<usize>,
+    /// Whether the API echoes each document's text in its result, see
+    /// [`RerankResult::document`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_documents: Option<bool>,
+    /// Results scoring below this are dropped from the response by the
+    /// client. Not sent to the API.
+    #[serde(skip)]
+    pub min_score: Option<f64>,
 }
 
 impl RerankRequest {
@@ -100,20 +267,75 @@ impl RerankRequest {
         documents: Vec<String>,
         model: RerankModel,
         top_k: Option<usize>,
-    ) -> Result<Self, ValidationError> {
-        if documents.is_empty() {
-            return Err(ValidationError::EmptyDocuments);
-        }
-        if documents.len() > MAX_DOCUMENTS {
-            return Err(ValidationError::TooManyDocuments);
+    ) -> Result<Self, RerankValidationError> {
+        if let Some(error) = Self::validate(&query, &documents, model).into_iter().next() {
+            return Err(error);
         }
         Ok(Self {
             query,
             documents,
             model,
             top_k,
+            return_documents: None,
+            min_score: None,
         })
     }
+
+    /// Asks the API to echo each document's text in its result.
+    pub fn with_return_documents(mut self, return_documents: bool) -> Self {
+        self.return_documents = Some(return_documents);
+        self
+    }
+
+    /// Drops the results with a relevance score below `min_score`.
+    pub fn with_min_score(mut self, min_score: f64) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+}
+
+impl RerankRequest {
+    /// Checks a query and documents against the limits of `model`, returning
+    /// every problem found. Token counts are local estimates.
+    pub fn validate(
+        query: &str,
+        documents: &[String],
+        model: RerankModel,
+    ) -> Vec<RerankValidationError> {
+        let mut errors = Vec::new();
+
+        if query.trim().is_empty() {
+            errors.push(RerankValidationError::MissingQuery);
+        }
+        let query_tokens = estimate_tokens(query);
+        if query_tokens > model.max_query_tokens() {
+            errors.push(RerankValidationError::QueryTooLong {
+                tokens: query_tokens,
+                max: model.max_query_tokens(),
+            });
+        }
+
+        if documents.is_empty() {
+            errors.push(RerankValidationError::EmptyDocuments);
+        }
+        if documents.len() > model.max_documents() {
+            errors.push(RerankValidationError::TooManyDocuments {
+                count: documents.len(),
+                max: model.max_documents(),
+            });
+        }
+        for (index, document) in documents.iter().enumerate() {
+            let tokens = query_tokens + estimate_tokens(document);
+            if tokens > model.max_context_length() {
+                errors.push(RerankValidationError::DocumentTooLong {
+                    index,
+                    tokens,
+                    max: model.max_context_length(),
+                });
+            }
+        }
+        errors
+    }
 }
 
 mod validate_documents {
@@ -0,0 +1,161 @@
+use log::warn;
+use reqwest::header::HeaderMap;
+use std::time::Duration;
+
+use super::request_id::RequestId;
+
+/// Header in which the server reports the API version that served a request.
+pub const API_VERSION_HEADER: &str = "x-api-version";
+
+/// Headers in which the server reports the ID of a request, in order of
+/// preference.
+pub const REQUEST_ID_HEADERS: [&str; 2] = ["x-request-id", "request-id"];
+
+/// Metadata reported by the server in the HTTP headers of a response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseMetadata {
+    /// ID the server assigned to the request, to quote when reporting issues
+    pub request_id: Option<String>,
+    /// API version that served the request
+    pub api_version: Option<String>,
+    /// `Deprecation` header: the endpoint or version is deprecated
+    pub deprecation: Option<String>,
+    /// `Sunset` header: when the endpoint or version will be removed
+    pub sunset: Option<String>,
+    /// `Warning` header, often used for deprecation notices
+    pub warning: Option<String>,
+    /// Quota the server reported for the API key
+    pub rate_limit: RateLimitHeaders,
+    /// Idempotency key the request was sent with, the same for all its
+    /// retries; `None` unless
+    /// [enabled](crate::config::VoyageConfig::with_idempotency_keys)
+    pub idempotency_key: Option<RequestId>,
+}
+
+/// Renamed to [`ResponseMetadata`].
+#[deprecated(note = "renamed to `ResponseMetadata`")]
+pub type ResponseHeaders = ResponseMetadata;
+
+/// The `x-ratelimit-*` headers of a response. Fields are `None` when the
+/// server did not send the header or its value could not be parsed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitHeaders {
+    /// `x-ratelimit-limit-requests`: requests allowed per window
+    pub limit_requests: Option<u32>,
+    /// `x-ratelimit-remaining-requests`: requests left in the window
+    pub remaining_requests: Option<u32>,
+    /// `x-ratelimit-reset-requests`: until the request quota is replenished
+    pub reset_requests: Option<Duration>,
+    /// `x-ratelimit-limit-tokens`: tokens allowed per window
+    pub limit_tokens: Option<u32>,
+    /// `x-ratelimit-remaining-tokens`: tokens left in the window
+    pub remaining_tokens: Option<u32>,
+    /// `x-ratelimit-reset-tokens`: until the token quota is replenished
+    pub reset_tokens: Option<Duration>,
+}
+
+impl RateLimitHeaders {
+    /// Extracts the rate limit state from response headers.
+    pub fn from_header_map(headers: &HeaderMap) -> Self {
+        let get = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        let count = |name: &str| get(name).and_then(|value| value.trim().parse().ok());
+        let reset = |name: &str| get(name).and_then(parse_reset);
+        Self {
+            limit_requests: count("x-ratelimit-limit-requests"),
+            remaining_requests: count("x-ratelimit-remaining-requests"),
+            reset_requests: reset("x-ratelimit-reset-requests"),
+            limit_tokens: count("x-ratelimit-limit-tokens"),
+            remaining_tokens: count("x-ratelimit-remaining-tokens"),
+            reset_tokens: reset("x-ratelimit-reset-tokens"),
+        }
+    }
+
+    /// Returns true if the server sent any rate limit header.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns true if the server reported no requests or no tokens left.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining_requests == Some(0) || self.remaining_tokens == Some(0)
+    }
+
+    /// How long until every exhausted quota is replenished, if known.
+    pub fn reset_in(&self) -> Option<Duration> {
+        let requests = self
+            .reset_requests
+            .filter(|_| self.remaining_requests == Some(0));
+        let tokens = self
+            .reset_tokens
+            .filter(|_| self.remaining_tokens == Some(0));
+        requests.max(tokens)
+    }
+}
+
+/// Parses a reset time given in seconds ("1.5") or as a duration with units
+/// ("1m30s", "250ms").
+fn parse_reset(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(seconds).ok();
+    }
+    let mut total = Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .filter(|&end| end > 0)?;
+        let number: f64 = rest[..number_end].parse().ok()?;
+        rest = &rest[number_end..];
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let seconds = match &rest[..unit_end] {
+            "h" => number * 3600.0,
+            "m" => number * 60.0,
+            "s" => number,
+            "ms" => number / 1000.0,
+            _ => return None,
+        };
+        total += Duration::try_from_secs_f64(seconds).ok()?;
+        rest = &rest[unit_end..];
+    }
+    Some(total)
+}
+
+impl ResponseMetadata {
+    /// Extracts the request ID, version, deprecation and rate limit
+    /// information from response headers.
+    pub fn from_header_map(headers: &HeaderMap) -> Self {
+        let get = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            request_id: REQUEST_ID_HEADERS.iter().find_map(|name| get(name)),
+            api_version: get(API_VERSION_HEADER),
+            deprecation: get("deprecation"),
+            sunset: get("sunset"),
+            warning: get("warning"),
+            rate_limit: RateLimitHeaders::from_header_map(headers),
+            idempotency_key: None,
+        }
+    }
+
+    /// Returns true if the server flagged the request as using a deprecated API.
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecation.is_some() || self.sunset.is_some()
+    }
+
+    /// Logs a warning when the server reports a deprecation.
+    pub fn warn_if_deprecated(&self, endpoint: &str) {
+        if self.is_deprecated() {
+            warn!(
+                "Voyage API reports {} as deprecated (deprecation: {:?}, sunset: {:?}, warning: {:?}, request id: {:?})",
+                endpoint, self.deprecation, self.sunset, self.warning, self.request_id
+            );
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::models::metadata::Metadata;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -15,6 +16,87 @@ pub enum SearchModel {
     Custom(String),
 }
 
+/// How [`SearchClient`](crate::client::search_client::SearchClient) rescales
+/// the scores of the results it returns, so that scores of different search
+/// types can be compared and thresholded. Every method keeps the order of the
+/// results. Nearest-neighbor scores are distances, lower being better, and
+/// stay so with [`None`](Self::None); the other methods negate them first, so
+/// the nearest result gets the best normalized score and higher is better
+/// for every search type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreNormalization {
+    /// Scores are left as computed
+    #[default]
+    None,
+    /// Scaled to `[0, 1]`: the best result scores 1 and the worst 0. Equal
+    /// scores all become 1.
+    MinMax,
+    /// Softmax over the results: positive, summing to 1
+    Softmax,
+    /// Standard scores: the number of standard deviations from the mean
+    /// score. Equal scores all become 0.
+    ZScore,
+}
+
+impl ScoreNormalization {
+    /// Rescales `scores` in place.
+    pub fn normalize(self, scores: &mut [f32]) {
+        if scores.is_empty() {
+            return;
+        }
+        match self {
+            Self::None => {}
+            Self::MinMax => {
+                let min = scores.iter().copied().fold(f32::INFINITY, f32::min);
+                let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let range = max - min;
+                for score in scores.iter_mut() {
+                    *score = if range > 0.0 {
+                        (*score - min) / range
+                    } else {
+                        1.0
+                    };
+                }
+            }
+            Self::Softmax => {
+                let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let mut total = 0.0;
+                for score in scores.iter_mut() {
+                    *score = (*score - max).exp();
+                    total += *score;
+                }
+                scores.iter_mut().for_each(|score| *score /= total);
+            }
+            Self::ZScore => {
+                let count = scores.len() as f32;
+                let mean = scores.iter().sum::<f32>() / count;
+                let variance = scores.iter().map(|s| (s - mean).powi(2)).sum::<f32>() / count;
+                let deviation = variance.sqrt();
+                for score in scores.iter_mut() {
+                    *score = if deviation > 0.0 {
+                        (*score - mean) / deviation
+                    } else {
+                        0.0
+                    };
+                }
+            }
+        }
+    }
+
+    /// Rescales the scores of `results` in place.
+    pub fn apply(self, results: &mut [SearchResult]) {
+        if self == Self::None {
+            return;
+        }
+        let mut scores: Vec<f32> = results.iter().map(|result| result.score).collect();
+        self.normalize(&mut scores);
+        for (result, score) in results.iter_mut().zip(scores) {
+            result.score = score;
+        }
+    }
+}
+
 impl SearchModel {
     pub fn as_str(&self) -> &str {
         match self {
@@ -51,12 +133,52 @@ pub struct SearchResponse {
     pub estimated_usage: EstimatedUsage,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct SearchResult {
     pub document: Vec<String>,
-    pub score: i32,
+    pub score: f32,
     pub index: usize,
     pub search_type: SearchType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
+}
+
+impl SearchResult {
+    pub fn new(document: Vec<String>, score: f32, index: usize, search_type: SearchType) -> Self {
+        Self {
+            document,
+            score,
+            index,
+            search_type,
+            metadata: None,
+        }
+    }
+
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    pub fn document(&self) -> &[String] {
+        &self.document
+    }
+
+    pub fn score(&self) -> f32 {
+        self.score
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn search_type(&self) -> &SearchType {
+        &self.search_type
+    }
+
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata.as_ref()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,15 +220,9 @@ use std::cmp::Ordering;
 
 impl PartialOrd for SearchResult {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for SearchResult {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match self.score.cmp(&other.score) {
-            ord @ (Ordering::Less | Ordering::Greater) => ord,
-            Ordering::Equal => self.index.cmp(&other.index),
+        match self.score.total_cmp(&other.score) {
+            ord @ (Ordering::Less | Ordering::Greater) => Some(ord),
+            Ordering::Equal => Some(self.index.cmp(&other.index)),
         }
     }
 }
@@ -0,0 +1,11 @@
+//! Local token count estimates.
+//!
+//! Voyage models use subword tokenizers that are not available offline. The
+//! estimate of one token per four bytes of UTF-8 errs on the high side for
+//! English prose, which makes it suitable for rejecting requests that would
+//! exceed a model limit before they are sent.
+
+/// Estimated number of tokens in `text`.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
@@ -0,0 +1,40 @@
+//! Convenience re-exports for the common case.
+//!
+//! ```
+//! use voyageai::prelude::*;
+//! ```
+//!
+//! brings the client and its configuration, the request builders, the
+//! `Embedder`/`Reranker` family of traits, the request and result types and
+//! the model enums into scope. Everything here is also reachable from its
+//! defining module.
+
+pub use crate::builder::{
+    embeddings::EmbeddingsRequestBuilder, rerank::RerankRequestBuilder,
+    search::SearchRequestBuilder, voyage::VoyageBuilder,
+};
+pub use crate::chunking::{Chunker, MarkdownChunker, ParagraphChunker};
+pub use crate::client::{
+    AutoBatcher, BulkEmbedder, DocumentSimilarity, LocalReranker, MultiQueryRetriever, Priority,
+    RankingStrategy,
+    RerankClient, SearchResult, VoyageAiClient,
+};
+pub use crate::config::{ApiKeyProvider, VoyageConfig};
+pub use crate::{cosine_similarity, try_cosine_similarity};
+pub use crate::errors::{SimilarityError, VoyageBuilderError, VoyageError};
+pub use crate::index::{VectorIndex, VectorStore};
+pub use crate::models::{
+    embedding::Embedding,
+    embeddings::{
+        EmbedOptions, EmbeddingModel, EmbeddingsInput, EmbeddingsRequest, EmbeddingsResponse,
+        EncodingFormat, IndexedEmbedding, InputType,
+    },
+    rerank::{RerankModel, RerankRequest, RerankResponse, RerankResult},
+    search::{ScoreNormalization, SearchModel, SearchType},
+};
+pub use crate::retrieval::{IngestionReport, RetrievalPipeline, ScoredChunk};
+pub use crate::traits::{
+    llm::{Embedder, Reranker},
+    query::QueryPreprocessor,
+    scoring::Scorer,
+};
@@ -0,0 +1,315 @@
+//! Compact embedding representations.
+//!
+//! Mirrors the quantized `output_dtype` options of the Voyage API:
+//!
+//! - [`Int8Embedding`]: one signed byte per dimension with a per-vector scale,
+//!   4x smaller than `f32` with almost no loss in ranking quality.
+//! - [`BinaryEmbedding`]: one bit per dimension (the sign), packed eight to a
+//!   byte, 32x smaller and compared with Hamming distance.
+//!
+//! Quantized similarity is a fast first pass; [`QuantizedIndex`] over-fetches
+//! candidates with it and rescores them against the full-precision vectors to
+//! recover exact ordering at the top of the ranking.
+
+use crate::cosine_similarity;
+use crate::errors::VoyageError;
+
+fn check_dimension(expected: usize, actual: usize) -> Result<(), VoyageError> {
+    if expected != actual {
+        return Err(VoyageError::SearchDimensionMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+/// An embedding quantized to `i8` with symmetric per-vector scaling:
+/// `value ≈ values[i] as f32 * scale`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Int8Embedding {
+    pub values: Vec<i8>,
+    pub scale: f32,
+}
+
+impl Int8Embedding {
+    pub fn quantize(embedding: &[f32]) -> Self {
+        let max_abs = embedding
+            .iter()
+            .fold(0.0f32, |max, value| max.max(value.abs()));
+        let scale = if max_abs > 0.0 { max_abs / 127.0 } else { 1.0 };
+        let values = embedding
+            .iter()
+            .map(|value| (value / scale).round().clamp(-127.0, 127.0) as i8)
+            .collect();
+        Self { values, scale }
+    }
+
+    /// Wraps `int8` values returned by the API, which are already scaled.
+    pub fn from_values(values: Vec<i8>) -> Self {
+        Self { values, scale: 1.0 }
+    }
+
+    /// Approximate reconstruction of the original embedding.
+    pub fn dequantize(&self) -> Vec<f32> {
+        self.values
+            .iter()
+            .map(|value| *value as f32 * self.scale)
+            .collect()
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Dot product computed in integer arithmetic, rescaled to `f32`.
+    pub fn dot(&self, other: &Self) -> Result<f32, VoyageError> {
+        check_dimension(self.dimension(), other.dimension())?;
+        let dot: i32 = self
+            .values
+            .iter()
+            .zip(&other.values)
+            .map(|(a, b)| *a as i32 * *b as i32)
+            .sum();
+        Ok(dot as f32 * self.scale * other.scale)
+    }
+
+    /// Cosine similarity computed in integer arithmetic. Scales cancel out.
+    pub fn cosine_similarity(&self, other: &Self) -> Result<f32, VoyageError> {
+        check_dimension(self.dimension(), other.dimension())?;
+        let (mut dot, mut norm_a, mut norm_b) = (0i64, 0i64, 0i64);
+        for (a, b) in self.values.iter().zip(&other.values) {
+            let (a, b) = (*a as i64, *b as i64);
+            dot += a * b;
+            norm_a += a * a;
+            norm_b += b * b;
+        }
+        if norm_a == 0 || norm_b == 0 {
+            return Ok(0.0);
+        }
+        Ok(dot as f32 / ((norm_a as f32).sqrt() * (norm_b as f32).sqrt()))
+    }
+}
+
+/// An embedding reduced to its sign bits: bit `i` is set when dimension `i` is
+/// positive. Bits are packed most significant first, matching the API's
+/// `ubinary` output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryEmbedding {
+    bits: Vec<u8>,
+    dimension: usize,
+}
+
+impl BinaryEmbedding {
+    pub fn quantize(embedding: &[f32]) -> Self {
+        let mut bits = vec![0u8; embedding.len().div_ceil(8)];
+        for (i, value) in embedding.iter().enumerate() {
+            if *value > 0.0 {
+                bits[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        Self {
+            bits,
+            dimension: embedding.len(),
+        }
+    }
+
+    /// Wraps packed bits returned by the API (`ubinary`) for an embedding of
+    /// `dimension` dimensions.
+    pub fn from_packed(bits: Vec<u8>, dimension: usize) -> Result<Self, VoyageError> {
+        check_dimension(dimension.div_ceil(8), bits.len())?;
+        Ok(Self { bits, dimension })
+    }
+
+    /// Converts the API's signed `binary` output, which is `ubinary` offset by -128.
+    pub fn from_signed_packed(bits: &[i8], dimension: usize) -> Result<Self, VoyageError> {
+        let bits = bits.iter().map(|byte| (*byte as i16 + 128) as u8).collect();
+        Self::from_packed(bits, dimension)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bits
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Number of dimensions whose signs differ.
+    pub fn hamming_distance(&self, other: &Self) -> Result<u32, VoyageError> {
+        check_dimension(self.dimension, other.dimension)?;
+        Ok(self
+            .bits
+            .iter()
+            .zip(&other.bits)
+            .map(|(a, b)| (a ^ b).count_ones())
+            .sum())
+    }
+
+    /// Fraction of dimensions whose signs agree, in `[0, 1]`.
+    pub fn similarity(&self, other: &Self) -> Result<f32, VoyageError> {
+        let distance = self.hamming_distance(other)?;
+        if self.dimension == 0 {
+            return Ok(0.0);
+        }
+        Ok(1.0 - distance as f32 / self.dimension as f32)
+    }
+}
+
+/// Precision used by a [`QuantizedIndex`] for its first-pass search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quantization {
+    #[default]
+    Int8,
+    Binary,
+}
+
+#[derive(Debug, Clone)]
+enum Codes {
+    Int8(Vec<Int8Embedding>),
+    Binary(Vec<BinaryEmbedding>),
+}
+
+/// A search result from [`QuantizedIndex::search`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuantizedMatch {
+    /// Position of the embedding in insertion order
+    pub index: usize,
+    /// Cosine similarity against the full-precision vector when rescored,
+    /// otherwise the quantized similarity
+    pub score: f32,
+}
+
+/// Embeddings searched in quantized form, with optional rescoring against the
+/// full-precision vectors.
+///
+/// Keeping the full-precision vectors is optional: without them the index only
+/// holds the compact codes and [`QuantizedIndex::search`] returns quantized scores.
+#[derive(Debug, Clone)]
+pub struct QuantizedIndex {
+    dimension: usize,
+    codes: Codes,
+    full_precision: Option<Vec<Vec<f32>>>,
+    oversample: usize,
+}
+
+impl QuantizedIndex {
+    pub fn new(quantization: Quantization) -> Self {
+        let codes = match quantization {
+            Quantization::Int8 => Codes::Int8(Vec::new()),
+            Quantization::Binary => Codes::Binary(Vec::new()),
+        };
+        Self {
+            dimension: 0,
+            codes,
+            full_precision: None,
+            oversample: 4,
+        }
+    }
+
+    /// Keeps the full-precision vectors so that search results can be rescored.
+    pub fn with_rescoring(mut self) -> Self {
+        self.full_precision.get_or_insert_with(Vec::new);
+        self
+    }
+
+    /// How many candidates per requested result the first pass retrieves
+    /// before rescoring. Higher values trade latency for recall.
+    pub fn with_oversample(mut self, oversample: usize) -> Self {
+        self.oversample = oversample.max(1);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.codes {
+            Codes::Int8(codes) => codes.len(),
+            Codes::Binary(codes) => codes.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Approximate memory used by the codes and, if kept, the full-precision vectors.
+    pub fn memory_bytes(&self) -> usize {
+        let codes: usize = match &self.codes {
+            Codes::Int8(codes) => codes.iter().map(|code| code.values.len() + 4).sum(),
+            Codes::Binary(codes) => codes.iter().map(|code| code.bits.len()).sum(),
+        };
+        let full = self
+            .full_precision
+            .as_ref()
+            .map_or(0, |vectors| vectors.iter().map(|v| v.len() * 4).sum());
+        codes + full
+    }
+
+    /// Adds an embedding and returns its position.
+    pub fn push(&mut self, embedding: &[f32]) -> Result<usize, VoyageError> {
+        if self.is_empty() {
+            self.dimension = embedding.len();
+        }
+        check_dimension(self.dimension, embedding.len())?;
+        match &mut self.codes {
+            Codes::Int8(codes) => codes.push(Int8Embedding::quantize(embedding)),
+            Codes::Binary(codes) => codes.push(BinaryEmbedding::quantize(embedding)),
+        }
+        if let Some(full_precision) = &mut self.full_precision {
+            full_precision.push(embedding.to_vec());
+        }
+        Ok(self.len() - 1)
+    }
+
+    /// Returns the `top_k` embeddings most similar to `query`, best first.
+    pub fn search(&self, query: &[f32], top_k: usize) -> Result<Vec<QuantizedMatch>, VoyageError> {
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        check_dimension(self.dimension, query.len())?;
+
+        let candidates = match self.full_precision {
+            Some(_) => top_k.saturating_mul(self.oversample),
+            None => top_k,
+        };
+        let mut matches: Vec<QuantizedMatch> = match &self.codes {
+            Codes::Int8(codes) => {
+                let query = Int8Embedding::quantize(query);
+                codes
+                    .iter()
+                    .enumerate()
+                    .map(|(index, code)| {
+                        let score = query.cosine_similarity(code)?;
+                        Ok(QuantizedMatch { index, score })
+                    })
+                    .collect::<Result<_, VoyageError>>()?
+            }
+            Codes::Binary(codes) => {
+                let query = BinaryEmbedding::quantize(query);
+                codes
+                    .iter()
+                    .enumerate()
+                    .map(|(index, code)| {
+                        let score = query.similarity(code)?;
+                        Ok(QuantizedMatch { index, score })
+                    })
+                    .collect::<Result<_, VoyageError>>()?
+            }
+        };
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.index.cmp(&b.index)));
+        matches.truncate(candidates);
+
+        if let Some(full_precision) = &self.full_precision {
+            rescore(query, &mut matches, full_precision);
+        }
+        matches.truncate(top_k);
+        Ok(matches)
+    }
+}
+
+/// Replaces the scores of `matches` with the cosine similarity between `query`
+/// and the corresponding full-precision vectors, and re-sorts them.
+pub fn rescore(query: &[f32], matches: &mut [QuantizedMatch], full_precision: &[Vec<f32>]) {
+    for m in matches.iter_mut() {
+        if let Some(vector) = full_precision.get(m.index) {
+            m.score = cosine_similarity(query, vector);
+        }
+    }
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.index.cmp(&b.index)));
+}
@@ -0,0 +1,266 @@
+//! "Ask your docs" reference pipeline.
+//!
+//! [`Quickstart::ask`] ingests a file or directory, embeds it into a
+//! [`VectorStore`], retrieves candidates with hybrid (vector + BM25) search,
+//! reranks them and assembles the best passages into a context block that can
+//! be handed to an LLM. It exercises every subsystem of the crate end to end and
+//! doubles as the shortest path from an API key to useful retrieval.
+//!
+//! Enabled with the `quickstart` feature.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use log::{debug, info};
+
+use crate::builder::search::SearchRequestBuilder;
+use crate::chunking::{Chunker, ParagraphChunker};
+use crate::client::embeddings_client::Client as EmbeddingsClient;
+use crate::client::multi_query::{reciprocal_rank_fusion, DEFAULT_RRF_K};
+use crate::client::rerank_client::{rerank_scores, DefaultRerankClient};
+use crate::client::search_client::SearchClient;
+use crate::client::voyage_client::VoyageAiClient;
+use crate::errors::VoyageError;
+use crate::index::{CollectionConfig, VectorStore};
+use crate::models::rerank::MAX_DOCUMENTS;
+use crate::models::search::{SearchModel, SearchType};
+use crate::utils::collect_text_files;
+
+/// Maximum number of texts per embeddings request.
+const EMBED_BATCH_SIZE: usize = 128;
+
+/// A passage of an ingested file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Passage {
+    /// File the passage was read from
+    pub source: PathBuf,
+    pub text: String,
+    /// Relevance score: the rerank score when available, otherwise the fused
+    /// hybrid search score
+    pub score: f32,
+}
+
+/// Result of [`Quickstart::ask`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answer {
+    pub question: String,
+    /// The most relevant passages, best first
+    pub passages: Vec<Passage>,
+    /// Passages formatted as a single block of context, within the character budget
+    pub context: String,
+}
+
+#[derive(Debug, Clone)]
+struct Chunk {
+    source: PathBuf,
+    text: String,
+}
+
+/// End-to-end retrieval over local files.
+#[derive(Debug)]
+pub struct Quickstart {
+    embeddings_client: Arc<EmbeddingsClient>,
+    rerank_client: Arc<DefaultRerankClient>,
+    store: VectorStore,
+    corpora: HashMap<String, Vec<Chunk>>,
+    chunk_chars: usize,
+    candidates: usize,
+    top_k: usize,
+    context_chars: usize,
+}
+
+impl Quickstart {
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self {
+            embeddings_client: client.config.embeddings_client.clone(),
+            rerank_client: client.config.rerank_client.clone(),
+            store: VectorStore::new(),
+            corpora: HashMap::new(),
+            chunk_chars: 1200,
+            candidates: 20,
+            top_k: 5,
+            context_chars: 6000,
+        }
+    }
+
+    /// Maximum size of a passage in characters.
+    pub fn chunk_chars(mut self, chunk_chars: usize) -> Self {
+        self.chunk_chars = chunk_chars.max(1);
+        self
+    }
+
+    /// Number of candidates taken from each retriever before reranking.
+    pub fn candidates(mut self, candidates: usize) -> Self {
+        self.candidates = candidates.clamp(1, MAX_DOCUMENTS);
+        self
+    }
+
+    /// Number of passages returned in the answer.
+    pub fn top_k(mut self, top_k: usize) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    /// Maximum size of the assembled context in characters.
+    pub fn context_chars(mut self, context_chars: usize) -> Self {
+        self.context_chars = context_chars;
+        self
+    }
+
+    /// Answers `question` from the files at `path`, ingesting them on first use.
+    pub async fn ask(
+        &mut self,
+        path: impl AsRef<Path>,
+        question: &str,
+    ) -> Result<Answer, VoyageError> {
+        let collection = self.ingest(path.as_ref()).await?;
+        let chunks = &self.corpora[&collection];
+
+        // Vector retrieval
+        let query_embedding = self.embeddings_client.embed(question).await?;
+        let vector_ranking: Vec<usize> = self
+            .store
+            .search(&collection, &query_embedding, self.candidates, None)?
+            .into_iter()
+            .filter_map(|m| m.record.id.parse().ok())
+            .collect();
+
+        // Lexical retrieval
+        let search_client = SearchClient::new(
+            (*self.embeddings_client).clone(),
+            (*self.rerank_client).clone(),
+        );
+        let request = SearchRequestBuilder::new()
+            .query(question)
+            .documents(chunks.iter().map(|chunk| chunk.text.clone()))
+            .model(SearchModel::BM25)
+            .search_type(SearchType::BM25)
+            .top_k(self.candidates)
+            .build()?;
+        let lexical_ranking: Vec<usize> = search_client
+            .search(&request)
+            .await?
+            .into_iter()
+            .filter(|result| result.score > 0.0)
+            .map(|result| result.index)
+            .collect();
+
+        let fused = reciprocal_rank_fusion(&[vector_ranking, lexical_ranking], DEFAULT_RRF_K);
+        let candidates: Vec<(usize, f32)> = fused.into_iter().take(self.candidates).collect();
+        debug!("Hybrid search produced {} candidates", candidates.len());
+
+        let mut passages = self.rerank(question, chunks, &candidates).await;
+        passages.truncate(self.top_k);
+        let context = assemble_context(&passages, self.context_chars);
+
+        Ok(Answer {
+            question: question.to_string(),
+            passages,
+            context,
+        })
+    }
+
+    /// Chunks, embeds and stores the files at `path`, returning the collection name.
+    async fn ingest(&mut self, path: &Path) -> Result<String, VoyageError> {
+        let collection = path.to_string_lossy().into_owned();
+        if self.corpora.contains_key(&collection) {
+            return Ok(collection);
+        }
+
+        let mut chunks = Vec::new();
+        for file in collect_text_files(path)? {
+            let content = fs::read_to_string(&file)?;
+            chunks.extend(
+                ParagraphChunker::new(self.chunk_chars)
+                    .chunk(&content)
+                    .into_iter()
+                    .map(|text| Chunk {
+                        source: file.clone(),
+                        text,
+                    }),
+            );
+        }
+        if chunks.is_empty() {
+            return Err(VoyageError::MissingDocuments(format!(
+                "no text files found at {}",
+                path.display()
+            )));
+        }
+        info!(
+            "Ingesting {} passages from {}",
+            chunks.len(),
+            path.display()
+        );
+
+        self.store
+            .create_collection(&collection, CollectionConfig::persistent())?;
+        for (batch_index, batch) in chunks.chunks(EMBED_BATCH_SIZE).enumerate() {
+            let texts: Vec<String> = batch.iter().map(|chunk| chunk.text.clone()).collect();
+            let embeddings = self.embeddings_client.embed_batch(&texts).await?;
+            for (offset, (chunk, embedding)) in batch.iter().zip(embeddings).enumerate() {
+                let id = batch_index * EMBED_BATCH_SIZE + offset;
+                self.store.insert(
+                    &collection,
+                    id.to_string(),
+                    chunk.text.clone(),
+                    embedding,
+                    None,
+                )?;
+            }
+        }
+
+        self.corpora.insert(collection.clone(), chunks);
+        Ok(collection)
+    }
+
+    /// Orders candidates by rerank score, falling back to the fused score for
+    /// candidates the rerank endpoint did not return.
+    async fn rerank(
+        &self,
+        question: &str,
+        chunks: &[Chunk],
+        candidates: &[(usize, f32)],
+    ) -> Vec<Passage> {
+        let documents = candidates
+            .iter()
+            .map(|(index, _)| chunks[*index].text.clone())
+            .collect();
+        let scores = rerank_scores(self.rerank_client.as_ref(), question, documents).await;
+
+        let mut passages: Vec<(bool, Passage)> = candidates
+            .iter()
+            .enumerate()
+            .map(|(position, (index, fused_score))| {
+                let reranked = scores.get(&position).map(|score| *score as f32);
+                (
+                    reranked.is_some(),
+                    Passage {
+                        source: chunks[*index].source.clone(),
+                        text: chunks[*index].text.clone(),
+                        score: reranked.unwrap_or(*fused_score),
+                    },
+                )
+            })
+            .collect();
+        // Reranked passages first, each group ordered by score
+        passages.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.score.total_cmp(&a.1.score)));
+        passages.into_iter().map(|(_, passage)| passage).collect()
+    }
+}
+
+/// Lists the text files at `path`, recursing into directories and skipping
+/// hidden entries. Files are returned in a stable, sorted order.
+/// Formats passages as `[source]` headed blocks until the budget is spent.
+fn assemble_context(passages: &[Passage], max_chars: usize) -> String {
+    let mut context = String::new();
+    for passage in passages {
+        let block = format!("[{}]\n{}\n\n", passage.source.display(), passage.text);
+        if context.len() + block.len() > max_chars {
+            break;
+        }
+        context.push_str(&block);
+    }
+    context.trim_end().to_string()
+}
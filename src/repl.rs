@@ -0,0 +1,337 @@
+//! Interactive querying of a saved [`VectorIndex`], behind `voyageai repl`.
+//!
+//! Each input line is either a query, answered with the best matches of the
+//! index and their scores, or a command:
+//!
+//! | command               | effect                                        |
+//! |-----------------------|-----------------------------------------------|
+//! | `:set top_k 5`        | number of hits to show                        |
+//! | `:set candidates 50`  | hits retrieved for reranking                  |
+//! | `:set rerank on`      | rerank the candidates with the rerank API     |
+//! | `:settings`           | show the settings                             |
+//! | `:history`            | list previous queries                         |
+//! | `!!`, `!3`            | run the last, or the third, query again       |
+//! | `:help`               | list the commands                             |
+//! | `:quit`               | leave; so does end of input                   |
+//!
+//! Errors of a query are printed and do not end the session.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::client::embeddings_client::Client as EmbeddingsClient;
+use crate::client::rerank_client::{rerank_scores, DefaultRerankClient};
+use crate::client::voyage_client::VoyageAiClient;
+use crate::errors::VoyageError;
+use crate::index::VectorIndex;
+
+/// Characters of a hit's text shown on its line.
+const PREVIEW_CHARS: usize = 100;
+
+const HELP: &str = "\
+Type a query to search the index, or a command:
+  :set top_k <n>        number of hits to show
+  :set candidates <n>   hits retrieved for reranking
+  :set rerank on|off    rerank the candidates
+  :settings             show the settings
+  :history              list previous queries
+  !! or !<n>            run the last or the n-th query again
+  :quit                 leave";
+
+/// Settings of a [`Repl`], changed with `:set`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplSettings {
+    /// Number of hits shown per query
+    pub top_k: usize,
+    /// Number of hits retrieved and reranked when `rerank` is on
+    pub candidates: usize,
+    pub rerank: bool,
+}
+
+impl Default for ReplSettings {
+    fn default() -> Self {
+        Self {
+            top_k: 5,
+            candidates: 50,
+            rerank: false,
+        }
+    }
+}
+
+/// A match shown by a [`Repl`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplHit {
+    pub id: String,
+    pub text: String,
+    /// The rerank score when reranked, otherwise the cosine similarity
+    pub score: f32,
+    /// Cosine similarity between the query and the document
+    pub vector_score: f32,
+    pub reranked: bool,
+}
+
+/// An interactive session over a [`VectorIndex`].
+#[derive(Debug)]
+pub struct Repl {
+    embeddings_client: Arc<EmbeddingsClient>,
+    rerank_client: Arc<DefaultRerankClient>,
+    index: VectorIndex,
+    settings: ReplSettings,
+    history: Vec<String>,
+    history_file: Option<PathBuf>,
+}
+
+impl Repl {
+    pub fn new(client: &VoyageAiClient, index: VectorIndex) -> Self {
+        Self {
+            embeddings_client: client.config.embeddings_client.clone(),
+            rerank_client: client.config.rerank_client.clone(),
+            index,
+            settings: ReplSettings::default(),
+            history: Vec::new(),
+            history_file: None,
+        }
+    }
+
+    pub fn with_settings(mut self, settings: ReplSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Loads the queries of previous sessions from `path`, if it exists, and
+    /// appends new queries to it.
+    pub fn with_history_file(mut self, path: impl Into<PathBuf>) -> Result<Self, VoyageError> {
+        let path = path.into();
+        match fs::read_to_string(&path) {
+            Ok(history) => self.history = history.lines().map(str::to_string).collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(VoyageError::ConfigError(format!(
+                    "{}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        }
+        self.history_file = Some(path);
+        Ok(self)
+    }
+
+    pub fn settings(&self) -> &ReplSettings {
+        &self.settings
+    }
+
+    /// Queries in the order they were run, oldest first.
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Returns the best matches for `query`, reranked if enabled.
+    pub async fn query(&self, query: &str) -> Result<Vec<ReplHit>, VoyageError> {
+        let embedding = self.embeddings_client.embed_query(query).await?;
+        let depth = if self.settings.rerank {
+            self.settings.candidates.max(self.settings.top_k)
+        } else {
+            self.settings.top_k
+        };
+        let mut hits: Vec<ReplHit> = self
+            .index
+            .search(&embedding, depth, None)?
+            .into_iter()
+            .map(|m| ReplHit {
+                id: m.record.id.clone(),
+                text: m.record.text.clone(),
+                score: m.score,
+                vector_score: m.score,
+                reranked: false,
+            })
+            .collect();
+
+        if self.settings.rerank {
+            let documents = hits.iter().map(|hit| hit.text.clone()).collect();
+            let scores = rerank_scores(self.rerank_client.as_ref(), query, documents).await;
+            for (position, score) in scores {
+                hits[position].score = score as f32;
+                hits[position].reranked = true;
+            }
+            hits.sort_by(|a, b| {
+                b.reranked
+                    .cmp(&a.reranked)
+                    .then(b.score.total_cmp(&a.score))
+            });
+        }
+        hits.truncate(self.settings.top_k);
+        Ok(hits)
+    }
+
+    /// Reads lines from `input` until `:quit` or end of input, writing a
+    /// prompt before each line and the results to `output`.
+    pub async fn run(&mut self, mut input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        writeln!(
+            output,
+            "{} documents of dimension {}. Type :help for commands.",
+            self.index.len(),
+            self.index.dimension()
+        )?;
+        let mut line = String::new();
+        loop {
+            write!(output, "> ")?;
+            output.flush()?;
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                writeln!(output)?;
+                return Ok(());
+            }
+            if !self.execute(line.trim(), &mut output).await? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Runs one line of input, returning `false` if the session should end.
+    pub async fn execute(&mut self, line: &str, output: &mut impl Write) -> io::Result<bool> {
+        if line.is_empty() {
+            return Ok(true);
+        }
+        if let Some(command) = line.strip_prefix(':') {
+            let mut words = command.split_whitespace();
+            match (words.next(), words.next(), words.next()) {
+                (Some("quit" | "q" | "exit"), None, _) => return Ok(false),
+                (Some("help" | "h"), None, _) => writeln!(output, "{HELP}")?,
+                (Some("settings"), None, _) => self.write_settings(output)?,
+                (Some("history"), None, _) => {
+                    for (number, query) in self.history.iter().enumerate() {
+                        writeln!(output, "{:>4}  {}", number + 1, query)?;
+                    }
+                }
+                (Some("set"), Some(key), Some(value)) => match self.set(key, value) {
+                    Ok(()) => self.write_settings(output)?,
+                    Err(message) => writeln!(output, "error: {message}")?,
+                },
+                _ => writeln!(output, "error: unknown command `{line}`, see :help")?,
+            }
+            return Ok(true);
+        }
+
+        let query = match line.strip_prefix('!') {
+            Some(reference) => match self.recall(reference) {
+                Some(query) => {
+                    writeln!(output, "{query}")?;
+                    query
+                }
+                None => {
+                    writeln!(output, "error: no query `{line}` in the history")?;
+                    return Ok(true);
+                }
+            },
+            None => line.to_string(),
+        };
+        self.remember(&query, output)?;
+        match self.query(&query).await {
+            Ok(hits) if hits.is_empty() => writeln!(output, "no matches")?,
+            Ok(hits) => {
+                for (rank, hit) in hits.iter().enumerate() {
+                    write_hit(output, rank, hit)?;
+                }
+            }
+            Err(e) => writeln!(output, "error: {e}")?,
+        }
+        Ok(true)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let count = || match value.parse::<usize>() {
+            Ok(count) if count > 0 => Ok(count),
+            _ => Err(format!("`{key}` must be a positive number")),
+        };
+        match key {
+            "top_k" => self.settings.top_k = count()?,
+            "candidates" => self.settings.candidates = count()?,
+            "rerank" => {
+                self.settings.rerank = match value {
+                    "on" | "true" | "yes" => true,
+                    "off" | "false" | "no" => false,
+                    _ => return Err("`rerank` must be on or off".to_string()),
+                }
+            }
+            _ => return Err(format!("unknown setting `{key}`")),
+        }
+        Ok(())
+    }
+
+    /// The query referenced by `!` or `!<n>`, numbered from 1.
+    fn recall(&self, reference: &str) -> Option<String> {
+        let query = match reference {
+            "!" => self.history.last(),
+            number => self
+                .history
+                .get(number.parse::<usize>().ok()?.checked_sub(1)?),
+        };
+        query.cloned()
+    }
+
+    fn remember(&mut self, query: &str, output: &mut impl Write) -> io::Result<()> {
+        if self.history.last().map(String::as_str) == Some(query) {
+            return Ok(());
+        }
+        self.history.push(query.to_string());
+        if let Some(path) = &self.history_file {
+            let appended = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{query}"));
+            if let Err(e) = appended {
+                writeln!(output, "warning: history not saved: {e}")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_settings(&self, output: &mut impl Write) -> io::Result<()> {
+        let settings = &self.settings;
+        writeln!(
+            output,
+            "top_k {}, candidates {}, rerank {}",
+            settings.top_k,
+            settings.candidates,
+            if settings.rerank { "on" } else { "off" }
+        )
+    }
+}
+
+fn write_hit(output: &mut impl Write, rank: usize, hit: &ReplHit) -> io::Result<()> {
+    let mut preview: String = hit
+        .text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(PREVIEW_CHARS + 1)
+        .collect();
+    if preview.chars().count() > PREVIEW_CHARS {
+        preview = preview.chars().take(PREVIEW_CHARS - 3).collect::<String>() + "...";
+    }
+    if hit.reranked {
+        writeln!(
+            output,
+            "{:>3}. {:.4} (vector {:.4})  {}  {}",
+            rank + 1,
+            hit.score,
+            hit.vector_score,
+            hit.id,
+            preview
+        )
+    } else {
+        writeln!(
+            output,
+            "{:>3}. {:.4}  {}  {}",
+            rank + 1,
+            hit.score,
+            hit.id,
+            preview
+        )
+    }
+}
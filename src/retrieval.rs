@@ -0,0 +1,668 @@
+//! Retrieval for RAG in a few lines.
+//!
+//! [`RetrievalPipeline`] wires a [`Chunker`], the embeddings client, a local
+//! [`VectorIndex`] and the reranker together:
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), voyageai::VoyageError> {
+//! use voyageai::{retrieval::RetrievalPipeline, VoyageAiClient};
+//!
+//! let client = VoyageAiClient::new();
+//! let mut pipeline = RetrievalPipeline::new(&client);
+//! pipeline
+//!     .add_document("guide", std::fs::read_to_string("guide.md")?, None)
+//!     .await?;
+//! for chunk in pipeline.query("How do I rotate API keys?").await? {
+//!     println!("{:.3} {}#{}", chunk.score, chunk.document_id, chunk.chunk_index);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Documents are chunked, embedded as [`InputType::Document`] and stored in
+//! the index. A query is embedded as [`InputType::Query`], the nearest
+//! `candidates` chunks are retrieved and reranked, and the best `top_k` are
+//! returned. With [`RetrievalPipelineBuilder::embedder`] another [`Embedder`],
+//! such as a local model, embeds the documents and queries instead.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use log::debug;
+
+use crate::analysis::{anomaly_scores_with, AnomalyMethod};
+use crate::builder::validation::MAX_EMBEDDING_INPUTS;
+use crate::chunking::{Chunker, ParagraphChunker};
+use crate::client::embeddings_client::Client as EmbeddingsClient;
+use crate::client::rerank_client::{rerank_scores, RerankClient};
+use crate::client::voyage_client::VoyageAiClient;
+use crate::errors::VoyageError;
+use crate::index::{DedupFilter, VectorIndex};
+use crate::models::embeddings::{EmbeddingsInput, EmbeddingsRequest, InputType};
+use crate::models::filter::MetadataFilter;
+use crate::models::metadata::Metadata;
+use crate::models::rerank::MAX_DOCUMENTS;
+use crate::traits::llm::Embedder;
+use crate::traits::query::QueryPreprocessor;
+
+/// A chunk returned by [`RetrievalPipeline::query`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoredChunk {
+    /// Id of the document the chunk belongs to
+    pub document_id: String,
+    /// Position of the chunk within its document
+    pub chunk_index: usize,
+    pub text: String,
+    /// Metadata of the document the chunk belongs to, with the location of
+    /// the item for chunks of [`RetrievalPipeline::add_code_file`]
+    pub metadata: Option<Metadata>,
+    /// Rerank score when the chunk was reranked, otherwise `vector_score`
+    pub score: f32,
+    /// Cosine similarity between the query and the chunk
+    pub vector_score: f32,
+    /// Whether `score` comes from the reranker
+    pub reranked: bool,
+}
+
+/// A chunk that [`RetrievalPipeline::report`] found unlike the rest of the
+/// corpus, see [`anomaly`](crate::analysis::anomaly).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkOutlier {
+    pub document_id: String,
+    pub chunk_index: usize,
+    pub text: String,
+    /// Anomaly score; higher is more unusual
+    pub score: f32,
+}
+
+/// Summary of the contents of a [`RetrievalPipeline`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IngestionReport {
+    pub documents: usize,
+    pub chunks: usize,
+    /// See [`RetrievalPipeline::tokens_used`]
+    pub tokens_used: u64,
+    /// The most unusual chunks, most unusual first. Empty unless enabled
+    /// with [`RetrievalPipelineBuilder::outlier_detection`].
+    pub outliers: Vec<ChunkOutlier>,
+}
+
+/// What [`RetrievalPipeline::add_repository`] added.
+#[cfg(feature = "repo")]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RepositoryReport {
+    pub files: usize,
+    pub chunks: usize,
+    /// The chunks that were embedded. Chunks of files already in the
+    /// pipeline whose text did not change keep their embeddings.
+    pub embedded: usize,
+    /// The commit recorded in the chunks' metadata, if any
+    pub commit: Option<String>,
+    /// The files left out because they are binary, too large or unreadable
+    pub skipped: Vec<(std::path::PathBuf, crate::code::SkipReason)>,
+}
+
+/// An [`Embedder`] replacing the embeddings client.
+#[derive(Clone)]
+struct CustomEmbedder(Arc<dyn Embedder>);
+
+impl std::fmt::Debug for CustomEmbedder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CustomEmbedder")
+    }
+}
+
+/// A [`QueryPreprocessor`] rewriting queries before they are embedded.
+#[derive(Clone)]
+struct CustomPreprocessor(Arc<dyn QueryPreprocessor>);
+
+impl std::fmt::Debug for CustomPreprocessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CustomPreprocessor")
+    }
+}
+
+/// Builder for [`RetrievalPipeline`].
+#[derive(Debug)]
+pub struct RetrievalPipelineBuilder {
+    embeddings_client: EmbeddingsClient,
+    embedder: Option<CustomEmbedder>,
+    query_preprocessor: Option<CustomPreprocessor>,
+    rerank_client: Option<Arc<dyn RerankClient>>,
+    chunker: Arc<dyn Chunker>,
+    index: VectorIndex,
+    dedup: Option<DedupFilter>,
+    outlier_detection: Option<(AnomalyMethod, usize)>,
+    candidates: usize,
+    top_k: usize,
+}
+
+impl RetrievalPipelineBuilder {
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self {
+            embeddings_client: client.config.embeddings_client.as_ref().clone(),
+            embedder: None,
+            query_preprocessor: None,
+            rerank_client: Some(client.config.rerank_client.clone()),
+            chunker: Arc::new(ParagraphChunker::default()),
+            index: VectorIndex::new(),
+            dedup: None,
+            outlier_detection: None,
+            candidates: 20,
+            top_k: 5,
+        }
+    }
+
+    /// How documents are split; defaults to [`ParagraphChunker`]. The
+    /// [`text_splitter`](crate::text_splitter) splitters can be used here too.
+    pub fn chunker(mut self, chunker: impl Chunker + 'static) -> Self {
+        self.chunker = Arc::new(chunker);
+        self
+    }
+
+    /// Index the chunks are stored in, e.g. one with HNSW enabled or loaded
+    /// from disk.
+    pub fn index(mut self, index: VectorIndex) -> Self {
+        self.index = index;
+        self
+    }
+
+    /// Drops chunks that `filter` flags as near-duplicates of a chunk
+    /// already stored, e.g. boilerplate repeated across pages. The chunks of
+    /// a document are numbered after dropping.
+    pub fn dedup(mut self, filter: DedupFilter) -> Self {
+        self.dedup = Some(filter);
+        self
+    }
+
+    /// Lists the `count` chunks scored most anomalous by `method` in
+    /// [`RetrievalPipeline::report`], to spot mis-chunked or off-topic
+    /// documents.
+    pub fn outlier_detection(mut self, method: AnomalyMethod, count: usize) -> Self {
+        self.outlier_detection = Some((method, count));
+        self
+    }
+
+    /// Number of chunks retrieved by vector search and passed to the reranker.
+    pub fn candidates(mut self, candidates: usize) -> Self {
+        self.candidates = candidates.clamp(1, MAX_DOCUMENTS);
+        self
+    }
+
+    /// Number of chunks returned by a query.
+    pub fn top_k(mut self, top_k: usize) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    /// Embeds documents and queries with `embedder` instead of the client,
+    /// e.g. a local model when offline. Tokens it uses are not counted in
+    /// [`RetrievalPipeline::tokens_used`].
+    pub fn embedder(mut self, embedder: impl Embedder) -> Self {
+        self.embedder = Some(CustomEmbedder(Arc::new(embedder)));
+        self
+    }
+
+    /// Rewrites queries before they are embedded, e.g. with
+    /// [`SymbolQueryExpander`](crate::traits::query::SymbolQueryExpander)
+    /// for code search. The reranker sees queries as given.
+    pub fn query_preprocessor(mut self, preprocessor: impl QueryPreprocessor) -> Self {
+        self.query_preprocessor = Some(CustomPreprocessor(Arc::new(preprocessor)));
+        self
+    }
+
+    pub fn rerank_client(mut self, rerank_client: impl RerankClient + 'static) -> Self {
+        self.rerank_client = Some(Arc::new(rerank_client));
+        self
+    }
+
+    /// Returns chunks in vector search order without calling the reranker.
+    pub fn without_rerank(mut self) -> Self {
+        self.rerank_client = None;
+        self
+    }
+
+    pub fn build(mut self) -> RetrievalPipeline {
+        let mut documents: HashMap<String, usize> = HashMap::new();
+        for (record, embedding) in self.index.iter() {
+            if let Some((document_id, _)) = split_chunk_id(&record.id) {
+                *documents.entry(document_id.to_string()).or_default() += 1;
+            }
+            if let Some(dedup) = &mut self.dedup {
+                dedup.insert(record.id.clone(), embedding);
+            }
+        }
+        RetrievalPipeline {
+            embeddings_client: self.embeddings_client,
+            embedder: self.embedder,
+            query_preprocessor: self.query_preprocessor,
+            rerank_client: self.rerank_client,
+            chunker: self.chunker,
+            index: self.index,
+            dedup: self.dedup,
+            outlier_detection: self.outlier_detection,
+            documents,
+            candidates: self.candidates,
+            top_k: self.top_k,
+            tokens_used: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Chunk → embed → index → search → rerank, behind two calls.
+#[derive(Debug)]
+pub struct RetrievalPipeline {
+    embeddings_client: EmbeddingsClient,
+    embedder: Option<CustomEmbedder>,
+    query_preprocessor: Option<CustomPreprocessor>,
+    rerank_client: Option<Arc<dyn RerankClient>>,
+    chunker: Arc<dyn Chunker>,
+    index: VectorIndex,
+    dedup: Option<DedupFilter>,
+    outlier_detection: Option<(AnomalyMethod, usize)>,
+    /// Number of chunks stored for each document
+    documents: HashMap<String, usize>,
+    candidates: usize,
+    top_k: usize,
+    /// Tokens billed for the pipeline's embeddings requests
+    tokens_used: AtomicU64,
+}
+
+impl RetrievalPipeline {
+    /// A pipeline with the default chunker, an in-memory index and reranking.
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self::builder(client).build()
+    }
+
+    pub fn builder(client: &VoyageAiClient) -> RetrievalPipelineBuilder {
+        RetrievalPipelineBuilder::new(client)
+    }
+
+    /// The index holding the chunks, e.g. to [save](VectorIndex::save) it.
+    pub fn index(&self) -> &VectorIndex {
+        &self.index
+    }
+
+    /// Number of documents added.
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Ids of the documents added, in no particular order.
+    pub fn document_ids(&self) -> impl Iterator<Item = &str> {
+        self.documents.keys().map(String::as_str)
+    }
+
+    /// Total tokens reported by the embeddings endpoint for the documents and
+    /// queries embedded so far. Reranking usage is not included.
+    pub fn tokens_used(&self) -> u64 {
+        self.tokens_used.load(Ordering::Relaxed)
+    }
+
+    /// Counts the documents, chunks and tokens ingested so far and, when
+    /// [enabled](RetrievalPipelineBuilder::outlier_detection), scores every
+    /// chunk for outliers.
+    pub fn report(&self) -> IngestionReport {
+        let mut report = IngestionReport {
+            documents: self.documents.len(),
+            chunks: self.index.len(),
+            tokens_used: self.tokens_used(),
+            outliers: Vec::new(),
+        };
+        if let Some((method, count)) = self.outlier_detection {
+            let (records, embeddings): (Vec<_>, Vec<_>) = self.index.iter().unzip();
+            let mut scored: Vec<(usize, f32)> = anomaly_scores_with(&embeddings, method)
+                .into_iter()
+                .enumerate()
+                .collect();
+            scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+            report.outliers = scored
+                .into_iter()
+                .filter_map(|(position, score)| {
+                    let record = records[position];
+                    let (document_id, chunk_index) = split_chunk_id(&record.id)?;
+                    Some(ChunkOutlier {
+                        document_id: document_id.to_string(),
+                        chunk_index,
+                        text: record.text.clone(),
+                        score,
+                    })
+                })
+                .take(count)
+                .collect();
+        }
+        report
+    }
+
+    /// Chunks, embeds and indexes a document, replacing any document with the
+    /// same id; chunks the replaced document had are not embedded again.
+    /// Returns the number of chunks stored.
+    pub async fn add_document(
+        &mut self,
+        id: impl Into<String>,
+        text: impl AsRef<str>,
+        metadata: Option<Metadata>,
+    ) -> Result<usize, VoyageError> {
+        let id = id.into();
+        let chunks = with_metadata(self.chunker.chunk(text.as_ref()), metadata);
+        Ok(self.ingest(vec![(id, chunks)]).await?.stored)
+    }
+
+    /// Adds a source file with one chunk per item, using the
+    /// [parser](crate::code::parser_for_path) for the extension of `path`
+    /// instead of the pipeline's chunker. Each chunk's metadata is `metadata`
+    /// with the [location of the item](crate::code::CodeChunk::metadata)
+    /// added, so that hits link to the exact lines; `path` (the document id)
+    /// gives the module path. A file that no enabled parser handles, that
+    /// does not parse or that has no items is chunked by the pipeline's
+    /// chunker. Rust needs the `ast` feature; Python, TypeScript, Go and Java
+    /// the `tree-sitter` feature.
+    pub async fn add_code_file(
+        &mut self,
+        path: impl Into<String>,
+        source: impl AsRef<str>,
+        metadata: Option<Metadata>,
+    ) -> Result<usize, VoyageError> {
+        let path = path.into();
+        let chunks = self.code_chunks(&path, source.as_ref(), metadata);
+        Ok(self.ingest(vec![(path, chunks)]).await?.stored)
+    }
+
+    /// Adds the files found by a [`RepoWalker`](crate::code::RepoWalker),
+    /// like [`add_code_file`](Self::add_code_file) but batching their chunks
+    /// into as few embeddings requests as possible. Each chunk's metadata has
+    /// the file's `path` relative to the walker's root and, when the walker
+    /// [annotates](crate::code::RepoWalker::annotate_commit) it, the `commit`
+    /// the repository is at, so that the index can be tied to a revision.
+    /// Document ids are the walker's root joined with the relative paths.
+    /// Requires the `repo` feature.
+    #[cfg(feature = "repo")]
+    pub async fn add_repository(
+        &mut self,
+        found: crate::code::RepoFiles,
+    ) -> Result<RepositoryReport, VoyageError> {
+        let commit = found.commit;
+        let mut report = RepositoryReport {
+            commit: commit.clone(),
+            skipped: found.skipped,
+            ..RepositoryReport::default()
+        };
+        let mut documents = Vec::new();
+        for file in found.files {
+            let source = match std::fs::read_to_string(&file.path) {
+                Ok(source) => source,
+                Err(e) => {
+                    let reason = crate::code::SkipReason::Unreadable(e.to_string());
+                    report.skipped.push((file.path, reason));
+                    continue;
+                }
+            };
+            let mut metadata = Metadata::new();
+            metadata.insert("path".to_string(), file.relative_path.into());
+            if let Some(commit) = &commit {
+                metadata.insert("commit".to_string(), commit.clone().into());
+            }
+            let id = file.path.to_string_lossy().into_owned();
+            let chunks = self.code_chunks(&id, &source, Some(metadata));
+            documents.push((id, chunks));
+        }
+        report.files = documents.len();
+        let ingested = self.ingest(documents).await?;
+        report.chunks = ingested.stored;
+        report.embedded = ingested.embedded;
+        Ok(report)
+    }
+
+    /// Adds several documents, batching their chunks into as few embeddings
+    /// requests as possible. Returns the number of chunks stored.
+    pub async fn add_documents<I, K, T>(&mut self, documents: I) -> Result<usize, VoyageError>
+    where
+        I: IntoIterator<Item = (K, T)>,
+        K: Into<String>,
+        T: AsRef<str>,
+    {
+        let documents = documents
+            .into_iter()
+            .map(|(id, text)| {
+                (
+                    id.into(),
+                    with_metadata(self.chunker.chunk(text.as_ref()), None),
+                )
+            })
+            .collect();
+        Ok(self.ingest(documents).await?.stored)
+    }
+
+    /// Removes a document and its chunks, returning whether it was present.
+    pub fn remove_document(&mut self, id: &str) -> bool {
+        let Some(count) = self.documents.remove(id) else {
+            return false;
+        };
+        for chunk_index in 0..count {
+            let chunk_id = chunk_id(id, chunk_index);
+            self.index.remove(&chunk_id);
+            if let Some(dedup) = &mut self.dedup {
+                dedup.remove(&chunk_id);
+            }
+        }
+        true
+    }
+
+    /// Returns the chunks most relevant to `query`, best first.
+    pub async fn query(&self, query: &str) -> Result<Vec<ScoredChunk>, VoyageError> {
+        self.query_filtered(query, None).await
+    }
+
+    /// Like [`RetrievalPipeline::query`], restricted to documents whose
+    /// metadata matches `filter`.
+    pub async fn query_filtered(
+        &self,
+        query: &str,
+        filter: Option<&MetadataFilter>,
+    ) -> Result<Vec<ScoredChunk>, VoyageError> {
+        if self.index.is_empty() {
+            return Ok(Vec::new());
+        }
+        let search_query = match &self.query_preprocessor {
+            Some(CustomPreprocessor(preprocessor)) => preprocessor.preprocess(query),
+            None => query.to_string(),
+        };
+        let embedding = self
+            .embed(vec![search_query], InputType::Query)
+            .await?
+            .pop()
+            .ok_or(VoyageError::EmptyResponse)?;
+
+        let mut chunks: Vec<ScoredChunk> = self
+            .index
+            .search(&embedding, self.candidates, filter)?
+            .into_iter()
+            .filter_map(|m| {
+                let (document_id, chunk_index) = split_chunk_id(&m.record.id)?;
+                Some(ScoredChunk {
+                    document_id: document_id.to_string(),
+                    chunk_index,
+                    text: m.record.text.clone(),
+                    metadata: m.record.metadata.clone(),
+                    score: m.score,
+                    vector_score: m.score,
+                    reranked: false,
+                })
+            })
+            .collect();
+
+        if let Some(rerank_client) = &self.rerank_client {
+            let documents = chunks.iter().map(|chunk| chunk.text.clone()).collect();
+            let scores = rerank_scores(rerank_client.as_ref(), query, documents).await;
+            for (position, score) in scores {
+                chunks[position].score = score as f32;
+                chunks[position].reranked = true;
+            }
+            // Reranked chunks first, each group ordered by score
+            chunks.sort_by(|a, b| {
+                b.reranked
+                    .cmp(&a.reranked)
+                    .then(b.score.total_cmp(&a.score))
+            });
+        }
+        chunks.truncate(self.top_k);
+        Ok(chunks)
+    }
+
+    /// One chunk per item of the source file at `path` with the item's
+    /// location added to `metadata`, or the pipeline's chunks when no parser
+    /// finds items, see [`add_code_file`](Self::add_code_file).
+    fn code_chunks(
+        &self,
+        path: &str,
+        source: &str,
+        metadata: Option<Metadata>,
+    ) -> Vec<MetadataChunk> {
+        let items = match crate::code::CodeChunker::for_file(path) {
+            Some(chunker) => chunker.chunk_items(source).unwrap_or_else(|e| {
+                debug!("{}: {}, chunking it as text", path, e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        if items.is_empty() {
+            return with_metadata(self.chunker.chunk(source), metadata);
+        }
+        items
+            .iter()
+            .map(|item| {
+                let mut chunk_metadata = metadata.clone().unwrap_or_default();
+                chunk_metadata.extend(item.metadata());
+                (item.text_with_path(), Some(chunk_metadata))
+            })
+            .collect()
+    }
+
+    /// Embeds and stores the chunks of `documents`, replacing any earlier
+    /// version of each. Chunks whose text the earlier version already had
+    /// keep their embeddings, so re-adding an edited document only embeds
+    /// the chunks that changed.
+    async fn ingest(
+        &mut self,
+        documents: Vec<(String, Vec<MetadataChunk>)>,
+    ) -> Result<Ingested, VoyageError> {
+        let mut known: HashMap<String, Vec<f32>> = HashMap::new();
+        for (id, _) in &documents {
+            for chunk_index in 0..self.documents.get(id).copied().unwrap_or(0) {
+                if let Some((record, embedding)) = self.index.get(&chunk_id(id, chunk_index)) {
+                    known.insert(record.text.clone(), embedding.to_vec());
+                }
+            }
+        }
+        let texts: Vec<String> = documents
+            .iter()
+            .flat_map(|(_, chunks)| chunks.iter().map(|(text, _)| text))
+            .filter(|text| !known.contains_key(*text))
+            .cloned()
+            .collect();
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(MAX_EMBEDDING_INPUTS) {
+            embeddings.extend(self.embed(batch.to_vec(), InputType::Document).await?);
+        }
+
+        let mut ingested = Ingested {
+            stored: 0,
+            embedded: texts.len(),
+        };
+        let mut embeddings = embeddings.into_iter();
+        for (id, chunks) in documents {
+            self.remove_document(&id);
+            let document_chunks = chunks.len();
+            let mut chunk_index = 0;
+            for (text, metadata) in chunks {
+                let embedding = match known.get(&text) {
+                    Some(embedding) => embedding.clone(),
+                    None => match embeddings.next() {
+                        Some(embedding) => embedding,
+                        None => break,
+                    },
+                };
+                let chunk_id = chunk_id(&id, chunk_index);
+                if let Some(dedup) = &mut self.dedup {
+                    if !dedup.admit(chunk_id.clone(), &embedding) {
+                        continue;
+                    }
+                }
+                self.index.insert(chunk_id, text, embedding, metadata)?;
+                chunk_index += 1;
+                ingested.stored += 1;
+                *self.documents.entry(id.clone()).or_default() += 1;
+            }
+            if chunk_index < document_chunks {
+                debug!(
+                    "Dropped {} near-duplicate chunks of {}",
+                    document_chunks - chunk_index,
+                    id
+                );
+            }
+        }
+        Ok(ingested)
+    }
+
+    async fn embed(
+        &self,
+        texts: Vec<String>,
+        input_type: InputType,
+    ) -> Result<Vec<Vec<f32>>, VoyageError> {
+        if let Some(CustomEmbedder(embedder)) = &self.embedder {
+            return match (input_type, texts.as_slice()) {
+                (InputType::Query, [query]) => Ok(vec![embedder.embed_query(query).await?]),
+                _ => embedder.embed_documents(&texts).await,
+            };
+        }
+        let count = texts.len();
+        let request = EmbeddingsRequest {
+            input: EmbeddingsInput::Multiple(texts),
+            model: self.embeddings_client.config().embedding_model,
+            input_type: Some(input_type),
+            truncation: None,
+            encoding_format: None,
+        };
+        let response = self.embeddings_client.create_embedding(&request).await?;
+        self.tokens_used
+            .fetch_add(u64::from(response.usage.total_tokens), Ordering::Relaxed);
+        response.into_embeddings(count)
+    }
+}
+
+/// The text of a chunk and the metadata it is stored with.
+type MetadataChunk = (String, Option<Metadata>);
+
+/// What [`RetrievalPipeline::ingest`] did.
+struct Ingested {
+    /// Chunks stored, leaving out near-duplicates
+    stored: usize,
+    /// Chunks embedded; the others reused the embedding of an unchanged chunk
+    #[cfg_attr(not(feature = "repo"), allow(dead_code))]
+    embedded: usize,
+}
+
+/// Pairs each chunk with the metadata of its document.
+fn with_metadata(
+    chunks: Vec<String>,
+    metadata: Option<Metadata>,
+) -> Vec<(String, Option<Metadata>)> {
+    chunks
+        .into_iter()
+        .map(|chunk| (chunk, metadata.clone()))
+        .collect()
+}
+
+fn chunk_id(document_id: &str, chunk_index: usize) -> String {
+    format!("{document_id}#{chunk_index}")
+}
+
+fn split_chunk_id(id: &str) -> Option<(&str, usize)> {
+    let (document_id, chunk_index) = id.rsplit_once('#')?;
+    Some((document_id, chunk_index.parse().ok()?))
+}
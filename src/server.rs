@@ -0,0 +1,542 @@
+//! HTTP server behind `voyageai serve`, exposing the client and a local
+//! index as a JSON API to services written in other languages. Enabled with
+//! the `serve` feature.
+//!
+//! | route          | request body                                  | response                              |
+//! |----------------|-----------------------------------------------|---------------------------------------|
+//! | `GET /health`  |                                               | `{"status", "documents"}`             |
+//! | `POST /embed`  | `{"input", "input_type"?}`                    | `{"model", "embeddings"}`             |
+//! | `POST /rerank` | `{"query", "documents", "top_k"?}`            | the rerank API's response             |
+//! | `POST /search` | `{"query", "top_k"?, "rerank"?, "filter"?}`   | `{"results": [{"id", "text", ...}]}`  |
+//!
+//! `input` is a text or a list of texts. `/search` answers from the index
+//! given to [`Server::with_index`], embedding queries with the client's model,
+//! and accepts a [`MetadataFilter`] as `filter`. An index given to
+//! [`Server::with_shared_index`] can be written to while the server runs;
+//! each search answers from the snapshot current when it starts.
+//!
+//! For tools that only speak another provider's API, `POST /v1/embeddings`
+//! accepts and answers OpenAI embeddings requests, including
+//! `"encoding_format": "base64"`, and `POST /api/embed` Ollama's. A `model`
+//! naming a Voyage model selects it; any other model, such as
+//! `text-embedding-3-small`, is answered with the client's model. Errors of
+//! the OpenAI route are in OpenAI's `{"error": {"message", "type"}}` shape.
+//!
+//! A request with an `Authorization: Bearer <key>` header is passed on to the
+//! API with that key instead of the server's own, so that each caller is
+//! billed and rate limited separately. At most
+//! [`max_concurrency`](Server::with_max_concurrency) requests are handled at
+//! once; the others wait. Errors are returned as `{"error": message}`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::rejection::JsonRejection;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::net::TcpListener;
+use tokio::sync::Semaphore;
+
+use crate::builder::validation::MAX_EMBEDDING_INPUTS;
+use crate::client::rerank_client::{rerank_scores, RerankRequestBuilder};
+use crate::client::voyage_client::VoyageAiClient;
+use crate::config::VoyageConfig;
+use crate::errors::VoyageError;
+use crate::index::{SharedIndex, VectorIndex};
+use crate::models::embeddings::{EmbeddingModel, EmbeddingsInput, EmbeddingsRequest, InputType};
+use crate::models::filter::MetadataFilter;
+use crate::models::metadata::Metadata;
+use crate::models::rerank::MAX_DOCUMENTS;
+
+/// Requests handled at once unless set with [`Server::with_max_concurrency`].
+pub const DEFAULT_MAX_CONCURRENCY: usize = 16;
+
+/// Clients kept for callers' API keys; the cache is emptied when full.
+const MAX_PASSTHROUGH_CLIENTS: usize = 256;
+
+/// Hits returned by `/search` without a `top_k`.
+const DEFAULT_SEARCH_TOP_K: usize = 10;
+
+/// The `serve` HTTP server.
+#[derive(Debug)]
+pub struct Server {
+    client: VoyageAiClient,
+    index: Option<SharedIndex>,
+    max_concurrency: usize,
+}
+
+#[derive(Debug)]
+struct ServerState {
+    client: VoyageAiClient,
+    index: Option<SharedIndex>,
+    permits: Semaphore,
+    /// Clients for API keys passed by callers
+    clients: Mutex<HashMap<String, VoyageAiClient>>,
+}
+
+impl Server {
+    /// A server making requests with `client`'s configuration.
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self {
+            client: client.clone(),
+            index: None,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+        }
+    }
+
+    /// The index `/search` answers from.
+    pub fn with_index(mut self, index: VectorIndex) -> Self {
+        self.index = Some(SharedIndex::new(index));
+        self
+    }
+
+    /// Like [`with_index`](Self::with_index), for an index that other tasks
+    /// keep writing to, e.g. to ingest documents while serving.
+    pub fn with_shared_index(mut self, index: SharedIndex) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// The routes of the server, e.g. to nest them in another application.
+    pub fn router(self) -> Router {
+        let state = Arc::new(ServerState {
+            client: self.client,
+            index: self.index,
+            permits: Semaphore::new(self.max_concurrency),
+            clients: Mutex::new(HashMap::new()),
+        });
+        Router::new()
+            .route("/health", get(health))
+            .route("/embed", post(embed))
+            .route("/rerank", post(rerank))
+            .route("/search", post(search))
+            .route("/v1/embeddings", post(openai_embeddings))
+            .route("/api/embed", post(ollama_embed))
+            .with_state(state)
+    }
+
+    /// Serves requests on `listener` until the process ends.
+    pub async fn serve(self, listener: TcpListener) -> Result<(), VoyageError> {
+        axum::serve(listener, self.router())
+            .await
+            .map_err(|e| VoyageError::Other(format!("Server error: {e}")))
+    }
+}
+
+impl ServerState {
+    /// The client to make a request with: one using the caller's API key if
+    /// given, otherwise the server's.
+    fn client_for(&self, headers: &HeaderMap) -> Result<VoyageAiClient, ApiError> {
+        let key = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+            .filter(|key| !key.is_empty());
+        let Some(key) = key else {
+            let config = &self.client.config.config;
+            if config.api_key.is_empty() && config.api_key_provider.is_none() {
+                return Err(ApiError::new(
+                    StatusCode::UNAUTHORIZED,
+                    "No API key: send `Authorization: Bearer <key>`",
+                ));
+            }
+            return Ok(self.client.clone());
+        };
+
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(client) = clients.get(key) {
+            return Ok(client.clone());
+        }
+        if clients.len() >= MAX_PASSTHROUGH_CLIENTS {
+            clients.clear();
+        }
+        let client = VoyageAiClient::new_with_config(VoyageConfig {
+            api_key: key.to_string(),
+            api_key_provider: None,
+            ..self.client.config.config.clone()
+        });
+        clients.insert(key.to_string(), client.clone());
+        Ok(client)
+    }
+}
+
+/// An error response, `{"error": message}`.
+#[derive(Debug)]
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<VoyageError> for ApiError {
+    fn from(error: VoyageError) -> Self {
+        let status = match &error {
+            VoyageError::BadRequest { .. }
+            | VoyageError::Validation(_)
+            | VoyageError::InputListTooLong
+            | VoyageError::TokenLimitExceeded(..)
+            | VoyageError::TooManyDocuments(_)
+            | VoyageError::QueryDocumentTokenLimitExceeded(..)
+            | VoyageError::SearchDimensionMismatch { .. } => StatusCode::BAD_REQUEST,
+            VoyageError::Unauthorized | VoyageError::MissingApiKey => StatusCode::UNAUTHORIZED,
+            VoyageError::Forbidden(_) => StatusCode::FORBIDDEN,
+            VoyageError::RateLimitExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
+            // Other rejections of the caller's request by the API
+            VoyageError::ApiError(status, ..) if status.is_client_error() => {
+                StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::BAD_REQUEST)
+            }
+            _ => StatusCode::BAD_GATEWAY,
+        };
+        Self::new(status, error.to_string())
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+/// An error response of the OpenAI route,
+/// `{"error": {"message", "type", "param", "code"}}`.
+#[derive(Debug)]
+struct OpenAiError(ApiError);
+
+impl<E: Into<ApiError>> From<E> for OpenAiError {
+    fn from(error: E) -> Self {
+        Self(error.into())
+    }
+}
+
+impl IntoResponse for OpenAiError {
+    fn into_response(self) -> Response {
+        let kind = match self.0.status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => "authentication_error",
+            StatusCode::TOO_MANY_REQUESTS => "rate_limit_error",
+            status if status.is_client_error() => "invalid_request_error",
+            _ => "api_error",
+        };
+        let body = json!({
+            "error": { "message": self.0.message, "type": kind, "param": null, "code": null }
+        });
+        (self.0.status, Json(body)).into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TextInput {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbedBody {
+    input: TextInput,
+    #[serde(default)]
+    input_type: Option<InputType>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiEmbeddingsBody {
+    /// A text or a list of texts; token arrays are not supported
+    input: TextInput,
+    #[serde(default)]
+    model: Option<String>,
+    /// `float`, the default, or `base64`
+    #[serde(default)]
+    encoding_format: Option<String>,
+    #[serde(default)]
+    dimensions: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaEmbedBody {
+    input: TextInput,
+    #[serde(default)]
+    model: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RerankBody {
+    query: String,
+    documents: Vec<String>,
+    #[serde(default)]
+    top_k: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchBody {
+    query: String,
+    #[serde(default)]
+    top_k: Option<usize>,
+    /// Rerank the hits with the rerank API
+    #[serde(default)]
+    rerank: bool,
+    #[serde(default)]
+    filter: Option<MetadataFilter>,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchHit {
+    id: String,
+    text: String,
+    /// The rerank score when reranked, otherwise the cosine similarity
+    score: f32,
+    vector_score: f32,
+    reranked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<Metadata>,
+}
+
+async fn health(State(state): State<Arc<ServerState>>) -> Json<serde_json::Value> {
+    let documents = state.index.as_ref().map_or(0, SharedIndex::len);
+    Json(json!({ "status": "ok", "documents": documents }))
+}
+
+async fn embed(
+    State(state): State<Arc<ServerState>>,
+    headers: HeaderMap,
+    body: Result<Json<EmbedBody>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(body) = body?;
+    let client = state.client_for(&headers)?;
+    let _permit = state.permits.acquire().await;
+
+    let texts = match body.input {
+        TextInput::One(text) => vec![text],
+        TextInput::Many(texts) => texts,
+    };
+    let embeddings = client
+        .config
+        .embeddings_client
+        .embed_all(&texts, body.input_type)
+        .await?;
+    Ok(Json(json!({
+        "model": client.config.config.embedding_model.to_string(),
+        "embeddings": embeddings,
+    })))
+}
+
+async fn rerank(
+    State(state): State<Arc<ServerState>>,
+    headers: HeaderMap,
+    body: Result<Json<RerankBody>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(body) = body?;
+    let client = state.client_for(&headers)?;
+    let _permit = state.permits.acquire().await;
+
+    let mut request = RerankRequestBuilder::new()
+        .query(body.query)
+        .add_documents(body.documents);
+    if let Some(top_k) = body.top_k {
+        request = request.top_k(top_k);
+    }
+    let response = client
+        .config
+        .rerank_client
+        .rerank(request.build().map_err(VoyageError::from)?)
+        .await?;
+    Ok(Json(
+        serde_json::to_value(response).map_err(VoyageError::from)?,
+    ))
+}
+
+async fn search(
+    State(state): State<Arc<ServerState>>,
+    headers: HeaderMap,
+    body: Result<Json<SearchBody>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(body) = body?;
+    let index = state
+        .index
+        .as_ref()
+        .map(SharedIndex::snapshot)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "The server has no index"))?;
+    let client = state.client_for(&headers)?;
+    let _permit = state.permits.acquire().await;
+
+    let top_k = body.top_k.unwrap_or(DEFAULT_SEARCH_TOP_K);
+    let depth = if body.rerank {
+        MAX_DOCUMENTS.min(top_k.max(50))
+    } else {
+        top_k
+    };
+    let embedding = client
+        .config
+        .embeddings_client
+        .embed_query(&body.query)
+        .await?;
+    let mut hits: Vec<SearchHit> = index
+        .search(&embedding, depth, body.filter.as_ref())?
+        .into_iter()
+        .map(|m| SearchHit {
+            id: m.record.id.clone(),
+            text: m.record.text.clone(),
+            score: m.score,
+            vector_score: m.score,
+            reranked: false,
+            metadata: m.record.metadata.clone(),
+        })
+        .collect();
+
+    if body.rerank {
+        let documents = hits.iter().map(|hit| hit.text.clone()).collect();
+        let scores =
+            rerank_scores(client.config.rerank_client.as_ref(), &body.query, documents).await;
+        for (position, score) in scores {
+            hits[position].score = score as f32;
+            hits[position].reranked = true;
+        }
+        hits.sort_by(|a, b| {
+            b.reranked
+                .cmp(&a.reranked)
+                .then(b.score.total_cmp(&a.score))
+        });
+    }
+    hits.truncate(top_k);
+    Ok(Json(json!({ "results": hits })))
+}
+
+async fn openai_embeddings(
+    State(state): State<Arc<ServerState>>,
+    headers: HeaderMap,
+    body: Result<Json<OpenAiEmbeddingsBody>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, OpenAiError> {
+    let Json(body) = body?;
+    let base64 = match body.encoding_format.as_deref() {
+        None | Some("float") => false,
+        Some("base64") => true,
+        Some(format) => {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Unsupported encoding_format `{format}`, use float or base64"),
+            )
+            .into())
+        }
+    };
+    let client = state.client_for(&headers)?;
+    let model = model_for(&client, body.model.as_deref());
+    if let Some(dimensions) = body.dimensions {
+        if dimensions != model.embedding_dimension() {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "{} produces {} dimensions, not {}",
+                    model,
+                    model.embedding_dimension(),
+                    dimensions
+                ),
+            )
+            .into());
+        }
+    }
+    let _permit = state.permits.acquire().await;
+
+    let (embeddings, tokens) = embed_with_model(&client, model, body.input).await?;
+    let data: Vec<serde_json::Value> = embeddings
+        .into_iter()
+        .enumerate()
+        .map(|(index, embedding)| {
+            let embedding = if base64 {
+                let bytes: Vec<u8> = embedding.iter().flat_map(|x| x.to_le_bytes()).collect();
+                json!(BASE64.encode(bytes))
+            } else {
+                json!(embedding)
+            };
+            json!({ "object": "embedding", "index": index, "embedding": embedding })
+        })
+        .collect();
+    Ok(Json(json!({
+        "object": "list",
+        "data": data,
+        "model": model.to_string(),
+        "usage": { "prompt_tokens": tokens, "total_tokens": tokens },
+    })))
+}
+
+async fn ollama_embed(
+    State(state): State<Arc<ServerState>>,
+    headers: HeaderMap,
+    body: Result<Json<OllamaEmbedBody>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(body) = body?;
+    let client = state.client_for(&headers)?;
+    let model = model_for(&client, body.model.as_deref());
+    let _permit = state.permits.acquire().await;
+
+    let (embeddings, tokens) = embed_with_model(&client, model, body.input).await?;
+    Ok(Json(json!({
+        "model": model.to_string(),
+        "embeddings": embeddings,
+        "prompt_eval_count": tokens,
+    })))
+}
+
+/// The Voyage model named by `requested`, or the client's for other
+/// providers' model names.
+fn model_for(client: &VoyageAiClient, requested: Option<&str>) -> EmbeddingModel {
+    requested
+        .and_then(|model| model.parse().ok())
+        .unwrap_or(client.config.config.embedding_model)
+}
+
+/// Embeds `input` with `model`, returning the embeddings in input order and
+/// the tokens used.
+async fn embed_with_model(
+    client: &VoyageAiClient,
+    model: EmbeddingModel,
+    input: TextInput,
+) -> Result<(Vec<Vec<f32>>, u32), VoyageError> {
+    let texts = match input {
+        TextInput::One(text) => vec![text],
+        TextInput::Many(texts) => texts,
+    };
+    let mut embeddings = Vec::with_capacity(texts.len());
+    let mut tokens = 0;
+    for batch in texts.chunks(MAX_EMBEDDING_INPUTS) {
+        let request = EmbeddingsRequest {
+            input: EmbeddingsInput::Multiple(batch.to_vec()),
+            model,
+            input_type: None,
+            truncation: None,
+            encoding_format: None,
+        };
+        let response = client
+            .config
+            .embeddings_client
+            .create_embedding(&request)
+            .await?;
+        tokens += response.usage.total_tokens;
+        embeddings.extend(response.into_embeddings(batch.len())?);
+    }
+    Ok((embeddings, tokens))
+}
@@ -0,0 +1,224 @@
+//! `tower::Service` implementations of the API endpoints, behind the `tower`
+//! feature.
+//!
+//! [`EmbeddingsService`] and [`RerankService`] send one request per call
+//! through the client's rate limiter, so they compose with standard tower
+//! middleware such as timeouts, concurrency limits and load shedding.
+//! [`VoyageService`] serves both endpoints behind one service.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use tower::{ServiceBuilder, ServiceExt};
+//! use voyageai::{service::EmbeddingsService, EmbeddingsInput, EmbeddingModel, VoyageAiClient};
+//! use voyageai::models::embeddings::EmbeddingsRequest;
+//!
+//! # async fn run(client: VoyageAiClient) -> Result<(), tower::BoxError> {
+//! let service = ServiceBuilder::new()
+//!     .timeout(Duration::from_secs(10))
+//!     .concurrency_limit(8)
+//!     .service(EmbeddingsService::new(&client));
+//! let request = EmbeddingsRequest {
+//!     input: EmbeddingsInput::Single("hello".to_string()),
+//!     model: EmbeddingModel::Voyage3Large,
+//!     input_type: None,
+//!     truncation: None,
+//!     encoding_format: None,
+//! };
+//! let response = service.oneshot(request).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tower::Service;
+
+use crate::client::embeddings_client::Client as EmbeddingsClient;
+use crate::client::rerank_client::DefaultRerankClient;
+use crate::client::voyage_client::VoyageAiClient;
+use crate::errors::VoyageError;
+use crate::models::embeddings::{EmbeddingsRequest, EmbeddingsResponse};
+use crate::models::rerank::{RerankRequest, RerankResponse};
+
+/// The future returned by the services in this module.
+pub struct ServiceFuture<T> {
+    inner: Pin<Box<dyn Future<Output = Result<T, VoyageError>> + Send>>,
+}
+
+impl<T> ServiceFuture<T> {
+    fn new(future: impl Future<Output = Result<T, VoyageError>> + Send + 'static) -> Self {
+        Self {
+            inner: Box::pin(future),
+        }
+    }
+}
+
+impl<T> Future for ServiceFuture<T> {
+    type Output = Result<T, VoyageError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+/// The embeddings endpoint as a `Service<EmbeddingsRequest>`.
+///
+/// Always ready: requests wait for the rate limiter once called. Clones
+/// share the client and its rate limiter.
+#[derive(Debug, Clone)]
+pub struct EmbeddingsService {
+    client: Arc<EmbeddingsClient>,
+}
+
+impl EmbeddingsService {
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self {
+            client: client.embeddings_client().clone(),
+        }
+    }
+
+    pub fn from_embeddings_client(client: EmbeddingsClient) -> Self {
+        Self {
+            client: Arc::new(client),
+        }
+    }
+}
+
+impl Service<EmbeddingsRequest> for EmbeddingsService {
+    type Response = EmbeddingsResponse;
+    type Error = VoyageError;
+    type Future = ServiceFuture<EmbeddingsResponse>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: EmbeddingsRequest) -> Self::Future {
+        let client = self.client.clone();
+        ServiceFuture::new(async move { client.create_embedding(&request).await })
+    }
+}
+
+/// The rerank endpoint as a `Service<RerankRequest>`.
+///
+/// Requests are sent as is, without the configured rerank fallback, so
+/// errors reach the middleware.
+#[derive(Debug, Clone)]
+pub struct RerankService {
+    client: Arc<DefaultRerankClient>,
+}
+
+impl RerankService {
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self {
+            client: client.config.rerank_client.clone(),
+        }
+    }
+
+    pub fn from_rerank_client(client: DefaultRerankClient) -> Self {
+        Self {
+            client: Arc::new(client),
+        }
+    }
+}
+
+impl Service<RerankRequest> for RerankService {
+    type Response = RerankResponse;
+    type Error = VoyageError;
+    type Future = ServiceFuture<RerankResponse>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RerankRequest) -> Self::Future {
+        let client = self.client.clone();
+        ServiceFuture::new(async move { client.rerank(request).await })
+    }
+}
+
+/// A request to either endpoint of [`VoyageService`].
+#[derive(Debug)]
+pub enum VoyageRequest {
+    Embeddings(EmbeddingsRequest),
+    Rerank(RerankRequest),
+}
+
+impl From<EmbeddingsRequest> for VoyageRequest {
+    fn from(request: EmbeddingsRequest) -> Self {
+        Self::Embeddings(request)
+    }
+}
+
+impl From<RerankRequest> for VoyageRequest {
+    fn from(request: RerankRequest) -> Self {
+        Self::Rerank(request)
+    }
+}
+
+/// The response of [`VoyageService`], matching the [`VoyageRequest`] variant.
+#[derive(Debug)]
+pub enum VoyageResponse {
+    Embeddings(EmbeddingsResponse),
+    Rerank(RerankResponse),
+}
+
+/// Both endpoints behind one `Service<VoyageRequest>`, for stacks that apply
+/// the same middleware to every API call.
+#[derive(Debug, Clone)]
+pub struct VoyageService {
+    embeddings: EmbeddingsService,
+    rerank: RerankService,
+}
+
+impl VoyageService {
+    pub fn new(client: &VoyageAiClient) -> Self {
+        Self {
+            embeddings: EmbeddingsService::new(client),
+            rerank: RerankService::new(client),
+        }
+    }
+}
+
+impl Service<VoyageRequest> for VoyageService {
+    type Response = VoyageResponse;
+    type Error = VoyageError;
+    type Future = ServiceFuture<VoyageResponse>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: VoyageRequest) -> Self::Future {
+        match request {
+            VoyageRequest::Embeddings(request) => {
+                let response = self.embeddings.call(request);
+                ServiceFuture::new(async move { response.await.map(VoyageResponse::Embeddings) })
+            }
+            VoyageRequest::Rerank(request) => {
+                let response = self.rerank.call(request);
+                ServiceFuture::new(async move { response.await.map(VoyageResponse::Rerank) })
+            }
+        }
+    }
+}
+
+impl VoyageAiClient {
+    /// The embeddings endpoint as a `tower::Service`.
+    pub fn embeddings_service(&self) -> EmbeddingsService {
+        EmbeddingsService::new(self)
+    }
+
+    /// The rerank endpoint as a `tower::Service`.
+    pub fn rerank_service(&self) -> RerankService {
+        RerankService::new(self)
+    }
+
+    /// Both endpoints as one `tower::Service`.
+    pub fn service(&self) -> VoyageService {
+        VoyageService::new(self)
+    }
+}
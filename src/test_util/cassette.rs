@@ -0,0 +1,299 @@
+//! Record-and-replay of API interactions.
+//!
+//! A [`Cassette`] mounted on a [`TestServer`](super::TestServer) forwards
+//! requests to the live API in record mode and saves the responses to a JSON
+//! file, keyed by a hash of the request. In replay mode, the default, the
+//! responses are served from the file without network access, so recorded
+//! tests are deterministic and free to run in CI. Re-record by running the
+//! tests with `VOYAGE_VCR=record` and an API key.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use wiremock::{Request, Respond, ResponseTemplate};
+
+use crate::config::DEFAULT_BASE_URL;
+use crate::errors::VoyageError;
+
+/// Whether a [`Cassette`] serves recorded responses or records new ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VcrMode {
+    /// Serve recorded responses only; unrecorded requests fail.
+    #[default]
+    Replay,
+    /// Forward every request to the live API and record the response,
+    /// replacing earlier recordings.
+    Record,
+    /// Serve recorded responses, recording requests not seen before.
+    Auto,
+}
+
+impl VcrMode {
+    /// Reads the mode from `VOYAGE_VCR` (`replay`, `record` or `auto`),
+    /// defaulting to replay.
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("VOYAGE_VCR") {
+            Ok(mode) => mode.parse(),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+}
+
+impl FromStr for VcrMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "replay" => Ok(Self::Replay),
+            "record" => Ok(Self::Record),
+            "auto" => Ok(Self::Auto),
+            other => Err(format!(
+                "Unknown VCR mode '{other}', expected replay, record or auto"
+            )),
+        }
+    }
+}
+
+/// A recorded request and the response of the live API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// Hash of the method, path and body identifying the request.
+    pub key: String,
+    pub method: String,
+    pub path: String,
+    /// Request body, kept for reviewing cassette diffs. Credentials are never
+    /// recorded.
+    pub request: Value,
+    pub status: u16,
+    pub response: Value,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// A file of recorded interactions, served by mounting it on a
+/// [`TestServer`](super::TestServer) with
+/// [`with_cassette`](super::TestServer::with_cassette).
+///
+/// Only successful responses are recorded; errors such as rate limits are
+/// passed through. Clones share the same recordings.
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: VcrMode,
+    upstream: String,
+    api_key: Option<String>,
+    interactions: Arc<Mutex<BTreeMap<String, Interaction>>>,
+}
+
+impl Cassette {
+    /// Opens the cassette at `path`. A missing file is an error in replay
+    /// mode and starts an empty cassette otherwise.
+    ///
+    /// Recording uses the key in `VOYAGE_API_KEY` unless one is set with
+    /// [`with_api_key`](Self::with_api_key).
+    pub fn open(path: impl Into<PathBuf>, mode: VcrMode) -> Result<Self, VoyageError> {
+        let path = path.into();
+        let interactions = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str::<CassetteFile>(&contents)?
+                .interactions
+                .into_iter()
+                .map(|interaction| (interaction.key.clone(), interaction))
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && mode != VcrMode::Replay => {
+                BTreeMap::new()
+            }
+            Err(e) => {
+                return Err(VoyageError::Other(format!(
+                    "Cannot read cassette {}: {e}",
+                    path.display()
+                )))
+            }
+        };
+        Ok(Self {
+            path,
+            mode,
+            upstream: DEFAULT_BASE_URL.to_string(),
+            api_key: std::env::var("VOYAGE_API_KEY").ok(),
+            interactions: Arc::new(Mutex::new(interactions)),
+        })
+    }
+
+    /// Opens the cassette at `path` in the mode set by `VOYAGE_VCR`.
+    pub fn from_env(path: impl Into<PathBuf>) -> Result<Self, VoyageError> {
+        Self::open(path, VcrMode::from_env().map_err(VoyageError::Other)?)
+    }
+
+    /// Records from `base_url` instead of the Voyage AI API.
+    pub fn with_upstream(mut self, base_url: impl Into<String>) -> Self {
+        self.upstream = base_url.into();
+        self
+    }
+
+    /// Key sent to the live API when recording.
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn mode(&self) -> VcrMode {
+        self.mode
+    }
+
+    /// The recorded interactions, ordered by key.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.interactions.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forwards `request` to the live API. Runs on its own thread and runtime
+    /// since responders are synchronous.
+    fn forward(&self, request: &Request) -> Result<(u16, String), String> {
+        let api_key = self
+            .api_key
+            .clone()
+            .ok_or("Recording requires an API key, set VOYAGE_API_KEY")?;
+        let url = format!(
+            "{}{}",
+            self.upstream.trim_end_matches('/'),
+            request.url.path()
+        );
+        let body = request.body.clone();
+
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| e.to_string())?;
+            runtime.block_on(async move {
+                let response = reqwest::Client::new()
+                    .post(url)
+                    .bearer_auth(api_key)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body)
+                    .send()
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let status = response.status().as_u16();
+                let text = response.text().await.map_err(|e| e.to_string())?;
+                Ok((status, text))
+            })
+        })
+        .join()
+        .map_err(|_| "Recording thread panicked".to_string())?
+    }
+
+    /// Saves the cassette, replacing the file atomically.
+    fn save(&self) -> Result<(), VoyageError> {
+        let file = CassetteFile {
+            interactions: self.interactions(),
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&file)? + "\n")?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    fn record(&self, key: String, request: &Request) -> ResponseTemplate {
+        let (status, text) = match self.forward(request) {
+            Ok(response) => response,
+            Err(message) => return error_response(502, &message),
+        };
+        if !(200..300).contains(&status) {
+            return ResponseTemplate::new(status).set_body_raw(text, "application/json");
+        }
+        let response = serde_json::from_str(&text).unwrap_or(Value::String(text));
+        let interaction = Interaction {
+            key: key.clone(),
+            method: request.method.to_string(),
+            path: request.url.path().to_string(),
+            request: request_value(request),
+            status,
+            response,
+        };
+        let template = replay(&interaction);
+        self.interactions.lock().unwrap().insert(key, interaction);
+        if let Err(e) = self.save() {
+            return error_response(500, &format!("Cannot save cassette: {e}"));
+        }
+        template
+    }
+}
+
+impl Respond for Cassette {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let key = request_key(request);
+        if self.mode != VcrMode::Record {
+            if let Some(interaction) = self.interactions.lock().unwrap().get(&key) {
+                return replay(interaction);
+            }
+        }
+        match self.mode {
+            VcrMode::Replay => error_response(
+                404,
+                &format!(
+                    "No interaction recorded for {} {} (key {key}) in {}; record it with VOYAGE_VCR=record",
+                    request.method,
+                    request.url.path(),
+                    self.path.display()
+                ),
+            ),
+            VcrMode::Record | VcrMode::Auto => self.record(key, request),
+        }
+    }
+}
+
+/// Parses the request body as JSON, falling back to the raw text.
+fn request_value(request: &Request) -> Value {
+    serde_json::from_slice(&request.body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&request.body).into_owned()))
+}
+
+/// Hash of the method, path and body. JSON bodies are re-serialized with
+/// sorted keys so formatting and field order do not change the key.
+fn request_key(request: &Request) -> String {
+    let canonical = format!(
+        "{} {}\n{}",
+        request.method,
+        request.url.path(),
+        request_value(request)
+    );
+    format!("{:016x}", super::fnv1a(canonical.as_bytes()))
+}
+
+fn replay(interaction: &Interaction) -> ResponseTemplate {
+    let body = match &interaction.response {
+        Value::String(text) => text.clone(),
+        response => response.to_string(),
+    };
+    ResponseTemplate::new(interaction.status).set_body_raw(body, "application/json")
+}
+
+fn error_response(status: u16, message: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status).set_body_json(json!({ "detail": message }))
+}
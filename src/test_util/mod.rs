@@ -0,0 +1,263 @@
+//! Hermetic test harness, available with the `test-util` feature.
+//!
+//! [`TestServer`] runs a local HTTP server that answers the embeddings and
+//! rerank endpoints, and hands out clients pointed at it, so code built on
+//! this crate can be tested without network access or an API key.
+//!
+//! ```no_run
+//! use voyageai::test_util::{fixtures, TestServer};
+//! use voyageai::traits::llm::Embedder;
+//!
+//! # async fn example() {
+//! let server = TestServer::start()
+//!     .await
+//!     .with_embeddings_fixture(fixtures::EMBEDDINGS_SINGLE)
+//!     .await;
+//! let embedding = server.client().embed("hello").await.unwrap();
+//! # }
+//! ```
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use crate::{VoyageAiClient, VoyageConfig};
+
+mod cassette;
+
+pub use cassette::{Cassette, Interaction, VcrMode};
+/// The mock server library behind [`TestServer`], for custom mocks.
+pub use wiremock;
+
+/// Responses recorded from the Voyage AI API.
+pub mod fixtures {
+    /// Embeddings response for a single input.
+    pub const EMBEDDINGS_SINGLE: &str = include_str!("../../tests/fixtures/embeddings/single.json");
+    /// Embeddings response for a batch of two inputs.
+    pub const EMBEDDINGS_BATCH: &str = include_str!("../../tests/fixtures/embeddings/batch.json");
+    /// Rerank response for two documents, ranking the second first.
+    pub const RERANK: &str = include_str!("../../tests/fixtures/rerank/valid.json");
+}
+
+/// API key configured on the clients returned by [`TestServer`].
+pub const TEST_API_KEY: &str = "test-key";
+
+/// Dimension of the embeddings generated by [`TestServer::with_embeddings`].
+const EMBEDDING_DIMENSION: usize = 1024;
+
+/// A local stand-in for the Voyage AI API.
+///
+/// Endpoints answer 404 until a response is registered with one of the
+/// `with_*` methods. The server stops when dropped.
+pub struct TestServer {
+    server: MockServer,
+}
+
+impl TestServer {
+    pub async fn start() -> Self {
+        Self {
+            server: MockServer::start().await,
+        }
+    }
+
+    /// Base URL of the server, to pass to [`VoyageConfig::with_base_url`].
+    pub fn uri(&self) -> String {
+        self.server.uri()
+    }
+
+    /// A configuration pointed at this server.
+    pub fn config(&self) -> VoyageConfig {
+        VoyageConfig::new(TEST_API_KEY.to_string()).with_base_url(self.uri())
+    }
+
+    /// A client pointed at this server.
+    pub fn client(&self) -> VoyageAiClient {
+        VoyageAiClient::new_with_config(self.config())
+    }
+
+    /// The underlying server, for registering custom mocks.
+    pub fn mock_server(&self) -> &MockServer {
+        &self.server
+    }
+
+    /// Requests received so far.
+    pub async fn received_requests(&self) -> Vec<Request> {
+        self.server.received_requests().await.unwrap_or_default()
+    }
+
+    /// Answers every embeddings request with `fixture`, a JSON response body.
+    pub async fn with_embeddings_fixture(self, fixture: &str) -> Self {
+        self.respond("embeddings", json_response(200, fixture))
+            .await
+    }
+
+    /// Answers every rerank request with `fixture`, a JSON response body.
+    pub async fn with_rerank_fixture(self, fixture: &str) -> Self {
+        self.respond("rerank", json_response(200, fixture)).await
+    }
+
+    /// Answers embeddings requests with deterministic embeddings generated
+    /// from the words of each input. Inputs sharing words have similar
+    /// embeddings.
+    /// Embeddings are base64-encoded when the request asks for it.
+    pub async fn with_embeddings(self) -> Self {
+        self.respond("embeddings", GeneratedEmbeddings).await
+    }
+
+    /// Answers rerank requests by scoring each document by the similarity of
+    /// its generated embedding to the query's, honoring `top_k`.
+    pub async fn with_rerank(self) -> Self {
+        self.respond("rerank", GeneratedRerank).await
+    }
+
+    /// Answers requests to `endpoint` (e.g. `"rerank"`) with `status` and
+    /// `body`.
+    pub async fn with_error(self, endpoint: &str, status: u16, body: &str) -> Self {
+        self.respond(endpoint, json_response(status, body)).await
+    }
+
+    /// Serves every request from `cassette`, recording responses of the live
+    /// API first when the cassette is in record mode.
+    pub async fn with_cassette(self, cassette: Cassette) -> Self {
+        Mock::given(method("POST"))
+            .respond_with(cassette)
+            .mount(&self.server)
+            .await;
+        self
+    }
+
+    async fn respond(self, endpoint: &str, responder: impl Respond + 'static) -> Self {
+        Mock::given(method("POST"))
+            .and(path(format!("/v1/{}", endpoint.trim_start_matches('/'))))
+            .respond_with(responder)
+            .mount(&self.server)
+            .await;
+        self
+    }
+}
+
+fn json_response(status: u16, body: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status).set_body_raw(body.to_string(), "application/json")
+}
+
+/// FNV-1a hash, stable across runs and platforms.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325u64, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Embedding of `text` as normalized word counts hashed into
+/// [`EMBEDDING_DIMENSION`] buckets.
+fn embed(text: &str) -> Vec<f32> {
+    let mut embedding = vec![0.0f32; EMBEDDING_DIMENSION];
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        let hash = fnv1a(word.to_lowercase().as_bytes());
+        embedding[(hash % EMBEDDING_DIMENSION as u64) as usize] += 1.0;
+    }
+    let norm = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        embedding[0] = 1.0;
+    } else {
+        embedding.iter_mut().for_each(|x| *x /= norm);
+    }
+    embedding
+}
+
+/// Rough token count reported in the usage of generated responses.
+fn tokens(texts: &[&str]) -> usize {
+    texts
+        .iter()
+        .map(|text| text.split_whitespace().count())
+        .sum()
+}
+
+fn request_body(request: &Request) -> Option<Value> {
+    serde_json::from_slice(&request.body).ok()
+}
+
+struct GeneratedEmbeddings;
+
+impl Respond for GeneratedEmbeddings {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let Some(body) = request_body(request) else {
+            return json_response(400, r#"{"detail": "Invalid JSON"}"#);
+        };
+        let texts: Vec<&str> = match &body["input"] {
+            Value::String(text) => vec![text.as_str()],
+            Value::Array(texts) => texts.iter().filter_map(Value::as_str).collect(),
+            _ => return json_response(400, r#"{"detail": "Invalid input"}"#),
+        };
+        let data: Vec<Value> = texts
+            .iter()
+            .enumerate()
+            .map(|(index, text)| {
+                let embedding = embed(text);
+                let embedding = if body["encoding_format"] == "base64" {
+                    let bytes: Vec<u8> = embedding.iter().flat_map(|x| x.to_le_bytes()).collect();
+                    json!(BASE64.encode(bytes))
+                } else {
+                    json!(embedding)
+                };
+                json!({"object": "embedding", "embedding": embedding, "index": index})
+            })
+            .collect();
+        ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "data": data,
+            "model": body["model"],
+            "usage": {"total_tokens": tokens(&texts)}
+        }))
+    }
+}
+
+struct GeneratedRerank;
+
+impl Respond for GeneratedRerank {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let Some(body) = request_body(request) else {
+            return json_response(400, r#"{"detail": "Invalid JSON"}"#);
+        };
+        let query = body["query"].as_str().unwrap_or_default();
+        let documents: Vec<&str> = body["documents"]
+            .as_array()
+            .map(|documents| documents.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let query_embedding = embed(query);
+        let mut scores: Vec<(usize, f32)> = documents
+            .iter()
+            .map(|document| crate::cosine_similarity(&query_embedding, &embed(document)))
+            .enumerate()
+            .collect();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        if let Some(top_k) = body["top_k"].as_u64() {
+            scores.truncate(top_k as usize);
+        }
+
+        let return_documents = body["return_documents"].as_bool().unwrap_or(false);
+        let data: Vec<Value> = scores
+            .into_iter()
+            .map(|(index, score)| {
+                let mut result = json!({"relevance_score": score, "index": index});
+                if return_documents {
+                    result["document"] = json!(documents[index]);
+                }
+                result
+            })
+            .collect();
+        let mut texts = documents;
+        texts.push(query);
+        ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "data": data,
+            "model": body["model"],
+            "usage": {"total_tokens": tokens(&texts)}
+        }))
+    }
+}
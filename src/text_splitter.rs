@@ -0,0 +1,350 @@
+//! Splitting text into overlapping chunks by characters, sentences or
+//! tokens.
+//!
+//! Every [`TextSplitter`] returns [`TextChunk`]s with byte offsets into the
+//! original text, so chunks can be traced back to their source. Text is
+//! broken at the coarsest boundary that fits: sentences, then words, then
+//! characters. The splitters also implement [`Chunker`] for use in a
+//! [`RetrievalPipeline`](crate::retrieval::RetrievalPipeline):
+//!
+//! ```
+//! use voyageai::text_splitter::{SentenceSplitter, TextSplitter};
+//!
+//! let text = "Rust is fast. Rust is safe. Rust is fun.";
+//! let chunks = SentenceSplitter::new(30).with_overlap(14).split(text);
+//!
+//! assert_eq!(chunks[0].text, "Rust is fast. Rust is safe.");
+//! assert_eq!(chunks[1].text, "Rust is safe. Rust is fun.");
+//! assert_eq!(&text[chunks[1].start..chunks[1].end], chunks[1].text);
+//! ```
+
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::chunking::Chunker;
+use crate::models::tokens::estimate_tokens;
+
+/// A chunk of text and its position in the text it was split from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextChunk {
+    /// The chunk, without leading or trailing whitespace
+    pub text: String,
+    /// Byte offset of the chunk's start in the original text
+    pub start: usize,
+    /// Byte offset just past the chunk's end in the original text
+    pub end: usize,
+}
+
+impl TextChunk {
+    /// Byte range of the chunk in the original text.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// Splits text into [`TextChunk`]s.
+pub trait TextSplitter: std::fmt::Debug + Send + Sync {
+    fn split(&self, text: &str) -> Vec<TextChunk>;
+}
+
+/// Packs words into chunks of at most `chunk_size` characters, breaking
+/// words longer than that on character boundaries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharacterSplitter {
+    chunk_size: usize,
+    overlap: usize,
+}
+
+impl CharacterSplitter {
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            chunk_size: chunk_size.max(1),
+            overlap: 0,
+        }
+    }
+
+    /// Repeats up to `overlap` characters from the end of each chunk at the
+    /// start of the next. Capped below the chunk size.
+    pub fn with_overlap(mut self, overlap: usize) -> Self {
+        self.overlap = overlap.min(self.chunk_size - 1);
+        self
+    }
+
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    pub fn overlap(&self) -> usize {
+        self.overlap
+    }
+}
+
+impl TextSplitter for CharacterSplitter {
+    fn split(&self, text: &str) -> Vec<TextChunk> {
+        split(
+            text,
+            Granularity::Word,
+            count_chars,
+            self.chunk_size,
+            self.overlap,
+        )
+    }
+}
+
+/// Packs whole sentences into chunks of at most `max_chars` characters.
+/// Sentences longer than that are split like [`CharacterSplitter`] does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SentenceSplitter {
+    max_chars: usize,
+    overlap: usize,
+}
+
+impl SentenceSplitter {
+    pub fn new(max_chars: usize) -> Self {
+        Self {
+            max_chars: max_chars.max(1),
+            overlap: 0,
+        }
+    }
+
+    /// Repeats the last sentences of each chunk, up to `overlap` characters,
+    /// at the start of the next. Capped below the chunk size.
+    pub fn with_overlap(mut self, overlap: usize) -> Self {
+        self.overlap = overlap.min(self.max_chars - 1);
+        self
+    }
+
+    pub fn max_chars(&self) -> usize {
+        self.max_chars
+    }
+
+    pub fn overlap(&self) -> usize {
+        self.overlap
+    }
+}
+
+impl TextSplitter for SentenceSplitter {
+    fn split(&self, text: &str) -> Vec<TextChunk> {
+        split(
+            text,
+            Granularity::Sentence,
+            count_chars,
+            self.max_chars,
+            self.overlap,
+        )
+    }
+}
+
+/// Packs words into chunks of at most `max_tokens` tokens, as counted by
+/// [`estimate_tokens`] unless another counter is set.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenSplitter {
+    max_tokens: usize,
+    overlap: usize,
+    counter: fn(&str) -> usize,
+}
+
+impl TokenSplitter {
+    pub fn new(max_tokens: usize) -> Self {
+        Self {
+            max_tokens: max_tokens.max(1),
+            overlap: 0,
+            counter: estimate_tokens,
+        }
+    }
+
+    /// Repeats up to `overlap` tokens from the end of each chunk at the start
+    /// of the next. Capped below the chunk size.
+    pub fn with_overlap(mut self, overlap: usize) -> Self {
+        self.overlap = overlap.min(self.max_tokens - 1);
+        self
+    }
+
+    /// Counts tokens with `counter`, e.g. a model's tokenizer.
+    pub fn with_counter(mut self, counter: fn(&str) -> usize) -> Self {
+        self.counter = counter;
+        self
+    }
+
+    pub fn max_tokens(&self) -> usize {
+        self.max_tokens
+    }
+
+    pub fn overlap(&self) -> usize {
+        self.overlap
+    }
+}
+
+impl TextSplitter for TokenSplitter {
+    fn split(&self, text: &str) -> Vec<TextChunk> {
+        split(
+            text,
+            Granularity::Word,
+            self.counter,
+            self.max_tokens,
+            self.overlap,
+        )
+    }
+}
+
+impl Chunker for CharacterSplitter {
+    fn chunk(&self, text: &str) -> Vec<String> {
+        self.split(text)
+            .into_iter()
+            .map(|chunk| chunk.text)
+            .collect()
+    }
+}
+
+impl Chunker for SentenceSplitter {
+    fn chunk(&self, text: &str) -> Vec<String> {
+        self.split(text)
+            .into_iter()
+            .map(|chunk| chunk.text)
+            .collect()
+    }
+}
+
+impl Chunker for TokenSplitter {
+    fn chunk(&self, text: &str) -> Vec<String> {
+        self.split(text)
+            .into_iter()
+            .map(|chunk| chunk.text)
+            .collect()
+    }
+}
+
+/// Boundaries text is broken at, coarsest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Granularity {
+    Sentence,
+    Word,
+    Char,
+}
+
+impl Granularity {
+    fn finer(self) -> Option<Self> {
+        match self {
+            Granularity::Sentence => Some(Granularity::Word),
+            Granularity::Word => Some(Granularity::Char),
+            Granularity::Char => None,
+        }
+    }
+
+    /// Byte ranges of the segments of `text`, offset by `offset`. Whitespace
+    /// is attached to the word before it.
+    fn segments(self, text: &str, offset: usize) -> Vec<Range<usize>> {
+        let ranges: Vec<Range<usize>> = match self {
+            Granularity::Sentence => text
+                .split_sentence_bound_indices()
+                .map(|(start, sentence)| start..start + sentence.len())
+                .collect(),
+            Granularity::Word => {
+                let mut ranges: Vec<Range<usize>> = Vec::new();
+                for (start, word) in text.split_word_bound_indices() {
+                    let end = start + word.len();
+                    match ranges.last_mut() {
+                        Some(last) if word.trim().is_empty() => last.end = end,
+                        _ => ranges.push(start..end),
+                    }
+                }
+                ranges
+            }
+            Granularity::Char => text
+                .char_indices()
+                .map(|(start, c)| start..start + c.len_utf8())
+                .collect(),
+        };
+        ranges
+            .into_iter()
+            .map(|range| range.start + offset..range.end + offset)
+            .collect()
+    }
+}
+
+fn count_chars(text: &str) -> usize {
+    text.chars().count()
+}
+
+fn split(
+    text: &str,
+    granularity: Granularity,
+    measure: fn(&str) -> usize,
+    max: usize,
+    overlap: usize,
+) -> Vec<TextChunk> {
+    let mut segments = Vec::new();
+    for range in granularity.segments(text, 0) {
+        fit(text, range, granularity, measure, max, &mut segments);
+    }
+    pack(text, &segments, measure, max, overlap)
+}
+
+/// Pushes `range`, split at finer boundaries while it exceeds `max`.
+fn fit(
+    text: &str,
+    range: Range<usize>,
+    granularity: Granularity,
+    measure: fn(&str) -> usize,
+    max: usize,
+    segments: &mut Vec<Range<usize>>,
+) {
+    match granularity.finer() {
+        Some(finer) if measure(text[range.clone()].trim()) > max => {
+            for part in finer.segments(&text[range.clone()], range.start) {
+                fit(text, part, finer, measure, max, segments);
+            }
+        }
+        _ => segments.push(range),
+    }
+}
+
+/// Packs consecutive segments into chunks measuring at most `max`, starting
+/// each chunk with the trailing segments of the previous one that measure at
+/// most `overlap`. Whitespace around a chunk is not measured.
+fn pack(
+    text: &str,
+    segments: &[Range<usize>],
+    measure: fn(&str) -> usize,
+    max: usize,
+    overlap: usize,
+) -> Vec<TextChunk> {
+    let size =
+        |from: usize, to: usize| measure(text[segments[from].start..segments[to - 1].end].trim());
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < segments.len() {
+        let mut end = start + 1;
+        while end < segments.len() && size(start, end + 1) <= max {
+            end += 1;
+        }
+        if let Some(chunk) = trimmed(text, segments[start].start..segments[end - 1].end) {
+            chunks.push(chunk);
+        }
+        if end == segments.len() {
+            break;
+        }
+        let mut next = end;
+        while next > start + 1 && size(next - 1, end) <= overlap {
+            next -= 1;
+        }
+        start = next;
+    }
+    chunks
+}
+
+fn trimmed(text: &str, range: Range<usize>) -> Option<TextChunk> {
+    let slice = &text[range.clone()];
+    let trimmed = slice.trim_start();
+    let start = range.start + slice.len() - trimmed.len();
+    let trimmed = trimmed.trim_end();
+    if trimmed.is_empty() {
+        return None;
+    }
+    Some(TextChunk {
+        text: trimmed.to_string(),
+        start,
+        end: start + trimmed.len(),
+    })
+}
@@ -1,18 +1,26 @@
 use crate::errors::VoyageError;
-use crate::models::embeddings::{EmbeddingModel, EmbeddingsInput, EmbeddingsRequest};
+use crate::models::embeddings::{EmbedOptions, IndexedEmbedding, InputType};
 use crate::VoyageAiClient;
 use crate::client::rerank_client::RerankClient;
+use crate::client::tasks::AbortOnDrop;
 use tokio::sync::oneshot;
-use tokio::task;
 
 /// Domain-specific future type for single text embedding that can be awaited
 pub struct TextEmbedding {
     receiver: oneshot::Receiver<Result<Vec<f32>, VoyageError>>,
+    /// Aborts the task producing the result when this future is dropped
+    _task: Option<AbortOnDrop>,
 }
 
 impl TextEmbedding {
-    fn new(receiver: oneshot::Receiver<Result<Vec<f32>, VoyageError>>) -> Self {
-        Self { receiver }
+    pub(crate) fn new(receiver: oneshot::Receiver<Result<Vec<f32>, VoyageError>>, task: AbortOnDrop) -> Self {
+        Self { receiver, _task: Some(task) }
+    }
+
+    /// A future for a result sent by a task shared with other callers, which
+    /// notices the caller is gone when the receiver is closed.
+    pub(crate) fn from_receiver(receiver: oneshot::Receiver<Result<Vec<f32>, VoyageError>>) -> Self {
+        Self { receiver, _task: None }
     }
 }
 
@@ -29,11 +37,13 @@ impl std::future::Future for TextEmbedding {
 /// Domain-specific future type for batch text embeddings that can be awaited
 pub struct BatchEmbedding {
     receiver: oneshot::Receiver<Result<Vec<Vec<f32>>, VoyageError>>,
+    /// Aborts the task producing the result when this future is dropped
+    _task: AbortOnDrop,
 }
 
 impl BatchEmbedding {
-    fn new(receiver: oneshot::Receiver<Result<Vec<Vec<f32>>, VoyageError>>) -> Self {
-        Self { receiver }
+    pub(crate) fn new(receiver: oneshot::Receiver<Result<Vec<Vec<f32>>, VoyageError>>, task: AbortOnDrop) -> Self {
+        Self { receiver, _task: task }
     }
 }
 
@@ -50,8 +60,8 @@ impl std::future::Future for BatchEmbedding {
 /// A stream of document similarities
 pub type DocumentSimilarityStream = tokio_stream::wrappers::ReceiverStream<crate::client::rerank_client::DocumentSimilarity>;
 
-/// A stream of text embeddings
-pub type TextEmbeddingStream = tokio_stream::wrappers::ReceiverStream<Vec<f32>>;
+/// A stream of text embeddings tagged with the position of their input text
+pub type TextEmbeddingStream = tokio_stream::wrappers::ReceiverStream<IndexedEmbedding>;
 
 /// Interface for embedding text into vectors
 pub trait Embedder: Send + Sync + 'static {
@@ -61,9 +71,44 @@ pub trait Embedder: Send + Sync + 'static {
     /// Get embeddings for multiple texts (returns a future with all embeddings)
     fn embed_batch(&self, texts: &[String]) -> BatchEmbedding;
     
-    /// Get embeddings for multiple texts as a stream (optional method)
+    /// Get embeddings for multiple texts as a stream, yielded as they complete
     fn embed_stream(&self, texts: Vec<String>) -> TextEmbeddingStream;
     // Default implementation is removed - each implementor must provide their own implementation
+
+    /// Get the embedding of a search query. Embedders distinguishing queries
+    /// from documents override this; the default is [`embed`](Self::embed).
+    fn embed_query(&self, query: &str) -> TextEmbedding {
+        self.embed(query)
+    }
+
+    /// Get the embeddings of corpus documents searched with
+    /// [`embed_query`](Self::embed_query) embeddings. The default is
+    /// [`embed_batch`](Self::embed_batch).
+    fn embed_documents(&self, documents: &[String]) -> BatchEmbedding {
+        self.embed_batch(documents)
+    }
+
+    /// Get the embedding of `text` with per-call [`EmbedOptions`]. The
+    /// default honors only the input type, embedding queries with
+    /// [`embed_query`](Self::embed_query) and anything else with
+    /// [`embed`](Self::embed).
+    fn embed_with(&self, text: &str, options: EmbedOptions) -> TextEmbedding {
+        match options.input_type {
+            Some(InputType::Query) => self.embed_query(text),
+            _ => self.embed(text),
+        }
+    }
+
+    /// Get the embeddings of `texts` with per-call [`EmbedOptions`]. The
+    /// default honors only the input type, embedding documents with
+    /// [`embed_documents`](Self::embed_documents) and anything else with
+    /// [`embed_batch`](Self::embed_batch).
+    fn embed_batch_with(&self, texts: &[String], options: EmbedOptions) -> BatchEmbedding {
+        match options.input_type {
+            Some(InputType::Document) => self.embed_documents(texts),
+            _ => self.embed_batch(texts),
+        }
+    }
 }
 
 /// Interface for reranking documents
@@ -74,91 +119,61 @@ pub trait Reranker: Send + Sync + 'static {
 
 impl Embedder for VoyageAiClient {
     fn embed(&self, text: &str) -> TextEmbedding {
-        // Clone everything needed for the async task
+        self.embed_with(text, EmbedOptions::default())
+    }
+
+    fn embed_batch(&self, texts: &[String]) -> BatchEmbedding {
+        self.embed_batch_with(texts, EmbedOptions::default())
+    }
+
+    /// Embeds `text` with the configured model and `options` in one request.
+    fn embed_with(&self, text: &str, options: EmbedOptions) -> TextEmbedding {
         let text = text.to_string();
-        // Create a cloned instance of the client for the task
         let embeddings_client = self.embeddings_client().clone();
-        
         let (tx, rx) = oneshot::channel();
-        
-        task::spawn(async move {
-            let result = async {
-                let request = EmbeddingsRequest {
-                    input: EmbeddingsInput::Single(text),
-                    model: EmbeddingModel::Voyage3Large,
-                    input_type: None,
-                    truncation: None,
-                    encoding_format: None,
-                };
-
-                let embeddings = embeddings_client.create_embedding(&request).await?;
-                Ok(embeddings.data[0].embedding.clone())
-            }.await;
-            
-            let _ = tx.send(result);
+        let task = self.tasks().spawn(async move {
+            let _ = tx.send(embeddings_client.embed_with(&text, options).await);
         });
-        
-        TextEmbedding::new(rx)
+        TextEmbedding::new(rx, task)
     }
 
-    fn embed_batch(&self, texts: &[String]) -> BatchEmbedding {
-        // Clone everything needed for the async task
+    /// Embeds `texts` with the configured model and `options`, splitting
+    /// them into requests the API accepts.
+    fn embed_batch_with(&self, texts: &[String], options: EmbedOptions) -> BatchEmbedding {
         let texts = texts.to_vec();
-        // Create a cloned instance of the client for the task
         let embeddings_client = self.embeddings_client().clone();
-        
         let (tx, rx) = oneshot::channel();
-        
-        task::spawn(async move {
-            let result = async {
-                let request = EmbeddingsRequest {
-                    input: EmbeddingsInput::Multiple(texts),
-                    model: EmbeddingModel::Voyage3Large,
-                    input_type: None,
-                    truncation: None,
-                    encoding_format: None,
-                };
-
-                let embeddings = embeddings_client.create_embedding(&request).await?;
-                Ok(embeddings.data.into_iter().map(|d| d.embedding).collect())
-            }.await;
-            
-            let _ = tx.send(result);
+        let task = self.tasks().spawn(async move {
+            let _ = tx.send(embeddings_client.embed_batch_with(&texts, options).await);
         });
-        
-        BatchEmbedding::new(rx)
+        BatchEmbedding::new(rx, task)
     }
-    
+
     fn embed_stream(&self, texts: Vec<String>) -> TextEmbeddingStream {
-        // Implementation that creates a stream
+        self.embeddings_client().embed_stream(texts)
+    }
+
+    /// Embeds `query` with `input_type` set to `query`.
+    fn embed_query(&self, query: &str) -> TextEmbedding {
+        let query = query.to_string();
         let embeddings_client = self.embeddings_client().clone();
-        let (tx, rx) = tokio::sync::mpsc::channel(texts.len());
-        
-        tokio::spawn(async move {
-            let request = EmbeddingsRequest {
-                input: EmbeddingsInput::Multiple(texts),
-                model: EmbeddingModel::Voyage3Large,
-                input_type: None,
-                truncation: None,
-                encoding_format: None,
-            };
-            
-            match embeddings_client.create_embedding(&request).await {
-                Ok(response) => {
-                    for embedding_data in response.data {
-                        if tx.send(embedding_data.embedding).await.is_err() {
-                            break; // receiver dropped
-                        }
-                    }
-                },
-                Err(e) => {
-                    log::error!("Error in embed_stream: {:?}", e);
-                    // Channel will be closed, receiver will get end of stream
-                }
-            }
+        let (tx, rx) = oneshot::channel();
+        let task = self.tasks().spawn(async move {
+            let _ = tx.send(embeddings_client.embed_query(&query).await);
+        });
+        TextEmbedding::new(rx, task)
+    }
+
+    /// Embeds `documents` with `input_type` set to `document`, splitting
+    /// them into requests the API accepts.
+    fn embed_documents(&self, documents: &[String]) -> BatchEmbedding {
+        let documents = documents.to_vec();
+        let embeddings_client = self.embeddings_client().clone();
+        let (tx, rx) = oneshot::channel();
+        let task = self.tasks().spawn(async move {
+            let _ = tx.send(embeddings_client.embed_documents(&documents).await);
         });
-        
-        tokio_stream::wrappers::ReceiverStream::new(rx)
+        BatchEmbedding::new(rx, task)
     }
 }
 
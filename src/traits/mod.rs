@@ -1,2 +1,4 @@
 pub mod llm;
+pub mod query;
+pub mod scoring;
 pub mod voyage;
@@ -0,0 +1,80 @@
+/// Interface for rewriting a search query before it is embedded.
+///
+/// Implemented for any `Fn(&str) -> String` closure. Only the query that is
+/// embedded (or, for BM25, tokenized) is rewritten; rerankers see the query
+/// as given.
+pub trait QueryPreprocessor: Send + Sync + 'static {
+    /// The query to search with in place of `query`
+    fn preprocess(&self, query: &str) -> String;
+}
+
+impl<F> QueryPreprocessor for F
+where
+    F: Fn(&str) -> String + Send + Sync + 'static,
+{
+    fn preprocess(&self, query: &str) -> String {
+        self(query)
+    }
+}
+
+/// Expands queries that look like a code symbol into its words, so that
+/// embeddings match code and prose mentioning the same concept:
+/// `HttpClientBuilder` becomes `http client builder HttpClientBuilder`, and
+/// `std::fs::read_to_string` becomes
+/// `std fs read to string std::fs::read_to_string`.
+///
+/// A query is a symbol when it is a single CamelCase, snake_case,
+/// kebab-case or path (`::`, `.`, `/`) word made of at least two words.
+/// Other queries are left alone.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SymbolQueryExpander;
+
+impl QueryPreprocessor for SymbolQueryExpander {
+    fn preprocess(&self, query: &str) -> String {
+        let symbol = query.trim();
+        let is_symbol = !symbol.is_empty()
+            && symbol
+                .chars()
+                .all(|c| c.is_alphanumeric() || SYMBOL_SEPARATORS.contains(&c))
+            && symbol.chars().any(char::is_alphabetic);
+        if !is_symbol {
+            return query.to_string();
+        }
+        let words = split_identifier(symbol);
+        if words.len() < 2 {
+            return query.to_string();
+        }
+        format!("{} {}", words.join(" "), symbol)
+    }
+}
+
+/// Characters separating the words of a symbol besides case changes.
+const SYMBOL_SEPARATORS: &[char] = &['_', '-', ':', '.', '/'];
+
+/// Splits an identifier or path into lowercase words at separators and case
+/// changes: `parseHTTPResponse_v2` gives `parse`, `http`, `response`, `v2`.
+/// Digits stay with the word before them.
+pub fn split_identifier(identifier: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in identifier.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (previous, current) = (chars[i - 1], chars[i]);
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            // `httpClient`, or the last capital of an acronym: `HTTPResponse`
+            let boundary = current.is_uppercase()
+                && (previous.is_lowercase()
+                    || previous.is_numeric()
+                    || (previous.is_uppercase() && next_is_lower));
+            if boundary {
+                words.push(chars[start..i].iter().collect::<String>().to_lowercase());
+                start = i;
+            }
+        }
+        if start < chars.len() {
+            words.push(chars[start..].iter().collect::<String>().to_lowercase());
+        }
+    }
+    words
+}
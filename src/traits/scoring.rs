@@ -0,0 +1,141 @@
+use crate::models::metadata::{Metadata, MetadataValue};
+use std::sync::Arc;
+
+/// Features available to a scorer when computing the final score of a search result.
+#[derive(Debug, Clone, Copy)]
+pub struct ScoreFeatures<'a> {
+    /// Position of the document in the request's document list
+    pub index: usize,
+    /// The document content
+    pub document: &'a str,
+    /// Raw score produced by the search type: cosine similarity, BM25 score,
+    /// or `1 / (1 + d)` of the Euclidean distance `d` for nearest-neighbor
+    /// search, so that higher is always better
+    pub similarity: f32,
+    /// Relevance score from the rerank endpoint, when reranking was requested
+    pub rerank_score: Option<f64>,
+    /// Metadata attached to the document, if any
+    pub metadata: Option<&'a Metadata>,
+    /// Score produced by the previous stage of the pipeline
+    pub score: f32,
+}
+
+impl ScoreFeatures<'_> {
+    /// Looks up a metadata value by key.
+    pub fn metadata_value(&self, key: &str) -> Option<&MetadataValue> {
+        self.metadata.and_then(|metadata| metadata.get(key))
+    }
+}
+
+/// Interface for computing the final score of a search result.
+///
+/// Implemented for any `Fn(&ScoreFeatures) -> f32` closure, so domain-specific
+/// ranking logic can be registered without defining a new type.
+pub trait Scorer: Send + Sync + 'static {
+    /// Compute a score for a result; higher scores rank first
+    fn score(&self, features: &ScoreFeatures<'_>) -> f32;
+}
+
+impl<F> Scorer for F
+where
+    F: Fn(&ScoreFeatures<'_>) -> f32 + Send + Sync + 'static,
+{
+    fn score(&self, features: &ScoreFeatures<'_>) -> f32 {
+        self(features)
+    }
+}
+
+/// Linear combination of the similarity and rerank scores.
+#[derive(Debug, Clone, Copy)]
+pub struct WeightedScorer {
+    pub similarity_weight: f32,
+    pub rerank_weight: f32,
+}
+
+impl Scorer for WeightedScorer {
+    fn score(&self, features: &ScoreFeatures<'_>) -> f32 {
+        let rerank = features.rerank_score.unwrap_or_default() as f32;
+        self.similarity_weight * features.similarity + self.rerank_weight * rerank
+    }
+}
+
+/// Adds `boost` to the score of documents whose metadata `key` equals `value`.
+#[derive(Debug, Clone)]
+pub struct MetadataBoost {
+    pub key: String,
+    pub value: MetadataValue,
+    pub boost: f32,
+}
+
+impl MetadataBoost {
+    pub fn new(key: impl Into<String>, value: impl Into<MetadataValue>, boost: f32) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+            boost,
+        }
+    }
+}
+
+impl Scorer for MetadataBoost {
+    fn score(&self, features: &ScoreFeatures<'_>) -> f32 {
+        match features.metadata_value(&self.key) {
+            Some(value) if *value == self.value => features.score + self.boost,
+            _ => features.score,
+        }
+    }
+}
+
+/// Ordered chain of scorers applied to search results.
+///
+/// Each stage receives the score produced by the previous stage in
+/// [`ScoreFeatures::score`]. The first stage starts from the rerank score when
+/// one is available, and from the raw similarity otherwise.
+#[derive(Clone, Default)]
+pub struct ScoringPipeline {
+    stages: Vec<Arc<dyn Scorer>>,
+}
+
+impl ScoringPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a scorer to the end of the pipeline.
+    pub fn with_scorer(mut self, scorer: impl Scorer) -> Self {
+        self.stages.push(Arc::new(scorer));
+        self
+    }
+
+    /// Append a scorer to the end of the pipeline.
+    pub fn push(&mut self, scorer: impl Scorer) {
+        self.stages.push(Arc::new(scorer));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Run every stage of the pipeline and return the final score.
+    pub fn score(&self, features: ScoreFeatures<'_>) -> f32 {
+        let initial = features
+            .rerank_score
+            .map(|score| score as f32)
+            .unwrap_or(features.similarity);
+        self.stages.iter().fold(initial, |score, stage| {
+            stage.score(&ScoreFeatures { score, ..features })
+        })
+    }
+}
+
+impl std::fmt::Debug for ScoringPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScoringPipeline")
+            .field("stages", &self.stages.len())
+            .finish()
+    }
+}
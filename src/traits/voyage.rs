@@ -1,16 +1,25 @@
-use crate::models::embeddings::{EmbeddingsInput, EmbeddingsResponse};
+use crate::client::voyage_client::VoyageAiClient;
+use crate::models::embeddings::{EmbeddingsInput, EmbeddingsRequest, EmbeddingsResponse};
 use crate::client::SearchRequest;
 use crate::client::SearchResult;
+use crate::client::tasks::AbortOnDrop;
 use tokio::sync::oneshot;
 
 /// Domain-specific future type for embeddings that can be awaited
 pub struct EmbeddingTask {
-    receiver: oneshot::Receiver<Result<EmbeddingsResponse, Box<dyn std::error::Error + Send + Sync>>>
+    receiver: oneshot::Receiver<Result<EmbeddingsResponse, Box<dyn std::error::Error + Send + Sync>>>,
+    /// Aborts the task producing the result when this future is dropped
+    _task: Option<AbortOnDrop>,
 }
 
 impl EmbeddingTask {
     pub fn new(receiver: oneshot::Receiver<Result<EmbeddingsResponse, Box<dyn std::error::Error + Send + Sync>>>) -> Self {
-        Self { receiver }
+        Self { receiver, _task: None }
+    }
+
+    pub(crate) fn with_task(mut self, task: AbortOnDrop) -> Self {
+        self._task = Some(task);
+        self
     }
 }
 
@@ -26,12 +35,19 @@ impl std::future::Future for EmbeddingTask {
 
 /// Domain-specific future type for search results that can be awaited
 pub struct SearchTask {
-    receiver: oneshot::Receiver<Result<Vec<SearchResult>, Box<dyn std::error::Error + Send + Sync>>>
+    receiver: oneshot::Receiver<Result<Vec<SearchResult>, Box<dyn std::error::Error + Send + Sync>>>,
+    /// Aborts the task producing the result when this future is dropped
+    _task: Option<AbortOnDrop>,
 }
 
 impl SearchTask {
     pub fn new(receiver: oneshot::Receiver<Result<Vec<SearchResult>, Box<dyn std::error::Error + Send + Sync>>>) -> Self {
-        Self { receiver }
+        Self { receiver, _task: None }
+    }
+
+    pub(crate) fn with_task(mut self, task: AbortOnDrop) -> Self {
+        self._task = Some(task);
+        self
     }
 }
 
@@ -45,11 +61,18 @@ impl std::future::Future for SearchTask {
     }
 }
 
+/// Request-level API of [`VoyageAiClient`], implemented for the client and
+/// for `Arc<VoyageAiClient>`.
+///
+/// Not part of the [prelude](crate::prelude): its `embed` would be ambiguous
+/// with [`Embedder::embed`](crate::traits::llm::Embedder::embed), which
+/// returns a single vector instead of the full response.
 pub trait VoyageAiClientExt {
-    /// Create embeddings for input text and return a future that resolves to the embedding
+    /// Create embeddings for a text (`&str`, `String`) or texts (`Vec<String>`,
+    /// `&[String]`) with the configured model, returning the full response
     fn embed<T>(&self, input: T) -> EmbeddingTask
     where
-        T: Into<EmbeddingsInput> + Send + 'static;
+        T: Into<EmbeddingsInput>;
 
     /// Create a rerank request builder for more options
     fn rerank_request(&self) -> crate::client::rerank_client::RerankRequestBuilder;
@@ -63,3 +86,59 @@ pub trait VoyageAiClientExt {
     /// Search using the provided request
     fn search(&self, request: SearchRequest) -> SearchTask;
 }
+
+impl VoyageAiClientExt for VoyageAiClient {
+    fn embed<T>(&self, input: T) -> EmbeddingTask
+    where
+        T: Into<EmbeddingsInput>,
+    {
+        self.embeddings(EmbeddingsRequest {
+            input: input.into(),
+            model: self.config.config.embedding_model,
+            input_type: None,
+            truncation: None,
+            encoding_format: None,
+        })
+    }
+
+    fn rerank_request(&self) -> crate::client::rerank_client::RerankRequestBuilder {
+        VoyageAiClient::rerank_request(self)
+    }
+
+    fn find_similar_documents(&self, query: &str, documents: Vec<String>) -> tokio_stream::wrappers::ReceiverStream<crate::client::rerank_client::DocumentSimilarity> {
+        VoyageAiClient::find_similar_documents(self, query, documents)
+    }
+
+    fn most_similar_document(&self, query: &str, documents: Vec<String>) -> crate::client::rerank_client::AsyncDocumentSimilarity {
+        VoyageAiClient::most_similar_document(self, query, documents)
+    }
+
+    fn search(&self, request: SearchRequest) -> SearchTask {
+        VoyageAiClient::search(self, request)
+    }
+}
+
+impl VoyageAiClientExt for std::sync::Arc<VoyageAiClient> {
+    fn embed<T>(&self, input: T) -> EmbeddingTask
+    where
+        T: Into<EmbeddingsInput>,
+    {
+        VoyageAiClientExt::embed(self.as_ref(), input)
+    }
+
+    fn rerank_request(&self) -> crate::client::rerank_client::RerankRequestBuilder {
+        self.as_ref().rerank_request()
+    }
+
+    fn find_similar_documents(&self, query: &str, documents: Vec<String>) -> tokio_stream::wrappers::ReceiverStream<crate::client::rerank_client::DocumentSimilarity> {
+        self.as_ref().find_similar_documents(query, documents)
+    }
+
+    fn most_similar_document(&self, query: &str, documents: Vec<String>) -> crate::client::rerank_client::AsyncDocumentSimilarity {
+        self.as_ref().most_similar_document(query, documents)
+    }
+
+    fn search(&self, request: SearchRequest) -> SearchTask {
+        self.as_ref().search(request)
+    }
+}
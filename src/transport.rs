@@ -0,0 +1,192 @@
+//! The HTTP layer the clients send API requests through.
+//!
+//! Every request of the embeddings, rerank and batch clients goes through a
+//! [`Transport`], which turns an [`HttpRequest`] into an [`HttpResponse`].
+//! The default, [`ReqwestTransport`], uses `reqwest`. Environments with their
+//! own HTTP stack, such as hyper, curl or a platform API, supply theirs with
+//! [`VoyageConfig::with_transport`](crate::config::VoyageConfig::with_transport):
+//!
+//! ```no_run
+//! use voyageai::transport::{HttpRequest, HttpResponse, Transport, TransportFuture};
+//! use voyageai::{VoyageAiClient, VoyageConfig};
+//!
+//! #[derive(Debug)]
+//! struct MyTransport;
+//!
+//! impl Transport for MyTransport {
+//!     fn send(&self, request: HttpRequest) -> TransportFuture {
+//!         TransportFuture::new(async move {
+//!             // Send `request.method` to `request.url` with the headers and body
+//!             # let _ = request;
+//!             # unimplemented!()
+//!         })
+//!     }
+//! }
+//!
+//! let config = VoyageConfig::new("pa-...".to_string()).with_transport(MyTransport);
+//! let client = VoyageAiClient::new_with_config(config);
+//! ```
+//!
+//! Rate limiting, retries, authentication and response parsing stay in the
+//! clients. [`request_timeout`](crate::config::VoyageConfig::request_timeout)
+//! only applies to the default transport; custom transports enforce their own.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+pub use reqwest::{Method, StatusCode};
+use serde::Serialize;
+
+use crate::errors::VoyageError;
+
+/// A request to the API.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// A request without headers or body.
+    pub fn new(method: Method, url: impl Into<String>) -> Self {
+        Self {
+            method,
+            url: url.into(),
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn get(url: impl Into<String>) -> Self {
+        Self::new(Method::GET, url)
+    }
+
+    pub fn post(url: impl Into<String>) -> Self {
+        Self::new(Method::POST, url)
+    }
+
+    /// Sets the header `name`. Values that are not valid header values are
+    /// rejected.
+    pub fn header(mut self, name: &'static str, value: &str) -> Result<Self, VoyageError> {
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| VoyageError::TransportError(format!("Invalid {name} header: {e}")))?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// Authenticates the request with `api_key`, marked sensitive so that it
+    /// is not shown by `Debug`.
+    pub fn bearer_auth(mut self, api_key: &str) -> Result<Self, VoyageError> {
+        let mut value = HeaderValue::from_str(&format!("Bearer {api_key}"))
+            .map_err(|_| VoyageError::TransportError("Invalid API key".to_string()))?;
+        value.set_sensitive(true);
+        self.headers.insert(AUTHORIZATION, value);
+        Ok(self)
+    }
+
+    /// Sets `body` as the body, serialized to JSON.
+    pub fn json(mut self, body: &impl Serialize) -> Result<Self, VoyageError> {
+        self.body = serde_json::to_vec(body)?;
+        self.headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        Ok(self)
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+}
+
+/// A response of the API, read in full.
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// The body as text, with invalid UTF-8 replaced.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// Future returned by [`Transport::send`].
+pub struct TransportFuture {
+    inner: Pin<Box<dyn Future<Output = Result<HttpResponse, VoyageError>> + Send>>,
+}
+
+impl TransportFuture {
+    pub fn new(
+        future: impl Future<Output = Result<HttpResponse, VoyageError>> + Send + 'static,
+    ) -> Self {
+        Self {
+            inner: Box::pin(future),
+        }
+    }
+}
+
+impl Future for TransportFuture {
+    type Output = Result<HttpResponse, VoyageError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+impl fmt::Debug for TransportFuture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TransportFuture")
+    }
+}
+
+/// Sends HTTP requests for the clients, see the [module documentation](self).
+///
+/// Responses with any status are returned as `Ok`; errors are for requests
+/// that got no response, such as connection failures and timeouts, and are
+/// best reported as [`VoyageError::TransportError`].
+pub trait Transport: fmt::Debug + Send + Sync + 'static {
+    fn send(&self, request: HttpRequest) -> TransportFuture;
+}
+
+/// The default [`Transport`], sending requests with `reqwest`.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// A transport sending requests with `client`, e.g. one configured with a
+    /// proxy or custom root certificates.
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture {
+        let request = self
+            .client
+            .request(request.method, request.url)
+            .headers(request.headers)
+            .body(request.body);
+        TransportFuture::new(async move {
+            let response = request.send().await?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response.bytes().await?.to_vec();
+            Ok(HttpResponse {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+}
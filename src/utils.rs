@@ -1,166 +1,219 @@
-use crate::models::ast::*;
-use quote::ToTokens;
-use syn::{Item as SynItem, ItemEnum, ItemFn, ItemMod, ItemStruct, ItemUse};
-
-pub fn parse_rust_ast(code: &str) -> Result<SerializableAst, syn::Error> {
-    let file = syn::parse_file(code)?;
-    let items = file.items.into_iter().map(convert_item).collect();
-    Ok(SerializableAst { items })
+#[cfg(feature = "ast")]
+pub use crate::code::parse_rust_ast;
+pub use crate::code::{extract_code_blocks, CodeBlock};
+use crate::errors::VoyageError;
+use crate::models::metadata::Metadata;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A passage of a Markdown document, split along its heading structure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownChunk {
+    /// The passage, without its headings
+    pub text: String,
+    /// Titles of the headings enclosing the passage, outermost first
+    pub headings: Vec<String>,
+    /// Languages of the fenced code blocks in the passage, `""` for blocks
+    /// without one
+    pub code_languages: Vec<String>,
 }
 
-fn convert_item(item: SynItem) -> Item {
-    match item {
-        SynItem::Fn(f) => Item::Function(convert_function(f)),
-        SynItem::Struct(s) => Item::Struct(convert_struct(s)),
-        SynItem::Enum(e) => Item::Enum(convert_enum(e)),
-        SynItem::Mod(m) => Item::Module(convert_module(m)),
-        SynItem::Use(u) => Item::Use(convert_use(u)),
-        other => Item::Other(other.to_token_stream().to_string()),
+impl MarkdownChunk {
+    /// The enclosing headings joined with `" > "`, e.g. `"Guide > Setup"`.
+    pub fn breadcrumb(&self) -> String {
+        self.headings.join(" > ")
     }
-}
 
-fn convert_function(f: ItemFn) -> Function {
-    Function {
-        name: f.sig.ident.to_string(),
-        visibility: Some(f.vis.to_token_stream().to_string()),
-        inputs: f
-            .sig
-            .inputs
-            .iter()
-            .map(|arg| arg.to_token_stream().to_string())
-            .collect(),
-        output: match &f.sig.output {
-            syn::ReturnType::Default => None,
-            syn::ReturnType::Type(_, ty) => Some(ty.to_token_stream().to_string()),
-        },
-        is_async: f.sig.asyncness.is_some(),
+    /// The passage preceded by its breadcrumb, so that its embedding
+    /// reflects where the passage sits in the document.
+    pub fn text_with_breadcrumb(&self) -> String {
+        if self.headings.is_empty() {
+            self.text.clone()
+        } else {
+            format!("{}\n\n{}", self.breadcrumb(), self.text)
+        }
     }
-}
 
-fn convert_struct(s: ItemStruct) -> Struct {
-    Struct {
-        name: s.ident.to_string(),
-        visibility: Some(s.vis.to_token_stream().to_string()),
-        fields: s
-            .fields
-            .iter()
-            .map(|f| Field {
-                name: f
-                    .ident
-                    .as_ref()
-                    .map(|i| i.to_string())
-                    .unwrap_or_default(),
-                ty: f.ty.to_token_stream().to_string(),
-                visibility: Some(f.vis.to_token_stream().to_string()),
-            })
-            .collect(),
+    /// Structure metadata for a vector store: `breadcrumb`, `heading`,
+    /// `heading_level` (0 before the first heading) and, for passages with
+    /// code, `code_languages` joined with commas.
+    pub fn metadata(&self) -> Metadata {
+        let mut metadata = Metadata::new();
+        metadata.insert("breadcrumb".to_string(), self.breadcrumb().into());
+        metadata.insert(
+            "heading".to_string(),
+            self.headings.last().cloned().unwrap_or_default().into(),
+        );
+        metadata.insert(
+            "heading_level".to_string(),
+            (self.headings.len() as f64).into(),
+        );
+        if !self.code_languages.is_empty() {
+            metadata.insert(
+                "code_languages".to_string(),
+                self.code_languages.join(",").into(),
+            );
+        }
+        metadata
     }
 }
 
-fn convert_enum(e: ItemEnum) -> Enum {
-    Enum {
-        name: e.ident.to_string(),
-        visibility: Some(e.vis.to_token_stream().to_string()),
-        variants: e.variants.iter().map(|v| v.ident.to_string()).collect(),
-    }
+/// A block of a Markdown section: a paragraph, or a fenced code block that
+/// is never split.
+struct MarkdownBlock {
+    text: String,
+    code_language: Option<String>,
 }
 
-fn convert_module(m: ItemMod) -> Module {
-    Module {
-        name: m.ident.to_string(),
-        visibility: Some(m.vis.to_token_stream().to_string()),
-    }
-}
+/// Splits `markdown` into chunks of at most `max_chars` characters that do
+/// not cross headings.
+///
+/// Each ATX heading (`#` to `######`) starts a new section, whose chunks
+/// carry the titles of the headings enclosing it. Paragraphs are packed into
+/// chunks like [`ParagraphChunker`](crate::chunking::ParagraphChunker) does;
+/// fenced code blocks stay whole, even when longer than `max_chars`, and
+/// headings inside them are ignored.
+pub fn chunk_markdown(markdown: &str, max_chars: usize) -> Vec<MarkdownChunk> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut blocks: Vec<MarkdownBlock> = Vec::new();
+    let mut paragraph = String::new();
+    let mut lines = markdown.lines();
 
-fn convert_use(u: ItemUse) -> Use {
-    Use {
-        path: u.tree.to_token_stream().to_string(),
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
+        if let Some(fence) = code_fence(trimmed) {
+            flush_paragraph(&mut paragraph, &mut blocks);
+            let language = trimmed[fence.len()..].split_whitespace().next();
+            let mut text = format!("{line}\n");
+            for code_line in lines.by_ref() {
+                text.push_str(code_line);
+                text.push('\n');
+                if code_line.trim_start().starts_with(fence) {
+                    break;
+                }
+            }
+            blocks.push(MarkdownBlock {
+                text: text.trim_end().to_string(),
+                code_language: Some(language.unwrap_or_default().to_string()),
+            });
+        } else if let Some((level, title)) = atx_heading(trimmed) {
+            flush_paragraph(&mut paragraph, &mut blocks);
+            pack_section(&headings, std::mem::take(&mut blocks), max_chars, &mut chunks);
+            headings.retain(|(outer, _)| *outer < level);
+            headings.push((level, title.to_string()));
+        } else if trimmed.is_empty() {
+            flush_paragraph(&mut paragraph, &mut blocks);
+        } else {
+            if !paragraph.is_empty() {
+                paragraph.push('\n');
+            }
+            paragraph.push_str(line.trim_end());
+        }
     }
+    flush_paragraph(&mut paragraph, &mut blocks);
+    pack_section(&headings, blocks, max_chars, &mut chunks);
+    chunks
 }
 
-pub struct CodeBlock {
-    pub language: Option<String>,
-    pub content: String,
+/// The fence opening a code block on `line`, if any.
+fn code_fence(line: &str) -> Option<&'static str> {
+    ["```", "~~~"]
+        .into_iter()
+        .find(|fence| line.starts_with(fence))
 }
 
-impl CodeBlock {
-    pub fn new(language: Option<String>, content: String) -> Self {
-        Self { language, content }
+/// The level and title of an ATX heading.
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
     }
+    Some((level, rest.trim().trim_end_matches('#').trim_end()))
+}
 
-    pub fn parse(&self) -> Result<SerializableAst, syn::Error> {
-        match self.language.as_deref() {
-            Some("rust") | Some("rs") => parse_rust_ast(&self.content),
-            _ => Ok(SerializableAst { items: vec![] }),
-        }
+fn flush_paragraph(paragraph: &mut String, blocks: &mut Vec<MarkdownBlock>) {
+    if !paragraph.is_empty() {
+        blocks.push(MarkdownBlock {
+            text: std::mem::take(paragraph),
+            code_language: None,
+        });
     }
 }
 
-pub fn extract_code_blocks(markdown: &str) -> Vec<CodeBlock> {
-    let mut blocks = Vec::new();
-    let mut lines = markdown.lines().peekable();
-
-    while let Some(line) = lines.next() {
-        if line.starts_with("```") {
-            let language = line.trim_start_matches('`').trim().to_string();
-            let language = if language.is_empty() {
-                None
-            } else {
-                Some(language)
-            };
-
-            let mut content = String::new();
-            for content_line in lines.by_ref() {
-                if content_line.starts_with("```") {
-                    break;
-                }
-                content.push_str(content_line);
-                content.push('\n');
-            }
-            blocks.push(CodeBlock::new(language, content));
+/// Packs the blocks of one section into chunks.
+fn pack_section(
+    headings: &[(usize, String)],
+    blocks: Vec<MarkdownBlock>,
+    max_chars: usize,
+    chunks: &mut Vec<MarkdownChunk>,
+) {
+    let new_chunk = || MarkdownChunk {
+        text: String::new(),
+        headings: headings.iter().map(|(_, title)| title.clone()).collect(),
+        code_languages: Vec::new(),
+    };
+    let mut current = new_chunk();
+    for block in blocks {
+        let length = block.text.chars().count();
+        if !current.text.is_empty() && current.text.chars().count() + length + 2 > max_chars {
+            chunks.push(std::mem::replace(&mut current, new_chunk()));
+        }
+        if block.code_language.is_none() && length > max_chars {
+            let chars: Vec<char> = block.text.chars().collect();
+            chunks.extend(chars.chunks(max_chars).map(|part| MarkdownChunk {
+                text: part.iter().collect(),
+                ..new_chunk()
+            }));
+            continue;
         }
+        if !current.text.is_empty() {
+            current.text.push_str("\n\n");
+        }
+        current.text.push_str(&block.text);
+        current.code_languages.extend(block.code_language);
+    }
+    if !current.text.is_empty() {
+        chunks.push(current);
     }
-
-    blocks
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_parse_rust_ast() {
-        let code = r#"
-            struct Test {
-                field: String,
-            }
-
-            fn example() -> String {
-                "test".to_string()
-            }
-        "#;
+/// File extensions picked up by [`collect_text_files`].
+pub const TEXT_EXTENSIONS: &[&str] = &["md", "markdown", "txt", "rst", "rs"];
 
-        let ast = parse_rust_ast(code).unwrap();
-        assert_eq!(ast.items.len(), 2);
+/// `path` itself if it is a file, otherwise the files below the directory
+/// `path` with one of the [`TEXT_EXTENSIONS`], skipping hidden entries, in
+/// sorted order.
+pub fn collect_text_files(path: &Path) -> Result<Vec<PathBuf>, VoyageError> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
     }
 
-    #[test]
-    fn test_extract_code_blocks() {
-        let markdown = r#"
-Some text
-
-```rust
-fn test() {}
-```
-
-```
-plain text
-```
-"#;
-
-        let blocks = extract_code_blocks(markdown);
-        assert_eq!(blocks.len(), 2);
-        assert_eq!(blocks[0].language, Some("rust".to_string()));
-        assert!(blocks[0].content.contains("fn test()"));
+    let mut files = Vec::new();
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry_path = entry?.path();
+            let hidden = entry_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with('.'));
+            if hidden {
+                continue;
+            }
+            if entry_path.is_dir() {
+                pending.push(entry_path);
+            } else if entry_path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| TEXT_EXTENSIONS.contains(&ext))
+            {
+                files.push(entry_path);
+            }
+        }
     }
+    files.sort();
+    Ok(files)
 }
@@ -0,0 +1,195 @@
+//! Vector arithmetic and pooling of embeddings.
+//!
+//! Building blocks for working with embeddings as plain `&[f32]`:
+//!
+//! - [`norm`], [`normalize`] and [`normalized`]: L2 length and unit vectors.
+//! - [`add`] and [`subtract`]: element-wise sums and differences, e.g. for
+//!   analogy-style queries (`king - man + woman`).
+//! - [`centroid`]: the mean point of a set of vectors.
+//! - [`check_dimensions`]: the common dimension of two vectors.
+//!
+//! and for combining the embeddings of a document's chunks, or of any set of
+//! related texts, into a single vector comparable with the others:
+//!
+//! - [`mean_pool`]: the average of the vectors.
+//! - [`weighted_mean_pool`]: the average with a weight per vector.
+//! - [`length_normalized_pool`]: the average weighted by the length of each
+//!   vector's text and scaled to unit length, so that a short trailing chunk
+//!   does not count as much as a full one.
+//! - [`max_pool`]: the largest value of each dimension.
+//!
+//! Functions of several vectors require a common dimension and return a
+//! [`VectorError`] otherwise, which converts into [`VoyageError`](crate::VoyageError).
+//!
+//! ```
+//! use voyageai::vectors::{add, length_normalized_pool, subtract, weighted_mean_pool};
+//! let chunks = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+//! assert_eq!(weighted_mean_pool(&chunks, &[3.0, 1.0]).unwrap(), vec![0.75, 0.25]);
+//! let document = length_normalized_pool(&chunks, &[1200, 1200]).unwrap();
+//! assert!((document[0] - 0.5f32.sqrt()).abs() < 1e-6);
+//!
+//! let (king, man, woman) = ([0.9, 0.8], [0.5, 0.1], [0.5, 0.9]);
+//! let queen = add(&subtract(&king, &man).unwrap(), &woman).unwrap();
+//! assert!((queen[1] - 1.6).abs() < 1e-6);
+//! ```
+
+use thiserror::Error;
+
+/// Why vectors could not be combined.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum VectorError {
+    /// No vectors were given
+    #[error("no vectors given")]
+    Empty,
+    /// The vector at `index` does not have the dimension of the first one
+    #[error("vector {index} has {actual} dimensions, expected {expected}")]
+    DimensionMismatch {
+        index: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// The vectors were produced by different models
+    #[error("embedding of model {actual} cannot be compared with one of {expected}")]
+    ModelMismatch { expected: String, actual: String },
+    /// The weights do not match the vectors or are not usable
+    #[error("invalid weights: {0}")]
+    InvalidWeights(String),
+}
+
+/// The common dimension of `a` and `b`.
+pub fn check_dimensions(a: &[f32], b: &[f32]) -> Result<usize, VectorError> {
+    if a.len() != b.len() {
+        return Err(VectorError::DimensionMismatch {
+            index: 1,
+            expected: a.len(),
+            actual: b.len(),
+        });
+    }
+    Ok(a.len())
+}
+
+/// The common dimension of a non-empty set of `vectors`.
+fn check_all(vectors: &[Vec<f32>]) -> Result<usize, VectorError> {
+    let dimension = vectors.first().ok_or(VectorError::Empty)?.len();
+    match vectors.iter().position(|vector| vector.len() != dimension) {
+        Some(index) => Err(VectorError::DimensionMismatch {
+            index,
+            expected: dimension,
+            actual: vectors[index].len(),
+        }),
+        None => Ok(dimension),
+    }
+}
+
+/// L2 length of `vector`.
+pub fn norm(vector: &[f32]) -> f32 {
+    vector.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
+
+/// Scales `vector` to unit L2 norm in place; a zero vector is left as is.
+pub fn normalize(vector: &mut [f32]) {
+    let norm = norm(vector);
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+/// `vector` scaled to unit L2 norm; see [`normalize`].
+pub fn normalized(vector: &[f32]) -> Vec<f32> {
+    let mut vector = vector.to_vec();
+    normalize(&mut vector);
+    vector
+}
+
+/// Element-wise `a + b`.
+pub fn add(a: &[f32], b: &[f32]) -> Result<Vec<f32>, VectorError> {
+    check_dimensions(a, b)?;
+    Ok(a.iter().zip(b).map(|(x, y)| x + y).collect())
+}
+
+/// Element-wise `a - b`.
+pub fn subtract(a: &[f32], b: &[f32]) -> Result<Vec<f32>, VectorError> {
+    check_dimensions(a, b)?;
+    Ok(a.iter().zip(b).map(|(x, y)| x - y).collect())
+}
+
+/// The mean point of `vectors`, summed in `f64` so that large sets do not
+/// lose precision.
+pub fn centroid(vectors: &[Vec<f32>]) -> Result<Vec<f32>, VectorError> {
+    let dimension = check_all(vectors)?;
+    let mut sum = vec![0.0f64; dimension];
+    for vector in vectors {
+        for (total, x) in sum.iter_mut().zip(vector) {
+            *total += f64::from(*x);
+        }
+    }
+    Ok(sum
+        .into_iter()
+        .map(|total| (total / vectors.len() as f64) as f32)
+        .collect())
+}
+
+/// Average of `vectors`: their [`centroid`].
+pub fn mean_pool(vectors: &[Vec<f32>]) -> Result<Vec<f32>, VectorError> {
+    centroid(vectors)
+}
+
+/// Average of `vectors` with `weights[i]` for `vectors[i]`. Weights must be
+/// finite, non-negative and not all zero; they need not sum to one.
+pub fn weighted_mean_pool(vectors: &[Vec<f32>], weights: &[f32]) -> Result<Vec<f32>, VectorError> {
+    let dimension = check_all(vectors)?;
+    if weights.len() != vectors.len() {
+        return Err(VectorError::InvalidWeights(format!(
+            "expected {} weights, got {}",
+            vectors.len(),
+            weights.len()
+        )));
+    }
+    if weights
+        .iter()
+        .any(|weight| !weight.is_finite() || *weight < 0.0)
+    {
+        return Err(VectorError::InvalidWeights(
+            "weights must be finite and non-negative".to_string(),
+        ));
+    }
+    let total: f64 = weights.iter().map(|weight| f64::from(*weight)).sum();
+    if total == 0.0 {
+        return Err(VectorError::InvalidWeights(
+            "weights are all zero".to_string(),
+        ));
+    }
+
+    let mut sum = vec![0.0f64; dimension];
+    for (vector, weight) in vectors.iter().zip(weights) {
+        for (total, x) in sum.iter_mut().zip(vector) {
+            *total += f64::from(*x) * f64::from(*weight);
+        }
+    }
+    Ok(sum.into_iter().map(|sum| (sum / total) as f32).collect())
+}
+
+/// Average of `vectors` weighted by `lengths[i]`, the length of the text
+/// `vectors[i]` embeds in any unit (bytes, tokens), scaled to unit length
+/// like the API's embeddings.
+pub fn length_normalized_pool(
+    vectors: &[Vec<f32>],
+    lengths: &[usize],
+) -> Result<Vec<f32>, VectorError> {
+    let weights: Vec<f32> = lengths.iter().map(|length| *length as f32).collect();
+    let mut pooled = weighted_mean_pool(vectors, &weights)?;
+    normalize(&mut pooled);
+    Ok(pooled)
+}
+
+/// Largest value of each dimension over `vectors`.
+pub fn max_pool(vectors: &[Vec<f32>]) -> Result<Vec<f32>, VectorError> {
+    let dimension = check_all(vectors)?;
+    let mut max = vec![f32::NEG_INFINITY; dimension];
+    for vector in vectors {
+        for (max, &x) in max.iter_mut().zip(vector) {
+            *max = max.max(x);
+        }
+    }
+    Ok(max)
+}
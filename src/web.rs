@@ -0,0 +1,461 @@
+//! Crawling small documentation sites into a [`RetrievalPipeline`], behind
+//! the `web` feature.
+//!
+//! [`WebIngestor`] fetches a seed page and, up to a depth limit, the pages
+//! it links to on the same host, honoring the site's `robots.txt`. The main
+//! content of each page is extracted as plain text and added to the pipeline
+//! with its URL and title as metadata:
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), voyageai::VoyageError> {
+//! use voyageai::{retrieval::RetrievalPipeline, web::WebIngestor, VoyageAiClient};
+//!
+//! let client = VoyageAiClient::new();
+//! let mut pipeline = RetrievalPipeline::new(&client);
+//! let pages = WebIngestor::new()
+//!     .with_max_depth(2)
+//!     .ingest("https://docs.example.com/", &mut pipeline)
+//!     .await?;
+//! println!("indexed {} pages", pages.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+use log::{debug, info, warn};
+use reqwest::header::{CONTENT_TYPE, USER_AGENT};
+use reqwest::Url;
+use scraper::{ElementRef, Html, Node, Selector};
+
+use crate::errors::VoyageError;
+use crate::models::metadata::Metadata;
+use crate::retrieval::RetrievalPipeline;
+
+/// User agent sent with every request and matched against `robots.txt`.
+pub const DEFAULT_USER_AGENT: &str = "voyageai-rs";
+
+/// Default limit of pages fetched by one crawl.
+pub const DEFAULT_MAX_PAGES: usize = 100;
+
+/// Elements whose text is not part of a page's main content.
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "nav", "header", "footer", "aside", "form",
+];
+
+/// Elements that start a new paragraph of extracted text.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "li",
+    "ul",
+    "ol",
+    "pre",
+    "blockquote",
+    "table",
+    "tr",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "dl",
+    "dt",
+    "dd",
+    "br",
+    "hr",
+];
+
+/// A page fetched by [`WebIngestor::crawl`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebPage {
+    /// The page's URL, without fragment
+    pub url: String,
+    /// Contents of the `<title>` element
+    pub title: Option<String>,
+    /// Main-content text, paragraphs separated by blank lines
+    pub text: String,
+    /// Links followed from the seed to reach the page
+    pub depth: usize,
+}
+
+impl WebPage {
+    /// `url`, `title` (when present) and `depth` as document metadata.
+    pub fn metadata(&self) -> Metadata {
+        let mut metadata = Metadata::new();
+        metadata.insert("url".to_string(), self.url.clone().into());
+        if let Some(title) = &self.title {
+            metadata.insert("title".to_string(), title.clone().into());
+        }
+        metadata.insert("depth".to_string(), (self.depth as f64).into());
+        metadata
+    }
+}
+
+/// Crawls a site from a seed URL, see the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct WebIngestor {
+    http: reqwest::Client,
+    user_agent: String,
+    max_depth: usize,
+    max_pages: usize,
+    respect_robots: bool,
+}
+
+impl Default for WebIngestor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebIngestor {
+    /// Fetches only the seed page, honoring `robots.txt`.
+    pub fn new() -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            max_depth: 0,
+            max_pages: DEFAULT_MAX_PAGES,
+            respect_robots: true,
+        }
+    }
+
+    /// Follows same-host links up to `max_depth` links away from the seed.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Stops after fetching `max_pages` pages.
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages.max(1);
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Uses `http` for requests, e.g. one with timeouts or a proxy.
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Whether to skip pages disallowed by `robots.txt` and wait its
+    /// `Crawl-delay` between requests. On by default.
+    pub fn respect_robots(mut self, enabled: bool) -> Self {
+        self.respect_robots = enabled;
+        self
+    }
+
+    /// Fetches the seed page and the pages reachable from it, in
+    /// breadth-first order. Fails if the seed cannot be fetched; other pages
+    /// that fail or are not HTML are logged and skipped.
+    pub async fn crawl(&self, seed: &str) -> Result<Vec<WebPage>, VoyageError> {
+        let mut seed = Url::parse(seed)
+            .map_err(|e| VoyageError::WebIngestError(format!("invalid seed URL '{seed}': {e}")))?;
+        seed.set_fragment(None);
+        let robots = if self.respect_robots {
+            self.robots(&seed).await
+        } else {
+            RobotsRules::default()
+        };
+        if !robots.allows(seed.path()) {
+            return Err(VoyageError::WebIngestError(format!(
+                "robots.txt disallows {seed}"
+            )));
+        }
+
+        let mut pages = Vec::new();
+        let mut seen = HashSet::from([seed.to_string()]);
+        let mut queue = VecDeque::from([(seed.clone(), 0)]);
+        while let Some((url, depth)) = queue.pop_front() {
+            if pages.len() >= self.max_pages {
+                break;
+            }
+            if !pages.is_empty() {
+                if let Some(delay) = robots.crawl_delay {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+            let html = match self.fetch_html(&url).await {
+                Ok(Some(html)) => html,
+                Ok(None) => continue,
+                Err(e) if url == seed => return Err(e),
+                Err(e) => {
+                    warn!("Skipping {}: {}", url, e);
+                    continue;
+                }
+            };
+            let document = Html::parse_document(&html);
+            if depth < self.max_depth {
+                for link in links(&document, &url) {
+                    if link.host_str() == seed.host_str()
+                        && link.port_or_known_default() == seed.port_or_known_default()
+                        && robots.allows(link.path())
+                        && seen.insert(link.to_string())
+                    {
+                        queue.push_back((link, depth + 1));
+                    }
+                }
+            }
+            debug!("Fetched {} at depth {}", url, depth);
+            pages.push(WebPage {
+                url: url.to_string(),
+                title: title(&document),
+                text: main_text(&document),
+                depth,
+            });
+        }
+        info!("Crawled {} pages from {}", pages.len(), seed);
+        Ok(pages)
+    }
+
+    /// Crawls from `seed` and adds every page with text to `pipeline`, keyed
+    /// by URL, with the [page metadata](WebPage::metadata). Returns the pages
+    /// added.
+    pub async fn ingest(
+        &self,
+        seed: &str,
+        pipeline: &mut RetrievalPipeline,
+    ) -> Result<Vec<WebPage>, VoyageError> {
+        let mut pages = self.crawl(seed).await?;
+        pages.retain(|page| !page.text.is_empty());
+        for page in &pages {
+            pipeline
+                .add_document(page.url.clone(), &page.text, Some(page.metadata()))
+                .await?;
+        }
+        Ok(pages)
+    }
+
+    /// The body of `url` if it is an HTML page.
+    async fn fetch_html(&self, url: &Url) -> Result<Option<String>, VoyageError> {
+        let response = self
+            .http
+            .get(url.clone())
+            .header(USER_AGENT, &self.user_agent)
+            .send()
+            .await?
+            .error_for_status()?;
+        let is_html = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_none_or(|value| value.starts_with("text/html"));
+        if !is_html {
+            debug!("Skipping {}: not an HTML page", url);
+            return Ok(None);
+        }
+        Ok(Some(response.text().await?))
+    }
+
+    /// The rules of the site's `robots.txt` for our user agent; everything
+    /// is allowed when it cannot be fetched.
+    async fn robots(&self, seed: &Url) -> RobotsRules {
+        let Ok(url) = seed.join("/robots.txt") else {
+            return RobotsRules::default();
+        };
+        let response = self
+            .http
+            .get(url)
+            .header(USER_AGENT, &self.user_agent)
+            .send()
+            .await;
+        match response {
+            Ok(response) if response.status().is_success() => match response.text().await {
+                Ok(text) => RobotsRules::parse(&text, &self.user_agent),
+                Err(_) => RobotsRules::default(),
+            },
+            _ => RobotsRules::default(),
+        }
+    }
+}
+
+/// The `robots.txt` rules applying to one user agent.
+#[derive(Debug, Default)]
+struct RobotsRules {
+    /// `(allowed, pattern)` pairs
+    rules: Vec<(bool, String)>,
+    crawl_delay: Option<Duration>,
+}
+
+impl RobotsRules {
+    /// Reads the group naming `user_agent`, or the `*` group if none does.
+    fn parse(text: &str, user_agent: &str) -> Self {
+        let agent = user_agent
+            .split('/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let mut specific = RobotsRules::default();
+        let mut wildcard = RobotsRules::default();
+        let mut found_specific = false;
+        // Agents of the current group, and whether its rules have started
+        let mut group: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let (key, value) = (key.trim().to_ascii_lowercase(), value.trim());
+            if key == "user-agent" {
+                if in_rules {
+                    group.clear();
+                    in_rules = false;
+                }
+                group.push(value.to_ascii_lowercase());
+                continue;
+            }
+            in_rules = true;
+            let matches_agent = group
+                .iter()
+                .any(|name| name != "*" && agent.contains(name.as_str()));
+            let target = if matches_agent {
+                found_specific = true;
+                &mut specific
+            } else if group.iter().any(|name| name == "*") {
+                &mut wildcard
+            } else {
+                continue;
+            };
+            match key.as_str() {
+                "allow" | "disallow" if !value.is_empty() => {
+                    target.rules.push((key == "allow", value.to_string()))
+                }
+                "crawl-delay" => {
+                    target.crawl_delay = value
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                }
+                _ => {}
+            }
+        }
+        if found_specific {
+            specific
+        } else {
+            wildcard
+        }
+    }
+
+    /// Whether `path` may be fetched: the longest matching rule wins, and
+    /// `Allow` wins ties.
+    fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| robots_match(pattern, path))
+            .max_by_key(|(allowed, pattern)| (pattern.len(), *allowed))
+            .is_none_or(|(allowed, _)| *allowed)
+    }
+}
+
+/// Matches a `robots.txt` path pattern, supporting `*` and a trailing `$`.
+fn robots_match(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let last = i + 1 == parts.len();
+        if anchored && last {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+fn title(document: &Html) -> Option<String> {
+    let selector = Selector::parse("title").expect("valid selector");
+    document
+        .select(&selector)
+        .next()
+        .map(|title| collapse_whitespace(&title.text().collect::<String>()))
+        .filter(|title| !title.is_empty())
+}
+
+/// Absolute http(s) links of the page, without fragments.
+fn links(document: &Html, base: &Url) -> Vec<Url> {
+    let selector = Selector::parse("a[href]").expect("valid selector");
+    document
+        .select(&selector)
+        .filter_map(|link| base.join(link.value().attr("href")?).ok())
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .map(|mut url| {
+            url.set_fragment(None);
+            url
+        })
+        .collect()
+}
+
+/// Text of the page's `<main>`, `<article>` or `role="main"` element, else of
+/// its body, leaving out navigation, scripts and the like.
+fn main_text(document: &Html) -> String {
+    let root = ["main", "article", "[role=main]", "body"]
+        .iter()
+        .find_map(|name| {
+            let selector = Selector::parse(name).expect("valid selector");
+            document.select(&selector).next()
+        });
+    let Some(root) = root else {
+        return String::new();
+    };
+    let mut paragraphs = vec![String::new()];
+    collect_text(root, &mut paragraphs);
+    paragraphs
+        .iter()
+        .map(|paragraph| collapse_whitespace(paragraph))
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn collect_text(element: ElementRef, paragraphs: &mut Vec<String>) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => paragraphs.last_mut().unwrap().push_str(text),
+            Node::Element(child_element) => {
+                let name = child_element.name();
+                if SKIPPED_ELEMENTS.contains(&name) {
+                    continue;
+                }
+                let block = BLOCK_ELEMENTS.contains(&name);
+                if block {
+                    paragraphs.push(String::new());
+                }
+                if let Some(child) = ElementRef::wrap(child) {
+                    collect_text(child, paragraphs);
+                }
+                if block {
+                    paragraphs.push(String::new());
+                } else {
+                    paragraphs.last_mut().unwrap().push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
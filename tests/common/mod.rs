@@ -0,0 +1,217 @@
+//! A scripted [`Transport`] shared by the tests that need to control when and
+//! how requests are answered, which the wiremock-backed `TestServer` cannot
+//! do under paused time.
+
+// Each test crate uses a different part of the helper
+#![allow(dead_code)]
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use voyageai::{
+    test_util::{fixtures, TEST_API_KEY},
+    transport::{HttpRequest, HttpResponse, StatusCode, Transport, TransportFuture},
+    VoyageAiClient, VoyageConfig, VoyageError,
+};
+
+/// Dimension of the embeddings answered by [`Reply::ok`].
+pub const EMBEDDING_DIMENSION: usize = 1024;
+
+/// How a [`ScriptedTransport`] answers one request.
+#[derive(Debug, Clone)]
+pub struct Reply {
+    delay: Duration,
+    outcome: Outcome,
+}
+
+#[derive(Debug, Clone)]
+enum Outcome {
+    Embeddings(fn(&str) -> Vec<f32>),
+    Response(StatusCode, Vec<u8>),
+    Failure,
+    Hang,
+}
+
+impl Reply {
+    /// A 200 answer: one embedding of [`EMBEDDING_DIMENSION`] values per
+    /// input for embeddings requests, [`fixtures::RERANK`] for rerank ones.
+    pub fn ok() -> Self {
+        Self::embeddings(|_| vec![0.5; EMBEDDING_DIMENSION])
+    }
+
+    /// A 200 answer embedding each input with `embed`.
+    pub fn embeddings(embed: fn(&str) -> Vec<f32>) -> Self {
+        Self::from(Outcome::Embeddings(embed))
+    }
+
+    /// An answer with `status` and a JSON error body.
+    pub fn error(status: u16) -> Self {
+        Self::from(Outcome::Response(
+            StatusCode::from_u16(status).unwrap(),
+            br#"{"detail": "failed"}"#.to_vec(),
+        ))
+    }
+
+    /// No response, as when the connection is reset.
+    pub fn failure() -> Self {
+        Self::from(Outcome::Failure)
+    }
+
+    /// No answer at all, until the caller gives up.
+    pub fn hang() -> Self {
+        Self::from(Outcome::Hang)
+    }
+
+    /// The same answer, sent after `delay`.
+    pub fn after(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+impl From<Outcome> for Reply {
+    fn from(outcome: Outcome) -> Self {
+        Self {
+            delay: Duration::ZERO,
+            outcome,
+        }
+    }
+}
+
+type Script = dyn Fn(&HttpRequest) -> Reply + Send + Sync;
+
+/// Answers requests as its script says, and records them.
+///
+/// Clones share the script and the record, so a test keeps a clone to look
+/// at the requests once the client owns the transport.
+#[derive(Clone)]
+pub struct ScriptedTransport {
+    script: Arc<Script>,
+    requests: Arc<Mutex<Vec<HttpRequest>>>,
+    in_flight: Arc<Mutex<(usize, usize)>>,
+}
+
+impl ScriptedTransport {
+    /// A transport answering each request with `script(request)`.
+    pub fn new(script: impl Fn(&HttpRequest) -> Reply + Send + Sync + 'static) -> Self {
+        Self {
+            script: Arc::new(script),
+            requests: Arc::default(),
+            in_flight: Arc::default(),
+        }
+    }
+
+    /// A transport answering with `replies` in order, then with
+    /// [`Reply::ok`].
+    pub fn replies(replies: impl IntoIterator<Item = Reply>) -> Self {
+        let replies = Mutex::new(replies.into_iter().collect::<Vec<_>>().into_iter());
+        Self::new(move |_| replies.lock().unwrap().next().unwrap_or_else(Reply::ok))
+    }
+
+    /// A configuration sending its requests through this transport.
+    pub fn config(&self) -> VoyageConfig {
+        VoyageConfig::new(TEST_API_KEY.to_string())
+            .with_base_url("http://scripted.invalid")
+            .with_transport(self.clone())
+    }
+
+    /// A client sending its requests through this transport.
+    pub fn client(&self) -> VoyageAiClient {
+        VoyageAiClient::new_with_config(self.config())
+    }
+
+    /// Requests sent so far.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Number of requests sent so far.
+    pub fn sent(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    /// The most requests that were waiting for an answer at once.
+    pub fn peak_in_flight(&self) -> usize {
+        self.in_flight.lock().unwrap().1
+    }
+}
+
+impl Default for ScriptedTransport {
+    /// A transport answering every request with [`Reply::ok`].
+    fn default() -> Self {
+        Self::new(|_| Reply::ok())
+    }
+}
+
+impl fmt::Debug for ScriptedTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScriptedTransport")
+            .field("sent", &self.sent())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Transport for ScriptedTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture {
+        let reply = (self.script)(&request);
+        self.requests.lock().unwrap().push(request.clone());
+        let in_flight = self.in_flight.clone();
+        TransportFuture::new(async move {
+            {
+                let mut in_flight = in_flight.lock().unwrap();
+                in_flight.0 += 1;
+                in_flight.1 = in_flight.1.max(in_flight.0);
+            }
+            tokio::time::sleep(reply.delay).await;
+            in_flight.lock().unwrap().0 -= 1;
+            let (status, body) = match reply.outcome {
+                Outcome::Embeddings(embed) => (StatusCode::OK, answer(&request, embed)),
+                Outcome::Response(status, body) => (status, body),
+                Outcome::Failure => {
+                    return Err(VoyageError::TransportError("connection reset".to_string()))
+                }
+                Outcome::Hang => std::future::pending().await,
+            };
+            Ok(HttpResponse {
+                status,
+                headers: Default::default(),
+                body,
+            })
+        })
+    }
+}
+
+/// The JSON body of `request`.
+pub fn body(request: &HttpRequest) -> Value {
+    serde_json::from_slice(&request.body).unwrap()
+}
+
+/// The texts of an embeddings request.
+pub fn inputs(request: &HttpRequest) -> Vec<String> {
+    match &body(request)["input"] {
+        Value::String(text) => vec![text.clone()],
+        input => serde_json::from_value(input.clone()).unwrap(),
+    }
+}
+
+fn answer(request: &HttpRequest, embed: fn(&str) -> Vec<f32>) -> Vec<u8> {
+    if request.url.ends_with("/rerank") {
+        return fixtures::RERANK.as_bytes().to_vec();
+    }
+    let texts = inputs(request);
+    let data: Vec<Value> = texts
+        .iter()
+        .enumerate()
+        .map(|(index, text)| json!({"object": "embedding", "embedding": embed(text), "index": index}))
+        .collect();
+    json!({
+        "object": "list",
+        "data": data,
+        "model": body(request)["model"],
+        "usage": {"total_tokens": texts.len()}
+    })
+    .to_string()
+    .into_bytes()
+}
@@ -9,8 +9,8 @@ use voyageai::{
         embeddings::{EmbeddingModel, EmbeddingsInput},
         search::{SearchModel, SearchQuery, SearchType},
     },
-    InputType, RerankModel, RerankRequest, VoyageBuilder,
-    traits::llm::{Embedder, Reranker},
+    InputType, VoyageBuilder,
+    traits::llm::Reranker,
 };
 use tokio_stream::StreamExt;
 
@@ -39,7 +39,7 @@ async fn test_voyage_ai_client() -> Result<(), Box<dyn std::error::Error>> {
         .expect("Failed to build client");
 
     // Test embeddings
-    let texts = vec![
+    let texts = [
         "Paris is the capital of France",
         "London is the capital of England",
         "Berlin is the capital of Germany",
@@ -127,9 +127,15 @@ async fn test_voyage_ai_client() -> Result<(), Box<dyn std::error::Error>> {
                 .map(|d| d.embedding.clone())
                 .collect(),
         ),
+        index: None,
+        namespace: None,
+        metadata: None,
+        filter: None,
         model: SearchModel::default(),
         top_k: None,
         search_type: SearchType::Similarity,
+        rerank: false,
+        min_score: None,
     };
 
     let search_response = client
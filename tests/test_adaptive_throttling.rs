@@ -0,0 +1,127 @@
+use std::time::Duration;
+use voyageai::{
+    client::{AdaptiveThrottling, RateLimit, RateLimitEndpoint, RateLimiter},
+    config::ConfigLoader,
+};
+
+const EMBEDDINGS: RateLimitEndpoint = RateLimitEndpoint::Embeddings;
+
+fn limiter() -> RateLimiter {
+    RateLimiter::new()
+        .with_adaptive_throttling(AdaptiveThrottling::default())
+        .with_limit(EMBEDDINGS, RateLimit::new(600, 1_000_000))
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rejection_halves_limits_once_per_pause() {
+    let limiter = limiter();
+
+    limiter
+        .record_rejection(EMBEDDINGS, 1, Duration::from_secs(1))
+        .await;
+    // Rejections of requests in flight during the pause do not back off again
+    limiter
+        .record_rejection(EMBEDDINGS, 1, Duration::from_secs(1))
+        .await;
+
+    let effective = limiter.effective_limit(EMBEDDINGS);
+    assert_eq!(effective.requests_per_minute, 300);
+    assert_eq!(effective.tokens_per_minute, 500_000);
+    assert_eq!(limiter.limit(EMBEDDINGS).requests_per_minute, 600);
+    // The other endpoint is unaffected
+    assert_eq!(
+        limiter.effective_limit(RateLimitEndpoint::Reranking),
+        limiter.limit(RateLimitEndpoint::Reranking)
+    );
+
+    tokio::time::advance(Duration::from_secs(2)).await;
+    limiter
+        .record_rejection(EMBEDDINGS, 1, Duration::from_secs(1))
+        .await;
+    assert!(limiter.effective_limit(EMBEDDINGS).requests_per_minute < 160);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_limits_ramp_back_up_without_rejections() {
+    let limiter = limiter();
+    limiter
+        .record_rejection(EMBEDDINGS, 1, Duration::from_secs(10))
+        .await;
+
+    // No recovery while paused
+    tokio::time::advance(Duration::from_secs(10)).await;
+    assert_eq!(limiter.effective_limit(EMBEDDINGS).requests_per_minute, 300);
+
+    // 10% of the configured limits per minute
+    tokio::time::advance(Duration::from_secs(60)).await;
+    assert_eq!(limiter.effective_limit(EMBEDDINGS).requests_per_minute, 360);
+
+    tokio::time::advance(Duration::from_secs(600)).await;
+    assert_eq!(
+        limiter.effective_limit(EMBEDDINGS),
+        limiter.limit(EMBEDDINGS)
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_backoff_stops_at_min_rate() {
+    let limiter = RateLimiter::new()
+        .with_limit(EMBEDDINGS, RateLimit::new(1000, 1_000_000))
+        .with_adaptive_throttling(AdaptiveThrottling::new(0.5, 0.0, 0.1));
+
+    for _ in 0..10 {
+        limiter
+            .record_rejection(EMBEDDINGS, 1, Duration::from_secs(1))
+            .await;
+        tokio::time::advance(Duration::from_secs(1)).await;
+    }
+
+    assert_eq!(limiter.effective_limit(EMBEDDINGS).requests_per_minute, 100);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_reduced_limits_delay_requests() {
+    let limiter = RateLimiter::new()
+        .with_adaptive_throttling(AdaptiveThrottling::new(0.5, 0.0, 0.1))
+        .with_limit(
+            EMBEDDINGS,
+            RateLimit::new(60, 1_000_000).with_burst(1, 1_000_000),
+        );
+    limiter
+        .record_rejection(EMBEDDINGS, 1, Duration::from_secs(1))
+        .await;
+
+    // The first request waits out the pause, the next one the halved rate
+    limiter.acquire(EMBEDDINGS, 1).await;
+    let waited = limiter.acquire(EMBEDDINGS, 1).await;
+
+    assert_eq!(waited, Duration::from_secs(2));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_without_adaptive_throttling_limits_stay() {
+    let limiter = RateLimiter::new();
+
+    limiter
+        .record_rejection(EMBEDDINGS, 1, Duration::from_secs(1))
+        .await;
+
+    assert_eq!(
+        limiter.effective_limit(EMBEDDINGS),
+        limiter.limit(EMBEDDINGS)
+    );
+}
+
+#[test]
+fn test_config_enables_adaptive_throttling() {
+    let config = ConfigLoader::new()
+        .without_file()
+        .with_env(|name| (name == "VOYAGE_ADAPTIVE_THROTTLING").then(|| "true".to_string()))
+        .load()
+        .unwrap();
+
+    assert_eq!(
+        config.adaptive_throttling,
+        Some(AdaptiveThrottling::default())
+    );
+}
@@ -0,0 +1,90 @@
+use voyageai::{
+    analysis::{anomaly_scores, anomaly_scores_with, AnomalyMethod},
+    retrieval::RetrievalPipeline,
+    test_util::TestServer,
+    VoyageError,
+};
+
+fn most_anomalous(scores: &[f32]) -> usize {
+    scores
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(position, _)| position)
+        .unwrap()
+}
+
+#[test]
+fn test_distance_to_centroid_flags_off_topic_embedding() {
+    let embeddings = vec![
+        vec![1.0, 0.1, 0.0],
+        vec![0.9, 0.2, 0.0],
+        vec![1.0, 0.0, 0.1],
+        vec![0.0, 0.0, 1.0],
+        vec![0.95, 0.15, 0.05],
+    ];
+    let scores = anomaly_scores(&embeddings);
+    assert_eq!(scores.len(), 5);
+    assert_eq!(most_anomalous(&scores), 3);
+    assert!(scores.iter().all(|score| (0.0..=2.0).contains(score)));
+
+    assert!(anomaly_scores::<Vec<f32>>(&[]).is_empty());
+}
+
+#[test]
+fn test_k_nearest_flags_embedding_between_topics() {
+    // Two tight topics and one embedding close to neither. Its distance to
+    // the centroid is small, but its neighbors are far away.
+    let embeddings = vec![
+        vec![1.0, 0.0, 0.0],
+        vec![0.98, 0.05, 0.0],
+        vec![0.97, 0.0, 0.05],
+        vec![0.0, 1.0, 0.0],
+        vec![0.05, 0.98, 0.0],
+        vec![0.0, 0.97, 0.05],
+        vec![0.5, 0.5, 0.7],
+    ];
+    let centroid = anomaly_scores(&embeddings);
+    assert_ne!(most_anomalous(&centroid), 6);
+
+    let nearest = anomaly_scores_with(&embeddings, AnomalyMethod::KNearest(2));
+    assert_eq!(most_anomalous(&nearest), 6);
+
+    // A single embedding has no neighbors to be unlike
+    assert_eq!(
+        anomaly_scores_with(&[vec![1.0, 0.0]], AnomalyMethod::KNearest(3)),
+        vec![0.0]
+    );
+}
+
+#[tokio::test]
+async fn test_pipeline_report_lists_outlier_chunks() -> Result<(), VoyageError> {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let mut pipeline = RetrievalPipeline::builder(&client)
+        .outlier_detection(AnomalyMethod::DistanceToCentroid, 1)
+        .without_rerank()
+        .build();
+
+    pipeline
+        .add_documents([
+            ("a", "Rust ownership and borrowing rules"),
+            ("b", "Rust borrowing rules and lifetimes"),
+            ("c", "Rust ownership rules and lifetimes"),
+            ("d", "Baking sourdough bread at home"),
+        ])
+        .await?;
+
+    let report = pipeline.report();
+    assert_eq!(report.documents, 4);
+    assert_eq!(report.chunks, 4);
+    assert_eq!(report.tokens_used, pipeline.tokens_used());
+    assert_eq!(report.outliers.len(), 1);
+    assert_eq!(report.outliers[0].document_id, "d");
+    assert_eq!(report.outliers[0].chunk_index, 0);
+    assert_eq!(report.outliers[0].text, "Baking sourdough bread at home");
+
+    let plain = RetrievalPipeline::builder(&client).without_rerank().build();
+    assert!(plain.report().outliers.is_empty());
+    Ok(())
+}
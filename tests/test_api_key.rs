@@ -0,0 +1,136 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use voyageai::{
+    client::embeddings_client::Client as EmbeddingsClient,
+    config::{
+        ApiKeyChain, ApiKeyProvider, CachedApiKey, CallbackApiKey, EnvApiKey, FileApiKey,
+        StaticApiKey,
+    },
+    EmbeddingModel, VoyageConfig, VoyageError,
+};
+
+#[tokio::test]
+async fn test_static_key() {
+    let provider = StaticApiKey::new("secret");
+
+    assert_eq!(provider.api_key().await.unwrap(), "secret");
+    assert!(!format!("{provider:?}").contains("secret"));
+}
+
+#[tokio::test]
+async fn test_env_key() {
+    std::env::set_var("VOYAGEAI_TEST_ENV_KEY", " from-env \n");
+
+    let key = EnvApiKey::new("VOYAGEAI_TEST_ENV_KEY").api_key().await;
+    let missing = EnvApiKey::new("VOYAGEAI_TEST_UNSET_KEY").api_key().await;
+
+    assert_eq!(key.unwrap(), "from-env");
+    assert!(matches!(missing, Err(VoyageError::MissingApiKey)));
+}
+
+#[tokio::test]
+async fn test_file_key_is_reread() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("api_key");
+    let provider = FileApiKey::new(&path);
+    assert!(matches!(
+        provider.api_key().await,
+        Err(VoyageError::MissingApiKey)
+    ));
+
+    std::fs::write(&path, "first\n").unwrap();
+    assert_eq!(provider.api_key().await.unwrap(), "first");
+    std::fs::write(&path, "second\n").unwrap();
+    assert_eq!(provider.api_key().await.unwrap(), "second");
+}
+
+#[tokio::test]
+async fn test_chain_skips_missing_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = ApiKeyChain::new()
+        .with(EnvApiKey::new("VOYAGEAI_TEST_UNSET_KEY"))
+        .with(FileApiKey::new(dir.path().join("missing")))
+        .with(StaticApiKey::new("fallback"));
+
+    assert_eq!(chain.api_key().await.unwrap(), "fallback");
+    assert!(matches!(
+        ApiKeyChain::new().api_key().await,
+        Err(VoyageError::MissingApiKey)
+    ));
+}
+
+#[tokio::test]
+async fn test_cached_callback_key() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let callback = CallbackApiKey::new(move || {
+        let n = counter.fetch_add(1, Ordering::SeqCst);
+        async move { Ok(format!("key-{n}")) }
+    });
+    let provider = CachedApiKey::new(callback, Duration::from_secs(60));
+
+    assert_eq!(provider.api_key().await.unwrap(), "key-0");
+    assert_eq!(provider.api_key().await.unwrap(), "key-0");
+    provider.invalidate();
+    assert_eq!(provider.api_key().await.unwrap(), "key-1");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_requests_use_the_current_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("api_key");
+    std::fs::write(&path, "old-key").unwrap();
+
+    let body = json!({
+        "object": "list",
+        "data": [{"object": "embedding", "embedding": vec![0.1; EmbeddingModel::Voyage3Large.embedding_dimension()], "index": 0}],
+        "model": "voyage-3-large",
+        "usage": {"total_tokens": 1}
+    })
+    .to_string();
+    let mut server = mockito::Server::new_async().await;
+    let old = server
+        .mock("POST", "/v1/embeddings")
+        .match_header("authorization", "Bearer old-key")
+        .with_header("content-type", "application/json")
+        .with_body(&body)
+        .create_async()
+        .await;
+    let new = server
+        .mock("POST", "/v1/embeddings")
+        .match_header("authorization", "Bearer new-key")
+        .with_header("content-type", "application/json")
+        .with_body(&body)
+        .create_async()
+        .await;
+
+    let config = VoyageConfig::with_provider(FileApiKey::new(&path)).with_base_url(server.url());
+    let client = EmbeddingsClient::new(config);
+    client.embed("hello").await.unwrap();
+    std::fs::write(&path, "new-key").unwrap();
+    client.embed("hello").await.unwrap();
+
+    old.assert_async().await;
+    new.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_missing_key_fails_before_sending() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/embeddings")
+        .expect(0)
+        .create_async()
+        .await;
+    let config = VoyageConfig::with_provider(EnvApiKey::new("VOYAGEAI_TEST_UNSET_KEY"))
+        .with_base_url(server.url());
+
+    let result = EmbeddingsClient::new(config).embed("hello").await;
+
+    assert!(matches!(result, Err(VoyageError::MissingApiKey)));
+    mock.assert_async().await;
+}
@@ -0,0 +1,94 @@
+use reqwest::header::{HeaderMap, HeaderValue};
+use voyageai::{
+    client::embeddings_client::Client as EmbeddingsClient,
+    config::{ApiVersion, DEFAULT_BASE_URL},
+    models::{
+        embeddings::{EmbeddingModel, EmbeddingsInput, EmbeddingsRequest},
+        ResponseMetadata,
+    },
+    VoyageConfig,
+};
+
+#[test]
+fn test_default_config_targets_latest_version() {
+    let config = VoyageConfig::new("key".to_string());
+
+    assert_eq!(config.api_version, ApiVersion::LATEST);
+    assert_eq!(config.base_url, DEFAULT_BASE_URL);
+    assert_eq!(
+        config.endpoint("embeddings"),
+        "https://api.voyageai.com/v1/embeddings"
+    );
+}
+
+#[test]
+fn test_endpoint_accepts_versioned_base_url() {
+    let config = VoyageConfig::new("key".to_string()).with_base_url("https://proxy.local/v1/");
+
+    assert_eq!(config.endpoint("/rerank"), "https://proxy.local/v1/rerank");
+}
+
+#[test]
+fn test_api_version_parsing() {
+    assert_eq!("v1".parse::<ApiVersion>(), Ok(ApiVersion::V1));
+    assert!("v9".parse::<ApiVersion>().is_err());
+    assert_eq!(ApiVersion::V1.to_string(), "v1");
+}
+
+#[test]
+fn test_response_headers_parsing() {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-api-version", HeaderValue::from_static("v1"));
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    headers.insert(
+        "sunset",
+        HeaderValue::from_static("Wed, 01 Jan 2031 00:00:00 GMT"),
+    );
+
+    let parsed = ResponseMetadata::from_header_map(&headers);
+
+    assert_eq!(parsed.api_version.as_deref(), Some("v1"));
+    assert!(parsed.is_deprecated());
+    assert!(parsed.warning.is_none());
+    assert!(!ResponseMetadata::default().is_deprecated());
+}
+
+#[tokio::test]
+async fn test_embeddings_response_surfaces_version_headers(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/embeddings")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("x-api-version", "v1")
+        .with_header("deprecation", "true")
+        .with_body(
+            serde_json::json!({
+                "object": "list",
+                "data": [{"object": "embedding", "embedding": vec![0.1; 1024], "index": 0}],
+                "model": "voyage-3-large",
+                "usage": {"total_tokens": 3}
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+
+    let config = VoyageConfig::new("key".to_string()).with_base_url(server.url());
+    let client = EmbeddingsClient::new(config);
+    let request = EmbeddingsRequest {
+        input: EmbeddingsInput::Single("hello".to_string()),
+        model: EmbeddingModel::Voyage3Large,
+        input_type: None,
+        truncation: None,
+        encoding_format: None,
+    };
+
+    let response = client.create_embedding(&request).await?;
+
+    mock.assert_async().await;
+    assert_eq!(response.headers.api_version.as_deref(), Some("v1"));
+    assert_eq!(response.headers.deprecation.as_deref(), Some("true"));
+    Ok(())
+}
@@ -0,0 +1,126 @@
+#![cfg(feature = "ast")]
+
+use voyageai::models::ast::{Item, SerializableAst};
+use voyageai::utils::parse_rust_ast;
+
+const CODE: &str = r#"
+/// A shape with an area.
+pub trait Shape: Send + Sync {
+    type Unit: Copy;
+    const SIDES: u32;
+
+    /// The area of the shape.
+    fn area(&self) -> f64;
+
+    fn describe(&self) -> String {
+        format!("area {}", self.area())
+    }
+}
+
+/// A square.
+#[derive(Debug, Clone)]
+pub struct Square<T: Copy> {
+    /// Length of a side.
+    pub side: T,
+}
+
+impl<T: Copy + Into<f64>> Shape for Square<T> {
+    type Unit = T;
+    const SIDES: u32 = 4;
+
+    fn area(&self) -> f64 {
+        let side: f64 = self.side.into();
+        side * side
+    }
+}
+
+impl<T: Copy> Square<T> {
+    pub fn new(side: T) -> Self {
+        Self { side }
+    }
+}
+
+pub type Squares = Vec<Square<f64>>;
+
+/// Largest side accepted.
+pub const MAX_SIDE: f64 = 1e6;
+
+mod geometry {
+    fn helper() {}
+}
+"#;
+
+#[test]
+fn test_captures_traits_impls_and_docs() {
+    let ast = parse_rust_ast(CODE).unwrap();
+    assert_eq!(ast.items.len(), 7);
+    assert!(!ast.items.iter().any(|item| matches!(item, Item::Other(_))));
+
+    let Item::Trait(shape) = &ast.items[0] else {
+        panic!("expected a trait, got {:?}", ast.items[0]);
+    };
+    assert_eq!(shape.name, "Shape");
+    assert_eq!(shape.docs.as_deref(), Some("A shape with an area."));
+    assert_eq!(shape.supertraits, ["Send", "Sync"]);
+    let methods: Vec<&str> = shape.methods.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(methods, ["area", "describe"]);
+    assert_eq!(
+        shape.methods[0].docs.as_deref(),
+        Some("The area of the shape.")
+    );
+    assert_eq!(shape.types[0].name, "Unit");
+    assert_eq!(shape.types[0].ty, None);
+    assert_eq!(shape.types[0].bounds, ["Copy"]);
+    assert_eq!(shape.consts[0].name, "SIDES");
+
+    let Item::Struct(square) = &ast.items[1] else {
+        panic!("expected a struct, got {:?}", ast.items[1]);
+    };
+    assert_eq!(square.generics.as_deref(), Some("< T : Copy >"));
+    assert_eq!(square.attributes, ["# [derive (Debug , Clone)]"]);
+    assert_eq!(square.fields[0].docs.as_deref(), Some("Length of a side."));
+
+    let Item::Impl(shape_impl) = &ast.items[2] else {
+        panic!("expected an impl, got {:?}", ast.items[2]);
+    };
+    assert_eq!(shape_impl.self_ty, "Square < T >");
+    assert_eq!(shape_impl.trait_.as_deref(), Some("Shape"));
+    assert_eq!(shape_impl.methods[0].name, "area");
+    assert_eq!(shape_impl.methods[0].output.as_deref(), Some("f64"));
+    assert_eq!(shape_impl.types[0].ty.as_deref(), Some("T"));
+
+    let Item::Impl(inherent) = &ast.items[3] else {
+        panic!("expected an impl, got {:?}", ast.items[3]);
+    };
+    assert_eq!(inherent.trait_, None);
+    assert_eq!(inherent.methods[0].name, "new");
+
+    assert!(matches!(&ast.items[4], Item::TypeAlias(alias) if alias.name == "Squares"));
+    let Item::Const(max_side) = &ast.items[5] else {
+        panic!("expected a const, got {:?}", ast.items[5]);
+    };
+    assert_eq!(max_side.ty, "f64");
+    assert_eq!(max_side.docs.as_deref(), Some("Largest side accepted."));
+
+    let Item::Module(geometry) = &ast.items[6] else {
+        panic!("expected a module, got {:?}", ast.items[6]);
+    };
+    assert!(matches!(&geometry.items[0], Item::Function(f) if f.name == "helper"));
+}
+
+#[test]
+fn test_round_trips_and_reads_older_outlines() {
+    let ast = parse_rust_ast(CODE).unwrap();
+    let json = serde_json::to_string(&ast).unwrap();
+    let read: SerializableAst = serde_json::from_str(&json).unwrap();
+    assert_eq!(format!("{read:?}"), format!("{ast:?}"));
+
+    // Outlines serialized before generics, attributes and docs were captured
+    let older = r#"{"items": [{"Function": {"name": "main", "visibility": "", "inputs": [], "output": null, "is_async": false}}]}"#;
+    let read: SerializableAst = serde_json::from_str(older).unwrap();
+    let Item::Function(main) = &read.items[0] else {
+        panic!("expected a function");
+    };
+    assert_eq!(main.docs, None);
+    assert!(main.attributes.is_empty());
+}
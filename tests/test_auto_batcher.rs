@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use voyageai::{client::AutoBatcher, test_util::TestServer, traits::llm::Embedder, VoyageAiClient};
+
+async fn batcher(server: &TestServer) -> (VoyageAiClient, AutoBatcher) {
+    let client = server.client();
+    let batcher = AutoBatcher::new(&client).with_max_delay(Duration::from_millis(50));
+    (client, batcher)
+}
+
+fn batch_sizes(requests: &[voyageai::test_util::wiremock::Request]) -> Vec<usize> {
+    requests
+        .iter()
+        .map(|request| {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            body["input"].as_array().map_or(1, |input| input.len())
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_concurrent_calls_share_one_request() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let (client, batcher) = batcher(&server).await;
+
+    let texts = ["alpha beta", "gamma delta", "epsilon zeta"];
+    let futures: Vec<_> = texts.iter().map(|text| batcher.embed(text)).collect();
+    let embeddings = futures::future::try_join_all(futures).await.unwrap();
+
+    let requests = server.received_requests().await;
+    assert_eq!(batch_sizes(&requests), vec![3]);
+
+    // Each caller gets the embedding of its own text
+    for (text, embedding) in texts.iter().zip(&embeddings) {
+        assert_eq!(embedding, &client.embed(text).await.unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_full_batches_are_sent_without_waiting() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let (_client, batcher) = batcher(&server).await;
+    let batcher = batcher
+        .with_max_batch_size(2)
+        .with_max_delay(Duration::from_secs(60));
+
+    let futures: Vec<_> = ["a", "b", "c", "d"]
+        .iter()
+        .map(|text| batcher.embed(text))
+        .collect();
+    let embeddings = tokio::time::timeout(
+        Duration::from_secs(10),
+        futures::future::try_join_all(futures),
+    )
+    .await
+    .expect("full batches should not wait for the delay")
+    .unwrap();
+
+    assert_eq!(embeddings.len(), 4);
+    assert_eq!(batch_sizes(&server.received_requests().await), vec![2, 2]);
+}
+
+#[tokio::test]
+async fn test_partial_batch_is_flushed_after_delay() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let (_client, batcher) = batcher(&server).await;
+
+    let embedding = batcher.embed("lonely text").await.unwrap();
+    assert_eq!(embedding.len(), 1024);
+    assert_eq!(batch_sizes(&server.received_requests().await), vec![1]);
+}
+
+#[tokio::test]
+async fn test_embed_batch_keeps_order() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let (client, batcher) = batcher(&server).await;
+
+    let texts = vec!["one".to_string(), "two".to_string(), "three".to_string()];
+    let embeddings = batcher.embed_batch(&texts).await.unwrap();
+    assert_eq!(embeddings, client.embed_batch(&texts).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_errors_reach_every_caller() {
+    let server = TestServer::start()
+        .await
+        .with_error("embeddings", 500, "boom")
+        .await;
+    let (_client, batcher) = batcher(&server).await;
+
+    let (first, second) = tokio::join!(batcher.embed("a"), batcher.embed("b"));
+    assert!(first.is_err());
+    assert!(second.is_err());
+    assert_eq!(server.received_requests().await.len(), 1);
+}
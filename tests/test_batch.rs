@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use serde_json::json;
+use voyageai::{
+    client::BatchClient,
+    models::batch::{embeddings_batch_jsonl, parse_embeddings_output, BatchStatus},
+    test_util::{
+        wiremock::{
+            matchers::{body_partial_json, body_string_contains, method, path},
+            Mock, ResponseTemplate,
+        },
+        TestServer,
+    },
+    InputType, VoyageError,
+};
+
+fn job(status: &str, output_file_id: Option<&str>) -> serde_json::Value {
+    json!({
+        "id": "batch_1",
+        "object": "batch",
+        "endpoint": "/v1/embeddings",
+        "input_file_id": "file_in",
+        "completion_window": "12h",
+        "status": status,
+        "output_file_id": output_file_id,
+        "request_counts": {"total": 2, "completed": 1, "failed": 0},
+        "created_at": "2026-01-01T00:00:00Z"
+    })
+}
+
+fn output_line(custom_id: &str, embedding: [f32; 2]) -> String {
+    json!({
+        "custom_id": custom_id,
+        "response": {
+            "status_code": 200,
+            "body": {
+                "object": "list",
+                "data": [{"object": "embedding", "embedding": embedding, "index": 0}],
+                "model": "voyage-3-large",
+                "usage": {"total_tokens": 3}
+            }
+        }
+    })
+    .to_string()
+}
+
+async fn batch_server() -> TestServer {
+    let server = TestServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/files"))
+        .and(body_string_contains("name=\"purpose\"\r\n\r\nbatch"))
+        .and(body_string_contains(
+            r#"{"custom_id":"a","body":{"input":["first text"]}}"#,
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "file_in", "object": "file", "filename": "embeddings.jsonl",
+            "bytes": 120, "purpose": "batch"
+        })))
+        .expect(1)
+        .mount(server.mock_server())
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/batches"))
+        .and(body_partial_json(json!({
+            "endpoint": "/v1/embeddings",
+            "input_file_id": "file_in",
+            "request_params": {"input_type": "document"}
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(job("validating", None)))
+        .expect(1)
+        .mount(server.mock_server())
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn test_submit_await_and_download_embeddings() -> Result<(), VoyageError> {
+    let server = batch_server().await;
+    // Two polls in progress, then completed
+    Mock::given(method("GET"))
+        .and(path("/v1/batches/batch_1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(job("in_progress", None)))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(server.mock_server())
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/batches/batch_1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(job("completed", Some("file_out"))))
+        .mount(server.mock_server())
+        .await;
+    let output = format!(
+        "{}\n{}\n",
+        output_line("a", [1.0, 0.0]),
+        output_line("b", [0.0, 1.0])
+    );
+    Mock::given(method("GET"))
+        .and(path("/v1/files/file_out/content"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(output))
+        .mount(server.mock_server())
+        .await;
+
+    let batches = BatchClient::new(&server.client()).with_poll_interval(Duration::from_millis(10));
+    let job = batches
+        .submit_embeddings(
+            [("a", "first text"), ("b", "second text")],
+            Some(InputType::Document),
+        )
+        .await?;
+    assert_eq!(job.status, BatchStatus::Validating);
+
+    let job = batches.await_completion(&job.id).await?;
+    assert_eq!(job.status, BatchStatus::Completed);
+    assert_eq!(job.request_counts.total, 2);
+
+    let results = batches.embeddings_results(&job).await?;
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].custom_id, "a");
+    assert_eq!(results[1].embedding, vec![0.0, 1.0]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_await_completion_fails_for_failed_jobs() -> Result<(), VoyageError> {
+    let server = TestServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/batches/batch_1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(job("failed", None)))
+        .mount(server.mock_server())
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/batches/batch_1/cancel"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(job("cancelling", None)))
+        .mount(server.mock_server())
+        .await;
+
+    let batches = BatchClient::new(&server.client());
+    assert!(matches!(
+        batches.await_completion("batch_1").await,
+        Err(VoyageError::BatchError(_))
+    ));
+    let job = batches.retrieve("batch_1").await?;
+    assert!(matches!(
+        batches.embeddings_results(&job).await,
+        Err(VoyageError::BatchError(_))
+    ));
+    assert_eq!(
+        batches.cancel("batch_1").await?.status,
+        BatchStatus::Cancelling
+    );
+    assert!(matches!(
+        batches.retrieve("missing").await,
+        Err(VoyageError::NotFound(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_batch_files() -> Result<(), VoyageError> {
+    let jsonl = embeddings_batch_jsonl([("x", "hello")])?;
+    assert_eq!(
+        jsonl,
+        "{\"custom_id\":\"x\",\"body\":{\"input\":[\"hello\"]}}\n"
+    );
+
+    let failed = json!({
+        "custom_id": "x",
+        "response": {"status_code": 400, "body": {"detail": "bad input"}}
+    })
+    .to_string();
+    let error = parse_embeddings_output(&failed).unwrap_err();
+    assert!(error.to_string().contains("bad input"));
+
+    // Unknown states are tolerated
+    let status: BatchStatus = serde_json::from_str("\"paused\"")?;
+    assert_eq!(status, BatchStatus::Unknown);
+    assert!(!status.is_terminal());
+    Ok(())
+}
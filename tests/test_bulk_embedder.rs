@@ -0,0 +1,136 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+use tokio_stream::StreamExt;
+use voyageai::{
+    client::embeddings_client::Client as EmbeddingsClient,
+    client::{BulkDocument, BulkEmbedder, BulkProgress, CheckpointStore, FileCheckpoint},
+    EmbeddingModel, VoyageConfig,
+};
+
+/// Encodes the number in "text-N" into the first dimension of the embedding.
+fn embed(text: &str) -> Vec<f32> {
+    let n: f32 = text.trim_start_matches("text-").parse().unwrap();
+    let mut embedding = vec![0.0; EmbeddingModel::Voyage3Large.embedding_dimension()];
+    embedding[0] = n;
+    embedding
+}
+
+async fn mock_server(expected_requests: usize) -> (mockito::ServerGuard, mockito::Mock) {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/embeddings")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body_from_request(|request| {
+            let body: Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+            let inputs = body["input"].as_array().cloned().unwrap_or_default();
+            let data: Vec<Value> = inputs
+                .iter()
+                .enumerate()
+                .map(|(index, text)| {
+                    json!({"object": "embedding", "embedding": embed(text.as_str().unwrap()), "index": index})
+                })
+                .collect();
+            json!({"object": "list", "data": data, "model": "voyage-3-large", "usage": {"total_tokens": inputs.len()}})
+                .to_string()
+                .into()
+        })
+        .expect(expected_requests)
+        .create_async()
+        .await;
+    (server, mock)
+}
+
+fn embedder(server: &mockito::ServerGuard) -> BulkEmbedder {
+    let client =
+        EmbeddingsClient::new(VoyageConfig::new("key".to_string()).with_base_url(server.url()));
+    BulkEmbedder::from_embeddings_client(client)
+}
+
+fn documents(range: std::ops::Range<usize>) -> Vec<BulkDocument> {
+    range
+        .map(|i| BulkDocument::new(format!("doc-{i}"), format!("text-{i}")))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_bulk_embedder_embeds_all_documents() {
+    let (server, mock) = mock_server(7).await;
+    let progress: Arc<Mutex<Vec<BulkProgress>>> = Arc::default();
+    let reports = progress.clone();
+
+    let mut results: Vec<_> = embedder(&server)
+        .with_batch_size(8)
+        .with_concurrency(3)
+        .with_total(50)
+        .with_progress_callback(move |p| reports.lock().unwrap().push(p.clone()))
+        .embed_all(documents(0..50))
+        .unwrap()
+        .collect()
+        .await;
+
+    mock.assert_async().await;
+    assert_eq!(results.len(), 50);
+    results.sort_by_key(|r| r.embedding[0] as usize);
+    for (i, result) in results.iter().enumerate() {
+        assert_eq!(result.id, format!("doc-{i}"));
+        assert_eq!(result.embedding[0], i as f32);
+    }
+
+    let progress = progress.lock().unwrap();
+    assert_eq!(progress.len(), 7);
+    let last = progress.iter().max_by_key(|p| p.done).unwrap();
+    assert_eq!(last.done, 50);
+    assert_eq!(last.tokens, 50);
+    assert_eq!(last.failed, 0);
+    assert_eq!(last.eta, Some(std::time::Duration::ZERO));
+}
+
+#[tokio::test]
+async fn test_bulk_embedder_resumes_from_checkpoint() {
+    let (server, mock) = mock_server(3).await;
+    let dir = tempfile::tempdir().unwrap();
+    let checkpoint = dir.path().join("job.checkpoint");
+    let done: Vec<String> = (0..20).map(|i| format!("doc-{i}")).collect();
+    FileCheckpoint::new(&checkpoint).record(&done, 20).unwrap();
+    let progress: Arc<Mutex<Vec<BulkProgress>>> = Arc::default();
+    let reports = progress.clone();
+
+    let results: Vec<_> = embedder(&server)
+        .with_batch_size(10)
+        .with_checkpoint(&checkpoint)
+        .with_progress_callback(move |p| reports.lock().unwrap().push(p.clone()))
+        .embed_all(documents(0..50))
+        .unwrap()
+        .collect()
+        .await;
+
+    mock.assert_async().await;
+    assert_eq!(results.len(), 30);
+    assert!(results.iter().all(|r| r.embedding[0] >= 20.0));
+    let progress = progress.lock().unwrap();
+    let last = progress.iter().max_by_key(|p| p.done).unwrap();
+    assert_eq!(last.skipped, 20);
+    assert_eq!(last.resumed_tokens, 20);
+    let state = FileCheckpoint::new(&checkpoint).load().unwrap();
+    assert_eq!(state.completed.len(), 50);
+    assert_eq!(state.tokens, 50);
+}
+
+#[tokio::test]
+async fn test_bulk_embedder_sink() {
+    let (server, mock) = mock_server(2).await;
+    let (sink, stream) = embedder(&server).with_batch_size(4).start().unwrap();
+
+    let producer = tokio::spawn(async move {
+        for document in documents(0..6) {
+            sink.send(document).await.unwrap();
+        }
+    });
+    let results: Vec<_> = stream.collect().await;
+    producer.await.unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(results.len(), 6);
+}
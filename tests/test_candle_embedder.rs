@@ -0,0 +1,138 @@
+#![cfg(feature = "candle")]
+
+use std::path::Path;
+
+use serde_json::json;
+use voyageai::{
+    client::candle_embedder::{
+        candle_core::{DType, Device},
+        candle_nn::{VarBuilder, VarMap},
+        tokenizers::Tokenizer,
+        BertConfig, CandleEmbedder, Pooling,
+    },
+    retrieval::RetrievalPipeline,
+    test_util::TestServer,
+    Embedder,
+};
+
+const WORDS: [&str; 10] = [
+    "cats", "purr", "and", "sleep", "dogs", "bark", "at", "the", "mail", "rust",
+];
+
+/// Writes a tiny randomly initialized BERT model to `dir`, laid out like a
+/// Hugging Face model repository.
+fn write_model(dir: &Path) {
+    let config = json!({
+        "vocab_size": WORDS.len() + 2,
+        "hidden_size": 8,
+        "num_hidden_layers": 1,
+        "num_attention_heads": 2,
+        "intermediate_size": 16,
+        "hidden_act": "gelu",
+        "hidden_dropout_prob": 0.0,
+        "max_position_embeddings": 32,
+        "type_vocab_size": 2,
+        "initializer_range": 0.02,
+        "layer_norm_eps": 1e-12,
+        "pad_token_id": 0,
+        "model_type": "bert",
+    });
+    std::fs::write(dir.join("config.json"), config.to_string()).unwrap();
+
+    let mut vocab = serde_json::Map::new();
+    vocab.insert("[PAD]".into(), json!(0));
+    vocab.insert("[UNK]".into(), json!(1));
+    for (id, word) in WORDS.iter().enumerate() {
+        vocab.insert(word.to_string(), json!(id + 2));
+    }
+    let tokenizer = json!({
+        "version": "1.0",
+        "truncation": null,
+        "padding": null,
+        "added_tokens": [],
+        "normalizer": { "type": "Lowercase" },
+        "pre_tokenizer": { "type": "Whitespace" },
+        "post_processor": null,
+        "decoder": null,
+        "model": { "type": "WordLevel", "vocab": vocab, "unk_token": "[UNK]" },
+    });
+    std::fs::write(dir.join("tokenizer.json"), tokenizer.to_string()).unwrap();
+
+    let config: BertConfig = serde_json::from_value(config).unwrap();
+    let varmap = VarMap::new();
+    let vb = VarBuilder::from_varmap(&varmap, DType::F32, &Device::Cpu);
+    // Loading the model into a `VarMap` initializes its weights
+    let tokenizer = Tokenizer::from_file(dir.join("tokenizer.json")).unwrap();
+    CandleEmbedder::new(&config, vb, tokenizer).unwrap();
+    varmap.save(dir.join("model.safetensors")).unwrap();
+}
+
+fn norm(vector: &[f32]) -> f32 {
+    vector.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
+
+#[tokio::test]
+async fn test_embeds_locally() {
+    let dir = tempfile::tempdir().unwrap();
+    write_model(dir.path());
+    let embedder = CandleEmbedder::from_dir(dir.path()).unwrap();
+    assert_eq!(embedder.dimension(), 8);
+
+    let texts = vec![
+        "cats purr".to_string(),
+        "dogs bark at the mail and sleep".to_string(),
+    ];
+    let embeddings = embedder.embed_batch(&texts).await.unwrap();
+    assert_eq!(embeddings.len(), 2);
+    for embedding in &embeddings {
+        assert_eq!(embedding.len(), 8);
+        assert!((norm(embedding) - 1.0).abs() < 1e-4);
+    }
+
+    // Padding to the longer text in a batch does not change an embedding
+    let alone = embedder.embed("cats purr").await.unwrap();
+    for (a, b) in alone.iter().zip(&embeddings[0]) {
+        assert!((a - b).abs() < 1e-4, "{alone:?} != {:?}", embeddings[0]);
+    }
+
+    let cls = embedder
+        .clone()
+        .with_pooling(Pooling::Cls)
+        .with_normalize(false)
+        .embed("cats purr")
+        .await
+        .unwrap();
+    assert_ne!(cls, alone);
+
+    let streamed: Vec<_> =
+        tokio_stream::StreamExt::collect(embedder.clone().with_batch_size(1).embed_stream(texts))
+            .await;
+    assert_eq!(streamed.len(), 2);
+    assert_eq!(streamed[1].index, 1);
+}
+
+#[tokio::test]
+async fn test_pipeline_with_local_embedder() {
+    let dir = tempfile::tempdir().unwrap();
+    write_model(dir.path());
+    let embedder = CandleEmbedder::from_dir(dir.path()).unwrap();
+    let server = TestServer::start().await;
+
+    let mut pipeline = RetrievalPipeline::builder(&server.client())
+        .embedder(embedder)
+        .without_rerank()
+        .top_k(1)
+        .build();
+    pipeline
+        .add_documents([
+            ("cats", "cats purr and sleep"),
+            ("dogs", "dogs bark at the mail"),
+        ])
+        .await
+        .unwrap();
+    let hits = pipeline.query("dogs bark at the mail").await.unwrap();
+    assert_eq!(hits[0].document_id, "dogs");
+    assert!((hits[0].score - 1.0).abs() < 1e-4);
+    assert!(server.received_requests().await.is_empty());
+    assert_eq!(pipeline.tokens_used(), 0);
+}
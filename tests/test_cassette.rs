@@ -0,0 +1,120 @@
+use voyageai::{
+    test_util::{Cassette, TestServer, VcrMode},
+    traits::llm::Embedder,
+};
+
+/// A live API stand-in to record from.
+async fn upstream() -> TestServer {
+    TestServer::start().await.with_embeddings().await
+}
+
+#[tokio::test]
+async fn test_record_then_replay() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cassettes/embed.json");
+    let upstream = upstream().await;
+
+    let recorder = Cassette::open(&path, VcrMode::Record)
+        .unwrap()
+        .with_upstream(upstream.uri())
+        .with_api_key("live-key");
+    let server = TestServer::start()
+        .await
+        .with_cassette(recorder.clone())
+        .await;
+    let recorded = server.client().embed("hello world").await.unwrap();
+    assert_eq!(recorder.len(), 1);
+    assert_eq!(upstream.received_requests().await.len(), 1);
+    assert_eq!(
+        upstream.received_requests().await[0].headers["authorization"],
+        "Bearer live-key"
+    );
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(!contents.contains("live-key"));
+    assert!(contents.contains("hello world"));
+
+    // Replay needs neither the upstream nor an API key
+    drop(upstream);
+    let player = Cassette::open(&path, VcrMode::Replay).unwrap();
+    assert_eq!(player.interactions(), recorder.interactions());
+    let server = TestServer::start().await.with_cassette(player).await;
+    let replayed = server.client().embed("hello world").await.unwrap();
+    assert_eq!(replayed, recorded);
+}
+
+#[tokio::test]
+async fn test_replay_miss_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("embed.json");
+    assert!(Cassette::open(&path, VcrMode::Replay).is_err());
+
+    let upstream = upstream().await;
+    let recorder = Cassette::open(&path, VcrMode::Record)
+        .unwrap()
+        .with_upstream(upstream.uri())
+        .with_api_key("live-key");
+    let server = TestServer::start().await.with_cassette(recorder).await;
+    server.client().embed("recorded").await.unwrap();
+
+    let server = TestServer::start()
+        .await
+        .with_cassette(Cassette::open(&path, VcrMode::Replay).unwrap())
+        .await;
+    let error = server.client().embed("not recorded").await.unwrap_err();
+    assert!(error.to_string().contains("No interaction recorded"));
+}
+
+#[tokio::test]
+async fn test_auto_records_only_new_requests() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("embed.json");
+    let upstream = upstream().await;
+
+    let cassette = Cassette::open(&path, VcrMode::Auto)
+        .unwrap()
+        .with_upstream(upstream.uri())
+        .with_api_key("live-key");
+    let server = TestServer::start()
+        .await
+        .with_cassette(cassette.clone())
+        .await;
+    let client = server.client();
+
+    client.embed("first").await.unwrap();
+    client.embed("first").await.unwrap();
+    client.embed("second").await.unwrap();
+
+    assert_eq!(cassette.len(), 2);
+    assert_eq!(upstream.received_requests().await.len(), 2);
+}
+
+#[tokio::test]
+async fn test_errors_are_not_recorded() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("embed.json");
+    let upstream = TestServer::start()
+        .await
+        .with_error("embeddings", 400, r#"{"detail": "bad input"}"#)
+        .await;
+
+    let cassette = Cassette::open(&path, VcrMode::Record)
+        .unwrap()
+        .with_upstream(upstream.uri())
+        .with_api_key("live-key");
+    let server = TestServer::start()
+        .await
+        .with_cassette(cassette.clone())
+        .await;
+
+    assert!(server.client().embed("hello").await.is_err());
+    assert!(cassette.is_empty());
+}
+
+#[test]
+fn test_mode_parsing() {
+    assert_eq!("record".parse(), Ok(VcrMode::Record));
+    assert_eq!("AUTO".parse(), Ok(VcrMode::Auto));
+    assert!("live".parse::<VcrMode>().is_err());
+    assert_eq!(VcrMode::default(), VcrMode::Replay);
+}
@@ -0,0 +1,66 @@
+use std::io::Write;
+
+use voyageai::client::{CheckpointStore, FileCheckpoint, JobState};
+use voyageai::VoyageError;
+
+fn ids(range: std::ops::Range<usize>) -> Vec<String> {
+    range.map(|i| format!("doc-{i}")).collect()
+}
+
+#[test]
+fn test_missing_checkpoint_is_empty() {
+    let dir = tempfile::tempdir().unwrap();
+    let checkpoint = FileCheckpoint::new(dir.path().join("job.checkpoint"));
+
+    assert_eq!(checkpoint.load().unwrap(), JobState::default());
+}
+
+#[test]
+fn test_checkpoint_accumulates_batches() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("job.checkpoint");
+    let checkpoint = FileCheckpoint::new(&path);
+    checkpoint.record(&ids(0..3), 30).unwrap();
+    checkpoint.record(&ids(3..5), 12).unwrap();
+
+    let state = FileCheckpoint::new(&path).load().unwrap();
+
+    assert_eq!(state.completed.len(), 5);
+    assert!(state.is_completed("doc-4"));
+    assert!(!state.is_completed("doc-5"));
+    assert_eq!(state.tokens, 42);
+}
+
+#[test]
+fn test_truncated_last_entry_is_discarded() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("job.checkpoint");
+    FileCheckpoint::new(&path).record(&ids(0..2), 8).unwrap();
+    // Simulate a crash in the middle of writing the next entry
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(br#"{"ids":["doc-2","do"#).unwrap();
+
+    let checkpoint = FileCheckpoint::new(&path);
+    let state = checkpoint.load().unwrap();
+    assert_eq!(state.completed.len(), 2);
+    assert_eq!(state.tokens, 8);
+
+    checkpoint.record(&ids(2..4), 6).unwrap();
+    let state = FileCheckpoint::new(&path).load().unwrap();
+    assert_eq!(state.completed.len(), 4);
+    assert_eq!(state.tokens, 14);
+}
+
+#[test]
+fn test_corrupt_entry_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("job.checkpoint");
+    std::fs::write(&path, "not json\n{\"ids\":[],\"tokens\":0}\n").unwrap();
+
+    let result = FileCheckpoint::new(&path).load();
+
+    assert!(matches!(result, Err(VoyageError::CheckpointError(_))));
+}
@@ -0,0 +1,198 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use voyageai::{
+    client::{CircuitBreaker, CircuitState, CircuitTransition},
+    VoyageAiClient, VoyageError,
+};
+
+mod common;
+
+use common::{Reply, ScriptedTransport};
+
+/// Answers with `statuses`, then with 200s. Status 0 fails without a
+/// response and status 1 never answers.
+fn failing(statuses: &[u16]) -> ScriptedTransport {
+    ScriptedTransport::replies(statuses.iter().map(|&status| match status {
+        0 => Reply::failure(),
+        1 => Reply::hang(),
+        200 => Reply::ok(),
+        status => Reply::error(status),
+    }))
+}
+
+fn client(transport: &ScriptedTransport, breaker: &CircuitBreaker) -> VoyageAiClient {
+    VoyageAiClient::new_with_config(transport.config().with_circuit_breaker(breaker.clone()))
+}
+
+async fn embed(client: &VoyageAiClient) -> Result<Vec<f32>, VoyageError> {
+    client.config.embeddings_client.embed_query("hello").await
+}
+
+fn recorder(breaker: CircuitBreaker) -> (CircuitBreaker, Arc<Mutex<Vec<CircuitTransition>>>) {
+    let transitions = Arc::new(Mutex::new(Vec::new()));
+    let recorded = transitions.clone();
+    let breaker = breaker.with_transition_callback(move |transition| {
+        recorded.lock().unwrap().push(*transition);
+    });
+    (breaker, transitions)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_breaker_opens_and_fails_fast() {
+    let (breaker, transitions) = recorder(
+        CircuitBreaker::new()
+            .with_minimum_requests(4)
+            .with_window_size(4)
+            .with_failure_rate_threshold(0.5)
+            .with_open_duration(Duration::from_secs(30)),
+    );
+    let transport = failing(&[200, 503, 0, 500]);
+    let client = client(&transport, &breaker);
+
+    assert!(embed(&client).await.is_ok());
+    assert!(embed(&client).await.is_err());
+    assert!(embed(&client).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert!(embed(&client).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    // Rejected without reaching the transport
+    let error = embed(&client).await.unwrap_err();
+    assert!(
+        matches!(error, VoyageError::CircuitOpen { retry_in } if retry_in <= Duration::from_secs(30))
+    );
+    assert_eq!(transport.sent(), 4);
+
+    let transitions = transitions.lock().unwrap();
+    assert_eq!(transitions.len(), 1);
+    assert_eq!(transitions[0].from, CircuitState::Closed);
+    assert_eq!(transitions[0].to, CircuitState::Open);
+    assert_eq!(transitions[0].failure_rate, 0.75);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_probes_close_or_reopen_the_breaker() {
+    let (breaker, transitions) = recorder(
+        CircuitBreaker::new()
+            .with_minimum_requests(2)
+            .with_open_duration(Duration::from_secs(10))
+            .with_probes(2),
+    );
+    // Opens, fails its first probe, then recovers
+    let client = client(&failing(&[503, 503, 503]), &breaker);
+    embed(&client).await.unwrap_err();
+    embed(&client).await.unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    tokio::time::advance(Duration::from_secs(10)).await;
+    assert!(matches!(
+        embed(&client).await,
+        Err(VoyageError::ApiError(..))
+    ));
+    assert_eq!(breaker.state(), CircuitState::Open);
+    assert!(matches!(
+        embed(&client).await,
+        Err(VoyageError::CircuitOpen { .. })
+    ));
+
+    tokio::time::advance(Duration::from_secs(10)).await;
+    embed(&client).await.unwrap();
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    embed(&client).await.unwrap();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    let states: Vec<_> = transitions
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|transition| transition.to)
+        .collect();
+    assert_eq!(
+        states,
+        [
+            CircuitState::Open,
+            CircuitState::HalfOpen,
+            CircuitState::Open,
+            CircuitState::HalfOpen,
+            CircuitState::Closed,
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_client_errors_do_not_trip_the_breaker() {
+    let breaker = CircuitBreaker::new().with_minimum_requests(2);
+    let client = client(&failing(&[400, 429, 401, 404]), &breaker);
+    for _ in 0..4 {
+        embed(&client).await.unwrap_err();
+    }
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert_eq!(breaker.failure_rate(), 0.0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_clients_share_a_breaker() {
+    let breaker = CircuitBreaker::new().with_minimum_requests(2);
+    let failing = client(&failing(&[503, 503]), &breaker);
+    let healthy = ScriptedTransport::default();
+    let other = client(&healthy, &breaker);
+
+    embed(&failing).await.unwrap_err();
+    embed(&failing).await.unwrap_err();
+    assert!(matches!(
+        embed(&other).await,
+        Err(VoyageError::CircuitOpen { .. })
+    ));
+    assert_eq!(healthy.sent(), 0);
+
+    breaker.reset();
+    embed(&other).await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_canceled_probe_frees_its_slot() {
+    let breaker = CircuitBreaker::new()
+        .with_minimum_requests(2)
+        .with_open_duration(Duration::from_secs(10))
+        .with_probes(1);
+    let client = client(&failing(&[503, 503, 1]), &breaker);
+    embed(&client).await.unwrap_err();
+    embed(&client).await.unwrap_err();
+    tokio::time::advance(Duration::from_secs(10)).await;
+
+    // The only probe hangs until its caller gives up
+    let probe = tokio::time::timeout(Duration::from_secs(1), embed(&client)).await;
+    assert!(probe.is_err());
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+    embed(&client).await.unwrap();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_transition_callback_can_query_the_breaker() {
+    let breaker = CircuitBreaker::new().with_minimum_requests(2);
+    let observer = breaker.clone();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorded = seen.clone();
+    let breaker = breaker.with_transition_callback(move |transition| {
+        recorded
+            .lock()
+            .unwrap()
+            .push((transition.to, observer.state(), observer.failure_rate()));
+    });
+    let client = client(&failing(&[503, 503]), &breaker);
+
+    embed(&client).await.unwrap_err();
+    embed(&client).await.unwrap_err();
+    breaker.reset();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            (CircuitState::Open, CircuitState::Open, 1.0),
+            (CircuitState::Closed, CircuitState::Closed, 0.0),
+        ]
+    );
+}
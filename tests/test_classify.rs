@@ -0,0 +1,97 @@
+use voyageai::{
+    classify::{Classifier, ClassifierMethod},
+    test_util::TestServer,
+    VoyageError,
+};
+
+fn examples() -> Vec<(&'static str, Vec<f32>)> {
+    vec![
+        ("sports", vec![1.0, 0.1, 0.0]),
+        ("sports", vec![0.9, 0.0, 0.1]),
+        ("cooking", vec![0.0, 1.0, 0.1]),
+        ("cooking", vec![0.1, 0.9, 0.0]),
+        ("travel", vec![0.0, 0.1, 1.0]),
+    ]
+}
+
+#[test]
+fn test_nearest_centroid_probabilities() -> Result<(), VoyageError> {
+    let classifier = Classifier::from_embeddings(examples())?;
+    assert_eq!(classifier.labels(), ["sports", "cooking", "travel"]);
+
+    let scores = classifier.classify_embedding(&[0.1, 1.0, 0.0]);
+    assert_eq!(scores.len(), 3);
+    assert_eq!(scores[0].label, "cooking");
+    assert!(scores[0].probability > 0.9);
+    let total: f32 = scores.iter().map(|score| score.probability).sum();
+    assert!((total - 1.0).abs() < 1e-5);
+
+    // A higher temperature spreads the probabilities
+    let smooth = classifier.clone().with_temperature(10.0);
+    let scores = smooth.classify_embedding(&[0.1, 1.0, 0.0]);
+    assert_eq!(scores[0].label, "cooking");
+    assert!(scores[0].probability < 0.5);
+    Ok(())
+}
+
+#[test]
+fn test_k_nearest_votes() -> Result<(), VoyageError> {
+    let classifier =
+        Classifier::from_embeddings(examples())?.with_method(ClassifierMethod::KNearest(2));
+    let scores = classifier.classify_embedding(&[1.0, 0.05, 0.05]);
+    assert_eq!(scores[0].label, "sports");
+    assert!(scores[0].probability > 0.99);
+    assert_eq!(scores[2].probability, 0.0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_classify_errors() -> Result<(), VoyageError> {
+    let empty: Vec<(String, Vec<f32>)> = Vec::new();
+    assert!(matches!(
+        Classifier::from_embeddings(empty),
+        Err(VoyageError::ClassificationError(_))
+    ));
+    assert!(matches!(
+        Classifier::from_embeddings([("a", vec![1.0, 0.0]), ("b", vec![1.0])]),
+        Err(VoyageError::EmbeddingDimensionMismatch { index: 1, .. })
+    ));
+    let classifier = Classifier::from_embeddings(examples())?;
+    assert!(matches!(
+        classifier.classify("text").await,
+        Err(VoyageError::ClassificationError(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_classify_texts() -> Result<(), VoyageError> {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let classifier = Classifier::builder(&client)
+        .fit([
+            ("rust", "rust ownership borrowing lifetimes"),
+            ("rust", "rust traits generics lifetimes"),
+            ("baking", "sourdough bread flour oven"),
+            ("baking", "bread dough flour yeast"),
+        ])
+        .await?;
+
+    let scores = classifier
+        .classify("borrowing and lifetimes in rust")
+        .await?;
+    assert_eq!(scores[0].label, "rust");
+
+    let batch = classifier
+        .classify_batch(&["flour and yeast".to_string(), "rust generics".to_string()])
+        .await?;
+    assert_eq!(batch[0][0].label, "baking");
+    assert_eq!(batch[1][0].label, "rust");
+
+    let no_examples: Vec<(String, String)> = Vec::new();
+    assert!(matches!(
+        Classifier::builder(&client).fit(no_examples).await,
+        Err(VoyageError::ClassificationError(_))
+    ));
+    Ok(())
+}
@@ -0,0 +1,213 @@
+#![cfg(feature = "cli")]
+
+use serde_json::Value;
+use voyageai::test_util::TestServer;
+
+/// Runs the `voyageai` binary against `server` with `args`, returning its
+/// stdout and stderr.
+async fn voyageai(server: &TestServer, args: &[&str]) -> (String, String) {
+    let dir = tempfile::tempdir().unwrap();
+    let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_voyageai"))
+        .args(args)
+        .args(["--api-key", "cli-key", "--base-url", &server.uri()])
+        // No voyage.toml or VOYAGE_* settings from the environment
+        .current_dir(dir.path())
+        .env_remove("VOYAGE_API_KEY")
+        .env_remove("VOYAGE_BASE_URL")
+        .output()
+        .await
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "{stderr}");
+    (stdout, stderr)
+}
+
+#[tokio::test]
+async fn test_global_flags() {
+    let server = TestServer::start().await.with_embeddings().await;
+
+    let (stdout, _) = voyageai(
+        &server,
+        &["embed", "-t", "hello", "-t", "world", "--format", "json"],
+    )
+    .await;
+    let records: Value = serde_json::from_str(&stdout).unwrap();
+    let records = records.as_array().unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1]["id"], "1");
+    assert_eq!(records[0]["model"], "voyage-3-large");
+    assert_eq!(
+        records[0]["dim"],
+        records[0]["vector"].as_array().unwrap().len()
+    );
+
+    let requests = server.received_requests().await;
+    assert_eq!(
+        requests[0].headers.get("authorization").unwrap(),
+        "Bearer cli-key"
+    );
+
+    // Global flags are also accepted before the command
+    let (stdout, _) = voyageai(
+        &server,
+        &[
+            "--format",
+            "json",
+            "embed",
+            "-m",
+            "voyage-code-3",
+            "-t",
+            "hi",
+        ],
+    )
+    .await;
+    let records: Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(records[0]["model"], "voyage-code-3");
+    let requests = server.received_requests().await;
+    let sent: Value = serde_json::from_slice(&requests.last().unwrap().body).unwrap();
+    assert_eq!(sent["model"], "voyage-code-3");
+}
+
+#[tokio::test]
+async fn test_quiet_and_csv_results() {
+    let server = TestServer::start()
+        .await
+        .with_embeddings()
+        .await
+        .with_rerank()
+        .await;
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("cats.txt"), "cats purr and sleep all day").unwrap();
+    let index = dir.path().join("index");
+    let index = index.to_str().unwrap();
+
+    let (_, stderr) = voyageai(
+        &server,
+        &["index", "build", dir.path().to_str().unwrap(), "-i", index],
+    )
+    .await;
+    assert!(stderr.contains("Embedding 1 files"));
+    let dogs = dir.path().join("dogs.txt");
+    std::fs::write(&dogs, "dogs bark at the mail carrier").unwrap();
+    let (stdout, stderr) = voyageai(
+        &server,
+        &[
+            "index",
+            "add",
+            "-i",
+            index,
+            dogs.to_str().unwrap(),
+            "--quiet",
+            "-f",
+            "json",
+        ],
+    )
+    .await;
+    assert!(stderr.is_empty(), "{stderr}");
+    let added: Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(added["files"], 1);
+
+    let (stdout, _) = voyageai(
+        &server,
+        &["index", "stats", "-i", index, "--format", "json"],
+    )
+    .await;
+    let stats: Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(stats["documents"], 2);
+    assert_eq!(stats["model"], "voyage-3-large");
+    assert_eq!(stats["written_by"], env!("CARGO_PKG_VERSION"));
+
+    let (stdout, _) = voyageai(
+        &server,
+        &[
+            "rerank", "-q", "pets", "-d", "cats", "-d", "rust", "-f", "csv",
+        ],
+    )
+    .await;
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next(), Some("rank,score,document"));
+    assert_eq!(lines.count(), 2);
+}
+
+#[tokio::test]
+async fn test_index_migrate() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("cats.txt"), "cats purr and sleep all day").unwrap();
+    let index = dir.path().join("index");
+    let index = index.to_str().unwrap();
+    voyageai(
+        &server,
+        &["index", "build", dir.path().to_str().unwrap(), "-i", index],
+    )
+    .await;
+
+    let migrated = dir.path().join("migrated");
+    let migrated = migrated.to_str().unwrap();
+    let (stdout, stderr) = voyageai(
+        &server,
+        &[
+            "index",
+            "migrate",
+            "-i",
+            index,
+            "-m",
+            "voyage-code-3",
+            "-o",
+            migrated,
+            "-f",
+            "json",
+        ],
+    )
+    .await;
+    assert!(
+        stderr.contains("with voyage-code-3 (was voyage-3-large)"),
+        "{stderr}"
+    );
+    let report: Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(report["from_model"], "voyage-3-large");
+    assert_eq!(report["model"], "voyage-code-3");
+    let requests = server.received_requests().await;
+    let sent: Value = serde_json::from_slice(&requests.last().unwrap().body).unwrap();
+    assert_eq!(sent["model"], "voyage-code-3");
+
+    let (stdout, _) = voyageai(
+        &server,
+        &["index", "stats", "-i", migrated, "--format", "json"],
+    )
+    .await;
+    let stats: Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(stats["model"], "voyage-code-3");
+    assert_eq!(stats["chunks"], report["chunks"]);
+}
+
+#[tokio::test]
+async fn test_index_compact_drops_deleted_chunks() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("cats.txt"), "cats purr and sleep all day").unwrap();
+    std::fs::write(dir.path().join("dogs.txt"), "dogs bark at the mail carrier").unwrap();
+    let path = dir.path().join("index");
+    let index = path.to_str().unwrap();
+    voyageai(
+        &server,
+        &["index", "build", dir.path().to_str().unwrap(), "-i", index],
+    )
+    .await;
+    let mut loaded = voyageai::VectorIndex::load(&path).unwrap();
+    let id = loaded.iter().next().unwrap().0.id.clone();
+    loaded.delete(&id);
+    loaded.save(&path).unwrap();
+
+    let parse = |stdout: String| serde_json::from_str::<Value>(&stdout).unwrap();
+    let (stdout, _) = voyageai(&server, &["index", "stats", "-i", index, "-f", "json"]).await;
+    assert_eq!(parse(stdout)["tombstoned"], 1);
+
+    let (stdout, _) = voyageai(&server, &["index", "compact", "-i", index, "-f", "json"]).await;
+    let report = parse(stdout);
+    assert_eq!(report["removed_tombstones"], 1);
+    assert!(report["disk_size_after"].as_u64() < report["disk_size_before"].as_u64());
+    let (stdout, _) = voyageai(&server, &["index", "stats", "-i", index, "-f", "json"]).await;
+    assert_eq!(parse(stdout)["tombstoned"], 0);
+}
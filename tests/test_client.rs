@@ -1,15 +1,13 @@
 use std::error::Error;
 use voyageai::{
-    EmbeddingModel, EmbeddingsRequestBuilder, RerankModel, RerankRequest, VoyageAiClient,
-    VoyageConfig, VoyageError, traits::llm::{Embedder, Reranker},
+    test_util::TestServer,
+    traits::llm::{Embedder, Reranker},
 };
 
 #[tokio::test]
 async fn test_embeddings() -> Result<(), Box<dyn Error>> {
-    let config = VoyageConfig::new(
-        std::env::var("VOYAGE_API_KEY").unwrap_or_else(|_| "test_key".to_string()),
-    );
-    let client = VoyageAiClient::new_with_config(config);
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
 
     // Use the Embedder trait directly
     let embedding = client.embed("test input").await?;
@@ -26,13 +24,11 @@ async fn test_embeddings() -> Result<(), Box<dyn Error>> {
 
 #[tokio::test]
 async fn test_reranking() -> Result<(), Box<dyn Error>> {
-    let config = VoyageConfig::new(
-        std::env::var("VOYAGE_API_KEY").unwrap_or_else(|_| "test_key".to_string()),
-    );
-    let client = VoyageAiClient::new_with_config(config);
+    let server = TestServer::start().await.with_rerank().await;
+    let client = server.client();
 
     // Use the Reranker trait directly
-    let documents = vec!["doc1".to_string(), "doc2".to_string()];
+    let documents = vec!["doc1".to_string(), "test query doc2".to_string()];
     let stream = client.rerank("test query", documents);
     
     // Collect the results from the stream
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use voyageai::{
+    models::embeddings::EmbeddingsInput, test_util::TestServer, traits::voyage::VoyageAiClientExt,
+};
+
+#[tokio::test]
+async fn test_embed_accepts_every_input_form() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+
+    let text = String::from("owned text");
+    let texts = vec!["first".to_string(), "second".to_string()];
+
+    let single = client.embed("borrowed text").await.unwrap();
+    assert_eq!(single.data.len(), 1);
+    assert_eq!(client.embed(text.clone()).await.unwrap().data.len(), 1);
+    assert_eq!(client.embed(&text).await.unwrap().data.len(), 1);
+    assert_eq!(client.embed(texts.clone()).await.unwrap().data.len(), 2);
+    assert_eq!(client.embed(texts.as_slice()).await.unwrap().data.len(), 2);
+    assert_eq!(
+        client.embed(vec!["a", "b", "c"]).await.unwrap().data.len(),
+        3
+    );
+
+    let requests = server.received_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["input"], "borrowed text");
+    assert_eq!(body["model"], "voyage-3-large");
+}
+
+#[tokio::test]
+async fn test_ext_is_implemented_for_arc() {
+    let server = TestServer::start()
+        .await
+        .with_embeddings()
+        .await
+        .with_rerank()
+        .await;
+    let client = Arc::new(server.client());
+
+    let task = {
+        let client = client.clone();
+        tokio::spawn(async move {
+            client
+                .embed("from another task")
+                .await
+                .map(|r| r.data.len())
+        })
+    };
+    assert_eq!(task.await.unwrap().unwrap(), 1);
+
+    let best = VoyageAiClientExt::most_similar_document(
+        &client,
+        "rust",
+        vec!["rust language".to_string(), "cooking pasta".to_string()],
+    )
+    .await
+    .unwrap();
+    assert_eq!(best.document, "rust language");
+}
+
+#[test]
+fn test_embeddings_input_conversions() {
+    let texts = ["a".to_string(), "b".to_string()];
+    assert!(matches!(
+        EmbeddingsInput::from(&texts[..]),
+        EmbeddingsInput::Multiple(v) if v == texts
+    ));
+    assert!(matches!(
+        EmbeddingsInput::from(&["x", "y"][..]),
+        EmbeddingsInput::Multiple(v) if v.len() == 2
+    ));
+    assert!(matches!(
+        EmbeddingsInput::from(&texts[0]),
+        EmbeddingsInput::Single(s) if s == "a"
+    ));
+}
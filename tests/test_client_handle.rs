@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use voyageai::{test_util::TestServer, traits::llm::Embedder, VoyageAiClient, VoyageConfig};
+
+#[test]
+fn test_clones_share_state() {
+    let client = VoyageAiClient::new_with_config(VoyageConfig::new("secret-key".to_string()));
+    let clone = client.clone();
+    assert!(Arc::ptr_eq(&client.config, &clone.config));
+    assert!(Arc::ptr_eq(
+        client.embeddings_client(),
+        clone.embeddings_client()
+    ));
+}
+
+#[test]
+fn test_debug_redacts_api_key() {
+    let client = VoyageAiClient::new_with_config(VoyageConfig::new("secret-key".to_string()));
+    let debug = format!("{:?}", client);
+    assert!(debug.contains("VoyageAiClient"));
+    assert!(debug.contains("[REDACTED]"));
+    assert!(!debug.contains("secret-key"));
+}
+
+#[tokio::test]
+async fn test_clones_serve_concurrent_handlers() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+
+    let handlers: Vec<_> = (0..4)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move { client.embed(&format!("request {i}")).await })
+        })
+        .collect();
+    for handler in handlers {
+        assert_eq!(handler.await.unwrap().unwrap().len(), 1024);
+    }
+}
+
+#[tokio::test]
+async fn test_dropping_a_clone_keeps_requests_running() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let clone = client.clone();
+
+    let embedding = clone.embed("still running");
+    drop(clone);
+    assert_eq!(embedding.await.unwrap().len(), 1024);
+}
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use voyageai::{
+    test_util::{
+        wiremock::{matchers::path, Mock, ResponseTemplate},
+        TestServer,
+    },
+    traits::llm::Embedder,
+    VoyageAiClient, VoyageError,
+};
+
+/// Answers embeddings requests after `delay`, so concurrent requests overlap.
+async fn slow_server(status: u16, delay: Duration) -> TestServer {
+    let server = TestServer::start().await;
+    let body = json!({
+        "object": "list",
+        "data": [{"object": "embedding", "embedding": vec![0.5f32; 1024], "index": 0}],
+        "model": "voyage-3-large",
+        "usage": {"total_tokens": 1}
+    });
+    Mock::given(path("/v1/embeddings"))
+        .respond_with(
+            ResponseTemplate::new(status)
+                .set_body_json(body)
+                .set_delay(delay),
+        )
+        .mount(server.mock_server())
+        .await;
+    server
+}
+
+fn client(server: &TestServer, coalesce: bool) -> Arc<VoyageAiClient> {
+    Arc::new(VoyageAiClient::new_with_config(
+        server.config().with_request_coalescing(coalesce),
+    ))
+}
+
+async fn embed_concurrently(
+    client: &Arc<VoyageAiClient>,
+    texts: &[&str],
+) -> Vec<Result<Vec<f32>, VoyageError>> {
+    let tasks: Vec<_> = texts
+        .iter()
+        .map(|text| {
+            let client = client.clone();
+            let text = text.to_string();
+            tokio::spawn(async move { client.embed(&text).await })
+        })
+        .collect();
+    let mut results = Vec::new();
+    for task in tasks {
+        results.push(task.await.unwrap());
+    }
+    results
+}
+
+#[tokio::test]
+async fn test_identical_requests_share_one_call() {
+    let server = slow_server(200, Duration::from_millis(200)).await;
+    let client = client(&server, true);
+
+    let results = embed_concurrently(&client, &["same"; 5]).await;
+
+    assert!(results
+        .iter()
+        .all(|result| result.as_ref().unwrap().len() == 1024));
+    assert_eq!(server.received_requests().await.len(), 1);
+
+    // Finished requests are not reused
+    client.embed("same").await.unwrap();
+    assert_eq!(server.received_requests().await.len(), 2);
+}
+
+#[tokio::test]
+async fn test_different_requests_are_not_coalesced() {
+    let server = slow_server(200, Duration::from_millis(200)).await;
+    let client = client(&server, true);
+
+    let results = embed_concurrently(&client, &["first", "second", "first"]).await;
+
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(server.received_requests().await.len(), 2);
+}
+
+#[tokio::test]
+async fn test_coalescing_is_off_by_default() {
+    let server = slow_server(200, Duration::from_millis(200)).await;
+    let client = Arc::new(VoyageAiClient::new_with_config(server.config()));
+
+    embed_concurrently(&client, &["same"; 3]).await;
+
+    assert_eq!(server.received_requests().await.len(), 3);
+}
+
+#[tokio::test]
+async fn test_errors_are_shared() {
+    let server = slow_server(429, Duration::from_millis(200)).await;
+    let client = client(&server, true);
+
+    let results = embed_concurrently(&client, &["same"; 3]).await;
+
+    assert!(results
+        .iter()
+        .all(|result| matches!(result, Err(VoyageError::RateLimitExceeded { .. }))));
+    assert_eq!(server.received_requests().await.len(), 1);
+}
+
+#[tokio::test]
+async fn test_waiter_takes_over_cancelled_request() {
+    let server = slow_server(200, Duration::from_millis(200)).await;
+    let client = client(&server, true);
+
+    let leader = {
+        let client = client.clone();
+        tokio::spawn(async move { client.embed("same").await })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let follower = {
+        let client = client.clone();
+        tokio::spawn(async move { client.embed("same").await })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    leader.abort();
+
+    assert_eq!(follower.await.unwrap().unwrap().len(), 1024);
+}
+
+#[tokio::test]
+async fn test_request_is_sent_again_after_every_caller_cancelled() {
+    let server = slow_server(200, Duration::from_millis(200)).await;
+    let client = client(&server, true);
+
+    let callers: Vec<_> = (0..2)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move { client.embed("same").await })
+        })
+        .collect();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    for caller in &callers {
+        caller.abort();
+    }
+    for caller in callers {
+        assert!(caller.await.unwrap_err().is_cancelled());
+    }
+    assert_eq!(server.received_requests().await.len(), 1);
+
+    assert_eq!(client.embed("same").await.unwrap().len(), 1024);
+    assert_eq!(server.received_requests().await.len(), 2);
+}
+
+#[test]
+fn test_duplicate_error() {
+    let error = VoyageError::RateLimitExceeded {
+        reset_in: Duration::from_secs(3),
+        metadata: None,
+    };
+    assert!(matches!(
+        error.duplicate(),
+        VoyageError::RateLimitExceeded { reset_in, .. } if reset_in == Duration::from_secs(3)
+    ));
+    assert_eq!(
+        VoyageError::ApiError(
+            reqwest::StatusCode::BAD_GATEWAY,
+            "down".into(),
+            Default::default()
+        )
+            .duplicate()
+            .to_string(),
+        "API error (status 502 Bad Gateway): down"
+    );
+}
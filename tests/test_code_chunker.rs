@@ -0,0 +1,140 @@
+#![cfg(feature = "ast")]
+
+use std::path::Path;
+
+use voyageai::{
+    code::{chunk_rust, module_path_for, CodeChunker, CodeItemKind},
+    models::{Metadata, MetadataValue},
+    retrieval::RetrievalPipeline,
+    test_util::TestServer,
+    Chunker,
+};
+
+const SOURCE: &str = r#"use std::fmt;
+
+/// A square.
+#[derive(Debug)]
+pub struct Square {
+    pub side: f64,
+}
+
+impl Square {
+    /// Area of the square.
+    pub fn area(&self) -> f64 {
+        self.side * self.side
+    }
+
+    pub(crate) fn scale(&mut self, factor: f64) {
+        self.side *= factor;
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "square of side {}", self.side)
+    }
+}
+
+impl Copy for Square {}
+
+mod tests {
+    fn helper() -> u32 {
+        1
+    }
+}
+"#;
+
+#[test]
+fn test_chunks_items_with_locations() {
+    let chunks = chunk_rust(SOURCE, &["shapes".to_string()]).unwrap();
+    let names: Vec<String> = chunks.iter().map(|chunk| chunk.qualified_name()).collect();
+    assert_eq!(
+        names,
+        [
+            "shapes::Square",
+            "shapes::Square::area",
+            "shapes::Square::scale",
+            "shapes::Square::fmt",
+            "shapes::Square",
+            "shapes::tests::helper",
+        ]
+    );
+
+    let square = &chunks[0];
+    assert_eq!(square.kind, CodeItemKind::Struct);
+    assert_eq!((square.start_line, square.end_line), (3, 7));
+    assert!(square.text.starts_with("/// A square.\n#[derive(Debug)]"));
+    assert_eq!(square.docs.as_deref(), Some("A square."));
+    assert_eq!(square.visibility, "pub");
+
+    let area = &chunks[1];
+    assert_eq!(area.kind, CodeItemKind::Method);
+    assert_eq!(area.parent.as_deref(), Some("Square"));
+    assert_eq!((area.start_line, area.end_line), (10, 13));
+    assert_eq!(chunks[2].visibility, "pub(crate)");
+    assert_eq!(chunks[3].visibility, "");
+    assert_eq!(chunks[4].kind, CodeItemKind::Impl);
+    assert_eq!(chunks[4].text, "impl Copy for Square {}");
+    assert_eq!(chunks[5].module_path, ["shapes", "tests"]);
+
+    let metadata = area.metadata();
+    assert_eq!(
+        metadata.get("item"),
+        Some(&MetadataValue::from("shapes::Square::area"))
+    );
+    assert_eq!(metadata.get("kind"), Some(&MetadataValue::from("fn")));
+    assert_eq!(metadata.get("start_line"), Some(&MetadataValue::from(10.0)));
+    assert_eq!(metadata.get("end_line"), Some(&MetadataValue::from(13.0)));
+    assert_eq!(
+        metadata.get("docs"),
+        Some(&MetadataValue::from("Area of the square."))
+    );
+    assert!(area
+        .text_with_path()
+        .starts_with("// shapes::Square::area\n    /// Area"));
+}
+
+#[test]
+fn test_module_paths_and_fallback() {
+    let path = |path: &str| module_path_for(Path::new(path));
+    assert!(path("src/lib.rs").is_empty());
+    assert_eq!(path("src/client/mod.rs"), ["client"]);
+    assert_eq!(
+        path("crates/voyage/src/client/search_client.rs"),
+        ["client", "search_client"]
+    );
+
+    let chunker = CodeChunker::for_file("src/shapes.rs").unwrap();
+    assert_eq!(chunker.module_path(), ["shapes"]);
+    assert_eq!(chunker.language(), "rust");
+    assert_eq!(chunker.chunk(SOURCE).len(), 6);
+    // Not Rust: packed into paragraph chunks
+    assert_eq!(chunker.chunk("fn (\n\nnot rust"), ["fn (\n\nnot rust"]);
+}
+
+#[tokio::test]
+async fn test_pipeline_stores_item_locations() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let mut pipeline = RetrievalPipeline::builder(&client).without_rerank().build();
+    let metadata = Metadata::from([("repo".to_string(), MetadataValue::from("geometry"))]);
+
+    let stored = pipeline
+        .add_code_file("src/shapes.rs", SOURCE, Some(metadata))
+        .await
+        .unwrap();
+    assert_eq!(stored, 6);
+
+    let hits = pipeline.query("area of the square").await.unwrap();
+    let area = hits
+        .iter()
+        .find(|hit| hit.text.contains("fn area"))
+        .expect("the area method is indexed");
+    let metadata = area.metadata.as_ref().unwrap();
+    assert_eq!(
+        metadata.get("item"),
+        Some(&MetadataValue::from("shapes::Square::area"))
+    );
+    assert_eq!(metadata.get("start_line"), Some(&MetadataValue::from(10.0)));
+    assert_eq!(metadata.get("repo"), Some(&MetadataValue::from("geometry")));
+}
@@ -0,0 +1,131 @@
+use voyageai::{
+    code::{CodeEmbedding, CodeIndex, CodeSimilarityWeights},
+    models::{filter::MetadataFilter, Metadata, MetadataValue},
+    VoyageError,
+};
+
+fn code(text: [f32; 2], ast: [f32; 2]) -> CodeEmbedding {
+    CodeEmbedding {
+        text_embedding: text.to_vec(),
+        ast_embedding: ast.to_vec(),
+    }
+}
+
+#[test]
+fn test_similarity_blends_text_and_ast() {
+    let a = code([1.0, 0.0], [1.0, 0.0]);
+    let b = code([1.0, 0.0], [0.0, 1.0]);
+    let similarity = |weights| a.similarity(&b, weights).unwrap();
+
+    assert_eq!(similarity(CodeSimilarityWeights::text_only()), 1.0);
+    assert_eq!(similarity(CodeSimilarityWeights::ast_only()), 0.0);
+    assert_eq!(similarity(CodeSimilarityWeights::new(3.0, 1.0)), 0.75);
+    assert!((similarity(CodeSimilarityWeights::default()) - 0.7).abs() < 1e-6);
+    // Negative weights count as 0, two zero weights as equal ones
+    assert_eq!(similarity(CodeSimilarityWeights::new(1.0, -1.0)), 1.0);
+    assert_eq!(similarity(CodeSimilarityWeights::new(0.0, 0.0)), 0.5);
+
+    let short = CodeEmbedding {
+        text_embedding: vec![1.0],
+        ast_embedding: vec![1.0, 0.0],
+    };
+    assert!(a
+        .similarity(&short, CodeSimilarityWeights::default())
+        .is_err());
+}
+
+#[test]
+fn test_index_ranks_by_weighted_similarity() {
+    let mut index = CodeIndex::new();
+    index
+        .insert("same_text", "a", code([1.0, 0.0], [0.0, 1.0]), None)
+        .unwrap();
+    index
+        .insert("same_shape", "b", code([0.0, 1.0], [1.0, 0.0]), None)
+        .unwrap();
+    let query = code([1.0, 0.0], [1.0, 0.0]);
+    let ranked = |index: &CodeIndex| -> Vec<String> {
+        index
+            .search(&query, 2, None)
+            .unwrap()
+            .iter()
+            .map(|hit| hit.record.id.clone())
+            .collect()
+    };
+
+    assert_eq!(ranked(&index), ["same_text", "same_shape"]);
+    index.set_weights(CodeSimilarityWeights::new(0.2, 0.8));
+    assert_eq!(ranked(&index), ["same_shape", "same_text"]);
+
+    let hits = index.search(&query, 1, None).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!((hits[0].text_score, hits[0].ast_score), (0.0, 1.0));
+    assert!((hits[0].score - 0.8).abs() < 1e-6);
+}
+
+#[test]
+fn test_index_filters_replaces_and_removes() {
+    let mut index = CodeIndex::new().with_weights(CodeSimilarityWeights::ast_only());
+    let metadata = |language: &str| {
+        Some(Metadata::from([(
+            "language".to_string(),
+            MetadataValue::from(language),
+        )]))
+    };
+    index
+        .insert(
+            "parse",
+            "fn parse()",
+            code([1.0, 0.0], [1.0, 0.0]),
+            metadata("rust"),
+        )
+        .unwrap();
+    index
+        .insert(
+            "load",
+            "def load():",
+            code([1.0, 0.0], [1.0, 0.0]),
+            metadata("python"),
+        )
+        .unwrap();
+
+    let python = MetadataFilter::eq("language", "python");
+    let hits = index
+        .search(&code([1.0, 0.0], [1.0, 0.0]), 10, Some(&python))
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].record.id, "load");
+
+    index
+        .insert(
+            "load",
+            "def load(path):",
+            code([0.0, 1.0], [0.0, 1.0]),
+            None,
+        )
+        .unwrap();
+    assert_eq!(index.len(), 2);
+    let (record, embedding) = index.get("load").unwrap();
+    assert_eq!(record.text, "def load(path):");
+    assert_eq!(embedding.ast_embedding, [0.0, 1.0]);
+
+    // Both dimensions are checked before anything is stored
+    let mismatched = CodeEmbedding {
+        text_embedding: vec![1.0, 0.0],
+        ast_embedding: vec![1.0, 0.0, 0.0],
+    };
+    assert!(matches!(
+        index.insert("bad", "", mismatched.clone(), None),
+        Err(VoyageError::SearchDimensionMismatch {
+            expected: 2,
+            actual: 3
+        })
+    ));
+    assert!(!index.contains("bad"));
+    assert!(index.search(&mismatched, 1, None).is_err());
+
+    let (record, _) = index.remove("parse").unwrap();
+    assert_eq!(record.text, "fn parse()");
+    assert_eq!(index.len(), 1);
+    assert!(index.remove("parse").is_none());
+}
@@ -0,0 +1,133 @@
+#![cfg(all(feature = "repo", feature = "ast"))]
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use voyageai::{
+    code::{CodeIndexer, RepoWalker},
+    retrieval::RetrievalPipeline,
+    test_util::TestServer,
+    VoyageError,
+};
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .output()
+        .expect("git runs");
+    assert!(output.status.success(), "git {:?} failed", args);
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+fn commit(dir: &Path, message: &str) -> String {
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-q", "-m", message]);
+    git(dir, &["rev-parse", "HEAD"])
+}
+
+fn embedded_inputs(requests: &[voyageai::test_util::wiremock::Request]) -> Vec<usize> {
+    requests
+        .iter()
+        .map(|request| {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            body["input"].as_array().map_or(1, |input| input.len())
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_update_from_git_only_embeds_changed_items() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let pipeline = RetrievalPipeline::builder(&client).without_rerank().build();
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    git(root, &["init", "-q"]);
+    fs::create_dir(root.join("src")).unwrap();
+    fs::write(
+        root.join("src/lib.rs"),
+        "pub fn area(side: f64) -> f64 {\n    side * side\n}\n\npub fn perimeter(side: f64) -> f64 {\n    4.0 * side\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("src/util.rs"),
+        "pub fn double(x: i32) -> i32 {\n    2 * x\n}\n",
+    )
+    .unwrap();
+    fs::write(root.join("src/old.rs"), "pub fn legacy() {}\n").unwrap();
+    let base = commit(root, "base");
+
+    let mut indexer = CodeIndexer::new(pipeline, RepoWalker::new(root).annotate_commit(true));
+    let report = indexer.index().await.unwrap();
+    assert_eq!((report.files, report.chunks, report.embedded), (3, 4, 4));
+
+    // Edit one of two functions, add a file, rename one and delete one
+    fs::write(
+        root.join("src/lib.rs"),
+        "\n/// Area of a square.\npub fn area(side: f64) -> f64 {\n    side * side\n}\n\npub fn perimeter(side: f64) -> f64 {\n    4.0 * side\n}\n",
+    )
+    .unwrap();
+    fs::write(root.join("src/new.rs"), "pub fn fresh() {}\n").unwrap();
+    git(root, &["mv", "src/util.rs", "src/math.rs"]);
+    fs::remove_file(root.join("src/old.rs")).unwrap();
+    let head = commit(root, "change");
+
+    let update = indexer
+        .update_from_git(&format!("{base}..{head}"))
+        .await
+        .unwrap();
+    assert_eq!(update.updated, ["src/lib.rs", "src/math.rs", "src/new.rs"]);
+    assert_eq!(update.removed, ["src/old.rs", "src/util.rs"]);
+    assert_eq!(update.commit.as_deref(), Some(head.as_str()));
+    assert_eq!(update.chunks, 4);
+    // `perimeter` only moved down a line and keeps its embedding; `double`
+    // is embedded again as its module path changed
+    assert_eq!(update.embedded, 3);
+    assert_eq!(embedded_inputs(&server.received_requests().await), [4, 3]);
+
+    let pipeline = indexer.pipeline();
+    assert_eq!(pipeline.index().len(), 4);
+    let mut documents: Vec<&str> = pipeline.document_ids().collect();
+    documents.sort();
+    let id = |path: &str| root.join(path).to_string_lossy().into_owned();
+    assert_eq!(
+        documents,
+        [id("src/lib.rs"), id("src/math.rs"), id("src/new.rs")]
+    );
+
+    // Nothing changed since
+    let update = indexer.update_from_git(&head).await.unwrap();
+    assert!(update.updated.is_empty() && update.removed.is_empty());
+    assert_eq!(server.received_requests().await.len(), 2);
+
+    assert!(matches!(
+        indexer.update_from_git("no-such-commit..HEAD").await,
+        Err(VoyageError::RepositoryError(_))
+    ));
+    assert!(indexer.update_from_git("--output=x").await.is_err());
+}
+
+#[tokio::test]
+async fn test_readding_a_document_reuses_unchanged_chunks() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let mut pipeline = RetrievalPipeline::builder(&client).without_rerank().build();
+
+    pipeline
+        .add_code_file("src/lib.rs", "fn a() {}\n\nfn b() {}\n", None)
+        .await
+        .unwrap();
+    pipeline
+        .add_code_file("src/lib.rs", "fn a() {}\n\nfn b() {}\n", None)
+        .await
+        .unwrap();
+    pipeline
+        .add_code_file("src/lib.rs", "fn a() {}\n\nfn c() {}\n", None)
+        .await
+        .unwrap();
+    assert_eq!(embedded_inputs(&server.received_requests().await), [2, 1]);
+    assert_eq!(pipeline.index().len(), 2);
+}
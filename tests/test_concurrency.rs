@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use voyageai::{
+    client::{rerank_client::RerankRequestBuilder, RateLimitEndpoint},
+    VoyageAiClient, VoyageConfig,
+};
+
+mod common;
+
+use common::{Reply, ScriptedTransport};
+
+/// Answers after 100ms, to let requests pile up.
+fn transport() -> ScriptedTransport {
+    ScriptedTransport::new(|_| Reply::ok().after(Duration::from_millis(100)))
+}
+
+async fn embed_many(client: &VoyageAiClient, count: usize) {
+    let tasks: Vec<_> = (0..count)
+        .map(|i| {
+            let embeddings = client.embeddings_client().clone();
+            tokio::spawn(async move { embeddings.embed_query(&format!("text {i}")).await })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_max_in_flight_queues_requests() {
+    let transport = transport();
+    let client = VoyageAiClient::new_with_config(transport.config().with_max_in_flight(2));
+
+    embed_many(&client, 10).await;
+    assert_eq!(transport.peak_in_flight(), 2);
+
+    let limiter = &client.voyage_config().concurrency_limiter;
+    let stats = limiter.stats(RateLimitEndpoint::Embeddings);
+    assert_eq!(stats.requests, 10);
+    assert_eq!(stats.queued, 8);
+    assert_eq!(stats.max_wait, Duration::from_millis(400));
+    assert_eq!(stats.mean_wait(), Duration::from_millis(200));
+    assert_eq!(limiter.available(RateLimitEndpoint::Embeddings), Some(2));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_endpoint_limits_are_separate() {
+    let transport = transport();
+    let config = transport
+        .config()
+        .with_max_in_flight_for(RateLimitEndpoint::Embeddings, 1);
+    let client = VoyageAiClient::new_with_config(config);
+    let limiter = client.voyage_config().concurrency_limiter.clone();
+    assert_eq!(limiter.available(RateLimitEndpoint::Reranking), None);
+
+    let rerank = async {
+        let request = RerankRequestBuilder::new()
+            .query("pets")
+            .add_documents(vec!["cats".to_string(), "rust".to_string()])
+            .build()
+            .unwrap();
+        client.config.rerank_client.rerank(request).await.unwrap();
+    };
+    tokio::join!(embed_many(&client, 3), rerank);
+
+    // One embeddings request at a time, next to the rerank request
+    assert_eq!(transport.peak_in_flight(), 2);
+    assert_eq!(limiter.stats(RateLimitEndpoint::Embeddings).queued, 2);
+    assert_eq!(limiter.stats(RateLimitEndpoint::Reranking).queued, 0);
+}
+
+#[tokio::test]
+async fn test_unlimited_by_default() {
+    let config = VoyageConfig::new("test-key".to_string());
+    assert!(!config.concurrency_limiter.is_limited());
+    let permit = config
+        .concurrency_limiter
+        .try_acquire(RateLimitEndpoint::Embeddings);
+    assert!(permit.is_some());
+
+    let limited = config.with_max_in_flight(1);
+    let permit = limited
+        .concurrency_limiter
+        .try_acquire(RateLimitEndpoint::Reranking);
+    assert!(permit.is_some());
+    assert!(limited
+        .concurrency_limiter
+        .try_acquire(RateLimitEndpoint::Embeddings)
+        .is_none());
+    drop(permit);
+    assert!(limited
+        .concurrency_limiter
+        .try_acquire(RateLimitEndpoint::Embeddings)
+        .is_some());
+}
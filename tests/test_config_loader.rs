@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use voyageai::{
+    client::{
+        rerank_client::RerankFallback, Priority, RateLimitEndpoint, RateLimitTier, RetryPolicy,
+    },
+    config::{ConfigLoader, ConfigSource},
+    test_util::TestServer,
+    traits::llm::Embedder,
+    EmbeddingModel, VoyageAiClient, VoyageBuilder, VoyageConfig, VoyageError,
+};
+
+fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> + Send + Sync + 'static {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    move |name| vars.get(name).cloned()
+}
+
+fn write_config(contents: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("voyage.toml");
+    std::fs::write(&path, contents).unwrap();
+    (dir, path)
+}
+
+#[test]
+fn test_defaults_without_file_or_env() {
+    let config = ConfigLoader::new()
+        .without_file()
+        .without_env()
+        .load()
+        .unwrap();
+    assert_eq!(config.embedding_model, EmbeddingModel::Voyage3Large);
+    assert_eq!(config.rate_limit_tier, RateLimitTier::Tier1);
+    assert_eq!(config.retry_policy, RetryPolicy::NONE);
+    assert!(config.sources.file().is_none());
+    assert!(config
+        .sources
+        .iter()
+        .all(|(_, source)| *source == ConfigSource::Default));
+}
+
+#[test]
+fn test_file_settings() {
+    let (_dir, path) = write_config(
+        r#"
+        base_url = "http://localhost:9999"
+        embedding_model = "voyage-code-3"
+        rate_limit_tier = 2
+        request_timeout = 30
+        rate_limit_timeout = 1.5
+        max_retries = 3
+        retry_initial_delay = 0.25
+        priority = "batch"
+        rerank_fallback = "on_rate_limit"
+        coalesce_requests = true
+        idempotency_keys = true
+        max_in_flight = 16
+        log_payloads = true
+        "#,
+    );
+    let config = ConfigLoader::new()
+        .with_file(&path)
+        .without_env()
+        .load()
+        .unwrap();
+
+    assert_eq!(config.base_url, "http://localhost:9999");
+    assert_eq!(config.embedding_model, EmbeddingModel::VoyageCode3);
+    assert_eq!(config.rate_limit_tier, RateLimitTier::Tier2);
+    assert_eq!(config.request_timeout, Some(Duration::from_secs(30)));
+    assert_eq!(config.rate_limit_timeout, Some(Duration::from_millis(1500)));
+    assert_eq!(
+        config.retry_policy,
+        RetryPolicy::new(3, Duration::from_millis(250))
+    );
+    assert_eq!(config.priority, Priority::Batch);
+    assert_eq!(config.rerank_fallback, RerankFallback::OnRateLimit);
+    assert!(config.coalesce_requests);
+    assert!(config.idempotency_keys);
+    assert_eq!(
+        config.concurrency_limiter.available(RateLimitEndpoint::Embeddings),
+        Some(16)
+    );
+    assert!(config.log_policy.logs_payloads());
+    assert_eq!(config.sources.file(), Some(path.as_path()));
+    assert_eq!(
+        config.sources.get("rate_limit_tier"),
+        &ConfigSource::File(path.clone())
+    );
+    assert_eq!(config.sources.get("api_version"), &ConfigSource::Default);
+}
+
+#[test]
+fn test_env_overrides_file() {
+    let (_dir, path) = write_config("base_url = \"http://file\"\nmax_retries = 2\n");
+    let config = ConfigLoader::new()
+        .with_file(&path)
+        .with_env(env(&[
+            ("VOYAGE_BASE_URL", "http://env"),
+            ("VOYAGE_API_KEY", "env-key"),
+            ("VOYAGE_RATE_LIMIT_TIER", "tier3"),
+        ]))
+        .load()
+        .unwrap();
+
+    assert_eq!(config.base_url, "http://env");
+    assert_eq!(config.api_key, "env-key");
+    assert_eq!(config.retry_policy.max_retries, 2);
+    assert_eq!(config.rate_limit_tier, RateLimitTier::Tier3);
+    assert_eq!(
+        config.sources.get("base_url"),
+        &ConfigSource::Env("VOYAGE_BASE_URL")
+    );
+    assert_eq!(
+        config.sources.get("max_retries"),
+        &ConfigSource::File(path.clone())
+    );
+
+    let report = config.sources.to_string();
+    assert!(report.contains("base_url: env VOYAGE_BASE_URL"));
+    assert!(report.contains(&format!("max_retries: file {}", path.display())));
+    assert!(report.contains("api_version: default"));
+    assert!(!report.contains("env-key"));
+}
+
+#[test]
+fn test_code_overrides_loaded_settings() {
+    let config = ConfigLoader::new()
+        .without_file()
+        .with_env(env(&[("VOYAGE_PRIORITY", "batch")]))
+        .load()
+        .unwrap()
+        .with_priority(Priority::High);
+    assert_eq!(config.priority, Priority::High);
+}
+
+#[test]
+fn test_config_file_from_env_variable() {
+    let (_dir, path) = write_config("embedding_model = \"voyage-code-3\"\n");
+    let config = ConfigLoader::new()
+        .with_env(env(&[("VOYAGE_CONFIG", path.to_str().unwrap())]))
+        .load()
+        .unwrap();
+    assert_eq!(config.embedding_model, EmbeddingModel::VoyageCode3);
+
+    let missing = ConfigLoader::new()
+        .with_env(env(&[("VOYAGE_CONFIG", "/nonexistent/voyage.toml")]))
+        .load();
+    assert!(matches!(missing, Err(VoyageError::ConfigError(_))));
+}
+
+#[test]
+fn test_errors_name_their_source() {
+    let (_dir, path) = write_config("rate_limit_teir = 2\n");
+    let error = ConfigLoader::new()
+        .with_file(&path)
+        .without_env()
+        .load()
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("unknown setting 'rate_limit_teir'"));
+
+    let (_dir, path) = write_config("api_key = \"secret\"\n");
+    assert!(ConfigLoader::new()
+        .with_file(&path)
+        .without_env()
+        .load()
+        .is_err());
+
+    let error = ConfigLoader::new()
+        .without_file()
+        .with_env(env(&[("VOYAGE_REQUEST_TIMEOUT", "soon")]))
+        .load()
+        .unwrap_err();
+    assert!(error.to_string().contains("VOYAGE_REQUEST_TIMEOUT"));
+}
+
+#[test]
+fn test_tier_scales_client_rate_limits() {
+    let config = VoyageConfig::new("key".to_string()).with_rate_limit_tier(RateLimitTier::Tier2);
+    let client = VoyageBuilder::new()
+        .with_config(config.clone())
+        .build()
+        .unwrap();
+    let limiter = client.embeddings_client().rate_limiter();
+    assert_eq!(
+        limiter
+            .limit(RateLimitEndpoint::Embeddings)
+            .requests_per_minute,
+        600
+    );
+    assert_eq!(
+        limiter
+            .limit(RateLimitEndpoint::Reranking)
+            .requests_per_minute,
+        200
+    );
+    let client = VoyageAiClient::new_with_config(config);
+    assert_eq!(
+        client
+            .embeddings_client()
+            .rate_limiter()
+            .limit(RateLimitEndpoint::Embeddings)
+            .tokens_per_minute,
+        2_000_000
+    );
+
+    let tier = RateLimitTier::Tier2;
+    let limit = tier.limit(RateLimitEndpoint::Embeddings);
+    assert_eq!(limit.requests_per_minute, 600);
+    assert_eq!(limit.tokens_per_minute, 2_000_000);
+    assert_eq!("3".parse::<RateLimitTier>(), Ok(RateLimitTier::Tier3));
+    assert!("4".parse::<RateLimitTier>().is_err());
+}
+
+#[tokio::test]
+async fn test_retry_policy_retries_rate_limited_requests() {
+    use voyageai::test_util::wiremock::{matchers::path, Mock, ResponseTemplate};
+
+    let server = TestServer::start().await;
+    Mock::given(path("/v1/embeddings"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .up_to_n_times(1)
+        .mount(server.mock_server())
+        .await;
+    let server = server.with_embeddings().await;
+
+    let client = VoyageAiClient::new_with_config(
+        server
+            .config()
+            .with_retry_policy(RetryPolicy::new(2, Duration::from_millis(1))),
+    );
+    assert_eq!(client.embed("retried").await.unwrap().len(), 1024);
+    assert_eq!(server.received_requests().await.len(), 2);
+}
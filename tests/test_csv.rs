@@ -0,0 +1,70 @@
+use voyageai::{
+    io::{csv::parse_csv, read_csv_texts, CsvText, CsvWriter},
+    VoyageError,
+};
+
+#[test]
+fn test_parse_quoted_fields() -> Result<(), VoyageError> {
+    let input = "id,body\r\n1,\"Hello, \"\"world\"\"\"\r\n\r\n2,\"two\nlines\"\n3,plain";
+    assert_eq!(
+        parse_csv(input)?,
+        vec![
+            vec!["id", "body"],
+            vec!["1", "Hello, \"world\""],
+            vec!["2", "two\nlines"],
+            vec!["3", "plain"],
+        ]
+    );
+    assert!(matches!(
+        parse_csv("id,body\n1,\"open"),
+        Err(VoyageError::InterchangeError(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_read_texts_by_column() -> Result<(), VoyageError> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tickets.csv");
+    std::fs::write(
+        &path,
+        "\u{feff}ticket,body,priority\nT-1,Printer is on fire,high\nT-2,,low\nT-3,\"Login fails, twice\",low\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        read_csv_texts(&path, "body", Some("ticket"))?,
+        vec![
+            CsvText {
+                id: "T-1".to_string(),
+                text: "Printer is on fire".to_string()
+            },
+            CsvText {
+                id: "T-3".to_string(),
+                text: "Login fails, twice".to_string()
+            },
+        ]
+    );
+    let ids: Vec<String> = read_csv_texts(&path, "body", None)?
+        .into_iter()
+        .map(|text| text.id)
+        .collect();
+    assert_eq!(ids, ["0", "2"]);
+
+    let error = read_csv_texts(&path, "text", None).unwrap_err();
+    assert!(error.to_string().contains("ticket, body, priority"));
+    Ok(())
+}
+
+#[test]
+fn test_writer_round_trips_through_parser() -> Result<(), VoyageError> {
+    let mut writer = CsvWriter::new(Vec::new(), &["rank", "score", "document"])?;
+    writer.write_row(&["1", "0.9500", "Says \"hi\", then\nleaves"])?;
+    let output = String::from_utf8(writer.finish()?).unwrap();
+    assert_eq!(
+        output,
+        "rank,score,document\n1,0.9500,\"Says \"\"hi\"\", then\nleaves\"\n"
+    );
+    assert_eq!(parse_csv(&output)?[1][2], "Says \"hi\", then\nleaves");
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use voyageai::{
+    chunking::ParagraphChunker,
+    index::{dedupe_by_similarity, DedupFilter},
+    retrieval::RetrievalPipeline,
+    test_util::TestServer,
+    VoyageError,
+};
+
+/// Deterministic pseudo-random unit-ish vectors.
+fn vectors(count: usize, dimension: usize, seed: u64) -> Vec<Vec<f32>> {
+    let mut state = seed;
+    (0..count)
+        .map(|_| {
+            (0..dimension)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    ((state >> 33) as f32 / (1u64 << 31) as f32) - 0.5
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn perturb(vector: &[f32], amount: f32) -> Vec<f32> {
+    vector
+        .iter()
+        .enumerate()
+        .map(|(i, x)| x + if i % 2 == 0 { amount } else { -amount })
+        .collect()
+}
+
+#[test]
+fn test_dedupe_by_similarity_keeps_first_occurrences() {
+    let embeddings = vec![
+        vec![1.0, 0.0, 0.0],
+        vec![0.0, 1.0, 0.0],
+        vec![0.99, 0.01, 0.0],
+        vec![0.0, 0.0, 1.0],
+        vec![0.0, 1.0, 0.0],
+    ];
+
+    assert_eq!(dedupe_by_similarity(&embeddings, 0.95), vec![0, 1, 3]);
+    // Similarity must exceed the threshold
+    assert_eq!(dedupe_by_similarity(&embeddings, 1.0), vec![0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn test_filter_finds_perturbed_copies_among_many() {
+    let base = vectors(2000, 64, 7);
+    let mut filter = DedupFilter::new(0.95);
+    for (i, vector) in base.iter().enumerate() {
+        filter.insert(format!("doc{i}"), vector);
+    }
+    assert_eq!(filter.len(), 2000);
+
+    let found = (0..200)
+        .filter(|&i| {
+            filter.find_duplicate(&perturb(&base[i * 10], 0.02)) == Some(&format!("doc{}", i * 10))
+        })
+        .count();
+    assert!(found >= 195, "found {found} of 200 near-duplicates");
+
+    let unrelated = vectors(200, 64, 99);
+    assert!(unrelated
+        .iter()
+        .all(|vector| filter.find_duplicate(vector).is_none()));
+}
+
+#[test]
+fn test_removed_embeddings_are_forgotten() {
+    let mut filter = DedupFilter::new(0.9).with_lsh(4, 8).with_seed(1);
+    assert!(filter.admit("a", &[1.0, 0.0]));
+    assert!(!filter.admit("b", &[1.0, 0.01]));
+    assert_eq!(filter.find_duplicate(&[0.99, 0.0]), Some("a"));
+
+    assert!(filter.remove("a"));
+    assert!(!filter.remove("a"));
+    assert!(filter.is_empty());
+    assert!(filter.admit("b", &[1.0, 0.01]));
+    // Other dimensions never match
+    assert_eq!(filter.find_duplicate(&[1.0, 0.0, 0.0]), None);
+}
+
+#[tokio::test]
+async fn test_pipeline_drops_duplicate_chunks() -> Result<(), VoyageError> {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let mut pipeline = RetrievalPipeline::builder(&client)
+        .chunker(ParagraphChunker::new(50))
+        .dedup(DedupFilter::new(0.98))
+        .without_rerank()
+        .build();
+
+    let boilerplate = "Copyright Example Corp, all rights reserved.";
+    let stored = pipeline
+        .add_documents([
+            ("a", format!("Rust ownership explained.\n\n{boilerplate}")),
+            ("b", format!("Baking sourdough bread.\n\n{boilerplate}")),
+        ])
+        .await?;
+    assert_eq!(stored, 3);
+    assert_eq!(pipeline.index().len(), 3);
+
+    // Removing the document holding the boilerplate frees it for reuse
+    pipeline.remove_document("a");
+    let stored = pipeline
+        .add_document("c", format!("Python typing.\n\n{boilerplate}"), None)
+        .await?;
+    assert_eq!(stored, 2);
+    let stored = pipeline.add_document("d", boilerplate, None).await?;
+    assert_eq!(stored, 0);
+    Ok(())
+}
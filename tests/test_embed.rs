@@ -27,7 +27,7 @@ async fn test_embedding() -> Result<(), Box<dyn std::error::Error>> {
         "Soul and rock both influenced modern music.",
     ];
 
-    let request = EmbeddingsRequestBuilder::new()
+    let _request = EmbeddingsRequestBuilder::new()
         .input(EmbeddingsInput::Multiple(
             inputs.iter().map(|&s| s.to_string()).collect(),
         ))
@@ -72,7 +72,7 @@ async fn test_embedding_single_input() -> Result<(), Box<dyn std::error::Error>>
 
     let input = "Soul rock music combines elements of both genres.";
 
-    let request = EmbeddingsRequestBuilder::new()
+    let _request = EmbeddingsRequestBuilder::new()
         .input(EmbeddingsInput::Single(input.to_string()))
         .model(EmbeddingModel::Voyage3Large)
         .input_type(InputType::Document)
@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use serde_json::Value;
+use voyageai::{
+    client::AutoBatcher,
+    test_util::{wiremock::Request, TestServer},
+    traits::llm::Embedder,
+    EmbedOptions, EmbeddingsResponse, EncodingFormat, InputType,
+};
+
+fn bodies(requests: &[Request]) -> Vec<Value> {
+    requests
+        .iter()
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_options_are_sent_with_the_request() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+
+    client.embed("cats").await.unwrap();
+    let options = EmbedOptions::new()
+        .input_type(InputType::Query)
+        .truncation(false);
+    client.embed_with("cats", options).await.unwrap();
+    let texts = vec!["cats".to_string(), "dogs".to_string()];
+    client
+        .embed_batch_with(&texts, EmbedOptions::new().input_type(InputType::Document))
+        .await
+        .unwrap();
+
+    let bodies = bodies(&server.received_requests().await);
+    // Defaults leave the settings to the API
+    assert!(bodies[0].get("input_type").is_none());
+    assert!(bodies[0].get("truncation").is_none());
+    assert_eq!(bodies[1]["input_type"], "query");
+    assert_eq!(bodies[1]["truncation"], false);
+    assert_eq!(bodies[2]["input_type"], "document");
+    assert_eq!(bodies[2]["model"], "voyage-3-large");
+}
+
+#[tokio::test]
+async fn test_base64_embeddings_are_decoded() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let texts = vec!["cats purr".to_string(), "dogs bark".to_string()];
+
+    let floats = client.embed_batch(&texts).await.unwrap();
+    let options = EmbedOptions::new().encoding_format(EncodingFormat::Base64);
+    let decoded = client.embed_batch_with(&texts, options).await.unwrap();
+    assert_eq!(decoded, floats);
+
+    let requests = server.received_requests().await;
+    assert_eq!(bodies(&requests)[1]["encoding_format"], "base64");
+
+    // Little-endian 1.0 and 2.0
+    let response: EmbeddingsResponse = serde_json::from_str(
+        r#"{"data": [{"object": "embedding", "embedding": "AACAPwAAAEA=", "index": 0}], "usage": {"total_tokens": 1}}"#,
+    )
+    .unwrap();
+    assert_eq!(response.data[0].embedding, vec![1.0, 2.0]);
+}
+
+#[tokio::test]
+async fn test_auto_batcher_sends_other_options_separately() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let batcher = AutoBatcher::new(&server.client())
+        .with_input_type(Some(InputType::Document))
+        .with_max_delay(Duration::from_millis(50));
+
+    let (a, b, c) = tokio::join!(
+        batcher.embed("alpha"),
+        batcher.embed_with("beta", EmbedOptions::new().input_type(InputType::Document)),
+        batcher.embed_with("gamma", EmbedOptions::new().truncation(false)),
+    );
+    a.unwrap();
+    b.unwrap();
+    c.unwrap();
+
+    let mut bodies = bodies(&server.received_requests().await);
+    bodies.sort_by_key(|body| body["input"].as_array().unwrap().len());
+    assert_eq!(bodies.len(), 2);
+    // The unbatched text keeps the batcher's input type
+    assert_eq!(bodies[0]["input"], serde_json::json!(["gamma"]));
+    assert_eq!(bodies[0]["input_type"], "document");
+    assert_eq!(bodies[0]["truncation"], false);
+    assert_eq!(bodies[1]["input"], serde_json::json!(["alpha", "beta"]));
+}
@@ -0,0 +1,90 @@
+use serde_json::{json, Value};
+use tokio_stream::StreamExt;
+use voyageai::{
+    client::embeddings_client::Client as EmbeddingsClient, EmbeddingModel, VoyageConfig,
+};
+
+/// Encodes the number in "text-N" into the first dimension of the embedding.
+fn embed(text: &str) -> Vec<f32> {
+    let n: f32 = text.trim_start_matches("text-").parse().unwrap();
+    let mut embedding = vec![0.0; EmbeddingModel::Voyage3Large.embedding_dimension()];
+    embedding[0] = n;
+    embedding
+}
+
+async fn mock_server(expected_requests: usize) -> (mockito::ServerGuard, mockito::Mock) {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/embeddings")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body_from_request(|request| {
+            let body: Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+            let inputs = body["input"].as_array().cloned().unwrap_or_default();
+            // Return the data out of order; the client must reassemble by index.
+            let data: Vec<Value> = inputs
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(index, text)| {
+                    json!({"object": "embedding", "embedding": embed(text.as_str().unwrap()), "index": index})
+                })
+                .collect();
+            json!({"object": "list", "data": data, "model": "voyage-3-large", "usage": {"total_tokens": 1}})
+                .to_string()
+                .into()
+        })
+        .expect(expected_requests)
+        .create_async()
+        .await;
+    (server, mock)
+}
+
+fn client(server: &mockito::ServerGuard) -> EmbeddingsClient {
+    EmbeddingsClient::new(VoyageConfig::new("key".to_string()).with_base_url(server.url()))
+}
+
+#[tokio::test]
+async fn test_embed_stream_preserves_input_indices() {
+    let (server, mock) = mock_server(4).await;
+    let texts: Vec<String> = (0..100).map(|i| format!("text-{i}")).collect();
+
+    let mut embeddings: Vec<_> = client(&server)
+        .embed_stream_with(texts, 25, 3)
+        .collect()
+        .await;
+
+    mock.assert_async().await;
+    assert_eq!(embeddings.len(), 100);
+    embeddings.sort_by_key(|item| item.index);
+    for (i, item) in embeddings.iter().enumerate() {
+        assert_eq!(item.index, i);
+        assert_eq!(item.embedding[0], i as f32);
+    }
+}
+
+#[tokio::test]
+async fn test_embed_stream_uneven_last_batch() {
+    let (server, mock) = mock_server(3).await;
+    let texts: Vec<String> = (0..70).map(|i| format!("text-{i}")).collect();
+
+    let embeddings: Vec<_> = client(&server)
+        .embed_stream_with(texts, 32, 4)
+        .collect()
+        .await;
+
+    mock.assert_async().await;
+    let mut indices: Vec<usize> = embeddings.iter().map(|item| item.index).collect();
+    indices.sort_unstable();
+    assert_eq!(indices, (0..70).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_embed_stream_empty_input() {
+    let (server, mock) = mock_server(0).await;
+
+    let embeddings: Vec<_> = client(&server).embed_stream(Vec::new()).collect().await;
+
+    mock.assert_async().await;
+    assert!(embeddings.is_empty());
+}
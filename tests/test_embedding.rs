@@ -0,0 +1,72 @@
+use voyageai::{
+    test_util::TestServer, vectors::VectorError, EmbedOptions, Embedding, VectorIndex, VoyageError,
+};
+
+#[test]
+fn test_mismatched_embeddings_are_not_compared() {
+    let cats = Embedding::new(vec![0.6, 0.8], "voyage-3-large");
+    let dogs = Embedding::new(vec![3.0, 4.0], "voyage-3-large");
+    assert!(cats.is_normalized());
+    assert!(!dogs.is_normalized());
+    assert!((cats.cosine_similarity(&dogs).unwrap() - 1.0).abs() < 1e-6);
+    assert!(dogs.clone().normalized().is_normalized());
+
+    let code = Embedding::new(vec![0.6, 0.8], "voyage-code-3");
+    assert_eq!(
+        cats.cosine_similarity(&code),
+        Err(VectorError::ModelMismatch {
+            expected: "voyage-3-large".to_string(),
+            actual: "voyage-code-3".to_string(),
+        })
+    );
+    let wider = Embedding::new(vec![0.6, 0.8, 0.0], "voyage-3-large");
+    assert!(matches!(
+        cats.cosine_similarity(&wider),
+        Err(VectorError::DimensionMismatch { .. })
+    ));
+    // Embeddings of unknown origin only need a matching dimension
+    let unlabeled = Embedding::from(vec![0.0, 1.0]);
+    assert!((cats.cosine_similarity(&unlabeled).unwrap() - 0.8).abs() < 1e-6);
+
+    let json = serde_json::to_string(&dogs).unwrap();
+    let read: Embedding = serde_json::from_str(&json).unwrap();
+    assert_eq!(read, dogs);
+    assert_eq!(read.model(), Some("voyage-3-large"));
+}
+
+#[tokio::test]
+async fn test_typed_embeddings_from_the_client_and_index() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let texts = vec!["cats purr".to_string(), "dogs bark".to_string()];
+
+    let embeddings = client
+        .embeddings_client()
+        .embed_typed(&texts, EmbedOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(embeddings.len(), 2);
+    assert_eq!(embeddings[0].model(), Some("voyage-3-large"));
+    assert_eq!(embeddings[0].dimension(), 1024);
+    assert!(embeddings[0].is_normalized());
+
+    let mut index = VectorIndex::new();
+    for (text, embedding) in texts.iter().zip(&embeddings) {
+        index
+            .insert_embedding(text.as_str(), text.as_str(), embedding.clone(), None)
+            .unwrap();
+    }
+    assert_eq!(index.model(), Some("voyage-3-large"));
+    let matches = index.search_embedding(&embeddings[1], 1, None).unwrap();
+    assert_eq!(matches[0].record.id, "dogs bark");
+
+    let code = Embedding::new(embeddings[0].to_vec(), "voyage-code-3");
+    assert!(matches!(
+        index.search_embedding(&code, 1, None),
+        Err(VoyageError::Vector(VectorError::ModelMismatch { .. }))
+    ));
+    assert!(index
+        .insert_embedding("code", "fn main() {}", code, None)
+        .is_err());
+    assert_eq!(index.len(), 2);
+}
@@ -0,0 +1,121 @@
+use serde_json::json;
+use voyageai::{
+    client::embeddings_client::Client as EmbeddingsClient, EmbeddingModel, VoyageConfig,
+    VoyageError,
+};
+
+fn response_body(embeddings: Vec<Vec<f32>>) -> String {
+    let data: Vec<_> = embeddings
+        .into_iter()
+        .enumerate()
+        .map(|(index, embedding)| json!({"object": "embedding", "embedding": embedding, "index": index}))
+        .collect();
+    json!({"object": "list", "data": data, "model": "voyage-3-large", "usage": {"total_tokens": 4}})
+        .to_string()
+}
+
+async fn client_returning(body: String) -> (mockito::ServerGuard, EmbeddingsClient) {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("POST", "/v1/embeddings")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(body)
+        .create_async()
+        .await;
+    let client =
+        EmbeddingsClient::new(VoyageConfig::new("key".to_string()).with_base_url(server.url()));
+    (server, client)
+}
+
+#[tokio::test]
+async fn test_empty_response_is_an_error() {
+    let (_server, client) = client_returning(response_body(Vec::new())).await;
+
+    let result = client.embed("hello").await;
+
+    assert!(matches!(result, Err(VoyageError::EmptyResponse)));
+}
+
+#[tokio::test]
+async fn test_dimension_mismatch_is_an_error() {
+    let dimension = EmbeddingModel::Voyage3Large.embedding_dimension();
+    let (_server, client) =
+        client_returning(response_body(vec![vec![0.1; dimension], vec![0.1; 3]])).await;
+
+    let result = client
+        .embed_batch(&["a".to_string(), "b".to_string()])
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(VoyageError::EmbeddingDimensionMismatch {
+            index: 1,
+            expected: 1024,
+            actual: 3
+        })
+    ));
+}
+
+#[tokio::test]
+async fn test_valid_response_passes_validation() {
+    let (_server, client) = client_returning(response_body(vec![vec![0.5; 1024]])).await;
+
+    let embedding = client.embed("hello").await.unwrap();
+
+    assert_eq!(embedding.len(), 1024);
+}
+
+/// A response whose entries are listed in the given index order; each
+/// embedding is filled with its own index so order can be checked.
+fn indexed_body(indices: &[usize]) -> String {
+    let data: Vec<_> = indices
+        .iter()
+        .map(|&index| json!({"object": "embedding", "embedding": vec![index as f32; 1024], "index": index}))
+        .collect();
+    json!({"object": "list", "data": data, "model": "voyage-3-large", "usage": {"total_tokens": 4}})
+        .to_string()
+}
+
+fn texts(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("text {i}")).collect()
+}
+
+#[tokio::test]
+async fn test_shuffled_response_is_reassembled_in_input_order() {
+    let (_server, client) = client_returning(indexed_body(&[3, 0, 4, 2, 1])).await;
+
+    let embeddings = client.embed_batch(&texts(5)).await.unwrap();
+
+    let order: Vec<f32> = embeddings.iter().map(|embedding| embedding[0]).collect();
+    assert_eq!(order, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+}
+
+#[tokio::test]
+async fn test_missing_index_is_an_error() {
+    let (_server, client) = client_returning(indexed_body(&[2, 0])).await;
+
+    let result = client.embed_batch(&texts(3)).await;
+
+    assert!(matches!(
+        result,
+        Err(VoyageError::MissingEmbedding { index: 1 })
+    ));
+}
+
+#[tokio::test]
+async fn test_duplicate_or_out_of_range_index_is_an_error() {
+    let (_server, client) = client_returning(indexed_body(&[1, 0, 1])).await;
+    let result = client.embed_batch(&texts(3)).await;
+    assert!(matches!(
+        result,
+        Err(VoyageError::UnexpectedEmbeddingIndex { index: 1 })
+    ));
+
+    let (_server, client) = client_returning(indexed_body(&[0, 5])).await;
+    let result = client.embed_batch(&texts(2)).await;
+    assert!(matches!(
+        result,
+        Err(VoyageError::UnexpectedEmbeddingIndex { index: 5 })
+    ));
+}
@@ -1,12 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use voyageai::{
-        builder::embeddings::EmbeddingsRequestBuilder,
-        cosine_similarity,
-        models::embeddings::{EmbeddingModel, EmbeddingsInput},
-        InputType, VoyageAiClient, VoyageConfig,
-        traits::llm::Embedder,
-    };
+    use voyageai::{cosine_similarity, traits::llm::Embedder, VoyageAiClient, VoyageConfig};
 
     #[tokio::test]
     async fn test_embeddings_similarity() {
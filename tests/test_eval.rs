@@ -0,0 +1,182 @@
+use serde_json::{json, Value};
+use voyageai::{
+    eval::{
+        benchmark_pipeline, benchmarks_to_csv, benchmarks_to_table, evaluate, evaluate_pipeline,
+        reports_to_csv, EvalDataset, EvalQuery,
+    },
+    retrieval::RetrievalPipeline,
+    VoyageAiClient, VoyageConfig, VoyageError,
+};
+
+fn query(text: &str, relevant: &[&str]) -> EvalQuery {
+    EvalQuery {
+        query: text.to_string(),
+        relevant: relevant.iter().map(|id| id.to_string()).collect(),
+    }
+}
+
+fn ranking(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+#[test]
+fn test_metrics() {
+    let queries = [query("q1", &["a", "b"]), query("q2", &["c"])];
+    let rankings = [ranking(&["x", "a", "y", "b"]), ranking(&["c", "z"])];
+
+    let report = evaluate("model", &queries, &rankings, &[1, 3]).unwrap();
+
+    // q1: first hit at rank 2, one of two relevant in the top 3
+    assert_eq!(report.queries[0].reciprocal_rank, 0.5);
+    assert_eq!(report.queries[0].recall[&1], 0.0);
+    assert_eq!(report.queries[0].recall[&3], 0.5);
+    let expected_ndcg = (1.0 / 3f64.log2()) / (1.0 + 1.0 / 3f64.log2());
+    assert!((report.queries[0].ndcg[&3] - expected_ndcg).abs() < 1e-9);
+    // q2: perfect ranking
+    assert_eq!(report.queries[1].reciprocal_rank, 1.0);
+    assert_eq!(report.queries[1].ndcg[&1], 1.0);
+
+    assert_eq!(report.mrr, 0.75);
+    assert_eq!(report.recall[&1], 0.5);
+    assert_eq!(report.recall[&3], 0.75);
+}
+
+#[test]
+fn test_rankings_must_match_queries() {
+    let queries = [query("q1", &["a"]), query("q2", &["b"])];
+
+    let error = evaluate("model", &queries, &[ranking(&["a"])], &[1]).unwrap_err();
+
+    assert!(matches!(error, VoyageError::EvalError(_)));
+    assert_eq!(
+        error.to_string(),
+        "Evaluation error: 1 rankings for 2 queries"
+    );
+}
+
+#[test]
+fn test_dataset_from_jsonl() {
+    let dataset = EvalDataset::from_jsonl(
+        r#"{"id": "d1", "text": "Paris is in France."}
+
+{"query": "capital of France", "relevant": ["d1"]}
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(dataset.documents.len(), 1);
+    assert_eq!(dataset.queries, vec![query("capital of France", &["d1"])]);
+    assert!(EvalDataset::from_jsonl("{\"unexpected\": 1}").is_err());
+}
+
+#[test]
+fn test_report_csv_and_json() {
+    let queries = [query("a, \"quoted\" query", &["a"])];
+    let report = evaluate("voyage-3", &queries, &[ranking(&["a"])], &[1]).unwrap();
+
+    let csv = reports_to_csv(std::slice::from_ref(&report));
+    assert_eq!(
+        csv,
+        "label,queries,mrr,recall@1,ndcg@1\nvoyage-3,1,1.0000,1.0000,1.0000\n"
+    );
+    assert!(report
+        .queries_to_csv()
+        .contains("\"a, \"\"quoted\"\" query\""));
+
+    let json: Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+    assert_eq!(json["mrr"], 1.0);
+}
+
+/// Serves embeddings that count mentions of France and Germany.
+async fn mock_embeddings(server: &mut mockito::Server) -> mockito::Mock {
+    server
+        .mock("POST", "/v1/embeddings")
+        .with_header("content-type", "application/json")
+        .with_body_from_request(|request| {
+            let body: Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+            let data: Vec<Value> = body["input"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .map(|(index, text)| {
+                    let text = text.as_str().unwrap().to_lowercase();
+                    let mut embedding = vec![0.0f32; 1024];
+                    embedding[0] = text.matches("france").count() as f32 + 0.01;
+                    embedding[1] = text.matches("germany").count() as f32 + 0.01;
+                    json!({"object": "embedding", "embedding": embedding, "index": index})
+                })
+                .collect();
+            json!({"object": "list", "data": data, "model": "voyage-3-large", "usage": {"total_tokens": 1}})
+                .to_string()
+                .into()
+        })
+        .create_async()
+        .await
+}
+
+fn dataset() -> EvalDataset {
+    EvalDataset::from_jsonl(
+        r#"{"id": "fr", "text": "Paris is the capital of France."}
+{"id": "de", "text": "Berlin is the capital of Germany."}
+{"query": "France", "relevant": ["fr"]}
+{"query": "Germany", "relevant": ["de"]}"#,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_evaluate_pipeline() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = mock_embeddings(&mut server).await;
+    let client = VoyageAiClient::new_with_config(
+        VoyageConfig::new("key".to_string()).with_base_url(server.url()),
+    );
+    let mut pipeline = RetrievalPipeline::builder(&client)
+        .without_rerank()
+        .top_k(2)
+        .build();
+    let dataset = dataset();
+
+    let report = evaluate_pipeline("voyage-3-large", &mut pipeline, &dataset, &[1, 2])
+        .await
+        .unwrap();
+
+    assert_eq!(report.mrr, 1.0);
+    assert_eq!(report.recall[&1], 1.0);
+    assert_eq!(report.ndcg[&2], 1.0);
+}
+
+#[tokio::test]
+async fn test_benchmark_pipeline() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = mock_embeddings(&mut server).await;
+    let client = VoyageAiClient::new_with_config(
+        VoyageConfig::new("key".to_string()).with_base_url(server.url()),
+    );
+    let mut pipeline = RetrievalPipeline::builder(&client)
+        .without_rerank()
+        .top_k(2)
+        .build();
+
+    let result = benchmark_pipeline("voyage-3-large", &mut pipeline, &dataset(), &[1])
+        .await
+        .unwrap();
+
+    assert_eq!(result.report.mrr, 1.0);
+    // One request for the corpus and one per query, each billed 1 token
+    assert_eq!(result.tokens, 3);
+
+    let table = benchmarks_to_table(std::slice::from_ref(&result));
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("label"));
+    assert!(lines[0].contains("recall@1"));
+    assert!(lines[1].chars().all(|c| c == '-'));
+    assert!(lines[2].starts_with("voyage-3-large  1.0000"));
+    assert!(lines[2].ends_with(" 3"));
+    assert_eq!(lines[0].len(), lines[2].len());
+
+    let csv = benchmarks_to_csv(&[result]);
+    assert!(csv.starts_with("label,mrr,recall@1,ndcg@1,index_ms,query_ms,p95_ms,tokens\n"));
+}
@@ -0,0 +1,196 @@
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use voyageai::{
+    index::{HnswConfig, NamespacedIndex},
+    VectorIndex, VoyageError,
+};
+
+fn past() -> SystemTime {
+    SystemTime::now() - Duration::from_secs(60)
+}
+
+fn future() -> SystemTime {
+    SystemTime::now() + Duration::from_secs(3600)
+}
+
+fn news() -> VectorIndex {
+    let mut index = VectorIndex::new();
+    index
+        .insert_expiring("old", "yesterday's news", vec![1.0, 0.0], None, past())
+        .unwrap();
+    index
+        .insert_expiring("fresh", "today's news", vec![0.9, 0.1], None, future())
+        .unwrap();
+    index
+        .insert("archive", "evergreen", vec![0.0, 1.0], None)
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_expired_documents_are_skipped_lazily() {
+    let index = news();
+
+    let ids: Vec<&str> = index
+        .search(&[1.0, 0.0], 10, None)
+        .unwrap()
+        .iter()
+        .map(|m| m.record.id.as_str())
+        .collect();
+    assert_eq!(ids, ["fresh", "archive"]);
+    assert!(index.get("old").is_none());
+    assert!(!index.contains("old"));
+    assert!(index.contains("fresh"));
+    // Still stored until evicted
+    assert_eq!(index.len(), 3);
+}
+
+#[test]
+fn test_hnsw_search_still_fills_top_k() {
+    let mut index = VectorIndex::new().with_hnsw(HnswConfig::default());
+    for i in 0..20 {
+        let embedding = vec![1.0, i as f32 / 100.0];
+        index
+            .insert_expiring(format!("old-{i}"), "stale", embedding, None, past())
+            .unwrap();
+    }
+    index
+        .insert("live", "current", vec![0.5, 0.5], None)
+        .unwrap();
+
+    let matches = index.search(&[1.0, 0.0], 1, None).unwrap();
+
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].record.id, "live");
+}
+
+#[test]
+fn test_evict_expired_removes_documents() {
+    let mut index = news();
+
+    assert_eq!(index.evict_expired(), 1);
+    assert_eq!(index.len(), 2);
+    assert_eq!(index.evict_expired(), 0);
+
+    // Evicting at a later time catches documents expiring before it
+    let later = SystemTime::now() + Duration::from_secs(7200);
+    assert_eq!(index.evict_expired_at(later), 1);
+    assert_eq!(index.iter().next().unwrap().0.id, "archive");
+}
+
+#[test]
+fn test_evict_expired_rebuilds_hnsw_and_tombstones() {
+    let mut index = VectorIndex::new().with_hnsw(HnswConfig::default());
+    for i in 0..20 {
+        let embedding = vec![1.0, i as f32 / 100.0];
+        let expires_at = if i % 2 == 0 { past() } else { future() };
+        index
+            .insert_expiring(format!("doc-{i}"), "text", embedding, None, expires_at)
+            .unwrap();
+    }
+    index.delete("doc-0");
+    index.delete("doc-1");
+
+    assert_eq!(index.evict_expired(), 10);
+
+    assert_eq!(index.tombstoned(), 1);
+    assert_eq!(index.len(), 9);
+    let matches = index.search(&[1.0, 0.19], 20, None).unwrap();
+    assert_eq!(matches.len(), 9);
+    assert_eq!(matches[0].record.id, "doc-19");
+    assert_eq!(index.get("doc-19").unwrap().1, &[1.0, 0.19]);
+}
+
+#[test]
+fn test_default_ttl_and_set_expiry() {
+    let mut index = VectorIndex::new().with_default_ttl(Duration::from_secs(60));
+    index.insert("a", "alpha", vec![1.0, 0.0], None).unwrap();
+    let expires_at = index.get("a").unwrap().0.expires_at.unwrap();
+    assert!(expires_at > SystemTime::now());
+    assert!(expires_at <= SystemTime::now() + Duration::from_secs(60));
+
+    assert!(index.set_expiry("a", None));
+    assert_eq!(
+        index.evict_expired_at(future() + Duration::from_secs(3600)),
+        0
+    );
+    assert!(index.set_expiry("a", Some(past())));
+    assert!(index.get("a").is_none());
+    assert!(!index.set_expiry("missing", None));
+}
+
+#[test]
+fn test_expiry_survives_save_and_load() -> Result<(), Box<dyn std::error::Error>> {
+    let index = news();
+    let mut bytes = Vec::new();
+    index.write_to(&mut bytes)?;
+
+    let loaded = VectorIndex::from_bytes(&bytes)?;
+
+    assert_eq!(loaded, index);
+    assert!(loaded.get("old").is_none());
+    assert!(loaded.iter().nth(1).unwrap().0.expires_at.is_some());
+    Ok(())
+}
+
+#[test]
+fn test_corrupt_expiry_is_a_format_error() {
+    let expires_at = SystemTime::UNIX_EPOCH + Duration::from_secs(0x0123_4567_89ab_cdef);
+    let mut index = VectorIndex::new();
+    index
+        .insert_expiring("a", "alpha", vec![1.0, 0.0], None, expires_at)
+        .unwrap();
+    let mut bytes = Vec::new();
+    index.write_to(&mut bytes).unwrap();
+
+    // Push the stored seconds past what a SystemTime can hold
+    let secs = 0x0123_4567_89ab_cdefu64.to_le_bytes();
+    let at = bytes
+        .windows(secs.len())
+        .position(|window| window == secs)
+        .unwrap();
+    bytes[at..at + secs.len()].fill(0xff);
+
+    let error = VectorIndex::from_bytes(&bytes).unwrap_err();
+    assert!(matches!(error, VoyageError::IndexFormatError(_)));
+}
+
+#[test]
+fn test_namespaces_evict_independently() {
+    let mut index = NamespacedIndex::new();
+    index
+        .insert_expiring("acme", "1", "stale", vec![1.0, 0.0], None, past())
+        .unwrap();
+    index
+        .insert("acme", "2", "kept", vec![0.0, 1.0], None)
+        .unwrap();
+    index
+        .insert("globex", "1", "kept", vec![1.0, 0.0], None)
+        .unwrap();
+    let globex = index.namespace("globex").unwrap().clone();
+
+    assert_eq!(
+        index.search("acme", &[1.0, 0.0], 10, None).unwrap().len(),
+        1
+    );
+    assert_eq!(index.evict_expired(), 1);
+    assert_eq!(index.len(), 2);
+    // Namespaces without expired documents are still shared
+    assert!(Arc::ptr_eq(&globex, index.namespace("globex").unwrap()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_background_eviction() {
+    let index = Arc::new(RwLock::new(VectorIndex::new()));
+    index
+        .write()
+        .unwrap()
+        .insert_expiring("old", "stale", vec![1.0, 0.0], None, past())
+        .unwrap();
+    let handle = VectorIndex::spawn_eviction(&index, Duration::from_secs(10));
+
+    tokio::time::sleep(Duration::from_secs(11)).await;
+    assert!(index.read().unwrap().is_empty());
+    handle.stop();
+}
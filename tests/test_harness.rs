@@ -0,0 +1,122 @@
+use tokio_stream::StreamExt;
+use voyageai::{
+    test_util::{fixtures, TestServer},
+    traits::llm::Embedder,
+    RerankResponse, VoyageBuilder, VoyageError,
+};
+
+#[tokio::test]
+async fn test_embeddings_fixtures() {
+    let server = TestServer::start()
+        .await
+        .with_embeddings_fixture(fixtures::EMBEDDINGS_BATCH)
+        .await;
+    let client = server.client();
+
+    let texts = vec!["first".to_string(), "second".to_string()];
+    let embeddings = client.embed_batch(&texts).await.unwrap();
+    assert_eq!(embeddings.len(), 2);
+    assert!(embeddings.iter().all(|embedding| embedding.len() == 1024));
+
+    let requests = server.received_requests().await;
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].url.path(), "/v1/embeddings");
+    assert_eq!(
+        requests[0].headers["authorization"],
+        format!("Bearer {}", voyageai::test_util::TEST_API_KEY)
+    );
+}
+
+#[tokio::test]
+async fn test_rerank_fixture() {
+    let server = TestServer::start()
+        .await
+        .with_rerank_fixture(fixtures::RERANK)
+        .await;
+
+    let best = server
+        .client()
+        .most_similar_document("capital of France", vec!["Berlin".into(), "Paris".into()])
+        .await
+        .unwrap();
+    assert_eq!(best.document, "Paris");
+}
+
+#[tokio::test]
+async fn test_generated_responses_are_deterministic() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+
+    let first = client.embed("the quick brown fox").await.unwrap();
+    let second = client.embed("The quick brown fox").await.unwrap();
+    let other = client.embed("a slow green turtle").await.unwrap();
+    assert_eq!(first, second);
+    assert!(voyageai::cosine_similarity(&first, &second) > 0.99);
+    assert!(voyageai::cosine_similarity(&first, &other) < 0.5);
+}
+
+#[tokio::test]
+async fn test_generated_rerank_honors_top_k() {
+    let server = TestServer::start().await.with_rerank().await;
+    let documents = vec![
+        "Berlin is in Germany".to_string(),
+        "Paris is the capital of France".to_string(),
+        "Madrid is in Spain".to_string(),
+    ];
+
+    let ranked: Vec<_> = server
+        .client()
+        .find_similar_documents("capital of France", documents)
+        .collect()
+        .await;
+    assert_eq!(ranked.len(), 3);
+    assert_eq!(ranked[0].document, "Paris is the capital of France");
+
+    let request = server
+        .client()
+        .rerank_request()
+        .query("capital of France")
+        .add_documents(["Berlin", "Paris", "France"])
+        .top_k(1)
+        .build()
+        .unwrap();
+    let response: RerankResponse = reqwest::Client::new()
+        .post(format!("{}/v1/rerank", server.uri()))
+        .json(&request)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(response.data.len(), 1);
+    assert_eq!(response.data[0].index, 2);
+}
+
+#[tokio::test]
+async fn test_error_response() {
+    let server = TestServer::start()
+        .await
+        .with_error(
+            "embeddings",
+            401,
+            r#"{"detail": "Provided API key is invalid."}"#,
+        )
+        .await;
+
+    let error = server.client().embed("hello").await.unwrap_err();
+    assert!(matches!(error, VoyageError::Unauthorized));
+}
+
+#[tokio::test]
+async fn test_builder_base_url() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = VoyageBuilder::new()
+        .with_api_key("key")
+        .with_base_url(server.uri())
+        .build()
+        .unwrap();
+
+    assert_eq!(client.config.config.base_url, server.uri());
+    assert_eq!(client.embed("hello").await.unwrap().len(), 1024);
+}
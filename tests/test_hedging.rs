@@ -0,0 +1,131 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use voyageai::{
+    client::{
+        rerank_client::{DefaultRerankClient, RerankRequestBuilder},
+        HedgingPolicy, RateLimit, RateLimitEndpoint, RateLimiter,
+    },
+    models::{request_id::IDEMPOTENCY_KEY_HEADER, rerank::RerankRequest},
+};
+
+mod common;
+
+use common::{Reply, ScriptedTransport};
+
+/// Answers each request after the next of `delays`, in milliseconds, then at
+/// once.
+fn with_delays(delays: &[u64]) -> ScriptedTransport {
+    ScriptedTransport::replies(
+        delays
+            .iter()
+            .map(|&ms| Reply::ok().after(Duration::from_millis(ms))),
+    )
+}
+
+fn request() -> RerankRequest {
+    RerankRequestBuilder::new()
+        .query("pets")
+        .add_documents(vec!["cats".to_string(), "rust".to_string()])
+        .build()
+        .unwrap()
+}
+
+fn client(
+    transport: &ScriptedTransport,
+    policy: &HedgingPolicy,
+    rate_limiter: RateLimiter,
+) -> DefaultRerankClient {
+    let config = transport
+        .config()
+        .with_hedging(RateLimitEndpoint::Reranking, policy.clone());
+    DefaultRerankClient::new(config, Arc::new(rate_limiter))
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_hedge_answers_a_slow_request() {
+    let policy = HedgingPolicy::new(0.95).with_initial_delay(Duration::from_millis(100));
+    let transport = with_delays(&[10_000, 20]);
+    let client = client(&transport, &policy, RateLimiter::new());
+
+    let started = tokio::time::Instant::now();
+    let response = client.rerank(request()).await.unwrap();
+    assert_eq!(response.data.len(), 2);
+    assert_eq!(started.elapsed(), Duration::from_millis(120));
+    assert_eq!(transport.sent(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_fast_requests_are_not_hedged() {
+    let policy = HedgingPolicy::new(0.95).with_initial_delay(Duration::from_millis(100));
+    let transport = with_delays(&[50, 50, 50]);
+    let client = client(&transport, &policy, RateLimiter::new());
+
+    for _ in 0..3 {
+        client.rerank(request()).await.unwrap();
+    }
+    assert_eq!(transport.sent(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_delay_follows_the_latency_percentile() {
+    let policy = HedgingPolicy::new(0.5)
+        .with_initial_delay(Duration::from_secs(5))
+        .with_min_samples(3);
+    let client = client(&with_delays(&[100, 300, 200]), &policy, RateLimiter::new());
+    assert_eq!(policy.delay(), Duration::from_secs(5));
+    for _ in 0..3 {
+        client.rerank(request()).await.unwrap();
+    }
+    assert_eq!(policy.delay(), Duration::from_millis(200));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_hedges_need_room_in_the_rate_limiter() {
+    let policy = HedgingPolicy::new(0.95).with_initial_delay(Duration::from_millis(100));
+    let transport = with_delays(&[1_000, 20]);
+    // Room for a single request
+    let rate_limiter = RateLimiter::new().with_limit(
+        RateLimitEndpoint::Reranking,
+        RateLimit::new(1, 1_000_000).with_burst(1, 1_000_000),
+    );
+    let client = client(&transport, &policy, rate_limiter);
+
+    let started = tokio::time::Instant::now();
+    client.rerank(request()).await.unwrap();
+    assert_eq!(started.elapsed(), Duration::from_millis(1_000));
+    assert_eq!(transport.sent(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_winning_hedge_records_the_whole_request() {
+    let policy = HedgingPolicy::new(0.5)
+        .with_initial_delay(Duration::from_millis(100))
+        .with_min_samples(1);
+    let transport = with_delays(&[10_000, 20]);
+    let client = client(&transport, &policy, RateLimiter::new());
+
+    client.rerank(request()).await.unwrap();
+    // The caller waited for the delay and the hedge, not the hedge alone
+    assert_eq!(policy.delay(), Duration::from_millis(120));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_hedges_get_their_own_idempotency_key() {
+    let policy = HedgingPolicy::new(0.95).with_initial_delay(Duration::from_millis(100));
+    let transport = with_delays(&[10_000, 20]);
+    let config = transport
+        .config()
+        .with_idempotency_keys(true)
+        .with_hedging(RateLimitEndpoint::Reranking, policy);
+    let client = DefaultRerankClient::new(config, Arc::new(RateLimiter::new()));
+
+    client.rerank(request()).await.unwrap();
+    let keys: Vec<_> = transport
+        .requests()
+        .iter()
+        .map(|request| request.headers[IDEMPOTENCY_KEY_HEADER].clone())
+        .collect();
+    assert_eq!(keys.len(), 2);
+    assert_ne!(keys[0], keys[1]);
+}
@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+
+use voyageai::{
+    index::{CollectionConfig, HnswConfig, VectorStore},
+    models::{Metadata, MetadataFilter, MetadataValue},
+    VectorIndex,
+};
+
+/// Deterministic pseudo-random vectors (64-bit LCG).
+fn random_vectors(count: usize, dimension: usize, seed: u64) -> Vec<Vec<f32>> {
+    let mut state = seed;
+    (0..count)
+        .map(|_| {
+            (0..dimension)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    ((state >> 33) as f32 / (1u64 << 31) as f32) - 0.5
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn build_index(vectors: &[Vec<f32>]) -> VectorIndex {
+    let mut index = VectorIndex::new();
+    for (i, vector) in vectors.iter().enumerate() {
+        index
+            .insert(
+                format!("doc-{i}"),
+                format!("text {i}"),
+                vector.clone(),
+                None,
+            )
+            .unwrap();
+    }
+    index
+}
+
+fn top_ids(index: &VectorIndex, query: &[f32], top_k: usize) -> HashSet<String> {
+    index
+        .search(query, top_k, None)
+        .unwrap()
+        .into_iter()
+        .map(|m| m.record.id.clone())
+        .collect()
+}
+
+fn recall(exact: &VectorIndex, approximate: &VectorIndex, queries: &[Vec<f32>]) -> f32 {
+    let mut found = 0;
+    for query in queries {
+        let expected = top_ids(exact, query, 10);
+        found += top_ids(approximate, query, 10)
+            .intersection(&expected)
+            .count();
+    }
+    found as f32 / (queries.len() * 10) as f32
+}
+
+#[test]
+fn test_hnsw_recall_matches_brute_force() {
+    let vectors = random_vectors(1000, 16, 7);
+    let queries = random_vectors(50, 16, 99);
+    let exact = build_index(&vectors);
+    let approximate = build_index(&vectors).with_hnsw(HnswConfig::default());
+
+    assert_eq!(approximate.hnsw_config(), Some(&HnswConfig::default()));
+    assert!(recall(&exact, &approximate, &queries) >= 0.9);
+}
+
+#[test]
+fn test_hnsw_scores_are_sorted_cosine_similarities() {
+    let vectors = random_vectors(300, 8, 3);
+    let mut index = VectorIndex::new().with_hnsw(HnswConfig::default().with_m(8));
+    for (i, vector) in vectors.iter().enumerate() {
+        index
+            .insert(i.to_string(), "", vector.clone(), None)
+            .unwrap();
+    }
+
+    let matches = index.search(&vectors[42], 5, None).unwrap();
+
+    assert_eq!(matches.len(), 5);
+    assert_eq!(matches[0].record.id, "42");
+    assert!((matches[0].score - 1.0).abs() < 1e-5);
+    assert!(matches
+        .windows(2)
+        .all(|pair| pair[0].score >= pair[1].score));
+}
+
+#[test]
+fn test_hnsw_stays_consistent_after_removals_and_replacements() {
+    let vectors = random_vectors(500, 12, 11);
+    let queries = random_vectors(30, 12, 5);
+    let mut approximate = build_index(&vectors).with_hnsw(HnswConfig::default());
+    let mut exact = build_index(&vectors);
+
+    for i in (0..500).step_by(3) {
+        let id = format!("doc-{i}");
+        assert!(approximate.remove(&id).is_some());
+        exact.remove(&id);
+    }
+    let replacements = random_vectors(50, 12, 23);
+    for (i, vector) in (1..500).step_by(10).zip(replacements) {
+        let id = format!("doc-{i}");
+        approximate
+            .insert(id.clone(), "new", vector.clone(), None)
+            .unwrap();
+        exact.insert(id, "new", vector, None).unwrap();
+    }
+
+    assert_eq!(approximate.len(), exact.len());
+    for query in &queries {
+        for m in approximate.search(query, 10, None).unwrap() {
+            assert!(exact.contains(&m.record.id));
+        }
+    }
+    assert!(recall(&exact, &approximate, &queries) >= 0.85);
+}
+
+#[test]
+fn test_hnsw_handles_removing_every_document() {
+    let vectors = random_vectors(20, 4, 1);
+    let mut index = build_index(&vectors).with_hnsw(HnswConfig::default());
+
+    for i in 0..20 {
+        index.remove(&format!("doc-{i}"));
+    }
+
+    assert!(index.search(&vectors[0], 3, None).unwrap().is_empty());
+    index.insert("again", "", vectors[0].clone(), None).unwrap();
+    assert_eq!(
+        index.search(&vectors[0], 3, None).unwrap()[0].record.id,
+        "again"
+    );
+}
+
+#[test]
+fn test_filtered_search_is_exact() {
+    let vectors = random_vectors(200, 8, 17);
+    let mut index = VectorIndex::new().with_hnsw(HnswConfig::default().with_ef_search(1));
+    for (i, vector) in vectors.iter().enumerate() {
+        let metadata = Metadata::from([("even".to_string(), MetadataValue::from(i % 2 == 0))]);
+        index
+            .insert(i.to_string(), "", vector.clone(), Some(metadata))
+            .unwrap();
+    }
+    let filter = MetadataFilter::eq("even", true);
+
+    let matches = index.search(&vectors[10], 100, Some(&filter)).unwrap();
+
+    assert_eq!(matches.len(), 100);
+    assert_eq!(matches[0].record.id, "10");
+}
+
+#[test]
+fn test_store_collection_with_hnsw() {
+    let store = VectorStore::new();
+    store
+        .create_collection(
+            "ann",
+            CollectionConfig::persistent().with_hnsw(HnswConfig::default()),
+        )
+        .unwrap();
+    for (i, vector) in random_vectors(100, 8, 2).into_iter().enumerate() {
+        store
+            .insert("ann", i.to_string(), "", vector, None)
+            .unwrap();
+    }
+
+    let query = random_vectors(100, 8, 2).remove(7);
+    let matches = store.search("ann", &query, 3, None).unwrap();
+
+    assert_eq!(matches.len(), 3);
+    assert_eq!(matches[0].record.id, "7");
+}
@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use voyageai::{
+    client::{embeddings_client::Client as EmbeddingsClient, RetryPolicy},
+    models::{
+        embeddings::{EmbeddingModel, EmbeddingsInput, EmbeddingsRequest},
+        request_id::IDEMPOTENCY_KEY_HEADER,
+        RequestId,
+    },
+    test_util::{
+        wiremock::{
+            matchers::{method, path},
+            Mock, Request, ResponseTemplate,
+        },
+        TestServer,
+    },
+    VoyageError,
+};
+
+fn request() -> EmbeddingsRequest {
+    EmbeddingsRequest {
+        input: EmbeddingsInput::Single("hello".to_string()),
+        model: EmbeddingModel::Voyage3Large,
+        input_type: None,
+        truncation: None,
+        encoding_format: None,
+    }
+}
+
+fn keys(requests: &[Request]) -> Vec<Option<String>> {
+    requests
+        .iter()
+        .map(|request| {
+            request
+                .headers
+                .get(IDEMPOTENCY_KEY_HEADER)
+                .map(|value| value.to_str().unwrap().to_string())
+        })
+        .collect()
+}
+
+/// A server rejecting the first embeddings request with a 429.
+async fn server_rejecting_once() -> TestServer {
+    let server = TestServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .up_to_n_times(1)
+        .mount(server.mock_server())
+        .await;
+    server.with_embeddings().await
+}
+
+#[test]
+fn test_request_ids_are_unique_uuids() {
+    let ids: HashSet<RequestId> = (0..1000).map(|_| RequestId::new()).collect();
+    assert_eq!(ids.len(), 1000);
+    let id = RequestId::new();
+    let groups: Vec<usize> = id.as_str().split('-').map(str::len).collect();
+    assert_eq!(groups, [8, 4, 4, 4, 12]);
+    assert_eq!(id.as_str().as_bytes()[14], b'4');
+    assert!(matches!(
+        id.as_str().as_bytes()[19],
+        b'8' | b'9' | b'a' | b'b'
+    ));
+    assert_eq!(RequestId::from("job-42").to_string(), "job-42");
+}
+
+#[tokio::test]
+async fn test_retries_reuse_the_idempotency_key() {
+    let server = server_rejecting_once().await;
+    let config = server
+        .config()
+        .with_retry_policy(RetryPolicy::new(2, Duration::from_millis(1)))
+        .with_idempotency_keys(true);
+    let client = EmbeddingsClient::new(config);
+
+    let response = client.create_embedding(&request()).await.unwrap();
+    let second = client.create_embedding(&request()).await.unwrap();
+
+    let keys = keys(&server.received_requests().await);
+    assert_eq!(keys.len(), 3);
+    // The rejected attempt and its retry share a key; the next call has its own
+    assert_eq!(keys[0], keys[1]);
+    assert_ne!(keys[1], keys[2]);
+    assert_eq!(
+        response
+            .headers
+            .idempotency_key
+            .as_ref()
+            .map(RequestId::as_str),
+        keys[1].as_deref()
+    );
+    assert_eq!(
+        second
+            .headers
+            .idempotency_key
+            .as_ref()
+            .map(RequestId::as_str),
+        keys[2].as_deref()
+    );
+}
+
+#[tokio::test]
+async fn test_keys_are_off_by_default_and_replayable() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = EmbeddingsClient::new(server.config());
+
+    let response = client.create_embedding(&request()).await.unwrap();
+    assert_eq!(response.headers.idempotency_key, None);
+
+    let id = RequestId::from("ingest-job-7/batch-3");
+    let replayed = client
+        .create_embedding_with_id(&request(), id.clone())
+        .await
+        .unwrap();
+    assert_eq!(replayed.headers.idempotency_key, Some(id));
+    assert_eq!(
+        keys(&server.received_requests().await),
+        [None, Some("ingest-job-7/batch-3".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn test_errors_carry_the_idempotency_key() {
+    let server = TestServer::start()
+        .await
+        .with_error("embeddings", 502, "bad gateway")
+        .await;
+    let client = EmbeddingsClient::new(server.config().with_idempotency_keys(true));
+
+    let error = client.create_embedding(&request()).await.unwrap_err();
+    assert!(matches!(error, VoyageError::ApiError(..)));
+    let sent = keys(&server.received_requests().await).remove(0);
+    assert_eq!(
+        error
+            .response_metadata()
+            .and_then(|metadata| metadata.idempotency_key.as_ref())
+            .map(RequestId::as_str),
+        sent.as_deref()
+    );
+    assert!(sent.is_some());
+}
@@ -0,0 +1,113 @@
+use voyageai::{
+    analysis::{KMeans, MiniBatchKMeans},
+    VoyageError,
+};
+
+/// Three well separated blobs of 20 points each, interleaved.
+fn blobs() -> Vec<Vec<f32>> {
+    let centers = [[0.0, 0.0, 5.0], [5.0, 0.0, 0.0], [0.0, 5.0, 0.0]];
+    (0..60)
+        .map(|i| {
+            let center = centers[i % 3];
+            let jitter = ((i * 7919) % 100) as f32 / 100.0 - 0.5;
+            vec![
+                center[0] + jitter,
+                center[1] - jitter,
+                center[2] + jitter * 0.5,
+            ]
+        })
+        .collect()
+}
+
+fn assert_recovers_blobs(assignments: &[usize]) {
+    for i in 0..60 {
+        assert_eq!(assignments[i], assignments[i % 3], "point {i}");
+    }
+    assert_ne!(assignments[0], assignments[1]);
+    assert_ne!(assignments[1], assignments[2]);
+    assert_ne!(assignments[0], assignments[2]);
+}
+
+#[test]
+fn test_kmeans_recovers_clusters() -> Result<(), VoyageError> {
+    let embeddings = blobs();
+    let clustering = KMeans::new(3).fit(&embeddings)?;
+
+    assert_recovers_blobs(&clustering.assignments);
+    assert_eq!(clustering.k(), 3);
+    assert_eq!(clustering.sizes(), vec![20, 20, 20]);
+    assert!(clustering.iterations < 100);
+    assert_eq!(
+        clustering.predict(&[4.9, 0.1, 0.0]),
+        clustering.assignments[1]
+    );
+    let centroid = &clustering.centroids[clustering.assignments[0]];
+    assert!((centroid[2] - 5.0).abs() < 0.5);
+
+    // Deterministic for a given seed
+    assert_eq!(KMeans::new(3).fit(&embeddings)?, clustering);
+    Ok(())
+}
+
+#[test]
+fn test_mini_batch_kmeans_recovers_clusters() -> Result<(), VoyageError> {
+    let embeddings = blobs();
+    let clustering = MiniBatchKMeans::new(3)
+        .with_batch_size(16)
+        .with_max_iterations(50)
+        .fit(&embeddings)?;
+
+    assert_recovers_blobs(&clustering.assignments);
+    assert_eq!(clustering.iterations, 50);
+    Ok(())
+}
+
+#[test]
+fn test_representatives_are_nearest_members() -> Result<(), VoyageError> {
+    let embeddings = vec![
+        vec![0.0, 0.0],
+        vec![0.2, 0.0],
+        vec![0.1, 0.0],
+        vec![10.0, 10.0],
+    ];
+    let clustering = KMeans::new(2).fit(&embeddings)?;
+    let representatives = clustering.representatives(&embeddings, 2);
+
+    let near_origin = clustering.assignments[0];
+    assert_eq!(representatives[near_origin], vec![2, 0]);
+    assert_eq!(representatives[clustering.assignments[3]], vec![3]);
+    assert_eq!(clustering.members(near_origin), vec![0, 1, 2]);
+    Ok(())
+}
+
+#[test]
+fn test_invalid_input_is_rejected() {
+    let embeddings = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+
+    assert!(matches!(
+        KMeans::new(0).fit(&embeddings),
+        Err(VoyageError::AnalysisError(_))
+    ));
+    assert!(matches!(
+        KMeans::new(3).fit(&embeddings),
+        Err(VoyageError::AnalysisError(_))
+    ));
+    assert!(matches!(
+        MiniBatchKMeans::new(1).fit(&[]),
+        Err(VoyageError::AnalysisError(_))
+    ));
+    assert!(matches!(
+        KMeans::new(1).fit(&[vec![0.0, 1.0], vec![1.0]]),
+        Err(VoyageError::EmbeddingDimensionMismatch { index: 1, .. })
+    ));
+}
+
+#[test]
+fn test_duplicate_points_fill_every_cluster() -> Result<(), VoyageError> {
+    let embeddings = vec![vec![1.0, 1.0]; 4];
+    let clustering = KMeans::new(2).fit(&embeddings)?;
+
+    assert_eq!(clustering.inertia, 0.0);
+    assert_eq!(clustering.assignments.len(), 4);
+    Ok(())
+}
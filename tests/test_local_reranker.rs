@@ -0,0 +1,133 @@
+use serde_json::{json, Value};
+use tokio_stream::StreamExt;
+use voyageai::{
+    client::{rerank_client::RerankStreamItem, LocalReranker, RerankClient},
+    retrieval::RetrievalPipeline,
+    traits::llm::Reranker,
+    VoyageAiClient, VoyageConfig,
+};
+
+/// Serves embeddings that count mentions of "paris" and "berlin", and fails
+/// every rerank request so any use of the endpoint shows up.
+async fn mock_server() -> mockito::ServerGuard {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("POST", "/v1/embeddings")
+        .with_header("content-type", "application/json")
+        .with_body_from_request(|request| {
+            let body: Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+            let data: Vec<Value> = body["input"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .map(|(index, text)| {
+                    let text = text.as_str().unwrap().to_lowercase();
+                    let mut embedding = vec![0.0f32; 1024];
+                    embedding[0] = text.matches("paris").count() as f32 + 0.01;
+                    embedding[1] = text.matches("berlin").count() as f32 + 0.01;
+                    json!({"object": "embedding", "embedding": embedding, "index": index})
+                })
+                .collect();
+            json!({"object": "list", "data": data, "model": "voyage-3-large", "usage": {"total_tokens": 5}})
+                .to_string()
+                .into()
+        })
+        .create_async()
+        .await;
+    server
+        .mock("POST", "/v1/rerank")
+        .with_status(500)
+        .expect(0)
+        .create_async()
+        .await;
+    server
+}
+
+fn client(server: &mockito::ServerGuard) -> VoyageAiClient {
+    VoyageAiClient::new_with_config(
+        VoyageConfig::new("key".to_string()).with_base_url(server.url()),
+    )
+}
+
+fn documents() -> Vec<String> {
+    vec![
+        "Berlin is the capital of Germany.".to_string(),
+        "Paris is the capital of France.".to_string(),
+        "Paris, Paris, Paris.".to_string(),
+    ]
+}
+
+#[tokio::test]
+async fn test_rank_by_cosine_similarity() {
+    let server = mock_server().await;
+    let reranker = LocalReranker::new(&client(&server));
+
+    let (ranked, usage) = reranker.rank("Paris", documents()).await.unwrap();
+
+    let order: Vec<&str> = ranked.iter().map(|d| d.document.as_str()).collect();
+    assert_eq!(order[2], "Berlin is the capital of Germany.");
+    assert!(order[..2].contains(&"Paris is the capital of France."));
+    assert_eq!(
+        ranked.iter().map(|d| d.rank).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+    assert!(ranked[0].similarity >= ranked[1].similarity);
+    assert!(ranked[1].similarity > ranked[2].similarity);
+    // One request for the query and one for the documents
+    assert_eq!(usage.total_tokens, 10);
+}
+
+#[tokio::test]
+async fn test_reranker_trait_and_usage_stream() {
+    let server = mock_server().await;
+    let reranker = LocalReranker::new(&client(&server));
+
+    let streamed: Vec<_> = reranker.rerank("Berlin", documents()).collect().await;
+    assert_eq!(streamed.len(), 3);
+    assert_eq!(streamed[0].document, "Berlin is the capital of Germany.");
+
+    let items: Vec<_> = reranker
+        .find_similar_documents_with_usage("Berlin", documents())
+        .collect()
+        .await;
+    assert!(matches!(items.last(), Some(RerankStreamItem::Usage(u)) if u.total_tokens == 10));
+
+    let best = reranker
+        .most_similar_document("Berlin", documents())
+        .await
+        .unwrap();
+    assert_eq!(best.document, "Berlin is the capital of Germany.");
+}
+
+#[tokio::test]
+async fn test_invalid_input() {
+    let server = mock_server().await;
+    let reranker = LocalReranker::new(&client(&server));
+
+    assert!(reranker.rank(" ", documents()).await.is_err());
+    assert!(reranker.rank("Paris", Vec::new()).await.is_err());
+    let streamed: Vec<_> = reranker.rerank("Paris", Vec::new()).collect().await;
+    assert!(streamed.is_empty());
+}
+
+#[tokio::test]
+async fn test_pipeline_with_local_reranker() {
+    let server = mock_server().await;
+    let client = client(&server);
+    let mut pipeline = RetrievalPipeline::builder(&client)
+        .rerank_client(LocalReranker::new(&client))
+        .top_k(1)
+        .build();
+    pipeline
+        .add_documents([
+            ("de", "Berlin is the capital of Germany."),
+            ("fr", "Paris is the capital of France."),
+        ])
+        .await
+        .unwrap();
+
+    let chunks = pipeline.query("Paris").await.unwrap();
+    assert_eq!(chunks[0].document_id, "fr");
+    assert!(chunks[0].reranked);
+}
@@ -0,0 +1,59 @@
+use voyageai::logging::{redact, LogPolicy};
+use voyageai::VoyageConfig;
+
+#[test]
+fn test_redacts_bearer_tokens() {
+    let redacted = redact("authorization: Bearer abc.def-123 sent; bearer xyz");
+
+    assert_eq!(
+        redacted,
+        "authorization: Bearer [REDACTED] sent; bearer [REDACTED]"
+    );
+}
+
+#[test]
+fn test_redacts_api_keys() {
+    let redacted = redact(r#"{"key": "pa-AbCdEfGhIjKlMnOpQrSt", "note": "pa-short"}"#);
+
+    assert_eq!(redacted, r#"{"key": "[REDACTED]", "note": "pa-short"}"#);
+}
+
+#[test]
+fn test_text_without_secrets_is_borrowed() {
+    assert!(matches!(
+        redact("nothing to see, café"),
+        std::borrow::Cow::Borrowed(_)
+    ));
+}
+
+#[test]
+fn test_payloads_are_omitted_by_default() {
+    let body = r#"{"documents": ["confidential"]}"#;
+
+    let logged = LogPolicy::default().payload(body);
+
+    assert_eq!(logged, format!("<{} bytes omitted>", body.len()));
+}
+
+#[test]
+fn test_payloads_are_redacted_and_truncated() {
+    let policy = LogPolicy::default()
+        .log_payloads(true)
+        .max_payload_chars(30);
+
+    assert_eq!(policy.payload("Bearer secret-token"), "Bearer [REDACTED]");
+    assert_eq!(
+        policy.payload(&"x".repeat(40)),
+        format!("{}... <10 bytes truncated>", "x".repeat(30))
+    );
+}
+
+#[test]
+fn test_config_debug_hides_api_key() {
+    let config = VoyageConfig::new("pa-AbCdEfGhIjKlMnOpQrSt".to_string()).log_payloads(true);
+
+    let debug = format!("{config:?}");
+
+    assert!(!debug.contains("AbCdEf"));
+    assert!(config.log_policy.logs_payloads());
+}
@@ -0,0 +1,103 @@
+use serde_json::Value;
+use voyageai::{client::LongTextStrategy, test_util::TestServer};
+
+/// About 100k tokens: four context-sized chunks of `voyage-3-large`.
+fn long_text() -> String {
+    let words = ["cats", "purr", "dogs", "bark", "rust", "compiles"];
+    (0..80_000)
+        .map(|i| words[i % words.len()])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn norm(vector: &[f32]) -> f32 {
+    vector.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
+
+#[tokio::test]
+async fn test_short_texts_are_embedded_as_they_are() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+
+    let embedding = client
+        .embed_long("cats purr", LongTextStrategy::MaxPool)
+        .await
+        .unwrap();
+    assert!(!embedding.is_empty());
+
+    let requests = server.received_requests().await;
+    let body: Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["input"], "cats purr");
+    assert_eq!(body["input_type"], "document");
+}
+
+#[tokio::test]
+async fn test_long_texts_are_chunked_and_pooled() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let text = long_text();
+
+    let chunks = client
+        .embeddings_client()
+        .embed_long_chunks(&text)
+        .await
+        .unwrap();
+    assert!(chunks.len() > 1);
+    for chunk in &chunks {
+        assert_eq!(&text[chunk.chunk.range()], chunk.chunk.text);
+        assert!(chunk.chunk.text.len() / 4 <= 32_000);
+    }
+    let requests = server.received_requests().await;
+    // Every chunk is sent in one request
+    assert_eq!(requests.len(), 1);
+
+    let mean = client
+        .embed_long(&text, LongTextStrategy::MeanPool)
+        .await
+        .unwrap();
+    let max = client
+        .embed_long(&text, LongTextStrategy::MaxPool)
+        .await
+        .unwrap();
+    assert!((norm(&mean) - 1.0).abs() < 1e-4);
+    assert!((norm(&max) - 1.0).abs() < 1e-4);
+    assert_ne!(mean, max);
+
+    // The mean of the chunk embeddings weighted by chunk length, normalized
+    let mut expected = vec![0.0f32; mean.len()];
+    for chunk in &chunks {
+        let weight = chunk.chunk.text.len() as f32;
+        for (sum, x) in expected.iter_mut().zip(&chunk.embedding) {
+            *sum += x * weight;
+        }
+    }
+    let expected_norm = norm(&expected);
+    for (a, b) in mean.iter().zip(&expected) {
+        assert!((a - b / expected_norm).abs() < 1e-5);
+    }
+}
+
+#[tokio::test]
+async fn test_truncate_and_first_chunk() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let text = long_text();
+
+    client
+        .embed_long(&text, LongTextStrategy::Truncate)
+        .await
+        .unwrap();
+    client
+        .embed_long(&text, LongTextStrategy::FirstChunk)
+        .await
+        .unwrap();
+
+    let requests = server.received_requests().await;
+    let truncated: Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(truncated["truncation"], true);
+    assert_eq!(truncated["input"].as_str().unwrap().len(), text.len());
+    let first: Value = serde_json::from_slice(&requests[1].body).unwrap();
+    let first = first["input"].as_str().unwrap();
+    assert!(text.starts_with(first));
+    assert!(first.len() < text.len());
+}
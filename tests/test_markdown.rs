@@ -0,0 +1,158 @@
+use voyageai::{
+    code::{extract_code_blocks, CodeBlock, CodeEmbedding},
+    models::MetadataValue,
+    test_util::TestServer,
+    utils::{chunk_markdown, MarkdownChunk},
+    Chunker, MarkdownChunker,
+};
+
+const GUIDE: &str = "\
+Intro before any heading.
+
+# Guide
+
+Welcome to the guide.
+
+## Setup
+
+Install the crate.
+
+```rust
+# not a heading
+fn main() {}
+```
+
+## Usage
+
+Call the client.
+
+# Reference ##
+
+See the docs.
+";
+
+#[test]
+fn test_chunks_follow_heading_structure() {
+    let chunks = chunk_markdown(GUIDE, 1000);
+
+    let outline: Vec<(String, &str)> = chunks
+        .iter()
+        .map(|chunk| (chunk.breadcrumb(), chunk.text.as_str()))
+        .collect();
+    assert_eq!(outline[0], (String::new(), "Intro before any heading."));
+    assert_eq!(outline[1], ("Guide".to_string(), "Welcome to the guide."));
+    assert_eq!(outline[2].0, "Guide > Setup");
+    assert_eq!(
+        outline[3],
+        ("Guide > Usage".to_string(), "Call the client.")
+    );
+    // Closing hashes are not part of the title, and a level 1 heading
+    // leaves the sections of the previous one
+    assert_eq!(outline[4], ("Reference".to_string(), "See the docs."));
+    assert_eq!(chunks.len(), 5);
+}
+
+#[test]
+fn test_code_blocks_stay_intact() {
+    let chunks = chunk_markdown(GUIDE, 20);
+
+    let setup: Vec<&MarkdownChunk> = chunks
+        .iter()
+        .filter(|chunk| chunk.breadcrumb() == "Guide > Setup")
+        .collect();
+    assert_eq!(setup.len(), 2);
+    assert_eq!(setup[0].text, "Install the crate.");
+    assert_eq!(setup[1].text, "```rust\n# not a heading\nfn main() {}\n```");
+    assert_eq!(setup[1].code_languages, vec!["rust"]);
+}
+
+#[test]
+fn test_long_paragraphs_are_split() {
+    let markdown = format!("# Title\n\n{}", "a".repeat(25));
+
+    let chunks = chunk_markdown(&markdown, 10);
+
+    assert_eq!(chunks.len(), 3);
+    assert!(chunks.iter().all(|chunk| chunk.headings == ["Title"]));
+}
+
+#[test]
+fn test_metadata_and_chunker() {
+    let chunks = chunk_markdown(GUIDE, 1000);
+    let metadata = chunks[2].metadata();
+
+    assert_eq!(metadata["breadcrumb"], MetadataValue::from("Guide > Setup"));
+    assert_eq!(metadata["heading"], MetadataValue::from("Setup"));
+    assert_eq!(metadata["heading_level"], MetadataValue::Number(2.0));
+    assert_eq!(metadata["code_languages"], MetadataValue::from("rust"));
+    assert!(!chunks[0].metadata().contains_key("code_languages"));
+
+    let texts = MarkdownChunker::new(1000).chunk(GUIDE);
+    assert_eq!(texts[1], "Guide\n\nWelcome to the guide.");
+    assert_eq!(texts[0], "Intro before any heading.");
+}
+
+#[tokio::test]
+async fn test_embed_markdown_document() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+
+    let embeddings = client
+        .embeddings_client()
+        .embed_markdown_document(GUIDE, 1000)
+        .await
+        .unwrap();
+
+    assert_eq!(embeddings.len(), 5);
+    assert_eq!(embeddings[2].chunk.breadcrumb(), "Guide > Setup");
+    assert_eq!(embeddings[2].embedding.len(), 1024);
+    let requests = server.received_requests().await;
+    assert_eq!(requests.len(), 1);
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["input_type"], "document");
+    assert_eq!(body["input"][1], "Guide\n\nWelcome to the guide.");
+}
+
+#[test]
+fn test_extract_code_blocks_follows_commonmark() {
+    let markdown = "\
+Run it:
+
+```rust,no_run
+fn main() {}
+```
+
+- In a list:
+
+  ~~~toml
+  [dependencies]
+  ~~~
+
+Then:
+
+    indented code
+
+```
+";
+    let blocks = extract_code_blocks(markdown);
+    assert_eq!(
+        blocks,
+        [
+            CodeBlock::new(Some("rust".into()), "fn main() {}\n".into()),
+            CodeBlock::new(Some("toml".into()), "[dependencies]\n".into()),
+            CodeBlock::new(None, "indented code\n".into()),
+            // An unclosed fence runs to the end of the document
+            CodeBlock::new(None, String::new()),
+        ]
+    );
+    // The former paths still resolve to the same items
+    let _: Vec<voyageai::utils::CodeBlock> = voyageai::utils::extract_code_blocks(markdown);
+}
+
+#[test]
+fn test_code_embedding_serde_is_unchanged() {
+    let json = r#"{"text_embedding":[0.5],"ast_embedding":[0.25]}"#;
+    let embedding: CodeEmbedding = serde_json::from_str(json).unwrap();
+    assert_eq!(embedding.ast_embedding, [0.25]);
+    assert_eq!(serde_json::to_string(&embedding).unwrap(), json);
+}
@@ -0,0 +1,156 @@
+use serde_json::{json, Value};
+use voyageai::{mcp::McpServer, test_util::TestServer, VectorIndex, VoyageAiClient};
+
+const DOCUMENTS: [(&str, &str); 3] = [
+    ("cats", "cats purr and sleep all day"),
+    ("dogs", "dogs bark at the mail carrier"),
+    ("rust", "rust compiles to fast native code"),
+];
+
+async fn index(client: &VoyageAiClient) -> VectorIndex {
+    let texts: Vec<String> = DOCUMENTS.iter().map(|(_, text)| text.to_string()).collect();
+    let embeddings = client
+        .config
+        .embeddings_client
+        .embed_documents(&texts)
+        .await
+        .unwrap();
+    let mut index = VectorIndex::new();
+    for ((id, text), embedding) in DOCUMENTS.iter().zip(embeddings) {
+        index.insert(*id, *text, embedding, None).unwrap();
+    }
+    index
+}
+
+async fn request(server: &McpServer, method: &str, params: Value) -> Value {
+    let message = json!({ "jsonrpc": "2.0", "id": 7, "method": method, "params": params });
+    let response = server.handle(&message.to_string()).await.unwrap();
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["id"], 7);
+    response
+}
+
+/// The JSON text content of a successful tool call.
+async fn call(server: &McpServer, tool: &str, arguments: Value) -> Value {
+    let response = request(
+        server,
+        "tools/call",
+        json!({ "name": tool, "arguments": arguments }),
+    )
+    .await;
+    assert_eq!(response["result"]["isError"], false, "{response}");
+    let text = response["result"]["content"][0]["text"].as_str().unwrap();
+    serde_json::from_str(text).unwrap()
+}
+
+#[tokio::test]
+async fn test_session() {
+    let api = TestServer::start()
+        .await
+        .with_embeddings()
+        .await
+        .with_rerank()
+        .await;
+    let client = api.client();
+    let server = McpServer::new(&client).with_index(index(&client).await);
+
+    let input = [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "protocolVersion": "2024-11-05", "capabilities": {}, "clientInfo": { "name": "test", "version": "1" } } }),
+        json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+    ]
+    .map(|message| message.to_string())
+    .join("\n");
+    let mut output = Vec::new();
+    server.run(input.as_bytes(), &mut output).await.unwrap();
+    let responses: Vec<Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["result"]["protocolVersion"], "2024-11-05");
+    assert_eq!(responses[0]["result"]["serverInfo"]["name"], "voyageai");
+    let tools: Vec<&str> = responses[1]["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(tools, ["semantic_search", "rerank", "embed"]);
+
+    let hits = call(
+        &server,
+        "semantic_search",
+        json!({ "query": "dogs bark at the mail carrier", "top_k": 2 }),
+    )
+    .await;
+    assert_eq!(hits.as_array().unwrap().len(), 2);
+    assert!(hits[0]["score"].as_f64().unwrap() >= hits[1]["score"].as_f64().unwrap());
+
+    let hits = call(
+        &server,
+        "semantic_search",
+        json!({ "query": "pets", "top_k": 1, "rerank": true }),
+    )
+    .await;
+    assert_eq!(hits[0]["reranked"], true);
+
+    let ranked = call(
+        &server,
+        "rerank",
+        json!({ "query": "pets", "documents": ["cats", "dogs", "rust"], "top_k": 2 }),
+    )
+    .await;
+    let ranked = ranked.as_array().unwrap();
+    assert!(!ranked.is_empty());
+    let index = ranked[0]["index"].as_u64().unwrap() as usize;
+    assert_eq!(ranked[0]["document"], ["cats", "dogs", "rust"][index]);
+
+    let embedded = call(&server, "embed", json!({ "input": ["a", "b"] })).await;
+    assert_eq!(embedded["embeddings"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_errors() {
+    let api = TestServer::start().await.with_embeddings().await;
+    let server = McpServer::new(&api.client());
+
+    // Without an index there is no search tool
+    let tools = request(&server, "tools/list", json!({})).await;
+    assert_eq!(tools["result"]["tools"].as_array().unwrap().len(), 2);
+    let response = request(
+        &server,
+        "tools/call",
+        json!({ "name": "semantic_search", "arguments": { "query": "cats" } }),
+    )
+    .await;
+    assert_eq!(response["error"]["code"], -32602);
+
+    let response = request(
+        &server,
+        "tools/call",
+        json!({ "name": "embed", "arguments": { "text": "cats" } }),
+    )
+    .await;
+    assert_eq!(response["error"]["code"], -32602);
+
+    // Failures of the tool itself are results the agent can read
+    let response = request(
+        &server,
+        "tools/call",
+        json!({ "name": "rerank", "arguments": { "query": "pets", "documents": [] } }),
+    )
+    .await;
+    assert_eq!(response["result"]["isError"], true);
+
+    let response = request(&server, "resources/list", json!({})).await;
+    assert_eq!(response["error"]["code"], -32601);
+
+    let response: Value = serde_json::from_str(&server.handle("{not json").await.unwrap()).unwrap();
+    assert_eq!(response["error"]["code"], -32700);
+    assert!(server
+        .handle(r#"{"jsonrpc": "2.0", "method": "notifications/cancelled"}"#)
+        .await
+        .is_none());
+}
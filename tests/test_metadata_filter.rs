@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use voyageai::{
+    builder::search::SearchRequestBuilder,
+    client::{
+        embeddings_client::Client as EmbeddingsClient, rerank_client::DefaultRerankClient,
+        search_client::SearchClient, RateLimiter,
+    },
+    models::{Metadata, MetadataFilter, MetadataValue},
+    SearchModel, SearchType, VoyageConfig,
+};
+
+fn metadata(tenant: &str, year: i64, public: bool) -> Metadata {
+    Metadata::from([
+        ("tenant".to_string(), MetadataValue::from(tenant)),
+        ("year".to_string(), MetadataValue::from(year)),
+        ("public".to_string(), MetadataValue::from(public)),
+    ])
+}
+
+#[test]
+fn test_equality_and_in_set() {
+    let doc = metadata("acme", 2024, true);
+
+    assert!(MetadataFilter::eq("tenant", "acme").matches(Some(&doc)));
+    assert!(!MetadataFilter::eq("tenant", "globex").matches(Some(&doc)));
+    assert!(MetadataFilter::in_set("tenant", ["globex", "acme"]).matches(Some(&doc)));
+    assert!(!MetadataFilter::in_set("tenant", ["globex"]).matches(Some(&doc)));
+}
+
+#[test]
+fn test_range_bounds_are_inclusive() {
+    let doc = metadata("acme", 2024, true);
+
+    assert!(MetadataFilter::range("year", Some(2024.0), Some(2024.0)).matches(Some(&doc)));
+    assert!(MetadataFilter::range("year", None, Some(2025.0)).matches(Some(&doc)));
+    assert!(!MetadataFilter::range("year", Some(2025.0), None).matches(Some(&doc)));
+    assert!(!MetadataFilter::range("tenant", Some(0.0), None).matches(Some(&doc)));
+}
+
+#[test]
+fn test_boolean_combinators() {
+    let doc = metadata("acme", 2024, true);
+    let filter = MetadataFilter::eq("tenant", "acme")
+        .and(MetadataFilter::eq("public", true))
+        .and(MetadataFilter::range("year", Some(2020.0), None));
+
+    assert!(filter.matches(Some(&doc)));
+    assert!(MetadataFilter::eq("tenant", "globex")
+        .or(MetadataFilter::eq("year", 2024))
+        .matches(Some(&doc)));
+    assert!(MetadataFilter::eq("tenant", "globex").not().matches(Some(&doc)));
+}
+
+#[test]
+fn test_missing_metadata_never_matches_keys() {
+    assert!(!MetadataFilter::eq("tenant", "acme").matches(None));
+    assert!(MetadataFilter::eq("tenant", "acme").not().matches(None));
+}
+
+#[test]
+fn test_filter_round_trips_through_json() -> Result<(), serde_json::Error> {
+    let filter = MetadataFilter::eq("tenant", "acme").and(MetadataFilter::range("year", Some(2020.0), None));
+
+    let json = serde_json::to_string(&filter)?;
+    let parsed: MetadataFilter = serde_json::from_str(&json)?;
+
+    assert_eq!(parsed, filter);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_only_considers_matching_documents() -> Result<(), Box<dyn std::error::Error>> {
+    let config = VoyageConfig::new("test_key".to_string());
+    let client = SearchClient::new(
+        EmbeddingsClient::new(config.clone()),
+        DefaultRerankClient::new(config, Arc::new(RateLimiter::new())),
+    );
+    let request = SearchRequestBuilder::new()
+        .query("quarterly report")
+        .documents([
+            "quarterly report for acme",
+            "quarterly report for globex",
+            "old quarterly report for acme",
+        ])
+        .metadata(vec![
+            metadata("acme", 2024, true),
+            metadata("globex", 2024, true),
+            metadata("acme", 2019, true),
+        ])
+        .filter(MetadataFilter::eq("tenant", "acme").and(MetadataFilter::range(
+            "year",
+            Some(2020.0),
+            None,
+        )))
+        .model(SearchModel::BM25)
+        .search_type(SearchType::BM25)
+        .build()?;
+
+    let results = client.search(&request).await?;
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].index, 0);
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use std::sync::{Arc, Mutex};
+
+use voyageai::{
+    client::{BulkEmbedder, BulkProgress},
+    index::HnswConfig,
+    models::{Metadata, MetadataValue},
+    EmbeddingModel, VectorIndex, VoyageError,
+};
+
+mod common;
+
+use common::{Reply, ScriptedTransport};
+
+/// Embeds each input with the length of the text as first value.
+/// Inputs containing "poison" fail their whole request.
+fn transport() -> ScriptedTransport {
+    ScriptedTransport::new(|request| {
+        if common::inputs(request)
+            .iter()
+            .any(|text| text.contains("poison"))
+        {
+            return Reply::error(400);
+        }
+        Reply::embeddings(|text| {
+            let mut embedding = vec![0.0; EmbeddingModel::VoyageCode3.embedding_dimension()];
+            embedding[0] = text.len() as f32;
+            embedding[1] = 1.0;
+            embedding
+        })
+    })
+}
+
+/// Models of the requests sent through `transport`.
+fn models(transport: &ScriptedTransport) -> Vec<String> {
+    transport
+        .requests()
+        .iter()
+        .map(|request| common::body(request)["model"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn old_index(texts: &[&str]) -> VectorIndex {
+    let mut index = VectorIndex::new()
+        .with_model("voyage-3-large")
+        .with_hnsw(HnswConfig::default());
+    for (i, text) in texts.iter().enumerate() {
+        let metadata = Metadata::from([("n".to_string(), MetadataValue::from(i as i64))]);
+        index
+            .insert(format!("doc-{i}"), *text, vec![1.0, 0.0], Some(metadata))
+            .unwrap();
+    }
+    index
+}
+
+#[tokio::test]
+async fn test_migrate_reembeds_and_keeps_documents() {
+    let transport = transport();
+    let old = old_index(&["a", "bb", "ccc"]);
+
+    let migrated = old
+        .migrate(EmbeddingModel::VoyageCode3, &transport.client())
+        .await
+        .unwrap();
+
+    assert_eq!(migrated.model(), Some("voyage-code-3"));
+    assert_eq!(
+        migrated.dimension(),
+        EmbeddingModel::VoyageCode3.embedding_dimension()
+    );
+    assert_eq!(migrated.hnsw_config(), old.hnsw_config());
+    let ids: Vec<&str> = migrated
+        .iter()
+        .map(|(record, _)| record.id.as_str())
+        .collect();
+    assert_eq!(ids, ["doc-0", "doc-1", "doc-2"]);
+    let (record, embedding) = migrated.get("doc-1").unwrap();
+    assert_eq!(record.text, "bb");
+    assert_eq!(record.metadata, old.get("doc-1").unwrap().0.metadata);
+    assert_eq!(embedding[..2], [2.0, 1.0]);
+    assert!(models(&transport)
+        .iter()
+        .all(|model| model == "voyage-code-3"));
+
+    // The original is untouched
+    assert_eq!(old.dimension(), 2);
+    assert_eq!(old.model(), Some("voyage-3-large"));
+}
+
+#[tokio::test]
+async fn test_migrate_reports_progress() {
+    let transport = transport();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let progress = seen.clone();
+    let embedder = BulkEmbedder::new(&transport.client())
+        .with_batch_size(2)
+        .with_progress_callback(move |status: &BulkProgress| {
+            progress.lock().unwrap().push((status.done, status.total));
+        });
+
+    let migrated = old_index(&["a", "b", "c", "d", "e"])
+        .migrate_with(EmbeddingModel::VoyageCode3, embedder)
+        .await
+        .unwrap();
+
+    assert_eq!(migrated.len(), 5);
+    assert_eq!(transport.sent(), 3);
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.last(), Some(&(5, Some(5))));
+}
+
+#[tokio::test]
+async fn test_failed_batches_fail_the_migration() {
+    let transport = transport();
+    let embedder = BulkEmbedder::new(&transport.client()).with_batch_size(1);
+
+    let error = old_index(&["fine", "poison", "fine too"])
+        .migrate_with(EmbeddingModel::VoyageCode3, embedder)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        error,
+        VoyageError::MigrationIncomplete {
+            embedded: 2,
+            total: 3
+        }
+    ));
+}
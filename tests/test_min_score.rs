@@ -0,0 +1,110 @@
+use tokio_stream::StreamExt;
+use voyageai::{
+    builder::search::SearchRequestBuilder,
+    models::search::{SearchModel, SearchType},
+    test_util::TestServer,
+};
+
+const DOCUMENTS: [&str; 4] = [
+    "rotate the api key every month",
+    "rotate the key",
+    "green tea brewing temperature",
+    "sourdough bread starter",
+];
+
+#[tokio::test]
+async fn test_rerank_drops_results_below_min_score() {
+    let server = TestServer::start().await.with_rerank().await;
+    let client = server.client();
+    let rerank = |min_score: Option<f64>| {
+        let mut builder = client
+            .rerank_request()
+            .query("rotate api key")
+            .add_documents(DOCUMENTS);
+        if let Some(min_score) = min_score {
+            builder = builder.min_score(min_score);
+        }
+        client.config.rerank_client.rerank(builder.build().unwrap())
+    };
+
+    let all = rerank(None).await.unwrap();
+    let threshold = all.results()[1].relevance_score();
+    let kept = rerank(Some(threshold)).await.unwrap();
+    assert_eq!(kept.results(), &all.results()[..2]);
+    assert_eq!(kept.total_documents(), DOCUMENTS.len());
+
+    // The threshold is applied by the client, not sent to the API
+    let requests = server.received_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+    assert!(body.get("min_score").is_none());
+
+    let streamed: Vec<_> = client
+        .rerank_stream(
+            client
+                .rerank_request()
+                .query("rotate api key")
+                .add_documents(DOCUMENTS)
+                .min_score(threshold)
+                .build()
+                .unwrap(),
+        )
+        .collect()
+        .await;
+    assert_eq!(streamed.len(), 2);
+    assert!(streamed
+        .iter()
+        .all(|document| document.similarity() >= threshold));
+}
+
+#[tokio::test]
+async fn test_search_drops_results_below_min_score() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let mut builder = SearchRequestBuilder::new();
+    builder
+        .query("rotate api key")
+        .documents(DOCUMENTS)
+        .model(SearchModel::default())
+        .search_type(SearchType::Similarity);
+
+    let all = client.search(builder.build().unwrap()).await.unwrap();
+    assert_eq!(all.len(), DOCUMENTS.len());
+    let threshold = all[1].score;
+
+    let request = builder.min_score(threshold).build().unwrap();
+    let kept = client.search(request.clone()).await.unwrap();
+    assert_eq!(kept, all[..2]);
+
+    let streamed: Vec<_> = client.search_stream(&request).collect().await;
+    assert_eq!(streamed, kept);
+
+    let none = builder.min_score(2.0).build().unwrap();
+    assert!(client.search(none).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_nearest_neighbor_min_score_is_a_maximum_distance() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let mut builder = SearchRequestBuilder::new();
+    builder
+        .query("rotate api key")
+        .documents(DOCUMENTS)
+        .model(SearchModel::default())
+        .search_type(SearchType::NearestNeighbor);
+
+    let all = client.search(builder.build().unwrap()).await.unwrap();
+    assert_eq!(all.len(), DOCUMENTS.len());
+    assert!(all[0].score <= all[1].score);
+    let threshold = all[1].score;
+
+    // The closest results are kept, not the farthest
+    let kept = client
+        .search(builder.min_score(threshold).build().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(kept, all[..2]);
+
+    let none = builder.min_score(-1.0).build().unwrap();
+    assert!(client.search(none).await.unwrap().is_empty());
+}
@@ -0,0 +1,56 @@
+use voyageai::{
+    builder::search::SearchRequestBuilder,
+    client::maximal_marginal_relevance,
+    models::search::{SearchModel, SearchType},
+    test_util::TestServer,
+    VoyageError,
+};
+
+#[test]
+fn test_mmr_skips_near_duplicates() {
+    let embeddings = vec![vec![1.0, 0.0], vec![0.99, 0.1], vec![0.0, 1.0]];
+    let relevance = [0.9, 0.89, 0.5];
+
+    assert_eq!(
+        maximal_marginal_relevance(&relevance, &embeddings, 2, 1.0),
+        vec![0, 1]
+    );
+    assert_eq!(
+        maximal_marginal_relevance(&relevance, &embeddings, 2, 0.5),
+        vec![0, 2]
+    );
+    assert_eq!(
+        maximal_marginal_relevance(&relevance, &embeddings, 5, 0.5),
+        vec![0, 2, 1]
+    );
+    assert!(maximal_marginal_relevance(&[], &[], 3, 0.5).is_empty());
+}
+
+#[tokio::test]
+async fn test_search_mmr_diversifies_results() -> Result<(), VoyageError> {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let mut builder = SearchRequestBuilder::new();
+    builder
+        .query("rust ownership borrowing")
+        .documents([
+            "rust ownership borrowing rules",
+            "rust ownership borrowing rules explained",
+            "rust async runtime",
+            "sourdough bread",
+        ])
+        .model(SearchModel::default())
+        .search_type(SearchType::Similarity)
+        .top_k(2);
+    let request = builder.build().unwrap();
+    let search_client = &client.config.search_client;
+
+    let plain = search_client.search(&request).await?;
+    let plain: Vec<usize> = plain.iter().map(|result| result.index).collect();
+    assert_eq!(plain, vec![0, 1]);
+
+    let diverse = search_client.search_mmr(&request, 0.5).await?;
+    let diverse: Vec<usize> = diverse.iter().map(|result| result.index).collect();
+    assert_eq!(diverse, vec![0, 2]);
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use voyageai::{
+    builder::search::SearchRequestBuilder,
+    client::{reciprocal_rank_fusion, MultiQueryRetriever},
+    models::search::{SearchModel, SearchType},
+    test_util::TestServer,
+    VoyageError,
+};
+
+const DOCUMENTS: [&str; 4] = [
+    "rotate the api key every month",
+    "green tea brewing temperature",
+    "replace an expired credential",
+    "sourdough bread starter",
+];
+
+fn request(top_k: Option<usize>) -> voyageai::client::SearchRequest {
+    let mut builder = SearchRequestBuilder::new();
+    builder
+        .query("unused")
+        .documents(DOCUMENTS)
+        .model(SearchModel::default())
+        .search_type(SearchType::Similarity);
+    if let Some(top_k) = top_k {
+        builder.top_k(top_k);
+    }
+    builder.build().unwrap()
+}
+
+#[test]
+fn test_reciprocal_rank_fusion() {
+    let fused = reciprocal_rank_fusion(&[vec![0, 1, 2], vec![2, 0]], 1.0);
+
+    let indices: Vec<usize> = fused.iter().map(|(index, _)| *index).collect();
+    assert_eq!(indices, vec![0, 2, 1]);
+    assert!((fused[0].1 - (1.0 / 2.0 + 1.0 / 3.0)).abs() < 1e-6);
+    assert!((fused[2].1 - 1.0 / 3.0).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn test_fuses_results_of_each_query() -> Result<(), VoyageError> {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let retriever = MultiQueryRetriever::new(&client);
+
+    let results = retriever
+        .search(
+            &["rotate api key", "replace expired credential"],
+            &request(Some(2)),
+        )
+        .await?;
+
+    // Each formulation finds a different document; both beat the rest
+    let mut indices: Vec<usize> = results.iter().map(|result| result.index).collect();
+    indices.sort();
+    assert_eq!(indices, vec![0, 2]);
+    assert!(results[0].score >= results[1].score);
+    assert_eq!(results[0].document, vec![DOCUMENTS[results[0].index]]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deduplicates_documents() -> Result<(), VoyageError> {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+
+    let results = MultiQueryRetriever::new(&client)
+        .search(
+            &["green tea", "tea temperature", "brewing tea"],
+            &request(None),
+        )
+        .await?;
+
+    assert_eq!(results.len(), DOCUMENTS.len());
+    assert_eq!(results[0].index, 1);
+    // Ranked first by all three queries
+    assert!((results[0].score - 3.0 / 61.0).abs() < 1e-6);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_requires_a_query() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let queries: [&str; 0] = [];
+
+    let result = MultiQueryRetriever::new(&client)
+        .search(&queries, &request(None))
+        .await;
+
+    assert!(matches!(result, Err(VoyageError::SearchBuilderError(_))));
+}
@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use voyageai::{
+    builder::search::SearchRequestBuilder,
+    client::{
+        embeddings_client::Client as EmbeddingsClient, rerank_client::DefaultRerankClient,
+        search_client::SearchClient, RateLimiter,
+    },
+    index::{HnswConfig, NamespaceStats, NamespacedIndex},
+    test_util::TestServer,
+    traits::llm::Embedder,
+    SearchModel, SearchType, VectorIndex, VoyageBuilderError, VoyageError,
+};
+
+fn tenants() -> NamespacedIndex {
+    let mut index = NamespacedIndex::new().with_model("voyage-3-large");
+    index
+        .insert("acme", "1", "acme invoice", vec![1.0, 0.0, 0.0], None)
+        .unwrap();
+    index
+        .insert("acme", "2", "acme contract", vec![0.0, 1.0, 0.0], None)
+        .unwrap();
+    // The same id in another namespace is another document
+    index
+        .insert("globex", "1", "globex invoice", vec![1.0, 0.0, 0.0], None)
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_searches_stay_in_their_namespace() {
+    let index = tenants();
+
+    let matches = index.search("acme", &[1.0, 0.0, 0.0], 10, None).unwrap();
+    let texts: Vec<&str> = matches.iter().map(|m| m.record.text.as_str()).collect();
+    assert_eq!(texts, ["acme invoice", "acme contract"]);
+    let matches = index.search("globex", &[0.0, 1.0, 0.0], 10, None).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].record.text, "globex invoice");
+
+    assert!(matches!(
+        index.search("initech", &[1.0, 0.0, 0.0], 10, None),
+        Err(VoyageError::NamespaceNotFound(namespace)) if namespace == "initech"
+    ));
+    assert!(matches!(
+        index.search("", &[1.0, 0.0, 0.0], 10, None),
+        Err(VoyageError::NamespaceRequired)
+    ));
+}
+
+#[test]
+fn test_hnsw_graphs_are_per_namespace() {
+    let index = tenants().with_hnsw(HnswConfig::default());
+    let matches = index.search("globex", &[0.0, 1.0, 0.0], 10, None).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert!(index.namespace("acme").unwrap().hnsw_config().is_some());
+}
+
+#[test]
+fn test_namespace_stats_and_deletion() {
+    let mut index = tenants();
+    assert_eq!(index.len(), 3);
+    assert_eq!(index.namespaces().collect::<Vec<_>>(), ["acme", "globex"]);
+    assert_eq!(
+        index.stats("acme"),
+        Some(NamespaceStats {
+            documents: 2,
+            tombstoned: 0,
+            dimension: 3,
+            model: Some("voyage-3-large".to_string()),
+        })
+    );
+
+    assert!(index.remove("globex", "2").is_none());
+    assert!(index.remove("acme", "1").is_some());
+    assert_eq!(index.stats("acme").unwrap().documents, 1);
+    assert_eq!(index.stats("globex").unwrap().documents, 1);
+
+    let dropped = index.drop_namespace("acme").unwrap();
+    assert_eq!(dropped.len(), 1);
+    assert!(!index.contains_namespace("acme"));
+    assert_eq!(index.stats("acme"), None);
+    assert_eq!(index.len(), 1);
+}
+
+#[test]
+fn test_save_and_load_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let index = tenants();
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("tenants.idx");
+
+    index.save(&path)?;
+    let loaded = NamespacedIndex::load(&path)?;
+
+    assert_eq!(loaded.namespaces().collect::<Vec<_>>(), ["acme", "globex"]);
+    for namespace in ["acme", "globex"] {
+        assert_eq!(loaded.namespace(namespace)?, index.namespace(namespace)?);
+    }
+
+    // Each format refuses the other with a hint
+    let error = VectorIndex::load(&path).unwrap_err();
+    assert!(error.to_string().contains("NamespacedIndex::load"));
+    let mut bytes = Vec::new();
+    index.namespace("acme")?.write_to(&mut bytes)?;
+    assert!(matches!(
+        NamespacedIndex::from_bytes(&bytes),
+        Err(VoyageError::IndexFormatError(_))
+    ));
+    let mut bytes = Vec::new();
+    index.write_to(&mut bytes)?;
+    assert!(NamespacedIndex::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_header_records_the_model_and_crate_version() -> Result<(), Box<dyn std::error::Error>> {
+    let mut bytes = Vec::new();
+    tenants().write_to(&mut bytes)?;
+
+    // New namespaces of a loaded index keep the model
+    let mut loaded = NamespacedIndex::from_bytes(&bytes)?;
+    loaded.insert("initech", "1", "memo", vec![0.0, 0.0, 1.0], None)?;
+    assert_eq!(
+        loaded.stats("initech").unwrap().model.as_deref(),
+        Some("voyage-3-large")
+    );
+
+    // Version 1 files have no model section; the namespaces' model is used
+    let section_len = u32::from_le_bytes(bytes[16..20].try_into()?) as usize;
+    let mut version_1 = bytes[..16].to_vec();
+    version_1[8..12].copy_from_slice(&1u32.to_le_bytes());
+    version_1.extend_from_slice(&bytes[20 + section_len..]);
+    let mut loaded = NamespacedIndex::from_bytes(&version_1)?;
+    loaded.insert("initech", "1", "memo", vec![0.0, 0.0, 1.0], None)?;
+    assert_eq!(
+        loaded.stats("initech").unwrap().model.as_deref(),
+        Some("voyage-3-large")
+    );
+
+    bytes[8..12].copy_from_slice(&99u32.to_le_bytes());
+    match NamespacedIndex::from_bytes(&bytes) {
+        Err(VoyageError::IndexVersionUnsupported {
+            version: 99,
+            written_by,
+            ..
+        }) => assert_eq!(written_by.as_deref(), Some(env!("CARGO_PKG_VERSION"))),
+        other => panic!("expected IndexVersionUnsupported, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_client_requires_a_namespace() -> Result<(), Box<dyn std::error::Error>> {
+    let server = TestServer::start().await.with_embeddings().await;
+    let texts = ["rotate the api key", "green tea brewing"];
+    let embeddings = server
+        .client()
+        .embed_documents(&texts.map(str::to_string))
+        .await?;
+    let mut index = NamespacedIndex::new().with_model("voyage-3-large");
+    index.insert("acme", "key", texts[0], embeddings[0].clone(), None)?;
+    index.insert("globex", "tea", texts[1], embeddings[1].clone(), None)?;
+    let index = Arc::new(index);
+    let config = server.client().voyage_config().clone();
+    let client = SearchClient::new(
+        EmbeddingsClient::new(config.clone()),
+        DefaultRerankClient::new(config, Arc::new(RateLimiter::new())),
+    );
+
+    let mut builder = SearchRequestBuilder::new();
+    builder
+        .query("rotate api key")
+        .namespaced_index(index)
+        .model(SearchModel::default())
+        .search_type(SearchType::Similarity);
+    assert!(matches!(
+        builder.build(),
+        Err(VoyageBuilderError::MissingField(field)) if field == "namespace"
+    ));
+
+    let results = client.search(&builder.namespace("globex").build()?).await?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].document, vec![texts[1]]);
+
+    let request = builder.namespace("initech").build()?;
+    assert!(matches!(
+        client.search(&request).await,
+        Err(VoyageError::NamespaceNotFound(_))
+    ));
+
+    // A namespace names nothing in an index without namespaces
+    let mut plain = VectorIndex::new().with_model("voyage-3-large");
+    plain.insert("key", texts[0], embeddings[0].clone(), None)?;
+    let request = builder.index(Arc::new(plain)).namespace("acme").build()?;
+    assert!(matches!(
+        client.search(&request).await,
+        Err(VoyageError::NamespaceNotFound(_))
+    ));
+    Ok(())
+}
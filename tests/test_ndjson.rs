@@ -0,0 +1,80 @@
+use std::io::Cursor;
+
+use futures::StreamExt;
+use voyageai::{
+    io::{read_ndjson, write_ndjson, Compression, EmbeddingRecord, NdjsonReader, NdjsonWriter},
+    VoyageError,
+};
+
+fn records() -> Vec<EmbeddingRecord> {
+    (0..50)
+        .map(|i| {
+            EmbeddingRecord::new(
+                format!("doc-{i}"),
+                "voyage-3-large",
+                vec![i as f32, -0.5, 0.25],
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_lines_follow_the_interchange_format() -> Result<(), VoyageError> {
+    let mut writer = NdjsonWriter::new(Vec::new());
+    writer.write(&EmbeddingRecord::new("a", "voyage-3", vec![0.5, -1.0]))?;
+    let bytes = writer.finish()?;
+    assert_eq!(
+        String::from_utf8(bytes).unwrap(),
+        "{\"id\":\"a\",\"model\":\"voyage-3\",\"dim\":2,\"vector\":[0.5,-1.0]}\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_reader_validates_lines() {
+    let input = "{\"id\":\"a\",\"model\":\"m\",\"dim\":1,\"vector\":[1.0]}\n\n\
+                 {\"id\":\"b\",\"model\":\"m\",\"dim\":3,\"vector\":[1.0]}\n\
+                 not json\n";
+    let results: Vec<_> = NdjsonReader::new(Cursor::new(input)).collect();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap().id, "a");
+    assert!(
+        matches!(&results[1], Err(VoyageError::InterchangeError(m)) if m.starts_with("line 3:"))
+    );
+    assert!(
+        matches!(&results[2], Err(VoyageError::InterchangeError(m)) if m.starts_with("line 4:"))
+    );
+}
+
+#[test]
+fn test_round_trip_with_every_compression() -> Result<(), VoyageError> {
+    let dir = tempfile::tempdir().unwrap();
+    let records = records();
+    for name in ["plain.jsonl", "packed.jsonl.gz", "packed.jsonl.zst"] {
+        let path = dir.path().join(name);
+        assert_eq!(write_ndjson(&path, &records)?, records.len());
+        assert_eq!(read_ndjson(&path)?, records, "{name}");
+    }
+
+    // Compressed files are recognized by content, whatever their name
+    let compressed = dir.path().join("packed.jsonl.gz");
+    let renamed = dir.path().join("renamed.jsonl");
+    std::fs::rename(&compressed, &renamed).unwrap();
+    let header = std::fs::read(&renamed).unwrap();
+    assert_eq!(Compression::detect(&header), Compression::Gzip);
+    assert_eq!(read_ndjson(&renamed)?.len(), records.len());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reader_streams_records() -> Result<(), VoyageError> {
+    let mut writer = NdjsonWriter::with_compression(Vec::new(), Compression::Zstd)?;
+    writer.write_all(&records())?;
+    let bytes = writer.finish()?;
+
+    let stream =
+        NdjsonReader::with_compression(Cursor::new(bytes), Compression::Zstd)?.into_stream(8);
+    let streamed: Vec<EmbeddingRecord> = stream.map(|record| record.unwrap()).collect().await;
+    assert_eq!(streamed, records());
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::cmp::Ordering;
+
+use voyageai::{client::PairwiseRanker, test_util::TestServer};
+
+fn documents() -> Vec<String> {
+    [
+        "green tea brewing temperature",
+        "sourdough bread starter",
+        "rotate the api key every month",
+        "birds sing at dawn",
+        "rotate the key",
+    ]
+    .map(str::to_string)
+    .to_vec()
+}
+
+#[tokio::test]
+async fn test_compare_orders_two_documents() {
+    let server = TestServer::start().await.with_rerank().await;
+    let ranker = PairwiseRanker::new(&server.client());
+    let query = "rotate api key";
+    let (key, tea) = ("rotate the api key every month", "green tea brewing");
+
+    assert_eq!(
+        ranker.compare(query, key, tea).await.unwrap(),
+        Ordering::Greater
+    );
+    assert_eq!(
+        ranker.compare(query, tea, key).await.unwrap(),
+        Ordering::Less
+    );
+    assert_eq!(
+        ranker.compare(query, key, key).await.unwrap(),
+        Ordering::Equal
+    );
+
+    let requests = server.received_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["documents"], serde_json::json!([key, tea]));
+}
+
+#[tokio::test]
+async fn test_tournament_picks_the_best_document() {
+    let server = TestServer::start().await.with_rerank().await;
+    let client = server.client();
+    let query = "rotate api key";
+
+    let ranked = client
+        .config
+        .rerank_client
+        .rerank(
+            client
+                .rerank_request()
+                .query(query)
+                .add_documents(documents())
+                .build()
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let best = &ranked.results()[0];
+
+    let winner = PairwiseRanker::new(&client)
+        .tournament(query, &documents())
+        .await
+        .unwrap();
+    assert_eq!(winner.index, best.index());
+    assert_eq!(winner.document, documents()[best.index()]);
+    assert!((winner.relevance_score - best.relevance_score()).abs() < 1e-6);
+    // 5 documents in pairs: 3 groups, then 2, then 1
+    assert_eq!(winner.rounds, 3);
+    assert_eq!(server.received_requests().await.len(), 1 + 3 + 2 + 1);
+
+    let winner = PairwiseRanker::new(&client)
+        .with_group_size(10)
+        .tournament(query, &documents())
+        .await
+        .unwrap();
+    assert_eq!(winner.index, best.index());
+    assert_eq!(winner.rounds, 1);
+}
+
+#[tokio::test]
+async fn test_tournament_needs_documents() {
+    let server = TestServer::start().await.with_rerank().await;
+    let ranker = PairwiseRanker::new(&server.client());
+    assert!(ranker.tournament("rotate api key", &[]).await.is_err());
+    assert!(server.received_requests().await.is_empty());
+}
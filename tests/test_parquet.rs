@@ -0,0 +1,126 @@
+use std::fs::File;
+use std::sync::Arc;
+
+use arrow_array::{
+    builder::Float64Builder, builder::ListBuilder, ArrayRef, Int64Array, RecordBatch, StringArray,
+};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use voyageai::{
+    io::{read_parquet, write_parquet, EmbeddingRecord},
+    models::{Metadata, MetadataValue},
+    VoyageError,
+};
+
+fn metadata(pairs: &[(&str, MetadataValue)]) -> Metadata {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect()
+}
+
+#[test]
+fn test_round_trip_with_metadata() -> Result<(), VoyageError> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("embeddings.parquet");
+    let records: Vec<EmbeddingRecord> = (0..10_000)
+        .map(|i| {
+            EmbeddingRecord::new(
+                format!("doc-{i}"),
+                "voyage-3-large",
+                vec![i as f32, 0.5, -1.0],
+            )
+        })
+        .collect();
+    let metadata: Vec<Metadata> = (0..records.len())
+        .map(|i| match i % 3 {
+            0 => metadata(&[
+                ("source", "docs".into()),
+                ("page", (i as f64).into()),
+                ("draft", true.into()),
+            ]),
+            // `version` mixes types, so it is stored as strings
+            1 => metadata(&[("source", "blog".into()), ("version", 2.0.into())]),
+            _ => metadata(&[("version", "v3".into())]),
+        })
+        .collect();
+
+    assert_eq!(
+        write_parquet(&path, &records, Some(&metadata))?,
+        records.len()
+    );
+    let rows = read_parquet(&path)?;
+    assert_eq!(rows.len(), records.len());
+    for (i, (record, row_metadata)) in rows.iter().enumerate() {
+        assert_eq!(record, &records[i]);
+        match i % 3 {
+            0 => assert_eq!(row_metadata, &metadata[i]),
+            1 => assert_eq!(
+                row_metadata,
+                &self::metadata(&[("source", "blog".into()), ("version", "2".into())])
+            ),
+            _ => assert_eq!(row_metadata, &metadata[i]),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_write_rejects_inconsistent_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bad.parquet");
+    let records = vec![
+        EmbeddingRecord::new("a", "m", vec![1.0, 0.0]),
+        EmbeddingRecord::new("b", "m", vec![1.0]),
+    ];
+    assert!(matches!(
+        write_parquet(&path, &records, None),
+        Err(VoyageError::EmbeddingDimensionMismatch { index: 1, .. })
+    ));
+    assert!(matches!(
+        write_parquet(&path, &records[..1], Some(&[])),
+        Err(VoyageError::InterchangeError(_))
+    ));
+    let reserved = [metadata(&[("vector", "x".into())])];
+    assert!(matches!(
+        write_parquet(&path, &records[..1], Some(&reserved)),
+        Err(VoyageError::InterchangeError(_))
+    ));
+    assert!(matches!(
+        write_parquet(&path, &[], None),
+        Err(VoyageError::InterchangeError(_))
+    ));
+}
+
+#[test]
+fn test_reads_list_vectors_written_by_other_tools() -> Result<(), VoyageError> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("polars.parquet");
+
+    let mut vectors = ListBuilder::new(Float64Builder::new());
+    for vector in [[0.25, 0.5], [1.0, -1.0]] {
+        vectors.values().append_slice(&vector);
+        vectors.append(true);
+    }
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(vec!["x", "y"])),
+        Arc::new(vectors.finish()),
+        Arc::new(Int64Array::from(vec![Some(7), None])),
+    ];
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("vector", columns[1].data_type().clone(), true),
+        Field::new("chunk", DataType::Int64, true),
+    ]));
+    let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+    let mut writer = ArrowWriter::try_new(File::create(&path).unwrap(), schema, None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+
+    let rows = read_parquet(&path)?;
+    assert_eq!(rows[0].0, EmbeddingRecord::new("x", "", vec![0.25, 0.5]));
+    assert_eq!(rows[0].1, metadata(&[("chunk", 7.0.into())]));
+    assert_eq!(rows[1].0.vector, vec![1.0, -1.0]);
+    assert!(rows[1].1.is_empty());
+    Ok(())
+}
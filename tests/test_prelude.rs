@@ -0,0 +1,46 @@
+use voyageai::prelude::*;
+use voyageai::test_util::TestServer;
+
+#[tokio::test]
+async fn test_prelude_covers_embedding_and_ranking() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client: VoyageAiClient = server.client();
+
+    let embedding = Embedder::embed(&client, "hello").await.unwrap();
+    let other = Embedder::embed(&client, "hello").await.unwrap();
+    assert!((cosine_similarity(&embedding, &other) - 1.0).abs() < 1e-6);
+
+    let request: EmbeddingsRequest = EmbeddingsRequestBuilder::new()
+        .input(EmbeddingsInput::Single("hello".to_string()))
+        .model(EmbeddingModel::Voyage3Large)
+        .input_type(InputType::Document)
+        .build()
+        .unwrap();
+    let response: EmbeddingsResponse = client.embeddings(request).await.unwrap();
+    assert_eq!(response.data.len(), 1);
+
+    let ranked = DocumentSimilarity::new("doc", 0.5).with_strategy(RankingStrategy::Embedding);
+    assert_eq!(ranked.rank(), 0);
+    let _: Result<(), VoyageError> = Ok(());
+    let _ = (
+        RerankModel::default(),
+        SearchType::Similarity,
+        Priority::default(),
+    );
+}
+
+#[test]
+fn test_root_reexports_match_modules() {
+    let _: voyageai::DocumentSimilarity =
+        voyageai::client::rerank_client::DocumentSimilarity::new("doc", 0.1);
+    let _: voyageai::SearchResult = voyageai::models::search::SearchResult::new(
+        Vec::new(),
+        0.0,
+        0,
+        voyageai::SearchType::Similarity,
+    );
+    fn takes_embedder<E: voyageai::Embedder>(_: &E) {}
+    let client =
+        voyageai::VoyageAiClient::new_with_config(voyageai::VoyageConfig::new("key".to_string()));
+    takes_embedder(&client);
+}
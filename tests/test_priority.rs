@@ -0,0 +1,191 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio_stream::StreamExt;
+
+use voyageai::{
+    client::{BulkDocument, BulkEmbedder, Priority, RateLimit, RateLimitEndpoint, RateLimiter},
+    test_util::TestServer,
+    traits::llm::Embedder,
+    VoyageBuilder, VoyageError,
+};
+
+const EMBEDDINGS: RateLimitEndpoint = RateLimitEndpoint::Embeddings;
+
+/// One request per second, no burst.
+fn limiter() -> RateLimiter {
+    RateLimiter::new().with_limit(EMBEDDINGS, RateLimit::new(60, 60_000).with_burst(1, 60_000))
+}
+
+/// Queues one task per priority in the given order and returns the order in
+/// which they were served.
+async fn serve(limiter: &RateLimiter, priorities: &[Priority]) -> Vec<Priority> {
+    let served = Arc::new(Mutex::new(Vec::new()));
+    let mut tasks = Vec::new();
+    for &priority in priorities {
+        let limiter = limiter.clone();
+        let served = served.clone();
+        tasks.push(tokio::spawn(async move {
+            limiter.acquire_with_priority(EMBEDDINGS, 1, priority).await;
+            served.lock().unwrap().push(priority);
+        }));
+        tokio::task::yield_now().await;
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+    let served = served.lock().unwrap().clone();
+    served
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_higher_priority_is_served_first() {
+    let limiter = limiter();
+    limiter.acquire(EMBEDDINGS, 1).await;
+
+    let served = serve(
+        &limiter,
+        &[
+            Priority::Batch,
+            Priority::Batch,
+            Priority::Normal,
+            Priority::High,
+        ],
+    )
+    .await;
+
+    // The first batch request was already waiting for the bucket; the high
+    // priority request takes its turn
+    assert_eq!(
+        served,
+        vec![
+            Priority::High,
+            Priority::Normal,
+            Priority::Batch,
+            Priority::Batch
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_same_priority_is_served_in_arrival_order() {
+    let limiter = limiter();
+    limiter.acquire(EMBEDDINGS, 1).await;
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    let mut tasks = Vec::new();
+    for id in 0..4 {
+        let limiter = limiter.clone();
+        let order = order.clone();
+        tasks.push(tokio::spawn(async move {
+            limiter
+                .acquire_with_priority(EMBEDDINGS, 1, Priority::Batch)
+                .await;
+            order.lock().unwrap().push(id);
+        }));
+        tokio::task::yield_now().await;
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert_eq!(*order.lock().unwrap(), vec![0, 1, 2, 3]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_queued_caller_respects_max_wait() {
+    let limiter = limiter();
+    limiter.acquire(EMBEDDINGS, 1).await;
+
+    let high = {
+        let limiter = limiter.clone();
+        tokio::spawn(async move {
+            limiter
+                .acquire_with_priority(EMBEDDINGS, 1, Priority::High)
+                .await
+        })
+    };
+    tokio::task::yield_now().await;
+
+    // Queued behind the high priority request, the bucket refills in a second
+    // but the batch request gives up at its deadline
+    let error = limiter
+        .acquire_within(EMBEDDINGS, 1, Priority::Batch, Duration::from_millis(500))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, VoyageError::RateLimitExceeded { .. }));
+    assert_eq!(high.await.unwrap().as_secs(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_cancelled_waiter_leaves_queue() {
+    let limiter = limiter();
+    limiter.acquire(EMBEDDINGS, 1).await;
+
+    let high = {
+        let limiter = limiter.clone();
+        tokio::spawn(async move {
+            limiter
+                .acquire_with_priority(EMBEDDINGS, 1, Priority::High)
+                .await
+        })
+    };
+    tokio::task::yield_now().await;
+    high.abort();
+    let _ = high.await;
+
+    assert_eq!(
+        limiter
+            .acquire_with_priority(EMBEDDINGS, 1, Priority::Batch)
+            .await
+            .as_secs(),
+        1
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_events_report_priority() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let limiter =
+        limiter().with_event_callback(move |event| sink.lock().unwrap().push(event.clone()));
+    limiter.acquire(EMBEDDINGS, 1).await;
+
+    limiter
+        .acquire_with_priority(EMBEDDINGS, 1, Priority::Batch)
+        .await;
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].priority, Priority::Batch);
+}
+
+#[tokio::test]
+async fn test_bulk_jobs_default_to_batch_priority() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = VoyageBuilder::new()
+        .with_api_key("key")
+        .with_base_url(server.uri())
+        .with_priority(Priority::High)
+        .build()
+        .unwrap();
+    assert_eq!(client.config.config.priority, Priority::High);
+
+    assert!(format!("{:?}", BulkEmbedder::new(&client)).contains("Batch"));
+    let bulk = BulkEmbedder::new(&client).with_priority(Priority::Normal);
+    assert!(format!("{bulk:?}").contains("Normal"));
+
+    let results: Vec<_> = BulkEmbedder::new(&client)
+        .embed_all(vec![BulkDocument::new("a", "first document")])
+        .unwrap()
+        .collect()
+        .await;
+    assert_eq!(results.len(), 1);
+    assert!(client.embed("query").await.is_ok());
+}
+
+#[test]
+fn test_priority_names() {
+    assert_eq!("batch".parse(), Ok(Priority::Batch));
+    assert_eq!(Priority::High.to_string(), "high");
+    assert!(Priority::High < Priority::Normal && Priority::Normal < Priority::Batch);
+}
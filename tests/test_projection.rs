@@ -0,0 +1,140 @@
+use voyageai::{
+    index::{CollectionConfig, DimensionAdapter},
+    vectors::norm,
+    Embedding, VectorIndex, VectorStore, VoyageError,
+};
+
+/// A unit vector of `dimension` values pointing mostly along `axis`.
+fn unit(dimension: usize, axis: usize) -> Vec<f32> {
+    let mut vector = vec![0.01; dimension];
+    vector[axis] = 1.0;
+    voyageai::vectors::normalized(&vector)
+}
+
+#[test]
+fn test_truncate_keeps_the_prefix_at_unit_length() {
+    let adapted = DimensionAdapter::Truncate
+        .adapt(&[3.0, 4.0, 12.0, 0.0], 2)
+        .unwrap();
+    assert_eq!(adapted, vec![0.6, 0.8]);
+    assert!(DimensionAdapter::Truncate.adapt(&[1.0, 0.0], 4).is_none());
+    assert_eq!(
+        DimensionAdapter::Truncate.adapt(&[2.0, 0.0], 2),
+        Some(vec![2.0, 0.0])
+    );
+}
+
+#[test]
+fn test_random_projection_is_seeded_and_preserves_angles() {
+    let adapter = DimensionAdapter::random_projection(7);
+    let (a, b, c) = (unit(512, 0), unit(512, 1), unit(512, 0));
+    let project = |vector: &[f32]| adapter.adapt(vector, 256).unwrap();
+
+    let projected = project(&a);
+    assert_eq!(projected.len(), 256);
+    assert!((norm(&projected) - 1.0).abs() < 1e-4);
+    assert_eq!(projected, project(&c));
+    assert_ne!(
+        projected,
+        DimensionAdapter::random_projection(8)
+            .adapt(&a, 256)
+            .unwrap()
+    );
+
+    let before = voyageai::cosine_similarity(&a, &b);
+    let after = voyageai::cosine_similarity(&projected, &project(&b));
+    assert!((before - after).abs() < 0.2, "{before} vs {after}");
+}
+
+#[test]
+fn test_index_refuses_other_dimensions_without_adapter() {
+    let mut index = VectorIndex::new();
+    index.insert("a", "alpha", unit(4, 0), None).unwrap();
+
+    assert!(matches!(
+        index.insert("b", "beta", unit(8, 1), None),
+        Err(VoyageError::SearchDimensionMismatch {
+            expected: 4,
+            actual: 8
+        })
+    ));
+    assert!(matches!(
+        index.search(&unit(8, 0), 1, None),
+        Err(VoyageError::SearchDimensionMismatch {
+            expected: 4,
+            actual: 8
+        })
+    ));
+}
+
+#[test]
+fn test_index_truncates_longer_queries_and_documents() {
+    let mut index =
+        VectorIndex::with_dimension(4).with_dimension_adapter(DimensionAdapter::Truncate);
+    index.insert("a", "alpha", unit(4, 0), None).unwrap();
+    index.insert("b", "beta", unit(8, 1), None).unwrap();
+    assert_eq!(index.get("b").unwrap().1.len(), 4);
+
+    let matches = index.search(&unit(8, 1), 2, None).unwrap();
+    assert_eq!(matches[0].record.id, "b");
+    assert!(matches[0].score > 0.99);
+
+    // Truncation cannot make a vector longer
+    assert!(matches!(
+        index.search(&unit(2, 0), 1, None),
+        Err(VoyageError::SearchDimensionMismatch {
+            expected: 4,
+            actual: 2
+        })
+    ));
+}
+
+#[test]
+fn test_adapter_does_not_bridge_models() {
+    let mut index = VectorIndex::new()
+        .with_model("voyage-code-3")
+        .with_dimension_adapter(DimensionAdapter::Truncate);
+    index
+        .insert_embedding(
+            "a",
+            "alpha",
+            Embedding::new(unit(4, 0), "voyage-code-3"),
+            None,
+        )
+        .unwrap();
+
+    let same_model = Embedding::new(unit(8, 0), "voyage-code-3");
+    assert_eq!(
+        index.search_embedding(&same_model, 1, None).unwrap().len(),
+        1
+    );
+
+    let other_model = Embedding::new(unit(8, 0), "voyage-3-large");
+    assert!(index.search_embedding(&other_model, 1, None).is_err());
+}
+
+#[test]
+fn test_store_collections_take_an_adapter() {
+    let store = VectorStore::new();
+    store
+        .create_collection(
+            "projected",
+            CollectionConfig::persistent()
+                .with_dimension_adapter(DimensionAdapter::random_projection(1)),
+        )
+        .unwrap();
+    store
+        .insert("projected", "a", "alpha", unit(64, 0), None)
+        .unwrap();
+    store
+        .insert("projected", "b", "beta", unit(128, 0), None)
+        .unwrap();
+
+    assert_eq!(store.len("projected").unwrap(), 2);
+    assert_eq!(
+        store.search("projected", &unit(128, 0), 1, None).unwrap()[0]
+            .record
+            .id,
+        "b"
+    );
+}
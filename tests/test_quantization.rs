@@ -0,0 +1,150 @@
+use voyageai::{
+    cosine_similarity,
+    quantization::{
+        rescore, BinaryEmbedding, Int8Embedding, Quantization, QuantizedIndex, QuantizedMatch,
+    },
+    VoyageError,
+};
+
+fn random_vectors(count: usize, dimension: usize, seed: u64) -> Vec<Vec<f32>> {
+    let mut state = seed;
+    (0..count)
+        .map(|_| {
+            (0..dimension)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    ((state >> 33) as f32 / (1u64 << 31) as f32) - 0.5
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn test_int8_round_trip_and_similarity() {
+    let vectors = random_vectors(2, 64, 1);
+    let a = Int8Embedding::quantize(&vectors[0]);
+    let b = Int8Embedding::quantize(&vectors[1]);
+
+    assert_eq!(a.dimension(), 64);
+    assert!(a.values.iter().any(|value| value.abs() == 127));
+    for (original, restored) in vectors[0].iter().zip(a.dequantize()) {
+        assert!((original - restored).abs() <= a.scale / 2.0 + 1e-6);
+    }
+    let exact = cosine_similarity(&vectors[0], &vectors[1]);
+    assert!((a.cosine_similarity(&b).unwrap() - exact).abs() < 0.02);
+    let exact_dot: f32 = vectors[0].iter().zip(&vectors[1]).map(|(x, y)| x * y).sum();
+    assert!((a.dot(&b).unwrap() - exact_dot).abs() < 0.05);
+}
+
+#[test]
+fn test_int8_zero_vector() {
+    let zero = Int8Embedding::quantize(&[0.0, 0.0]);
+
+    assert_eq!(zero.values, vec![0, 0]);
+    assert_eq!(zero.cosine_similarity(&zero).unwrap(), 0.0);
+}
+
+#[test]
+fn test_binary_packing_and_hamming() {
+    let a = BinaryEmbedding::quantize(&[0.5, -0.1, 0.2, 0.0, -1.0, 1.0, 1.0, 1.0, 0.3]);
+    let b = BinaryEmbedding::quantize(&[0.5, 0.1, 0.2, 0.0, -1.0, 1.0, 1.0, 1.0, -0.3]);
+
+    assert_eq!(a.as_bytes(), &[0b1010_0111, 0b1000_0000]);
+    assert_eq!(a.hamming_distance(&b).unwrap(), 2);
+    assert!((a.similarity(&b).unwrap() - 7.0 / 9.0).abs() < 1e-6);
+    assert_eq!(
+        BinaryEmbedding::from_packed(a.as_bytes().to_vec(), 9).unwrap(),
+        a
+    );
+    let signed: Vec<i8> = a
+        .as_bytes()
+        .iter()
+        .map(|byte| (*byte as i16 - 128) as i8)
+        .collect();
+    assert_eq!(BinaryEmbedding::from_signed_packed(&signed, 9).unwrap(), a);
+}
+
+#[test]
+fn test_dimension_mismatch_is_rejected() {
+    let a = BinaryEmbedding::quantize(&[1.0; 8]);
+    let b = BinaryEmbedding::quantize(&[1.0; 16]);
+
+    assert!(matches!(
+        a.hamming_distance(&b),
+        Err(VoyageError::SearchDimensionMismatch {
+            expected: 8,
+            actual: 16
+        })
+    ));
+    assert!(BinaryEmbedding::from_packed(vec![0; 3], 8).is_err());
+    assert!(Int8Embedding::quantize(&[1.0])
+        .dot(&Int8Embedding::quantize(&[1.0, 2.0]))
+        .is_err());
+}
+
+#[test]
+fn test_quantized_index_rescoring_recovers_exact_order() {
+    let vectors = random_vectors(500, 64, 3);
+    let query = &random_vectors(1, 64, 9)[0];
+    let mut exact: Vec<(usize, f32)> = vectors
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (i, cosine_similarity(query, v)))
+        .collect();
+    exact.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut index = QuantizedIndex::new(Quantization::Binary)
+        .with_rescoring()
+        .with_oversample(10);
+    for vector in &vectors {
+        index.push(vector).unwrap();
+    }
+    let matches = index.search(query, 5).unwrap();
+
+    assert_eq!(matches.len(), 5);
+    assert_eq!(matches[0].index, exact[0].0);
+    assert!((matches[0].score - exact[0].1).abs() < 1e-6);
+    assert!(matches
+        .windows(2)
+        .all(|pair| pair[0].score >= pair[1].score));
+}
+
+#[test]
+fn test_quantized_index_memory_savings() {
+    let vectors = random_vectors(100, 256, 5);
+    let mut int8 = QuantizedIndex::new(Quantization::Int8);
+    let mut binary = QuantizedIndex::new(Quantization::Binary);
+    for vector in &vectors {
+        int8.push(vector).unwrap();
+        binary.push(vector).unwrap();
+    }
+    let full = 100 * 256 * 4;
+
+    assert!(int8.memory_bytes() * 3 < full);
+    assert_eq!(binary.memory_bytes() * 32, full);
+    assert_eq!(int8.search(&vectors[0], 1).unwrap()[0].index, 0);
+    assert!(int8.push(&[1.0]).is_err());
+}
+
+#[test]
+fn test_rescore_sorts_by_full_precision() {
+    let full = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+    let mut matches = vec![
+        QuantizedMatch {
+            index: 1,
+            score: 0.9,
+        },
+        QuantizedMatch {
+            index: 0,
+            score: 0.1,
+        },
+    ];
+
+    rescore(&[1.0, 0.1], &mut matches, &full);
+
+    assert_eq!(matches[0].index, 0);
+    assert!(matches[0].score > 0.99);
+}
@@ -0,0 +1,76 @@
+use voyageai::{
+    builder::search::SearchRequestBuilder,
+    models::search::{SearchModel, SearchType},
+    test_util::TestServer,
+    traits::llm::Embedder,
+};
+
+async fn input_types(server: &TestServer) -> Vec<serde_json::Value> {
+    server
+        .received_requests()
+        .await
+        .iter()
+        .map(|request| {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            body["input_type"].clone()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_embed_query_and_documents_set_input_types() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+
+    let query = client.embed_query("what is rust").await.unwrap();
+    let documents = client
+        .embed_documents(&["rust is a language".to_string(), "tea".to_string()])
+        .await
+        .unwrap();
+    client.embed("plain").await.unwrap();
+
+    assert_eq!(query.len(), 1024);
+    assert_eq!(documents.len(), 2);
+    assert_eq!(
+        input_types(&server).await,
+        vec![
+            serde_json::json!("query"),
+            serde_json::json!("document"),
+            serde_json::Value::Null
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_embed_documents_splits_large_inputs() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let documents: Vec<String> = (0..200).map(|i| format!("document {i}")).collect();
+
+    let embeddings = client
+        .embeddings_client()
+        .embed_documents(&documents)
+        .await
+        .unwrap();
+
+    assert_eq!(embeddings.len(), 200);
+    assert_eq!(server.received_requests().await.len(), 2);
+}
+
+#[tokio::test]
+async fn test_search_embeds_query_and_documents_asymmetrically() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let request = SearchRequestBuilder::new()
+        .query("rust language")
+        .documents(["rust is a systems language", "green tea"])
+        .model(SearchModel::default())
+        .search_type(SearchType::NearestNeighbor)
+        .build()
+        .unwrap();
+
+    let results = client.search(request).await.unwrap();
+
+    assert_eq!(results[0].index, 0);
+    assert_eq!(input_types(&server).await, vec!["query", "document"]);
+}
@@ -0,0 +1,82 @@
+use voyageai::{
+    retrieval::RetrievalPipeline,
+    test_util::TestServer,
+    traits::query::{split_identifier, QueryPreprocessor, SymbolQueryExpander},
+};
+
+#[test]
+fn test_split_identifier() {
+    assert_eq!(
+        split_identifier("HttpClientBuilder"),
+        ["http", "client", "builder"]
+    );
+    assert_eq!(
+        split_identifier("parseHTTPResponse_v2"),
+        ["parse", "http", "response", "v2"]
+    );
+    assert_eq!(
+        split_identifier("std::fs::read_to_string"),
+        ["std", "fs", "read", "to", "string"]
+    );
+    assert_eq!(
+        split_identifier("utf8Decoder.decode"),
+        ["utf8", "decoder", "decode"]
+    );
+    assert_eq!(split_identifier("URL"), ["url"]);
+}
+
+#[test]
+fn test_symbol_expander_only_expands_symbols() {
+    let expand = |query: &str| SymbolQueryExpander.preprocess(query);
+    assert_eq!(
+        expand("HttpClientBuilder"),
+        "http client builder HttpClientBuilder"
+    );
+    assert_eq!(
+        expand(" voyageai::code::RepoWalker "),
+        "voyageai code repo walker voyageai::code::RepoWalker"
+    );
+    assert_eq!(expand("max-file-size"), "max file size max-file-size");
+    // Prose, single words and numbers are left alone
+    for query in [
+        "how do I build an http client?",
+        "builder",
+        "URL",
+        "1.2.3",
+        "",
+    ] {
+        assert_eq!(expand(query), query);
+    }
+}
+
+#[tokio::test]
+async fn test_pipeline_embeds_preprocessed_query() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let mut pipeline = RetrievalPipeline::builder(&client)
+        .without_rerank()
+        .query_preprocessor(SymbolQueryExpander)
+        .build();
+    pipeline
+        .add_document("client", "The http client builder sets timeouts.", None)
+        .await
+        .unwrap();
+
+    let hits = pipeline.query("HttpClientBuilder").await.unwrap();
+    assert_eq!(hits[0].document_id, "client");
+    let requests = server.received_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+    assert_eq!(body["input"][0], "http client builder HttpClientBuilder");
+    assert_eq!(body["input_type"], "query");
+
+    // Closures work too
+    let pipeline = RetrievalPipeline::builder(&client)
+        .index(pipeline.index().clone())
+        .without_rerank()
+        .query_preprocessor(|query: &str| query.to_uppercase())
+        .build();
+    pipeline.query("timeouts").await.unwrap();
+    let requests = server.received_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&requests[2].body).unwrap();
+    assert_eq!(body["input"][0], "TIMEOUTS");
+}
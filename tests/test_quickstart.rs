@@ -0,0 +1,131 @@
+#![cfg(feature = "quickstart")]
+
+use std::fs;
+
+use serde_json::{json, Value};
+use voyageai::{quickstart::Quickstart, VoyageAiClient, VoyageConfig, VoyageError};
+
+const TOPICS: [&str; 3] = ["rust", "python", "bread"];
+
+/// Embeds text as a keyword histogram over `TOPICS`, zero-padded to the
+/// dimension of voyage-3-large.
+fn embed(text: &str) -> Vec<f32> {
+    let text = text.to_lowercase();
+    let mut embedding: Vec<f32> = TOPICS
+        .iter()
+        .map(|topic| text.matches(topic).count() as f32 + 0.01)
+        .collect();
+    embedding.resize(1024, 0.0);
+    embedding
+}
+
+fn request_json(request: &mockito::Request) -> Value {
+    serde_json::from_slice(request.body().unwrap()).unwrap()
+}
+
+async fn mock_server() -> mockito::ServerGuard {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("POST", "/v1/embeddings")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body_from_request(|request| {
+            let body = request_json(request);
+            let inputs: Vec<String> = match &body["input"] {
+                Value::String(text) => vec![text.clone()],
+                Value::Array(texts) => texts.iter().map(|t| t.as_str().unwrap().to_string()).collect(),
+                _ => Vec::new(),
+            };
+            let data: Vec<Value> = inputs
+                .iter()
+                .enumerate()
+                .map(|(index, text)| json!({"object": "embedding", "embedding": embed(text), "index": index}))
+                .collect();
+            json!({"object": "list", "data": data, "model": "voyage-3-large", "usage": {"total_tokens": 1}})
+                .to_string()
+                .into()
+        })
+        .create_async()
+        .await;
+    server
+        .mock("POST", "/v1/rerank")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body_from_request(|request| {
+            // Favour documents that mention "ownership"
+            let body = request_json(request);
+            let documents = body["documents"].as_array().cloned().unwrap_or_default();
+            let mut data: Vec<Value> = documents
+                .iter()
+                .enumerate()
+                .map(|(index, document)| {
+                    let score = if document.as_str().unwrap().contains("ownership") { 0.9 } else { 0.1 };
+                    json!({"relevance_score": score, "index": index})
+                })
+                .collect();
+            data.sort_by(|a, b| {
+                b["relevance_score"].as_f64().unwrap().total_cmp(&a["relevance_score"].as_f64().unwrap())
+            });
+            json!({"object": "list", "data": data, "model": "rerank-2", "usage": {"total_tokens": 1}})
+                .to_string()
+                .into()
+        })
+        .create_async()
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn test_ask_returns_reranked_passages_with_context() -> Result<(), Box<dyn std::error::Error>>
+{
+    let server = mock_server().await;
+    let dir = tempfile::tempdir()?;
+    fs::write(
+        dir.path().join("rust.md"),
+        "Rust is a systems language.\n\nRust ownership rules prevent data races.",
+    )?;
+    fs::write(
+        dir.path().join("bread.txt"),
+        "Bread needs flour, water and time.",
+    )?;
+    fs::write(dir.path().join(".hidden.md"), "Rust ownership secrets.")?;
+    fs::write(dir.path().join("image.png"), "not text")?;
+
+    let client = VoyageAiClient::new_with_config(
+        VoyageConfig::new("key".to_string()).with_base_url(server.url()),
+    );
+    let mut quickstart = Quickstart::new(&client).chunk_chars(40).top_k(2);
+
+    let answer = quickstart
+        .ask(dir.path(), "How does rust handle ownership?")
+        .await?;
+
+    assert_eq!(answer.passages.len(), 2);
+    assert_eq!(
+        answer.passages[0].text,
+        "Rust ownership rules prevent data races."
+    );
+    assert!(answer.passages[0].source.ends_with("rust.md"));
+    assert!((answer.passages[0].score - 0.9).abs() < 1e-6);
+    assert!(answer.passages.iter().all(|p| !p.text.contains("secrets")));
+    assert!(answer.context.starts_with('['));
+    assert!(answer
+        .context
+        .contains("Rust ownership rules prevent data races."));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ask_rejects_empty_directory() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_server().await;
+    let dir = tempfile::tempdir()?;
+    let client = VoyageAiClient::new_with_config(
+        VoyageConfig::new("key".to_string()).with_base_url(server.url()),
+    );
+    let mut quickstart = Quickstart::new(&client);
+
+    let result = quickstart.ask(dir.path(), "anything").await;
+
+    assert!(matches!(result, Err(VoyageError::MissingDocuments(_))));
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use voyageai::client::{
+    retry::{retry_after, DEFAULT_RETRY_AFTER},
+    RateLimitEndpoint, RateLimitEvent, RateLimitEventKind, RateLimiter,
+};
+
+fn recording_limiter() -> (RateLimiter, Arc<Mutex<Vec<RateLimitEvent>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let limiter = RateLimiter::new().with_event_callback(move |event: &RateLimitEvent| {
+        sink.lock().unwrap().push(event.clone());
+    });
+    (limiter, events)
+}
+
+#[tokio::test]
+async fn test_no_event_when_under_limit() {
+    let (limiter, events) = recording_limiter();
+
+    let waited = limiter.acquire(RateLimitEndpoint::Embeddings, 100).await;
+
+    assert!(waited.is_zero());
+    assert!(events.lock().unwrap().is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_delay_emits_event_with_bucket_levels() {
+    let (limiter, events) = recording_limiter();
+    limiter
+        .acquire(RateLimitEndpoint::Embeddings, 1_000_000)
+        .await;
+
+    let waited = limiter.acquire(RateLimitEndpoint::Embeddings, 10).await;
+
+    assert!(!waited.is_zero());
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.endpoint, RateLimitEndpoint::Embeddings);
+    assert_eq!(event.kind, RateLimitEventKind::Delayed);
+    // 10 tokens refill in 0.6ms at one million tokens per minute
+    assert_eq!(event.wait, Duration::from_micros(600));
+    assert!(waited >= event.wait);
+    assert_eq!(event.tokens_requested, 10);
+    assert_eq!(event.available_requests, 299);
+    assert_eq!(event.available_tokens, 0);
+    assert_eq!(event.tpm_limit, 1_000_000);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rejection_emits_event() {
+    let (limiter, events) = recording_limiter();
+    limiter.acquire(RateLimitEndpoint::Reranking, 50).await;
+
+    limiter
+        .record_rejection(RateLimitEndpoint::Reranking, 20, Duration::from_secs(5))
+        .await;
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].endpoint, RateLimitEndpoint::Reranking);
+    assert_eq!(events[0].kind, RateLimitEventKind::Rejected);
+    assert_eq!(events[0].wait, Duration::from_secs(5));
+    assert_eq!(events[0].available_tokens, 2_000_000 - 50);
+}
+
+#[test]
+fn test_retry_after_header_parsing() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(retry_after(&headers), DEFAULT_RETRY_AFTER);
+
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+    assert_eq!(retry_after(&headers), Duration::from_secs(7));
+
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+    assert_eq!(retry_after(&headers), DEFAULT_RETRY_AFTER);
+}
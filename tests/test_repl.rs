@@ -0,0 +1,117 @@
+use std::io::Cursor;
+
+use voyageai::{
+    repl::{Repl, ReplSettings},
+    test_util::TestServer,
+    VectorIndex, VoyageAiClient,
+};
+
+const DOCUMENTS: [(&str, &str); 3] = [
+    ("cats", "cats purr and sleep all day"),
+    ("dogs", "dogs bark at the mail carrier"),
+    ("rust", "rust compiles to fast native code"),
+];
+
+async fn saved_index(client: &VoyageAiClient) -> (tempfile::TempDir, VectorIndex) {
+    let texts: Vec<String> = DOCUMENTS.iter().map(|(_, text)| text.to_string()).collect();
+    let embeddings = client
+        .config
+        .embeddings_client
+        .embed_documents(&texts)
+        .await
+        .unwrap();
+    let mut index = VectorIndex::new();
+    for ((id, text), embedding) in DOCUMENTS.iter().zip(embeddings) {
+        index.insert(*id, *text, embedding, None).unwrap();
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my_index");
+    index.save(&path).unwrap();
+    let index = VectorIndex::load(&path).unwrap();
+    (dir, index)
+}
+
+async fn run(repl: &mut Repl, input: &str) -> String {
+    let mut output = Vec::new();
+    repl.run(Cursor::new(input), &mut output).await.unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[tokio::test]
+async fn test_queries_and_settings() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let (_dir, index) = saved_index(&client).await;
+    let mut repl = Repl::new(&client, index);
+
+    let output = run(
+        &mut repl,
+        ":set top_k 1\ndogs bark\n:set top_k zero\n:set colour red\n:bogus\n",
+    )
+    .await;
+    assert!(output.starts_with("3 documents of dimension"));
+    assert!(output.contains("top_k 1, candidates 50, rerank off"));
+    assert!(output.contains("  1. "));
+    assert!(output.contains("dogs  dogs bark at the mail carrier"));
+    assert!(!output.contains("  2. "));
+    assert!(output.contains("error: `top_k` must be a positive number"));
+    assert!(output.contains("error: unknown setting `colour`"));
+    assert!(output.contains("error: unknown command `:bogus`"));
+    assert_eq!(repl.settings().top_k, 1);
+}
+
+#[tokio::test]
+async fn test_history_is_recalled_and_kept_across_sessions() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let (dir, index) = saved_index(&client).await;
+    let history = dir.path().join("history");
+
+    let mut repl = Repl::new(&client, index.clone())
+        .with_history_file(&history)
+        .unwrap();
+    let output = run(
+        &mut repl,
+        "cats purr\nrust code\n!1\n!!\n!9\n:history\n:quit\nignored\n",
+    )
+    .await;
+    assert_eq!(repl.history(), ["cats purr", "rust code", "cats purr"]);
+    assert!(output.contains("error: no query `!9` in the history"));
+    assert!(output.contains("   2  rust code"));
+    assert!(!output.contains("ignored"));
+
+    let repl = Repl::new(&client, index)
+        .with_history_file(&history)
+        .unwrap();
+    assert_eq!(repl.history(), ["cats purr", "rust code", "cats purr"]);
+}
+
+#[tokio::test]
+async fn test_rerank_scores_candidates() {
+    let server = TestServer::start()
+        .await
+        .with_embeddings()
+        .await
+        .with_rerank()
+        .await;
+    let client = server.client();
+    let (_dir, index) = saved_index(&client).await;
+    let settings = ReplSettings {
+        top_k: 2,
+        rerank: true,
+        ..ReplSettings::default()
+    };
+    let repl = Repl::new(&client, index).with_settings(settings);
+
+    let hits = repl.query("cats sleep").await.unwrap();
+    assert_eq!(hits.len(), 2);
+    assert!(hits.iter().all(|hit| hit.reranked));
+    assert_eq!(hits[0].id, "cats");
+    let requests = server.received_requests().await;
+    let rerank = requests
+        .iter()
+        .find(|request| request.url.path().ends_with("/rerank"))
+        .expect("a rerank request");
+    let body: serde_json::Value = serde_json::from_slice(&rerank.body).unwrap();
+    assert_eq!(body["documents"].as_array().unwrap().len(), 3);
+}
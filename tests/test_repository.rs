@@ -0,0 +1,172 @@
+#![cfg(feature = "repo")]
+
+use std::fs;
+use std::path::Path;
+
+use voyageai::{
+    code::{language_for_path, RepoWalker, SkipReason},
+    models::MetadataValue,
+    retrieval::RetrievalPipeline,
+    test_util::TestServer,
+};
+
+const COMMIT: &str = "0123456789abcdef0123456789abcdef01234567";
+
+/// A repository with ignored, hidden, binary, large and unknown files.
+fn repository() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let write = |path: &str, contents: &[u8]| {
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    };
+    write(".gitignore", b"target/\n*.log\n");
+    write("README.md", b"# Shapes\n\nGeometry helpers.\n");
+    write(
+        "src/lib.rs",
+        b"/// Area of a square.\npub fn area(side: f64) -> f64 {\n    side * side\n}\n",
+    );
+    write("src/util.py", b"def double(x):\n    return 2 * x\n");
+    write("tests/fixtures/big.rs", b"fn fixture() {}\n");
+    write("target/debug/build.rs", b"fn generated() {}\n");
+    write("debug.log", b"ignored\n");
+    write(".hidden/notes.md", b"hidden\n");
+    write("logo.rs", b"\x89PNG\0\0\0binary");
+    write("data.bin", b"unknown extension\n");
+    write("docs/huge.md", &vec![b'a'; 2048]);
+    write(".git/HEAD", b"ref: refs/heads/main\n");
+    write(
+        ".git/packed-refs",
+        format!("{} refs/heads/main\n", COMMIT).as_bytes(),
+    );
+    dir
+}
+
+fn relative_paths(walker: &RepoWalker) -> Vec<String> {
+    walker
+        .walk()
+        .unwrap()
+        .files
+        .into_iter()
+        .map(|file| file.relative_path)
+        .collect()
+}
+
+#[test]
+fn test_walk_honors_gitignore_and_filters() {
+    let dir = repository();
+    let walker = RepoWalker::new(dir.path()).max_file_size(1024);
+    let found = walker.walk().unwrap();
+    let paths: Vec<&str> = found
+        .files
+        .iter()
+        .map(|file| file.relative_path.as_str())
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            "README.md",
+            "src/lib.rs",
+            "src/util.py",
+            "tests/fixtures/big.rs"
+        ]
+    );
+    assert_eq!(found.commit, None);
+
+    let mut skipped = found.skipped.clone();
+    skipped.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        skipped,
+        [
+            (
+                dir.path().join("docs/huge.md"),
+                SkipReason::TooLarge { size: 2048 }
+            ),
+            (dir.path().join("logo.rs"), SkipReason::Binary),
+        ]
+    );
+
+    assert_eq!(
+        relative_paths(&walker.clone().include("src/**")),
+        ["src/lib.rs", "src/util.py"]
+    );
+    assert_eq!(
+        relative_paths(&walker.clone().exclude("tests/").exclude("*.py")),
+        ["README.md", "src/lib.rs"]
+    );
+    assert_eq!(
+        relative_paths(&walker.clone().languages(["python", "markdown"])),
+        ["README.md", "src/util.py"]
+    );
+    let with_hidden = relative_paths(&walker.clone().hidden(true));
+    assert!(with_hidden.contains(&".hidden/notes.md".to_string()));
+
+    // A file as root
+    assert_eq!(
+        relative_paths(&RepoWalker::new(dir.path().join("src/lib.rs"))),
+        ["lib.rs"]
+    );
+    assert_eq!(
+        language_for_path(Path::new("web/App.tsx")),
+        Some("typescript")
+    );
+    assert!(RepoWalker::new(dir.path()).include("[").walk().is_err());
+}
+
+#[test]
+fn test_head_commit() {
+    let dir = repository();
+    // From packed-refs, then from a loose ref, then a detached HEAD
+    let walker = RepoWalker::new(dir.path().join("src"));
+    assert_eq!(walker.head_commit().as_deref(), Some(COMMIT));
+    let loose = "fedcba9876543210fedcba9876543210fedcba98";
+    fs::create_dir_all(dir.path().join(".git/refs/heads")).unwrap();
+    fs::write(
+        dir.path().join(".git/refs/heads/main"),
+        format!("{loose}\n"),
+    )
+    .unwrap();
+    assert_eq!(walker.head_commit().as_deref(), Some(loose));
+    fs::write(dir.path().join(".git/HEAD"), format!("{COMMIT}\n")).unwrap();
+    assert_eq!(walker.head_commit().as_deref(), Some(COMMIT));
+
+    let elsewhere = tempfile::tempdir().unwrap();
+    assert_eq!(RepoWalker::new(elsewhere.path()).head_commit(), None);
+}
+
+#[tokio::test]
+async fn test_pipeline_indexes_repository_at_commit() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let mut pipeline = RetrievalPipeline::builder(&client).without_rerank().build();
+    let dir = repository();
+
+    let found = RepoWalker::new(dir.path())
+        .include("src/**")
+        .annotate_commit(true)
+        .walk()
+        .unwrap();
+    let report = pipeline.add_repository(found).await.unwrap();
+    assert_eq!(report.files, 2);
+    assert_eq!(report.commit.as_deref(), Some(COMMIT));
+    assert_eq!(pipeline.len(), 2);
+    // Both files went out in one request
+    assert_eq!(server.received_requests().await.len(), 1);
+
+    let hits = pipeline.query("area of a square").await.unwrap();
+    let area = hits
+        .iter()
+        .find(|hit| hit.text.contains("fn area"))
+        .expect("the Rust function is indexed");
+    assert_eq!(
+        area.document_id,
+        dir.path().join("src/lib.rs").to_string_lossy()
+    );
+    let metadata = area.metadata.as_ref().unwrap();
+    assert_eq!(
+        metadata.get("path"),
+        Some(&MetadataValue::from("src/lib.rs"))
+    );
+    assert_eq!(metadata.get("commit"), Some(&MetadataValue::from(COMMIT)));
+    assert_eq!(metadata.get("item"), Some(&MetadataValue::from("area")));
+}
@@ -1,12 +1,10 @@
-use voyageai::{RerankModel, RerankRequest, VoyageAiClient, traits::llm::{Reranker, Embedder}};
+use voyageai::{RerankModel, RerankRequest, test_util::TestServer, traits::llm::Reranker};
 use tokio_stream::StreamExt;
 
 #[tokio::test]
 async fn test_rerank() -> Result<(), Box<dyn std::error::Error>> {
-    let config = voyageai::VoyageConfig::new(
-        std::env::var("VOYAGE_API_KEY").expect("VOYAGE_API_KEY must be set")
-    );
-    let client = VoyageAiClient::new_with_config(config);
+    let server = TestServer::start().await.with_rerank().await;
+    let client = server.client();
 
     let query = "What is the capital of France?";
     let documents = vec![
@@ -50,9 +48,6 @@ async fn test_rerank() -> Result<(), Box<dyn std::error::Error>> {
 
 #[tokio::test]
 async fn test_rerank_invalid_input() -> Result<(), Box<dyn std::error::Error>> {
-    let _api_key = std::env::var("VOYAGE_API_KEY").expect("VOYAGE_API_KEY must be set");
-    let _client = VoyageAiClient::new();
-
     let result = RerankRequest::new("".to_string(), vec![], RerankModel::Rerank2, Some(2));
 
     assert!(result.is_err());
@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio_stream::StreamExt;
+use voyageai::{
+    client::rerank_client::{DocumentSimilarity, RankingStrategy, RerankFallback},
+    VoyageAiClient, VoyageBuilder, VoyageConfig, VoyageError,
+};
+
+/// Serves keyword-count embeddings and answers rerank requests with `status`.
+async fn mock_server(status: usize) -> mockito::ServerGuard {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("POST", "/v1/embeddings")
+        .with_header("content-type", "application/json")
+        .with_body_from_request(|request| {
+            let body: Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+            let data: Vec<Value> = body["input"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .map(|(index, text)| {
+                    let text = text.as_str().unwrap().to_lowercase();
+                    let mut embedding = vec![0.0f32; 1024];
+                    embedding[0] = text.matches("paris").count() as f32 + 0.01;
+                    embedding[1] = text.matches("berlin").count() as f32 + 0.01;
+                    json!({"object": "embedding", "embedding": embedding, "index": index})
+                })
+                .collect();
+            json!({"object": "list", "data": data, "model": "voyage-3-large", "usage": {"total_tokens": 1}})
+                .to_string()
+                .into()
+        })
+        .create_async()
+        .await;
+    server
+        .mock("POST", "/v1/rerank")
+        .with_status(status)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "object": "list",
+                "data": [{"relevance_score": 0.8, "index": 1}, {"relevance_score": 0.1, "index": 0}],
+                "model": "rerank-2",
+                "usage": {"total_tokens": 1}
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+    server
+}
+
+fn client(server: &mockito::ServerGuard, fallback: RerankFallback) -> VoyageAiClient {
+    VoyageAiClient::new_with_config(
+        VoyageConfig::new("key".to_string())
+            .with_base_url(server.url())
+            .with_rerank_fallback(fallback),
+    )
+}
+
+fn documents() -> Vec<String> {
+    vec!["Berlin".to_string(), "Paris".to_string()]
+}
+
+async fn rank(client: &VoyageAiClient) -> Vec<DocumentSimilarity> {
+    client
+        .find_similar_documents("Paris", documents())
+        .collect()
+        .await
+}
+
+#[tokio::test]
+async fn test_successful_rerank_is_tagged() {
+    let server = mock_server(200).await;
+
+    let ranked = rank(&client(&server, RerankFallback::OnError)).await;
+
+    assert_eq!(ranked[0].document, "Paris");
+    assert_eq!(ranked[0].similarity, 0.8);
+    assert!(ranked.iter().all(|d| d.strategy == RankingStrategy::Rerank));
+}
+
+#[tokio::test]
+async fn test_rate_limit_falls_back() {
+    let server = mock_server(429).await;
+
+    assert!(rank(&client(&server, RerankFallback::Never))
+        .await
+        .is_empty());
+
+    let ranked = rank(&client(&server, RerankFallback::OnRateLimit)).await;
+    assert_eq!(ranked.len(), 2);
+    assert_eq!(ranked[0].document, "Paris");
+    assert!(ranked
+        .iter()
+        .all(|d| d.strategy == RankingStrategy::Embedding));
+}
+
+#[tokio::test]
+async fn test_server_error_falls_back_only_on_error_policy() {
+    let server = mock_server(500).await;
+
+    assert!(rank(&client(&server, RerankFallback::OnRateLimit))
+        .await
+        .is_empty());
+
+    let client = client(&server, RerankFallback::OnError);
+    let best = client
+        .most_similar_document_with_usage("Paris", documents())
+        .await
+        .unwrap();
+    assert_eq!(best.document.document, "Paris");
+    assert_eq!(best.document.strategy, RankingStrategy::Embedding);
+    // The usage of the fallback is the embedding tokens
+    assert_eq!(best.usage.total_tokens, 2);
+}
+
+#[test]
+fn test_policy() {
+    let rate_limited = VoyageError::RateLimitExceeded {
+        reset_in: Duration::from_secs(1),
+        metadata: None,
+    };
+    let unavailable = VoyageError::ServiceUnavailable;
+
+    assert!(!RerankFallback::Never.applies_to(&rate_limited));
+    assert!(RerankFallback::OnRateLimit.applies_to(&rate_limited));
+    assert!(!RerankFallback::OnRateLimit.applies_to(&unavailable));
+    assert!(RerankFallback::OnError.applies_to(&unavailable));
+}
+
+#[test]
+fn test_builder_sets_fallback() {
+    let client = VoyageBuilder::new()
+        .with_api_key("key")
+        .with_rerank_fallback(RerankFallback::OnRateLimit)
+        .build()
+        .unwrap();
+    assert_eq!(
+        client.config.config.rerank_fallback,
+        RerankFallback::OnRateLimit
+    );
+}
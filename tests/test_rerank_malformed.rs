@@ -0,0 +1,102 @@
+use tokio_stream::StreamExt;
+use voyageai::{RerankResponse, VoyageAiClient, VoyageConfig, VoyageError};
+
+const VALID: &str = include_str!("fixtures/rerank/valid.json");
+const INDEX_OUT_OF_RANGE: &str = include_str!("fixtures/rerank/index_out_of_range.json");
+const DUPLICATE_INDEX: &str = include_str!("fixtures/rerank/duplicate_index.json");
+const MISSING_DATA: &str = include_str!("fixtures/rerank/missing_data.json");
+
+async fn serve(body: &str) -> mockito::ServerGuard {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("POST", "/v1/rerank")
+        .with_header("content-type", "application/json")
+        .with_body(body)
+        .create_async()
+        .await;
+    server
+}
+
+fn client(server: &mockito::ServerGuard) -> VoyageAiClient {
+    VoyageAiClient::new_with_config(
+        VoyageConfig::new("key".to_string()).with_base_url(server.url()),
+    )
+}
+
+fn documents() -> Vec<String> {
+    vec!["Berlin".to_string(), "Paris".to_string()]
+}
+
+#[test]
+fn test_validate_fixtures() {
+    let parse = |body: &str| serde_json::from_str::<RerankResponse>(body).unwrap();
+
+    assert!(parse(VALID).validate(2).is_ok());
+    // The same response is out of range for a single document
+    assert!(matches!(
+        parse(VALID).validate(1),
+        Err(VoyageError::MalformedResponse(_))
+    ));
+    assert!(matches!(
+        parse(INDEX_OUT_OF_RANGE).validate(2),
+        Err(VoyageError::MalformedResponse(message)) if message.contains("index 5")
+    ));
+    assert!(matches!(
+        parse(DUPLICATE_INDEX).validate(2),
+        Err(VoyageError::MalformedResponse(message)) if message.contains("duplicated")
+    ));
+}
+
+#[tokio::test]
+async fn test_out_of_range_index_is_an_error() {
+    let server = serve(INDEX_OUT_OF_RANGE).await;
+    let client = client(&server);
+
+    let error = client
+        .most_similar_document("capital of France", documents())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, VoyageError::MalformedResponse(_)));
+
+    // Streams end without panicking
+    let streamed: Vec<_> = client
+        .find_similar_documents("capital of France", documents())
+        .collect()
+        .await;
+    assert!(streamed.is_empty());
+}
+
+#[tokio::test]
+async fn test_duplicate_index_is_an_error() {
+    let server = serve(DUPLICATE_INDEX).await;
+
+    let error = client(&server)
+        .most_similar_document_with_usage("capital of France", documents())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, VoyageError::MalformedResponse(_)));
+}
+
+#[tokio::test]
+async fn test_missing_data_is_a_json_error() {
+    let server = serve(MISSING_DATA).await;
+
+    let error = client(&server)
+        .most_similar_document("capital of France", documents())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, VoyageError::JsonError(_)));
+}
+
+#[tokio::test]
+async fn test_valid_fixture_maps_to_documents() {
+    let server = serve(VALID).await;
+    let client = client(&server);
+
+    let best = client
+        .most_similar_document("capital of France", documents())
+        .await
+        .unwrap();
+    assert_eq!(best.document, "Paris");
+    assert_eq!(best.similarity, 0.91);
+}
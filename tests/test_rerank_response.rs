@@ -0,0 +1,86 @@
+use voyageai::{test_util::TestServer, DocumentSimilarity, RerankResponse};
+
+fn documents() -> Vec<String> {
+    [
+        "cats purr",
+        "dogs bark",
+        "rust compiles",
+        "cats sleep",
+        "birds sing",
+    ]
+    .map(str::to_string)
+    .to_vec()
+}
+
+#[tokio::test]
+async fn test_documents_are_echoed_on_request() {
+    let server = TestServer::start().await.with_rerank().await;
+    let client = server.client();
+
+    let request = client
+        .rerank_request()
+        .query("cats")
+        .add_documents(documents())
+        .top_k(3)
+        .return_documents(true)
+        .build()
+        .unwrap();
+    let response = client.config.rerank_client.rerank(request).await.unwrap();
+
+    assert_eq!(response.model(), "rerank-2");
+    assert_eq!(response.len(), 3);
+    assert_eq!(response.total_documents(), 5);
+    for result in response.results() {
+        assert_eq!(
+            result.document(),
+            Some(documents()[result.index()].as_str())
+        );
+    }
+
+    let requests = server.received_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["return_documents"], true);
+
+    let ranked: Vec<DocumentSimilarity> = response.clone().into();
+    assert_eq!(ranked.len(), 3);
+    assert_eq!(ranked[2].rank(), 2);
+    assert_eq!(
+        ranked[0].document(),
+        response.results()[0].document().unwrap()
+    );
+
+    // Serializes and reads back, including the client-side count
+    let json = serde_json::to_string(&response).unwrap();
+    let read: RerankResponse = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.total_documents(), 5);
+    assert_eq!(read.results(), response.results());
+}
+
+#[tokio::test]
+async fn test_pages_and_documents_from_the_request() {
+    let server = TestServer::start().await.with_rerank().await;
+    let client = server.client();
+
+    let request = client
+        .rerank_request()
+        .query("cats")
+        .add_documents(documents())
+        .build()
+        .unwrap();
+    let response = client.config.rerank_client.rerank(request).await.unwrap();
+    assert!(response
+        .results()
+        .iter()
+        .all(|result| result.document().is_none()));
+
+    assert_eq!(response.page_count(2), 3);
+    assert_eq!(response.page(0, 2), &response.results()[..2]);
+    assert_eq!(response.page(2, 2).len(), 1);
+    assert!(response.page(3, 2).is_empty());
+
+    let ranked = response.clone().into_document_similarities(&documents());
+    for (similarity, result) in ranked.iter().zip(response.results()) {
+        assert_eq!(similarity.document(), documents()[result.index()]);
+        assert_eq!(similarity.similarity(), result.relevance_score());
+    }
+}
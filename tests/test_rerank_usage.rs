@@ -0,0 +1,93 @@
+use serde_json::json;
+use tokio_stream::StreamExt;
+use voyageai::{
+    client::rerank_client::RerankStreamItem, models::rerank::Usage, VoyageAiClient, VoyageConfig,
+};
+
+async fn mock_server() -> mockito::ServerGuard {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("POST", "/v1/rerank")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "object": "list",
+                "data": [
+                    {"relevance_score": 0.9, "index": 1},
+                    {"relevance_score": 0.2, "index": 0}
+                ],
+                "model": "rerank-2",
+                "usage": {"total_tokens": 42}
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+    server
+}
+
+fn client(server: &mockito::ServerGuard) -> VoyageAiClient {
+    VoyageAiClient::new_with_config(
+        VoyageConfig::new("key".to_string()).with_base_url(server.url()),
+    )
+}
+
+fn documents() -> Vec<String> {
+    vec!["Berlin".to_string(), "Paris".to_string()]
+}
+
+#[tokio::test]
+async fn test_stream_ends_with_usage() {
+    let server = mock_server().await;
+
+    let items: Vec<RerankStreamItem> = client(&server)
+        .find_similar_documents_with_usage("capital of France", documents())
+        .collect()
+        .await;
+
+    assert_eq!(items.len(), 3);
+    match &items[0] {
+        RerankStreamItem::Document(document) => {
+            assert_eq!(document.document, "Paris");
+            assert_eq!(document.rank, 0);
+        }
+        other => panic!("expected a document, got {other:?}"),
+    }
+    assert!(matches!(&items[1], RerankStreamItem::Document(d) if d.document == "Berlin"));
+    assert!(matches!(
+        items[2],
+        RerankStreamItem::Usage(Usage { total_tokens: 42 })
+    ));
+}
+
+#[tokio::test]
+async fn test_most_similar_document_with_usage() {
+    let server = mock_server().await;
+    let client = client(&server);
+
+    let best = client
+        .most_similar_document_with_usage("capital of France", documents())
+        .await
+        .unwrap();
+    assert_eq!(best.document.document, "Paris");
+    assert_eq!(best.document.similarity, 0.9);
+    assert_eq!(best.usage.total_tokens, 42);
+
+    let document = client
+        .most_similar_document("capital of France", documents())
+        .await
+        .unwrap();
+    assert_eq!(document.document, "Paris");
+}
+
+#[tokio::test]
+async fn test_invalid_request_has_no_usage() {
+    let server = mock_server().await;
+
+    let items: Vec<RerankStreamItem> = client(&server)
+        .find_similar_documents_with_usage("query", Vec::new())
+        .collect()
+        .await;
+    assert!(items.is_empty());
+}
@@ -0,0 +1,180 @@
+use reqwest::header::{HeaderMap, HeaderValue};
+use std::time::Duration;
+use voyageai::{
+    client::{embeddings_client::Client as EmbeddingsClient, Priority, RateLimitEndpoint},
+    models::{
+        embeddings::{EmbeddingModel, EmbeddingsInput, EmbeddingsRequest},
+        RateLimitHeaders, ResponseMetadata,
+    },
+    VoyageConfig, VoyageError,
+};
+
+fn request() -> EmbeddingsRequest {
+    EmbeddingsRequest {
+        input: EmbeddingsInput::Single("hello".to_string()),
+        model: EmbeddingModel::Voyage3Large,
+        input_type: None,
+        truncation: None,
+        encoding_format: None,
+    }
+}
+
+fn embeddings_body() -> String {
+    serde_json::json!({
+        "object": "list",
+        "data": [{"object": "embedding", "embedding": vec![0.1; 1024], "index": 0}],
+        "model": "voyage-3-large",
+        "usage": {"total_tokens": 3}
+    })
+    .to_string()
+}
+
+#[test]
+fn test_rate_limit_headers_parsing() {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-request-id", HeaderValue::from_static("req_123"));
+    headers.insert(
+        "x-ratelimit-limit-requests",
+        HeaderValue::from_static("300"),
+    );
+    headers.insert(
+        "x-ratelimit-remaining-requests",
+        HeaderValue::from_static("0"),
+    );
+    headers.insert(
+        "x-ratelimit-reset-requests",
+        HeaderValue::from_static("1m30s"),
+    );
+    headers.insert(
+        "x-ratelimit-remaining-tokens",
+        HeaderValue::from_static("5000"),
+    );
+    headers.insert(
+        "x-ratelimit-reset-tokens",
+        HeaderValue::from_static("250ms"),
+    );
+
+    let metadata = ResponseMetadata::from_header_map(&headers);
+
+    assert_eq!(metadata.request_id.as_deref(), Some("req_123"));
+    let quota = metadata.rate_limit;
+    assert_eq!(quota.limit_requests, Some(300));
+    assert_eq!(quota.reset_requests, Some(Duration::from_secs(90)));
+    assert_eq!(quota.reset_tokens, Some(Duration::from_millis(250)));
+    assert_eq!(quota.limit_tokens, None);
+    assert!(quota.is_exhausted());
+    // Only the exhausted request quota delays requests
+    assert_eq!(quota.reset_in(), Some(Duration::from_secs(90)));
+    assert!(RateLimitHeaders::default().is_empty());
+}
+
+#[test]
+fn test_reset_accepts_seconds_and_rejects_garbage() {
+    let parse = |value: &'static str| {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-reset-tokens", HeaderValue::from_static(value));
+        RateLimitHeaders::from_header_map(&headers).reset_tokens
+    };
+
+    assert_eq!(parse("1.5"), Some(Duration::from_millis(1500)));
+    assert_eq!(parse("1h"), Some(Duration::from_secs(3600)));
+    assert_eq!(parse("soon"), None);
+    assert_eq!(parse("5x"), None);
+}
+
+#[tokio::test]
+async fn test_response_metadata_pauses_limiter_on_exhausted_quota(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/v1/embeddings")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("request-id", "req_ok")
+        .with_header("x-ratelimit-remaining-requests", "0")
+        .with_header("x-ratelimit-reset-requests", "30s")
+        .with_body(embeddings_body())
+        .create_async()
+        .await;
+
+    let config = VoyageConfig::new("key".to_string()).with_base_url(server.url());
+    let client = EmbeddingsClient::new(config);
+
+    let response = client.create_embedding(&request()).await?;
+
+    assert_eq!(response.headers.request_id.as_deref(), Some("req_ok"));
+    assert_eq!(response.headers.rate_limit.remaining_requests, Some(0));
+    let error = client
+        .rate_limiter()
+        .acquire_within(
+            RateLimitEndpoint::Embeddings,
+            1,
+            Priority::Normal,
+            Duration::from_secs(1),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        VoyageError::RateLimitExceeded { reset_in, metadata: None } if reset_in > Duration::from_secs(20)
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_errors_carry_response_metadata() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/v1/embeddings")
+        .with_status(502)
+        .with_header("x-request-id", "req_err")
+        .with_body("bad gateway")
+        .create_async()
+        .await;
+
+    let config = VoyageConfig::new("key".to_string()).with_base_url(server.url());
+    let client = EmbeddingsClient::new(config);
+
+    let error = client.create_embedding(&request()).await.unwrap_err();
+
+    assert!(matches!(error, VoyageError::ApiError(status, _, _) if status == 502));
+    let metadata = error.response_metadata().expect("metadata of the response");
+    assert_eq!(metadata.request_id.as_deref(), Some("req_err"));
+    assert_eq!(
+        error.duplicate().response_metadata(),
+        error.response_metadata()
+    );
+    assert!(VoyageError::Unauthorized.response_metadata().is_none());
+}
+
+#[tokio::test]
+async fn test_rate_limit_error_carries_response_metadata() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/v1/embeddings")
+        .with_status(429)
+        .with_header("x-request-id", "req_429")
+        .with_header("retry-after", "1")
+        .with_body("slow down")
+        .create_async()
+        .await;
+
+    let config = VoyageConfig::new("key".to_string()).with_base_url(server.url());
+    let client = EmbeddingsClient::new(config);
+
+    let error = client.create_embedding(&request()).await.unwrap_err();
+
+    assert!(matches!(
+        error,
+        VoyageError::RateLimitExceeded {
+            metadata: Some(_),
+            ..
+        }
+    ));
+    assert_eq!(
+        error
+            .response_metadata()
+            .and_then(|m| m.request_id.as_deref()),
+        Some("req_429")
+    );
+}
@@ -0,0 +1,86 @@
+use serde_json::json;
+use voyageai::{
+    client::rerank_client::{DocumentSimilarity, RankingStrategy, RerankMatch, RerankStreamItem},
+    models::{
+        rerank::{RerankResult, Usage},
+        search::{SearchResult, SearchType},
+    },
+};
+
+#[test]
+fn test_document_similarity_serializes_stably() {
+    let similarity = DocumentSimilarity::new("Rust is fast", 0.75)
+        .with_rank(2)
+        .with_strategy(RankingStrategy::Embedding);
+    let value = serde_json::to_value(&similarity).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "rank": 2,
+            "similarity": 0.75,
+            "document": "Rust is fast",
+            "strategy": "embedding",
+        })
+    );
+    let parsed: DocumentSimilarity = serde_json::from_value(value).unwrap();
+    assert_eq!(parsed, similarity);
+}
+
+#[test]
+fn test_document_similarity_strategy_defaults_to_rerank() {
+    let parsed: DocumentSimilarity =
+        serde_json::from_value(json!({"rank": 0, "similarity": 0.5, "document": "doc"})).unwrap();
+    assert_eq!(parsed.strategy(), RankingStrategy::Rerank);
+    assert_eq!(parsed.document(), "doc");
+    assert_eq!(parsed.similarity(), 0.5);
+    assert_eq!(parsed.rank(), 0);
+}
+
+#[test]
+fn test_document_similarity_from_rerank_result() {
+    let result: RerankResult = serde_json::from_value(json!({
+        "relevance_score": 0.9,
+        "index": 3,
+        "document": "returned text",
+    }))
+    .unwrap();
+    let similarity = DocumentSimilarity::from(result).with_rank(1);
+    assert_eq!(similarity.document(), "returned text");
+    assert_eq!(similarity.similarity(), 0.9);
+    assert_eq!(similarity.rank(), 1);
+    assert_eq!(similarity.strategy(), RankingStrategy::Rerank);
+}
+
+#[test]
+fn test_rerank_match_and_stream_items_round_trip() {
+    let usage = Usage { total_tokens: 12 };
+    let best = RerankMatch {
+        document: DocumentSimilarity::new("doc", 0.4),
+        usage,
+    };
+    let parsed: RerankMatch = serde_json::from_str(&serde_json::to_string(&best).unwrap()).unwrap();
+    assert_eq!(parsed, best);
+
+    let items = vec![
+        RerankStreamItem::Document(DocumentSimilarity::new("doc", 0.4)),
+        RerankStreamItem::Usage(usage),
+    ];
+    let value = serde_json::to_value(&items).unwrap();
+    assert_eq!(value[1], json!({"usage": {"total_tokens": 12}}));
+    let parsed: Vec<RerankStreamItem> = serde_json::from_value(value).unwrap();
+    assert_eq!(parsed, items);
+}
+
+#[test]
+fn test_search_result_builder_and_accessors() {
+    let result = SearchResult::new(vec!["text".to_string()], 0.5, 4, SearchType::Similarity);
+    assert_eq!(result.document(), ["text".to_string()]);
+    assert_eq!(result.score(), 0.5);
+    assert_eq!(result.index(), 4);
+    assert_eq!(result.search_type(), &SearchType::Similarity);
+    assert!(result.metadata().is_none());
+
+    let parsed: SearchResult =
+        serde_json::from_str(&serde_json::to_string(&result).unwrap()).unwrap();
+    assert_eq!(parsed, result);
+}
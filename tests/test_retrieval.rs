@@ -0,0 +1,185 @@
+use serde_json::{json, Value};
+use voyageai::{
+    chunking::{Chunker, ParagraphChunker},
+    models::{Metadata, MetadataFilter, MetadataValue},
+    retrieval::RetrievalPipeline,
+    VoyageAiClient, VoyageConfig,
+};
+
+const TOPICS: [&str; 3] = ["rust", "python", "bread"];
+
+/// Embeds text as a keyword histogram over `TOPICS`, zero-padded to the
+/// dimension of voyage-3-large.
+fn embed(text: &str) -> Vec<f32> {
+    let text = text.to_lowercase();
+    let mut embedding: Vec<f32> = TOPICS
+        .iter()
+        .map(|topic| text.matches(topic).count() as f32 + 0.01)
+        .collect();
+    embedding.resize(1024, 0.0);
+    embedding
+}
+
+fn request_json(request: &mockito::Request) -> Value {
+    serde_json::from_slice(request.body().unwrap()).unwrap()
+}
+
+async fn mock_server() -> mockito::ServerGuard {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("POST", "/v1/embeddings")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body_from_request(|request| {
+            let body = request_json(request);
+            let inputs: Vec<String> = match &body["input"] {
+                Value::String(text) => vec![text.clone()],
+                Value::Array(texts) => texts.iter().map(|t| t.as_str().unwrap().to_string()).collect(),
+                _ => Vec::new(),
+            };
+            let data: Vec<Value> = inputs
+                .iter()
+                .enumerate()
+                .map(|(index, text)| json!({"object": "embedding", "embedding": embed(text), "index": index}))
+                .collect();
+            json!({"object": "list", "data": data, "model": "voyage-3-large", "usage": {"total_tokens": 1}})
+                .to_string()
+                .into()
+        })
+        .create_async()
+        .await;
+    server
+        .mock("POST", "/v1/rerank")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body_from_request(|request| {
+            // Favour documents that mention "ownership"
+            let body = request_json(request);
+            let documents = body["documents"].as_array().cloned().unwrap_or_default();
+            let mut data: Vec<Value> = documents
+                .iter()
+                .enumerate()
+                .map(|(index, document)| {
+                    let score = if document.as_str().unwrap().contains("ownership") { 0.9 } else { 0.1 };
+                    json!({"relevance_score": score, "index": index})
+                })
+                .collect();
+            data.sort_by(|a, b| {
+                b["relevance_score"].as_f64().unwrap().total_cmp(&a["relevance_score"].as_f64().unwrap())
+            });
+            json!({"object": "list", "data": data, "model": "rerank-2", "usage": {"total_tokens": 1}})
+                .to_string()
+                .into()
+        })
+        .create_async()
+        .await;
+    server
+}
+
+fn client(server: &mockito::ServerGuard) -> VoyageAiClient {
+    VoyageAiClient::new_with_config(
+        VoyageConfig::new("key".to_string()).with_base_url(server.url()),
+    )
+}
+
+const RUST_DOC: &str = "Rust is a systems language.\n\nRust ownership rules prevent data races.";
+
+#[tokio::test]
+async fn test_query_returns_reranked_chunks() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_server().await;
+    let client = client(&server);
+    let mut pipeline = RetrievalPipeline::builder(&client)
+        .chunker(ParagraphChunker::new(40))
+        .top_k(2)
+        .build();
+
+    let stored = pipeline
+        .add_documents([
+            ("rust", RUST_DOC),
+            ("bread", "Bread needs flour, water and time."),
+        ])
+        .await?;
+    let chunks = pipeline.query("How does rust handle ownership?").await?;
+
+    assert_eq!(stored, 3);
+    assert_eq!(pipeline.len(), 2);
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0].document_id, "rust");
+    assert_eq!(chunks[0].chunk_index, 1);
+    assert_eq!(chunks[0].text, "Rust ownership rules prevent data races.");
+    assert!(chunks[0].reranked);
+    assert!((chunks[0].score - 0.9).abs() < 1e-6);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_query_without_rerank_uses_vector_scores() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_server().await;
+    let client = client(&server);
+    let mut pipeline = RetrievalPipeline::builder(&client).without_rerank().build();
+    pipeline
+        .add_document("bread", "Bread needs flour.", None)
+        .await?;
+    pipeline
+        .add_document("python", "Python is dynamic.", None)
+        .await?;
+
+    let chunks = pipeline.query("bread").await?;
+
+    assert_eq!(chunks[0].document_id, "bread");
+    assert!(!chunks[0].reranked);
+    assert_eq!(chunks[0].score, chunks[0].vector_score);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_documents_are_replaced_and_removed() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_server().await;
+    let client = client(&server);
+    let mut pipeline = RetrievalPipeline::builder(&client)
+        .chunker(ParagraphChunker::new(40))
+        .build();
+
+    pipeline.add_document("doc", RUST_DOC, None).await?;
+    pipeline
+        .add_document("doc", "Bread needs flour.", None)
+        .await?;
+    assert_eq!(pipeline.index().len(), 1);
+    assert_eq!(pipeline.document_ids().collect::<Vec<_>>(), ["doc"]);
+
+    assert!(pipeline.remove_document("doc"));
+    assert_eq!(pipeline.document_ids().count(), 0);
+    assert!(pipeline.index().is_empty());
+    assert!(pipeline.query("bread").await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_query_filtered_by_metadata() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_server().await;
+    let client = client(&server);
+    let mut pipeline = RetrievalPipeline::builder(&client).without_rerank().build();
+    let public = Metadata::from([("public".to_string(), MetadataValue::from(true))]);
+    pipeline
+        .add_document("rust", "Rust ownership.", None)
+        .await?;
+    pipeline
+        .add_document("bread", "Bread and rust.", Some(public))
+        .await?;
+
+    let filter = MetadataFilter::eq("public", true);
+    let chunks = pipeline
+        .query_filtered("rust ownership", Some(&filter))
+        .await?;
+
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].document_id, "bread");
+    Ok(())
+}
+
+#[test]
+fn test_paragraph_chunker() {
+    let chunks = ParagraphChunker::new(10).chunk("one\n\ntwo\n\nthree\n\nabcdefghijkl");
+
+    assert_eq!(chunks, vec!["one\n\ntwo", "three", "abcdefghij", "kl"]);
+}
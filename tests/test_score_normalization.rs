@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use voyageai::{
+    builder::search::SearchRequestBuilder,
+    client::{
+        embeddings_client::Client as EmbeddingsClient, rerank_client::DefaultRerankClient,
+        search_client::SearchClient, RateLimiter,
+    },
+    test_util::TestServer,
+    ScoreNormalization, SearchModel, SearchType,
+};
+
+fn assert_close(actual: &[f32], expected: &[f32]) {
+    for (a, b) in actual.iter().zip(expected) {
+        assert!((a - b).abs() < 1e-5, "{actual:?} != {expected:?}");
+    }
+}
+
+fn normalized(normalization: ScoreNormalization, scores: &[f32]) -> Vec<f32> {
+    let mut scores = scores.to_vec();
+    normalization.normalize(&mut scores);
+    scores
+}
+
+#[test]
+fn test_normalizations() {
+    let scores = [0.9, 0.5, 0.1];
+
+    assert_eq!(normalized(ScoreNormalization::None, &scores), scores);
+    assert_close(
+        &normalized(ScoreNormalization::MinMax, &scores),
+        &[1.0, 0.5, 0.0],
+    );
+    let softmax = normalized(ScoreNormalization::Softmax, &scores);
+    assert!((softmax.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+    assert!(softmax[0] > softmax[1] && softmax[1] > softmax[2]);
+    let z = normalized(ScoreNormalization::ZScore, &scores);
+    assert_close(&z, &[1.2247449, 0.0, -1.2247449]);
+
+    // Equal scores
+    assert_eq!(
+        normalized(ScoreNormalization::MinMax, &[0.3, 0.3]),
+        vec![1.0, 1.0]
+    );
+    assert_eq!(
+        normalized(ScoreNormalization::ZScore, &[0.3, 0.3]),
+        vec![0.0, 0.0]
+    );
+    assert!(normalized(ScoreNormalization::Softmax, &[]).is_empty());
+}
+
+#[tokio::test]
+async fn test_search_results_are_normalized() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let config = server.client().voyage_config().clone();
+    let client = SearchClient::new(
+        EmbeddingsClient::new(config.clone()),
+        DefaultRerankClient::new(config, Arc::new(RateLimiter::new())),
+    )
+    .with_score_normalization(ScoreNormalization::MinMax);
+    let request = SearchRequestBuilder::new()
+        .query("rotate api key")
+        .documents([
+            "rotate the api key every month",
+            "green tea brewing temperature",
+            "sourdough bread starter",
+        ])
+        .model(SearchModel::default())
+        .search_type(SearchType::Similarity)
+        .build()
+        .unwrap();
+
+    let results = client.search(&request).await.unwrap();
+    assert_eq!(results[0].index, 0);
+    assert_eq!(results[0].score, 1.0);
+    assert_eq!(results[2].score, 0.0);
+    assert!(results
+        .windows(2)
+        .all(|pair| pair[0].score >= pair[1].score));
+}
+
+#[tokio::test]
+async fn test_nearest_neighbor_distances_are_normalized_nearest_first() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let config = server.client().voyage_config().clone();
+    let client = |normalization| {
+        SearchClient::new(
+            EmbeddingsClient::new(config.clone()),
+            DefaultRerankClient::new(config.clone(), Arc::new(RateLimiter::new())),
+        )
+        .with_score_normalization(normalization)
+    };
+    let request = SearchRequestBuilder::new()
+        .query("rotate api key")
+        .documents([
+            "rotate the api key every month",
+            "green tea brewing temperature",
+            "sourdough bread starter",
+        ])
+        .model(SearchModel::default())
+        .search_type(SearchType::NearestNeighbor)
+        .build()
+        .unwrap();
+    let raw = client(ScoreNormalization::None)
+        .search(&request)
+        .await
+        .unwrap();
+    let order: Vec<usize> = raw.iter().map(|result| result.index).collect();
+    assert!(raw[0].score <= raw[1].score && raw[1].score <= raw[2].score);
+
+    for normalization in [
+        ScoreNormalization::MinMax,
+        ScoreNormalization::Softmax,
+        ScoreNormalization::ZScore,
+    ] {
+        let results = client(normalization).search(&request).await.unwrap();
+        let indexes: Vec<usize> = results.iter().map(|result| result.index).collect();
+        assert_eq!(indexes, order, "{normalization:?}");
+        assert!(
+            results[0].score > results[2].score,
+            "{normalization:?}: {results:?}"
+        );
+        let scores: Vec<f32> = results.iter().map(|result| result.score).collect();
+        let mut expected: Vec<f32> = raw.iter().map(|result| -result.score).collect();
+        normalization.normalize(&mut expected);
+        assert_close(&scores, &expected);
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::Arc;
+use voyageai::{
+    builder::search::SearchRequestBuilder,
+    client::{
+        embeddings_client::Client as EmbeddingsClient, rerank_client::DefaultRerankClient,
+        search_client::SearchClient, RateLimiter,
+    },
+    models::{Metadata, MetadataValue},
+    test_util::TestServer,
+    traits::scoring::{MetadataBoost, ScoreFeatures, ScoringPipeline, WeightedScorer},
+    SearchModel, SearchType, VoyageConfig,
+};
+
+fn search_client() -> SearchClient {
+    let config = VoyageConfig::new("test_key".to_string());
+    SearchClient::new(
+        EmbeddingsClient::new(config.clone()),
+        DefaultRerankClient::new(config, Arc::new(RateLimiter::new())),
+    )
+}
+
+fn metadata(source: &str) -> Metadata {
+    Metadata::from([("source".to_string(), MetadataValue::from(source))])
+}
+
+fn features(similarity: f32, rerank_score: Option<f64>) -> ScoreFeatures<'static> {
+    ScoreFeatures {
+        index: 0,
+        document: "doc",
+        similarity,
+        rerank_score,
+        metadata: None,
+        score: similarity,
+    }
+}
+
+#[test]
+fn test_empty_pipeline_prefers_rerank_score() {
+    let pipeline = ScoringPipeline::new();
+
+    assert_eq!(pipeline.score(features(0.2, None)), 0.2);
+    assert_eq!(pipeline.score(features(0.2, Some(0.9))), 0.9);
+}
+
+#[test]
+fn test_pipeline_stages_chain_scores() {
+    let pipeline = ScoringPipeline::new()
+        .with_scorer(WeightedScorer {
+            similarity_weight: 0.5,
+            rerank_weight: 0.5,
+        })
+        .with_scorer(|features: &ScoreFeatures<'_>| features.score * 2.0);
+
+    let score = pipeline.score(features(0.4, Some(0.8)));
+
+    assert!((score - 1.2).abs() < 1e-6);
+    assert_eq!(pipeline.len(), 2);
+}
+
+#[test]
+fn test_metadata_boost() {
+    let boost = ScoringPipeline::new().with_scorer(MetadataBoost::new("source", "docs", 1.0));
+    let docs = metadata("docs");
+    let forum = metadata("forum");
+
+    let boosted = boost.score(ScoreFeatures {
+        metadata: Some(&docs),
+        ..features(0.5, None)
+    });
+    let unchanged = boost.score(ScoreFeatures {
+        metadata: Some(&forum),
+        ..features(0.5, None)
+    });
+
+    assert_eq!(boosted, 1.5);
+    assert_eq!(unchanged, 0.5);
+}
+
+#[tokio::test]
+async fn test_search_client_applies_scorers() -> Result<(), Box<dyn std::error::Error>> {
+    let client = search_client().with_scorer(MetadataBoost::new("source", "docs", 10.0));
+    let request = SearchRequestBuilder::new()
+        .query("rust async runtime")
+        .documents([
+            "rust async runtime rust async runtime",
+            "the rust async book",
+            "unrelated text",
+        ])
+        .metadata(vec![metadata("forum"), metadata("docs"), metadata("forum")])
+        .model(SearchModel::BM25)
+        .search_type(SearchType::BM25)
+        .top_k(2)
+        .build()?;
+
+    let results = client.search(&request).await?;
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].index, 1, "Boosted official docs should rank first");
+    assert_eq!(results[1].index, 0);
+    assert!(results[0].score > results[1].score);
+    assert_eq!(results[0].metadata, Some(metadata("docs")));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_client_without_scorers_keeps_raw_ranking(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let client = search_client();
+    let request = SearchRequestBuilder::new()
+        .query("rust async runtime")
+        .documents([
+            "rust async runtime rust async runtime",
+            "the rust async book",
+            "unrelated text",
+        ])
+        .model(SearchModel::BM25)
+        .search_type(SearchType::BM25)
+        .build()?;
+
+    let results = client.search(&request).await?;
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].index, 0);
+    assert_eq!(results[2].score, 0.0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_nearest_neighbor_distances_are_scored_as_similarities(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let server = TestServer::start().await.with_embeddings().await;
+    let config = server.client().voyage_config().clone();
+    let client = |scored: bool| {
+        let client = SearchClient::new(
+            EmbeddingsClient::new(config.clone()),
+            DefaultRerankClient::new(config.clone(), Arc::new(RateLimiter::new())),
+        );
+        match scored {
+            true => client.with_scorer(|features: &ScoreFeatures<'_>| features.similarity),
+            false => client,
+        }
+    };
+    let request = SearchRequestBuilder::new()
+        .query("rotate api key")
+        .documents([
+            "green tea brewing temperature",
+            "rotate the api key every month",
+            "sourdough bread starter",
+        ])
+        .model(SearchModel::default())
+        .search_type(SearchType::NearestNeighbor)
+        .build()?;
+
+    let distances = client(false).search(&request).await?;
+    let scored = client(true).search(&request).await?;
+
+    // The nearest document still ranks first
+    let order = |results: &[voyageai::SearchResult]| -> Vec<usize> {
+        results.iter().map(|result| result.index).collect()
+    };
+    assert_eq!(order(&scored), order(&distances));
+    for (scored, distance) in scored.iter().zip(&distances) {
+        assert!((scored.score - 1.0 / (1.0 + distance.score)).abs() < 1e-6);
+    }
+    Ok(())
+}
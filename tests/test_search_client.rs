@@ -1,11 +1,10 @@
-use voyageai::{VoyageAiClient, VoyageConfig, traits::llm::{Embedder, Reranker}};
+use voyageai::{test_util::TestServer, traits::llm::{Embedder, Reranker}};
 use tokio_stream::StreamExt;
 
 #[tokio::test]
 async fn test_search_client_integration() -> Result<(), Box<dyn std::error::Error>> {
-    let api_key = std::env::var("VOYAGE_API_KEY").expect("VOYAGE_API_KEY must be set");
-    let config = VoyageConfig::new(api_key);
-    let client = VoyageAiClient::new_with_config(config);
+    let server = TestServer::start().await.with_embeddings().await.with_rerank().await;
+    let client = server.client();
 
     // Test direct embedding
     let text = "Test document";
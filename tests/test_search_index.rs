@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use voyageai::{
+    builder::search::SearchRequestBuilder,
+    client::{
+        embeddings_client::Client as EmbeddingsClient, rerank_client::DefaultRerankClient,
+        search_client::SearchClient, RateLimiter,
+    },
+    models::{Metadata, MetadataFilter, MetadataValue},
+    test_util::TestServer,
+    traits::llm::Embedder,
+    vectors::VectorError,
+    SearchModel, SearchType, VectorIndex, VoyageError,
+};
+
+const DOCUMENTS: [&str; 3] = [
+    "rotate the api key every month",
+    "green tea brewing temperature",
+    "sourdough bread starter",
+];
+
+fn search_client(server: &TestServer) -> SearchClient {
+    let config = server.client().voyage_config().clone();
+    SearchClient::new(
+        EmbeddingsClient::new(config.clone()),
+        DefaultRerankClient::new(config, Arc::new(RateLimiter::new())),
+    )
+}
+
+async fn index(server: &TestServer, model: &str) -> VectorIndex {
+    let texts: Vec<String> = DOCUMENTS.map(str::to_string).to_vec();
+    let embeddings = server.client().embed_documents(&texts).await.unwrap();
+    let mut index = VectorIndex::new().with_model(model);
+    for (position, (text, embedding)) in texts.iter().zip(embeddings).enumerate() {
+        let topic = if position == 0 { "security" } else { "food" };
+        let metadata = Metadata::from([("topic".to_string(), MetadataValue::from(topic))]);
+        index
+            .insert(
+                position.to_string(),
+                text.as_str(),
+                embedding,
+                Some(metadata),
+            )
+            .unwrap();
+    }
+    index
+}
+
+#[tokio::test]
+async fn test_search_a_saved_index() -> Result<(), VoyageError> {
+    let server = TestServer::start().await.with_embeddings().await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("docs.index");
+    index(&server, "voyage-3-large").await.save(&path)?;
+
+    let request = SearchRequestBuilder::new()
+        .query("rotate api key")
+        .index_path(&path)
+        .model(SearchModel::default())
+        .search_type(SearchType::Similarity)
+        .top_k(2)
+        .build()?;
+    let results = search_client(&server).search(&request).await?;
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].index, 0);
+    assert_eq!(results[0].document, vec![DOCUMENTS[0]]);
+    assert!(results[0].metadata.is_some());
+    // Only the query was embedded
+    assert_eq!(server.received_requests().await.len(), 2);
+
+    let request = SearchRequestBuilder::new()
+        .query("rotate api key")
+        .index_path(&path)
+        .filter(MetadataFilter::eq("topic", "food"))
+        .model(SearchModel::default())
+        .search_type(SearchType::MaximalMarginalRelevance)
+        .build()?;
+    let results = search_client(&server).search(&request).await?;
+    let mut indices: Vec<usize> = results.iter().map(|result| result.index).collect();
+    indices.sort();
+    assert_eq!(indices, vec![1, 2]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_loaded_index_of_another_model_is_rejected() -> Result<(), VoyageError> {
+    let server = TestServer::start().await.with_embeddings().await;
+    let index = Arc::new(index(&server, "voyage-code-3").await);
+
+    let mut builder = SearchRequestBuilder::new();
+    builder
+        .query("rotate api key")
+        .index(index)
+        .model(SearchModel::default());
+    let request = builder.search_type(SearchType::Similarity).build()?;
+    assert!(matches!(
+        search_client(&server).search(&request).await,
+        Err(VoyageError::Vector(VectorError::ModelMismatch { .. }))
+    ));
+
+    let request = builder.search_type(SearchType::BM25).build()?;
+    assert!(matches!(
+        search_client(&server).search(&request).await,
+        Err(VoyageError::SearchBuilderError(_))
+    ));
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use tokio_stream::StreamExt;
+use voyageai::{
+    builder::search::SearchRequestBuilder,
+    models::search::{SearchModel, SearchType},
+    test_util::TestServer,
+};
+
+const DOCUMENTS: [&str; 3] = [
+    "rotate the api key every month",
+    "green tea brewing temperature",
+    "sourdough bread starter",
+];
+
+#[tokio::test]
+async fn test_stream_yields_the_search_results_in_order() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let request = SearchRequestBuilder::new()
+        .query("rotate api key")
+        .documents(DOCUMENTS)
+        .model(SearchModel::default())
+        .search_type(SearchType::Similarity)
+        .top_k(2)
+        .build()
+        .unwrap();
+
+    let streamed: Vec<_> = client.search_stream(&request).collect().await;
+    let searched = client.search(request).await.unwrap();
+    assert_eq!(streamed.len(), 2);
+    assert_eq!(streamed, searched);
+    assert_eq!(streamed[0].index, 0);
+}
+
+#[tokio::test]
+async fn test_failed_search_ends_the_stream() {
+    let server = TestServer::start()
+        .await
+        .with_error("embeddings", 400, r#"{"detail": "Invalid input"}"#)
+        .await;
+    let request = SearchRequestBuilder::new()
+        .query("rotate api key")
+        .documents(DOCUMENTS)
+        .model(SearchModel::default())
+        .search_type(SearchType::Similarity)
+        .build()
+        .unwrap();
+
+    let streamed: Vec<_> = server.client().search_stream(&request).collect().await;
+    assert!(streamed.is_empty());
+}
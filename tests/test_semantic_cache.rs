@@ -0,0 +1,91 @@
+use voyageai::{client::SemanticCache, test_util::TestServer, VoyageError};
+
+#[test]
+fn test_lookup_embedding_returns_most_similar_entry_above_threshold() {
+    let client = voyageai::VoyageAiClient::with_key("test-key");
+    let cache: SemanticCache<String> = SemanticCache::new(&client);
+    cache.insert_embedding("weather", vec![1.0, 0.0, 0.0], "sunny".to_string());
+    cache.insert_embedding("stocks", vec![0.0, 1.0, 0.0], "up".to_string());
+
+    let hit = cache.lookup_embedding(&[0.9, 0.1, 0.0], 0.9).unwrap();
+    assert_eq!(hit.query, "weather");
+    assert_eq!(hit.payload, "sunny");
+    assert!(hit.similarity > 0.9);
+    assert!(cache.lookup_embedding(&[0.6, 0.6, 0.5], 0.9).is_none());
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+    // Re-inserting a query replaces its payload
+    cache.insert_embedding("weather", vec![1.0, 0.0, 0.0], "rainy".to_string());
+    assert_eq!(cache.len(), 2);
+    assert_eq!(
+        cache
+            .lookup_embedding(&[1.0, 0.0, 0.0], 0.9)
+            .unwrap()
+            .payload,
+        "rainy"
+    );
+}
+
+#[test]
+fn test_least_recently_used_entry_is_evicted() {
+    let client = voyageai::VoyageAiClient::with_key("test-key");
+    let cache = SemanticCache::new(&client).with_capacity(2);
+    cache.insert_embedding("a", vec![1.0, 0.0, 0.0], 1);
+    cache.insert_embedding("b", vec![0.0, 1.0, 0.0], 2);
+    // Using "a" makes "b" the least recently used
+    assert!(cache.lookup_embedding(&[1.0, 0.0, 0.0], 0.99).is_some());
+    cache.insert_embedding("c", vec![0.0, 0.0, 1.0], 3);
+
+    assert_eq!(cache.len(), 2);
+    assert!(cache.lookup_embedding(&[0.0, 1.0, 0.0], 0.99).is_none());
+    assert_eq!(
+        cache
+            .lookup_embedding(&[1.0, 0.0, 0.0], 0.99)
+            .unwrap()
+            .payload,
+        1
+    );
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[tokio::test]
+async fn test_paraphrased_queries_hit_the_cache() -> Result<(), VoyageError> {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let cache: SemanticCache<String> = SemanticCache::new(&client);
+
+    let mut computed = 0;
+    let answer = cache
+        .get_or_insert_with("how do I reset my password", 0.8, || async {
+            computed += 1;
+            "Use the account page".to_string()
+        })
+        .await?;
+    assert_eq!(answer, "Use the account page");
+    assert_eq!(computed, 1);
+
+    let hit = cache
+        .lookup("how do I reset my password please", 0.8)
+        .await?
+        .expect("paraphrase should hit");
+    assert_eq!(hit.query, "how do I reset my password");
+    assert_eq!(hit.payload, "Use the account page");
+    assert!(cache.lookup("sourdough baking tips", 0.8).await?.is_none());
+
+    // Identical queries are answered without embedding them
+    let requests = server.received_requests().await.len();
+    let hit = cache
+        .lookup("how do I reset my password", 0.8)
+        .await?
+        .unwrap();
+    assert_eq!(hit.similarity, 1.0);
+    assert_eq!(server.received_requests().await.len(), requests);
+
+    cache
+        .insert("sourdough baking tips", "Feed the starter".to_string())
+        .await?;
+    assert_eq!(cache.len(), 2);
+    Ok(())
+}
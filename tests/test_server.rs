@@ -0,0 +1,240 @@
+use serde_json::{json, Value};
+use voyageai::{
+    index::SharedIndex, server::Server, test_util::TestServer, VectorIndex, VoyageAiClient,
+};
+
+const DOCUMENTS: [(&str, &str); 3] = [
+    ("cats", "cats purr and sleep all day"),
+    ("dogs", "dogs bark at the mail carrier"),
+    ("rust", "rust compiles to fast native code"),
+];
+
+async fn index(client: &VoyageAiClient) -> VectorIndex {
+    let texts: Vec<String> = DOCUMENTS.iter().map(|(_, text)| text.to_string()).collect();
+    let embeddings = client
+        .config
+        .embeddings_client
+        .embed_documents(&texts)
+        .await
+        .unwrap();
+    let mut index = VectorIndex::new();
+    for ((id, text), embedding) in DOCUMENTS.iter().zip(embeddings) {
+        index.insert(*id, *text, embedding, None).unwrap();
+    }
+    index
+}
+
+/// Serves `server` on a free local port and returns its address.
+async fn spawn(server: Server) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(server.serve(listener));
+    format!("http://{address}")
+}
+
+async fn post(url: String, body: Value) -> (u16, Value) {
+    let response = reqwest::Client::new()
+        .post(url)
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    let status = response.status().as_u16();
+    (status, response.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn test_embed_and_search() {
+    let api = TestServer::start().await.with_embeddings().await;
+    let client = api.client();
+    let base = spawn(Server::new(&client).with_index(index(&client).await)).await;
+
+    let health: Value = reqwest::get(format!("{base}/health"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(health, json!({ "status": "ok", "documents": 3 }));
+
+    let (status, body) = post(format!("{base}/embed"), json!({ "input": "hello" })).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["embeddings"].as_array().unwrap().len(), 1);
+    assert!(body["model"].is_string());
+
+    let (status, body) = post(
+        format!("{base}/embed"),
+        json!({ "input": ["a", "b"], "input_type": "document" }),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(body["embeddings"].as_array().unwrap().len(), 2);
+
+    let (status, body) = post(
+        format!("{base}/search"),
+        json!({ "query": "dogs bark at the mail carrier", "top_k": 2 }),
+    )
+    .await;
+    assert_eq!(status, 200);
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results[0]["score"].as_f64().unwrap() >= results[1]["score"].as_f64().unwrap());
+    assert_eq!(results[0]["reranked"], false);
+}
+
+#[tokio::test]
+async fn test_searches_see_documents_ingested_while_serving() {
+    let api = TestServer::start().await.with_embeddings().await;
+    let client = api.client();
+    let shared = SharedIndex::new(index(&client).await);
+    let base = spawn(Server::new(&client).with_shared_index(shared.clone())).await;
+    let query = "penguins huddle on the ice";
+
+    let (_, body) = post(
+        format!("{base}/search"),
+        json!({ "query": query, "top_k": 1 }),
+    )
+    .await;
+    assert_ne!(body["results"][0]["id"], "penguins");
+
+    let embedding = client
+        .config
+        .embeddings_client
+        .embed_documents(&[query.to_string()])
+        .await
+        .unwrap()
+        .remove(0);
+    shared.insert("penguins", query, embedding, None).unwrap();
+
+    let (_, body) = post(
+        format!("{base}/search"),
+        json!({ "query": query, "top_k": 1 }),
+    )
+    .await;
+    assert_eq!(body["results"][0]["id"], "penguins");
+    let health: Value = reqwest::get(format!("{base}/health"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(health["documents"], 4);
+}
+
+#[tokio::test]
+async fn test_rerank_and_reranked_search() {
+    let api = TestServer::start()
+        .await
+        .with_embeddings()
+        .await
+        .with_rerank()
+        .await;
+    let client = api.client();
+    let base = spawn(Server::new(&client).with_index(index(&client).await)).await;
+
+    let (status, body) = post(
+        format!("{base}/rerank"),
+        json!({ "query": "pets", "documents": ["cats", "dogs", "rust"], "top_k": 2 }),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert!(body["data"].is_array());
+
+    let (status, body) = post(
+        format!("{base}/search"),
+        json!({ "query": "pets", "top_k": 1, "rerank": true }),
+    )
+    .await;
+    assert_eq!(status, 200);
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["reranked"], true);
+}
+
+#[tokio::test]
+async fn test_passes_the_callers_api_key() {
+    let api = TestServer::start().await.with_embeddings().await;
+    let base = spawn(Server::new(&api.client())).await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{base}/embed"))
+        .bearer_auth("caller-key")
+        .json(&json!({ "input": "hello" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let requests = api.received_requests().await;
+    assert_eq!(
+        requests[0].headers.get("authorization").unwrap(),
+        "Bearer caller-key"
+    );
+}
+
+#[tokio::test]
+async fn test_errors() {
+    let api = TestServer::start().await.with_embeddings().await;
+    let base = spawn(Server::new(&api.client())).await;
+
+    let (status, body) = post(format!("{base}/search"), json!({ "query": "cats" })).await;
+    assert_eq!(status, 404);
+    assert_eq!(body["error"], "The server has no index");
+
+    let (status, body) = post(format!("{base}/embed"), json!({ "text": "hello" })).await;
+    assert_eq!(status, 422);
+    assert!(body["error"].as_str().unwrap().contains("input"));
+
+    let (status, _) = post(
+        format!("{base}/rerank"),
+        json!({ "query": "pets", "documents": [] }),
+    )
+    .await;
+    assert_eq!(status, 400);
+}
+
+#[tokio::test]
+async fn test_openai_and_ollama_embeddings() {
+    let api = TestServer::start().await.with_embeddings().await;
+    let base = spawn(Server::new(&api.client())).await;
+
+    let (status, body) = post(
+        format!("{base}/v1/embeddings"),
+        json!({ "input": ["a", "b"], "model": "text-embedding-3-small" }),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(body["object"], "list");
+    assert_eq!(body["data"][1]["object"], "embedding");
+    assert_eq!(body["data"][1]["index"], 1);
+    let embedding = body["data"][0]["embedding"].as_array().unwrap();
+    assert!(body["usage"]["total_tokens"].is_u64());
+
+    let (status, body) = post(
+        format!("{base}/v1/embeddings"),
+        json!({ "input": "a", "model": "voyage-code-3", "encoding_format": "base64" }),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(body["model"], "voyage-code-3");
+    let requests = api.received_requests().await;
+    let sent: Value = serde_json::from_slice(&requests.last().unwrap().body).unwrap();
+    assert_eq!(sent["model"], "voyage-code-3");
+    let encoded = body["data"][0]["embedding"].as_str().unwrap();
+    assert_eq!(encoded.len(), (embedding.len() * 4).div_ceil(3) * 4);
+
+    let (status, body) = post(
+        format!("{base}/v1/embeddings"),
+        json!({ "input": "a", "dimensions": 7 }),
+    )
+    .await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"]["type"], "invalid_request_error");
+
+    let (status, body) = post(
+        format!("{base}/api/embed"),
+        json!({ "input": ["a", "b", "c"], "model": "nomic-embed-text" }),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(body["embeddings"].as_array().unwrap().len(), 3);
+}
@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use tower::{
+    limit::ConcurrencyLimitLayer, timeout::TimeoutLayer, Service, ServiceBuilder, ServiceExt,
+};
+use voyageai::{
+    models::{embeddings::EmbeddingsRequest, rerank::RerankRequest},
+    service::{VoyageRequest, VoyageResponse},
+    test_util::{
+        wiremock::{matchers::path, Mock, ResponseTemplate},
+        TestServer,
+    },
+    EmbeddingModel, EmbeddingsInput, RerankModel, VoyageError,
+};
+
+fn embeddings_request(texts: &[&str]) -> EmbeddingsRequest {
+    EmbeddingsRequest {
+        input: EmbeddingsInput::from(texts),
+        model: EmbeddingModel::Voyage3Large,
+        input_type: None,
+        truncation: None,
+        encoding_format: None,
+    }
+}
+
+fn rerank_request() -> RerankRequest {
+    RerankRequest::new(
+        "rust".to_string(),
+        vec!["cooking pasta".to_string(), "rust language".to_string()],
+        RerankModel::Rerank2,
+        None,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_embeddings_service_with_middleware() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+
+    let mut service = ServiceBuilder::new()
+        .layer(TimeoutLayer::new(Duration::from_secs(5)))
+        .layer(ConcurrencyLimitLayer::new(2))
+        .service(client.embeddings_service());
+
+    let response = service
+        .ready()
+        .await
+        .unwrap()
+        .call(embeddings_request(&["a", "b"]))
+        .await
+        .unwrap();
+    assert_eq!(response.data.len(), 2);
+}
+
+#[tokio::test]
+async fn test_rerank_service() {
+    let server = TestServer::start().await.with_rerank().await;
+    let client = server.client();
+
+    let response = client
+        .rerank_service()
+        .oneshot(rerank_request())
+        .await
+        .unwrap();
+    assert_eq!(response.data.len(), 2);
+    assert_eq!(response.data[0].index, 1);
+}
+
+#[tokio::test]
+async fn test_voyage_service_routes_requests() {
+    let server = TestServer::start()
+        .await
+        .with_embeddings()
+        .await
+        .with_rerank()
+        .await;
+    let service = server.client().service();
+
+    let embeddings = service
+        .clone()
+        .oneshot(VoyageRequest::from(embeddings_request(&["a"])))
+        .await
+        .unwrap();
+    assert!(matches!(embeddings, VoyageResponse::Embeddings(r) if r.data.len() == 1));
+
+    let rerank = service.oneshot(rerank_request().into()).await.unwrap();
+    assert!(matches!(rerank, VoyageResponse::Rerank(r) if r.data.len() == 2));
+}
+
+#[tokio::test]
+async fn test_errors_pass_through_middleware() {
+    let server = TestServer::start()
+        .await
+        .with_error("embeddings", 401, "bad key")
+        .await;
+    let result = server
+        .client()
+        .embeddings_service()
+        .oneshot(embeddings_request(&["a"]))
+        .await;
+    assert!(matches!(result, Err(VoyageError::Unauthorized)));
+}
+
+#[tokio::test]
+async fn test_timeout_layer_cancels_slow_requests() {
+    let server = TestServer::start().await;
+    Mock::given(path("/v1/embeddings"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+        .mount(server.mock_server())
+        .await;
+
+    let service = ServiceBuilder::new()
+        .timeout(Duration::from_millis(50))
+        .service(server.client().embeddings_service());
+    let error = service
+        .oneshot(embeddings_request(&["a"]))
+        .await
+        .unwrap_err();
+    assert!(error.is::<tower::timeout::error::Elapsed>());
+}
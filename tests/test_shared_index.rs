@@ -0,0 +1,146 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use voyageai::{
+    builder::search::IndexSource,
+    index::{HnswConfig, SharedIndex},
+    models::{Metadata, MetadataValue},
+    VectorIndex,
+};
+
+const DIMENSION: usize = 8;
+
+fn embedding(seed: usize) -> Vec<f32> {
+    (0..DIMENSION)
+        .map(|i| ((seed * 31 + i * 7) % 17) as f32 + 1.0)
+        .collect()
+}
+
+#[test]
+fn test_snapshots_are_isolated_from_writes() {
+    let index = SharedIndex::default();
+    index.insert("a", "alpha", embedding(0), None).unwrap();
+    let before = index.snapshot();
+
+    index.write(|index| {
+        index.insert("b", "beta", embedding(1), None).unwrap();
+        index.delete("a");
+    });
+
+    assert_eq!(before.len(), 1);
+    assert!(before.contains("a"));
+    let after = index.snapshot();
+    assert_eq!(after.len(), 1);
+    assert!(after.contains("b"));
+    // Without readers holding a snapshot, writes do not copy the index
+    drop((before, after));
+    let snapshot = Arc::as_ptr(&index.snapshot());
+    index.insert("c", "gamma", embedding(2), None).unwrap();
+    assert_eq!(Arc::as_ptr(&index.snapshot()), snapshot);
+}
+
+#[test]
+fn test_search_requests_resolve_the_latest_snapshot() {
+    let index = SharedIndex::new(VectorIndex::new());
+    let source = IndexSource::Shared(index.clone());
+    assert!(source.resolve().unwrap().is_empty());
+
+    index.insert("a", "alpha", embedding(0), None).unwrap();
+
+    assert_eq!(source.resolve().unwrap().len(), 1);
+    let matches = index.search(&embedding(0), 1, None).unwrap();
+    assert_eq!(matches[0].record.id, "a");
+}
+
+/// Readers search while a writer ingests documents in pairs and deletes
+/// older pairs. Every snapshot must hold whole pairs only and stay unchanged
+/// while it is searched.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_readers_and_writer() {
+    const PAIRS: usize = 300;
+    let index = SharedIndex::new(VectorIndex::new().with_hnsw(HnswConfig::default()));
+
+    let writer = index.clone();
+    let ingest = tokio::spawn(async move {
+        for pair in 0..PAIRS {
+            writer.write(|index| {
+                for side in ["left", "right"] {
+                    let metadata =
+                        Metadata::from([("pair".to_string(), MetadataValue::from(pair as i64))]);
+                    index
+                        .insert(
+                            format!("{pair}-{side}"),
+                            side,
+                            embedding(pair),
+                            Some(metadata),
+                        )
+                        .unwrap();
+                }
+                if pair % 10 == 9 {
+                    index.delete(&format!("{}-left", pair - 5));
+                    index.delete(&format!("{}-right", pair - 5));
+                }
+            });
+            tokio::task::yield_now().await;
+        }
+    });
+
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let index = index.clone();
+            tokio::spawn(async move {
+                let mut largest = 0;
+                let mut searches = 0;
+                loop {
+                    let snapshot = index.snapshot();
+                    let len = snapshot.len();
+                    assert_eq!(len % 2, 0, "a write was seen half done");
+                    for (record, _) in snapshot.iter() {
+                        let pair = record.id.split('-').next().unwrap();
+                        assert!(snapshot.contains(&format!("{pair}-left")));
+                        assert!(snapshot.contains(&format!("{pair}-right")));
+                    }
+                    let matches = snapshot.search(&embedding(searches), 10, None).unwrap();
+                    assert!(matches.len() <= len.min(10));
+                    assert!(matches.iter().all(|m| m.score.is_finite()));
+                    assert_eq!(snapshot.len(), len);
+                    largest = largest.max(len);
+                    searches += 1;
+                    if len == PAIRS * 2 - PAIRS / 10 * 2 {
+                        return (largest, searches);
+                    }
+                    tokio::task::yield_now().await;
+                }
+            })
+        })
+        .collect();
+
+    ingest.await.unwrap();
+    for reader in readers {
+        let (largest, searches) = reader.await.unwrap();
+        assert_eq!(largest, PAIRS * 2 - PAIRS / 10 * 2);
+        assert!(searches > 0);
+    }
+    assert_eq!(index.snapshot().tombstoned(), PAIRS / 10 * 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_background_eviction_of_a_shared_index() {
+    let index = SharedIndex::default();
+    index
+        .write(|index| {
+            index.insert_expiring(
+                "old",
+                "stale",
+                embedding(0),
+                None,
+                SystemTime::now() - Duration::from_secs(1),
+            )
+        })
+        .unwrap();
+    let handle = index.spawn_eviction(Duration::from_secs(10));
+
+    tokio::time::sleep(Duration::from_secs(11)).await;
+    assert_eq!(index.snapshot().iter().count(), 0);
+    handle.stop();
+}
@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use tokio_stream::StreamExt;
+use voyageai::{
+    client::{AutoBatcher, BulkDocument, BulkEmbedder},
+    models::embeddings::{EmbeddingsInput, EmbeddingsRequest},
+    traits::llm::Embedder,
+    EmbeddingModel, VoyageAiClient, VoyageError,
+};
+
+mod common;
+
+use common::{Reply, ScriptedTransport};
+
+/// A client whose requests are answered after `delay`.
+fn client(delay: Duration) -> VoyageAiClient {
+    ScriptedTransport::new(move |_| Reply::ok().after(delay)).client()
+}
+
+fn request() -> EmbeddingsRequest {
+    EmbeddingsRequest {
+        input: EmbeddingsInput::Single("hello".to_string()),
+        model: EmbeddingModel::Voyage3Large,
+        input_type: None,
+        truncation: None,
+        encoding_format: None,
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shutdown_drains_in_flight_requests() {
+    let client = client(Duration::from_millis(100));
+    let task = client.embeddings(request());
+    tokio::task::yield_now().await;
+
+    let report = client.shutdown(Duration::from_secs(1)).await;
+    assert!(report.drained);
+    assert_eq!(report.in_flight, 0);
+    assert_eq!(report.aborted, 0);
+    assert_eq!(task.await.unwrap().data.len(), 1);
+
+    // New requests are refused, by every client of the configuration
+    assert!(client.voyage_config().lifecycle.is_shutting_down());
+    let error = client
+        .embeddings_client()
+        .embed_query("late")
+        .await
+        .unwrap_err();
+    assert!(matches!(error, VoyageError::ShuttingDown));
+    assert!(client.embeddings(request()).await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_streams_started_before_shutdown_finish() {
+    let client = client(Duration::from_millis(100));
+    let texts: Vec<String> = (0..3).map(|i| format!("text {i}")).collect();
+    // One request at a time, so two start after the shutdown began
+    let stream = client.embeddings_client().embed_stream_with(texts, 1, 1);
+    let embeddings = tokio::spawn(stream.collect::<Vec<_>>());
+    tokio::task::yield_now().await;
+
+    let report = client.shutdown(Duration::from_secs(1)).await;
+    assert!(report.drained);
+    assert_eq!(embeddings.await.unwrap().len(), 3);
+
+    let late = client
+        .embeddings_client()
+        .embed_stream(vec!["late".to_string()]);
+    assert_eq!(late.collect::<Vec<_>>().await.len(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shutdown_aborts_tasks_after_timeout() {
+    let client = client(Duration::from_secs(10));
+    let task = client.embeddings(request());
+    tokio::task::yield_now().await;
+
+    let report = client.shutdown(Duration::from_millis(100)).await;
+    assert!(!report.drained);
+    assert_eq!(report.in_flight, 1);
+    assert_eq!(report.aborted, 1);
+    assert!(task.await.unwrap_err().to_string().contains("canceled"));
+    assert_eq!(client.voyage_config().lifecycle.in_flight(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shutdown_flushes_the_auto_batcher() {
+    let client = client(Duration::from_millis(10));
+    let batcher = AutoBatcher::new(&client).with_max_delay(Duration::from_secs(60));
+    let embedding = batcher.embed("queued");
+    tokio::task::yield_now().await;
+
+    let started = tokio::time::Instant::now();
+    let report = client.shutdown(Duration::from_secs(5)).await;
+    assert!(report.drained);
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(embedding.await.unwrap().len(), 1024);
+    assert!(matches!(
+        batcher.embed("late").await,
+        Err(VoyageError::ShuttingDown)
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shutdown_finishes_bulk_jobs() {
+    let client = client(Duration::from_millis(10));
+    let (sink, stream) = BulkEmbedder::new(&client)
+        .with_batch_size(10)
+        .start()
+        .unwrap();
+    for i in 0..3 {
+        sink.send(BulkDocument::new(format!("doc-{i}"), "text"))
+            .await
+            .unwrap();
+    }
+    tokio::task::yield_now().await;
+
+    // The partial batch is embedded although the sink is still open
+    let report = client.shutdown(Duration::from_secs(5)).await;
+    assert!(report.drained);
+    let embedded: Vec<_> = stream.collect().await;
+    assert_eq!(embedded.len(), 3);
+    assert!(matches!(
+        BulkEmbedder::new(&client).start(),
+        Err(VoyageError::ShuttingDown)
+    ));
+}
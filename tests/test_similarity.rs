@@ -0,0 +1,96 @@
+use voyageai::{
+    client::embeddings_client::cosine_similarity_matrix, cosine_similarity, test_util::TestServer,
+    try_cosine_similarity, SimilarityError, VoyageError,
+};
+
+#[tokio::test]
+async fn test_similarity_of_two_texts() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+
+    let close = client
+        .similarity("the cat sat on the mat", "the cat sat")
+        .await
+        .unwrap();
+    let far = client
+        .similarity("the cat sat on the mat", "quarterly revenue grew")
+        .await
+        .unwrap();
+    let swapped = client
+        .similarity("the cat sat", "the cat sat on the mat")
+        .await
+        .unwrap();
+
+    assert!(close > far, "{close} <= {far}");
+    assert!((close - swapped).abs() < 1e-6);
+
+    // Both texts go in one request, embedded as documents
+    let requests = server.received_requests().await;
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["input"].as_array().unwrap().len(), 2);
+    assert_eq!(body["input_type"], "document");
+}
+
+#[tokio::test]
+async fn test_similarity_matrix() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let texts: Vec<String> = ["red apples", "green apples", "stock market"]
+        .iter()
+        .map(|text| text.to_string())
+        .collect();
+
+    let matrix = client.similarity_matrix(&texts).await.unwrap();
+
+    assert_eq!(matrix.len(), 3);
+    for (i, row) in matrix.iter().enumerate() {
+        assert_eq!(row.len(), 3);
+        assert!((row[i] - 1.0).abs() < 1e-5);
+        for (j, similarity) in row.iter().enumerate() {
+            assert_eq!(*similarity, matrix[j][i]);
+        }
+    }
+    assert!(matrix[0][1] > matrix[0][2]);
+}
+
+#[tokio::test]
+async fn test_similarity_matrix_splits_large_inputs() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let texts: Vec<String> = (0..130).map(|i| format!("text {i}")).collect();
+
+    let matrix = client.similarity_matrix(&texts).await.unwrap();
+
+    assert_eq!(matrix.len(), 130);
+    assert_eq!(server.received_requests().await.len(), 2);
+    assert!(client.similarity_matrix(&[]).await.unwrap().is_empty());
+}
+
+#[test]
+fn test_cosine_similarity_matrix() {
+    let matrix = cosine_similarity_matrix(&[vec![1.0, 0.0], vec![0.0, 2.0], vec![1.0, 1.0]]);
+
+    assert_eq!(matrix[0][1], 0.0);
+    assert!((matrix[0][2] - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    assert_eq!(matrix[2][0], matrix[0][2]);
+}
+
+#[test]
+fn test_checked_cosine_similarity() {
+    assert!(
+        (try_cosine_similarity(&[1.0, 0.0], &[1.0, 1.0]).unwrap() - 0.5f32.sqrt()).abs() < 1e-6
+    );
+    assert_eq!(try_cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]), Ok(0.0));
+    assert_eq!(
+        try_cosine_similarity(&[1.0, 0.0], &[1.0]),
+        Err(SimilarityError::DimensionMismatch { left: 2, right: 1 })
+    );
+    assert_eq!(try_cosine_similarity(&[], &[]), Err(SimilarityError::Empty));
+    // The lenient version keeps returning 0.0
+    assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0]), 0.0);
+    let error: VoyageError = SimilarityError::Empty.into();
+    assert!(matches!(
+        error,
+        VoyageError::Similarity(SimilarityError::Empty)
+    ));
+}
@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use serde_json::json;
+use voyageai::{
+    models::embeddings::{EmbeddingsInput, EmbeddingsRequest},
+    EmbeddingModel, VoyageAiClient, VoyageConfig,
+};
+
+/// Serves an embedding after `delay`, long enough to observe requests in
+/// flight.
+async fn slow_server(delay: Duration) -> mockito::ServerGuard {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("POST", "/v1/embeddings")
+        .with_header("content-type", "application/json")
+        .with_body_from_request(move |_| {
+            std::thread::sleep(delay);
+            json!({
+                "object": "list",
+                "data": [{"object": "embedding", "embedding": vec![0.5f32; 1024], "index": 0}],
+                "model": "voyage-3-large",
+                "usage": {"total_tokens": 1}
+            })
+            .to_string()
+            .into()
+        })
+        .create_async()
+        .await;
+    server
+}
+
+fn client(server: &mockito::ServerGuard) -> VoyageAiClient {
+    VoyageAiClient::new_with_config(
+        VoyageConfig::new("key".to_string()).with_base_url(server.url()),
+    )
+}
+
+fn request() -> EmbeddingsRequest {
+    EmbeddingsRequest {
+        input: EmbeddingsInput::Single("hello".to_string()),
+        model: EmbeddingModel::Voyage3Large,
+        input_type: None,
+        truncation: None,
+        encoding_format: None,
+    }
+}
+
+async fn wait_until_idle(client: &VoyageAiClient) {
+    for _ in 0..100 {
+        if client.tasks().is_empty() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("{} tasks still running", client.tasks().len());
+}
+
+#[tokio::test]
+async fn test_completed_tasks_are_reaped() {
+    let server = slow_server(Duration::ZERO).await;
+    let client = client(&server);
+
+    let response = client.embeddings(request()).await.unwrap();
+    assert_eq!(response.data.len(), 1);
+    wait_until_idle(&client).await;
+}
+
+#[tokio::test]
+async fn test_dropping_future_aborts_task() {
+    let server = slow_server(Duration::from_millis(500)).await;
+    let client = client(&server);
+
+    let task = client.embeddings(request());
+    assert_eq!(client.tasks().len(), 1);
+
+    drop(task);
+    wait_until_idle(&client).await;
+}
+
+#[tokio::test]
+async fn test_abort_cancels_pending_futures() {
+    let server = slow_server(Duration::from_millis(500)).await;
+    let client = client(&server);
+
+    let task = client.embeddings(request());
+    client.abort().await;
+
+    assert!(client.tasks().is_empty());
+    let error = task.await.unwrap_err();
+    assert!(error.to_string().contains("canceled"));
+
+    // The client stays usable after an abort
+    assert!(client.embeddings(request()).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_dropping_client_cancels_pending_futures() {
+    let server = slow_server(Duration::from_millis(500)).await;
+    let client = client(&server);
+
+    let task = client.embeddings(request());
+    drop(client);
+
+    assert!(task.await.is_err());
+}
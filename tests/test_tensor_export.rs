@@ -0,0 +1,84 @@
+use voyageai::{
+    io::{read_npy, read_safetensors, write_npy, write_npz, write_safetensors, EmbeddingRecord},
+    VoyageError,
+};
+
+fn records() -> Vec<EmbeddingRecord> {
+    (0..4)
+        .map(|i| EmbeddingRecord::new(format!("doc-{i}"), "voyage-3", vec![i as f32, -0.5, 0.25]))
+        .collect()
+}
+
+#[test]
+fn test_npy_round_trip() -> Result<(), VoyageError> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("embeddings.npy");
+    let vectors: Vec<Vec<f32>> = records().into_iter().map(|record| record.vector).collect();
+    write_npy(&path, &vectors)?;
+
+    let bytes = std::fs::read(&path).unwrap();
+    assert!(bytes.starts_with(b"\x93NUMPY\x01\x00"));
+    let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    assert_eq!((10 + header_len) % 64, 0);
+    let header = std::str::from_utf8(&bytes[10..10 + header_len]).unwrap();
+    assert!(header.contains("'descr': '<f4'"));
+    assert!(header.contains("'shape': (4, 3)"));
+    assert_eq!(bytes.len(), 10 + header_len + 4 * 3 * 4);
+
+    assert_eq!(read_npy(&path)?, vectors);
+    Ok(())
+}
+
+#[test]
+fn test_npz_holds_embeddings_and_ids() -> Result<(), VoyageError> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("embeddings.npz");
+    write_npz(&path, &records())?;
+
+    let bytes = std::fs::read(&path).unwrap();
+    assert!(bytes.starts_with(b"PK\x03\x04"));
+    let contains = |needle: &[u8]| bytes.windows(needle.len()).any(|window| window == needle);
+    assert!(contains(b"embeddings.npy"));
+    assert!(contains(b"ids.npy"));
+    assert!(contains(b"'descr': '<U5'"));
+    let end = &bytes[bytes.len() - 22..];
+    assert!(end.starts_with(b"PK\x05\x06"));
+    assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+    Ok(())
+}
+
+#[test]
+fn test_safetensors_round_trip() -> Result<(), VoyageError> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("embeddings.safetensors");
+    write_safetensors(&path, &records())?;
+
+    let bytes = std::fs::read(&path).unwrap();
+    let header_len = u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize;
+    assert_eq!(header_len % 8, 0);
+    let header: serde_json::Value = serde_json::from_slice(&bytes[8..8 + header_len]).unwrap();
+    assert_eq!(header["embeddings"]["dtype"], "F32");
+    assert_eq!(header["embeddings"]["shape"], serde_json::json!([4, 3]));
+    assert_eq!(header["__metadata__"]["model"], "voyage-3");
+
+    assert_eq!(read_safetensors(&path)?, records());
+    Ok(())
+}
+
+#[test]
+fn test_exports_reject_mixed_dimensions() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut records = records();
+    records[2].vector.push(1.0);
+    records[2].dim = 4;
+
+    assert!(matches!(
+        write_npz(dir.path().join("e.npz"), &records),
+        Err(VoyageError::EmbeddingDimensionMismatch { index: 2, .. })
+    ));
+    assert!(matches!(
+        write_safetensors(dir.path().join("e.safetensors"), &records),
+        Err(VoyageError::EmbeddingDimensionMismatch { index: 2, .. })
+    ));
+    assert!(read_npy(dir.path().join("missing.npy")).is_err());
+}
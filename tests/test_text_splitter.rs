@@ -0,0 +1,94 @@
+use voyageai::{
+    chunking::{Chunker, ParagraphChunker},
+    text_splitter::{CharacterSplitter, SentenceSplitter, TextChunk, TextSplitter, TokenSplitter},
+};
+
+fn texts(chunks: &[TextChunk]) -> Vec<&str> {
+    chunks.iter().map(|chunk| chunk.text.as_str()).collect()
+}
+
+fn assert_offsets(text: &str, chunks: &[TextChunk]) {
+    for chunk in chunks {
+        assert_eq!(&text[chunk.range()], chunk.text);
+    }
+}
+
+#[test]
+fn test_character_splitter_breaks_between_words() {
+    let text = "  alpha beta gamma delta  ";
+    let chunks = CharacterSplitter::new(12).split(text);
+
+    assert_eq!(texts(&chunks), vec!["alpha beta", "gamma delta"]);
+    assert_eq!((chunks[0].start, chunks[0].end), (2, 12));
+    assert_offsets(text, &chunks);
+}
+
+#[test]
+fn test_character_splitter_overlap_and_long_words() {
+    let text = "one two three four";
+    let chunks = CharacterSplitter::new(9).with_overlap(4).split(text);
+    assert_eq!(texts(&chunks), vec!["one two", "two three", "four"]);
+    assert_offsets(text, &chunks);
+
+    let chunks = CharacterSplitter::new(4).split("abcdefghij");
+    assert_eq!(texts(&chunks), vec!["abcd", "efgh", "ij"]);
+}
+
+#[test]
+fn test_sentence_splitter_keeps_sentences_whole() {
+    let text = "Rust is fast. Rust is safe. Rust is fun.";
+    let chunks = SentenceSplitter::new(30).split(text);
+    assert_eq!(
+        texts(&chunks),
+        vec!["Rust is fast. Rust is safe.", "Rust is fun."]
+    );
+    assert_offsets(text, &chunks);
+
+    let chunks = SentenceSplitter::new(30).with_overlap(14).split(text);
+    assert_eq!(
+        texts(&chunks),
+        vec!["Rust is fast. Rust is safe.", "Rust is safe. Rust is fun."]
+    );
+    assert_offsets(text, &chunks);
+}
+
+#[test]
+fn test_sentence_splitter_handles_multibyte_text() {
+    let text = "Größe zählt. Äpfel schmecken süß! Ende.";
+    let chunks = SentenceSplitter::new(15).split(text);
+
+    assert_eq!(
+        texts(&chunks),
+        vec!["Größe zählt.", "Äpfel schmecken", "süß! Ende."]
+    );
+    assert_offsets(text, &chunks);
+}
+
+#[test]
+fn test_token_splitter_counts_tokens() {
+    fn words(text: &str) -> usize {
+        text.split_whitespace().count()
+    }
+    let text = "a b c d e f g";
+    let chunks = TokenSplitter::new(3)
+        .with_overlap(1)
+        .with_counter(words)
+        .split(text);
+
+    assert_eq!(texts(&chunks), vec!["a b c", "c d e", "e f g"]);
+    assert_offsets(text, &chunks);
+}
+
+#[test]
+fn test_splitters_are_chunkers() {
+    assert!(CharacterSplitter::new(10).chunk("   ").is_empty());
+    assert_eq!(
+        TokenSplitter::new(2).chunk("abcd efgh"),
+        vec!["abcd", "efgh"]
+    );
+    // Oversized paragraphs are split at sentence boundaries
+    assert_eq!(
+        ParagraphChunker::new(20).chunk("Short one.\n\nFirst sentence. Second sentence."),
+        vec!["Short one.", "First sentence.", "Second sentence."]
+    );
+}
@@ -0,0 +1,152 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::time::Instant;
+use voyageai::{
+    client::{Priority, RateLimit, RateLimitEndpoint, RateLimiter},
+    test_util::TestServer,
+    traits::llm::Embedder,
+    VoyageBuilder, VoyageError,
+};
+
+const EMBEDDINGS: RateLimitEndpoint = RateLimitEndpoint::Embeddings;
+
+/// 60 requests and 6000 tokens per minute: one request and 100 tokens per
+/// second.
+fn limiter(request_burst: u32, token_burst: u32) -> RateLimiter {
+    RateLimiter::new().with_limit(
+        EMBEDDINGS,
+        RateLimit::new(60, 6_000).with_burst(request_burst, token_burst),
+    )
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_burst_then_steady_rate() {
+    let limiter = limiter(3, 6_000);
+    let start = Instant::now();
+
+    for _ in 0..3 {
+        assert!(limiter.acquire(EMBEDDINGS, 1).await.is_zero());
+    }
+    // The fourth request waits for one request to refill
+    let waited = limiter.acquire(EMBEDDINGS, 1).await;
+    assert_eq!(waited.as_secs(), 1);
+    assert_eq!(start.elapsed().as_secs(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tokens_refill_continuously() {
+    let limiter = limiter(60, 1_000);
+
+    limiter.acquire(EMBEDDINGS, 1_000).await;
+    let waited = limiter.acquire(EMBEDDINGS, 250).await;
+    assert_eq!(waited.as_millis(), 2_500);
+
+    // Oversized requests wait for a full bucket rather than forever
+    let waited = limiter.acquire(EMBEDDINGS, 5_000).await;
+    assert_eq!(waited.as_secs(), 10);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_endpoints_have_separate_buckets() {
+    let limiter = limiter(1, 6_000);
+
+    limiter.acquire(EMBEDDINGS, 1).await;
+    assert!(limiter
+        .acquire(RateLimitEndpoint::Reranking, 1)
+        .await
+        .is_zero());
+    assert_eq!(
+        limiter
+            .limit(RateLimitEndpoint::Reranking)
+            .requests_per_minute,
+        100
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_acquire_within_fails_fast() {
+    let limiter = limiter(1, 6_000);
+    limiter.acquire(EMBEDDINGS, 1).await;
+    let start = Instant::now();
+
+    let error = limiter
+        .acquire_within(EMBEDDINGS, 1, Priority::Normal, Duration::from_millis(500))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, VoyageError::RateLimitExceeded { reset_in, .. } if reset_in.as_secs() == 1)
+    );
+    assert!(start.elapsed().is_zero());
+
+    let waited = limiter
+        .acquire_within(EMBEDDINGS, 1, Priority::Normal, Duration::from_secs(2))
+        .await
+        .unwrap();
+    assert_eq!(waited.as_secs(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_waiters_are_served_in_order() {
+    let limiter = limiter(1, 6_000);
+    limiter.acquire(EMBEDDINGS, 1).await;
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    // A large request queued first is not overtaken by small ones
+    let mut tasks = Vec::new();
+    for (id, tokens) in [(0, 6_000), (1, 1), (2, 1)] {
+        let limiter = limiter.clone();
+        let order = order.clone();
+        tasks.push(tokio::spawn(async move {
+            limiter.acquire(EMBEDDINGS, tokens).await;
+            order.lock().unwrap().push(id);
+        }));
+        tokio::task::yield_now().await;
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_usage_corrects_estimate() {
+    let limiter = limiter(60, 1_000);
+
+    limiter.acquire(EMBEDDINGS, 100).await;
+    limiter.record_usage(EMBEDDINGS, 100, 1_000).await;
+    // The bucket is now empty: 900 more tokens were used than estimated
+    assert_eq!(limiter.acquire(EMBEDDINGS, 100).await.as_secs(), 1);
+
+    limiter.record_usage(EMBEDDINGS, 100, 0).await;
+    assert!(limiter.acquire(EMBEDDINGS, 100).await.is_zero());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rejection_pauses_endpoint() {
+    let limiter = limiter(60, 6_000);
+
+    limiter
+        .record_rejection(EMBEDDINGS, 1, Duration::from_secs(5))
+        .await;
+    assert_eq!(limiter.acquire(EMBEDDINGS, 1).await.as_secs(), 5);
+    assert!(limiter.acquire(EMBEDDINGS, 1).await.is_zero());
+}
+
+#[tokio::test]
+async fn test_client_honors_rate_limit_timeout() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = VoyageBuilder::new()
+        .with_api_key("key")
+        .with_base_url(server.uri())
+        .with_rate_limiter(limiter(1, 6_000))
+        .with_rate_limit_timeout(Duration::from_millis(100))
+        .build()
+        .unwrap();
+
+    assert!(client.embed("first").await.is_ok());
+    // The next request slot is a second away
+    let error = client.embed("second").await.unwrap_err();
+    assert!(matches!(error, VoyageError::RateLimitExceeded { .. }));
+    assert_eq!(server.received_requests().await.len(), 1);
+}
@@ -0,0 +1,139 @@
+use voyageai::{
+    index::{HnswConfig, NamespacedIndex},
+    models::{Metadata, MetadataFilter, MetadataValue},
+    VectorIndex,
+};
+
+fn feed() -> VectorIndex {
+    let mut index = VectorIndex::new();
+    for (id, source, embedding) in [
+        ("a", "blog", vec![1.0, 0.0, 0.0]),
+        ("b", "news", vec![0.9, 0.1, 0.0]),
+        ("c", "news", vec![0.0, 1.0, 0.0]),
+        ("d", "blog", vec![0.0, 0.0, 1.0]),
+    ] {
+        let metadata = Metadata::from([("source".to_string(), MetadataValue::from(source))]);
+        index
+            .insert(id, format!("text of {id}"), embedding, Some(metadata))
+            .unwrap();
+    }
+    index
+}
+
+fn ids(index: &VectorIndex, query: &[f32]) -> Vec<String> {
+    index
+        .search(query, 10, None)
+        .unwrap()
+        .into_iter()
+        .map(|m| m.record.id.clone())
+        .collect()
+}
+
+#[test]
+fn test_deleted_documents_disappear_from_reads() {
+    let mut index = feed();
+
+    assert!(index.delete("a"));
+    assert!(!index.delete("a"));
+    assert!(!index.delete("missing"));
+
+    assert_eq!(index.len(), 3);
+    assert_eq!(index.tombstoned(), 1);
+    assert!(index.get("a").is_none());
+    assert!(!index.contains("a"));
+    assert_eq!(ids(&index, &[1.0, 0.0, 0.0]), ["b", "c", "d"]);
+    assert_eq!(index.iter().count(), 3);
+}
+
+#[test]
+fn test_delete_where_tombstones_matching_documents() {
+    let mut index = feed();
+
+    assert_eq!(index.delete_where(&MetadataFilter::eq("source", "news")), 2);
+    // Already deleted documents are not counted again
+    assert_eq!(index.delete_where(&MetadataFilter::eq("source", "news")), 0);
+
+    assert_eq!(index.tombstoned(), 2);
+    assert_eq!(ids(&index, &[0.9, 0.1, 0.0]), ["a", "d"]);
+}
+
+#[test]
+fn test_reinserting_revives_a_deleted_id() {
+    let mut index = feed();
+    index.delete("c");
+
+    index
+        .insert("c", "new c", vec![0.0, 1.0, 0.0], None)
+        .unwrap();
+
+    assert_eq!(index.tombstoned(), 0);
+    assert_eq!(index.get("c").unwrap().0.text, "new c");
+}
+
+#[test]
+fn test_compact_reclaims_space() -> Result<(), Box<dyn std::error::Error>> {
+    let mut index = feed().with_hnsw(HnswConfig::default());
+    index.delete("a");
+    index.delete("c");
+    let mut before = Vec::new();
+    index.write_to(&mut before)?;
+
+    assert_eq!(index.compact(), 2);
+    assert_eq!(index.compact(), 0);
+
+    assert_eq!(index.len(), 2);
+    assert_eq!(index.tombstoned(), 0);
+    assert_eq!(ids(&index, &[1.0, 0.0, 0.0]), ["b", "d"]);
+    assert_eq!(index.get("d").unwrap().1, &[0.0, 0.0, 1.0]);
+    let mut after = Vec::new();
+    index.write_to(&mut after)?;
+    assert!(after.len() < before.len());
+    Ok(())
+}
+
+#[test]
+fn test_tombstones_survive_save_and_load() -> Result<(), Box<dyn std::error::Error>> {
+    let mut index = feed();
+    index.delete("b");
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("feed.idx");
+    index.save(&path)?;
+
+    let mut loaded = VectorIndex::load(&path)?;
+
+    assert_eq!(loaded, index);
+    assert_eq!(VectorIndex::inspect(&path)?.count, 4);
+    assert_eq!(loaded.tombstoned(), 1);
+    assert!(loaded.get("b").is_none());
+    loaded.compact();
+    loaded.save(&path)?;
+    assert_eq!(VectorIndex::inspect(&path)?.count, 3);
+    Ok(())
+}
+
+#[test]
+fn test_namespaced_delete_and_compact() {
+    let mut index = NamespacedIndex::new();
+    index
+        .insert("acme", "1", "invoice", vec![1.0, 0.0], None)
+        .unwrap();
+    index
+        .insert("acme", "2", "contract", vec![0.0, 1.0], None)
+        .unwrap();
+    index
+        .insert("globex", "1", "invoice", vec![1.0, 0.0], None)
+        .unwrap();
+
+    assert!(index.delete("acme", "1"));
+    assert!(!index.delete("initech", "1"));
+    assert_eq!(
+        index.delete_where("globex", &MetadataFilter::eq("source", "news")),
+        0
+    );
+    let stats = index.stats("acme").unwrap();
+    assert_eq!((stats.documents, stats.tombstoned), (1, 1));
+    assert_eq!(index.stats("globex").unwrap().documents, 1);
+
+    assert_eq!(index.compact(), 1);
+    assert_eq!(index.stats("acme").unwrap().tombstoned, 0);
+}
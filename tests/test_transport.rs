@@ -0,0 +1,124 @@
+use std::sync::{Arc, Mutex};
+
+use voyageai::{
+    client::rerank_client::RerankRequestBuilder,
+    test_util::{fixtures, TestServer},
+    transport::{
+        HttpRequest, HttpResponse, Method, ReqwestTransport, StatusCode, Transport, TransportFuture,
+    },
+    VoyageAiClient, VoyageBuilder, VoyageConfig, VoyageError,
+};
+
+/// Answers every request with `body` and records the requests.
+#[derive(Debug, Default)]
+struct CannedTransport {
+    body: &'static str,
+    requests: Arc<Mutex<Vec<HttpRequest>>>,
+}
+
+impl Transport for CannedTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture {
+        self.requests.lock().unwrap().push(request);
+        let body = self.body.as_bytes().to_vec();
+        TransportFuture::new(async move {
+            Ok(HttpResponse {
+                status: StatusCode::OK,
+                headers: Default::default(),
+                body,
+            })
+        })
+    }
+}
+
+/// Adds a header to every request before handing it to `inner`.
+#[derive(Debug)]
+struct TaggingTransport {
+    inner: ReqwestTransport,
+}
+
+impl Transport for TaggingTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture {
+        match request.header("x-tenant", "acme") {
+            Ok(request) => self.inner.send(request),
+            Err(e) => TransportFuture::new(async move { Err(e) }),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct OfflineTransport;
+
+impl Transport for OfflineTransport {
+    fn send(&self, _request: HttpRequest) -> TransportFuture {
+        TransportFuture::new(async {
+            Err(VoyageError::TransportError(
+                "network unreachable".to_string(),
+            ))
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_requests_go_through_the_transport() {
+    let transport = CannedTransport {
+        body: fixtures::EMBEDDINGS_SINGLE,
+        ..Default::default()
+    };
+    let requests = transport.requests.clone();
+    let config = VoyageConfig::new("test-key".to_string())
+        .with_base_url("http://transport.invalid")
+        .with_transport(transport);
+    let client = VoyageAiClient::new_with_config(config);
+
+    let embedding = client
+        .config
+        .embeddings_client
+        .embed_query("hello")
+        .await
+        .unwrap();
+    assert!(!embedding.is_empty());
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(request.url, "http://transport.invalid/v1/embeddings");
+    assert_eq!(request.headers["authorization"], "Bearer test-key");
+    assert_eq!(request.headers["content-type"], "application/json");
+    let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+    assert_eq!(body["input"], "hello");
+    // The API key is not shown when requests are logged
+    assert!(!format!("{request:?}").contains("test-key"));
+}
+
+#[tokio::test]
+async fn test_custom_transport_wrapping_the_default() {
+    let server = TestServer::start().await.with_rerank().await;
+    let client = VoyageBuilder::new()
+        .with_config(server.config())
+        .with_transport(TaggingTransport {
+            inner: ReqwestTransport::default(),
+        })
+        .build()
+        .unwrap();
+
+    let request = RerankRequestBuilder::new()
+        .query("pets")
+        .add_documents(vec!["cats".to_string(), "rust".to_string()])
+        .build()
+        .unwrap();
+    let response = client.config.rerank_client.rerank(request).await.unwrap();
+    assert_eq!(response.data.len(), 2);
+    let received = server.received_requests().await;
+    assert_eq!(received[0].headers.get("x-tenant").unwrap(), "acme");
+}
+
+#[tokio::test]
+async fn test_transport_errors_are_returned() {
+    let config = VoyageConfig::new("test-key".to_string()).with_transport(OfflineTransport);
+    let client = VoyageAiClient::new_with_config(config);
+    let result = client.config.embeddings_client.embed_query("hello").await;
+    assert!(
+        matches!(result, Err(VoyageError::TransportError(message)) if message == "network unreachable")
+    );
+}
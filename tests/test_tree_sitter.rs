@@ -0,0 +1,340 @@
+#![cfg(feature = "tree-sitter")]
+
+use std::path::Path;
+
+use voyageai::{
+    code::{
+        parser_for_path, CodeChunk, CodeChunker, CodeItemKind, GoParser, JavaParser,
+        LanguageParser, PythonParser, TypeScriptParser,
+    },
+    models::MetadataValue,
+    retrieval::RetrievalPipeline,
+    test_util::TestServer,
+    Chunker,
+};
+
+const PYTHON: &str = r#"import math
+
+
+class Circle:
+    """A circle."""
+
+    unit = 1.0
+
+    def __init__(self, radius):
+        self.radius = radius
+
+    @property
+    def area(self):
+        """Area of the circle."""
+        return math.pi * self.radius ** 2
+
+
+def unit_circle():
+    return Circle(1.0)
+"#;
+
+const TYPESCRIPT: &str = r#"import { pi } from "./math";
+
+/** A circle. */
+export class Circle {
+  constructor(public radius: number) {}
+
+  /** Area of the circle. */
+  area(): number {
+    return pi * this.radius ** 2;
+  }
+
+  private scale(factor: number): void {
+    this.radius *= factor;
+  }
+}
+
+export interface Shape {
+  area(): number;
+}
+
+type Radius = number;
+
+export const unitCircle = () => new Circle(1);
+"#;
+
+const GO: &str = r#"package shapes
+
+import "math"
+
+// Circle is a circle.
+// Its radius is in meters.
+type Circle struct {
+	Radius float64
+}
+
+// Area of the circle.
+func (c *Circle) Area() float64 {
+	return math.Pi * c.Radius * c.Radius
+}
+
+type (
+	Shape interface {
+		Area() float64
+	}
+	radius = float64
+)
+
+func unitCircle() Circle {
+	return Circle{Radius: 1}
+}
+"#;
+
+const JAVA: &str = r#"package com.example.shapes;
+
+/** A circle. */
+public class Circle {
+    private final double radius;
+
+    public Circle(double radius) {
+        this.radius = radius;
+    }
+
+    /** Area of the circle. */
+    @Override
+    public double area() {
+        return Math.PI * radius * radius;
+    }
+
+    enum Unit {
+        METERS;
+
+        String symbol() {
+            return "m";
+        }
+    }
+}
+"#;
+
+fn summary(chunks: &[CodeChunk]) -> Vec<(String, CodeItemKind, usize, usize)> {
+    chunks
+        .iter()
+        .map(|chunk| {
+            (
+                chunk.qualified_name(),
+                chunk.kind,
+                chunk.start_line,
+                chunk.end_line,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_python_items() {
+    let chunks = PythonParser
+        .parse_items(PYTHON, &["shapes".to_string()])
+        .unwrap();
+    assert_eq!(
+        summary(&chunks),
+        [
+            ("shapes.Circle".to_string(), CodeItemKind::Class, 4, 7),
+            (
+                "shapes.Circle.__init__".to_string(),
+                CodeItemKind::Method,
+                9,
+                10
+            ),
+            (
+                "shapes.Circle.area".to_string(),
+                CodeItemKind::Method,
+                12,
+                15
+            ),
+            (
+                "shapes.unit_circle".to_string(),
+                CodeItemKind::Function,
+                18,
+                19
+            ),
+        ]
+    );
+    assert_eq!(chunks[0].docs.as_deref(), Some("A circle."));
+    assert!(chunks[2].text.starts_with("    @property\n    def area"));
+    assert_eq!(chunks[2].docs.as_deref(), Some("Area of the circle."));
+    assert_eq!(chunks[3].docs, None);
+    assert!(chunks[3]
+        .text_with_path()
+        .starts_with("# shapes.unit_circle\ndef unit_circle"));
+}
+
+#[test]
+fn test_typescript_items() {
+    let chunks = TypeScriptParser::new()
+        .parse_items(TYPESCRIPT, &["shapes".to_string()])
+        .unwrap();
+    assert_eq!(
+        summary(&chunks),
+        [
+            ("shapes.Circle".to_string(), CodeItemKind::Class, 3, 4),
+            (
+                "shapes.Circle.constructor".to_string(),
+                CodeItemKind::Method,
+                5,
+                5
+            ),
+            (
+                "shapes.Circle.area".to_string(),
+                CodeItemKind::Method,
+                7,
+                10
+            ),
+            (
+                "shapes.Circle.scale".to_string(),
+                CodeItemKind::Method,
+                12,
+                14
+            ),
+            ("shapes.Shape".to_string(), CodeItemKind::Interface, 17, 19),
+            ("shapes.Radius".to_string(), CodeItemKind::TypeAlias, 21, 21),
+            (
+                "shapes.unitCircle".to_string(),
+                CodeItemKind::Function,
+                23,
+                23
+            ),
+        ]
+    );
+    assert_eq!(chunks[0].visibility, "export");
+    assert_eq!(chunks[0].docs.as_deref(), Some("A circle."));
+    assert_eq!(chunks[2].docs.as_deref(), Some("Area of the circle."));
+    assert_eq!(chunks[3].visibility, "private");
+    assert_eq!(chunks[5].visibility, "");
+
+    let tsx = TypeScriptParser::tsx()
+        .parse_items("export function App() {\n  return <div />;\n}\n", &[])
+        .unwrap();
+    assert_eq!(
+        summary(&tsx),
+        [("App".to_string(), CodeItemKind::Function, 1, 3)]
+    );
+}
+
+#[test]
+fn test_go_items() {
+    let chunks = GoParser.parse_items(GO, &["shapes".to_string()]).unwrap();
+    assert_eq!(
+        summary(&chunks),
+        [
+            ("shapes.Circle".to_string(), CodeItemKind::Struct, 5, 9),
+            (
+                "shapes.Circle.Area".to_string(),
+                CodeItemKind::Method,
+                11,
+                14
+            ),
+            ("shapes.Shape".to_string(), CodeItemKind::Interface, 17, 19),
+            ("shapes.radius".to_string(), CodeItemKind::TypeAlias, 20, 20),
+            (
+                "shapes.unitCircle".to_string(),
+                CodeItemKind::Function,
+                23,
+                25
+            ),
+        ]
+    );
+    assert_eq!(
+        chunks[0].docs.as_deref(),
+        Some("Circle is a circle.\nIts radius is in meters.")
+    );
+    assert_eq!(chunks[1].visibility, "exported");
+    assert_eq!(chunks[4].visibility, "");
+    assert_eq!(
+        GoParser.module_path(Path::new("internal/shapes/circle.go")),
+        ["internal", "shapes"]
+    );
+}
+
+#[test]
+fn test_java_items() {
+    let chunks = JavaParser
+        .parse_items(JAVA, &["com".to_string(), "example".to_string()])
+        .unwrap();
+    assert_eq!(
+        summary(&chunks),
+        [
+            ("com.example.Circle".to_string(), CodeItemKind::Class, 3, 5),
+            (
+                "com.example.Circle.Circle".to_string(),
+                CodeItemKind::Method,
+                7,
+                9
+            ),
+            (
+                "com.example.Circle.area".to_string(),
+                CodeItemKind::Method,
+                11,
+                15
+            ),
+            (
+                "com.example.Circle.Unit".to_string(),
+                CodeItemKind::Enum,
+                17,
+                18
+            ),
+            (
+                "com.example.Circle.Unit.symbol".to_string(),
+                CodeItemKind::Method,
+                20,
+                22
+            ),
+        ]
+    );
+    assert_eq!(chunks[0].visibility, "public");
+    assert_eq!(chunks[2].docs.as_deref(), Some("Area of the circle."));
+    assert_eq!(chunks[3].visibility, "");
+    assert_eq!(
+        JavaParser.module_path(Path::new("core/src/main/java/com/example/Circle.java")),
+        ["com", "example"]
+    );
+}
+
+#[test]
+fn test_chunker_picks_parser_by_extension() {
+    let language = |path: &str| parser_for_path(Path::new(path)).map(|parser| parser.language());
+    assert_eq!(language("app/models.py"), Some("python"));
+    assert_eq!(language("web/App.tsx"), Some("typescript"));
+    assert_eq!(language("main.go"), Some("go"));
+    assert_eq!(language("README.md"), None);
+
+    let chunker = CodeChunker::for_file("src/shapes/__init__.py").unwrap();
+    assert_eq!(chunker.module_path(), ["shapes"]);
+    assert_eq!(chunker.chunk(PYTHON).len(), 4);
+    // No items: packed into paragraph chunks
+    assert_eq!(
+        chunker.chunk("Just prose.\n\nMore prose."),
+        ["Just prose.\n\nMore prose."]
+    );
+}
+
+#[tokio::test]
+async fn test_pipeline_stores_item_locations() {
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let mut pipeline = RetrievalPipeline::builder(&client).without_rerank().build();
+
+    let stored = pipeline
+        .add_code_file("src/main/java/com/example/Circle.java", JAVA, None)
+        .await
+        .unwrap();
+    assert_eq!(stored, 5);
+
+    let hits = pipeline.query("area of the circle").await.unwrap();
+    let area = hits
+        .iter()
+        .find(|hit| hit.text.contains("double area()"))
+        .expect("the area method is indexed");
+    let metadata = area.metadata.as_ref().unwrap();
+    assert_eq!(
+        metadata.get("item"),
+        Some(&MetadataValue::from("com.example.Circle.area"))
+    );
+    assert_eq!(metadata.get("language"), Some(&MetadataValue::from("java")));
+    assert_eq!(metadata.get("start_line"), Some(&MetadataValue::from(11.0)));
+}
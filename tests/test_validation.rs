@@ -0,0 +1,241 @@
+use std::sync::Arc;
+
+use tokio_stream::StreamExt;
+use voyageai::{
+    client::{
+        rerank_client::{
+            DefaultRerankClient, RerankClient, RerankRequestBuilder as ClientRerankRequestBuilder,
+        },
+        RateLimiter,
+    },
+    models::rerank::RerankValidationError,
+    EmbeddingModel, EmbeddingsRequestBuilder, InputType, RerankModel, RerankRequest,
+    RerankRequestBuilder, Violation, VoyageConfig, VoyageError,
+};
+
+#[test]
+fn test_embeddings_builder_accepts_valid_request() {
+    let request = EmbeddingsRequestBuilder::new()
+        .documents(["hello", "world"])
+        .model(EmbeddingModel::Voyage3Large)
+        .input_type(InputType::Document)
+        .build();
+
+    assert!(request.is_ok());
+}
+
+#[test]
+fn test_embeddings_builder_reports_missing_fields() {
+    let error = EmbeddingsRequestBuilder::new().build().unwrap_err();
+
+    assert_eq!(
+        error.violations,
+        vec![
+            Violation::MissingField("input"),
+            Violation::MissingField("model")
+        ]
+    );
+}
+
+#[test]
+fn test_embeddings_builder_reports_every_violation() {
+    let mut documents = vec!["ok".to_string(), "  ".to_string(), "x".repeat(200_000)];
+    documents.extend((0..130).map(|i| format!("doc {i}")));
+
+    let error = EmbeddingsRequestBuilder::new()
+        .documents(documents)
+        .model(EmbeddingModel::Voyage3Large)
+        .input_type(InputType::Code)
+        .build()
+        .unwrap_err();
+
+    assert!(error.contains(&Violation::EmptyText { index: 1 }));
+    assert!(error.contains(&Violation::TooManyInputs {
+        count: 133,
+        max: 128
+    }));
+    assert!(error.contains(&Violation::InputTooLong {
+        index: 2,
+        tokens: 50_000,
+        max: 32_000
+    }));
+    assert!(error.contains(&Violation::IncompatibleInputType {
+        input_type: InputType::Code,
+        model: EmbeddingModel::Voyage3Large
+    }));
+    assert_eq!(error.violations.len(), 4);
+    let message = error.to_string();
+    assert!(message.contains("input 1 is empty"));
+    assert!(message.contains("not supported by voyage-3-large"));
+}
+
+#[test]
+fn test_embeddings_builder_rejects_empty_input() {
+    let error = EmbeddingsRequestBuilder::new()
+        .documents(Vec::<String>::new())
+        .model(EmbeddingModel::VoyageCode3)
+        .input_type(InputType::Code)
+        .build()
+        .unwrap_err();
+
+    assert_eq!(error.violations, vec![Violation::EmptyInput]);
+}
+
+#[test]
+fn test_rerank_builder_reports_every_violation() {
+    let documents: Vec<String> = (0..1001)
+        .map(|i| {
+            if i == 5 {
+                "y".repeat(70_000)
+            } else {
+                format!("doc {i}")
+            }
+        })
+        .collect();
+
+    let error = RerankRequestBuilder::new()
+        .query("q".repeat(20_000))
+        .documents(documents)
+        .model(RerankModel::Rerank2)
+        .build()
+        .unwrap_err();
+
+    assert!(
+        error.contains(&Violation::Rerank(RerankValidationError::QueryTooLong {
+            tokens: 5_000,
+            max: 4_000
+        }))
+    );
+    assert!(error.contains(&Violation::Rerank(
+        RerankValidationError::TooManyDocuments {
+            count: 1001,
+            max: 1000
+        }
+    )));
+    assert!(
+        error.contains(&Violation::Rerank(RerankValidationError::DocumentTooLong {
+            index: 5,
+            tokens: 22_500,
+            max: 16_000
+        }))
+    );
+}
+
+#[test]
+fn test_rerank_builders_report_missing_query() {
+    let error = RerankRequestBuilder::new()
+        .documents(vec!["doc".to_string()])
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        error.violations,
+        vec![
+            Violation::Rerank(RerankValidationError::MissingQuery),
+            Violation::MissingField("model")
+        ]
+    );
+
+    let error = ClientRerankRequestBuilder::new().build().unwrap_err();
+    assert_eq!(
+        error.violations,
+        vec![
+            Violation::Rerank(RerankValidationError::MissingQuery),
+            Violation::Rerank(RerankValidationError::EmptyDocuments)
+        ]
+    );
+}
+
+#[test]
+fn test_rerank_request_new_validates() {
+    let documents = vec!["doc".to_string()];
+
+    assert!(RerankRequest::new(
+        "query".into(),
+        documents.clone(),
+        RerankModel::Rerank2,
+        None
+    )
+    .is_ok());
+    assert_eq!(
+        RerankRequest::new(" ".into(), documents, RerankModel::Rerank2, None).unwrap_err(),
+        RerankValidationError::MissingQuery
+    );
+    assert_eq!(
+        RerankRequest::new("query".into(), Vec::new(), RerankModel::Rerank2, None).unwrap_err(),
+        RerankValidationError::EmptyDocuments
+    );
+    assert_eq!(
+        RerankRequest::validate("query", &["x".repeat(64_000)], RerankModel::Rerank2),
+        vec![RerankValidationError::DocumentTooLong {
+            index: 0,
+            tokens: 16_002,
+            max: 16_000
+        }]
+    );
+}
+
+#[test]
+fn test_rerank_limits_follow_model() {
+    let query = "q".repeat(20_000);
+    let document = vec!["x".repeat(64_000)];
+
+    // ~5_000 query tokens and ~21_000 in context fit rerank-2.5 only
+    assert!(RerankRequest::validate(&query, &document, RerankModel::Rerank25).is_empty());
+    assert_eq!(
+        RerankRequest::validate(&query, &document, RerankModel::Rerank2Lite),
+        vec![
+            RerankValidationError::QueryTooLong {
+                tokens: 5_000,
+                max: 2_000
+            },
+            RerankValidationError::DocumentTooLong {
+                index: 0,
+                tokens: 21_000,
+                max: 8_000
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_rerank_model_names() {
+    for model in [
+        RerankModel::Rerank25,
+        RerankModel::Rerank25Lite,
+        RerankModel::Rerank2,
+        RerankModel::Rerank2Lite,
+    ] {
+        let name = model.to_string();
+        assert_eq!(name.parse::<RerankModel>(), Ok(model));
+        assert_eq!(serde_json::to_value(model).unwrap(), name.as_str());
+    }
+    assert_eq!(RerankModel::Rerank25Lite.as_str(), "rerank-2.5-lite");
+    assert!("rerank-1".parse::<RerankModel>().is_err());
+}
+
+#[tokio::test]
+async fn test_invalid_rerank_does_not_panic() {
+    let client = DefaultRerankClient::new(
+        VoyageConfig::new("key".to_string()),
+        Arc::new(RateLimiter::new()),
+    );
+
+    let results: Vec<_> = client
+        .find_similar_documents("query", Vec::new())
+        .collect()
+        .await;
+    assert!(results.is_empty());
+
+    let error = client
+        .most_similar_document("", vec!["doc".to_string()])
+        .await
+        .unwrap_err();
+    assert!(matches!(error, VoyageError::Validation(_)));
+}
+
+#[test]
+fn test_validation_error_converts_to_voyage_error() {
+    let error: VoyageError = EmbeddingsRequestBuilder::new().build().unwrap_err().into();
+
+    assert!(matches!(error, VoyageError::Validation(_)));
+}
@@ -0,0 +1,328 @@
+use voyageai::{
+    index::NamespacedIndex,
+    models::{Metadata, MetadataFilter, MetadataValue},
+    VectorIndex, VoyageError,
+};
+
+fn sample_index() -> VectorIndex {
+    let mut index = VectorIndex::new();
+    let docs = Metadata::from([
+        ("source".to_string(), MetadataValue::from("docs")),
+        ("year".to_string(), MetadataValue::from(2024_i64)),
+    ]);
+    index
+        .insert("a", "alpha", vec![1.0, 0.0, 0.0], Some(docs))
+        .unwrap();
+    index.insert("b", "beta", vec![0.0, 1.0, 0.0], None).unwrap();
+    index.insert("c", "gamma", vec![0.7, 0.7, 0.0], None).unwrap();
+    index
+}
+
+#[test]
+fn test_insert_replace_and_remove() {
+    let mut index = sample_index();
+    assert_eq!(index.len(), 3);
+    assert_eq!(index.dimension(), 3);
+
+    index.insert("b", "beta v2", vec![0.0, 0.0, 1.0], None).unwrap();
+    assert_eq!(index.len(), 3);
+    let (record, embedding) = index.get("b").unwrap();
+    assert_eq!(record.text, "beta v2");
+    assert_eq!(embedding, &[0.0, 0.0, 1.0]);
+
+    let (removed, _) = index.remove("a").unwrap();
+    assert_eq!(removed.id, "a");
+    assert!(!index.contains("a"));
+    assert_eq!(index.get("c").unwrap().1, &[0.7, 0.7, 0.0]);
+    assert!(index.remove("a").is_none());
+}
+
+#[test]
+fn test_insert_rejects_dimension_mismatch() {
+    let mut index = sample_index();
+
+    let result = index.insert("d", "delta", vec![1.0, 2.0], None);
+
+    assert!(matches!(
+        result,
+        Err(VoyageError::SearchDimensionMismatch {
+            expected: 3,
+            actual: 2
+        })
+    ));
+}
+
+#[test]
+fn test_search_ranks_by_cosine_similarity() {
+    let index = sample_index();
+
+    let matches = index.search(&[1.0, 0.1, 0.0], 2, None).unwrap();
+
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].record.id, "a");
+    assert_eq!(matches[1].record.id, "c");
+    assert!(matches[0].score > matches[1].score);
+}
+
+#[test]
+fn test_search_applies_metadata_filter() {
+    let index = sample_index();
+    let filter = MetadataFilter::eq("source", "docs");
+
+    let matches = index.search(&[0.0, 1.0, 0.0], 10, Some(&filter)).unwrap();
+
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].record.id, "a");
+}
+
+#[test]
+fn test_save_and_load_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let index = sample_index();
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("corpus.idx");
+
+    index.save(&path)?;
+    let loaded = VectorIndex::load(&path)?;
+
+    assert_eq!(loaded, index);
+    Ok(())
+}
+
+#[test]
+fn test_model_is_saved_with_the_index() -> Result<(), Box<dyn std::error::Error>> {
+    let index = sample_index().with_model("voyage-3-large");
+    let mut bytes = Vec::new();
+    index.write_to(&mut bytes)?;
+
+    let loaded = VectorIndex::from_bytes(&bytes)?;
+
+    assert_eq!(loaded.model(), Some("voyage-3-large"));
+    assert_eq!(loaded, index);
+    Ok(())
+}
+
+/// The bincode record list at the start of `records`, without the tombstones
+/// following it and, unless `keep_expiries`, without the `None` expiry tag
+/// ending each record.
+fn legacy_records(records: &[u8], keep_expiries: bool) -> Vec<u8> {
+    let mut offset = 0;
+    let mut take = |len: usize| {
+        offset += len;
+        &records[offset - len..offset]
+    };
+    let read_u64 = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap()) as usize;
+    let mut stripped = Vec::new();
+    let count = take(8);
+    stripped.extend_from_slice(count);
+    for _ in 0..read_u64(count) {
+        // Id and text
+        for _ in 0..2 {
+            let len = take(8);
+            stripped.extend_from_slice(len);
+            stripped.extend_from_slice(take(read_u64(len)));
+        }
+        let metadata = take(1);
+        stripped.extend_from_slice(metadata);
+        if metadata == [1] {
+            let entries = take(8);
+            stripped.extend_from_slice(entries);
+            for _ in 0..read_u64(entries) {
+                let len = take(8);
+                stripped.extend_from_slice(len);
+                stripped.extend_from_slice(take(read_u64(len)));
+                let tag = take(4);
+                stripped.extend_from_slice(tag);
+                let value_len = match tag[0] {
+                    0 => 1,
+                    1 => 8,
+                    _ => {
+                        let len = take(8);
+                        stripped.extend_from_slice(len);
+                        read_u64(len)
+                    }
+                };
+                stripped.extend_from_slice(take(value_len));
+            }
+        }
+        let expiry = take(1);
+        assert_eq!(expiry, [0], "records to downgrade must not expire");
+        if keep_expiries {
+            stripped.extend_from_slice(expiry);
+        }
+    }
+    stripped
+}
+
+/// Rewrites the current file `bytes` as an older `version`: version 4 has no
+/// tombstones, version 3 no document expiry either, version 2 no crate
+/// version before the model name, and version 1 no model name.
+fn downgrade(bytes: &[u8], version: u32, vector_bytes: usize) -> Vec<u8> {
+    let records_len = u64::from_le_bytes(bytes[24..32].try_into().unwrap()) as usize;
+    let crate_version_len = u64::from_le_bytes(bytes[32..40].try_into().unwrap()) as usize;
+    let mut prefix = 8 + crate_version_len;
+    // The `None` tag or the length and bytes of the model name
+    prefix += match bytes[32 + prefix] {
+        0 => 1,
+        _ => 9 + u64::from_le_bytes(bytes[33 + prefix..41 + prefix].try_into().unwrap()) as usize,
+    };
+    let mut skip = match version {
+        3 | 4 => 0,
+        _ => 8 + crate_version_len,
+    };
+    if version == 1 {
+        // The `None` tag of the model name
+        skip += 1;
+    }
+    let mut records = bytes[32 + skip..32 + prefix].to_vec();
+    records.extend(legacy_records(
+        &bytes[32 + prefix..32 + records_len],
+        version == 4,
+    ));
+    let vectors = bytes[bytes.len() - vector_bytes..].to_vec();
+    let mut old = bytes[..8].to_vec();
+    old.extend_from_slice(&version.to_le_bytes());
+    old.extend_from_slice(&bytes[12..24]);
+    old.extend_from_slice(&(records.len() as u64).to_le_bytes());
+    old.extend_from_slice(&records);
+    old.resize(old.len().next_multiple_of(4), 0);
+    old.extend_from_slice(&vectors);
+    old
+}
+
+#[test]
+fn test_loads_version_1_files() -> Result<(), Box<dyn std::error::Error>> {
+    let index = sample_index();
+    let mut bytes = Vec::new();
+    index.write_to(&mut bytes)?;
+    let v1 = downgrade(&bytes, 1, 4 * 3 * 3);
+
+    let loaded = VectorIndex::from_bytes(&v1)?;
+
+    assert_eq!(loaded.model(), None);
+    assert_eq!(loaded, index);
+    Ok(())
+}
+
+#[test]
+fn test_older_versions_are_upgraded() -> Result<(), Box<dyn std::error::Error>> {
+    let index = sample_index().with_model("voyage-3-large");
+    let mut bytes = Vec::new();
+    index.write_to(&mut bytes)?;
+    for version in [4, 3, 2] {
+        let old = downgrade(&bytes, version, 4 * 3 * 3);
+
+        let info = VectorIndex::inspect_bytes(&old)?;
+        assert_eq!(info.format_version, version);
+        assert!(!info.is_current());
+        assert_eq!(info.model.as_deref(), Some("voyage-3-large"));
+        let written_by = (version >= 3).then_some(env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.crate_version.as_deref(), written_by);
+
+        let loaded = VectorIndex::from_bytes(&old)?;
+        assert_eq!(loaded, index);
+        let mut upgraded = Vec::new();
+        loaded.write_to(&mut upgraded)?;
+        assert_eq!(upgraded, bytes);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_inspect_reads_the_header() -> Result<(), Box<dyn std::error::Error>> {
+    let mut bytes = Vec::new();
+    sample_index()
+        .with_model("voyage-code-3")
+        .write_to(&mut bytes)?;
+
+    let info = VectorIndex::inspect_bytes(&bytes)?;
+
+    assert!(info.is_current());
+    assert_eq!(info.dimension, 3);
+    assert_eq!(info.count, 3);
+    assert_eq!(info.model.as_deref(), Some("voyage-code-3"));
+    assert_eq!(info.crate_version.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+    Ok(())
+}
+
+#[test]
+fn test_newer_versions_are_refused_with_guidance() {
+    let mut bytes = Vec::new();
+    sample_index().write_to(&mut bytes).unwrap();
+    let current = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+    bytes[8..12].copy_from_slice(&(current + 1).to_le_bytes());
+
+    let error = VectorIndex::from_bytes(&bytes).unwrap_err();
+
+    match &error {
+        VoyageError::IndexVersionUnsupported {
+            version,
+            supported,
+            written_by,
+        } => {
+            assert_eq!(*version, current + 1);
+            assert_eq!(*supported, current);
+            assert_eq!(written_by.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+    assert!(error.to_string().contains("upgrade voyageai to"));
+    assert!(matches!(
+        VectorIndex::inspect_bytes(&bytes),
+        Err(VoyageError::IndexVersionUnsupported { .. })
+    ));
+}
+
+#[test]
+fn test_serialization_is_deterministic() -> Result<(), Box<dyn std::error::Error>> {
+    let mut first = Vec::new();
+    let mut second = Vec::new();
+
+    sample_index().write_to(&mut first)?;
+    sample_index().write_to(&mut second)?;
+
+    assert_eq!(first, second);
+    Ok(())
+}
+
+#[test]
+fn test_save_keeps_files_with_other_extensions() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let neighbor = dir.path().join("corpus.tmp");
+    std::fs::write(&neighbor, "unrelated")?;
+
+    sample_index().save(dir.path().join("corpus.idx"))?;
+    NamespacedIndex::new().save(dir.path().join("corpus.ns"))?;
+
+    assert_eq!(std::fs::read_to_string(&neighbor)?, "unrelated");
+    let mut names: Vec<_> = std::fs::read_dir(dir.path())?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<Result<_, _>>()?;
+    names.sort();
+    assert_eq!(names, ["corpus.idx", "corpus.ns", "corpus.tmp"]);
+    Ok(())
+}
+
+#[test]
+fn test_load_rejects_corrupt_input() {
+    let mut bytes = Vec::new();
+    sample_index().write_to(&mut bytes).unwrap();
+
+    assert!(matches!(
+        VectorIndex::from_bytes(b"not an index at all, definitely not"),
+        Err(VoyageError::IndexFormatError(_))
+    ));
+    assert!(matches!(
+        VectorIndex::from_bytes(&bytes[..bytes.len() - 4]),
+        Err(VoyageError::IndexFormatError(_))
+    ));
+}
+
+#[test]
+fn test_empty_index_round_trip() {
+    let mut bytes = Vec::new();
+    VectorIndex::new().write_to(&mut bytes).unwrap();
+
+    let loaded = VectorIndex::from_bytes(&bytes).unwrap();
+
+    assert!(loaded.is_empty());
+}
@@ -0,0 +1,90 @@
+use std::time::Duration;
+use voyageai::{
+    index::{CollectionConfig, VectorStore},
+    VoyageError,
+};
+
+fn store_with_scratch(ttl: Duration) -> VectorStore {
+    let store = VectorStore::new();
+    store
+        .create_collection("scratch", CollectionConfig::ephemeral(ttl))
+        .unwrap();
+    store
+        .create_collection("corpus", CollectionConfig::persistent())
+        .unwrap();
+    store
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_documents_expire_after_ttl() {
+    let store = store_with_scratch(Duration::from_secs(60));
+    store.insert("scratch", "a", "alpha", vec![1.0, 0.0], None).unwrap();
+    store.insert("corpus", "a", "alpha", vec![1.0, 0.0], None).unwrap();
+
+    tokio::time::advance(Duration::from_secs(30)).await;
+    assert_eq!(store.len("scratch").unwrap(), 1);
+
+    tokio::time::advance(Duration::from_secs(31)).await;
+    assert_eq!(store.len("scratch").unwrap(), 0);
+    assert!(store.search("scratch", &[1.0, 0.0], 10, None).unwrap().is_empty());
+    assert_eq!(store.len("corpus").unwrap(), 1);
+
+    assert_eq!(store.expire(), 1);
+    assert_eq!(store.expire(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_reinsert_restarts_ttl() {
+    let store = store_with_scratch(Duration::from_secs(60));
+    store.insert("scratch", "a", "alpha", vec![1.0, 0.0], None).unwrap();
+
+    tokio::time::advance(Duration::from_secs(45)).await;
+    store.insert("scratch", "a", "alpha", vec![1.0, 0.0], None).unwrap();
+    tokio::time::advance(Duration::from_secs(45)).await;
+
+    assert_eq!(store.len("scratch").unwrap(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_background_expiry_reclaims_documents() {
+    let store = store_with_scratch(Duration::from_secs(10));
+    store.insert("scratch", "a", "alpha", vec![1.0, 0.0], None).unwrap();
+    let handle = store.spawn_expiry(Duration::from_secs(5));
+
+    tokio::time::sleep(Duration::from_secs(16)).await;
+
+    assert_eq!(store.expire(), 0, "background task should already have expired the document");
+    handle.stop();
+}
+
+#[tokio::test]
+async fn test_search_and_remove() {
+    let store = store_with_scratch(Duration::from_secs(60));
+    store.insert("corpus", "a", "alpha", vec![1.0, 0.0], None).unwrap();
+    store.insert("corpus", "b", "beta", vec![0.0, 1.0], None).unwrap();
+
+    let matches = store.search("corpus", &[0.1, 1.0], 1, None).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].record.id, "b");
+
+    assert!(store.remove("corpus", "b").unwrap());
+    assert!(!store.remove("corpus", "b").unwrap());
+    assert_eq!(store.len("corpus").unwrap(), 1);
+}
+
+#[test]
+fn test_collection_management_errors() {
+    let store = store_with_scratch(Duration::from_secs(60));
+
+    assert!(matches!(
+        store.create_collection("corpus", CollectionConfig::persistent()),
+        Err(VoyageError::CollectionExists(_))
+    ));
+    assert!(matches!(
+        store.insert("missing", "a", "alpha", vec![1.0], None),
+        Err(VoyageError::CollectionNotFound(_))
+    ));
+    assert_eq!(store.collection_names(), vec!["corpus", "scratch"]);
+    assert!(store.drop_collection("scratch"));
+    assert_eq!(store.collection_names(), vec!["corpus"]);
+}
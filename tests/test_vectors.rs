@@ -0,0 +1,93 @@
+use voyageai::{
+    vectors::{
+        add, centroid, check_dimensions, length_normalized_pool, max_pool, mean_pool, norm,
+        normalize, normalized, subtract, weighted_mean_pool, VectorError,
+    },
+    VoyageError,
+};
+
+fn assert_close(actual: &[f32], expected: &[f32]) {
+    assert_eq!(actual.len(), expected.len());
+    for (a, b) in actual.iter().zip(expected) {
+        assert!((a - b).abs() < 1e-6, "{actual:?} != {expected:?}");
+    }
+}
+
+#[test]
+fn test_pooling() {
+    let vectors = vec![vec![1.0, 0.0, 2.0], vec![0.0, 1.0, -2.0]];
+
+    assert_close(&mean_pool(&vectors).unwrap(), &[0.5, 0.5, 0.0]);
+    assert_close(&max_pool(&vectors).unwrap(), &[1.0, 1.0, 2.0]);
+    assert_close(
+        &weighted_mean_pool(&vectors, &[3.0, 1.0]).unwrap(),
+        &[0.75, 0.25, 1.0],
+    );
+    // Weights need not sum to one
+    assert_eq!(
+        weighted_mean_pool(&vectors, &[6.0, 2.0]).unwrap(),
+        weighted_mean_pool(&vectors, &[0.75, 0.25]).unwrap()
+    );
+
+    let pooled = length_normalized_pool(&vectors, &[300, 100]).unwrap();
+    let mut expected = vec![0.75, 0.25, 1.0];
+    normalize(&mut expected);
+    assert_close(&pooled, &expected);
+    assert!((pooled.iter().map(|x| x * x).sum::<f32>() - 1.0).abs() < 1e-6);
+
+    let mut zero = vec![0.0; 3];
+    normalize(&mut zero);
+    assert_eq!(zero, vec![0.0; 3]);
+}
+
+#[test]
+fn test_invalid_inputs_are_rejected() {
+    let vectors = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+
+    assert_eq!(mean_pool(&[]), Err(VectorError::Empty));
+    assert_eq!(
+        max_pool(&[vec![1.0, 0.0], vec![1.0, 1.0], vec![1.0]]),
+        Err(VectorError::DimensionMismatch {
+            index: 2,
+            expected: 2,
+            actual: 1
+        })
+    );
+    assert!(matches!(
+        weighted_mean_pool(&vectors, &[1.0]),
+        Err(VectorError::InvalidWeights(_))
+    ));
+    assert!(weighted_mean_pool(&vectors, &[1.0, -1.0]).is_err());
+    assert!(weighted_mean_pool(&vectors, &[1.0, f32::NAN]).is_err());
+    assert!(length_normalized_pool(&vectors, &[0, 0]).is_err());
+}
+
+#[test]
+fn test_arithmetic() {
+    let a = [3.0, 4.0];
+    let b = [1.0, -1.0];
+
+    assert_eq!(norm(&a), 5.0);
+    assert_close(&normalized(&a), &[0.6, 0.8]);
+    assert_eq!(add(&a, &b).unwrap(), vec![4.0, 3.0]);
+    assert_eq!(subtract(&a, &b).unwrap(), vec![2.0, 5.0]);
+    assert_eq!(check_dimensions(&a, &b), Ok(2));
+    assert_eq!(
+        centroid(&[a.to_vec(), b.to_vec(), vec![2.0, 0.0]]).unwrap(),
+        vec![2.0, 1.0]
+    );
+
+    let error = add(&a, &[1.0]).unwrap_err();
+    assert_eq!(
+        error,
+        VectorError::DimensionMismatch {
+            index: 1,
+            expected: 2,
+            actual: 1
+        }
+    );
+    // Converts into the crate's error with `?`
+    let error: VoyageError = error.into();
+    assert!(matches!(error, VoyageError::Vector(_)));
+    assert_eq!(error.to_string(), "vector 1 has 1 dimensions, expected 2");
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use voyageai::{
+    analysis::{export_projector, project, write_metadata_tsv, write_vectors_tsv, Pca},
+    models::Metadata,
+    VoyageError,
+};
+
+/// Points spread mostly along (1, 1, 0), a little along (0, 0, 1), and not
+/// at all along (1, -1, 0).
+fn points() -> Vec<Vec<f32>> {
+    (0..20)
+        .map(|i| {
+            let t = i as f32 - 10.0;
+            let s = if i % 4 == 0 || i % 4 == 3 { 0.5 } else { -0.5 };
+            vec![t + 3.0, t + 3.0, s]
+        })
+        .collect()
+}
+
+#[test]
+fn test_pca_finds_directions_of_greatest_variance() -> Result<(), VoyageError> {
+    let embeddings = points();
+    let pca = Pca::fit(&embeddings, 3)?;
+
+    let first = &pca.components()[0];
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+    assert!((first[0] - half).abs() < 1e-3 && (first[1] - half).abs() < 1e-3);
+    assert!(pca.components()[1][2].abs() > 0.999);
+    let third = &pca.components()[2];
+    let overlap: f32 = first.iter().zip(third).map(|(a, b)| a * b).sum();
+    assert!(overlap.abs() < 1e-4);
+
+    let ratios = pca.explained_variance_ratio();
+    assert!(ratios[0] > 0.99);
+    assert!(ratios[2] < 1e-4, "{ratios:?}");
+    assert!((ratios.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+
+    // Coordinates are centered and preserve distances along the components
+    let projected = project(&embeddings, 2)?;
+    let sum: f32 = projected.iter().map(|point| point[0]).sum();
+    assert!(sum.abs() < 1e-3);
+    let gap = projected[1][0] - projected[0][0];
+    assert!(
+        (gap - 2f32.sqrt()).abs() < 1e-3,
+        "{gap} {:?}",
+        pca.components()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_pca_rejects_invalid_input() {
+    assert!(matches!(
+        Pca::fit(&points(), 4),
+        Err(VoyageError::AnalysisError(_))
+    ));
+    assert!(matches!(
+        Pca::fit(&[], 2),
+        Err(VoyageError::AnalysisError(_))
+    ));
+}
+
+#[test]
+fn test_tsv_formats() -> Result<(), VoyageError> {
+    let mut vectors = Vec::new();
+    write_vectors_tsv(&mut vectors, &[vec![1.0, -0.5], vec![0.25, 2.0]])?;
+    assert_eq!(String::from_utf8(vectors).unwrap(), "1\t-0.5\n0.25\t2\n");
+
+    let rows: Vec<Metadata> = vec![
+        HashMap::from([
+            ("title".to_string(), "Tabs\there".into()),
+            ("year".to_string(), 2024.0.into()),
+        ]),
+        HashMap::from([("title".to_string(), "Second".into())]),
+    ];
+    let mut metadata = Vec::new();
+    write_metadata_tsv(&mut metadata, &rows)?;
+    assert_eq!(
+        String::from_utf8(metadata).unwrap(),
+        "title\tyear\nTabs here\t2024\nSecond\t\n"
+    );
+
+    // A single column has no header
+    let mut metadata = Vec::new();
+    write_metadata_tsv(&mut metadata, &rows[1..])?;
+    assert_eq!(String::from_utf8(metadata).unwrap(), "Second\n");
+    Ok(())
+}
+
+#[test]
+fn test_export_projector_writes_both_files() -> Result<(), VoyageError> {
+    let temp = tempfile::tempdir().unwrap();
+    let directory = temp.path().join("projector");
+    let embeddings = vec![vec![1.0, 2.0], vec![3.0, 4.0]];
+    let metadata: Vec<Metadata> = vec![
+        HashMap::from([("label".to_string(), "a".into())]),
+        HashMap::from([("label".to_string(), "b".into())]),
+    ];
+
+    export_projector(&directory, &embeddings, Some(&metadata))?;
+    let vectors = std::fs::read_to_string(directory.join("vectors.tsv")).unwrap();
+    let labels = std::fs::read_to_string(directory.join("metadata.tsv")).unwrap();
+    assert_eq!(vectors, "1\t2\n3\t4\n");
+    assert_eq!(labels, "a\nb\n");
+
+    let mismatched = export_projector(&directory, &embeddings, Some(&metadata[..1]));
+    assert!(matches!(mismatched, Err(VoyageError::AnalysisError(_))));
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use voyageai::{
+    models::MetadataValue, retrieval::RetrievalPipeline, test_util::TestServer, web::WebIngestor,
+    VoyageError,
+};
+
+const HOME: &str = r#"<html><head><title>Docs Home</title></head><body>
+<nav><a href="/nav-only">Navigation</a></nav>
+<main>
+  <h1>Welcome</h1>
+  <p>Start with the <a href="/guide#install">guide</a>.</p>
+  <p>See also the <a href="/private/secrets">secrets</a> and <a href="https://elsewhere.example/">another site</a>.</p>
+  <script>console.log("ignored")</script>
+</main>
+<footer>Copyright</footer>
+</body></html>"#;
+
+const GUIDE: &str = r#"<html><head><title>Guide</title></head><body>
+<article><h2>Installation</h2><p>Add the crate to Cargo.toml.</p><a href="/deep">Deeper</a></article>
+</body></html>"#;
+
+async fn site() -> mockito::ServerGuard {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/robots.txt")
+        .with_body("User-agent: *\nDisallow: /private/\n")
+        .create_async()
+        .await;
+    for (path, body) in [
+        ("/", HOME),
+        ("/guide", GUIDE),
+        ("/deep", "<html><body><p>Deep page</p></body></html>"),
+        (
+            "/nav-only",
+            "<html><body><p>Navigation page</p></body></html>",
+        ),
+        (
+            "/private/secrets",
+            "<html><body><p>Secret</p></body></html>",
+        ),
+    ] {
+        server
+            .mock("GET", path)
+            .with_header("content-type", "text/html; charset=utf-8")
+            .with_body(body)
+            .create_async()
+            .await;
+    }
+    server
+}
+
+#[tokio::test]
+async fn test_seed_only_extracts_main_content() -> Result<(), VoyageError> {
+    let site = site().await;
+    let pages = WebIngestor::new()
+        .crawl(&format!("{}/", site.url()))
+        .await?;
+
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].title.as_deref(), Some("Docs Home"));
+    assert_eq!(pages[0].depth, 0);
+    assert!(pages[0]
+        .text
+        .starts_with("Welcome\n\nStart with the guide ."));
+    assert!(!pages[0].text.contains("Navigation"));
+    assert!(!pages[0].text.contains("console.log"));
+    assert!(!pages[0].text.contains("Copyright"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_follows_same_host_links_within_depth() -> Result<(), VoyageError> {
+    let site = site().await;
+    let pages = WebIngestor::new()
+        .with_max_depth(1)
+        .crawl(&format!("{}/", site.url()))
+        .await?;
+
+    // Links inside <nav> are still followed, but robots.txt, other hosts and
+    // pages past the depth limit are not
+    let urls: Vec<String> = pages.iter().map(|page| page.url.clone()).collect();
+    assert_eq!(
+        urls,
+        vec![
+            format!("{}/", site.url()),
+            format!("{}/nav-only", site.url()),
+            format!("{}/guide", site.url()),
+        ]
+    );
+    assert_eq!(
+        pages[2].text,
+        "Installation\n\nAdd the crate to Cargo.toml.\n\nDeeper"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_pages_and_ignoring_robots() -> Result<(), VoyageError> {
+    let site = site().await;
+    let pages = WebIngestor::new()
+        .with_max_depth(5)
+        .respect_robots(false)
+        .crawl(&format!("{}/", site.url()))
+        .await?;
+    assert!(pages
+        .iter()
+        .any(|page| page.url.ends_with("/private/secrets")));
+    assert!(pages.iter().any(|page| page.url.ends_with("/deep")));
+
+    let pages = WebIngestor::new()
+        .with_max_depth(5)
+        .with_max_pages(2)
+        .crawl(&format!("{}/", site.url()))
+        .await?;
+    assert_eq!(pages.len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_disallowed_or_unreachable_seed_fails() {
+    let site = site().await;
+    let disallowed = WebIngestor::new()
+        .crawl(&format!("{}/private/secrets", site.url()))
+        .await;
+    assert!(matches!(disallowed, Err(VoyageError::WebIngestError(_))));
+
+    let missing = WebIngestor::new()
+        .crawl(&format!("{}/missing", site.url()))
+        .await;
+    assert!(matches!(missing, Err(VoyageError::RequestError(_))));
+}
+
+#[tokio::test]
+async fn test_ingest_adds_pages_with_url_metadata() -> Result<(), VoyageError> {
+    let site = site().await;
+    let server = TestServer::start().await.with_embeddings().await;
+    let client = server.client();
+    let mut pipeline = RetrievalPipeline::builder(&client).without_rerank().build();
+
+    let pages = WebIngestor::new()
+        .with_max_depth(1)
+        .ingest(&format!("{}/", site.url()), &mut pipeline)
+        .await?;
+    let chunks = pipeline.query("crate installation Cargo.toml").await?;
+
+    assert_eq!(pipeline.len(), pages.len());
+    let guide = format!("{}/guide", site.url());
+    assert_eq!(chunks[0].document_id, guide);
+    let metadata = chunks[0].metadata.as_ref().unwrap();
+    assert_eq!(metadata["url"], MetadataValue::String(guide));
+    assert_eq!(
+        metadata["title"],
+        MetadataValue::String("Guide".to_string())
+    );
+    assert_eq!(metadata["depth"], MetadataValue::Number(1.0));
+    Ok(())
+}